@@ -0,0 +1,376 @@
+/* tslint:disable */
+/* eslint-disable */
+/* prettier-ignore */
+
+/* auto-generated by NAPI-RS */
+
+const { existsSync, readFileSync } = require('fs')
+const { join } = require('path')
+
+const { platform, arch } = process
+
+let nativeBinding = null
+let localFileExisted = false
+let loadError = null
+
+function isMusl() {
+  // For Node 10
+  if (!process.report || typeof process.report.getReport !== 'function') {
+    try {
+      const lddPath = require('child_process').execSync('which ldd').toString().trim()
+      return readFileSync(lddPath, 'utf8').includes('musl')
+    } catch (e) {
+      return true
+    }
+  } else {
+    const { glibcVersionRuntime } = process.report.getReport().header
+    return !glibcVersionRuntime
+  }
+}
+
+switch (platform) {
+  case 'android':
+    switch (arch) {
+      case 'arm64':
+        localFileExisted = existsSync(join(__dirname, 'nanalogue.android-arm64.node'))
+        try {
+          if (localFileExisted) {
+            nativeBinding = require('./nanalogue.android-arm64.node')
+          } else {
+            nativeBinding = require('@nanalogue/node-android-arm64')
+          }
+        } catch (e) {
+          loadError = e
+        }
+        break
+      case 'arm':
+        localFileExisted = existsSync(join(__dirname, 'nanalogue.android-arm-eabi.node'))
+        try {
+          if (localFileExisted) {
+            nativeBinding = require('./nanalogue.android-arm-eabi.node')
+          } else {
+            nativeBinding = require('@nanalogue/node-android-arm-eabi')
+          }
+        } catch (e) {
+          loadError = e
+        }
+        break
+      default:
+        throw new Error(`Unsupported architecture on Android ${arch}`)
+    }
+    break
+  case 'win32':
+    switch (arch) {
+      case 'x64':
+        localFileExisted = existsSync(
+          join(__dirname, 'nanalogue.win32-x64-msvc.node')
+        )
+        try {
+          if (localFileExisted) {
+            nativeBinding = require('./nanalogue.win32-x64-msvc.node')
+          } else {
+            nativeBinding = require('@nanalogue/node-win32-x64-msvc')
+          }
+        } catch (e) {
+          loadError = e
+        }
+        break
+      case 'ia32':
+        localFileExisted = existsSync(
+          join(__dirname, 'nanalogue.win32-ia32-msvc.node')
+        )
+        try {
+          if (localFileExisted) {
+            nativeBinding = require('./nanalogue.win32-ia32-msvc.node')
+          } else {
+            nativeBinding = require('@nanalogue/node-win32-ia32-msvc')
+          }
+        } catch (e) {
+          loadError = e
+        }
+        break
+      case 'arm64':
+        localFileExisted = existsSync(
+          join(__dirname, 'nanalogue.win32-arm64-msvc.node')
+        )
+        try {
+          if (localFileExisted) {
+            nativeBinding = require('./nanalogue.win32-arm64-msvc.node')
+          } else {
+            nativeBinding = require('@nanalogue/node-win32-arm64-msvc')
+          }
+        } catch (e) {
+          loadError = e
+        }
+        break
+      default:
+        throw new Error(`Unsupported architecture on Windows: ${arch}`)
+    }
+    break
+  case 'darwin':
+    localFileExisted = existsSync(join(__dirname, 'nanalogue.darwin-universal.node'))
+    try {
+      if (localFileExisted) {
+        nativeBinding = require('./nanalogue.darwin-universal.node')
+      } else {
+        nativeBinding = require('@nanalogue/node-darwin-universal')
+      }
+      break
+    } catch {}
+    switch (arch) {
+      case 'x64':
+        localFileExisted = existsSync(join(__dirname, 'nanalogue.darwin-x64.node'))
+        try {
+          if (localFileExisted) {
+            nativeBinding = require('./nanalogue.darwin-x64.node')
+          } else {
+            nativeBinding = require('@nanalogue/node-darwin-x64')
+          }
+        } catch (e) {
+          loadError = e
+        }
+        break
+      case 'arm64':
+        localFileExisted = existsSync(
+          join(__dirname, 'nanalogue.darwin-arm64.node')
+        )
+        try {
+          if (localFileExisted) {
+            nativeBinding = require('./nanalogue.darwin-arm64.node')
+          } else {
+            nativeBinding = require('@nanalogue/node-darwin-arm64')
+          }
+        } catch (e) {
+          loadError = e
+        }
+        break
+      default:
+        throw new Error(`Unsupported architecture on macOS: ${arch}`)
+    }
+    break
+  case 'freebsd':
+    if (arch !== 'x64') {
+      throw new Error(`Unsupported architecture on FreeBSD: ${arch}`)
+    }
+    localFileExisted = existsSync(join(__dirname, 'nanalogue.freebsd-x64.node'))
+    try {
+      if (localFileExisted) {
+        nativeBinding = require('./nanalogue.freebsd-x64.node')
+      } else {
+        nativeBinding = require('@nanalogue/node-freebsd-x64')
+      }
+    } catch (e) {
+      loadError = e
+    }
+    break
+  case 'linux':
+    switch (arch) {
+      case 'x64':
+        if (isMusl()) {
+          localFileExisted = existsSync(
+            join(__dirname, 'nanalogue.linux-x64-musl.node')
+          )
+          try {
+            if (localFileExisted) {
+              nativeBinding = require('./nanalogue.linux-x64-musl.node')
+            } else {
+              nativeBinding = require('@nanalogue/node-linux-x64-musl')
+            }
+          } catch (e) {
+            loadError = e
+          }
+        } else {
+          localFileExisted = existsSync(
+            join(__dirname, 'nanalogue.linux-x64-gnu.node')
+          )
+          try {
+            if (localFileExisted) {
+              nativeBinding = require('./nanalogue.linux-x64-gnu.node')
+            } else {
+              nativeBinding = require('@nanalogue/node-linux-x64-gnu')
+            }
+          } catch (e) {
+            loadError = e
+          }
+        }
+        break
+      case 'arm64':
+        if (isMusl()) {
+          localFileExisted = existsSync(
+            join(__dirname, 'nanalogue.linux-arm64-musl.node')
+          )
+          try {
+            if (localFileExisted) {
+              nativeBinding = require('./nanalogue.linux-arm64-musl.node')
+            } else {
+              nativeBinding = require('@nanalogue/node-linux-arm64-musl')
+            }
+          } catch (e) {
+            loadError = e
+          }
+        } else {
+          localFileExisted = existsSync(
+            join(__dirname, 'nanalogue.linux-arm64-gnu.node')
+          )
+          try {
+            if (localFileExisted) {
+              nativeBinding = require('./nanalogue.linux-arm64-gnu.node')
+            } else {
+              nativeBinding = require('@nanalogue/node-linux-arm64-gnu')
+            }
+          } catch (e) {
+            loadError = e
+          }
+        }
+        break
+      case 'arm':
+        if (isMusl()) {
+          localFileExisted = existsSync(
+            join(__dirname, 'nanalogue.linux-arm-musleabihf.node')
+          )
+          try {
+            if (localFileExisted) {
+              nativeBinding = require('./nanalogue.linux-arm-musleabihf.node')
+            } else {
+              nativeBinding = require('@nanalogue/node-linux-arm-musleabihf')
+            }
+          } catch (e) {
+            loadError = e
+          }
+        } else {
+          localFileExisted = existsSync(
+            join(__dirname, 'nanalogue.linux-arm-gnueabihf.node')
+          )
+          try {
+            if (localFileExisted) {
+              nativeBinding = require('./nanalogue.linux-arm-gnueabihf.node')
+            } else {
+              nativeBinding = require('@nanalogue/node-linux-arm-gnueabihf')
+            }
+          } catch (e) {
+            loadError = e
+          }
+        }
+        break
+      case 'riscv64':
+        if (isMusl()) {
+          localFileExisted = existsSync(
+            join(__dirname, 'nanalogue.linux-riscv64-musl.node')
+          )
+          try {
+            if (localFileExisted) {
+              nativeBinding = require('./nanalogue.linux-riscv64-musl.node')
+            } else {
+              nativeBinding = require('@nanalogue/node-linux-riscv64-musl')
+            }
+          } catch (e) {
+            loadError = e
+          }
+        } else {
+          localFileExisted = existsSync(
+            join(__dirname, 'nanalogue.linux-riscv64-gnu.node')
+          )
+          try {
+            if (localFileExisted) {
+              nativeBinding = require('./nanalogue.linux-riscv64-gnu.node')
+            } else {
+              nativeBinding = require('@nanalogue/node-linux-riscv64-gnu')
+            }
+          } catch (e) {
+            loadError = e
+          }
+        }
+        break
+      case 's390x':
+        localFileExisted = existsSync(
+          join(__dirname, 'nanalogue.linux-s390x-gnu.node')
+        )
+        try {
+          if (localFileExisted) {
+            nativeBinding = require('./nanalogue.linux-s390x-gnu.node')
+          } else {
+            nativeBinding = require('@nanalogue/node-linux-s390x-gnu')
+          }
+        } catch (e) {
+          loadError = e
+        }
+        break
+      default:
+        throw new Error(`Unsupported architecture on Linux: ${arch}`)
+    }
+    break
+  default:
+    throw new Error(`Unsupported OS: ${platform}, architecture: ${arch}`)
+}
+
+if (!nativeBinding) {
+  if (loadError) {
+    throw loadError
+  }
+  throw new Error(`Failed to load native binding`)
+}
+
+const { peek, peekMany, clearPeekCache, peekWithReads, readInfo, readInfoBatch, readInfoJson, getRead, countReads, filterStats, simulateModBam, simulateModBamTyped, bamMods, bamModsJson, bamModsTyped, windowReads, windowReadsBatch, windowReadsArrays, windowAggregate, windowMatrix, seqTable, seqTableRecords, readsTable, alignedSequences, qualityProfiles, gcContent, NanalogueBam, CancelToken, readInfoStream, ReadInfoStream, exportBam, exportFastq, exportBedgraph, buildIndex, modQualHistogram, suggestThreshold, mapqHistogram, modSummary, modCountsByContig, alignStats, annotateReads, pileup, coverage, contigSummary, idxstats, estimateReadCount, flagstat, readGroups, validateSimulationConfig, setMaxConcurrency, initThreadPool, setLogCallback, version, parseRegion, WinOp, WindowFormat, WindowCoordinate, EdgeMode, ModStrand, ReadFilterKind, SortBy, ErrorKind } = nativeBinding
+
+module.exports.peek = peek
+module.exports.peekMany = peekMany
+module.exports.clearPeekCache = clearPeekCache
+module.exports.peekWithReads = peekWithReads
+module.exports.readInfo = readInfo
+module.exports.readInfoBatch = readInfoBatch
+module.exports.readInfoJson = readInfoJson
+module.exports.getRead = getRead
+module.exports.countReads = countReads
+module.exports.filterStats = filterStats
+module.exports.simulateModBam = simulateModBam
+module.exports.simulateModBamTyped = simulateModBamTyped
+module.exports.bamMods = bamMods
+module.exports.bamModsJson = bamModsJson
+module.exports.bamModsTyped = bamModsTyped
+module.exports.windowReads = windowReads
+module.exports.windowReadsBatch = windowReadsBatch
+module.exports.windowReadsArrays = windowReadsArrays
+module.exports.windowAggregate = windowAggregate
+module.exports.windowMatrix = windowMatrix
+module.exports.seqTable = seqTable
+module.exports.seqTableRecords = seqTableRecords
+module.exports.readsTable = readsTable
+module.exports.alignedSequences = alignedSequences
+module.exports.qualityProfiles = qualityProfiles
+module.exports.gcContent = gcContent
+module.exports.NanalogueBam = NanalogueBam
+module.exports.CancelToken = CancelToken
+module.exports.readInfoStream = readInfoStream
+module.exports.ReadInfoStream = ReadInfoStream
+module.exports.exportBam = exportBam
+module.exports.exportFastq = exportFastq
+module.exports.exportBedgraph = exportBedgraph
+module.exports.buildIndex = buildIndex
+module.exports.modQualHistogram = modQualHistogram
+module.exports.suggestThreshold = suggestThreshold
+module.exports.mapqHistogram = mapqHistogram
+module.exports.modSummary = modSummary
+module.exports.modCountsByContig = modCountsByContig
+module.exports.alignStats = alignStats
+module.exports.annotateReads = annotateReads
+module.exports.pileup = pileup
+module.exports.coverage = coverage
+module.exports.contigSummary = contigSummary
+module.exports.idxstats = idxstats
+module.exports.estimateReadCount = estimateReadCount
+module.exports.flagstat = flagstat
+module.exports.readGroups = readGroups
+module.exports.validateSimulationConfig = validateSimulationConfig
+module.exports.setMaxConcurrency = setMaxConcurrency
+module.exports.initThreadPool = initThreadPool
+module.exports.setLogCallback = setLogCallback
+module.exports.version = version
+module.exports.parseRegion = parseRegion
+module.exports.WinOp = WinOp
+module.exports.WindowFormat = WindowFormat
+module.exports.WindowCoordinate = WindowCoordinate
+module.exports.EdgeMode = EdgeMode
+module.exports.ModStrand = ModStrand
+module.exports.ReadFilterKind = ReadFilterKind
+module.exports.SortBy = SortBy
+module.exports.ErrorKind = ErrorKind
@@ -1,6 +1,40 @@
 //! NAPI-RS build script for Node.js native addon setup.
 
+use std::env;
+use std::fs;
+use std::path::Path;
+
 /// Entry point for the build script that configures NAPI-RS.
 fn main() {
     napi_build::setup();
+    core_version();
+}
+
+/// Passes the version of `nanalogue_core` to the crate as
+/// `NANALOGUE_CORE_VERSION`, as cargo only sets the version of the crate being
+/// built. The version is the one locked in the nearest `Cargo.lock`.
+fn core_version() {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let lock = Path::new(&manifest_dir)
+        .ancestors()
+        .map(|v| v.join("Cargo.lock"))
+        .find(|v| v.is_file());
+    if let Some(v) = lock.as_ref() {
+        println!("cargo:rerun-if-changed={}", v.display());
+    }
+    let version = lock
+        .and_then(|v| fs::read_to_string(v).ok())
+        .and_then(|v| locked_version(&v, "nanalogue"))
+        .unwrap_or_else(|| "unknown".to_owned());
+    println!("cargo:rustc-env=NANALOGUE_CORE_VERSION={version}");
+}
+
+/// Version of the package `name` in the text of a `Cargo.lock`.
+fn locked_version(lock: &str, name: &str) -> Option<String> {
+    let name_line = format!("name = \"{name}\"");
+    lock.split("[[package]]")
+        .find(|v| v.lines().any(|w| w.trim() == name_line))?
+        .lines()
+        .find_map(|v| v.trim().strip_prefix("version = "))
+        .map(|v| v.trim_matches('"').to_owned())
 }
@@ -4,16 +4,118 @@
 export interface PeekResult {
   contigs: Record<string, number>;
   modifications: [string, string, string][];
+  /** Call counts and an example call of each entry of modifications, in the same order. */
+  modification_details: PeekModification[];
+}
+
+/** Calls of one modification in the records peek samples. */
+export interface PeekModification {
+  base: string;
+  strand: string;
+  mod_code: string;
+  /** Number of calls in the sampled records, whatever their probability. */
+  n_calls: number;
+  /** Id of the first sampled read with a call, absent if there are no calls. */
+  example_read_id?: string;
+  /** 0-based position on the basecalled sequence of the first call of that read. */
+  example_query_position?: number;
+  /** 0-based position on the contig of that call, absent if it is not aligned. */
+  example_reference_position?: number;
 }
 
 export interface PeekOptions {
   bamPath: string;
-  /** If true, treat bamPath as a URL. Otherwise treat as file path. */
+  /**
+   * If true, treat bamPath as a URL, if false as a file path. If unset, paths
+   * starting with http://, https://, s3://, gs:// or file:// are URLs.
+   * A file:// URL is read as the local file it names.
+   */
   treatAsUrl?: boolean;
+  /** Reference FASTA used to decode CRAM input, which cannot be read without it. Ignored for BAM input. */
+  referenceFasta?: string;
+  /**
+   * Times to retry opening a URL after a failed attempt (default: 0). The wait
+   * between attempts starts at 100 ms and doubles each time, up to 5 s. Ignored
+   * for local files.
+   */
+  urlRetries?: number;
+  /**
+   * Time limit of each attempt at opening a URL, in milliseconds. Must be at
+   * least 1. Unset means no limit. An attempt that times out is not retried.
+   * Ignored for local files.
+   */
+  urlTimeoutMs?: number;
+  /**
+   * Extra HTTP request headers sent when opening a URL and reading from it, e.g.
+   * `Authorization` or `User-Agent`. Only `http://` and `https://` URLs take
+   * headers; other URLs are rejected. Ignored for local files.
+   */
+  urlHeaders?: Record<string, string>;
+  /**
+   * Number of records peek examines for modifications, from the start of the
+   * file or of region (default: 100). Larger samples find rarer modifications
+   * but read more of the file. Ignored by readGroups.
+   */
+  sampleSize?: number;
+  /**
+   * Region (e.g. 'chr1:1000-2000') whose records peek examines for
+   * modifications, instead of the start of the file. Needs an index. Ignored by
+   * readGroups.
+   */
+  region?: string;
+  /**
+   * Read the coordinates of region as 1-based and inclusive, as samtools does,
+   * instead of 0-based and half-open (the default).
+   */
+  oneBased?: boolean;
+  /**
+   * Read the file even if the result of an identical peek is cached, and cache
+   * the new result in its place. Ignored by readGroups.
+   */
+  bypassCache?: boolean;
+  /**
+   * Version of the data at a URL, e.g. its ETag, as the cache cannot tell when
+   * it changes: results are cached per URL and version. Ignored for local
+   * files, whose modification time and size are used instead, and by
+   * readGroups.
+   */
+  cacheVersion?: string;
 }
 
+/**
+ * Recent results are cached, keyed by the path and its modification time and
+ * size (or the URL and cacheVersion) and the other options, so peeking at an
+ * unchanged file again does not read it.
+ */
 export declare function peek(options: PeekOptions): Promise<PeekResult>;
 
+/** Empties the cache of peek results, so later calls read their files again. */
+export declare function clearPeekCache(): void;
+
+/** Outcome of peek on one file of peekMany. */
+export interface PeekEntry {
+  /** Path or URL of the file, as given. */
+  bam_path: string;
+  /** Whether the file could be peeked at. */
+  ok: boolean;
+  /** Result of peek, if ok. */
+  result?: PeekResult;
+  /** Kind of error, as in the code of a rejected query, if not ok. */
+  code?: ErrorKind;
+  /** Error message, if not ok. */
+  error?: string;
+}
+
+/**
+ * Peeks at several files concurrently, up to the setMaxConcurrency limit. The
+ * entries are in the order of paths; a file that cannot be read gives an entry
+ * with its error instead of rejecting the whole batch.
+ */
+export declare function peekMany(
+  paths: string[],
+  treatAsUrl?: boolean,
+): Promise<PeekEntry[]>;
+
 // Read info types
 export interface MappedReadInfo {
   read_id: string;
@@ -24,6 +126,8 @@ export interface MappedReadInfo {
   alignment_length: number;
   alignment_type: 'primary_forward' | 'primary_reverse' | 'secondary_forward' | 'secondary_reverse' | 'supplementary_forward' | 'supplementary_reverse';
   mod_count: string;
+  /** Whether the record passes the read filters; only given with `noPrefilter`. */
+  passed_prefilter?: boolean;
 }
 
 export interface UnmappedReadInfo {
@@ -31,38 +135,191 @@ export interface UnmappedReadInfo {
   sequence_length: number;
   alignment_type: 'unmapped';
   mod_count: string;
+  /** Whether the record passes the read filters; only given with `noPrefilter`. */
+  passed_prefilter?: boolean;
 }
 
 export type ReadInfoRecord = MappedReadInfo | UnmappedReadInfo;
 
+/** Strand, relative to the basecalled sequence, on which modifications were called. */
+export declare const ModStrand: {
+  /** Modifications called on the basecalled strand. */
+  readonly Bc: 'bc';
+  /** Modifications called on the complement of the basecalled strand. */
+  readonly BcComp: 'bc_comp';
+};
+export type ModStrand = (typeof ModStrand)[keyof typeof ModStrand];
+
+/** Alignment type that a read must have to pass readFilter. */
+export declare const ReadFilterKind: {
+  /** Primary alignment to the reference strand. */
+  readonly PrimaryForward: 'primary_forward';
+  /** Primary alignment opposite the reference strand. */
+  readonly PrimaryReverse: 'primary_reverse';
+  /** Secondary alignment to the reference strand. */
+  readonly SecondaryForward: 'secondary_forward';
+  /** Secondary alignment opposite the reference strand. */
+  readonly SecondaryReverse: 'secondary_reverse';
+  /** Supplementary alignment to the reference strand. */
+  readonly SupplementaryForward: 'supplementary_forward';
+  /** Supplementary alignment opposite the reference strand. */
+  readonly SupplementaryReverse: 'supplementary_reverse';
+  /** Read marked as unmapped. */
+  readonly Unmapped: 'unmapped';
+};
+export type ReadFilterKind = (typeof ReadFilterKind)[keyof typeof ReadFilterKind];
+
+/** Order in which readInfo and bamMods list the reads that pass the filters. */
+export declare const SortBy: {
+  /** By contig in header order, then by reference start; unmapped reads last. */
+  readonly Position: 'position';
+  /** By sequence length, shortest first. */
+  readonly Length: 'length';
+  /** By read id. */
+  readonly ReadId: 'read_id';
+};
+export type SortBy = (typeof SortBy)[keyof typeof SortBy];
+
 // Base options shared by ReadOptions (excluding region/fullRegion)
 interface BaseReadOptionsCore {
-  /** Path to the BAM file (local path or URL). */
+  /**
+   * Path to the BAM file (local path or URL), or "-" to read BAM data piped into
+   * stdin. Stdin cannot be combined with region, regions or regionBed.
+   */
   bamPath: string;
-  /** If true, treat bamPath as a URL. Otherwise treat as file path. */
+  /**
+   * If true, treat bamPath as a URL, if false as a file path. If unset, paths
+   * starting with http://, https://, s3://, gs:// or file:// are URLs.
+   * A file:// URL is read as the local file it names.
+   */
   treatAsUrl?: boolean;
+  /** Reference FASTA used to decode CRAM input, which cannot be read without it. Ignored for BAM input. */
+  referenceFasta?: string;
+  /**
+   * Times to retry opening a URL after a failed attempt (default: 0). The wait
+   * between attempts starts at 100 ms and doubles each time, up to 5 s. Ignored
+   * for local files.
+   */
+  urlRetries?: number;
+  /**
+   * Time limit of each attempt at opening a URL, in milliseconds. Must be at
+   * least 1. Unset means no limit. An attempt that times out is not retried.
+   * Ignored for local files.
+   */
+  urlTimeoutMs?: number;
+  /**
+   * Extra HTTP request headers sent when opening a URL and reading from it, e.g.
+   * `Authorization` or `User-Agent`. Only `http://` and `https://` URLs take
+   * headers; other URLs are rejected. Ignored for local files.
+   */
+  urlHeaders?: Record<string, string>;
+  /**
+   * Build the .bai index of a local, coordinate-sorted BAM file that has none,
+   * with a warning, instead of rejecting with IndexMissing. Defaults to false.
+   * Ignored by NanalogueBam methods.
+   */
+  autoIndex?: boolean;
   /** Minimum sequence length filter. */
   minSeqLen?: number;
+  /** Maximum sequence length filter. Must be >= minSeqLen if both are set. */
+  maxSeqLen?: number;
   /** Minimum alignment length filter. */
   minAlignLen?: number;
+  /**
+   * Maximum alignment length filter. Must be >= minAlignLen if both are set.
+   * Unmapped reads are excluded when it is set, as with minAlignLen.
+   */
+  maxAlignLen?: number;
   /** Filter to a set of read IDs. */
   readIdSet?: string[];
-  /** Number of threads for BAM reading. */
+  /**
+   * Keep reads whose id matches this regular expression, anywhere in the id
+   * unless anchored (e.g. `^run1_`). With readIdSet, reads must satisfy both.
+   */
+  readIdRegex?: string;
+  /**
+   * Keep reads whose RG tag is one of these read group ids, as listed by
+   * readGroups. Reads without the tag are excluded.
+   */
+  readGroup?: string[];
+  /**
+   * Number of threads for BAM reading. Unset, the reader uses the pool of
+   * initThreadPool if it has been started.
+   */
   threads?: number;
-  /** Include zero-length sequences (may cause crashes). */
+  /**
+   * Include records without a sequence, such as most secondary alignments. They
+   * have a sequence length of 0 and no modification data, and are skipped when
+   * windowing.
+   */
   includeZeroLen?: boolean;
-  /** Comma-separated read filter (e.g., "primary_forward,primary_reverse"). */
-  readFilter?: string;
+  /**
+   * Alignment types to keep, as a list (e.g., [ReadFilterKind.PrimaryForward]) or a
+   * comma-separated string (e.g., "primary_forward,primary_reverse").
+   */
+  readFilter?: ReadFilterKind[] | string;
   /** Subsample fraction (0.0 to 1.0). */
   sampleFraction?: number;
   /** Minimum mapping quality filter. */
   mapqFilter?: number;
+  /**
+   * Maximum mapping quality filter. Must be >= mapqFilter if both are set.
+   * Reads with unavailable mapping quality (255) are excluded unless this is 255.
+   */
+  mapqMax?: number;
   /** Exclude reads with unavailable mapping quality. */
   excludeMapqUnavail?: boolean;
+  /** Exclude secondary alignments (SAM flag 0x100). */
+  excludeSecondary?: boolean;
+  /** Exclude supplementary alignments (SAM flag 0x800). */
+  excludeSupplementary?: boolean;
+  /**
+   * Exclude reads marked as PCR or optical duplicates (SAM flag 0x400), which
+   * cannot otherwise be read.
+   */
+  excludeDuplicate?: boolean;
+  /**
+   * Exclude reads that failed quality checks (SAM flag 0x200), which cannot
+   * otherwise be read.
+   */
+  excludeQcFail?: boolean;
+  /**
+   * Keep only the first record of each read id that passes the other filters,
+   * so a read with several alignments (e.g. in different regions) counts once.
+   */
+  dedupe?: boolean;
+  /**
+   * Return every record of readInfo, readInfoStream and bamMods, also those the
+   * read filters would leave out, each with `passed_prefilter` telling whether
+   * it passes them. Other functions still filter.
+   */
+  noPrefilter?: boolean;
+  /**
+   * Minimum number of modified calls per read: calls that pass the modification
+   * filters with a probability of at least 0.5, as counted in `mod_count`.
+   */
+  minMods?: number;
+  /**
+   * Keep only reads with modification data, that is with MM and ML tags, so
+   * that reads without them do not pass as reads without calls. Defaults to
+   * false.
+   */
+  requireMods?: boolean;
+  /**
+   * Add a `has_mod_data` field to each read of bamMods and bamModsJson, false
+   * for a read without MM and ML tags, to tell it apart from a read whose calls
+   * the modification filters all leave out. Other functions ignore it.
+   */
+  reportModData?: boolean;
   /** Filter to specific modification tag. */
   tag?: string;
-  /** Filter by modification strand ("bc" or "bc_comp"). */
-  modStrand?: string;
+  /**
+   * Several modification tags whose calls are all kept, e.g. `['m', 'h']` for
+   * 5mC and 5hmC together, as an alternative to `tag`.
+   */
+  tags?: string[];
+  /** Filter by modification strand. */
+  modStrand?: ModStrand;
   /** Minimum modification quality threshold. */
   minModQual?: number;
   /**
@@ -71,18 +328,103 @@ interface BaseReadOptionsCore {
    * If low > high, throws an error. If high - low <= 1, no rejection range is applied.
    */
   rejectModQualNonInclusive?: [number, number];
+  /**
+   * Keep modification calls with a probability of at least this (0 to 1), e.g.
+   * 0.8 for ML values of 204 and above. Overrides minModQual and
+   * rejectModQualNonInclusive.
+   */
+  modThreshold?: number;
+  /**
+   * Lowest ML value (0-255) kept for each listed modification code, e.g.
+   * { m: 200, h: 150 }; calls of other codes are filtered as usual. Each
+   * bamMods mod_table entry and bamModsTyped call of a listed code gets its
+   * threshold. Not available for windowing.
+   */
+  modThresholds?: Record<string, number>;
   /** Trim modification info from read ends (bp). */
   trimReadEndsMod?: number;
   /** Base quality filter for modifications. */
   baseQualFilterMod?: number;
   /** Genomic region for modification filtering. */
   modRegion?: string;
+  /**
+   * Read the coordinates of region, regions and modRegion as 1-based and
+   * inclusive, as samtools does, so that 'chr1:100-200' covers bases 100 to 200
+   * counting from 1. By default they are 0-based and half-open, so that the
+   * same string covers bases 100 to 199 counting from 0. regionBed files are
+   * always 0-based.
+   */
+  oneBased?: boolean;
   /** Maximum number of records to return. Must be > 0 if set. If omitted, returns all records. */
   limit?: number;
-  /** Number of records to skip before returning results. Must be >= 0 if set. Defaults to 0. */
+  /** Number of records that pass the filters to skip before returning results. Must be >= 0 if set. Defaults to 0. */
   offset?: number;
-  /** Seed for deterministic sampling. Required for stable pagination with sampleFraction. */
+  /**
+   * Seed for deterministic sampling. Required for stable pagination with sampleFraction.
+   * If omitted, each call samples a different subset.
+   */
   sampleSeed?: number;
+  /**
+   * Order of the reads of readInfo, readInfoJson, bamMods, bamModsJson and
+   * bamModsTyped. Sorting holds every read that passes the filters in memory
+   * before the first is returned, and limit/offset apply to the sorted reads.
+   * Not supported by readInfoStream. If omitted, reads come in file order.
+   */
+  sortBy?: SortBy;
+  /**
+   * Called with the number of BAM records processed so far (readInfo, bamMods and exportBam only).
+   * The last call always reports the total number of records read.
+   */
+  onProgress?: (recordsProcessed: number) => void;
+  /** Number of records between onProgress calls. Must be > 0 if set. Defaults to 1000. */
+  progressInterval?: number;
+  /**
+   * Columns of the seqTable output, in order (seqTable only). Defaults to
+   * ["read_id", "sequence", "qualities"].
+   */
+  columns?: SeqTableColumn[];
+  /**
+   * If true, show reads aligned to the minus strand reverse-complemented, in the
+   * orientation they were sequenced in (seqTable, seqTableRecords and
+   * readsTable only). Defaults to false: all reads in the orientation of the
+   * reference.
+   */
+  reverseComplementMinus?: boolean;
+  /**
+   * If false, leave out the base qualities (seqTable, seqTableRecords and
+   * readsTable only): the qualities column, which columns then cannot list,
+   * and the qualities field of seqTableRecords. Defaults to true.
+   */
+  showBaseQual?: boolean;
+  /**
+   * If false, show insertions in upper case like the other bases (seqTable,
+   * seqTableRecords and readsTable only). Defaults to true: lower case.
+   */
+  showInsertions?: boolean;
+  /**
+   * If false, show modified bases as their base instead of as "Z" (or "z" in
+   * an insertion) (seqTable, seqTableRecords and readsTable only). Defaults to
+   * true.
+   */
+  showModMarkers?: boolean;
+  /**
+   * Offset of the base quality encoding: 33 for Phred+33 or 64 for the
+   * Phred+64 of some legacy data (qualityProfiles, seqTable, seqTableRecords,
+   * readsTable and exportFastq only). Defaults to 33. Qualities are given as
+   * phred values whatever the encoding.
+   */
+  qualOffset?: number;
+  /**
+   * Most reads to give rows for, e.g. for a preview of a dense region
+   * (seqTable, seqTableRecords and readsTable only). Must be at least 1.
+   * Reading stops at the first record of a read past them.
+   */
+  maxReads?: number;
+  /**
+   * If true, indent each read of the bamModsJson output over several lines
+   * (bamModsJson only). Defaults to false: one line per read.
+   */
+  pretty?: boolean;
 }
 
 /**
@@ -93,39 +435,459 @@ interface BaseReadOptionsCore {
 interface ReadOptionsWithRegion extends BaseReadOptionsCore {
   /** Genomic region filter (e.g., "chr1:1000-2000"). */
   region: string;
+  regions?: undefined;
+  regionBed?: undefined;
   /**
    * Only include reads fully spanning the region.
-   * Can only be set when `region` is specified.
+   * Can only be set when `region`, `regions` or `regionBed` is specified.
    */
   fullRegion?: boolean;
 }
 
+/**
+ * ReadOptions with several region filters specified.
+ * Each region is fetched in turn, and a read overlapping more than one of them
+ * is returned once, except by seqTable, seqTableRecords and readsTable, which
+ * give a row for each region. `fullRegion` applies to each region.
+ */
+interface ReadOptionsWithRegions extends BaseReadOptionsCore {
+  region?: undefined;
+  /** Genomic regions to query (e.g., ["chr1:1000-2000", "chr2"]). */
+  regions: string[];
+  regionBed?: undefined;
+  /** Only include reads fully spanning at least one of the regions. */
+  fullRegion?: boolean;
+}
+
+/**
+ * ReadOptions with the regions read from a BED file.
+ * Behaves like `regions` with one region per BED interval.
+ */
+interface ReadOptionsWithRegionBed extends BaseReadOptionsCore {
+  region?: undefined;
+  regions?: undefined;
+  /**
+   * Path to a BED file of regions to query. Only the first three columns
+   * (contig, 0-based start, exclusive end) are read.
+   */
+  regionBed: string;
+  /** Only include reads fully spanning at least one of the regions. */
+  fullRegion?: boolean;
+}
+
 /**
  * ReadOptions without a region filter.
  * When `region` is not set, `fullRegion` cannot be used.
  */
 interface ReadOptionsWithoutRegion extends BaseReadOptionsCore {
   region?: undefined;
+  regions?: undefined;
+  regionBed?: undefined;
   fullRegion?: undefined;
 }
 
 /**
  * Options for read operations including BAM filtering and modification parameters.
  *
- * Note: `fullRegion` can only be set when `region` is specified.
+ * Note: `fullRegion` can only be set when `region`, `regions` or `regionBed` is specified.
  */
-export type ReadOptions = ReadOptionsWithRegion | ReadOptionsWithoutRegion;
+export type ReadOptions =
+  | ReadOptionsWithRegion
+  | ReadOptionsWithRegions
+  | ReadOptionsWithRegionBed
+  | ReadOptionsWithoutRegion;
 
 export declare function readInfo(options: ReadOptions): Promise<ReadInfoRecord[]>;
 
+/** Outcome of readInfo on one set of options of readInfoBatch. */
+export interface ReadInfoEntry {
+  /** Whether the query succeeded. */
+  ok: boolean;
+  /** Result of readInfo, if ok. */
+  result?: ReadInfoRecord[];
+  /** Kind of error, as in the code of a rejected query, if not ok. */
+  code?: ErrorKind;
+  /** Error message, if not ok. */
+  error?: string;
+}
+
+/**
+ * Runs readInfo on each of several sets of options concurrently, up to the
+ * setMaxConcurrency limit. The entries are in the order of optionsList; a query
+ * that fails gives an entry with its error instead of rejecting the whole batch.
+ */
+export declare function readInfoBatch(
+  optionsList: ReadOptions[],
+): Promise<ReadInfoEntry[]>;
+
+/** Result of peekWithReads. */
+export interface PeekWithReads {
+  /** What peek gives, with modifications from the first sampleSize records. */
+  peek: PeekResult;
+  /** What readInfo gives for the same options. */
+  reads: ReadInfoRecord[];
+}
+
+/**
+ * Peeks at a file and lists its first reads, as peek and readInfo do, opening
+ * the file once. limit sets how many reads are listed and sampleSize (default:
+ * 100) how many records are examined for modifications; reading stops once
+ * both are done. The result is not cached. Does not accept sortBy.
+ */
+export declare function peekWithReads(
+  options: ReadOptions,
+  sampleSize?: number,
+): Promise<PeekWithReads>;
+
+/**
+ * Counts the reads that pass the filters: the length of the array readInfo
+ * returns for the same options, without building the read information.
+ */
+export declare function countReads(options: ReadOptions): Promise<number>;
+
+/**
+ * Number of reads left after each read filter, applied in this order. Each
+ * count includes only the reads that passed every earlier filter; filters that
+ * are not set leave the count unchanged.
+ */
+export interface FilterStats {
+  /** Records read, from the regions if any are given. */
+  total: number;
+  /** After sampleFraction. */
+  after_sample: number;
+  /** After minSeqLen, maxSeqLen and includeZeroLen. */
+  after_len: number;
+  /** After mapqFilter, mapqMax and excludeMapqUnavail. */
+  after_mapq: number;
+  /** After readIdSet and readIdRegex. */
+  after_read_id: number;
+  /** After minAlignLen and maxAlignLen. */
+  after_align_len: number;
+  /** After readFilter. */
+  after_read_filter: number;
+  /** After region with fullRegion. */
+  after_region: number;
+  /** After excludeSecondary, excludeSupplementary, excludeDuplicate and excludeQcFail. */
+  after_flags: number;
+  /** After readGroup. */
+  after_read_group: number;
+  /** After requireMods and minMods. */
+  after_min_mods: number;
+  /** After dedupe: the reads that pass every filter, as countReads counts. */
+  after_dedupe: number;
+  /**
+   * Reads that pass every filter without MM and ML tags, whose mod_table is
+   * empty whatever the modification filters. Always 0 with requireMods.
+   */
+  without_mod_data: number;
+}
+
+/**
+ * Counts the reads left after each read filter, to find the filter that drops
+ * them. Does not accept limit or offset.
+ */
+export declare function filterStats(options: ReadOptions): Promise<FilterStats>;
+
+/**
+ * Takes the same options as readInfo and returns the JSON text written by the
+ * core `read_info` command, which parses to the same array.
+ */
+export declare function readInfoJson(options: ReadOptions): Promise<string>;
+
+/**
+ * Returns the readInfo record of the read `readId`, or null if no read of that
+ * id passes the filters. With `region`, only that region is fetched; otherwise
+ * the file is scanned until the read is found. Of several alignments of the
+ * read, the first in file order is returned. Ignores `offset`, `limit` and
+ * `noPrefilter`, and does not accept `readIdSet`.
+ */
+export declare function getRead(
+  options: ReadOptions,
+  readId: string,
+): Promise<ReadInfoRecord | null>;
+
+/**
+ * Async iterator over the reads that readInfo would return.
+ *
+ * Reads are produced on a worker thread that stays at most a few reads ahead of
+ * the consumer. Leaving a `for await` loop early, or calling `return()`, stops
+ * the worker and frees its place under setMaxConcurrency straight away; a
+ * stream left unfinished otherwise keeps it until it is garbage-collected.
+ */
+export declare class ReadInfoStream implements AsyncIterableIterator<ReadInfoRecord> {
+  private constructor();
+  next(): Promise<IteratorResult<ReadInfoRecord, undefined>>;
+  return(): Promise<IteratorResult<ReadInfoRecord, undefined>>;
+  [Symbol.asyncIterator](): ReadInfoStream;
+}
+
+/**
+ * Streams the same records as readInfo one at a time instead of returning them
+ * all at once. Errors in the options or the BAM file are raised by the first
+ * call to `next()`.
+ */
+export declare function readInfoStream(options: ReadOptions): ReadInfoStream;
+
+// BAM export types
+export interface ExportBamResult {
+  /** Number of records written to the output BAM. */
+  recordsWritten: number;
+  /** Path of the `.bai` index written next to the output, if the output was indexed. */
+  indexPath?: string;
+}
+
+/**
+ * Writes the reads that readInfo would return to a new BAM file with the source
+ * header. Records are copied unchanged, so MM/ML tags are kept. A `.bai` index is
+ * written when the output is in coordinate order, and an earlier one removed
+ * otherwise. The output is removed again if reading or writing fails.
+ */
+export declare function exportBam(
+  options: ReadOptions,
+  outputPath: string,
+): Promise<ExportBamResult>;
+
+export interface ExportFastqResult {
+  /** Number of reads written to the output FASTQ. */
+  recordsWritten: number;
+  /** Path of the file written, with the `.gz` extension added by `compress`. */
+  outputPath: string;
+}
+
+/**
+ * Writes the reads that readInfo would return to a new FASTQ file, with reads
+ * aligned to the reverse strand reverse-complemented back to the orientation
+ * they were sequenced in. As with `samtools fastq`, secondary and supplementary
+ * alignments are left out, and bases without a quality are given 1. With
+ * `compress`, the file is gzip-compressed and `.gz` is added to its name unless
+ * present.
+ */
+export declare function exportFastq(
+  options: ReadOptions,
+  outputPath: string,
+  compress?: boolean,
+): Promise<ExportFastqResult>;
+
+/**
+ * Windows each read in reference coordinates as windowAggregate does and writes
+ * the mean window value of each bin to a bedGraph file of `contig start end
+ * value` lines in 0-based coordinates, over `region` or every contig, for IGV or
+ * the UCSC browser. `step` must equal `win`, and the calls must be of one
+ * modification, e.g. chosen with `tag`. Bins without windows are left out, or
+ * written as 0 if `skipEmpty` is false. Resolves to the number of lines written.
+ */
+export declare function exportBedgraph(
+  options: WindowOptions,
+  outputPath: string,
+  skipEmpty?: boolean,
+): Promise<number>;
+
+/**
+ * Creates a `.bai` index next to a coordinate-sorted BAM file, so that it can be
+ * queried. Rejects with an error if the file is not sorted by coordinate.
+ */
+export declare function buildIndex(bamPath: string): Promise<void>;
+
+/**
+ * Limits how many queries read BAM files at the same time. Queries over the
+ * limit wait for a running one to finish before opening their file; a
+ * readInfoStream holds its place until it ends or is closed. Call without an
+ * argument to remove the limit, which is the default. Throws if the limit is 0.
+ */
+export declare function setMaxConcurrency(maxConcurrency?: number): void;
+
+/**
+ * Starts a pool of `threads` htslib threads, from 1 to 255, that the readers of
+ * later queries and NanalogueBam handles share to decompress BAM data, rather
+ * than starting and stopping threads of their own each time. Readers given the
+ * `threads` option still start their own. Calling it again replaces the pool;
+ * readers already open keep the old one until they close. Throws if `threads`
+ * is 0.
+ */
+export declare function initThreadPool(threads: number): void;
+
+/** One diagnostic message of the library, as setLogCallback passes it on. */
+export interface LogRecord {
+  level: 'error' | 'warn' | 'info' | 'debug' | 'trace';
+  message: string;
+}
+
+/**
+ * Sets a function that receives the diagnostics of the library: the messages
+ * its dependencies log at `info` level and above, and the warnings otherwise
+ * written to stderr, such as windows that windowReads skips because `winOp`
+ * cannot value them. Messages arrive asynchronously, and the callback
+ * does not keep the process alive. Call without an argument to remove it, which
+ * is the default: logged messages are then dropped and warnings go to stderr.
+ */
+export declare function setLogCallback(callback?: (record: LogRecord) => void): void;
+
+/** Versions of the binding and of the libraries it loaded. */
+export interface Versions {
+  /** Version of this package. */
+  node_binding: string;
+  /** Version of nanalogue-core the binding was built with. */
+  core: string;
+  /** Version of the htslib library linked into the binding. */
+  htslib: string;
+}
+
+/** Returns the versions of the binding, nanalogue-core and htslib. */
+export declare function version(): Versions;
+
+/** Bounds of a region string, as parseRegion gives them. */
+export interface ParsedRegion {
+  contig: string;
+  /** First position (0-based) of the region, unset for a whole contig. */
+  start?: number;
+  /**
+   * Position (0-based) after the region, unset for a whole contig or a region
+   * that runs to the end of the contig.
+   */
+  end?: number;
+}
+
+/**
+ * Parses a region string such as `'chr1:1000-2000'` as the `region` option
+ * does, without opening any file, and returns its 0-based, half-open bounds.
+ * With `oneBased`, the coordinates are read as 1-based and inclusive. Throws
+ * an `InvalidRegion` error if the string cannot be parsed. The contig is not
+ * checked against any BAM header.
+ */
+export declare function parseRegion(region: string, oneBased?: boolean): ParsedRegion;
+
+/** Kind of error a function failed with, found in the `code` of the error. */
+export declare const ErrorKind: {
+  /** An option is missing, malformed or out of range. */
+  readonly InvalidOptions: 'InvalidOptions';
+  /** A region cannot be parsed, is not in the BAM header, or cannot be fetched. */
+  readonly InvalidRegion: 'InvalidRegion';
+  /** The BAM file or another input file does not exist. */
+  readonly FileNotFound: 'FileNotFound';
+  /** The BAM file has no index, or its index cannot be loaded. */
+  readonly IndexMissing: 'IndexMissing';
+  /** Opening, reading or writing a file failed. */
+  readonly IoError: 'IoError';
+  /** The BAM data is malformed, e.g. a truncated record or invalid modification tags. */
+  readonly InvalidData: 'InvalidData';
+  /** The call was stopped with a CancelToken. */
+  readonly Cancelled: 'Cancelled';
+  /** The call failed inside the bindings, e.g. a worker thread panicked. */
+  readonly Internal: 'Internal';
+};
+export type ErrorKind = (typeof ErrorKind)[keyof typeof ErrorKind];
+
+/**
+ * Error thrown or rejected with by the functions and classes of this module.
+ * Errors raised while converting arguments, before any nanalogue code runs, have
+ * the NAPI status as their code instead, e.g. 'InvalidArg'.
+ */
+export interface NanalogueError extends Error {
+  code: ErrorKind | string;
+}
+
 // Simulation types
 export interface SimulateOptions {
   jsonConfig: string;
   bamPath: string;
   fastaPath: string;
+  /**
+   * Seed for the random generation, so that the same seed and configuration
+   * write byte-identical files. Overrides any `seed` in jsonConfig. Must be
+   * non-negative.
+   */
+  seed?: number;
+  /**
+   * Replace bamPath and fastaPath if they exist. Defaults to false, in which
+   * case an existing file is rejected with an IoError.
+   */
+  overwrite?: boolean;
 }
 
-export declare function simulateModBam(options: SimulateOptions): Promise<void>;
+/** Summary of the data written by simulateModBam. */
+export interface SimulateStats {
+  /** Number of records in the BAM file, mapped or not. */
+  n_reads: number;
+  /** Number of modification calls, i.e. of entries in the ML tags. */
+  n_mods: number;
+  /** Number of contigs in the FASTA file and BAM header. */
+  n_contigs: number;
+  /** Total length of the read sequences. */
+  total_bases: number;
+  /** Ids of the reads in the BAM file, in file order and each given once. */
+  read_ids: string[];
+  /** Names of the contigs in the FASTA file and BAM header, in header order. */
+  contigs: string[];
+}
+
+export declare function simulateModBam(options: SimulateOptions): Promise<SimulateStats>;
+
+/**
+ * Checks a simulation configuration as simulateModBam would, without reading or
+ * writing any files. Rejects with an error naming the offending field.
+ */
+export declare function validateSimulationConfig(jsonConfig: string): Promise<void>;
+
+/** Modification of a group of simulated reads. */
+export interface ModConfig {
+  /** Base that is modified: A, C, G, T or N. */
+  base: string;
+  isStrandPlus: boolean;
+  /** Modification code, e.g. "m" or "76792". */
+  modCode: string;
+  /** Number of bases of interest in each window, repeated along the read. */
+  win: number[];
+  /** Range of modification probabilities of each window, as [low, high]. */
+  modRange: [number, number][];
+}
+
+/** Group of simulated reads. */
+export interface ReadConfig {
+  number: number;
+  /** Range of read lengths as fractions of the contig length. */
+  lenRange: [number, number];
+  /** Defaults to [0, 0]. */
+  mapqRange?: [number, number];
+  /** Defaults to [0, 0]. */
+  baseQualRange?: [number, number];
+  /** Barcode added to both ends of each read. */
+  barcode?: string;
+  /** Part of each read to delete, as fractions [start, end] of its length. */
+  delete?: [number, number];
+  /** Sequence inserted in the middle of each read. */
+  insertMiddle?: string;
+  /** Fraction of bases changed to another base, between 0 and 1. */
+  mismatch?: number;
+  mods?: ModConfig[];
+}
+
+/** Simulated contigs. */
+export interface ContigConfig {
+  number: number;
+  /** Range of contig lengths in bp. */
+  lenRange: [number, number];
+  /** Sequence repeated along each contig instead of a random one. */
+  repeatedSeq?: string;
+}
+
+/** Typed form of the JSON configuration of simulateModBam, with camelCase fields. */
+export interface SimulationConfig {
+  contigs: ContigConfig;
+  reads: ReadConfig[];
+  /** Makes the simulation reproducible. Must be non-negative. */
+  seed?: number;
+}
+
+/**
+ * Same as simulateModBam, with a typed configuration instead of a JSON string.
+ * Rejects with an error naming the offending field, e.g. `reads[0].lenRange`.
+ */
+export declare function simulateModBamTyped(
+  config: SimulationConfig,
+  bamPath: string,
+  fastaPath: string,
+  overwrite?: boolean,
+): Promise<SimulateStats>;
 
 // Detailed modification data types (bamMods)
 export interface ModTableEntry {
@@ -133,6 +895,8 @@ export interface ModTableEntry {
   is_strand_plus: boolean;
   mod_code: string;
   data: [number, number, number][]; // [read_pos, ref_pos, probability]
+  /** Lowest ML value kept for mod_code, if modThresholds lists it. */
+  threshold?: number;
 }
 
 export interface MappedBamModRecord {
@@ -146,6 +910,14 @@ export interface MappedBamModRecord {
   mod_table: ModTableEntry[];
   read_id: string;
   seq_len: number;
+  /**
+   * Whether the read has MM and ML tags; only given with `reportModData`. False
+   * tells a read without them from one whose calls the modification filters
+   * all leave out, as both have an empty mod_table.
+   */
+  has_mod_data?: boolean;
+  /** Whether the record passes the read filters; only given with `noPrefilter`. */
+  passed_prefilter?: boolean;
 }
 
 export interface UnmappedBamModRecord {
@@ -153,46 +925,567 @@ export interface UnmappedBamModRecord {
   mod_table: ModTableEntry[];
   read_id: string;
   seq_len: number;
+  /**
+   * Whether the read has MM and ML tags; only given with `reportModData`. False
+   * tells a read without them from one whose calls the modification filters
+   * all leave out, as both have an empty mod_table.
+   */
+  has_mod_data?: boolean;
+  /** Whether the record passes the read filters; only given with `noPrefilter`. */
+  passed_prefilter?: boolean;
 }
 
 export type BamModRecord = MappedBamModRecord | UnmappedBamModRecord;
 
 export declare function bamMods(options: ReadOptions): Promise<BamModRecord[]>;
 
+/**
+ * Returns the reads of bamMods as JSON text: one line per read, or indented
+ * over several lines with `pretty: true`.
+ */
+export declare function bamModsJson(options: ReadOptions): Promise<string>;
+
+/** One modification call on one read. */
+export interface ModCall {
+  read_id: string;
+  /** 0-based position of the call on the basecalled sequence. */
+  query_position: number;
+  /** 0-based position of the call on the contig, absent if the base is not aligned. */
+  reference_position?: number;
+  /** Modification code, e.g. "m", or a numeric code such as "76792". */
+  mod_code: string;
+  /** Unmodified base the modification is on, e.g. "C". */
+  canonical_base: string;
+  /** Strand of the modification in the MM tag. */
+  strand: '+' | '-';
+  /** Probability of modification as stored in the ML tag, from 0 to 255. */
+  ml_value: number;
+  /** Probability of modification between 0 and 1. */
+  probability: number;
+  /** Lowest ML value kept for mod_code, if modThresholds lists it. */
+  threshold?: number;
+}
+
+/**
+ * Returns the modification calls of the reads that bamMods would return, one
+ * object per call. `offset` and `limit` count reads, not calls.
+ */
+export declare function bamModsTyped(options: ReadOptions): Promise<ModCall[]>;
+
+/**
+ * Counts the modification calls that bamModsTyped would return at each ML value,
+ * giving 256 counts for the values 0 to 255. With `bins` (1 to 256), values are
+ * grouped into that many bins of near-equal width, value `v` going to bin
+ * `floor(v * bins / 256)`.
+ */
+export declare function modQualHistogram(options: ReadOptions, bins?: number): Promise<number[]>;
+
+/** Threshold on the ML values of modification calls, from suggestThreshold. */
+export interface ThresholdSuggestion {
+  /**
+   * Lowest ML value, from 0 to 255, of the calls above the threshold, so that
+   * it can be given as minModQual.
+   */
+  threshold: number;
+  /**
+   * "otsu", or "default" if the calls have fewer than two distinct ML values,
+   * when the threshold is 128.
+   */
+  method: 'otsu' | 'default';
+}
+
+/**
+ * Suggests a threshold on the ML values of the calls that modQualHistogram
+ * counts, by Otsu's method, e.g. to prefill a minModQual slider.
+ */
+export declare function suggestThreshold(options: ReadOptions): Promise<ThresholdSuggestion>;
+
+/**
+ * Counts the reads that readInfo would return at each mapping quality, giving
+ * 256 counts for the values 0 to 255. A mapping quality of 255 means it is
+ * unavailable; such reads are left out with `excludeMapqUnavail`.
+ */
+export declare function mapqHistogram(options: ReadOptions): Promise<number[]>;
+
+/** Modification call totals of one read. */
+export interface ModSummary {
+  read_id: string;
+  /** Number of calls with a probability of at least 0.5. */
+  n_mod: number;
+  /** Number of calls that pass the modification filters. */
+  n_valid: number;
+  /** Mean probability of modification of the valid calls, absent if there are none. */
+  mean_prob?: number;
+}
+
+/**
+ * Totals the modification calls of each read that bamMods would return, in
+ * the same order. `offset` and `limit` count reads.
+ */
+export declare function modSummary(options: ReadOptions): Promise<ModSummary[]>;
+
+/** Modification calls of one modification code on one contig. */
+export interface ContigModCount {
+  contig: string;
+  /** Modification code, e.g. `m`, or a numeric code such as `76792`. */
+  mod_code: string;
+  /** Number of calls with a probability of at least 0.5. */
+  n_mod: number;
+  /** Number of calls that pass the modification filters. */
+  n_valid: number;
+}
+
+/**
+ * Totals the modification calls aligned to each contig, per modification
+ * code, over the reads that bamMods would return. Calls on bases not aligned
+ * to the reference are left out. Totals are in header order of the contigs,
+ * leaving out contigs without calls; `offset` and `limit` count reads.
+ */
+export declare function modCountsByContig(options: ReadOptions): Promise<ContigModCount[]>;
+
+/** Alignment statistics of one read, from its CIGAR string. */
+export interface AlignStats {
+  read_id: string;
+  /** Number of reference bases the alignment spans, 0 if unmapped. */
+  ref_span: number;
+  /** Soft-clipped bases at the start of the alignment on the reference. */
+  soft_clip_left: number;
+  /** Soft-clipped bases at the end of the alignment on the reference. */
+  soft_clip_right: number;
+  /** Inserted bases. */
+  n_ins: number;
+  /** Deleted bases, not counting skipped regions such as introns. */
+  n_del: number;
+  /** Bases aligned to the reference, whether they match it or not. */
+  n_match: number;
+}
+
+/**
+ * Gives the CIGAR statistics of each read that readInfo would return, in the
+ * same order. `offset` and `limit` count reads. Left and right are along the
+ * reference, whatever the strand of the read.
+ */
+export declare function alignStats(options: ReadOptions): Promise<AlignStats[]>;
+
+/** Named interval of a contig, as in a BED file, given to annotateReads. */
+export interface Feature {
+  /** Contig name, as in the BAM header. */
+  contig: string;
+  /** 0-based start of the feature. */
+  start: number;
+  /** 0-based exclusive end of the feature, after `start`. */
+  end: number;
+  /** Name of the feature, given back for the reads that overlap it. */
+  name: string;
+}
+
+/** Features one read overlaps, from annotateReads. */
+export interface ReadAnnotation {
+  read_id: string;
+  /**
+   * Names of the features the alignment of the read overlaps by at least one
+   * base, in the order the features were given. Empty for an unmapped read.
+   */
+  overlapping: string[];
+}
+
+/**
+ * Lists the features that the alignment of each read overlaps, with one entry
+ * per read that readInfo would return, in the same order. `offset` and
+ * `limit` count reads. A read overlaps a feature if its reference span shares
+ * at least one base with it. Features are indexed once, so many features can
+ * be given. Rejects with `InvalidRegion` if a feature is on a contig that is
+ * not in the BAM header, has a negative coordinate or does not end after its
+ * start.
+ */
+export declare function annotateReads(options: ReadOptions, features: Feature[]): Promise<ReadAnnotation[]>;
+
+/** Modification calls at one reference position, strand and modification code. */
+export interface PileupRecord {
+  contig: string;
+  /** 0-based position on the contig. */
+  pos: number;
+  /**
+   * Reference strand the modified base is on; '+' for the C calls that
+   * collapseStrands collapses.
+   */
+  strand: '+' | '-';
+  /** Modification code, e.g. "m", or a numeric code such as "76792". */
+  mod_code: string;
+  /** Number of calls with a probability of at least 0.5. */
+  n_mod: number;
+  /** Number of calls that pass the modification filters. */
+  n_valid: number;
+  /** n_mod / n_valid. */
+  fraction: number;
+}
+
+/** Options of pileup that the other functions do not take. */
+export interface PileupOptions {
+  /**
+   * Leave out the positions with fewer valid calls (n_valid) than this, such
+   * as those covered by only one or two reads. Defaults to 0: every position
+   * with a call.
+   */
+  minCoverage?: number;
+  /**
+   * If true, count the calls on the C of the minus strand of a CpG at the C of
+   * the plus strand, so each CpG gives one record, whether the calls are
+   * reported on C (C+m) or on its complement (G-m). The reference is not read,
+   * so C calls outside CpGs are moved too; calls on other bases keep their
+   * strand. Defaults to false.
+   */
+  collapseStrands?: boolean;
+}
+
+/**
+ * Counts modified and valid modification calls at each position of `region`,
+ * which is required. Records are sorted by position, strand and modification code.
+ */
+export declare function pileup(
+  options: ReadOptions,
+  pileupOptions?: PileupOptions,
+): Promise<PileupRecord[]>;
+
+/** Read depth at a position, or its mean over a bin of positions. */
+export interface CoverageRecord {
+  /** 0-based position on the contig, the first position of the bin when binned. */
+  pos: number;
+  /** Number of reads spanning the position, or its mean over the bin. */
+  depth: number;
+}
+
+/**
+ * Counts the reads spanning each position of `region`, which is required.
+ * With `binSize`, returns the mean depth of each bin of that many positions.
+ */
+export declare function coverage(options: ReadOptions, binSize?: number): Promise<CoverageRecord[]>;
+
+/** Length and read count of a contig. */
+export interface ContigSummary {
+  contig: string;
+  /** Contig length from the BAM header. */
+  length: number;
+  /** Number of mapped records on the contig. */
+  n_reads: number;
+}
+
+/**
+ * Counts the mapped records on each contig of the BAM header, in header order.
+ * With no read filters the counts come from the index, as in `samtools idxstats`.
+ */
+export declare function contigSummary(options: ReadOptions): Promise<ContigSummary[]>;
+
+/** Record counts of a contig from the index. */
+export interface IdxStat {
+  /** Contig name, or `*` for unmapped records without a position. */
+  contig: string;
+  /** Contig length from the BAM header, 0 for `*`. */
+  length: number;
+  /** Number of mapped records on the contig. */
+  mapped: number;
+  /** Number of unmapped records placed on the contig. */
+  unmapped: number;
+}
+
+/**
+ * Lists the mapped and unmapped record counts of each contig from the index,
+ * as `samtools idxstats`, followed by a `*` row of unplaced unmapped records.
+ * Needs an index; BAM records are not read.
+ */
+export declare function idxstats(options: PeekOptions): Promise<IdxStat[]>;
+
+/**
+ * Estimates how many records a query reads, e.g. to size a progress bar: the
+ * count is what `onProgress` reaches, as read filters do not apply. The count
+ * comes from the index and is exact without regions or for regions covering
+ * whole contigs; over part of a contig it is approximate, scaled by the share
+ * of the contig covered. Needs an index; CRAM records are read to count them.
+ */
+export declare function estimateReadCount(options: ReadOptions): Promise<number>;
+
+/**
+ * Read counts per alignment category, as in `samtools flagstat` with QC-passed
+ * and QC-failed reads counted together. Pair categories count primary
+ * alignments only.
+ */
+export interface Flagstat {
+  /** Number of reads. */
+  total: number;
+  /** Reads that failed quality checks (flag 0x200). */
+  qc_failed: number;
+  /** Primary alignments, neither secondary nor supplementary. */
+  primary: number;
+  /** Secondary alignments (flag 0x100). */
+  secondary: number;
+  /** Supplementary alignments (flag 0x800). */
+  supplementary: number;
+  /** Duplicates (flag 0x400). */
+  duplicates: number;
+  /** Primary alignments that are duplicates. */
+  primary_duplicates: number;
+  /** Mapped reads. */
+  mapped: number;
+  /** Mapped primary alignments. */
+  primary_mapped: number;
+  /** Primary alignments of paired reads (flag 0x1). */
+  paired: number;
+  /** Paired primary alignments of the first read of the pair (flag 0x40). */
+  read1: number;
+  /** Paired primary alignments of the last read of the pair (flag 0x80). */
+  read2: number;
+  /** Mapped paired primary alignments that are properly paired (flag 0x2). */
+  properly_paired: number;
+  /** Mapped paired primary alignments whose mate is mapped too. */
+  with_mate_mapped: number;
+  /** Mapped paired primary alignments whose mate is unmapped. */
+  singletons: number;
+  /** Mapped paired primary alignments with the mate on another contig. */
+  mate_on_other_contig: number;
+  /** Those of mate_on_other_contig with a mapping quality of at least 5. */
+  mate_on_other_contig_mapq5: number;
+}
+
+/**
+ * Counts the reads readInfo returns for the same options by alignment category,
+ * from their SAM flags.
+ */
+export declare function flagstat(options: ReadOptions): Promise<Flagstat>;
+
+/** Lists the read group ids of the @RG header lines, in header order. */
+export declare function readGroups(options: PeekOptions): Promise<string[]>;
+
+/** Windowing operation applied by windowReads. */
+export declare const WinOp: {
+  /** Mean modification density in each window. */
+  readonly Density: 'density';
+  /** Gradient of the modification density in each window. */
+  readonly GradDensity: 'grad_density';
+  /** Sum of the modification probabilities in each window. */
+  readonly Sum: 'sum';
+  /** Highest modification probability in each window. */
+  readonly Max: 'max';
+  /** Number of modified calls (probability of at least 0.5) in each window. */
+  readonly Count: 'count';
+  /** Mean modification probability in each window, without thresholding. */
+  readonly RawMean: 'raw_mean';
+};
+export type WinOp = (typeof WinOp)[keyof typeof WinOp];
+
+/** Output format of windowReads. */
+export declare const WindowFormat: {
+  /** JSON array with one entry per read, holding its windows in mod_table. */
+  readonly Json: 'json';
+  /** Tab-separated table with a header line and one row per window. */
+  readonly Tsv: 'tsv';
+};
+export type WindowFormat = (typeof WindowFormat)[keyof typeof WindowFormat];
+
+/** Coordinates along which windowReads windows the calls of each read. */
+export declare const WindowCoordinate: {
+  /** Windows of win calls along the read. */
+  readonly Read: 'read';
+  /**
+   * Windows of win reference bases, starting at multiples of step on the
+   * contig. Reads without an alignment are left out, and the JSON output has
+   * one object per window with the fields of the TSV columns.
+   */
+  readonly Reference: 'reference';
+};
+export type WindowCoordinate =
+  (typeof WindowCoordinate)[keyof typeof WindowCoordinate];
+
+/**
+ * What windowReads does with the calls of a read past its last window of win
+ * calls, when windowing along the read.
+ */
+export declare const EdgeMode: {
+  /** Leave them out, as are reads with fewer than win calls. */
+  readonly Drop: 'drop';
+  /**
+   * Add the window that starts at the next step, cut short at the last call.
+   * It is valued over the fewer calls it holds, so sum and count totals only
+   * count those. A read with fewer than win calls gets one such window.
+   */
+  readonly Partial: 'partial';
+  /**
+   * Add a window of the last win calls, i.e. the short window padded out with
+   * the calls before it. Reads with fewer than win calls are still left out.
+   */
+  readonly Pad: 'pad';
+};
+export type EdgeMode = (typeof EdgeMode)[keyof typeof EdgeMode];
+
 // Base options shared by WindowOptions (excluding region/fullRegion)
 interface BaseWindowOptionsCore {
-  /** Path to the BAM file (local path or URL). */
+  /**
+   * Path to the BAM file (local path or URL), or "-" to read BAM data piped into
+   * stdin. Stdin cannot be combined with region, regions or regionBed.
+   */
   bamPath: string;
-  /** If true, treat bamPath as a URL. Otherwise treat as file path. */
+  /**
+   * If true, treat bamPath as a URL, if false as a file path. If unset, paths
+   * starting with http://, https://, s3://, gs:// or file:// are URLs.
+   * A file:// URL is read as the local file it names.
+   */
   treatAsUrl?: boolean;
-  /** Window size in number of bases. */
+  /** Reference FASTA used to decode CRAM input, which cannot be read without it. Ignored for BAM input. */
+  referenceFasta?: string;
+  /**
+   * Times to retry opening a URL after a failed attempt (default: 0). The wait
+   * between attempts starts at 100 ms and doubles each time, up to 5 s. Ignored
+   * for local files.
+   */
+  urlRetries?: number;
+  /**
+   * Time limit of each attempt at opening a URL, in milliseconds. Must be at
+   * least 1. Unset means no limit. An attempt that times out is not retried.
+   * Ignored for local files.
+   */
+  urlTimeoutMs?: number;
+  /**
+   * Extra HTTP request headers sent when opening a URL and reading from it, e.g.
+   * `Authorization` or `User-Agent`. Only `http://` and `https://` URLs take
+   * headers; other URLs are rejected. Ignored for local files.
+   */
+  urlHeaders?: Record<string, string>;
+  /**
+   * Build the .bai index of a local, coordinate-sorted BAM file that has none,
+   * with a warning, instead of rejecting with IndexMissing. Defaults to false.
+   */
+  autoIndex?: boolean;
+  /**
+   * Window size in number of calls, or of reference bases with
+   * WindowCoordinate.Reference.
+   */
   win: number;
   /** Step size for sliding the window. */
   step: number;
-  /** Type of windowing operation: "density" or "grad_density". */
-  winOp?: 'density' | 'grad_density';
+  /** Type of windowing operation. Defaults to WinOp.Density. */
+  winOp?: WinOp;
+  /** Format of the output. Defaults to WindowFormat.Json. */
+  outputFormat?: WindowFormat;
+  /**
+   * File to write the output to instead of returning it, for outputs too large
+   * for a string. The promise then resolves to the number of lines written.
+   */
+  outputPath?: string;
+  /**
+   * Gzip-compress the file written to outputPath, adding a ".gz" extension to
+   * the path if it does not have one. Requires outputPath.
+   */
+  compress?: boolean;
+  /** Windows with fewer valid calls (n_valid) than this are left out. */
+  minWindowCalls?: number;
+  /**
+   * Coordinates along which reads are windowed. Defaults to
+   * WindowCoordinate.Read.
+   */
+  coordinate?: WindowCoordinate;
+  /**
+   * What to do with the calls of a read past its last window of win calls.
+   * Defaults to EdgeMode.Drop. Ignored with WindowCoordinate.Reference, whose
+   * windows at the ends of a read already hold whatever calls fall in them.
+   */
+  edgeMode?: EdgeMode;
+  /**
+   * Window the reads on several threads. The selected reads are all read into
+   * memory first, and the output is in the same order as without it. Defaults
+   * to false. Only honoured by windowReads.
+   */
+  parallel?: boolean;
   /** Minimum sequence length filter. */
   minSeqLen?: number;
+  /** Maximum sequence length filter. Must be >= minSeqLen if both are set. */
+  maxSeqLen?: number;
   /** Minimum alignment length filter. */
   minAlignLen?: number;
+  /**
+   * Maximum alignment length filter. Must be >= minAlignLen if both are set.
+   * Unmapped reads are excluded when it is set, as with minAlignLen.
+   */
+  maxAlignLen?: number;
   /** Filter to a set of read IDs. */
   readIdSet?: string[];
-  /** Number of threads for BAM reading. */
+  /**
+   * Keep reads whose id matches this regular expression, anywhere in the id
+   * unless anchored (e.g. `^run1_`). With readIdSet, reads must satisfy both.
+   */
+  readIdRegex?: string;
+  /**
+   * Keep reads whose RG tag is one of these read group ids, as listed by
+   * readGroups. Reads without the tag are excluded.
+   */
+  readGroup?: string[];
+  /**
+   * Number of threads for BAM reading. Unset, the reader uses the pool of
+   * initThreadPool if it has been started.
+   */
   threads?: number;
-  /** Include zero-length sequences (may cause crashes). */
+  /**
+   * Include records without a sequence, such as most secondary alignments. They
+   * have a sequence length of 0 and no modification data, and are skipped when
+   * windowing.
+   */
   includeZeroLen?: boolean;
-  /** Comma-separated read filter. */
-  readFilter?: string;
+  /** Alignment types to keep, as a list or a comma-separated string. */
+  readFilter?: ReadFilterKind[] | string;
   /** Subsample fraction (0.0 to 1.0). */
   sampleFraction?: number;
   /** Minimum mapping quality filter. */
   mapqFilter?: number;
+  /**
+   * Maximum mapping quality filter. Must be >= mapqFilter if both are set.
+   * Reads with unavailable mapping quality (255) are excluded unless this is 255.
+   */
+  mapqMax?: number;
   /** Exclude reads with unavailable mapping quality. */
   excludeMapqUnavail?: boolean;
+  /** Exclude secondary alignments (SAM flag 0x100). */
+  excludeSecondary?: boolean;
+  /** Exclude supplementary alignments (SAM flag 0x800). */
+  excludeSupplementary?: boolean;
+  /**
+   * Exclude reads marked as PCR or optical duplicates (SAM flag 0x400), which
+   * cannot otherwise be read.
+   */
+  excludeDuplicate?: boolean;
+  /**
+   * Exclude reads that failed quality checks (SAM flag 0x200), which cannot
+   * otherwise be read.
+   */
+  excludeQcFail?: boolean;
+  /**
+   * Keep only the first record of each read id that passes the other filters,
+   * so a read with several alignments (e.g. in different regions) counts once.
+   */
+  dedupe?: boolean;
+  /**
+   * Return every record of readInfo and bamMods, also those the read filters
+   * would leave out, each with `passed_prefilter` telling whether it passes
+   * them. Other functions still filter.
+   */
+  noPrefilter?: boolean;
+  /**
+   * Minimum number of modified calls per read: calls that pass the modification
+   * filters with a probability of at least 0.5, as counted in `mod_count`.
+   */
+  minMods?: number;
+  /**
+   * Keep only reads with modification data, that is with MM and ML tags, so
+   * that reads without them do not pass as reads without calls. Defaults to
+   * false.
+   */
+  requireMods?: boolean;
   /** Filter to specific modification tag. */
   tag?: string;
+  /**
+   * Several modification tags whose calls are all kept, e.g. `['m', 'h']` for
+   * 5mC and 5hmC together, as an alternative to `tag`.
+   */
+  tags?: string[];
   /** Filter by modification strand. */
-  modStrand?: string;
+  modStrand?: ModStrand;
   /** Minimum modification quality threshold. */
   minModQual?: number;
   /**
@@ -201,18 +1494,37 @@ interface BaseWindowOptionsCore {
    * If low > high, throws an error. If high - low <= 1, no rejection range is applied.
    */
   rejectModQualNonInclusive?: [number, number];
+  /**
+   * Keep modification calls with a probability of at least this (0 to 1), e.g.
+   * 0.8 for ML values of 204 and above. Overrides minModQual and
+   * rejectModQualNonInclusive.
+   */
+  modThreshold?: number;
   /** Trim modification info from read ends (bp). */
   trimReadEndsMod?: number;
   /** Base quality filter for modifications. */
   baseQualFilterMod?: number;
   /** Genomic region for modification filtering. */
   modRegion?: string;
+  /**
+   * Read the coordinates of region, regions and modRegion as 1-based and
+   * inclusive, as samtools does, so that 'chr1:100-200' covers bases 100 to 200
+   * counting from 1. By default they are 0-based and half-open, so that the
+   * same string covers bases 100 to 199 counting from 0. regionBed files are
+   * always 0-based.
+   */
+  oneBased?: boolean;
   /** Maximum number of records to return. Must be > 0 if set. If omitted, returns all records. */
   limit?: number;
-  /** Number of records to skip before returning results. Must be >= 0 if set. Defaults to 0. */
+  /** Number of records that pass the filters to skip before returning results. Must be >= 0 if set. Defaults to 0. */
   offset?: number;
-  /** Seed for deterministic sampling. Required for stable pagination with sampleFraction. */
+  /**
+   * Seed for deterministic sampling. Required for stable pagination with sampleFraction.
+   * If omitted, each call samples a different subset.
+   */
   sampleSeed?: number;
+  /** Token to cancel the windowing before it finishes. */
+  cancelToken?: CancelToken;
 }
 
 /**
@@ -223,10 +1535,42 @@ interface BaseWindowOptionsCore {
 interface WindowOptionsWithRegion extends BaseWindowOptionsCore {
   /** Genomic region filter (e.g., "chr1:1000-2000"). */
   region: string;
+  regions?: undefined;
+  regionBed?: undefined;
   /**
    * Only include reads fully spanning the region.
-   * Can only be set when `region` is specified.
+   * Can only be set when `region`, `regions` or `regionBed` is specified.
+   */
+  fullRegion?: boolean;
+}
+
+/**
+ * WindowOptions with several region filters specified.
+ * Each region is fetched in turn, and a read overlapping more than one of them
+ * is returned once. `fullRegion` applies to each region.
+ */
+interface WindowOptionsWithRegions extends BaseWindowOptionsCore {
+  region?: undefined;
+  /** Genomic regions to query (e.g., ["chr1:1000-2000", "chr2"]). */
+  regions: string[];
+  regionBed?: undefined;
+  /** Only include reads fully spanning at least one of the regions. */
+  fullRegion?: boolean;
+}
+
+/**
+ * WindowOptions with the regions read from a BED file.
+ * Behaves like `regions` with one region per BED interval.
+ */
+interface WindowOptionsWithRegionBed extends BaseWindowOptionsCore {
+  region?: undefined;
+  regions?: undefined;
+  /**
+   * Path to a BED file of regions to query. Only the first three columns
+   * (contig, 0-based start, exclusive end) are read.
    */
+  regionBed: string;
+  /** Only include reads fully spanning at least one of the regions. */
   fullRegion?: boolean;
 }
 
@@ -236,16 +1580,353 @@ interface WindowOptionsWithRegion extends BaseWindowOptionsCore {
  */
 interface WindowOptionsWithoutRegion extends BaseWindowOptionsCore {
   region?: undefined;
+  regions?: undefined;
+  regionBed?: undefined;
   fullRegion?: undefined;
 }
 
 /**
  * Options for windowed modification analysis.
  *
- * Note: `fullRegion` can only be set when `region` is specified.
+ * Note: `fullRegion` can only be set when `region`, `regions` or `regionBed` is specified.
  */
-export type WindowOptions = WindowOptionsWithRegion | WindowOptionsWithoutRegion;
+export type WindowOptions =
+  | WindowOptionsWithRegion
+  | WindowOptionsWithRegions
+  | WindowOptionsWithRegionBed
+  | WindowOptionsWithoutRegion;
 
+export declare function windowReads(
+  options: WindowOptions & { outputPath: string },
+): Promise<number>;
 export declare function windowReads(options: WindowOptions): Promise<string>;
 
+/** Outcome of windowReads on one set of options of windowReadsBatch. */
+export interface WindowReadsEntry {
+  /** Whether the query succeeded. */
+  ok: boolean;
+  /**
+   * Result of windowReads, if ok: the output, or the number of lines written
+   * to its outputPath.
+   */
+  result?: string | number;
+  /** Kind of error, as in the code of a rejected query, if not ok. */
+  code?: ErrorKind;
+  /** Error message, if not ok. */
+  error?: string;
+}
+
+/**
+ * Runs windowReads on each of several sets of options concurrently, as
+ * readInfoBatch does for readInfo.
+ */
+export declare function windowReadsBatch(
+  optionsList: WindowOptions[],
+): Promise<WindowReadsEntry[]>;
+
+/** Window values of windowReadsArrays, in runs of one read and modification. */
+export interface WindowArrays {
+  /** Values of all windows, read by read and along each read. */
+  values: Float64Array;
+  /** Read id of each run of windows. */
+  read_ids: string[];
+  /** Modification code of each run of windows. */
+  mod_types: string[];
+  /**
+   * Index in `values` of the first window of each run, followed by the length
+   * of `values`: run `i` is `values.subarray(offsets[i], offsets[i + 1])`.
+   */
+  offsets: Uint32Array;
+}
+
+/**
+ * Windows the reads as windowReads does, but returns the window values as a
+ * Float64Array without building TSV or JSON text. `outputPath`, `compress` and
+ * `outputFormat` are not supported.
+ */
+export declare function windowReadsArrays(options: WindowOptions): Promise<WindowArrays>;
+
+/** Window values of the reads over one bin of the reference, pooled. */
+export interface WindowBin {
+  contig: string;
+  /** First reference position (0-based) of the bin. */
+  bin_start: number;
+  /** Reference position (0-based) after the bin. */
+  bin_end: number;
+  /** Modification code. */
+  mod_type: string;
+  /** Mean of the window values of the reads over the bin. */
+  mean: number;
+  /** Number of read windows pooled in the bin. */
+  n_reads: number;
+}
+
+/**
+ * Windows each read in reference coordinates, as windowReads does with
+ * `coordinate: 'reference'`, and pools the window values of all reads per bin
+ * of `win` bases starting at multiples of `step`. `outputPath`, `compress`,
+ * `outputFormat` and `coordinate: 'read'` are not supported.
+ */
+export declare function windowAggregate(options: WindowOptions): Promise<WindowBin[]>;
+
+/** Window values of windowMatrix, one row per read and modification. */
+export interface WindowMatrix {
+  /** Read id of each row. */
+  read_ids: string[];
+  /** Modification code of each row. */
+  mod_types: string[];
+  /** First reference position (0-based) of the window of each column. */
+  positions: number[];
+  /**
+   * Window values row after row: row `i`, column `j` is
+   * `values[i * positions.length + j]`, NaN where the read has no window.
+   */
+  values: Float64Array;
+}
+
+/**
+ * Windows each read in reference coordinates, as windowReads does with
+ * `coordinate: 'reference'`, and returns the window values over `region` as a
+ * matrix of reads by the windows of `win` bases starting at multiples of
+ * `step`, e.g. for a heatmap. `region` is required; `regions`, `regionBed`,
+ * `outputPath`, `compress`, `outputFormat` and `coordinate: 'read'` are not
+ * supported.
+ */
+export declare function windowMatrix(options: WindowOptions): Promise<WindowMatrix>;
+
+/**
+ * Token that lets JavaScript stop a running query.
+ *
+ * Pass the token in the query options and call `cancel()`; the query stops
+ * before its next BAM record and rejects with an error whose message and
+ * `code` are both `'Cancelled'`. A token stays cancelled once tripped, so use
+ * a new token for each query.
+ */
+export declare class CancelToken {
+  constructor();
+  /** Requests cancellation of every query using this token. */
+  cancel(): void;
+  /** Whether `cancel()` has been called. */
+  get isCancelled(): boolean;
+}
+
+/**
+ * Returns the sequence table over `region` as a TSV string. With `regions`
+ * instead, the tables of the regions follow each other, with a first `region`
+ * column giving the region of each row.
+ */
 export declare function seqTable(options: ReadOptions): Promise<string>;
+
+/** Column of the core reads table that seqTable can output. */
+export type SeqTableColumn =
+  | 'read_id'
+  | 'align_length'
+  | 'sequence_length_template'
+  | 'alignment_type'
+  | 'mod_count'
+  | 'sequence'
+  | 'qualities';
+
+/** Sequence and base qualities of one read over the seqTable region. */
+export interface SeqTableRecord {
+  read_id: string;
+  /** Sequence, with "." for deletion, lowercase for insertion, and "Z" (or "z") for modification. */
+  sequence: string;
+  /**
+   * Base qualities separated by ".", with 255 for a deletion. Absent if
+   * showBaseQual is false.
+   */
+  qualities?: string;
+  /** Region of the read, when the table is over several `regions`. */
+  region?: string;
+}
+
+/** Returns the rows of seqTable as objects instead of a TSV string. Does not accept `columns`. */
+export declare function seqTableRecords(options: ReadOptions): Promise<SeqTableRecord[]>;
+
+/** Returns seqTable as a TSV string with every column of the core reads table. Does not accept `columns`. */
+export declare function readsTable(options: ReadOptions): Promise<string>;
+
+/** Aligned bases of one read over the alignedSequences region. */
+export interface AlignedSequence {
+  read_id: string;
+  /** Reference position (0-based) of the first character of `aligned_seq`. */
+  ref_start: number;
+  /** One character per reference position from `ref_start`: the read base, or "-" for a deletion or skipped region. */
+  aligned_seq: string;
+}
+
+/**
+ * Returns the plain aligned bases of each read over `region`, which is required.
+ * Unlike seqTable, modified bases are not marked and insertions are left out, so
+ * each character is one reference position. Reads overlapping only part of the
+ * region are kept unless `fullRegion` is set.
+ */
+export declare function alignedSequences(options: ReadOptions): Promise<AlignedSequence[]>;
+
+/** Base qualities of one read, from qualityProfiles. */
+export interface QualityProfile {
+  read_id: string;
+  /**
+   * Mean of the base qualities in `q`, leaving out deletions. Absent if the
+   * read has no base qualities.
+   */
+  mean_q?: number;
+  /**
+   * Phred quality of each base, in the orientation of the reference. Over a
+   * region, one per character of the seqTable sequence, with 255 for a deletion.
+   */
+  q: number[];
+}
+
+/**
+ * Returns the base qualities of each read as phred values. With `region`, they
+ * are clipped to it so that they line up with the seqTable sequence; reads
+ * overlapping only part of the region are kept unless `fullRegion` is set.
+ * Does not accept `regions` or `regionBed`.
+ */
+export declare function qualityProfiles(options: ReadOptions): Promise<QualityProfile[]>;
+
+/** GC content of one read, from gcContent. */
+export interface GcContent {
+  read_id: string;
+  /**
+   * Fraction of the A, C, G and T bases that are G or C, so that N bases do
+   * not count. Absent if there are none.
+   */
+  gc_fraction?: number;
+  /** Number of bases that are neither A, C, G nor T, such as N. */
+  n_count: number;
+}
+
+/**
+ * Returns the GC content of each read. With `region`, only the bases aligned
+ * in it count, inserted bases included; reads overlapping only part of the
+ * region are kept unless `fullRegion` is set. Does not accept `regions` or
+ * `regionBed`.
+ */
+export declare function gcContent(options: ReadOptions): Promise<GcContent[]>;
+
+// Reusable BAM handle
+export interface NanalogueBamOptions {
+  /** Path to the BAM file (local path or URL). */
+  bamPath: string;
+  /**
+   * If true, treat bamPath as a URL, if false as a file path. If unset, paths
+   * starting with http://, https://, s3://, gs:// or file:// are URLs.
+   * A file:// URL is read as the local file it names.
+   */
+  treatAsUrl?: boolean;
+  /** Reference FASTA used to decode CRAM input, which cannot be read without it. Ignored for BAM input. */
+  referenceFasta?: string;
+  /**
+   * Times to retry opening a URL after a failed attempt (default: 0). The wait
+   * between attempts starts at 100 ms and doubles each time, up to 5 s. Ignored
+   * for local files.
+   */
+  urlRetries?: number;
+  /**
+   * Time limit of each attempt at opening a URL, in milliseconds. Must be at
+   * least 1. Unset means no limit. An attempt that times out is not retried.
+   * Ignored for local files.
+   */
+  urlTimeoutMs?: number;
+  /**
+   * Extra HTTP request headers sent when opening a URL and reading from it, e.g.
+   * `Authorization` or `User-Agent`. Only `http://` and `https://` URLs take
+   * headers; other URLs are rejected. Ignored for local files.
+   */
+  urlHeaders?: Record<string, string>;
+  /**
+   * Build the .bai index of a local, coordinate-sorted BAM file that has none,
+   * with a warning, instead of rejecting with IndexMissing. Defaults to false.
+   */
+  autoIndex?: boolean;
+  /**
+   * Number of threads for BAM reading, shared by all queries. Unset, the
+   * reader uses the pool of initThreadPool if it has been started.
+   */
+  threads?: number;
+}
+
+/** Omits keys from every member of a union type. */
+type DistributiveOmit<T, K extends PropertyKey> = T extends unknown ? Omit<T, K> : never;
+
+/** Keys fixed when constructing a NanalogueBam, which its methods do not accept. */
+type NanalogueBamFixedKeys =
+  | 'bamPath'
+  | 'treatAsUrl'
+  | 'threads'
+  | 'referenceFasta'
+  | 'urlRetries'
+  | 'urlTimeoutMs'
+  | 'urlHeaders'
+  | 'autoIndex';
+
+/** ReadOptions for NanalogueBam methods. */
+export type NanalogueBamReadOptions = DistributiveOmit<ReadOptions, NanalogueBamFixedKeys>;
+
+/** WindowOptions for NanalogueBam methods. */
+export type NanalogueBamWindowOptions = DistributiveOmit<WindowOptions, NanalogueBamFixedKeys>;
+
+/**
+ * A BAM file kept open across queries.
+ *
+ * The file, index, and header are read once by the constructor, which throws if
+ * they cannot be opened. Each query reuses the open reader instead of reopening
+ * the file. Queries on one instance run one at a time.
+ */
+export declare class NanalogueBam {
+  constructor(options: NanalogueBamOptions);
+  readInfo(options?: NanalogueBamReadOptions): Promise<ReadInfoRecord[]>;
+  readInfoJson(options?: NanalogueBamReadOptions): Promise<string>;
+  getRead(
+    options: NanalogueBamReadOptions,
+    readId: string,
+  ): Promise<ReadInfoRecord | null>;
+  countReads(options?: NanalogueBamReadOptions): Promise<number>;
+  peekWithReads(
+    options?: NanalogueBamReadOptions,
+    sampleSize?: number,
+  ): Promise<PeekWithReads>;
+  filterStats(options?: NanalogueBamReadOptions): Promise<FilterStats>;
+  bamMods(options?: NanalogueBamReadOptions): Promise<BamModRecord[]>;
+  bamModsJson(options?: NanalogueBamReadOptions): Promise<string>;
+  bamModsTyped(options?: NanalogueBamReadOptions): Promise<ModCall[]>;
+  modQualHistogram(options?: NanalogueBamReadOptions, bins?: number): Promise<number[]>;
+  suggestThreshold(options?: NanalogueBamReadOptions): Promise<ThresholdSuggestion>;
+  mapqHistogram(options?: NanalogueBamReadOptions): Promise<number[]>;
+  modSummary(options?: NanalogueBamReadOptions): Promise<ModSummary[]>;
+  modCountsByContig(options?: NanalogueBamReadOptions): Promise<ContigModCount[]>;
+  alignStats(options?: NanalogueBamReadOptions): Promise<AlignStats[]>;
+  annotateReads(options: NanalogueBamReadOptions, features: Feature[]): Promise<ReadAnnotation[]>;
+  pileup(options: NanalogueBamReadOptions, pileupOptions?: PileupOptions): Promise<PileupRecord[]>;
+  coverage(options: NanalogueBamReadOptions, binSize?: number): Promise<CoverageRecord[]>;
+  contigSummary(options?: NanalogueBamReadOptions): Promise<ContigSummary[]>;
+  idxstats(): Promise<IdxStat[]>;
+  estimateReadCount(options?: NanalogueBamReadOptions): Promise<number>;
+  flagstat(options?: NanalogueBamReadOptions): Promise<Flagstat>;
+  readGroups(): Promise<string[]>;
+  windowReads(options: NanalogueBamWindowOptions & { outputPath: string }): Promise<number>;
+  windowReads(options: NanalogueBamWindowOptions): Promise<string>;
+  windowReadsArrays(options: NanalogueBamWindowOptions): Promise<WindowArrays>;
+  windowAggregate(options: NanalogueBamWindowOptions): Promise<WindowBin[]>;
+  windowMatrix(options: NanalogueBamWindowOptions): Promise<WindowMatrix>;
+  seqTable(options: NanalogueBamReadOptions): Promise<string>;
+  seqTableRecords(options: NanalogueBamReadOptions): Promise<SeqTableRecord[]>;
+  readsTable(options: NanalogueBamReadOptions): Promise<string>;
+  alignedSequences(options: NanalogueBamReadOptions): Promise<AlignedSequence[]>;
+  qualityProfiles(options: NanalogueBamReadOptions): Promise<QualityProfile[]>;
+  gcContent(options: NanalogueBamReadOptions): Promise<GcContent[]>;
+  exportBam(options: NanalogueBamReadOptions, outputPath: string): Promise<ExportBamResult>;
+  exportFastq(
+    options: NanalogueBamReadOptions,
+    outputPath: string,
+    compress?: boolean,
+  ): Promise<ExportFastqResult>;
+  exportBedgraph(
+    options: NanalogueBamWindowOptions,
+    outputPath: string,
+    skipEmpty?: boolean,
+  ): Promise<number>;
+}
@@ -1,320 +1,71 @@
-/* tslint:disable */
-/* eslint-disable */
-/* prettier-ignore */
+// Entry point of the package. The native bindings are loaded by binding.js,
+// which `napi build` generates; the additions they need on the JS side are made
+// here, so that rebuilding does not drop them.
 
-/* auto-generated by NAPI-RS */
+const binding = require('./binding')
 
-const { existsSync, readFileSync } = require('fs')
-const { join } = require('path')
-
-const { platform, arch } = process
+// Native classes cannot define symbol-keyed methods, so make the stream async-iterable here
+binding.ReadInfoStream.prototype[Symbol.asyncIterator] = function () {
+  return this
+}
 
-let nativeBinding = null
-let localFileExisted = false
-let loadError = null
+// Native errors only carry NAPI status codes, so the Rust side prefixes each
+// message with its ErrorKind as `[Kind] `; move that prefix to `err.code` here
+const ERROR_KIND_PREFIX = /^\[(\w+)\] /
 
-function isMusl() {
-  // For Node 10
-  if (!process.report || typeof process.report.getReport !== 'function') {
-    try {
-      const lddPath = require('child_process').execSync('which ldd').toString().trim()
-      return readFileSync(lddPath, 'utf8').includes('musl')
-    } catch (e) {
-      return true
+function withErrorKind(err) {
+  const match = err instanceof Error ? ERROR_KIND_PREFIX.exec(err.message) : null
+  if (match) {
+    err.code = match[1]
+    err.message = err.message.slice(match[0].length)
+    if (typeof err.stack === 'string') {
+      err.stack = err.stack.replace(match[0], '')
     }
-  } else {
-    const { glibcVersionRuntime } = process.report.getReport().header
-    return !glibcVersionRuntime
   }
+  return err
 }
 
-switch (platform) {
-  case 'android':
-    switch (arch) {
-      case 'arm64':
-        localFileExisted = existsSync(join(__dirname, 'nanalogue.android-arm64.node'))
-        try {
-          if (localFileExisted) {
-            nativeBinding = require('./nanalogue.android-arm64.node')
-          } else {
-            nativeBinding = require('@nanalogue/node-android-arm64')
-          }
-        } catch (e) {
-          loadError = e
-        }
-        break
-      case 'arm':
-        localFileExisted = existsSync(join(__dirname, 'nanalogue.android-arm-eabi.node'))
-        try {
-          if (localFileExisted) {
-            nativeBinding = require('./nanalogue.android-arm-eabi.node')
-          } else {
-            nativeBinding = require('@nanalogue/node-android-arm-eabi')
-          }
-        } catch (e) {
-          loadError = e
-        }
-        break
-      default:
-        throw new Error(`Unsupported architecture on Android ${arch}`)
-    }
-    break
-  case 'win32':
-    switch (arch) {
-      case 'x64':
-        localFileExisted = existsSync(
-          join(__dirname, 'nanalogue.win32-x64-msvc.node')
-        )
-        try {
-          if (localFileExisted) {
-            nativeBinding = require('./nanalogue.win32-x64-msvc.node')
-          } else {
-            nativeBinding = require('@nanalogue/node-win32-x64-msvc')
-          }
-        } catch (e) {
-          loadError = e
-        }
-        break
-      case 'ia32':
-        localFileExisted = existsSync(
-          join(__dirname, 'nanalogue.win32-ia32-msvc.node')
-        )
-        try {
-          if (localFileExisted) {
-            nativeBinding = require('./nanalogue.win32-ia32-msvc.node')
-          } else {
-            nativeBinding = require('@nanalogue/node-win32-ia32-msvc')
-          }
-        } catch (e) {
-          loadError = e
-        }
-        break
-      case 'arm64':
-        localFileExisted = existsSync(
-          join(__dirname, 'nanalogue.win32-arm64-msvc.node')
-        )
-        try {
-          if (localFileExisted) {
-            nativeBinding = require('./nanalogue.win32-arm64-msvc.node')
-          } else {
-            nativeBinding = require('@nanalogue/node-win32-arm64-msvc')
-          }
-        } catch (e) {
-          loadError = e
-        }
-        break
-      default:
-        throw new Error(`Unsupported architecture on Windows: ${arch}`)
-    }
-    break
-  case 'darwin':
-    localFileExisted = existsSync(join(__dirname, 'nanalogue.darwin-universal.node'))
+function rethrowWithErrorKind(err) {
+  throw withErrorKind(err)
+}
+
+function wrapErrors(fn) {
+  return function (...args) {
+    let result
     try {
-      if (localFileExisted) {
-        nativeBinding = require('./nanalogue.darwin-universal.node')
-      } else {
-        nativeBinding = require('@nanalogue/node-darwin-universal')
-      }
-      break
-    } catch {}
-    switch (arch) {
-      case 'x64':
-        localFileExisted = existsSync(join(__dirname, 'nanalogue.darwin-x64.node'))
-        try {
-          if (localFileExisted) {
-            nativeBinding = require('./nanalogue.darwin-x64.node')
-          } else {
-            nativeBinding = require('@nanalogue/node-darwin-x64')
-          }
-        } catch (e) {
-          loadError = e
-        }
-        break
-      case 'arm64':
-        localFileExisted = existsSync(
-          join(__dirname, 'nanalogue.darwin-arm64.node')
-        )
-        try {
-          if (localFileExisted) {
-            nativeBinding = require('./nanalogue.darwin-arm64.node')
-          } else {
-            nativeBinding = require('@nanalogue/node-darwin-arm64')
-          }
-        } catch (e) {
-          loadError = e
-        }
-        break
-      default:
-        throw new Error(`Unsupported architecture on macOS: ${arch}`)
+      result = fn.apply(this, args)
+    } catch (err) {
+      rethrowWithErrorKind(err)
     }
-    break
-  case 'freebsd':
-    if (arch !== 'x64') {
-      throw new Error(`Unsupported architecture on FreeBSD: ${arch}`)
+    return result instanceof Promise ? result.catch(rethrowWithErrorKind) : result
+  }
+}
+
+function wrapClassErrors(cls) {
+  for (const name of Object.getOwnPropertyNames(cls.prototype)) {
+    const { value } = Object.getOwnPropertyDescriptor(cls.prototype, name)
+    if (name !== 'constructor' && typeof value === 'function') {
+      cls.prototype[name] = wrapErrors(value)
     }
-    localFileExisted = existsSync(join(__dirname, 'nanalogue.freebsd-x64.node'))
-    try {
-      if (localFileExisted) {
-        nativeBinding = require('./nanalogue.freebsd-x64.node')
-      } else {
-        nativeBinding = require('@nanalogue/node-freebsd-x64')
+  }
+  return new Proxy(cls, {
+    construct(target, args, newTarget) {
+      try {
+        return Reflect.construct(target, args, newTarget)
+      } catch (err) {
+        rethrowWithErrorKind(err)
       }
-    } catch (e) {
-      loadError = e
-    }
-    break
-  case 'linux':
-    switch (arch) {
-      case 'x64':
-        if (isMusl()) {
-          localFileExisted = existsSync(
-            join(__dirname, 'nanalogue.linux-x64-musl.node')
-          )
-          try {
-            if (localFileExisted) {
-              nativeBinding = require('./nanalogue.linux-x64-musl.node')
-            } else {
-              nativeBinding = require('@nanalogue/node-linux-x64-musl')
-            }
-          } catch (e) {
-            loadError = e
-          }
-        } else {
-          localFileExisted = existsSync(
-            join(__dirname, 'nanalogue.linux-x64-gnu.node')
-          )
-          try {
-            if (localFileExisted) {
-              nativeBinding = require('./nanalogue.linux-x64-gnu.node')
-            } else {
-              nativeBinding = require('@nanalogue/node-linux-x64-gnu')
-            }
-          } catch (e) {
-            loadError = e
-          }
-        }
-        break
-      case 'arm64':
-        if (isMusl()) {
-          localFileExisted = existsSync(
-            join(__dirname, 'nanalogue.linux-arm64-musl.node')
-          )
-          try {
-            if (localFileExisted) {
-              nativeBinding = require('./nanalogue.linux-arm64-musl.node')
-            } else {
-              nativeBinding = require('@nanalogue/node-linux-arm64-musl')
-            }
-          } catch (e) {
-            loadError = e
-          }
-        } else {
-          localFileExisted = existsSync(
-            join(__dirname, 'nanalogue.linux-arm64-gnu.node')
-          )
-          try {
-            if (localFileExisted) {
-              nativeBinding = require('./nanalogue.linux-arm64-gnu.node')
-            } else {
-              nativeBinding = require('@nanalogue/node-linux-arm64-gnu')
-            }
-          } catch (e) {
-            loadError = e
-          }
-        }
-        break
-      case 'arm':
-        if (isMusl()) {
-          localFileExisted = existsSync(
-            join(__dirname, 'nanalogue.linux-arm-musleabihf.node')
-          )
-          try {
-            if (localFileExisted) {
-              nativeBinding = require('./nanalogue.linux-arm-musleabihf.node')
-            } else {
-              nativeBinding = require('@nanalogue/node-linux-arm-musleabihf')
-            }
-          } catch (e) {
-            loadError = e
-          }
-        } else {
-          localFileExisted = existsSync(
-            join(__dirname, 'nanalogue.linux-arm-gnueabihf.node')
-          )
-          try {
-            if (localFileExisted) {
-              nativeBinding = require('./nanalogue.linux-arm-gnueabihf.node')
-            } else {
-              nativeBinding = require('@nanalogue/node-linux-arm-gnueabihf')
-            }
-          } catch (e) {
-            loadError = e
-          }
-        }
-        break
-      case 'riscv64':
-        if (isMusl()) {
-          localFileExisted = existsSync(
-            join(__dirname, 'nanalogue.linux-riscv64-musl.node')
-          )
-          try {
-            if (localFileExisted) {
-              nativeBinding = require('./nanalogue.linux-riscv64-musl.node')
-            } else {
-              nativeBinding = require('@nanalogue/node-linux-riscv64-musl')
-            }
-          } catch (e) {
-            loadError = e
-          }
-        } else {
-          localFileExisted = existsSync(
-            join(__dirname, 'nanalogue.linux-riscv64-gnu.node')
-          )
-          try {
-            if (localFileExisted) {
-              nativeBinding = require('./nanalogue.linux-riscv64-gnu.node')
-            } else {
-              nativeBinding = require('@nanalogue/node-linux-riscv64-gnu')
-            }
-          } catch (e) {
-            loadError = e
-          }
-        }
-        break
-      case 's390x':
-        localFileExisted = existsSync(
-          join(__dirname, 'nanalogue.linux-s390x-gnu.node')
-        )
-        try {
-          if (localFileExisted) {
-            nativeBinding = require('./nanalogue.linux-s390x-gnu.node')
-          } else {
-            nativeBinding = require('@nanalogue/node-linux-s390x-gnu')
-          }
-        } catch (e) {
-          loadError = e
-        }
-        break
-      default:
-        throw new Error(`Unsupported architecture on Linux: ${arch}`)
-    }
-    break
-  default:
-    throw new Error(`Unsupported OS: ${platform}, architecture: ${arch}`)
+    },
+  })
 }
 
-if (!nativeBinding) {
-  if (loadError) {
-    throw loadError
+// Exported functions are camelCase and classes PascalCase; enums are plain objects
+for (const [name, value] of Object.entries(binding)) {
+  if (typeof value !== 'function') {
+    module.exports[name] = value
+  } else if (/^[A-Z]/.test(name)) {
+    module.exports[name] = wrapClassErrors(value)
+  } else {
+    module.exports[name] = wrapErrors(value)
   }
-  throw new Error(`Failed to load native binding`)
 }
-
-const { peek, readInfo, simulateModBam, bamMods, windowReads, seqTable } = nativeBinding
-
-module.exports.peek = peek
-module.exports.readInfo = readInfo
-module.exports.simulateModBam = simulateModBam
-module.exports.bamMods = bamMods
-module.exports.windowReads = windowReads
-module.exports.seqTable = seqTable
@@ -0,0 +1,123 @@
+//! Per-read statistics of the CIGAR string of each alignment.
+
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use crate::progress::{Progress, progress_interval};
+use crate::regions::Regions;
+use crate::{
+    ReadOptions, build_input_options, passes_read_filters, query_reader, validate_pagination,
+};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::IndexedReader;
+use rust_htslib::bam::record::{Cigar, Record};
+
+/// Alignment statistics of one read, from its CIGAR string.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug, Default)]
+pub struct AlignStats {
+    /// Read id.
+    #[napi(js_name = "read_id")]
+    pub read_id: String,
+    /// Number of reference bases the alignment spans, 0 if unmapped.
+    #[napi(js_name = "ref_span")]
+    pub ref_span: u32,
+    /// Soft-clipped bases at the start of the alignment on the reference.
+    #[napi(js_name = "soft_clip_left")]
+    pub soft_clip_left: u32,
+    /// Soft-clipped bases at the end of the alignment on the reference.
+    #[napi(js_name = "soft_clip_right")]
+    pub soft_clip_right: u32,
+    /// Inserted bases.
+    #[napi(js_name = "n_ins")]
+    pub n_ins: u32,
+    /// Deleted bases, not counting skipped regions such as introns.
+    #[napi(js_name = "n_del")]
+    pub n_del: u32,
+    /// Bases aligned to the reference, whether they match it or not.
+    #[napi(js_name = "n_match")]
+    pub n_match: u32,
+}
+
+impl AlignStats {
+    /// Walks the CIGAR string of `record`.
+    fn from_record(record: &Record) -> Self {
+        let mut stats = Self {
+            read_id: String::from_utf8_lossy(record.qname()).into_owned(),
+            ..Self::default()
+        };
+        let cigar = record.cigar();
+        // Hard clips are outside the read, so soft clips are the outermost
+        // operations that are not hard clips
+        let is_hard_clip = |v: &&Cigar| matches!(**v, Cigar::HardClip(_));
+        let first = cigar.iter().position(|v| !is_hard_clip(&v));
+        let last = cigar.iter().rposition(|v| !is_hard_clip(&v));
+        for (i, op) in cigar.iter().enumerate() {
+            match *op {
+                Cigar::Match(v) | Cigar::Equal(v) | Cigar::Diff(v) => {
+                    stats.n_match = stats.n_match.saturating_add(v);
+                    stats.ref_span = stats.ref_span.saturating_add(v);
+                }
+                Cigar::Ins(v) => stats.n_ins = stats.n_ins.saturating_add(v),
+                Cigar::Del(v) => {
+                    stats.n_del = stats.n_del.saturating_add(v);
+                    stats.ref_span = stats.ref_span.saturating_add(v);
+                }
+                Cigar::RefSkip(v) => stats.ref_span = stats.ref_span.saturating_add(v),
+                Cigar::SoftClip(v) => {
+                    if first == Some(i) {
+                        stats.soft_clip_left = v;
+                    }
+                    if last == Some(i) && first != last {
+                        stats.soft_clip_right = v;
+                    }
+                }
+                Cigar::HardClip(_) | Cigar::Pad(_) => {}
+            }
+        }
+        stats
+    }
+}
+
+/// Walks the CIGAR string of each read to give its alignment statistics.
+///
+/// Returns one entry per read that `read_info` returns for the same options, in
+/// the same order, so read filters apply and `offset` and `limit` count reads.
+/// Left and right are along the reference, whatever the strand of the read.
+///
+/// # Errors
+/// Returns an error if input options are invalid or BAM reading fails.
+#[napi]
+pub async fn align_stats(options: ReadOptions) -> Result<Vec<AlignStats>> {
+    run_limited(move || align_stats_sync(&options, None)).await
+}
+
+/// Synchronous implementation of `align_stats`.
+pub(crate) fn align_stats_sync(
+    options: &ReadOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<Vec<AlignStats>> {
+    let (offset, limit) = validate_pagination(options)?;
+    let interval = progress_interval(options.progress_interval)?;
+    let (mut bam, mut mods, extra) = build_input_options(options)?;
+    let regions = Regions::from_options(options)?;
+
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, _) = reader.records(&mut bam, &mut mods, regions.as_ref())?;
+
+    let filtered = Progress::new(rc_records, options.on_progress.clone(), interval).filter(|r| {
+        r.as_ref()
+            .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    filtered
+        .skip(offset)
+        .take(limit)
+        .map(|r| {
+            let record = r.map_err(|e| {
+                ErrorKind::of_htslib(&e).error(format!("Failed to read BAM record: {e}"))
+            })?;
+            Ok(AlignStats::from_record(&record))
+        })
+        .collect()
+}
@@ -0,0 +1,152 @@
+//! Aligned bases of each read over a region, for alignment viewers.
+
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use crate::progress::{Progress, progress_interval};
+use crate::regions::core_region;
+use crate::{
+    ReadOptions, build_input_options, passes_read_filters, query_reader, validate_pagination,
+};
+use bedrs::{Bed3, Coordinates as _};
+use nanalogue_core::{CurrRead, Error as CoreError, GenomicRegion};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::{IndexedReader, Record};
+use std::str::FromStr as _;
+
+/// Bases of one read over the region of `aligned_sequences`.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct AlignedSequence {
+    /// Read id.
+    #[napi(js_name = "read_id")]
+    pub read_id: String,
+    /// Reference position (0-based) of the first character of `aligned_seq`.
+    #[napi(js_name = "ref_start")]
+    pub ref_start: i64,
+    /// One character per reference position from `ref_start`: the read base
+    /// aligned there, or `-` for a deletion or skipped region.
+    #[napi(js_name = "aligned_seq")]
+    pub aligned_seq: String,
+}
+
+impl AlignedSequence {
+    /// Builds the aligned bases of `record` over `region`.
+    ///
+    /// Returns `None` for a record without sequence or without aligned bases in
+    /// the region, such as an unmapped read.
+    ///
+    /// # Errors
+    /// Returns an error if the alignment of the record is invalid.
+    fn from_record(record: &Record, region: &Bed3<i32, u64>) -> Result<Option<Self>> {
+        let failed =
+            |e: CoreError| ErrorKind::of_core(&e).error(format!("aligned_sequences failed: {e}"));
+        let seq = record.seq().as_bytes();
+        if seq.is_empty() || record.is_unmapped() {
+            return Ok(None);
+        }
+        let read = CurrRead::default()
+            .try_from_only_alignment(record)
+            .map_err(failed)?;
+        let coord_map = match read.seq_coords_from_ref_coords(record, region) {
+            Ok(v) => v,
+            Err(CoreError::UnavailableData(_)) => return Ok(None),
+            Err(e) => return Err(failed(e)),
+        };
+
+        // Inserted bases have no reference position, so they are left out
+        let mut aligned_seq = String::with_capacity(coord_map.len());
+        for entry in coord_map {
+            match entry {
+                Some((true, i)) => {
+                    let &base = seq.get(i).ok_or_else(|| {
+                        ErrorKind::Internal.error(format!(
+                            "aligned_sequences failed: position {i} is outside read {}",
+                            read.read_id()
+                        ))
+                    })?;
+                    aligned_seq.push(char::from(base));
+                }
+                Some((false, _)) => {}
+                None => aligned_seq.push('-'),
+            }
+        }
+        if aligned_seq.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            read_id: read.read_id().to_owned(),
+            ref_start: record.pos().max(i64::try_from(region.start()).map_err(|e| {
+                ErrorKind::Internal.error(format!("aligned_sequences failed: {e}"))
+            })?),
+            aligned_seq,
+        }))
+    }
+}
+
+/// Returns the aligned bases of each read over a region.
+///
+/// Requires the `region` parameter to be set. Unlike [`crate::seq_table`],
+/// bases are given as they are, without `Z` for modified bases, and insertions
+/// are left out so that each character is one reference position. Reads that
+/// only overlap part of the region are kept, starting at `ref_start`, unless
+/// `full_region` is set. Reads without aligned bases in the region are left out
+/// after `offset` and `limit` are applied.
+///
+/// # Errors
+/// Returns an error if region is missing, input options are invalid or BAM
+/// reading fails.
+#[napi]
+pub async fn aligned_sequences(options: ReadOptions) -> Result<Vec<AlignedSequence>> {
+    run_limited(move || aligned_sequences_sync(&options, None)).await
+}
+
+/// Synchronous implementation of `aligned_sequences`.
+pub(crate) fn aligned_sequences_sync(
+    options: &ReadOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<Vec<AlignedSequence>> {
+    let (offset, limit) = validate_pagination(options)?;
+    let interval = progress_interval(options.progress_interval)?;
+
+    if options.regions.is_some() || options.region_bed.is_some() {
+        return Err(ErrorKind::InvalidOptions.error(
+            "alignedSequences takes a single region; regions and regionBed are not supported",
+        ));
+    }
+    let region_str = options
+        .region
+        .as_deref()
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| {
+            ErrorKind::InvalidOptions
+                .error("region parameter is required for aligned_sequences (cannot be empty)")
+        })?;
+
+    let (mut bam, mut mods, extra) = build_input_options(options)?;
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, header) = reader.records(&mut bam, &mut mods, None)?;
+
+    let region_bed3 = GenomicRegion::from_str(&core_region(region_str, options.one_based)?)
+        .map_err(|e| ErrorKind::InvalidRegion.error(format!("Invalid region: {e}")))?
+        .try_to_bed3(&header)
+        .map_err(|e| {
+            ErrorKind::InvalidRegion.error(format!("Failed to convert region to bed3: {e}"))
+        })?;
+
+    let filtered = Progress::new(rc_records, options.on_progress.clone(), interval).filter(|r| {
+        r.as_ref()
+            .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    let mut sequences = Vec::new();
+    for entry in filtered.skip(offset).take(limit) {
+        let record = entry.map_err(|e| {
+            ErrorKind::of_htslib(&e).error(format!("aligned_sequences failed: {e}"))
+        })?;
+        if let Some(v) = AlignedSequence::from_record(&record, &region_bed3)? {
+            sequences.push(v);
+        }
+    }
+    Ok(sequences)
+}
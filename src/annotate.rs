@@ -0,0 +1,165 @@
+//! Features of interest, such as genes or target regions, that each read overlaps.
+
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use crate::progress::{Progress, progress_interval};
+use crate::regions::Regions;
+use crate::{
+    ReadOptions, build_input_options, passes_read_filters, query_reader, validate_pagination,
+};
+use bedrs::types::Query;
+use bedrs::{Bed3, Bed4, IntervalContainer};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::{HeaderView, IndexedReader, Record};
+
+/// Named interval of a contig, as in a BED file, given to `annotate_reads`.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub struct Feature {
+    /// Contig name, as in the BAM header.
+    pub contig: String,
+    /// 0-based start of the feature.
+    pub start: i64,
+    /// 0-based exclusive end of the feature, after `start`.
+    pub end: i64,
+    /// Name of the feature, given back for the reads that overlap it.
+    pub name: String,
+}
+
+/// Features one read overlaps, from `annotate_reads`.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct ReadAnnotation {
+    /// Read id.
+    #[napi(js_name = "read_id")]
+    pub read_id: String,
+    /// Names of the features the alignment of the read overlaps by at least one
+    /// base, in the order the features were given. Empty for an unmapped read.
+    pub overlapping: Vec<String>,
+}
+
+/// Features indexed by contig and start, each holding its position in the
+/// list given, so that the features overlapping a read are found by a binary
+/// search rather than by comparing every feature.
+type FeatureIndex = IntervalContainer<Bed4<i32, u64, usize>, i32, u64>;
+
+/// Builds the index of `features` over the contigs of `header`.
+///
+/// # Errors
+/// Returns an error if a feature is on a contig that is not in `header`, or
+/// its start is negative or not before its end.
+fn feature_index(features: &[Feature], header: &HeaderView) -> Result<FeatureIndex> {
+    let mut records = Vec::with_capacity(features.len());
+    for (i, feature) in features.iter().enumerate() {
+        let invalid = |reason: &str| {
+            ErrorKind::InvalidRegion.error(format!(
+                "Invalid feature '{}' ({}:{}-{}): {reason}",
+                feature.name, feature.contig, feature.start, feature.end
+            ))
+        };
+        let tid = header
+            .tid(feature.contig.as_bytes())
+            .and_then(|v| i32::try_from(v).ok())
+            .ok_or_else(|| invalid("the contig is not in the BAM header"))?;
+        let (Ok(start), Ok(end)) = (u64::try_from(feature.start), u64::try_from(feature.end))
+        else {
+            return Err(invalid("coordinates must not be negative"));
+        };
+        if start >= end {
+            return Err(invalid("start must be less than end"));
+        }
+        records.push(Bed4::new(tid, start, end, i));
+    }
+    Ok(IntervalContainer::from_unsorted(records))
+}
+
+/// Names of the features of `index` that the alignment of `record` overlaps,
+/// in the order of `features`.
+///
+/// # Errors
+/// Returns an error if the search of the index fails.
+fn overlapping_features(
+    record: &Record,
+    index: &FeatureIndex,
+    features: &[Feature],
+) -> Result<Vec<String>> {
+    let (Ok(start), Ok(end)) = (
+        u64::try_from(record.pos()),
+        u64::try_from(record.cigar().end_pos()),
+    ) else {
+        return Ok(Vec::new());
+    };
+    // An index without features cannot be searched
+    if record.is_unmapped() || start >= end || features.is_empty() {
+        return Ok(Vec::new());
+    }
+    let span = Bed3::new(record.tid(), start, end);
+    let mut found: Vec<usize> = index
+        .query_iter(&span, Query::default())
+        .map_err(|e| ErrorKind::Internal.error(format!("annotate_reads failed: {e}")))?
+        .map(|v| *v.name())
+        .collect();
+    found.sort_unstable();
+    Ok(found
+        .into_iter()
+        .filter_map(|i| features.get(i).map(|v| v.name.clone()))
+        .collect())
+}
+
+/// Lists the features that the alignment of each read overlaps.
+///
+/// Returns one entry per read that `read_info` returns for the same options, in
+/// the same order, so read filters apply and `offset` and `limit` count reads.
+/// A read overlaps a feature if its reference span, from the first to the last
+/// aligned base, shares at least one base with it, so a feature inside a
+/// deletion or intron of the read counts. The features are indexed once, so
+/// the search for each read takes time in the logarithm of their number rather
+/// than in proportion to it.
+///
+/// # Errors
+/// Returns an error if a feature is invalid or on a contig that is not in the
+/// BAM header, input options are invalid, or BAM reading fails.
+#[napi]
+pub async fn annotate_reads(
+    options: ReadOptions,
+    features: Vec<Feature>,
+) -> Result<Vec<ReadAnnotation>> {
+    run_limited(move || annotate_reads_sync(&options, &features, None)).await
+}
+
+/// Synchronous implementation of `annotate_reads`.
+pub(crate) fn annotate_reads_sync(
+    options: &ReadOptions,
+    features: &[Feature],
+    shared: Option<&mut IndexedReader>,
+) -> Result<Vec<ReadAnnotation>> {
+    let (offset, limit) = validate_pagination(options)?;
+    let interval = progress_interval(options.progress_interval)?;
+    let (mut bam, mut mods, extra) = build_input_options(options)?;
+    let regions = Regions::from_options(options)?;
+
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, header) = reader.records(&mut bam, &mut mods, regions.as_ref())?;
+    let index = feature_index(features, &header)?;
+
+    let filtered = Progress::new(rc_records, options.on_progress.clone(), interval).filter(|r| {
+        r.as_ref()
+            .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    filtered
+        .skip(offset)
+        .take(limit)
+        .map(|r| {
+            let record = r.map_err(|e| {
+                ErrorKind::of_htslib(&e).error(format!("Failed to read BAM record: {e}"))
+            })?;
+            Ok(ReadAnnotation {
+                read_id: String::from_utf8_lossy(record.qname()).into_owned(),
+                overlapping: overlapping_features(&record, &index, features)?,
+            })
+        })
+        .collect()
+}
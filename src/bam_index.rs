@@ -0,0 +1,111 @@
+//! Building `.bai` indexes for BAM files.
+
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::{Read as _, Reader, Record, index};
+use rust_htslib::errors::Error as HtsError;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Creates a `.bai` index next to a coordinate-sorted BAM file.
+///
+/// The other functions read local files through their index, so a BAM file
+/// without one must be indexed before it can be queried.
+///
+/// # Errors
+/// Returns an error if the file cannot be opened, is not sorted by coordinate,
+/// or the index cannot be written.
+#[napi]
+pub async fn build_index(bam_path: String) -> Result<()> {
+    run_limited(move || build_bai(&bam_path, 1)).await
+}
+
+/// Writes `<bam_path>.bai`, explaining the failure if the file is not sorted by coordinate.
+pub(crate) fn build_bai(bam_path: &str, threads: u32) -> Result<()> {
+    index::build(bam_path, None, index::Type::Bai, threads).map_err(|e| {
+        if matches!(e, HtsError::BamBuildIndex) && !is_coordinate_sorted(bam_path) {
+            ErrorKind::InvalidData.error(format!(
+                "Cannot index BAM '{bam_path}': records are not sorted by coordinate; sort it first, e.g. with `samtools sort`"
+            ))
+        } else if matches!(e, HtsError::BamNotIndexable) {
+            ErrorKind::InvalidData.error(format!(
+                "Cannot index '{bam_path}': only BAM files can be indexed"
+            ))
+        } else {
+            ErrorKind::IoError.error(format!("Failed to index BAM '{bam_path}': {e}"))
+        }
+    })
+}
+
+/// Whether an index file that htslib looks for sits next to `bam_path`: a
+/// `.bai`, `.csi` or `.crai` file named after it, or a `.bai` file in place of
+/// its extension.
+pub(crate) fn has_index_file(bam_path: &Path) -> bool {
+    let mut candidates: Vec<PathBuf> = ["bai", "csi", "crai"]
+        .iter()
+        .map(|v| {
+            let mut name = bam_path.as_os_str().to_owned();
+            name.push(".");
+            name.push(v);
+            PathBuf::from(name)
+        })
+        .collect();
+    candidates.push(bam_path.with_extension("bai"));
+    candidates.iter().any(|v| v.exists())
+}
+
+/// Tracks whether records arrive in an order that a `.bai` index accepts.
+///
+/// As in htslib, the reads of each contig must form one block sorted by position,
+/// and reads without a reference id must all come at the end.
+#[derive(Debug, Default)]
+pub(crate) struct CoordinateOrder {
+    /// Contigs whose block of reads has ended.
+    finished_tids: HashSet<i32>,
+    /// Reference id and position of the previous record.
+    last: Option<(i32, i64)>,
+    /// Set once a record is out of order.
+    unsorted: bool,
+}
+
+impl CoordinateOrder {
+    /// Takes the next record into account.
+    pub(crate) fn push(&mut self, record: &Record) {
+        let (tid, pos) = (record.tid(), record.pos());
+        let in_order = match self.last {
+            None => true,
+            Some((last_tid, last_pos)) if last_tid == tid => tid < 0 || pos >= last_pos,
+            Some((last_tid, _)) => {
+                let _: bool = self.finished_tids.insert(last_tid);
+                last_tid >= 0 && !self.finished_tids.contains(&tid)
+            }
+        };
+        self.unsorted |= !in_order;
+        self.last = Some((tid, pos));
+    }
+
+    /// Whether every record so far was in order.
+    pub(crate) fn is_sorted(&self) -> bool {
+        !self.unsorted
+    }
+}
+
+/// Checks whether the records of a BAM file come in coordinate order.
+///
+/// Returns true if the file cannot be read, so that the caller's original error
+/// is reported instead.
+fn is_coordinate_sorted(bam_path: &str) -> bool {
+    let Ok(mut reader) = Reader::from_path(bam_path) else {
+        return true;
+    };
+    let mut order = CoordinateOrder::default();
+    for entry in reader.records() {
+        let Ok(record) = entry else {
+            return true;
+        };
+        order.push(&record);
+    }
+    order.is_sorted()
+}
@@ -0,0 +1,182 @@
+//! Several queries of one kind in a single call, with an outcome per query.
+
+use crate::error::ErrorKind;
+use crate::records::ReadInfoRecord;
+use crate::{PeekOptions, PeekResult, ReadOptions, WindowOptions, peek, read_info, window_reads};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+/// Outcome of `read_info()` on one set of options of `read_info_batch()`.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug, Default)]
+pub struct ReadInfoEntry {
+    /// Whether the query succeeded.
+    pub ok: bool,
+    /// Result of `read_info()`, if `ok`.
+    pub result: Option<Vec<ReadInfoRecord>>,
+    /// Kind of error, as in the `code` of a rejected query, if not `ok`.
+    pub code: Option<String>,
+    /// Error message, if not `ok`.
+    pub error: Option<String>,
+}
+
+impl From<Result<Vec<ReadInfoRecord>>> for ReadInfoEntry {
+    fn from(outcome: Result<Vec<ReadInfoRecord>>) -> Self {
+        let (result, code, error) = split_outcome(outcome);
+        Self {
+            ok: result.is_some(),
+            result,
+            code,
+            error,
+        }
+    }
+}
+
+/// Outcome of `window_reads()` on one set of options of `window_reads_batch()`.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug, Default)]
+pub struct WindowReadsEntry {
+    /// Whether the query succeeded.
+    pub ok: bool,
+    /// Result of `window_reads()`, if `ok`: the output, or the number of lines
+    /// written to its `output_path`.
+    pub result: Option<Either<String, i64>>,
+    /// Kind of error, as in the `code` of a rejected query, if not `ok`.
+    pub code: Option<String>,
+    /// Error message, if not `ok`.
+    pub error: Option<String>,
+}
+
+impl From<Result<Either<String, i64>>> for WindowReadsEntry {
+    fn from(outcome: Result<Either<String, i64>>) -> Self {
+        let (result, code, error) = split_outcome(outcome);
+        Self {
+            ok: result.is_some(),
+            result,
+            code,
+            error,
+        }
+    }
+}
+
+/// Outcome of `peek()` on one file of `peek_many()`.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug, Default)]
+pub struct PeekEntry {
+    /// Path or URL of the file, as given.
+    #[napi(js_name = "bam_path")]
+    pub bam_path: String,
+    /// Whether the file could be peeked at.
+    pub ok: bool,
+    /// Result of `peek()`, if `ok`.
+    pub result: Option<PeekResult>,
+    /// Kind of error, as in the `code` of a rejected query, if not `ok`.
+    pub code: Option<String>,
+    /// Error message, if not `ok`.
+    pub error: Option<String>,
+}
+
+impl PeekEntry {
+    /// The entry of the file at `bam_path`, given the outcome of peeking at it.
+    fn new(bam_path: String, outcome: Result<PeekResult>) -> Self {
+        let (result, code, error) = split_outcome(outcome);
+        Self {
+            bam_path,
+            ok: result.is_some(),
+            result,
+            code,
+            error,
+        }
+    }
+}
+
+/// Splits `outcome` into its value, or the kind and message of its error.
+fn split_outcome<T>(outcome: Result<T>) -> (Option<T>, Option<String>, Option<String>) {
+    match outcome {
+        Ok(v) => (Some(v), None, None),
+        Err(e) => {
+            let (code, error) = ErrorKind::split(&e);
+            (None, code, Some(error))
+        }
+    }
+}
+
+/// Runs `query` on each of `options_list` concurrently and returns the
+/// outcomes in the same order.
+///
+/// # Errors
+/// Returns an error only if a worker thread fails.
+async fn run_batch<O, T, F, Fut>(options_list: Vec<O>, query: F) -> Result<Vec<Result<T>>>
+where
+    F: Fn(O) -> Fut,
+    Fut: Future<Output = Result<T>> + Send + 'static,
+    T: Send + 'static,
+{
+    let tasks: Vec<_> = options_list
+        .into_iter()
+        .map(|v| tokio::spawn(query(v)))
+        .collect();
+    let mut outcomes = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        outcomes.push(
+            task.await
+                .map_err(|e| ErrorKind::Internal.error(format!("Task join error: {e}")))?,
+        );
+    }
+    Ok(outcomes)
+}
+
+/// Runs `read_info()` on each of several sets of options in one call, e.g. for
+/// the tracks of a view.
+///
+/// The queries run concurrently, up to the limit of
+/// [`crate::set_max_concurrency`], and the entries are in the order of
+/// `options_list`. A query that fails gives an entry with its error instead of
+/// failing the whole batch.
+///
+/// # Errors
+/// Returns an error only if a worker thread fails.
+#[napi]
+pub async fn read_info_batch(options_list: Vec<ReadOptions>) -> Result<Vec<ReadInfoEntry>> {
+    let outcomes = run_batch(options_list, read_info).await?;
+    Ok(outcomes.into_iter().map(Into::into).collect())
+}
+
+/// Runs `window_reads()` on each of several sets of options in one call, as
+/// [`read_info_batch`] does for `read_info()`.
+///
+/// # Errors
+/// Returns an error only if a worker thread fails.
+#[napi]
+pub async fn window_reads_batch(options_list: Vec<WindowOptions>) -> Result<Vec<WindowReadsEntry>> {
+    let outcomes = run_batch(options_list, window_reads).await?;
+    Ok(outcomes.into_iter().map(Into::into).collect())
+}
+
+/// Peeks at several BAM files in one call, as [`read_info_batch`] runs
+/// `read_info()`.
+///
+/// A file that cannot be read gives an entry with its error instead of failing
+/// the whole batch.
+///
+/// # Errors
+/// Returns an error only if a worker thread fails.
+#[napi]
+pub async fn peek_many(paths: Vec<String>, treat_as_url: Option<bool>) -> Result<Vec<PeekEntry>> {
+    let outcomes = run_batch(paths.clone(), |bam_path| {
+        peek(PeekOptions {
+            bam_path,
+            treat_as_url,
+            ..PeekOptions::default()
+        })
+    })
+    .await?;
+    Ok(paths
+        .into_iter()
+        .zip(outcomes)
+        .map(|(bam_path, outcome)| PeekEntry::new(bam_path, outcome))
+        .collect())
+}
@@ -0,0 +1,225 @@
+//! Mean modification density of genomic bins written as a bedGraph file, for
+//! genome browsers such as IGV or the UCSC browser.
+
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use crate::regions::core_region;
+use crate::window_aggregate::pool_windows;
+use crate::{WindowCoordinate, WindowOptions, is_url, with_window_records};
+use bedrs::Coordinates as _;
+use nanalogue_core::{Error as CoreError, GenomicRegion};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::{HeaderView, IndexedReader};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::str::FromStr as _;
+
+/// Stretch of a contig whose bins are written.
+#[derive(Debug)]
+struct Span {
+    /// Contig name.
+    contig: String,
+    /// First position (0-based) of the stretch.
+    start: i64,
+    /// Position (0-based) after the stretch.
+    end: i64,
+    /// Length of the contig, where its last bin ends.
+    length: i64,
+}
+
+/// Windows each read in reference coordinates, as `window_aggregate` does, and
+/// writes the mean window value of each bin to `output_path` as a bedGraph.
+///
+/// Each line is `contig start end value` with 0-based, half-open coordinates
+/// as bedGraph uses, so a 1-based `region` is converted on the way in and bins
+/// are written as they are. Bins are the `win` bases starting at multiples of
+/// `win`, so `step` must equal `win` as bedGraph intervals do not overlap, and
+/// the last bin of a contig ends at the contig end. With `region` only the bins
+/// overlapping it are written, otherwise those of every contig in header order.
+/// Bins no read window covers are left out unless `skip_empty` is false, in
+/// which case they are written with a value of 0. As a bedGraph holds one value
+/// per bin, the calls must be of one modification, e.g. chosen with `tag`.
+///
+/// # Errors
+/// Returns an error if `step` differs from `win`, `regions` or `region_bed` or
+/// options of the `window_reads` output are set, the calls are of more than one
+/// modification, or reading or writing fails.
+#[napi]
+pub async fn export_bedgraph(
+    options: WindowOptions,
+    output_path: String,
+    skip_empty: Option<bool>,
+) -> Result<i64> {
+    run_limited(move || {
+        export_bedgraph_sync(&options, &output_path, skip_empty.unwrap_or(true), None)
+    })
+    .await
+}
+
+/// Synchronous implementation of `export_bedgraph`.
+pub(crate) fn export_bedgraph_sync(
+    options: &WindowOptions,
+    output_path: &str,
+    skip_empty: bool,
+    shared: Option<&mut IndexedReader>,
+) -> Result<i64> {
+    if options.output_path.is_some()
+        || options.compress.is_some()
+        || options.output_format.is_some()
+    {
+        return Err(ErrorKind::InvalidOptions.error(
+            "exportBedgraph writes to its outputPath argument; the outputPath, compress and outputFormat options are not supported",
+        ));
+    }
+    if let Some(WindowCoordinate::Read) = options.coordinate {
+        return Err(ErrorKind::InvalidOptions.error(
+            "exportBedgraph windows reads in reference coordinates; coordinate must be 'reference'",
+        ));
+    }
+    if options.step != options.win {
+        return Err(ErrorKind::InvalidOptions.error(
+            "exportBedgraph needs step equal to win, as bedGraph intervals cannot overlap",
+        ));
+    }
+    if options.regions.is_some() || options.region_bed.is_some() {
+        return Err(ErrorKind::InvalidOptions.error(
+            "exportBedgraph takes a single region; regions and regionBed are not supported",
+        ));
+    }
+    if options.bam_path == output_path && !is_url(&options.bam_path, options.treat_as_url) {
+        return Err(ErrorKind::InvalidOptions
+            .error("outputPath must differ from bamPath, which is being read"));
+    }
+    let region = options
+        .region
+        .as_deref()
+        .filter(|v| !v.is_empty())
+        .map(|v| core_region(v, options.one_based).map(Cow::into_owned))
+        .transpose()?;
+
+    let (bins, spans) = with_window_records(
+        options,
+        shared,
+        "export_bedgraph",
+        |records, window_options, mods, extra, header| {
+            let bins = pool_windows(
+                records,
+                &window_options,
+                mods,
+                extra.mod_selection(),
+                options,
+            )?;
+            Ok((bins, spans_of(region.as_deref(), header)?))
+        },
+    )?;
+    let mut mod_types: Vec<&str> = bins.iter().map(|v| v.mod_type.as_str()).collect();
+    mod_types.sort_unstable();
+    mod_types.dedup();
+    if mod_types.len() > 1 {
+        return Err(ErrorKind::InvalidOptions.error(format!(
+            "exportBedgraph writes one modification, but the calls are of {}; choose one with tag",
+            mod_types.join(", ")
+        )));
+    }
+    let means: HashMap<(&str, i64), f64> = bins
+        .iter()
+        .map(|v| ((v.contig.as_str(), v.bin_start), v.mean))
+        .collect();
+
+    let file = File::create(output_path).map_err(|e| {
+        ErrorKind::IoError.error(format!(
+            "Failed to create bedGraph file '{output_path}': {e}"
+        ))
+    })?;
+    let mut writer = BufWriter::new(file);
+    let result = write_bedgraph(
+        &mut writer,
+        &spans,
+        &means,
+        i64::from(options.win),
+        skip_empty,
+    )
+    .and_then(|written| writer.flush().map(|()| written));
+    match result {
+        Ok(written) => Ok(i64::try_from(written).unwrap_or(i64::MAX)),
+        // Leave no partial output behind. A file that cannot be removed is left
+        // in place, as the original error matters more.
+        Err(e) => match fs::remove_file(output_path) {
+            Ok(()) | Err(_) => Err(ErrorKind::IoError.error(format!(
+                "Failed to write bedGraph file '{output_path}': {e}"
+            ))),
+        },
+    }
+}
+
+/// Stretches of the contigs of `header` whose bins are written: `region` if
+/// given, otherwise every contig.
+fn spans_of(
+    region: Option<&str>,
+    header: &HeaderView,
+) -> std::result::Result<Vec<Span>, CoreError> {
+    let contig = |tid: u32| -> std::result::Result<(String, i64), CoreError> {
+        let length = header.target_len(tid).unwrap_or(0);
+        Ok((
+            String::from_utf8_lossy(header.tid2name(tid)).into_owned(),
+            i64::try_from(length)?,
+        ))
+    };
+    match region {
+        Some(v) => {
+            let bed3 = GenomicRegion::from_str(v)?.try_to_bed3(header)?;
+            let (name, length) = contig(u32::try_from(*bed3.chr())?)?;
+            Ok(vec![Span {
+                contig: name,
+                start: i64::try_from(bed3.start())?,
+                end: i64::try_from(bed3.end())?.min(length),
+                length,
+            }])
+        }
+        None => (0..header.target_count())
+            .map(|tid| {
+                contig(tid).map(|(name, length)| Span {
+                    contig: name,
+                    start: 0,
+                    end: length,
+                    length,
+                })
+            })
+            .collect(),
+    }
+}
+
+/// Writes the bins of `win` bases over `spans` to `writer` as bedGraph lines,
+/// with the mean of each bin from `means`, returning how many were written.
+fn write_bedgraph<W: Write>(
+    writer: &mut W,
+    spans: &[Span],
+    means: &HashMap<(&str, i64), f64>,
+    win: i64,
+    skip_empty: bool,
+) -> std::io::Result<usize> {
+    let step = usize::try_from(win).unwrap_or(usize::MAX);
+    let mut written: usize = 0;
+    for span in spans {
+        // Bins start at multiples of `win`, so the first one holds `start`
+        let first = span.start.checked_div(win).unwrap_or(0).saturating_mul(win);
+        for bin_start in (first..span.end).step_by(step) {
+            let value = means.get(&(span.contig.as_str(), bin_start)).copied();
+            if value.is_none() && skip_empty {
+                continue;
+            }
+            let bin_end = bin_start.saturating_add(win).min(span.length);
+            writeln!(
+                writer,
+                "{}\t{bin_start}\t{bin_end}\t{}",
+                span.contig,
+                value.unwrap_or(0.0)
+            )?;
+            written = written.saturating_add(1);
+        }
+    }
+    Ok(written)
+}
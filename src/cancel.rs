@@ -0,0 +1,101 @@
+//! Cooperative cancellation of queries running on blocking threads.
+
+use crate::error::ErrorKind;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Token that lets JavaScript stop a running query.
+///
+/// Pass the token in the query options and call `cancel()` from JS; the query
+/// stops before its next BAM record and rejects with a `Cancelled` error.
+/// A token stays cancelled once tripped, so use a new token for each query.
+#[napi]
+#[derive(Debug, Default)]
+pub struct CancelToken {
+    /// Flag shared with the queries the token was passed to.
+    cancelled: Arc<AtomicBool>,
+}
+
+#[napi]
+impl CancelToken {
+    /// Creates a token that is not cancelled.
+    #[napi(constructor)]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests cancellation of every query using this token.
+    #[napi]
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether `cancel()` has been called.
+    #[napi(getter)]
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// The flag of a [`CancelToken`] as received in query options.
+#[derive(Debug, Clone)]
+pub struct CancelFlag(Arc<AtomicBool>);
+
+impl CancelFlag {
+    /// Whether the token has been cancelled.
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Returns an error if the token has been cancelled.
+    pub(crate) fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(ErrorKind::Cancelled.error("Cancelled"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl FromNapiValue for CancelFlag {
+    unsafe fn from_napi_value(env: sys::napi_env, napi_val: sys::napi_value) -> Result<Self> {
+        // Unwrapping an object of another class as a `CancelToken` is undefined
+        // behaviour, so the class is checked first.
+        let _: sys::napi_value = <&CancelToken as ValidateNapiValue>::validate(env, napi_val)?;
+        let token = <&CancelToken as FromNapiValue>::from_napi_value(env, napi_val)?;
+        Ok(Self(Arc::clone(&token.cancelled)))
+    }
+}
+
+/// Iterator adapter that ends early once `flag` is cancelled.
+///
+/// Callers should [`CancelFlag::check`] after consuming the iterator to tell a
+/// cancelled run from one that finished normally.
+pub(crate) struct Cancellable<I> {
+    /// Wrapped record iterator.
+    inner: I,
+    /// Flag to check before each item; `None` makes the adapter a pass-through.
+    flag: Option<CancelFlag>,
+}
+
+impl<I> Cancellable<I> {
+    /// Wraps `inner`, stopping once `flag` is cancelled.
+    pub(crate) fn new(inner: I, flag: Option<CancelFlag>) -> Self {
+        Self { inner, flag }
+    }
+}
+
+impl<I: Iterator> Iterator for Cancellable<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.flag.as_ref().is_some_and(CancelFlag::is_cancelled) {
+            return None;
+        }
+        self.inner.next()
+    }
+}
@@ -0,0 +1,77 @@
+//! Limit on the number of queries reading BAM files at the same time.
+
+use crate::error::ErrorKind;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use std::sync::{Arc, LazyLock, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Semaphore with one permit per query allowed to read at once, replaced
+/// whenever the limit changes.
+static READER_SLOTS: LazyLock<Mutex<Arc<Semaphore>>> =
+    LazyLock::new(|| Mutex::new(Arc::new(Semaphore::new(Semaphore::MAX_PERMITS))));
+
+/// Sets the maximum number of queries that read BAM files at the same time.
+///
+/// Queries over the limit wait for a running query to finish before opening
+/// their file. A `read_info_stream` holds its place until it is exhausted or
+/// closed. Passing nothing removes the limit, which is the default. Queries that
+/// are already running or waiting keep the limit they started with.
+///
+/// # Errors
+/// Returns an error if `max_concurrency` is zero.
+#[napi]
+pub fn set_max_concurrency(max_concurrency: Option<u32>) -> Result<()> {
+    let permits = match max_concurrency {
+        Some(0) => return Err(ErrorKind::InvalidOptions.error("maxConcurrency must be at least 1")),
+        Some(v) => usize::try_from(v)
+            .map_err(|e| ErrorKind::InvalidOptions.error(format!("Invalid maxConcurrency: {e}")))?
+            .min(Semaphore::MAX_PERMITS),
+        None => Semaphore::MAX_PERMITS,
+    };
+    *lock_slots()? = Arc::new(Semaphore::new(permits));
+    Ok(())
+}
+
+/// The semaphore of the current limit.
+fn current_slots() -> Result<Arc<Semaphore>> {
+    lock_slots().map(|v| Arc::clone(&v))
+}
+
+/// Locks the semaphore of the current limit.
+fn lock_slots() -> Result<std::sync::MutexGuard<'static, Arc<Semaphore>>> {
+    READER_SLOTS
+        .lock()
+        .map_err(|e| ErrorKind::Internal.error(format!("Concurrency limit lock poisoned: {e}")))
+}
+
+/// Waits until a query may read, returning the permit that holds its place.
+///
+/// # Errors
+/// Returns an error if the semaphore cannot be reached.
+pub(crate) async fn acquire_slot() -> Result<OwnedSemaphorePermit> {
+    current_slots()?
+        .acquire_owned()
+        .await
+        .map_err(|e| ErrorKind::Internal.error(format!("Failed to wait for a reader slot: {e}")))
+}
+
+/// Runs `f` on a blocking thread once a reader slot is free, holding the slot
+/// until `f` returns.
+///
+/// # Errors
+/// Returns an error if `f` does, or if the blocking task fails to complete.
+pub(crate) async fn run_limited<T, F>(f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    let permit = acquire_slot().await?;
+    tokio::task::spawn_blocking(move || {
+        let result = f();
+        drop(permit);
+        result
+    })
+    .await
+    .map_err(|e| ErrorKind::Internal.error(format!("Task join error: {e}")))?
+}
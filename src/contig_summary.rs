@@ -0,0 +1,244 @@
+//! Per-contig read counts, from the BAM index where possible.
+
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use crate::progress::{Progress, progress_interval};
+use crate::regions::Regions;
+use crate::url_open::UrlOpen;
+use crate::{
+    ReadOptions, build_input_options, check_cram_reference, is_cram, load_bam, passes_read_filters,
+    query_reader, validate_pagination,
+};
+use nanalogue_core::{InputBam, PathOrURLOrStdin, nanalogue_bam_reader_from_stdin};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::{FetchDefinition, HeaderView, IndexedReader, Read, Reader};
+
+/// Length and read count of a contig.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct ContigSummary {
+    /// Contig name.
+    pub contig: String,
+    /// Contig length from the BAM header.
+    pub length: i64,
+    /// Number of mapped records on the contig.
+    #[napi(js_name = "n_reads")]
+    pub n_reads: i64,
+}
+
+/// Counts the mapped records on each contig of the BAM header.
+///
+/// Contigs are listed in header order, including those without reads. When no
+/// read selection options are set, every mapped record counts, as in
+/// `samtools idxstats`, and the counts come from the index without reading any
+/// records; a file without an index is streamed instead. Otherwise, the reads
+/// are those `read_info` returns for the same options, which leaves out
+/// zero-length records such as most secondary alignments.
+///
+/// # Errors
+/// Returns an error if input options are invalid or BAM reading fails.
+#[napi]
+pub async fn contig_summary(options: ReadOptions) -> Result<Vec<ContigSummary>> {
+    run_limited(move || contig_summary_sync(&options, None)).await
+}
+
+/// Synchronous implementation of `contig_summary`.
+pub(crate) fn contig_summary_sync(
+    options: &ReadOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<Vec<ContigSummary>> {
+    let (header, counts) = if selects_every_read(options) {
+        let interval = progress_interval(options.progress_interval)?;
+        let (bam, _, _) = build_input_options(options)?;
+        index_counts(&bam, shared, options, interval)?
+    } else {
+        filtered_counts(options, shared)?
+    };
+
+    (0u32..)
+        .zip(header.target_names())
+        .zip(counts)
+        .map(|((tid, name), count)| {
+            let raw_length = header.target_len(tid).ok_or_else(|| {
+                ErrorKind::InvalidData.error(format!(
+                    "Missing length for contig {}",
+                    String::from_utf8_lossy(name)
+                ))
+            })?;
+            let to_i64 = |v: u64| {
+                i64::try_from(v)
+                    .map_err(|e| ErrorKind::Internal.error(format!("contig_summary failed: {e}")))
+            };
+            Ok(ContigSummary {
+                contig: String::from_utf8_lossy(name).into_owned(),
+                length: to_i64(raw_length)?,
+                n_reads: to_i64(count)?,
+            })
+        })
+        .collect()
+}
+
+/// Whether `options` leave every record of the file selected, so that the index
+/// counts can stand in for reading the records.
+fn selects_every_read(options: &ReadOptions) -> bool {
+    options.min_seq_len.is_none()
+        && options.max_seq_len.is_none()
+        && options.min_align_len.is_none()
+        && options.max_align_len.is_none()
+        && options.read_id_set.is_none()
+        && options.read_id_regex.is_none()
+        && options.read_group.is_none()
+        && options.include_zero_len.is_none()
+        && options.read_filter.is_none()
+        && options.sample_fraction.is_none()
+        && options.mapq_filter.is_none()
+        && options.mapq_max.is_none()
+        && options.exclude_mapq_unavail.is_none()
+        && options.exclude_secondary.is_none()
+        && options.exclude_supplementary.is_none()
+        && options.exclude_duplicate.is_none()
+        && options.exclude_qc_fail.is_none()
+        && options.min_mods.is_none()
+        && options.require_mods.is_none()
+        && options.region.is_none()
+        && options.regions.is_none()
+        && options.region_bed.is_none()
+        && options.full_region.is_none()
+        && options.limit.is_none()
+        && options.offset.is_none()
+}
+
+/// Mapped record counts per contig from the index, streaming the file if it has
+/// none or its index holds no counts.
+#[expect(
+    clippy::pattern_type_mismatch,
+    reason = "the path is bound through the borrowed `bam_path` in the tuple by default binding modes"
+)]
+fn index_counts(
+    bam: &InputBam,
+    shared: Option<&mut IndexedReader>,
+    options: &ReadOptions,
+    interval: u32,
+) -> Result<(HeaderView, Vec<u64>)> {
+    let reference_fasta = options.reference_fasta.as_deref();
+    let mut owned;
+    let reader = if let Some(v) = shared {
+        v
+    } else {
+        check_cram_reference(&bam.bam_path, reference_fasta)?;
+        if let PathOrURLOrStdin::Stdin = bam.bam_path {
+            let mut reader = nanalogue_bam_reader_from_stdin().map_err(|e| {
+                ErrorKind::of_core(&e).error(format!("Failed to open BAM from stdin: {e}"))
+            })?;
+            if let Some(v) = reference_fasta {
+                reader.set_reference(v).map_err(|e| {
+                    ErrorKind::of_htslib(&e).error(format!("Failed to load reference FASTA: {e}"))
+                })?;
+            }
+            return stream_counts(&mut reader, options, interval);
+        }
+        let url_open = UrlOpen::new(
+            options.url_retries,
+            options.url_timeout_ms,
+            options.url_headers.as_ref(),
+        )?;
+        owned = match (
+            load_bam(
+                bam,
+                reference_fasta,
+                &url_open,
+                options.auto_index == Some(true),
+            ),
+            &bam.bam_path,
+        ) {
+            (Ok(v), _) => v,
+            // Without an index, a local file can still be read from start to end
+            (Err(_), PathOrURLOrStdin::Path(path)) => {
+                let mut reader = Reader::from_path(path).map_err(|e| {
+                    ErrorKind::of_htslib(&e).error(format!("Failed to open BAM: {e}"))
+                })?;
+                if let Some(v) = reference_fasta {
+                    reader.set_reference(v).map_err(|e| {
+                        ErrorKind::of_htslib(&e)
+                            .error(format!("Failed to load reference FASTA: {e}"))
+                    })?;
+                }
+                return stream_counts(&mut reader, options, interval);
+            }
+            (Err(e), _) => return Err(e),
+        };
+        &mut owned
+    };
+    // CRAM indexes hold no read counts, so the records are read instead
+    if is_cram(&bam.bam_path) {
+        reader
+            .fetch(FetchDefinition::All)
+            .map_err(|e| ErrorKind::of_htslib(&e).error(format!("Failed to fetch region: {e}")))?;
+        return stream_counts(reader, options, interval);
+    }
+    let stats = reader.index_stats().map_err(|e| {
+        ErrorKind::of_htslib(&e).error(format!("Failed to read index statistics: {e}"))
+    })?;
+    let counts = stats
+        .into_iter()
+        .filter(|&(tid, ..)| tid >= 0)
+        .map(|(_, _, mapped, _)| mapped)
+        .collect();
+    Ok((reader.header().clone(), counts))
+}
+
+/// Mapped record counts per contig from reading every record.
+fn stream_counts<R: Read>(
+    reader: &mut R,
+    options: &ReadOptions,
+    interval: u32,
+) -> Result<(HeaderView, Vec<u64>)> {
+    let header = reader.header().clone();
+    let mut counts = vec![0u64; usize::try_from(header.target_count()).unwrap_or(0)];
+    for entry in Progress::new(reader.rc_records(), options.on_progress.clone(), interval) {
+        let record = entry.map_err(|e| {
+            ErrorKind::of_htslib(&e).error(format!("Failed to read BAM record: {e}"))
+        })?;
+        count_record(&mut counts, record.is_unmapped(), record.tid());
+    }
+    Ok((header, counts))
+}
+
+/// Mapped record counts per contig over the reads that pass the filters in `options`.
+fn filtered_counts(
+    options: &ReadOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<(HeaderView, Vec<u64>)> {
+    let (offset, limit) = validate_pagination(options)?;
+    let interval = progress_interval(options.progress_interval)?;
+    let (mut bam, mut mods, extra) = build_input_options(options)?;
+    let regions = Regions::from_options(options)?;
+
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, header) = reader.records(&mut bam, &mut mods, regions.as_ref())?;
+
+    let filtered = Progress::new(rc_records, options.on_progress.clone(), interval).filter(|r| {
+        r.as_ref()
+            .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    let mut counts = vec![0u64; usize::try_from(header.target_count()).unwrap_or(0)];
+    for entry in filtered.skip(offset).take(limit) {
+        let record = entry.map_err(|e| {
+            ErrorKind::of_htslib(&e).error(format!("Failed to read BAM record: {e}"))
+        })?;
+        count_record(&mut counts, record.is_unmapped(), record.tid());
+    }
+    Ok((header, counts))
+}
+
+/// Adds a record to the count of its contig if it is mapped.
+fn count_record(counts: &mut [u64], is_unmapped: bool, tid: i32) {
+    if is_unmapped {
+        return;
+    }
+    if let Some(count) = usize::try_from(tid).ok().and_then(|i| counts.get_mut(i)) {
+        *count = count.saturating_add(1);
+    }
+}
@@ -0,0 +1,52 @@
+//! Number of reads that pass the filters, without building per-read output.
+
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use crate::progress::{Progress, progress_interval};
+use crate::regions::Regions;
+use crate::{
+    ReadOptions, build_input_options, passes_read_filters, query_reader, validate_pagination,
+};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::IndexedReader;
+
+/// Counts the reads that pass the filters.
+///
+/// Gives the length of the array `read_info` returns for the same options, so
+/// `offset` and `limit` count reads too, but only reads the records and builds
+/// no read information.
+///
+/// # Errors
+/// Returns an error if input options are invalid or BAM reading fails.
+#[napi]
+pub async fn count_reads(options: ReadOptions) -> Result<i64> {
+    run_limited(move || count_reads_sync(&options, None)).await
+}
+
+/// Synchronous implementation of `count_reads`.
+pub(crate) fn count_reads_sync(
+    options: &ReadOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<i64> {
+    let (offset, limit) = validate_pagination(options)?;
+    let interval = progress_interval(options.progress_interval)?;
+    let (mut bam, mut mods, extra) = build_input_options(options)?;
+    let regions = Regions::from_options(options)?;
+
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, _) = reader.records(&mut bam, &mut mods, regions.as_ref())?;
+
+    let filtered = Progress::new(rc_records, options.on_progress.clone(), interval).filter(|r| {
+        r.as_ref()
+            .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    let mut count = 0i64;
+    for entry in filtered.skip(offset).take(limit) {
+        let _record = entry.map_err(|e| {
+            ErrorKind::of_htslib(&e).error(format!("Failed to read BAM record: {e}"))
+        })?;
+        count = count.saturating_add(1);
+    }
+    Ok(count)
+}
@@ -0,0 +1,115 @@
+//! Read depth at each position of a region.
+
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use crate::pileup::{region_bounds, single_region};
+use crate::progress::{Progress, progress_interval};
+use crate::{
+    ReadOptions, build_input_options, passes_read_filters, query_reader, validate_pagination,
+};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::IndexedReader;
+
+/// Read depth at a reference position, or averaged over a bin of positions.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct CoverageRecord {
+    /// 0-based position on the contig, the first position of the bin when binned.
+    pub pos: i64,
+    /// Number of reads spanning the position, or its mean over the bin.
+    pub depth: f64,
+}
+
+/// Counts the reads spanning each position of a region.
+///
+/// Requires the `region` parameter to be set. Reads are selected as in
+/// `bam_mods`, so `full_region` and the read filters apply, and `offset` and
+/// `limit` count reads. A read spans the positions from its alignment start to its
+/// alignment end, deletions and skipped regions included. With `bin_size`, the
+/// region is split into bins of that many positions and each record holds the
+/// mean depth of one bin, the last bin being shorter if the region length is not
+/// a multiple of `bin_size`.
+///
+/// # Errors
+/// Returns an error if region is missing, `bin_size` is zero, input options are
+/// invalid, or BAM reading fails.
+#[napi]
+pub async fn coverage(options: ReadOptions, bin_size: Option<u32>) -> Result<Vec<CoverageRecord>> {
+    run_limited(move || coverage_sync(&options, bin_size, None)).await
+}
+
+/// Synchronous implementation of `coverage`.
+pub(crate) fn coverage_sync(
+    options: &ReadOptions,
+    raw_bin_size: Option<u32>,
+    shared: Option<&mut IndexedReader>,
+) -> Result<Vec<CoverageRecord>> {
+    let region_str = single_region(options, "coverage")?;
+    let bin_size = usize::try_from(raw_bin_size.unwrap_or(1))
+        .map_err(|e| ErrorKind::InvalidOptions.error(format!("Invalid bin_size: {e}")))?;
+    if bin_size == 0 {
+        return Err(ErrorKind::InvalidOptions.error("bin_size must be at least 1"));
+    }
+    let (offset, limit) = validate_pagination(options)?;
+    let interval = progress_interval(options.progress_interval)?;
+    let (mut bam, mut mods, extra) = build_input_options(options)?;
+
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, header) = reader.records(&mut bam, &mut mods, None)?;
+    let (_, start, end) = region_bounds(&region_str, &header)?;
+    let to_index = |v: i64| {
+        usize::try_from(v.clamp(start, end).saturating_sub(start))
+            .map_err(|e| ErrorKind::Internal.error(format!("coverage failed: {e}")))
+    };
+    let len = to_index(end)?;
+
+    let filtered = Progress::new(rc_records, options.on_progress.clone(), interval).filter(|r| {
+        r.as_ref()
+            .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    // Depth changes at each position, so that a read costs two updates however
+    // long it is
+    let mut changes = vec![0i64; len.saturating_add(1)];
+    for entry in filtered.skip(offset).take(limit) {
+        let record = entry.map_err(|e| {
+            ErrorKind::of_htslib(&e).error(format!("Failed to read BAM record: {e}"))
+        })?;
+        if record.is_unmapped() {
+            continue;
+        }
+        let (read_start, read_end) = (to_index(record.pos())?, to_index(record.cigar().end_pos())?);
+        if read_start >= read_end {
+            continue;
+        }
+        if let Some(v) = changes.get_mut(read_start) {
+            *v = v.saturating_add(1);
+        }
+        if let Some(v) = changes.get_mut(read_end) {
+            *v = v.saturating_sub(1);
+        }
+    }
+
+    let depths: Vec<i64> = changes
+        .iter()
+        .take(len)
+        .scan(0i64, |depth, v| {
+            *depth = depth.saturating_add(*v);
+            Some(*depth)
+        })
+        .collect();
+    Ok(depths
+        .chunks(bin_size)
+        .zip((start..).step_by(bin_size))
+        .map(|(bin, pos)| {
+            let total: i64 = bin.iter().sum();
+            #[expect(
+                clippy::cast_precision_loss,
+                reason = "depth sums and bin sizes are far below 2^53"
+            )]
+            let depth = total as f64 / bin.len() as f64;
+            CoverageRecord { pos, depth }
+        })
+        .collect())
+}
@@ -0,0 +1,330 @@
+//! Windows of `window_reads` along the read that keep the calls past the last
+//! window of `win` calls.
+//!
+//! The core windowing slides windows of `win` calls along each modification of
+//! a read and stops at the last window that fits, so the calls after it are in
+//! no window. With an [`EdgeMode`] other than [`EdgeMode::Drop`], the windows
+//! are made here instead, written in the output format of the core windowing
+//! with one more window holding those calls, so that the writers of
+//! [`window_ops`](crate::window_ops) apply to them as they do to the core output.
+//! Windows that the window function rejects are reported through
+//! [`logging::warn`], where the core windowing writes them to stderr, so that
+//! `window_reads` also makes the windows here when the window function rejects
+//! windows of `win` calls.
+
+use crate::logging;
+use crate::records::{ModSelection, read_mod_data};
+use crate::{EdgeMode, WindowFormat};
+use nanalogue_core::read_utils::AlignAndModData;
+use nanalogue_core::{
+    AlignmentInfoBuilder, CurrRead, Error, F32AbsValAtMost1, InputMods, InputWindowing, ModChar,
+    OptionalTag,
+};
+use rust_htslib::bam::Record;
+use std::io::Write;
+use std::ops::Range;
+use std::rc::Rc;
+
+/// Reference position of a window without aligned calls, as the core writes it.
+const INVALID_REF_POS: i64 = -1;
+
+/// One call of a modification of a read: its ML value, its start and end on the
+/// basecalled sequence, and its reference start and end if it is aligned.
+type Call = (u8, i64, i64, Option<i64>, Option<i64>);
+
+/// Window of the core output:
+/// `(win_start, win_end, win_val, mean_base_qual, ref_win_start, ref_win_end)`.
+type CoreWindow = (i64, i64, F32AbsValAtMost1, u8, i64, i64);
+
+/// Windows of one modification of a read.
+#[derive(Debug)]
+struct ModWindows {
+    /// Base that is modified.
+    base: char,
+    /// Whether the modification is on the plus strand.
+    is_strand_plus: bool,
+    /// Modification code.
+    mod_code: ModChar,
+    /// Windows of the modification along the read.
+    data: Vec<CoreWindow>,
+}
+
+impl ModWindows {
+    /// Writes the windows as an entry of the `mod_table` of the core JSON output.
+    fn write_json<W: Write>(&self, handle: &mut W) -> Result<(), Error> {
+        write!(
+            handle,
+            "{{\"base\":\"{}\",\"is_strand_plus\":{},\"mod_code\":{},\"data\":{}}}",
+            self.base,
+            self.is_strand_plus,
+            serde_json::to_string(&self.mod_code)?,
+            serde_json::to_string(&self.data)?,
+        )?;
+        Ok(())
+    }
+}
+
+/// Ranges of the calls of each window when sliding `win` calls by `step` along
+/// `len` calls, with the window at the end that `edge_mode` adds.
+pub(crate) fn window_ranges(
+    len: usize,
+    win: usize,
+    step: usize,
+    edge_mode: EdgeMode,
+) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = match len.checked_sub(win) {
+        Some(last) => (0..=last)
+            .step_by(step)
+            .map(|v| v..v.saturating_add(win))
+            .collect(),
+        None => Vec::new(),
+    };
+    let covered = ranges.last().map_or(0, |v| v.end);
+    let next = ranges.last().map_or(0, |v| v.start.saturating_add(step));
+    // Calls past the last window, unless they fall between steps
+    if covered < len && next < len {
+        match edge_mode {
+            EdgeMode::Drop => {}
+            EdgeMode::Partial => ranges.push(next..len),
+            EdgeMode::Pad => {
+                if let Some(v) = len.checked_sub(win) {
+                    ranges.push(v..len);
+                }
+            }
+        }
+    }
+    ranges
+}
+
+/// Windows `records` along each read as the core windowing does, with the
+/// window at the end of each modification that `edge_mode` adds, and writes
+/// them to `handle` in the core output `format`.
+///
+/// Windows that `window_function` cannot value are left out with a warning, as
+/// the core windowing does.
+///
+/// # Errors
+/// Returns an error if a record cannot be read, its modification data is
+/// invalid, or writing fails.
+pub(crate) fn run<W, F, D>(
+    handle: &mut W,
+    records: D,
+    window_options: &InputWindowing,
+    mods: &InputMods<OptionalTag>,
+    window_function: F,
+    format: WindowFormat,
+    edge_mode: EdgeMode,
+) -> Result<(), Error>
+where
+    W: Write,
+    F: Fn(&[u8]) -> Result<F32AbsValAtMost1, Error>,
+    D: IntoIterator<Item = Result<Rc<Record>, rust_htslib::errors::Error>>,
+{
+    let win = window_options.win.get();
+    let step = window_options.step.get();
+    match format {
+        WindowFormat::Json => write!(handle, "[")?,
+        WindowFormat::Tsv => writeln!(
+            handle,
+            "#contig\tref_win_start\tref_win_end\tread_id\twin_val\tstrand\t\
+             base\tmod_strand\tmod_type\twin_start\twin_end\tbasecall_qual",
+        )?,
+    }
+    let mut is_first = true;
+    for entry in records {
+        let record = entry?;
+        let read = read_mod_data(&record, mods, ModSelection::default())?;
+        let mut mod_table = Vec::new();
+        for base_mod in &read.mod_data().0.base_mods {
+            let calls: Vec<Call> = base_mod
+                .ranges
+                .annotations
+                .iter()
+                .map(|k| (k.qual, k.start, k.end, k.reference_start, k.reference_end))
+                .collect();
+            let data = window_ranges(calls.len(), win, step, edge_mode)
+                .into_iter()
+                .filter_map(|range| {
+                    let window_idx = range.start;
+                    let window = calls.get(range).unwrap_or_default();
+                    match core_window(window, record.qual(), &window_function) {
+                        Ok(v) => Some(v),
+                        Err(e) => {
+                            // Worded as the warning of the core windowing
+                            logging::warn(&format!(
+                                "Skipping {} window starting at {}:{window_idx} due to error: {e}",
+                                window.len(),
+                                read.read_id(),
+                            ));
+                            None
+                        }
+                    }
+                })
+                .collect();
+            mod_table.push(ModWindows {
+                base: char::from(base_mod.modified_base),
+                is_strand_plus: base_mod.strand == '+',
+                mod_code: ModChar::new(base_mod.modification_type),
+                data,
+            });
+        }
+        match format {
+            WindowFormat::Json => {
+                write!(handle, "{}", if is_first { "\n" } else { ",\n" })?;
+                write_json_read(handle, &read, &mod_table)?;
+            }
+            WindowFormat::Tsv => write_tsv_read(handle, &read, &mod_table)?,
+        }
+        is_first = false;
+    }
+    if let WindowFormat::Json = format {
+        writeln!(handle, "\n]")?;
+    }
+    Ok(())
+}
+
+/// Writes the windows `mod_table` of `read` as a read entry of the core JSON
+/// output.
+fn write_json_read<W: Write>(
+    handle: &mut W,
+    read: &CurrRead<AlignAndModData>,
+    mod_table: &[ModWindows],
+) -> Result<(), Error> {
+    write!(
+        handle,
+        "{{\"alignment_type\":{},",
+        serde_json::to_string(&read.read_state())?
+    )?;
+    if !read.read_state().is_unmapped() {
+        let (contig_id, start) = read.contig_id_and_start()?;
+        let end = start
+            .checked_add(read.align_len()?)
+            .ok_or_else(|| Error::Arithmetic("alignment end overflow".to_owned()))?;
+        let alignment = AlignmentInfoBuilder::default()
+            .start(start)
+            .end(end)
+            .contig(read.contig_name()?.to_owned())
+            .contig_id(contig_id)
+            .build()?;
+        write!(
+            handle,
+            "\"alignment\":{},",
+            serde_json::to_string(&alignment)?
+        )?;
+    }
+    write!(handle, "\"mod_table\":[")?;
+    for (i, windows) in mod_table.iter().enumerate() {
+        if i > 0 {
+            write!(handle, ",")?;
+        }
+        windows.write_json(handle)?;
+    }
+    write!(
+        handle,
+        "],\"read_id\":{},\"seq_len\":{}}}",
+        serde_json::to_string(read.read_id())?,
+        read.seq_len()?
+    )?;
+    Ok(())
+}
+
+/// Writes the windows `mod_table` of `read` as rows of the core TSV output.
+fn write_tsv_read<W: Write>(
+    handle: &mut W,
+    read: &CurrRead<AlignAndModData>,
+    mod_table: &[ModWindows],
+) -> Result<(), Error> {
+    let contig = if read.read_state().is_unmapped() {
+        "."
+    } else {
+        read.contig_name()?
+    };
+    for windows in mod_table {
+        let mod_strand = if windows.is_strand_plus { '+' } else { '-' };
+        for &(start, end, win_val, qual, ref_start, ref_end) in &windows.data {
+            writeln!(
+                handle,
+                "{contig}\t{ref_start}\t{ref_end}\t{}\t{win_val}\t{}\t{}\t\
+                 {mod_strand}\t{}\t{start}\t{end}\t{qual}",
+                read.read_id(),
+                read.strand(),
+                windows.base,
+                windows.mod_code,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Window of `calls` as the core windowing gives it, with the mean of the base
+/// qualities `base_qual` of the read it covers.
+///
+/// # Errors
+/// Returns the error of `window_function` if it cannot value the window, or
+/// `Error::EmptyWindow` if there are no calls.
+fn core_window<F>(
+    calls: &[Call],
+    base_qual: &[u8],
+    window_function: &F,
+) -> Result<CoreWindow, Error>
+where
+    F: Fn(&[u8]) -> Result<F32AbsValAtMost1, Error>,
+{
+    let quals: Vec<u8> = calls.iter().map(|&(qual, ..)| qual).collect();
+    let win_val = window_function(&quals)?;
+    let (&(_, win_start, _, _, _), &(_, _, win_end, _, _)) = calls
+        .first()
+        .zip(calls.last())
+        .ok_or_else(|| Error::EmptyWindow("in `core_window`".to_owned()))?;
+    let ref_win_start = calls
+        .iter()
+        .filter_map(|&(_, _, _, v, _)| v)
+        .min()
+        .unwrap_or(INVALID_REF_POS);
+    let ref_win_end = calls
+        .iter()
+        .filter_map(|&(_, _, _, _, v)| v)
+        .max()
+        .map_or(INVALID_REF_POS, |v| v.saturating_add(1));
+    let covered = usize::try_from(win_start)
+        .ok()
+        .zip(usize::try_from(win_end).ok())
+        .and_then(|(start, end)| base_qual.get(start..end))
+        .unwrap_or_default();
+    Ok((
+        win_start,
+        win_end,
+        win_val,
+        mean_base_qual(covered),
+        ref_win_start,
+        ref_win_end,
+    ))
+}
+
+/// Mean of base qualities as the core windowing computes it: over the error
+/// probabilities rather than the quality scores, or 255 if the qualities are
+/// missing.
+fn mean_base_qual(quals: &[u8]) -> u8 {
+    let Some(&lowest) = quals.iter().min() else {
+        return u8::MAX;
+    };
+    if quals.first() == Some(&u8::MAX) {
+        return u8::MAX;
+    }
+    let total: f64 = quals
+        .iter()
+        .map(|&v| 10f64.powf(-0.1 * f64::from(v.saturating_sub(lowest))))
+        .sum();
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "numbers of bases in a window are far below 2^52"
+    )]
+    let mean = total / quals.len() as f64;
+    #[expect(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        reason = "the mean error probability is at most 1, so the score is small and not negative"
+    )]
+    let score = (-10.0 * mean.log10()).round() as u8;
+    lowest.saturating_add(score)
+}
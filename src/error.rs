@@ -0,0 +1,132 @@
+//! Kinds of errors, which JS callers read from the `code` of a rejected query.
+//!
+//! NAPI errors carry a fixed set of status codes, so the kind is sent to JS as a
+//! `[Kind] ` prefix of the message. `index.js` moves it from the message to the
+//! `code` property of the error before the caller sees it.
+
+use nanalogue_core::Error as CoreError;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::errors::Error as HtslibError;
+
+/// Kind of error a query failed with, set as the `code` of the JS error.
+#[napi(string_enum)]
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum ErrorKind {
+    /// An option is missing, malformed or out of range.
+    InvalidOptions,
+    /// A region cannot be parsed, is not in the BAM header, or cannot be fetched.
+    InvalidRegion,
+    /// The BAM file or another input file does not exist.
+    FileNotFound,
+    /// The BAM file has no index, or its index cannot be loaded.
+    IndexMissing,
+    /// Opening, reading or writing a file failed.
+    IoError,
+    /// The BAM data is malformed, e.g. a truncated record or invalid modification tags.
+    InvalidData,
+    /// The query was stopped with a `CancelToken`.
+    Cancelled,
+    /// The query failed inside the bindings, e.g. a worker thread panicked.
+    Internal,
+}
+
+impl ErrorKind {
+    /// Name of the kind, as found in the `code` of the JS error.
+    fn code(self) -> &'static str {
+        match self {
+            Self::InvalidOptions => "InvalidOptions",
+            Self::InvalidRegion => "InvalidRegion",
+            Self::FileNotFound => "FileNotFound",
+            Self::IndexMissing => "IndexMissing",
+            Self::IoError => "IoError",
+            Self::InvalidData => "InvalidData",
+            Self::Cancelled => "Cancelled",
+            Self::Internal => "Internal",
+        }
+    }
+
+    /// Error of this kind with the message `reason`.
+    pub(crate) fn error(self, reason: impl AsRef<str>) -> Error {
+        let status = match self {
+            Self::Cancelled => Status::Cancelled,
+            Self::InvalidOptions => Status::InvalidArg,
+            Self::InvalidRegion
+            | Self::FileNotFound
+            | Self::IndexMissing
+            | Self::IoError
+            | Self::InvalidData
+            | Self::Internal => Status::GenericFailure,
+        };
+        Error::new(status, format!("[{}] {}", self.code(), reason.as_ref()))
+    }
+
+    /// Splits `e` into the name of its kind and its message, for failures that are
+    /// reported as values instead of rejecting. The kind is absent if `e` was not
+    /// built by [`ErrorKind::error`].
+    pub(crate) fn split(e: &Error) -> (Option<String>, String) {
+        match e.reason.strip_prefix('[').and_then(|v| v.split_once("] ")) {
+            Some((code, message)) => (Some(code.to_owned()), message.to_owned()),
+            None => (None, e.reason.clone()),
+        }
+    }
+
+    /// Kind of a failure to read or write a file.
+    pub(crate) fn of_io(e: &std::io::Error) -> Self {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            Self::FileNotFound
+        } else {
+            Self::IoError
+        }
+    }
+
+    /// Kind of an error from `nanalogue_core`.
+    #[expect(
+        clippy::pattern_type_mismatch,
+        reason = "the htslib error is bound through the borrowed `e` by default binding modes"
+    )]
+    pub(crate) fn of_core(e: &CoreError) -> Self {
+        if let CoreError::RustHtslibError(v) = e {
+            return Self::of_htslib(v);
+        }
+        if let CoreError::InputOutputError(_) | CoreError::WriteOutput(_) = e {
+            return Self::IoError;
+        }
+        if let CoreError::InvalidRegion { .. }
+        | CoreError::InvalidContigAndStart(_)
+        | CoreError::InvalidAlignCoords(_) = e
+        {
+            return Self::InvalidRegion;
+        }
+        Self::InvalidData
+    }
+
+    /// Kind of an error from `rust_htslib`.
+    pub(crate) fn of_htslib(e: &HtslibError) -> Self {
+        if let HtslibError::FileNotFound { .. } = *e {
+            return Self::FileNotFound;
+        }
+        if let HtslibError::BamInvalidIndex { .. } | HtslibError::BamNotIndexable = *e {
+            return Self::IndexMissing;
+        }
+        if let HtslibError::Fetch
+        | HtslibError::GenomicSeek { .. }
+        | HtslibError::UnknownSequence { .. }
+        | HtslibError::InvalidTid { .. } = *e
+        {
+            return Self::InvalidRegion;
+        }
+        if let HtslibError::BamOpen { .. }
+        | HtslibError::FileSeek
+        | HtslibError::NonUnicodePath
+        | HtslibError::WriteRecord
+        | HtslibError::BamInvalidReferencePath { .. }
+        | HtslibError::BamWriteIndex
+        | HtslibError::BamBuildIndex = *e
+        {
+            return Self::IoError;
+        }
+        Self::InvalidData
+    }
+}
@@ -0,0 +1,331 @@
+//! Writing the reads that pass the read filters to a new BAM or FASTQ file.
+
+use crate::bam_index::{CoordinateOrder, build_bai};
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use crate::progress::{Progress, progress_interval};
+use crate::records::{base_quals, qual_offset};
+use crate::regions::Regions;
+use crate::{
+    ReadOptions, build_input_options, is_url, passes_read_filters, query_reader,
+    validate_pagination,
+};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::{Format, Header, IndexedReader, Record, Writer};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+/// Quality written for bases stored without one, as `samtools fastq` does.
+const DEFAULT_FASTQ_QUAL: u8 = 1;
+
+/// Summary of a finished `export_bam` call.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug, Default)]
+pub struct ExportBamResult {
+    /// Number of records written to the output BAM.
+    pub records_written: i64,
+    /// Path of the `.bai` index written next to the output, if the output was indexed.
+    pub index_path: Option<String>,
+}
+
+/// Writes the reads that pass the read filters to a new BAM file.
+///
+/// Reads are selected exactly as `read_info` selects them, including pagination,
+/// and written unchanged with the header of the source BAM, so MM/ML tags and all
+/// other fields are kept. Modification filters do not alter the written records.
+/// A `.bai` index is written alongside the output when its records are in
+/// coordinate order; an index left there by an earlier export is removed
+/// otherwise. The output is removed again if reading or writing fails.
+///
+/// # Errors
+/// Returns an error if input options are invalid, the output path is the input
+/// path, or reading, writing or indexing fails.
+#[napi]
+pub async fn export_bam(options: ReadOptions, output_path: String) -> Result<ExportBamResult> {
+    run_limited(move || export_bam_sync(&options, &output_path, None)).await
+}
+
+/// Synchronous implementation of `export_bam` that runs on a blocking thread.
+pub(crate) fn export_bam_sync(
+    options: &ReadOptions,
+    output_path: &str,
+    shared: Option<&mut IndexedReader>,
+) -> Result<ExportBamResult> {
+    if options.bam_path == output_path && !is_url(&options.bam_path, options.treat_as_url) {
+        return Err(ErrorKind::InvalidOptions
+            .error("outputPath must differ from bamPath, which is being read"));
+    }
+    let (offset, limit) = validate_pagination(options)?;
+    let interval = progress_interval(options.progress_interval)?;
+    let (mut bam, mut mods, extra) = build_input_options(options)?;
+    let regions = Regions::from_options(options)?;
+
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, header) = reader.records(&mut bam, &mut mods, regions.as_ref())?;
+
+    let mut writer = Writer::from_path(output_path, &Header::from_template(&header), Format::Bam)
+        .map_err(|e| {
+        ErrorKind::IoError.error(format!("Failed to create BAM file '{output_path}': {e}"))
+    })?;
+    if let Some(v) = options.threads {
+        writer
+            .set_threads(usize::from(v))
+            .map_err(|e| ErrorKind::Internal.error(format!("Failed to set writer threads: {e}")))?;
+    }
+
+    // The index of the file being replaced no longer matches it
+    let bai_path = format!("{output_path}.bai");
+    match fs::remove_file(&bai_path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            return Err(ErrorKind::IoError.error(format!(
+                "Failed to remove the index '{bai_path}' of the replaced output: {e}"
+            )));
+        }
+        Ok(()) | Err(_) => {}
+    }
+
+    let filtered = Progress::new(rc_records, options.on_progress.clone(), interval).filter(|r| {
+        r.as_ref()
+            .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    let result = write_bam(filtered.skip(offset).take(limit), &mut writer, output_path);
+    // The index can only be built once the writer has flushed and closed the file.
+    drop(writer);
+    let (records_written, order) = match result {
+        Ok(v) => v,
+        // Leave no partial output behind, e.g. after a truncated input. A file
+        // that cannot be removed is left in place, as the original error matters
+        // more.
+        Err(e) => {
+            return match fs::remove_file(output_path) {
+                Ok(()) | Err(_) => Err(e),
+            };
+        }
+    };
+
+    let index_path = if order.is_sorted() {
+        build_bai(output_path, options.threads.map_or(1, u32::from))?;
+        Some(bai_path)
+    } else {
+        None
+    };
+
+    Ok(ExportBamResult {
+        records_written: i64::try_from(records_written).unwrap_or(i64::MAX),
+        index_path,
+    })
+}
+
+/// Writes `records` to `writer`, returning how many were written and their
+/// order.
+fn write_bam<I>(
+    records: I,
+    writer: &mut Writer,
+    output_path: &str,
+) -> Result<(usize, CoordinateOrder)>
+where
+    I: Iterator<Item = std::result::Result<Rc<Record>, rust_htslib::errors::Error>>,
+{
+    let mut written: usize = 0;
+    let mut order = CoordinateOrder::default();
+    for entry in records {
+        let record = entry.map_err(|e| {
+            ErrorKind::of_htslib(&e).error(format!("Failed to read BAM record: {e}"))
+        })?;
+        order.push(&record);
+        writer.write(&record).map_err(|e| {
+            ErrorKind::IoError.error(format!("Failed to write BAM file '{output_path}': {e}"))
+        })?;
+        written = written.saturating_add(1);
+    }
+    Ok((written, order))
+}
+
+/// Summary of a finished `export_fastq` call.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug, Default)]
+pub struct ExportFastqResult {
+    /// Number of reads written to the output FASTQ.
+    pub records_written: i64,
+    /// Path of the file written, with the `.gz` extension added by `compress`.
+    pub output_path: String,
+}
+
+/// Writes the reads that pass the read filters to a new FASTQ file.
+///
+/// Reads are selected exactly as `read_info` selects them, including pagination,
+/// and each is written as `@read_id`, its sequence, `+` and its base qualities.
+/// Reads aligned to the reverse strand are reverse-complemented back to the
+/// orientation they were sequenced in. As with `samtools fastq`, secondary and
+/// supplementary alignments and records without sequence are left out so that
+/// each read is written once, and bases without a quality are given 1. The
+/// qualities are written as Phred+33, converted from Phred+64 if `qual_offset`
+/// is 64. With
+/// `compress`, the file is gzip-compressed and a `.gz` extension is added unless
+/// present. The file is removed again if the call fails.
+///
+/// # Errors
+/// Returns an error if input options are invalid, the output path is the input
+/// path, or reading or writing fails.
+#[napi]
+pub async fn export_fastq(
+    options: ReadOptions,
+    output_path: String,
+    compress: Option<bool>,
+) -> Result<ExportFastqResult> {
+    run_limited(move || export_fastq_sync(&options, &output_path, compress == Some(true), None))
+        .await
+}
+
+/// Synchronous implementation of `export_fastq` that runs on a blocking thread.
+pub(crate) fn export_fastq_sync(
+    options: &ReadOptions,
+    raw_output_path: &str,
+    compress: bool,
+    shared: Option<&mut IndexedReader>,
+) -> Result<ExportFastqResult> {
+    let has_gz_extension = Path::new(raw_output_path)
+        .extension()
+        .is_some_and(|v| v.eq_ignore_ascii_case("gz"));
+    let output_path = if compress && !has_gz_extension {
+        format!("{raw_output_path}.gz")
+    } else {
+        raw_output_path.to_owned()
+    };
+    if options.bam_path == output_path && !is_url(&options.bam_path, options.treat_as_url) {
+        return Err(ErrorKind::InvalidOptions
+            .error("outputPath must differ from bamPath, which is being read"));
+    }
+    let file = File::create(&output_path).map_err(|e| {
+        ErrorKind::IoError.error(format!("Failed to create FASTQ file '{output_path}': {e}"))
+    })?;
+    let write_error = |e: std::io::Error| {
+        ErrorKind::IoError.error(format!("Failed to write FASTQ file '{output_path}': {e}"))
+    };
+    let result = if compress {
+        let mut writer = GzEncoder::new(BufWriter::new(file), Compression::default());
+        write_fastq(options, shared, &mut writer, &write_error).and_then(|written| {
+            // The gzip trailer is only written when the encoder is finished
+            writer
+                .finish()
+                .and_then(|mut v| v.flush())
+                .map(|()| written)
+                .map_err(write_error)
+        })
+    } else {
+        let mut writer = BufWriter::new(file);
+        write_fastq(options, shared, &mut writer, &write_error)
+            .and_then(|written| writer.flush().map(|()| written).map_err(write_error))
+    };
+    match result {
+        Ok(written) => Ok(ExportFastqResult {
+            records_written: i64::try_from(written).unwrap_or(i64::MAX),
+            output_path,
+        }),
+        // Leave no partial output behind, e.g. after a cancellation. A file that
+        // cannot be removed is left in place, as the original error matters more.
+        Err(e) => match fs::remove_file(&output_path) {
+            Ok(()) | Err(_) => Err(e),
+        },
+    }
+}
+
+/// Writes the reads selected by `options` to `writer` as FASTQ, returning how
+/// many were written.
+fn write_fastq<W, E>(
+    options: &ReadOptions,
+    shared: Option<&mut IndexedReader>,
+    writer: &mut W,
+    write_error: &E,
+) -> Result<usize>
+where
+    W: Write,
+    E: Fn(std::io::Error) -> Error,
+{
+    let (offset, limit) = validate_pagination(options)?;
+    let interval = progress_interval(options.progress_interval)?;
+    let encoding = qual_offset(options)?;
+    let (mut bam, mut mods, extra) = build_input_options(options)?;
+    let regions = Regions::from_options(options)?;
+
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, _) = reader.records(&mut bam, &mut mods, regions.as_ref())?;
+
+    let filtered = Progress::new(rc_records, options.on_progress.clone(), interval).filter(|r| {
+        r.as_ref()
+            .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    let mut written: usize = 0;
+    for entry in filtered.skip(offset).take(limit) {
+        let record = entry.map_err(|e| {
+            ErrorKind::of_htslib(&e).error(format!("Failed to read BAM record: {e}"))
+        })?;
+        if record.is_secondary() || record.is_supplementary() || record.seq_len() == 0 {
+            continue;
+        }
+        writer
+            .write_all(&fastq_entry(&record, encoding)?)
+            .map_err(write_error)?;
+        written = written.saturating_add(1);
+    }
+    Ok(written)
+}
+
+/// FASTQ entry of `record`, in the orientation the read was sequenced in, for
+/// qualities encoded with `offset`.
+///
+/// # Errors
+/// Returns an error if the qualities are not encoded with `offset`.
+fn fastq_entry(record: &Record, offset: u8) -> Result<Vec<u8>> {
+    let mut seq = record.seq().as_bytes();
+    let mut qual: Vec<u8> = base_quals(record, offset)?
+        .into_iter()
+        .map(|v| if v == u8::MAX { DEFAULT_FASTQ_QUAL } else { v }.saturating_add(b'!'))
+        .collect();
+    if record.is_reverse() {
+        seq.reverse();
+        for base in &mut seq {
+            *base = complement(*base);
+        }
+        qual.reverse();
+    }
+    let qname = record.qname();
+    let mut entry = Vec::with_capacity(
+        qname
+            .len()
+            .saturating_add(seq.len())
+            .saturating_add(qual.len())
+            .saturating_add(6),
+    );
+    entry.push(b'@');
+    entry.extend_from_slice(qname);
+    entry.push(b'\n');
+    entry.extend_from_slice(&seq);
+    entry.extend_from_slice(b"\n+\n");
+    entry.extend_from_slice(&qual);
+    entry.push(b'\n');
+    Ok(entry)
+}
+
+/// Complement of a base, keeping its case. Bases other than A, C, G and T, such
+/// as N, are kept as they are.
+const fn complement(base: u8) -> u8 {
+    match base {
+        b'A' => b'T',
+        b'T' => b'A',
+        b'C' => b'G',
+        b'G' => b'C',
+        b'a' => b't',
+        b't' => b'a',
+        b'c' => b'g',
+        b'g' => b'c',
+        other => other,
+    }
+}
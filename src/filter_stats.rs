@@ -0,0 +1,292 @@
+//! Number of reads left after each read filter, to find the filter that drops them.
+
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use crate::progress::{Progress, progress_interval};
+use crate::read_groups::in_read_groups;
+use crate::records::has_mod_tags;
+use crate::regions::Regions;
+use crate::{ExtraFilters, ReadOptions, build_input_options, has_min_mods, query_reader};
+use nanalogue_core::{BamPreFilt as _, InputBam, InputMods, InputRegionOptions as _, OptionalTag};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::{IndexedReader, Record, ext::BamRecordExtensions as _};
+
+/// Read filters in the order they are checked.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum FilterStage {
+    /// `sample_fraction` and `sample_seed`.
+    Sample,
+    /// `min_seq_len`, `max_seq_len` and `include_zero_len`.
+    Len,
+    /// `mapq_filter`, `mapq_max` and `exclude_mapq_unavail`.
+    Mapq,
+    /// `read_id_set` and `read_id_regex`.
+    ReadId,
+    /// `min_align_len` and `max_align_len`.
+    AlignLen,
+    /// `read_filter`.
+    ReadFilter,
+    /// `region` and `full_region`.
+    Region,
+    /// `exclude_secondary`, `exclude_supplementary`, `exclude_duplicate` and
+    /// `exclude_qc_fail`.
+    Flags,
+    /// `read_group`.
+    ReadGroup,
+    /// `require_mods` and `min_mods`.
+    MinMods,
+    /// `dedupe`.
+    Dedupe,
+}
+
+/// Number of [`FilterStage`]s.
+const STAGES: usize = 11;
+
+impl FilterStage {
+    /// Number of stages checked before this one.
+    const fn index(self) -> usize {
+        match self {
+            Self::Sample => 0,
+            Self::Len => 1,
+            Self::Mapq => 2,
+            Self::ReadId => 3,
+            Self::AlignLen => 4,
+            Self::ReadFilter => 5,
+            Self::Region => 6,
+            Self::Flags => 7,
+            Self::ReadGroup => 8,
+            Self::MinMods => 9,
+            Self::Dedupe => 10,
+        }
+    }
+}
+
+/// The first read filter that a record fails, if any.
+///
+/// The filters of `bam` are those of the core `pre_filt`, checked one at a time
+/// so that the failing one is known; the `extra` filters follow. With `dedupe`,
+/// a record that passes every filter is remembered, so each record should be
+/// checked once.
+pub(crate) fn failed_stage(
+    record: &Record,
+    bam: &InputBam,
+    mods: &InputMods<OptionalTag>,
+    extra: &ExtraFilters,
+) -> Option<FilterStage> {
+    // Sampling comes first and is checked for every record, as in `pre_filt`, so
+    // that unseeded sampling draws the same number of times
+    if !record.filt_random_subset(bam.sample_fraction, bam.sample_seed) {
+        return Some(FilterStage::Sample);
+    }
+    if !record.filt_by_len(bam.min_seq_len, bam.include_zero_len)
+        || extra
+            .max_seq_len
+            .is_some_and(|v| record.seq_len() as u64 > u64::from(v))
+    {
+        return Some(FilterStage::Len);
+    }
+    if !record.filt_by_mapq(bam.mapq_filter, bam.exclude_mapq_unavail)
+        || extra.mapq_max.is_some_and(|v| record.mapq() > v)
+    {
+        return Some(FilterStage::Mapq);
+    }
+    let id_listed = match (bam.read_id.as_ref(), bam.read_id_set.as_ref()) {
+        (Some(v), _) => record.filt_by_read_id(v),
+        (None, Some(v)) => record.filt_by_read_id_set(v),
+        (None, None) => true,
+    };
+    if !id_listed
+        || extra
+            .read_id_regex
+            .as_ref()
+            .is_some_and(|v| !v.is_match(record.qname()))
+    {
+        return Some(FilterStage::ReadId);
+    }
+    if bam
+        .min_align_len
+        .is_some_and(|v| !record.filt_by_align_len(v))
+        || extra.max_align_len.is_some_and(|v| {
+            record.is_unmapped()
+                || record.pos() < 0
+                || record.reference_end().saturating_sub(record.pos()) > i64::from(v)
+        })
+    {
+        return Some(FilterStage::AlignLen);
+    }
+    if bam
+        .read_filter
+        .as_ref()
+        .is_some_and(|v| !record.filt_by_bitwise_or_flags(v))
+    {
+        return Some(FilterStage::ReadFilter);
+    }
+    if bam
+        .region_filter()
+        .as_ref()
+        .is_some_and(|v| !record.filt_by_region(v, bam.is_full_overlap()))
+    {
+        return Some(FilterStage::Region);
+    }
+    if u32::from(record.flags()) & extra.excluded_flags != 0 {
+        return Some(FilterStage::Flags);
+    }
+    if extra
+        .read_group
+        .as_ref()
+        .is_some_and(|v| !in_read_groups(record, v))
+    {
+        return Some(FilterStage::ReadGroup);
+    }
+    if (extra.require_mods && !has_mod_tags(record))
+        || extra
+            .min_mods
+            .is_some_and(|v| !has_min_mods(record, mods, extra.mod_selection(), v))
+    {
+        return Some(FilterStage::MinMods);
+    }
+    if extra
+        .seen_read_ids
+        .as_ref()
+        .is_some_and(|v| !v.borrow_mut().insert(record.qname().to_vec()))
+    {
+        return Some(FilterStage::Dedupe);
+    }
+    None
+}
+
+/// Number of reads left after each read filter, applied in this order.
+///
+/// Each count includes only the reads that passed every earlier filter, so the
+/// filter that drops reads is the one after which the count falls. Filters that
+/// are not set leave the count unchanged.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct FilterStats {
+    /// Records read, from the regions if any are given.
+    pub total: i64,
+    /// Reads left after `sample_fraction`.
+    #[napi(js_name = "after_sample")]
+    pub after_sample: i64,
+    /// Reads left after `min_seq_len`, `max_seq_len` and `include_zero_len`.
+    #[napi(js_name = "after_len")]
+    pub after_len: i64,
+    /// Reads left after `mapq_filter`, `mapq_max` and `exclude_mapq_unavail`.
+    #[napi(js_name = "after_mapq")]
+    pub after_mapq: i64,
+    /// Reads left after `read_id_set` and `read_id_regex`.
+    #[napi(js_name = "after_read_id")]
+    pub after_read_id: i64,
+    /// Reads left after `min_align_len` and `max_align_len`.
+    #[napi(js_name = "after_align_len")]
+    pub after_align_len: i64,
+    /// Reads left after `read_filter`.
+    #[napi(js_name = "after_read_filter")]
+    pub after_read_filter: i64,
+    /// Reads left after `region` with `full_region`.
+    #[napi(js_name = "after_region")]
+    pub after_region: i64,
+    /// Reads left after the `exclude_*` SAM flag filters.
+    #[napi(js_name = "after_flags")]
+    pub after_flags: i64,
+    /// Reads left after `read_group`.
+    #[napi(js_name = "after_read_group")]
+    pub after_read_group: i64,
+    /// Reads left after `require_mods` and `min_mods`.
+    #[napi(js_name = "after_min_mods")]
+    pub after_min_mods: i64,
+    /// Reads left after `dedupe`: the reads that pass every filter, as counted by
+    /// `count_reads`.
+    #[napi(js_name = "after_dedupe")]
+    pub after_dedupe: i64,
+    /// Reads among those that pass every filter without modification data, that
+    /// is without MM and ML tags, whose `mod_table` is empty whatever the
+    /// modification filters. Always 0 with `require_mods`.
+    #[napi(js_name = "without_mod_data")]
+    pub without_mod_data: i64,
+}
+
+impl From<[i64; STAGES + 1]> for FilterStats {
+    fn from(counts: [i64; STAGES + 1]) -> Self {
+        let [
+            total,
+            after_sample,
+            after_len,
+            after_mapq,
+            after_read_id,
+            after_align_len,
+            after_read_filter,
+            after_region,
+            after_flags,
+            after_read_group,
+            after_min_mods,
+            after_dedupe,
+        ] = counts;
+        Self {
+            total,
+            after_sample,
+            after_len,
+            after_mapq,
+            after_read_id,
+            after_align_len,
+            after_read_filter,
+            after_region,
+            after_flags,
+            after_read_group,
+            after_min_mods,
+            after_dedupe,
+            without_mod_data: 0,
+        }
+    }
+}
+
+/// Counts the reads left after each read filter.
+///
+/// Takes the options of `read_info` and reads every record, checking the
+/// filters one at a time, so users can see which filter drops their reads.
+///
+/// # Errors
+/// Returns an error if input options are invalid, `limit` or `offset` is set, or
+/// BAM reading fails.
+#[napi]
+pub async fn filter_stats(options: ReadOptions) -> Result<FilterStats> {
+    run_limited(move || filter_stats_sync(&options, None)).await
+}
+
+/// Synchronous implementation of `filter_stats`.
+pub(crate) fn filter_stats_sync(
+    options: &ReadOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<FilterStats> {
+    if options.limit.is_some() || options.offset.is_some() {
+        return Err(ErrorKind::InvalidOptions
+            .error("filterStats counts every read; limit and offset are not supported"));
+    }
+    let interval = progress_interval(options.progress_interval)?;
+    let (mut bam, mut mods, extra) = build_input_options(options)?;
+    let regions = Regions::from_options(options)?;
+
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, _) = reader.records(&mut bam, &mut mods, regions.as_ref())?;
+
+    let mut counts = [0i64; STAGES + 1];
+    let mut without_mod_data = 0i64;
+    for entry in Progress::new(rc_records, options.on_progress.clone(), interval) {
+        let record = entry.map_err(|e| {
+            ErrorKind::of_htslib(&e).error(format!("Failed to read BAM record: {e}"))
+        })?;
+        let passed = failed_stage(&record, &bam, &mods, &extra).map_or(STAGES, FilterStage::index);
+        for count in counts.iter_mut().take(passed.saturating_add(1)) {
+            *count = count.saturating_add(1);
+        }
+        if passed == STAGES && !has_mod_tags(&record) {
+            without_mod_data = without_mod_data.saturating_add(1);
+        }
+    }
+    Ok(FilterStats {
+        without_mod_data,
+        ..counts.into()
+    })
+}
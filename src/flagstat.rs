@@ -0,0 +1,163 @@
+//! Counts of reads by SAM flag category, as `samtools flagstat`.
+
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use crate::progress::{Progress, progress_interval};
+use crate::regions::Regions;
+use crate::{
+    ReadOptions, build_input_options, passes_read_filters, query_reader, validate_pagination,
+};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::{IndexedReader, Record};
+
+/// Read counts per alignment category.
+///
+/// Categories are those of `samtools flagstat`, with QC-passed and QC-failed
+/// reads counted together; `qc_failed` gives how many of them failed. The pair
+/// categories count primary alignments only.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug, Default)]
+pub struct Flagstat {
+    /// Number of reads.
+    pub total: i64,
+    /// Reads that failed quality checks (flag 0x200).
+    #[napi(js_name = "qc_failed")]
+    pub qc_failed: i64,
+    /// Primary alignments, neither secondary nor supplementary.
+    pub primary: i64,
+    /// Secondary alignments (flag 0x100).
+    pub secondary: i64,
+    /// Supplementary alignments (flag 0x800).
+    pub supplementary: i64,
+    /// Duplicates (flag 0x400).
+    pub duplicates: i64,
+    /// Primary alignments that are duplicates.
+    #[napi(js_name = "primary_duplicates")]
+    pub primary_duplicates: i64,
+    /// Mapped reads.
+    pub mapped: i64,
+    /// Mapped primary alignments.
+    #[napi(js_name = "primary_mapped")]
+    pub primary_mapped: i64,
+    /// Primary alignments of paired reads (flag 0x1).
+    pub paired: i64,
+    /// Paired primary alignments of the first read of the pair (flag 0x40).
+    pub read1: i64,
+    /// Paired primary alignments of the last read of the pair (flag 0x80).
+    pub read2: i64,
+    /// Mapped paired primary alignments that are properly paired (flag 0x2).
+    #[napi(js_name = "properly_paired")]
+    pub properly_paired: i64,
+    /// Mapped paired primary alignments whose mate is mapped too.
+    #[napi(js_name = "with_mate_mapped")]
+    pub with_mate_mapped: i64,
+    /// Mapped paired primary alignments whose mate is unmapped.
+    pub singletons: i64,
+    /// Mapped paired primary alignments whose mate is mapped to another contig.
+    #[napi(js_name = "mate_on_other_contig")]
+    pub mate_on_other_contig: i64,
+    /// Those of `mate_on_other_contig` with a mapping quality of at least 5.
+    #[napi(js_name = "mate_on_other_contig_mapq5")]
+    pub mate_on_other_contig_mapq5: i64,
+}
+
+impl Flagstat {
+    /// Adds `record` to the counts of its categories.
+    fn add(&mut self, record: &Record) {
+        /// Adds one to a count.
+        fn inc(count: &mut i64) {
+            *count = count.saturating_add(1);
+        }
+        let mapped = !record.is_unmapped();
+        inc(&mut self.total);
+        if record.is_quality_check_failed() {
+            inc(&mut self.qc_failed);
+        }
+        if record.is_secondary() {
+            inc(&mut self.secondary);
+        } else if record.is_supplementary() {
+            inc(&mut self.supplementary);
+        } else {
+            inc(&mut self.primary);
+            if record.is_paired() {
+                inc(&mut self.paired);
+                if record.is_proper_pair() && mapped {
+                    inc(&mut self.properly_paired);
+                }
+                if record.is_first_in_template() {
+                    inc(&mut self.read1);
+                }
+                if record.is_last_in_template() {
+                    inc(&mut self.read2);
+                }
+                if mapped && record.is_mate_unmapped() {
+                    inc(&mut self.singletons);
+                }
+                if mapped && !record.is_mate_unmapped() {
+                    inc(&mut self.with_mate_mapped);
+                    if record.mtid() != record.tid() {
+                        inc(&mut self.mate_on_other_contig);
+                        if record.mapq() >= 5 {
+                            inc(&mut self.mate_on_other_contig_mapq5);
+                        }
+                    }
+                }
+            }
+            if mapped {
+                inc(&mut self.primary_mapped);
+            }
+            if record.is_duplicate() {
+                inc(&mut self.primary_duplicates);
+            }
+        }
+        if mapped {
+            inc(&mut self.mapped);
+        }
+        if record.is_duplicate() {
+            inc(&mut self.duplicates);
+        }
+    }
+}
+
+/// Counts reads by alignment category from their SAM flags, as `samtools flagstat`.
+///
+/// Reads are those that `read_info` returns for the same options, so read filters
+/// apply, and `offset` and `limit` count reads. Unlike `samtools flagstat`, this
+/// leaves out zero-length records, such as most secondary alignments, unless
+/// `include_zero_len` is set.
+///
+/// # Errors
+/// Returns an error if input options are invalid or BAM reading fails.
+#[napi]
+pub async fn flagstat(options: ReadOptions) -> Result<Flagstat> {
+    run_limited(move || flagstat_sync(&options, None)).await
+}
+
+/// Synchronous implementation of `flagstat`.
+pub(crate) fn flagstat_sync(
+    options: &ReadOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<Flagstat> {
+    let (offset, limit) = validate_pagination(options)?;
+    let interval = progress_interval(options.progress_interval)?;
+    let (mut bam, mut mods, extra) = build_input_options(options)?;
+    let regions = Regions::from_options(options)?;
+
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, _) = reader.records(&mut bam, &mut mods, regions.as_ref())?;
+
+    let filtered = Progress::new(rc_records, options.on_progress.clone(), interval).filter(|r| {
+        r.as_ref()
+            .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    let mut stats = Flagstat::default();
+    for entry in filtered.skip(offset).take(limit) {
+        let record = entry.map_err(|e| {
+            ErrorKind::of_htslib(&e).error(format!("Failed to read BAM record: {e}"))
+        })?;
+        stats.add(&record);
+    }
+    Ok(stats)
+}
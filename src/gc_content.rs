@@ -0,0 +1,127 @@
+//! GC content of each read, for run quality control.
+
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use crate::progress::{Progress, progress_interval};
+use crate::regions::{read_positions, region_bed3};
+use crate::{
+    ReadOptions, build_input_options, passes_read_filters, query_reader, validate_pagination,
+};
+use bedrs::Bed3;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::{IndexedReader, Record};
+
+/// GC content of one read, from `gc_content`.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct GcContent {
+    /// Read id.
+    #[napi(js_name = "read_id")]
+    pub read_id: String,
+    /// Fraction of the bases that are G or C, out of the A, C, G and T bases,
+    /// so that N bases do not count. Absent if there are none.
+    #[napi(js_name = "gc_fraction")]
+    pub gc_fraction: Option<f64>,
+    /// Number of bases that are neither A, C, G nor T, such as N.
+    #[napi(js_name = "n_count")]
+    pub n_count: u32,
+}
+
+impl GcContent {
+    /// Counts the bases of `record`, over `region` if given.
+    ///
+    /// Returns `None` for a record without sequence, or, over a region, without
+    /// bases aligned in it.
+    ///
+    /// # Errors
+    /// Returns an error if the alignment of the record is invalid.
+    fn from_record(record: &Record, region: Option<&Bed3<i32, u64>>) -> Result<Option<Self>> {
+        let seq = record.seq().as_bytes();
+        if seq.is_empty() {
+            return Ok(None);
+        }
+        let bases: Vec<u8> = match region {
+            None => seq,
+            Some(bed3) => {
+                let Some(positions) = read_positions(record, bed3, "gc_content")? else {
+                    return Ok(None);
+                };
+                // Deletions have no base of the read
+                positions
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|i| seq.get(i).copied())
+                    .collect()
+            }
+        };
+        if bases.is_empty() {
+            return Ok(None);
+        }
+        let (mut gc, mut at, mut other) = (0u32, 0u32, 0u32);
+        for base in bases {
+            let count = match base.to_ascii_uppercase() {
+                b'G' | b'C' => &mut gc,
+                b'A' | b'T' => &mut at,
+                _ => &mut other,
+            };
+            *count = count.saturating_add(1);
+        }
+        let called = gc.saturating_add(at);
+        Ok(Some(Self {
+            read_id: String::from_utf8_lossy(record.qname()).into_owned(),
+            gc_fraction: (called > 0).then(|| f64::from(gc) / f64::from(called)),
+            n_count: other,
+        }))
+    }
+}
+
+/// Returns the GC content of each read.
+///
+/// With `region` set, only the bases of each read aligned in it count, inserted
+/// bases included, so that GC is measured over the aligned portion rather than
+/// the whole read. Reads that only overlap part of the region are kept unless
+/// `full_region` is set. Reads without bases are left out after `offset` and
+/// `limit` are applied.
+///
+/// # Errors
+/// Returns an error if `regions` or `region_bed` is set, input options are
+/// invalid or BAM reading fails.
+#[napi]
+pub async fn gc_content(options: ReadOptions) -> Result<Vec<GcContent>> {
+    run_limited(move || gc_content_sync(&options, None)).await
+}
+
+/// Synchronous implementation of `gc_content`.
+pub(crate) fn gc_content_sync(
+    options: &ReadOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<Vec<GcContent>> {
+    let (offset, limit) = validate_pagination(options)?;
+    let interval = progress_interval(options.progress_interval)?;
+
+    if options.regions.is_some() || options.region_bed.is_some() {
+        return Err(ErrorKind::InvalidOptions
+            .error("gcContent takes a single region; regions and regionBed are not supported"));
+    }
+
+    let (mut bam, mut mods, extra) = build_input_options(options)?;
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, header) = reader.records(&mut bam, &mut mods, None)?;
+    let region = region_bed3(options, &header)?;
+
+    let filtered = Progress::new(rc_records, options.on_progress.clone(), interval).filter(|r| {
+        r.as_ref()
+            .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    let mut contents = Vec::new();
+    for entry in filtered.skip(offset).take(limit) {
+        let record =
+            entry.map_err(|e| ErrorKind::of_htslib(&e).error(format!("gc_content failed: {e}")))?;
+        if let Some(v) = GcContent::from_record(&record, region.as_ref())? {
+            contents.push(v);
+        }
+    }
+    Ok(contents)
+}
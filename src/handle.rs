@@ -0,0 +1,1102 @@
+//! A BAM file kept open across queries.
+
+use crate::align_stats::{AlignStats, align_stats_sync};
+use crate::aligned_sequences::{AlignedSequence, aligned_sequences_sync};
+use crate::annotate::{Feature, ReadAnnotation, annotate_reads_sync};
+use crate::bedgraph::export_bedgraph_sync;
+use crate::concurrency::run_limited;
+use crate::contig_summary::{ContigSummary, contig_summary_sync};
+use crate::count::count_reads_sync;
+use crate::coverage::{CoverageRecord, coverage_sync};
+use crate::error::ErrorKind;
+use crate::export::{ExportBamResult, ExportFastqResult, export_bam_sync, export_fastq_sync};
+use crate::filter_stats::{FilterStats, filter_stats_sync};
+use crate::flagstat::{Flagstat, flagstat_sync};
+use crate::gc_content::{GcContent, gc_content_sync};
+use crate::idxstats::{IdxStat, estimate_read_count_of, idxstats_of};
+use crate::mapq_histogram::mapq_histogram_sync;
+use crate::mod_counts::{ContigModCount, mod_counts_by_contig_sync};
+use crate::mod_histogram::{ThresholdSuggestion, mod_qual_histogram_sync, suggest_threshold_sync};
+use crate::mod_summary::{ModSummary, mod_summary_sync};
+use crate::peek_reads::{PeekWithReads, peek_with_reads_sync};
+use crate::pileup::{PileupOptions, PileupRecord, pileup_sync};
+use crate::quality_profiles::{QualityProfile, quality_profiles_sync};
+use crate::read_groups::read_group_ids;
+use crate::thread_pool;
+use crate::url_open::UrlOpen;
+use crate::window_aggregate::{WindowBin, window_aggregate_sync};
+use crate::window_arrays::{WindowArrays, window_reads_arrays_sync};
+use crate::window_matrix::{WindowMatrix, window_matrix_sync};
+use crate::{
+    CancelFlag, EdgeMode, ModCall, ModStrand, ProgressCallback, ReadFilter, ReadInfoRecord,
+    ReadOptions, SeqTableRecord, SortBy, WinOp, WindowCoordinate, WindowFormat, WindowOptions,
+    bam_mods_json_sync, bam_mods_sync, bam_mods_typed_sync, get_read_sync, is_cram, load_bam,
+    read_info_json_sync, read_info_sync, reads_table_sync, seq_table_records_sync, seq_table_sync,
+    window_reads_sync,
+};
+use nanalogue_core::InputBam;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::{IndexedReader, Read as _};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Options for opening a [`NanalogueBam`].
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug, Default)]
+pub struct NanalogueBamOptions {
+    /// Path to the BAM file (local path or URL).
+    pub bam_path: String,
+    /// If true, treat `bam_path` as a URL, if false as a file path. If unset, paths
+    /// starting with `http://`, `https://`, `s3://`, `gs://` or `file://` are URLs.
+    /// A `file://` URL is read as the local file it names.
+    pub treat_as_url: Option<bool>,
+    /// Number of threads for BAM reading, shared by all queries. Unset, the
+    /// reader uses the pool of [`crate::init_thread_pool`] if it has been started.
+    pub threads: Option<u8>,
+    /// Reference FASTA used to decode CRAM input, which cannot be read without it.
+    /// Ignored for BAM input.
+    pub reference_fasta: Option<String>,
+    /// Times to retry opening a URL after a failed attempt (default: 0). The wait
+    /// between attempts starts at 100 ms and doubles each time, up to 5 s. Ignored
+    /// for local files.
+    pub url_retries: Option<u32>,
+    /// Time limit of each attempt at opening a URL, in milliseconds. Must be at
+    /// least 1. Unset means no limit. An attempt that times out is not retried.
+    /// Ignored for local files.
+    pub url_timeout_ms: Option<u32>,
+    /// Extra HTTP request headers sent when opening a URL and reading from it, e.g.
+    /// `Authorization` or `User-Agent`. Only `http://` and `https://` URLs take
+    /// headers; other URLs are rejected. Ignored for local files.
+    pub url_headers: Option<HashMap<String, String>>,
+    /// Build a `.bai` index next to a local BAM file that has none, with a
+    /// warning, before opening it; the file must be sorted by coordinate. If not
+    /// set, such a file is rejected with an `IndexMissing` error. Defaults to
+    /// false.
+    pub auto_index: Option<bool>,
+}
+
+/// Options of the read queries of a [`NanalogueBam`]: those of [`ReadOptions`]
+/// without the location of the file and the reading settings, which are given
+/// to the constructor.
+#[napi(object, object_to_js = false)]
+#[non_exhaustive]
+#[derive(Debug, Default, Clone)]
+pub struct NanalogueBamReadOptions {
+    /// Minimum sequence length filter.
+    pub min_seq_len: Option<u32>,
+    /// Maximum sequence length filter. Must be >= `min_seq_len` if both are set.
+    pub max_seq_len: Option<u32>,
+    /// Minimum alignment length filter.
+    pub min_align_len: Option<i32>,
+    /// Maximum alignment length filter. Must be >= `min_align_len` if both are set.
+    /// Unmapped reads are excluded when it is set, as with `min_align_len`.
+    pub max_align_len: Option<i32>,
+    /// Filter to a set of read IDs.
+    pub read_id_set: Option<Vec<String>>,
+    /// Keep reads whose id matches this regular expression, anywhere in the id
+    /// unless anchored (e.g. `^run1_`). With `read_id_set`, reads must satisfy both.
+    pub read_id_regex: Option<String>,
+    /// Keep reads whose `RG` tag is one of these read group ids, as listed by
+    /// `read_groups`. Reads without the tag are excluded.
+    pub read_group: Option<Vec<String>>,
+    /// Include records without a sequence, such as most secondary alignments. They
+    /// have a sequence length of 0 and no modification data, and are skipped when
+    /// windowing.
+    pub include_zero_len: Option<bool>,
+    /// Alignment types to keep, as a list or a comma-separated string
+    /// (e.g., `primary_forward,primary_reverse`).
+    pub read_filter: Option<ReadFilter>,
+    /// Subsample fraction (0.0 to 1.0).
+    pub sample_fraction: Option<f64>,
+    /// Minimum mapping quality filter.
+    pub mapq_filter: Option<u8>,
+    /// Maximum mapping quality filter. Must be >= `mapq_filter` if both are set.
+    /// Reads with unavailable mapping quality (255) are excluded unless this is 255.
+    pub mapq_max: Option<u8>,
+    /// Exclude reads with unavailable mapping quality.
+    pub exclude_mapq_unavail: Option<bool>,
+    /// Exclude secondary alignments (SAM flag 0x100).
+    pub exclude_secondary: Option<bool>,
+    /// Exclude supplementary alignments (SAM flag 0x800).
+    pub exclude_supplementary: Option<bool>,
+    /// Exclude reads marked as PCR or optical duplicates (SAM flag 0x400), which
+    /// `nanalogue_core` cannot otherwise read.
+    pub exclude_duplicate: Option<bool>,
+    /// Exclude reads that failed quality checks (SAM flag 0x200), which
+    /// `nanalogue_core` cannot otherwise read.
+    pub exclude_qc_fail: Option<bool>,
+    /// Return every record of `read_info`, `read_info_stream` and `bam_mods`, also
+    /// those the read filters would leave out, each with `passed_prefilter` telling
+    /// whether it passes them. Other queries still filter. Defaults to false.
+    pub no_prefilter: Option<bool>,
+    /// Keep only the first record of each read id that passes the other filters,
+    /// so that a read with several alignments, such as a supplementary alignment
+    /// in another of the `regions`, is counted once. Defaults to false.
+    pub dedupe: Option<bool>,
+    /// Minimum number of modified calls per read: calls that pass the modification
+    /// filters with a probability of at least 0.5, as counted in `mod_count`.
+    pub min_mods: Option<u32>,
+    /// Keep only reads with modification data, that is with MM and ML tags, so
+    /// that reads without them do not pass as reads without calls. Defaults to
+    /// false.
+    pub require_mods: Option<bool>,
+    /// Add a `has_mod_data` field to each read of `bam_mods` and `bam_mods_json`,
+    /// false for a read without MM and ML tags, to tell it apart from a read whose
+    /// calls the modification filters all leave out, as both have an empty
+    /// `mod_table`. Other queries ignore it. Defaults to false.
+    pub report_mod_data: Option<bool>,
+    /// Genomic region filter (e.g., "chr1:1000-2000").
+    pub region: Option<String>,
+    /// Several genomic regions to query in one call, as an alternative to `region`.
+    /// A read overlapping more than one region is returned once, except by
+    /// `seq_table`, `seq_table_records` and `reads_table`, which give the table
+    /// of each region in turn.
+    pub regions: Option<Vec<String>>,
+    /// Path to a BED file whose intervals are queried as `regions`.
+    /// Only the first three columns are read.
+    pub region_bed: Option<String>,
+    /// Only include reads fully spanning the region (or one of the `regions`).
+    pub full_region: Option<bool>,
+    /// Read the coordinates of `region`, `regions` and `mod_region` as 1-based
+    /// and inclusive, as samtools does, instead of 0-based and half-open (the
+    /// default). `region_bed` files are always 0-based.
+    pub one_based: Option<bool>,
+    /// Filter to specific modification tag.
+    pub tag: Option<String>,
+    /// Several modification tags whose calls are all kept, e.g. `["m", "h"]` for
+    /// 5mC and 5hmC together, as an alternative to `tag`.
+    pub tags: Option<Vec<String>>,
+    /// Filter by modification strand.
+    pub mod_strand: Option<ModStrand>,
+    /// Minimum modification quality threshold.
+    pub min_mod_qual: Option<u8>,
+    /// Reject modification calls where low < probability < high.
+    /// Array of [low, high] where both are 0-255.
+    pub reject_mod_qual_non_inclusive: Option<Vec<u8>>,
+    /// Keep modification calls with a probability of at least this (0 to 1), e.g.
+    /// 0.8 for calls of ML value 204 and above. Overrides `min_mod_qual` and
+    /// `reject_mod_qual_non_inclusive`.
+    pub mod_threshold: Option<f64>,
+    /// Lowest ML value (0-255) of the calls kept per modification code, e.g.
+    /// `{ m: 200, h: 150 }` to call 5mC and 5hmC at their own cutoffs. Applies on
+    /// top of the other modification filters; codes not listed are unaffected.
+    /// Not available for windowing.
+    pub mod_thresholds: Option<HashMap<String, u8>>,
+    /// Trim modification info from read ends (bp).
+    pub trim_read_ends_mod: Option<u32>,
+    /// Base quality filter for modifications.
+    pub base_qual_filter_mod: Option<u8>,
+    /// Genomic region for modification filtering.
+    pub mod_region: Option<String>,
+    /// Maximum number of records to return. Must be > 0 if set. If omitted, returns all records.
+    pub limit: Option<i64>,
+    /// Number of records that pass the filters to skip before returning results. Must be >= 0
+    /// if set. Defaults to 0.
+    pub offset: Option<i64>,
+    /// Seed for deterministic sampling. Required for stable pagination with `sample_fraction`.
+    /// Must be non-negative if set. If omitted, each call samples a different subset.
+    pub sample_seed: Option<i64>,
+    /// Order of the reads listed by `read_info` and `bam_mods` (and their JSON and
+    /// typed forms). Sorting reads every read that passes the filters into memory
+    /// before the first is returned; `offset` and `limit` then apply to the sorted
+    /// reads. Not supported by `read_info_stream`. If unset, reads are listed in
+    /// file order.
+    pub sort_by: Option<SortBy>,
+    /// Called with the number of BAM records processed so far (`read_info`, `bam_mods` and `export_bam` only).
+    pub on_progress: Option<ProgressCallback>,
+    /// Number of records between `on_progress` calls. Must be > 0 if set. Defaults to 1000.
+    pub progress_interval: Option<u32>,
+    /// Columns of the `seq_table` output, in order (`seq_table` only). Defaults to
+    /// `read_id`, `sequence` and `qualities`.
+    pub columns: Option<Vec<String>>,
+    /// If true, show reads aligned to the minus strand reverse-complemented, in the
+    /// orientation they were sequenced in (`seq_table`, `seq_table_records` and
+    /// `reads_table` only). Defaults to false: all reads in the orientation of the
+    /// reference.
+    pub reverse_complement_minus: Option<bool>,
+    /// If false, leave the base qualities out of the sequence table: the
+    /// `qualities` column of `seq_table` and `reads_table`, which `columns` then
+    /// cannot list, and the `qualities` field of `seq_table_records`. Defaults to
+    /// true.
+    pub show_base_qual: Option<bool>,
+    /// If false, show insertions in the sequence table in upper case like the
+    /// other bases. Defaults to true: lower case.
+    pub show_insertions: Option<bool>,
+    /// If false, show modified bases in the sequence table as their base instead
+    /// of as `Z` (or `z` in an insertion). Defaults to true.
+    pub show_mod_markers: Option<bool>,
+    /// Offset of the base quality encoding, 33 for Phred+33 or 64 for the
+    /// Phred+64 of some legacy data (`quality_profiles`, `seq_table`,
+    /// `seq_table_records`, `reads_table` and `export_fastq` only). Defaults to
+    /// 33. Qualities are given as phred values whatever the encoding.
+    pub qual_offset: Option<u8>,
+    /// Most reads the sequence table has rows for (`seq_table`,
+    /// `seq_table_records` and `reads_table` only), such as for a preview of a
+    /// dense region. Must be at least 1. Reading stops at the first record of a
+    /// read past them, after any `offset`, so the table is not built in full.
+    pub max_reads: Option<u32>,
+    /// If true, indent each read of the `bam_mods_json` output over several lines
+    /// (`bam_mods_json` only). Defaults to false: one line per read.
+    pub pretty: Option<bool>,
+}
+
+/// Options of the windowing queries of a [`NanalogueBam`]: those of
+/// [`WindowOptions`] without the location of the file and the reading settings,
+/// which are given to the constructor.
+#[napi(object, object_to_js = false)]
+#[non_exhaustive]
+#[derive(Debug, Default)]
+pub struct NanalogueBamWindowOptions {
+    /// Window size in number of calls, or of reference bases with
+    /// [`WindowCoordinate::Reference`].
+    pub win: i32,
+    /// Step size for sliding the window.
+    pub step: i32,
+    /// Type of windowing operation. Defaults to [`WinOp::Density`].
+    pub win_op: Option<WinOp>,
+    /// Format of the output. Defaults to [`WindowFormat::Json`].
+    pub output_format: Option<WindowFormat>,
+    /// Windows with fewer valid calls than this are left out of the output.
+    pub min_window_calls: Option<u32>,
+    /// Coordinates along which reads are windowed. Defaults to
+    /// [`WindowCoordinate::Read`].
+    pub coordinate: Option<WindowCoordinate>,
+    /// What to do with the calls of a read past its last window of `win` calls.
+    /// Defaults to [`EdgeMode::Drop`]. Ignored with [`WindowCoordinate::Reference`],
+    /// whose windows at the ends of a read already hold whatever calls fall in them.
+    pub edge_mode: Option<EdgeMode>,
+    /// Window the reads on several threads. The selected reads are all read into
+    /// memory first, and the output is in the same order as without it.
+    /// Defaults to false. Only honoured by `window_reads`.
+    pub parallel: Option<bool>,
+    // BAM filtering options (duplicated from ReadOptions due to NAPI-RS limitation)
+    /// Minimum sequence length filter.
+    pub min_seq_len: Option<u32>,
+    /// Maximum sequence length filter. Must be >= `min_seq_len` if both are set.
+    pub max_seq_len: Option<u32>,
+    /// Minimum alignment length filter.
+    pub min_align_len: Option<i32>,
+    /// Maximum alignment length filter. Must be >= `min_align_len` if both are set.
+    /// Unmapped reads are excluded when it is set, as with `min_align_len`.
+    pub max_align_len: Option<i32>,
+    /// Filter to a set of read IDs.
+    pub read_id_set: Option<Vec<String>>,
+    /// Keep reads whose id matches this regular expression, anywhere in the id
+    /// unless anchored (e.g. `^run1_`). With `read_id_set`, reads must satisfy both.
+    pub read_id_regex: Option<String>,
+    /// Keep reads whose `RG` tag is one of these read group ids, as listed by
+    /// `read_groups`. Reads without the tag are excluded.
+    pub read_group: Option<Vec<String>>,
+    /// Include records without a sequence, such as most secondary alignments. They
+    /// have a sequence length of 0 and no modification data, and are skipped when
+    /// windowing.
+    pub include_zero_len: Option<bool>,
+    /// Alignment types to keep, as a list or a comma-separated string.
+    pub read_filter: Option<ReadFilter>,
+    /// Subsample fraction (0.0 to 1.0).
+    pub sample_fraction: Option<f64>,
+    /// Minimum mapping quality filter.
+    pub mapq_filter: Option<u8>,
+    /// Maximum mapping quality filter. Must be >= `mapq_filter` if both are set.
+    /// Reads with unavailable mapping quality (255) are excluded unless this is 255.
+    pub mapq_max: Option<u8>,
+    /// Exclude reads with unavailable mapping quality.
+    pub exclude_mapq_unavail: Option<bool>,
+    /// Exclude secondary alignments (SAM flag 0x100).
+    pub exclude_secondary: Option<bool>,
+    /// Exclude supplementary alignments (SAM flag 0x800).
+    pub exclude_supplementary: Option<bool>,
+    /// Exclude reads marked as PCR or optical duplicates (SAM flag 0x400), which
+    /// `nanalogue_core` cannot otherwise read.
+    pub exclude_duplicate: Option<bool>,
+    /// Exclude reads that failed quality checks (SAM flag 0x200), which
+    /// `nanalogue_core` cannot otherwise read.
+    pub exclude_qc_fail: Option<bool>,
+    /// Return every record of `read_info` and `bam_mods`, also those the read
+    /// filters would leave out, each with `passed_prefilter` telling whether it
+    /// passes them. Other queries still filter. Defaults to false.
+    pub no_prefilter: Option<bool>,
+    /// Keep only the first record of each read id that passes the other filters,
+    /// so that a read with several alignments, such as a supplementary alignment
+    /// in another of the `regions`, is counted once. Defaults to false.
+    pub dedupe: Option<bool>,
+    /// Minimum number of modified calls per read: calls that pass the modification
+    /// filters with a probability of at least 0.5, as counted in `mod_count`.
+    pub min_mods: Option<u32>,
+    /// Keep only reads with modification data, that is with MM and ML tags.
+    /// Defaults to false.
+    pub require_mods: Option<bool>,
+    /// Genomic region filter.
+    pub region: Option<String>,
+    /// Several genomic regions to query in one call, as an alternative to `region`.
+    pub regions: Option<Vec<String>>,
+    /// Path to a BED file whose intervals are queried as `regions`.
+    /// Only the first three columns are read.
+    pub region_bed: Option<String>,
+    /// Only include reads fully spanning the region (or one of the `regions`).
+    pub full_region: Option<bool>,
+    /// Read the coordinates of `region`, `regions` and `mod_region` as 1-based
+    /// and inclusive instead of 0-based and half-open (the default).
+    pub one_based: Option<bool>,
+    // Mod options
+    /// Filter to specific modification tag.
+    pub tag: Option<String>,
+    /// Several modification tags whose calls are all kept, e.g. `["m", "h"]` for
+    /// 5mC and 5hmC together, as an alternative to `tag`.
+    pub tags: Option<Vec<String>>,
+    /// Filter by modification strand.
+    pub mod_strand: Option<ModStrand>,
+    /// Minimum modification quality threshold.
+    pub min_mod_qual: Option<u8>,
+    /// Reject modification calls where low < probability < high.
+    /// Array of [low, high] where both are 0-255.
+    pub reject_mod_qual_non_inclusive: Option<Vec<u8>>,
+    /// Keep modification calls with a probability of at least this (0 to 1), e.g.
+    /// 0.8 for calls of ML value 204 and above. Overrides `min_mod_qual` and
+    /// `reject_mod_qual_non_inclusive`.
+    pub mod_threshold: Option<f64>,
+    /// Trim modification info from read ends (bp).
+    pub trim_read_ends_mod: Option<u32>,
+    /// Base quality filter for modifications.
+    pub base_qual_filter_mod: Option<u8>,
+    /// Genomic region for modification filtering.
+    pub mod_region: Option<String>,
+    /// Maximum number of records to return. Must be > 0 if set. If omitted, returns all records.
+    pub limit: Option<i64>,
+    /// Number of records that pass the filters to skip before returning results. Must be >= 0
+    /// if set. Defaults to 0.
+    pub offset: Option<i64>,
+    /// Seed for deterministic sampling. Required for stable pagination with `sample_fraction`.
+    /// Must be non-negative if set. If omitted, each call samples a different subset.
+    pub sample_seed: Option<i64>,
+    /// Token to cancel the windowing before it finishes.
+    pub cancel_token: Option<CancelFlag>,
+    /// File to write the output to instead of returning it, for outputs too large
+    /// to hold in a string. The call then resolves to the number of lines written.
+    pub output_path: Option<String>,
+    /// Gzip-compress the file written to `output_path`, adding a `.gz` extension
+    /// to the path if it does not have one.
+    pub compress: Option<bool>,
+}
+
+/// A BAM file kept open across queries.
+///
+/// The file (and its index and header) is opened once in the constructor; each
+/// query re-fetches its region on the same reader instead of reopening the file,
+/// which matters most for remote files. Queries on one instance run one at a time.
+#[napi]
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct NanalogueBam {
+    /// Path or URL the reader was opened from.
+    bam_path: String,
+    /// Whether `bam_path` is a URL.
+    treat_as_url: Option<bool>,
+    /// The open reader, shared with the blocking tasks that run queries.
+    reader: Arc<Mutex<IndexedReader>>,
+}
+
+#[napi]
+impl NanalogueBam {
+    /// Opens the BAM file and its index.
+    ///
+    /// # Errors
+    /// Returns an error if the path/URL is invalid or the file, index, or header
+    /// cannot be read.
+    #[napi(constructor)]
+    pub fn new(options: NanalogueBamOptions) -> Result<Self> {
+        let bam = InputBam::try_from(&ReadOptions {
+            bam_path: options.bam_path.clone(),
+            treat_as_url: options.treat_as_url,
+            threads: options.threads,
+            ..ReadOptions::default()
+        })?;
+        let url_open = UrlOpen::new(
+            options.url_retries,
+            options.url_timeout_ms,
+            options.url_headers.as_ref(),
+        )?;
+        let mut reader = load_bam(
+            &bam,
+            options.reference_fasta.as_deref(),
+            &url_open,
+            options.auto_index == Some(true),
+        )?;
+        if options.threads.is_some() || !thread_pool::attach(&mut reader)? {
+            reader
+                .set_threads(bam.threads.get() as usize)
+                .map_err(|e| {
+                    ErrorKind::Internal.error(format!("Failed to set BAM threads: {e}"))
+                })?;
+        }
+        Ok(Self {
+            bam_path: options.bam_path,
+            treat_as_url: options.treat_as_url,
+            reader: Arc::new(Mutex::new(reader)),
+        })
+    }
+
+    /// Returns read information as an array of records. See [`crate::read_info`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn read_info(
+        &self,
+        options: Option<NanalogueBamReadOptions>,
+    ) -> Result<Vec<ReadInfoRecord>> {
+        let query = self.read_query(options.unwrap_or_default());
+        self.run_blocking(move |reader| read_info_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Returns the read information of one read, or `null` if it is absent. See
+    /// [`crate::get_read`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn get_read(
+        &self,
+        options: NanalogueBamReadOptions,
+        read_id: String,
+    ) -> Result<Option<ReadInfoRecord>> {
+        let query = self.read_query(options);
+        self.run_blocking(move |reader| get_read_sync(&query, &read_id, Some(reader)))
+            .await
+    }
+
+    /// Returns read information as JSON text. See [`crate::read_info_json`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn read_info_json(&self, options: Option<NanalogueBamReadOptions>) -> Result<String> {
+        let query = self.read_query(options.unwrap_or_default());
+        self.run_blocking(move |reader| read_info_json_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Counts the reads that pass the filters. See [`crate::count_reads`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn count_reads(&self, options: Option<NanalogueBamReadOptions>) -> Result<i64> {
+        let query = self.read_query(options.unwrap_or_default());
+        self.run_blocking(move |reader| count_reads_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Peeks at the file and lists its first reads in one pass. See
+    /// [`crate::peek_with_reads`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn peek_with_reads(
+        &self,
+        options: Option<NanalogueBamReadOptions>,
+        sample_size: Option<u32>,
+    ) -> Result<PeekWithReads> {
+        let query = self.read_query(options.unwrap_or_default());
+        self.run_blocking(move |reader| peek_with_reads_sync(&query, sample_size, Some(reader)))
+            .await
+    }
+
+    /// Counts the reads left after each read filter. See [`crate::filter_stats`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn filter_stats(
+        &self,
+        options: Option<NanalogueBamReadOptions>,
+    ) -> Result<FilterStats> {
+        let query = self.read_query(options.unwrap_or_default());
+        self.run_blocking(move |reader| filter_stats_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Returns detailed modification data for reads as JSON. See [`crate::bam_mods`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn bam_mods(
+        &self,
+        options: Option<NanalogueBamReadOptions>,
+    ) -> Result<serde_json::Value> {
+        let query = self.read_query(options.unwrap_or_default());
+        self.run_blocking(move |reader| bam_mods_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Returns detailed modification data for reads as JSON text. See
+    /// [`crate::bam_mods_json`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn bam_mods_json(&self, options: Option<NanalogueBamReadOptions>) -> Result<String> {
+        let query = self.read_query(options.unwrap_or_default());
+        self.run_blocking(move |reader| bam_mods_json_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Returns one record per modification call. See [`crate::bam_mods_typed`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn bam_mods_typed(
+        &self,
+        options: Option<NanalogueBamReadOptions>,
+    ) -> Result<Vec<ModCall>> {
+        let query = self.read_query(options.unwrap_or_default());
+        self.run_blocking(move |reader| bam_mods_typed_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Counts modification calls at each ML value. See [`crate::mod_qual_histogram`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn mod_qual_histogram(
+        &self,
+        options: Option<NanalogueBamReadOptions>,
+        bins: Option<u32>,
+    ) -> Result<Vec<u32>> {
+        let query = self.read_query(options.unwrap_or_default());
+        self.run_blocking(move |reader| mod_qual_histogram_sync(&query, bins, Some(reader)))
+            .await
+    }
+
+    /// Suggests a threshold on the ML values of the modification calls. See
+    /// [`crate::suggest_threshold`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn suggest_threshold(
+        &self,
+        options: Option<NanalogueBamReadOptions>,
+    ) -> Result<ThresholdSuggestion> {
+        let query = self.read_query(options.unwrap_or_default());
+        self.run_blocking(move |reader| suggest_threshold_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Counts the reads at each mapping quality. See [`crate::mapq_histogram`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn mapq_histogram(
+        &self,
+        options: Option<NanalogueBamReadOptions>,
+    ) -> Result<Vec<u32>> {
+        let query = self.read_query(options.unwrap_or_default());
+        self.run_blocking(move |reader| mapq_histogram_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Totals the modification calls of each read. See [`crate::mod_summary`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn mod_summary(
+        &self,
+        options: Option<NanalogueBamReadOptions>,
+    ) -> Result<Vec<ModSummary>> {
+        let query = self.read_query(options.unwrap_or_default());
+        self.run_blocking(move |reader| mod_summary_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Totals the modification calls on each contig. See
+    /// [`crate::mod_counts_by_contig`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn mod_counts_by_contig(
+        &self,
+        options: Option<NanalogueBamReadOptions>,
+    ) -> Result<Vec<ContigModCount>> {
+        let query = self.read_query(options.unwrap_or_default());
+        self.run_blocking(move |reader| mod_counts_by_contig_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Returns the CIGAR statistics of each read. See [`crate::align_stats`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn align_stats(
+        &self,
+        options: Option<NanalogueBamReadOptions>,
+    ) -> Result<Vec<AlignStats>> {
+        let query = self.read_query(options.unwrap_or_default());
+        self.run_blocking(move |reader| align_stats_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Lists the features each read overlaps. See [`crate::annotate_reads`].
+    ///
+    /// # Errors
+    /// Returns an error if a feature or the options are invalid or the query
+    /// fails.
+    #[napi]
+    pub async fn annotate_reads(
+        &self,
+        options: NanalogueBamReadOptions,
+        features: Vec<Feature>,
+    ) -> Result<Vec<ReadAnnotation>> {
+        let query = self.read_query(options);
+        self.run_blocking(move |reader| annotate_reads_sync(&query, &features, Some(reader)))
+            .await
+    }
+
+    /// Counts modification calls at each position of a region. See [`crate::pileup`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn pileup(
+        &self,
+        options: NanalogueBamReadOptions,
+        pileup_options: Option<PileupOptions>,
+    ) -> Result<Vec<PileupRecord>> {
+        let query = self.read_query(options);
+        self.run_blocking(move |reader| {
+            pileup_sync(&query, pileup_options.unwrap_or_default(), Some(reader))
+        })
+        .await
+    }
+
+    /// Counts the reads spanning each position of a region. See [`crate::coverage`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn coverage(
+        &self,
+        options: NanalogueBamReadOptions,
+        bin_size: Option<u32>,
+    ) -> Result<Vec<CoverageRecord>> {
+        let query = self.read_query(options);
+        self.run_blocking(move |reader| coverage_sync(&query, bin_size, Some(reader)))
+            .await
+    }
+
+    /// Counts reads by alignment category. See [`crate::flagstat`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn flagstat(&self, options: Option<NanalogueBamReadOptions>) -> Result<Flagstat> {
+        let query = self.read_query(options.unwrap_or_default());
+        self.run_blocking(move |reader| flagstat_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Counts the mapped records on each contig. See [`crate::contig_summary`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn contig_summary(
+        &self,
+        options: Option<NanalogueBamReadOptions>,
+    ) -> Result<Vec<ContigSummary>> {
+        let query = self.read_query(options.unwrap_or_default());
+        self.run_blocking(move |reader| contig_summary_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Lists the read group ids of the BAM header. See [`crate::read_groups`].
+    ///
+    /// # Errors
+    /// Returns an error if the query fails.
+    #[napi]
+    pub async fn read_groups(&self) -> Result<Vec<String>> {
+        self.run_blocking(|reader| Ok(read_group_ids(reader.header())))
+            .await
+    }
+
+    /// Lists the mapped and unmapped record counts of each contig from the index.
+    /// See [`crate::idxstats`].
+    ///
+    /// # Errors
+    /// Returns an error if the index cannot be read.
+    #[napi]
+    pub async fn idxstats(&self) -> Result<Vec<IdxStat>> {
+        let bam = InputBam::try_from(&self.read_query(NanalogueBamReadOptions::default()))?;
+        let cram = is_cram(&bam.bam_path);
+        self.run_blocking(move |reader| idxstats_of(reader, cram))
+            .await
+    }
+
+    /// Estimates how many records a query with `options` reads. See
+    /// [`crate::estimate_read_count`].
+    ///
+    /// # Errors
+    /// Returns an error if the regions are invalid or the index cannot be read.
+    #[napi]
+    pub async fn estimate_read_count(
+        &self,
+        options: Option<NanalogueBamReadOptions>,
+    ) -> Result<i64> {
+        let bam = InputBam::try_from(&self.read_query(NanalogueBamReadOptions::default()))?;
+        let cram = is_cram(&bam.bam_path);
+        let query = self.read_query(options.unwrap_or_default());
+        self.run_blocking(move |reader| estimate_read_count_of(reader, &query, cram))
+            .await
+    }
+
+    /// Windows modification data along reads and returns JSON or TSV as string, or writes it
+    /// to `output_path`. See [`crate::window_reads`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn window_reads(
+        &self,
+        options: NanalogueBamWindowOptions,
+    ) -> Result<Either<String, i64>> {
+        let query = self.window_query(options);
+        self.run_blocking(move |reader| window_reads_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Pools the reference windows of the reads into genomic bins. See
+    /// [`crate::window_aggregate`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn window_aggregate(
+        &self,
+        options: NanalogueBamWindowOptions,
+    ) -> Result<Vec<WindowBin>> {
+        let query = self.window_query(options);
+        self.run_blocking(move |reader| window_aggregate_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Returns the reference windows of the reads over a region as a matrix of
+    /// reads by windows. See [`crate::window_matrix`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn window_matrix(&self, options: NanalogueBamWindowOptions) -> Result<WindowMatrix> {
+        let query = self.window_query(options);
+        self.run_blocking(move |reader| window_matrix_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Returns the window values as a typed array. See
+    /// [`crate::window_reads_arrays`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn window_reads_arrays(
+        &self,
+        options: NanalogueBamWindowOptions,
+    ) -> Result<WindowArrays> {
+        let query = self.window_query(options);
+        self.run_blocking(move |reader| window_reads_arrays_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Returns sequence table with read info as TSV string. See [`crate::seq_table`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn seq_table(&self, options: NanalogueBamReadOptions) -> Result<String> {
+        let query = self.read_query(options);
+        self.run_blocking(move |reader| seq_table_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Returns the rows of the sequence table as objects. See [`crate::seq_table_records`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn seq_table_records(
+        &self,
+        options: NanalogueBamReadOptions,
+    ) -> Result<Vec<SeqTableRecord>> {
+        let query = self.read_query(options);
+        self.run_blocking(move |reader| seq_table_records_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Returns every column of the core reads table as TSV string. See
+    /// [`crate::reads_table`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn reads_table(&self, options: NanalogueBamReadOptions) -> Result<String> {
+        let query = self.read_query(options);
+        self.run_blocking(move |reader| reads_table_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Returns the aligned bases of each read over a region. See
+    /// [`crate::aligned_sequences`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn aligned_sequences(
+        &self,
+        options: NanalogueBamReadOptions,
+    ) -> Result<Vec<AlignedSequence>> {
+        let query = self.read_query(options);
+        self.run_blocking(move |reader| aligned_sequences_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Returns the base qualities of each read as phred values. See
+    /// [`crate::quality_profiles`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn quality_profiles(
+        &self,
+        options: NanalogueBamReadOptions,
+    ) -> Result<Vec<QualityProfile>> {
+        let query = self.read_query(options);
+        self.run_blocking(move |reader| quality_profiles_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Returns the GC content of each read. See [`crate::gc_content`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or the query fails.
+    #[napi]
+    pub async fn gc_content(&self, options: NanalogueBamReadOptions) -> Result<Vec<GcContent>> {
+        let query = self.read_query(options);
+        self.run_blocking(move |reader| gc_content_sync(&query, Some(reader)))
+            .await
+    }
+
+    /// Writes the reads that pass the read filters to a new BAM file. See [`crate::export_bam`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or reading, writing or indexing fails.
+    #[napi]
+    pub async fn export_bam(
+        &self,
+        options: NanalogueBamReadOptions,
+        output_path: String,
+    ) -> Result<ExportBamResult> {
+        let query = self.read_query(options);
+        self.run_blocking(move |reader| export_bam_sync(&query, &output_path, Some(reader)))
+            .await
+    }
+
+    /// Writes the reads that pass the read filters to a new FASTQ file. See
+    /// [`crate::export_fastq`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or reading or writing fails.
+    #[napi]
+    pub async fn export_fastq(
+        &self,
+        options: NanalogueBamReadOptions,
+        output_path: String,
+        compress: Option<bool>,
+    ) -> Result<ExportFastqResult> {
+        let query = self.read_query(options);
+        self.run_blocking(move |reader| {
+            export_fastq_sync(&query, &output_path, compress == Some(true), Some(reader))
+        })
+        .await
+    }
+
+    /// Writes the mean modification density of genomic bins to a bedGraph file.
+    /// See [`crate::export_bedgraph`].
+    ///
+    /// # Errors
+    /// Returns an error if the options are invalid or reading or writing fails.
+    #[napi]
+    pub async fn export_bedgraph(
+        &self,
+        options: NanalogueBamWindowOptions,
+        output_path: String,
+        skip_empty: Option<bool>,
+    ) -> Result<i64> {
+        let query = self.window_query(options);
+        self.run_blocking(move |reader| {
+            export_bedgraph_sync(
+                &query,
+                &output_path,
+                skip_empty.unwrap_or(true),
+                Some(reader),
+            )
+        })
+        .await
+    }
+
+    /// Query options for the file of this instance.
+    fn read_query(&self, options: NanalogueBamReadOptions) -> ReadOptions {
+        // NOTE: When adding fields to NanalogueBamReadOptions, ensure they are mirrored here.
+        ReadOptions {
+            bam_path: self.bam_path.clone(),
+            treat_as_url: self.treat_as_url,
+            reference_fasta: None,
+            url_retries: None,
+            url_timeout_ms: None,
+            url_headers: None,
+            auto_index: None,
+            threads: None,
+            min_seq_len: options.min_seq_len,
+            max_seq_len: options.max_seq_len,
+            min_align_len: options.min_align_len,
+            max_align_len: options.max_align_len,
+            read_id_set: options.read_id_set,
+            read_id_regex: options.read_id_regex,
+            read_group: options.read_group,
+            include_zero_len: options.include_zero_len,
+            read_filter: options.read_filter,
+            sample_fraction: options.sample_fraction,
+            mapq_filter: options.mapq_filter,
+            mapq_max: options.mapq_max,
+            exclude_mapq_unavail: options.exclude_mapq_unavail,
+            exclude_secondary: options.exclude_secondary,
+            exclude_supplementary: options.exclude_supplementary,
+            exclude_duplicate: options.exclude_duplicate,
+            exclude_qc_fail: options.exclude_qc_fail,
+            no_prefilter: options.no_prefilter,
+            dedupe: options.dedupe,
+            min_mods: options.min_mods,
+            require_mods: options.require_mods,
+            report_mod_data: options.report_mod_data,
+            region: options.region,
+            regions: options.regions,
+            region_bed: options.region_bed,
+            full_region: options.full_region,
+            one_based: options.one_based,
+            tag: options.tag,
+            tags: options.tags,
+            mod_strand: options.mod_strand,
+            min_mod_qual: options.min_mod_qual,
+            reject_mod_qual_non_inclusive: options.reject_mod_qual_non_inclusive,
+            mod_threshold: options.mod_threshold,
+            mod_thresholds: options.mod_thresholds,
+            trim_read_ends_mod: options.trim_read_ends_mod,
+            base_qual_filter_mod: options.base_qual_filter_mod,
+            mod_region: options.mod_region,
+            limit: options.limit,
+            offset: options.offset,
+            sample_seed: options.sample_seed,
+            sort_by: options.sort_by,
+            on_progress: options.on_progress,
+            progress_interval: options.progress_interval,
+            columns: options.columns,
+            reverse_complement_minus: options.reverse_complement_minus,
+            show_base_qual: options.show_base_qual,
+            show_insertions: options.show_insertions,
+            show_mod_markers: options.show_mod_markers,
+            qual_offset: options.qual_offset,
+            max_reads: options.max_reads,
+            pretty: options.pretty,
+        }
+    }
+
+    /// Windowing options for the file of this instance.
+    fn window_query(&self, options: NanalogueBamWindowOptions) -> WindowOptions {
+        // NOTE: When adding fields to NanalogueBamWindowOptions, ensure they are mirrored here.
+        WindowOptions {
+            bam_path: self.bam_path.clone(),
+            treat_as_url: self.treat_as_url,
+            reference_fasta: None,
+            url_retries: None,
+            url_timeout_ms: None,
+            url_headers: None,
+            auto_index: None,
+            threads: None,
+            win: options.win,
+            step: options.step,
+            win_op: options.win_op,
+            output_format: options.output_format,
+            min_window_calls: options.min_window_calls,
+            coordinate: options.coordinate,
+            edge_mode: options.edge_mode,
+            parallel: options.parallel,
+            min_seq_len: options.min_seq_len,
+            max_seq_len: options.max_seq_len,
+            min_align_len: options.min_align_len,
+            max_align_len: options.max_align_len,
+            read_id_set: options.read_id_set,
+            read_id_regex: options.read_id_regex,
+            read_group: options.read_group,
+            include_zero_len: options.include_zero_len,
+            read_filter: options.read_filter,
+            sample_fraction: options.sample_fraction,
+            mapq_filter: options.mapq_filter,
+            mapq_max: options.mapq_max,
+            exclude_mapq_unavail: options.exclude_mapq_unavail,
+            exclude_secondary: options.exclude_secondary,
+            exclude_supplementary: options.exclude_supplementary,
+            exclude_duplicate: options.exclude_duplicate,
+            exclude_qc_fail: options.exclude_qc_fail,
+            no_prefilter: options.no_prefilter,
+            dedupe: options.dedupe,
+            min_mods: options.min_mods,
+            require_mods: options.require_mods,
+            region: options.region,
+            regions: options.regions,
+            region_bed: options.region_bed,
+            full_region: options.full_region,
+            one_based: options.one_based,
+            tag: options.tag,
+            tags: options.tags,
+            mod_strand: options.mod_strand,
+            min_mod_qual: options.min_mod_qual,
+            reject_mod_qual_non_inclusive: options.reject_mod_qual_non_inclusive,
+            mod_threshold: options.mod_threshold,
+            trim_read_ends_mod: options.trim_read_ends_mod,
+            base_qual_filter_mod: options.base_qual_filter_mod,
+            mod_region: options.mod_region,
+            limit: options.limit,
+            offset: options.offset,
+            sample_seed: options.sample_seed,
+            cancel_token: options.cancel_token,
+            output_path: options.output_path,
+            compress: options.compress,
+        }
+    }
+
+    /// Runs `f` with the shared reader on a blocking thread, within the
+    /// concurrency limit.
+    async fn run_blocking<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut IndexedReader) -> Result<T> + Send + 'static,
+    {
+        let reader = Arc::clone(&self.reader);
+        run_limited(move || {
+            let mut guard = reader
+                .lock()
+                .map_err(|e| ErrorKind::Internal.error(format!("BAM reader lock poisoned: {e}")))?;
+            f(&mut guard)
+        })
+        .await
+    }
+}
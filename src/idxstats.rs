@@ -0,0 +1,217 @@
+//! Mapped and unmapped record counts from the BAM index, as `samtools idxstats`,
+//! and the record counts of queries estimated from them.
+
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use crate::regions::{Regions, region_bed3};
+use crate::url_open::UrlOpen;
+use crate::{PeekOptions, ReadOptions, is_cram, load_bam};
+use bedrs::Coordinates as _;
+use nanalogue_core::InputBam;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::{FetchDefinition, IndexedReader, Read as _};
+
+/// Record counts of a contig from the index.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct IdxStat {
+    /// Contig name, or `*` for unmapped records without a position.
+    pub contig: String,
+    /// Contig length from the BAM header, 0 for `*`.
+    pub length: i64,
+    /// Number of mapped records on the contig.
+    pub mapped: i64,
+    /// Number of unmapped records placed on the contig, e.g. the unmapped mate
+    /// of a mapped read.
+    pub unmapped: i64,
+}
+
+/// Lists the mapped and unmapped record counts of each contig from the index.
+///
+/// Like `samtools idxstats`, contigs are listed in header order, including those
+/// without reads, followed by a `*` row counting the unmapped records without a
+/// position. For BAM input only the header and index are read, which is much
+/// faster than `contig_summary` on large files; CRAM indexes hold no counts, so
+/// CRAM records are read instead. Every record counts, including secondary and
+/// supplementary alignments.
+///
+/// # Errors
+/// Returns an error if the path/URL is invalid, the file has no index, or the
+/// BAM file or index cannot be read.
+#[napi]
+pub async fn idxstats(options: PeekOptions) -> Result<Vec<IdxStat>> {
+    run_limited(move || {
+        let bam = InputBam::try_from(&ReadOptions {
+            bam_path: options.bam_path.clone(),
+            treat_as_url: options.treat_as_url,
+            ..ReadOptions::default()
+        })?;
+        let url_open = UrlOpen::new(
+            options.url_retries,
+            options.url_timeout_ms,
+            options.url_headers.as_ref(),
+        )?;
+        let mut reader = load_bam(&bam, options.reference_fasta.as_deref(), &url_open, false)?;
+        idxstats_of(&mut reader, is_cram(&bam.bam_path))
+    })
+    .await
+}
+
+/// Per-contig counts from the index of `reader`, or from its records if it reads
+/// CRAM.
+pub(crate) fn idxstats_of(reader: &mut IndexedReader, cram: bool) -> Result<Vec<IdxStat>> {
+    let header = reader.header().clone();
+    let stats = if cram {
+        stream_stats(reader)?
+    } else {
+        reader.index_stats().map_err(|e| {
+            ErrorKind::of_htslib(&e).error(format!("Failed to read index statistics: {e}"))
+        })?
+    };
+    let to_i64 = |v: u64| {
+        i64::try_from(v).map_err(|e| ErrorKind::Internal.error(format!("idxstats failed: {e}")))
+    };
+    stats
+        .into_iter()
+        .map(|(tid, length, mapped, unmapped)| {
+            let contig = match u32::try_from(tid) {
+                Ok(v) => String::from_utf8_lossy(header.tid2name(v)).into_owned(),
+                Err(_) => String::from("*"),
+            };
+            Ok(IdxStat {
+                contig,
+                length: to_i64(length)?,
+                mapped: to_i64(mapped)?,
+                unmapped: to_i64(unmapped)?,
+            })
+        })
+        .collect()
+}
+
+/// Estimates how many records a query with `options` reads, e.g. to size a
+/// progress bar before streaming.
+///
+/// The count comes from the index, as in `idxstats`, and is the number of records
+/// the query fetches, which is what `on_progress` counts: read filters do not
+/// apply. It is exact without `region`, `regions` or `region_bed`, and for
+/// regions that cover whole contigs. For a region over part of a contig, the
+/// records of the contig are scaled by the fraction of it that the region covers,
+/// so the count is approximate, as it is for overlapping regions, whose shared
+/// records count for each. CRAM indexes hold no counts, so CRAM records are
+/// read instead, which is slower.
+///
+/// # Errors
+/// Returns an error if the path/URL or the regions are invalid, the file has no
+/// index, or the BAM file or index cannot be read.
+#[napi]
+pub async fn estimate_read_count(options: ReadOptions) -> Result<i64> {
+    run_limited(move || {
+        let bam = InputBam::try_from(&ReadOptions {
+            bam_path: options.bam_path.clone(),
+            treat_as_url: options.treat_as_url,
+            ..ReadOptions::default()
+        })?;
+        let url_open = UrlOpen::new(
+            options.url_retries,
+            options.url_timeout_ms,
+            options.url_headers.as_ref(),
+        )?;
+        let mut reader = load_bam(
+            &bam,
+            options.reference_fasta.as_deref(),
+            &url_open,
+            options.auto_index == Some(true),
+        )?;
+        estimate_read_count_of(&mut reader, &options, is_cram(&bam.bam_path))
+    })
+    .await
+}
+
+/// Estimated record count of a query with `options` on `reader`, from its
+/// per-contig counts.
+pub(crate) fn estimate_read_count_of(
+    reader: &mut IndexedReader,
+    options: &ReadOptions,
+    cram: bool,
+) -> Result<i64> {
+    let header = reader.header().clone();
+    let regions = match (
+        Regions::from_options(options)?,
+        region_bed3(options, &header)?,
+    ) {
+        (Some(v), _) => Some(v.to_bed3(&header)?),
+        (None, Some(v)) => Some(vec![v]),
+        (None, None) => None,
+    };
+    let stats = idxstats_of(reader, cram)?;
+    let Some(list) = regions else {
+        return Ok(stats.iter().fold(0i64, |total, v| {
+            total.saturating_add(v.mapped).saturating_add(v.unmapped)
+        }));
+    };
+    let mut estimate = 0f64;
+    for bed3 in list {
+        let Some(stat) = usize::try_from(*bed3.chr()).ok().and_then(|v| stats.get(v)) else {
+            continue;
+        };
+        let length = u64::try_from(stat.length).unwrap_or(0);
+        let covered = bed3.end().min(length).saturating_sub(bed3.start());
+        if covered == 0 {
+            continue;
+        }
+        #[expect(
+            clippy::cast_precision_loss,
+            reason = "contig lengths and record counts are far below 2^52"
+        )]
+        let fraction = covered as f64 / length as f64;
+        #[expect(
+            clippy::cast_precision_loss,
+            reason = "record counts are far below 2^52"
+        )]
+        let records = stat.mapped.saturating_add(stat.unmapped) as f64;
+        estimate += records * fraction;
+    }
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "the estimate is at most the number of records in the file"
+    )]
+    Ok(estimate.round() as i64)
+}
+
+/// Counts in the form of `IndexedReader::index_stats` from reading every record,
+/// which also counts the unmapped records without a position under tid -1.
+fn stream_stats(reader: &mut IndexedReader) -> Result<Vec<(i64, u64, u64, u64)>> {
+    let header = reader.header().clone();
+    let mut stats = (0..header.target_count())
+        .map(|tid| {
+            (
+                i64::from(tid),
+                header.target_len(tid).unwrap_or(0),
+                0u64,
+                0u64,
+            )
+        })
+        .chain([(-1, 0, 0, 0)])
+        .collect::<Vec<_>>();
+    reader
+        .fetch(FetchDefinition::All)
+        .map_err(|e| ErrorKind::of_htslib(&e).error(format!("Failed to fetch region: {e}")))?;
+    for entry in reader.rc_records() {
+        let record = entry.map_err(|e| {
+            ErrorKind::of_htslib(&e).error(format!("Failed to read BAM record: {e}"))
+        })?;
+        // Records without a position, of tid -1, go in the last row
+        let row = usize::try_from(record.tid()).unwrap_or(stats.len().saturating_sub(1));
+        if let Some(&mut (_, _, ref mut mapped, ref mut unmapped)) = stats.get_mut(row) {
+            let count = if record.is_unmapped() {
+                unmapped
+            } else {
+                mapped
+            };
+            *count = count.saturating_add(1);
+        }
+    }
+    Ok(stats)
+}
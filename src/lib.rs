@@ -4,193 +4,586 @@
 //! library, enabling single-molecule BAM/Mod-BAM analysis in Node.js
 //! and Electron applications.
 
+use bam_index::{build_bai, has_index_file};
+use cancel::Cancellable;
+use concurrency::run_limited;
+use filter_stats::failed_stage;
+use flate2::Compression;
+use flate2::write::GzEncoder;
 use nanalogue_core::{
-    BamPreFilt as _, BamRcRecords, F32Bw0and1, GenomicRegion, InputBam, InputBamBuilder, InputMods,
-    InputModsBuilder, InputWindowingBuilder, OptionalTag, OrdPair, PathOrURLOrStdin,
-    SeqDisplayOptions, SimulationConfig, ThresholdState, analysis, nanalogue_indexed_bam_reader,
-    nanalogue_indexed_bam_reader_from_url, peek as rust_peek, read_info as rust_read_info,
-    reads_table as rust_reads_table, simulate_mod_bam as rust_simulate_mod_bam,
-    window_reads as rust_window_reads,
+    AllowedAGCTN, Contains as _, F32AbsValAtMost1, F32Bw0and1, GenomicRegion, InputBam,
+    InputBamBuilder, InputMods, InputModsBuilder, InputRegionOptions as _, InputWindowing,
+    InputWindowingBuilder, ModChar, OptionalTag, OrdPair, PathOrURLOrStdin, SimulationConfig,
+    ThresholdState, analysis, nanalogue_bam_reader_from_stdin,
+    simulate_mod_bam as rust_simulate_mod_bam, window_reads as rust_window_reads,
 };
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
-use rust_htslib::bam::FetchDefinition;
-use std::collections::{HashMap, HashSet};
+use peek_cache::cached_peek;
+use pileup::MOD_THRESHOLD;
+use progress::{Progress, progress_interval};
+use records::{
+    ModSelection, SEQ_TABLE_COLUMNS, SeqDisplay, SeqTableRow, SeqTableRows, has_mod_tags,
+    read_info_mods, read_json_value, read_mod_data, seq_table_columns,
+};
+use regex::bytes::Regex;
+use regions::{RegionRecords, Regions, core_region};
+use rust_htslib::bam::{
+    FetchDefinition, HeaderView, IndexedReader, RcRecords, Read as _, Reader, Record, record::Aux,
+};
+use rust_htslib::htslib::{BAM_FDUP, BAM_FQCFAIL, BAM_FSECONDARY, BAM_FSUPPLEMENTARY};
+use serde::Serialize;
+use sort::sort_records;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fs::{self, File};
+use std::io::{BufWriter, Read as _, Write};
 use std::num::NonZeroU32;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::str::FromStr as _;
 use url::Url;
+use url_open::UrlOpen;
+use window_ops::{CountedWindows, ScaledWindows, TaggedWindows};
+
+mod align_stats;
+mod aligned_sequences;
+mod annotate;
+mod bam_index;
+mod batch;
+mod bedgraph;
+mod cancel;
+mod concurrency;
+mod contig_summary;
+mod count;
+mod coverage;
+mod edge_windows;
+mod error;
+mod export;
+mod filter_stats;
+mod flagstat;
+mod gc_content;
+mod handle;
+mod idxstats;
+mod logging;
+mod mapq_histogram;
+mod mod_counts;
+mod mod_histogram;
+mod mod_summary;
+mod parallel_windows;
+mod peek_cache;
+mod peek_reads;
+mod pileup;
+mod progress;
+mod quality_profiles;
+mod read_filter;
+mod read_groups;
+mod records;
+mod ref_windows;
+mod regions;
+mod simulation;
+mod sort;
+mod streaming;
+mod thread_pool;
+mod url_headers;
+mod url_open;
+mod version;
+mod window_aggregate;
+mod window_arrays;
+mod window_matrix;
+mod window_ops;
+
+pub use align_stats::{AlignStats, align_stats};
+pub use aligned_sequences::{AlignedSequence, aligned_sequences};
+pub use annotate::{Feature, ReadAnnotation, annotate_reads};
+pub use bam_index::build_index;
+pub use batch::{
+    PeekEntry, ReadInfoEntry, WindowReadsEntry, peek_many, read_info_batch, window_reads_batch,
+};
+pub use bedgraph::export_bedgraph;
+pub use cancel::CancelFlag;
+pub use concurrency::set_max_concurrency;
+pub use contig_summary::{ContigSummary, contig_summary};
+pub use count::count_reads;
+pub use coverage::{CoverageRecord, coverage};
+pub use error::ErrorKind;
+pub use export::{export_bam, export_fastq};
+pub use filter_stats::{FilterStats, filter_stats};
+pub use flagstat::{Flagstat, flagstat};
+pub use gc_content::{GcContent, gc_content};
+pub use idxstats::{IdxStat, estimate_read_count, idxstats};
+pub use logging::{LogRecord, set_log_callback};
+pub use mapq_histogram::mapq_histogram;
+pub use mod_counts::{ContigModCount, mod_counts_by_contig};
+pub use mod_histogram::{ThresholdSuggestion, mod_qual_histogram, suggest_threshold};
+pub use mod_summary::{ModSummary, mod_summary};
+pub use peek_cache::clear_peek_cache;
+pub use peek_reads::{PeekWithReads, peek_with_reads};
+pub use pileup::{PileupOptions, PileupRecord, pileup};
+pub use progress::ProgressCallback;
+pub use quality_profiles::{QualityProfile, quality_profiles};
+pub use read_filter::{ReadFilter, ReadFilterKind};
+pub use read_groups::read_groups;
+pub use records::{ModCall, ReadInfoRecord, SeqTableRecord};
+pub use regions::{ParsedRegion, parse_region};
+pub use simulation::{
+    TypedContigConfig, TypedModConfig, TypedReadConfig, TypedSimulationConfig,
+    simulate_mod_bam_typed,
+};
+pub use sort::SortBy;
+pub use streaming::read_info_stream;
+pub use thread_pool::init_thread_pool;
+pub use version::{Versions, version};
+pub use window_aggregate::{WindowBin, window_aggregate};
+pub use window_arrays::{WindowArrays, window_reads_arrays};
+pub use window_matrix::{WindowMatrix, window_matrix};
+
+/// Value of `bam_path` that reads BAM data from stdin instead of a file.
+const STDIN_PATH: &str = "-";
+
+/// Schemes of the `bam_path` values read as URLs when `treat_as_url` is not set.
+const URL_SCHEMES: [&str; 5] = ["http", "https", "s3", "gs", "file"];
+
+/// Whether `bam_path` is read as a URL: as `treat_as_url` says if set, or else if
+/// it starts with one of the [`URL_SCHEMES`] followed by `://`.
+fn is_url(bam_path: &str, treat_as_url: Option<bool>) -> bool {
+    treat_as_url.unwrap_or_else(|| {
+        bam_path
+            .split_once("://")
+            .is_some_and(|(scheme, _)| URL_SCHEMES.iter().any(|v| scheme.eq_ignore_ascii_case(v)))
+    })
+}
+
+/// Where the BAM data of `bam_path` is read from: a URL if [`is_url`] says so,
+/// or else a local path.
+///
+/// A `file://` URL is read as the local path it names, as such URLs come from
+/// drag and drop in Electron, without going through the URL reader of htslib.
+///
+/// # Errors
+/// Returns an error if the URL is malformed, or a `file://` URL names no local
+/// path, e.g. one with a remote host.
+fn bam_location(bam_path: &str, treat_as_url: Option<bool>) -> Result<PathOrURLOrStdin> {
+    if !is_url(bam_path, treat_as_url) {
+        return Ok(PathOrURLOrStdin::Path(PathBuf::from(bam_path)));
+    }
+    let url = Url::parse(bam_path)
+        .map_err(|e| ErrorKind::InvalidOptions.error(format!("Invalid URL: {e}")))?;
+    if url.scheme() != "file" {
+        return Ok(PathOrURLOrStdin::URL(url));
+    }
+    url.to_file_path()
+        .map(PathOrURLOrStdin::Path)
+        .map_err(|()| {
+            ErrorKind::InvalidOptions.error(format!(
+                "Invalid URL: '{bam_path}' does not name a local file"
+            ))
+        })
+}
 
 /// Result from `peek()` containing BAM file metadata.
 #[napi(object)]
 #[non_exhaustive]
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct PeekResult {
     /// Map of contig names to their lengths.
     pub contigs: HashMap<String, i64>,
     /// List of detected modifications, each as `[base, strand, mod_code]`.
     pub modifications: Vec<Vec<String>>,
+    /// Call counts and an example call of each entry of `modifications`, in
+    /// the same order.
+    #[napi(js_name = "modification_details")]
+    pub modification_details: Vec<PeekModification>,
+}
+
+/// Calls of one modification in the records `peek()` samples.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug, Default, Clone)]
+pub struct PeekModification {
+    /// Unmodified base the modification is on, e.g. `C`.
+    pub base: String,
+    /// Strand of the modification in the MM tag, `+` or `-`.
+    pub strand: String,
+    /// Modification code, e.g. `m`, or a numeric code such as `76792`.
+    #[napi(js_name = "mod_code")]
+    pub mod_code: String,
+    /// Number of calls in the sampled records, whatever their probability.
+    #[napi(js_name = "n_calls")]
+    pub n_calls: i64,
+    /// Id of the first sampled read with a call, absent if there are no calls.
+    #[napi(js_name = "example_read_id")]
+    pub example_read_id: Option<String>,
+    /// 0-based position on the basecalled sequence of the first call of that read.
+    #[napi(js_name = "example_query_position")]
+    pub example_query_position: Option<i64>,
+    /// 0-based position on the contig of that call, absent if it is not aligned.
+    #[napi(js_name = "example_reference_position")]
+    pub example_reference_position: Option<i64>,
 }
 
-/// Options for the `peek()` function.
+/// Options for the `peek()` and `read_groups()` functions.
 #[napi(object)]
 #[non_exhaustive]
 #[derive(Debug, Default)]
 pub struct PeekOptions {
     /// Path to the BAM file (local path or URL).
     pub bam_path: String,
-    /// If true, treat `bam_path` as a URL. Otherwise treat as file path.
+    /// If true, treat `bam_path` as a URL, if false as a file path. If unset, paths
+    /// starting with `http://`, `https://`, `s3://`, `gs://` or `file://` are URLs.
+    /// A `file://` URL is read as the local file it names.
     pub treat_as_url: Option<bool>,
+    /// Reference FASTA used to decode CRAM input, which cannot be read without it.
+    /// Ignored for BAM input.
+    pub reference_fasta: Option<String>,
+    /// Times to retry opening a URL after a failed attempt (default: 0). The wait
+    /// between attempts starts at 100 ms and doubles each time, up to 5 s. Ignored
+    /// for local files.
+    pub url_retries: Option<u32>,
+    /// Time limit of each attempt at opening a URL, in milliseconds. Must be at
+    /// least 1. Unset means no limit. An attempt that times out is not retried.
+    /// Ignored for local files.
+    pub url_timeout_ms: Option<u32>,
+    /// Extra HTTP request headers sent when opening a URL and reading from it, e.g.
+    /// `Authorization` or `User-Agent`. Only `http://` and `https://` URLs take
+    /// headers; other URLs are rejected. Ignored for local files.
+    pub url_headers: Option<HashMap<String, String>>,
+    /// Number of records `peek()` examines for modifications, from the start of
+    /// the file or of `region` (default: 100). Must be at least 1. Larger samples
+    /// find rarer modifications but read more of the file. Ignored by
+    /// `read_groups()`.
+    pub sample_size: Option<u32>,
+    /// Genomic region (e.g. "chr1:1000-2000") whose records `peek()` examines
+    /// for modifications, instead of those at the start of the file. Needs an
+    /// index. Contigs still come from the whole header. Ignored by
+    /// `read_groups()`.
+    pub region: Option<String>,
+    /// Read the coordinates of `region` as 1-based and inclusive, as samtools
+    /// does, instead of 0-based and half-open (the default).
+    pub one_based: Option<bool>,
+    /// Read the file even if the result of an identical `peek()` is cached, and
+    /// cache the new result in its place. Ignored by `read_groups()`.
+    pub bypass_cache: Option<bool>,
+    /// Version of the data at a URL, e.g. its `ETag`, as the cache cannot tell
+    /// when it changes: results are cached per URL and version. Ignored for
+    /// local files, whose modification time and size are used instead, and by
+    /// `read_groups()`.
+    pub cache_version: Option<String>,
 }
 
+/// Number of records `peek()` examines when `sample_size` is not set.
+const DEFAULT_PEEK_SAMPLE_SIZE: u32 = 100;
+
 /// Peek at BAM file metadata - returns contigs and detected modifications.
 ///
-/// Reads the BAM header and examines the first few records to determine
-/// the contigs present in the file and any DNA/RNA modifications detected.
+/// Reads the BAM header and examines the first `sample_size` records, of the
+/// file or of `region`, to determine the contigs present in the file and any
+/// DNA/RNA modifications detected, with how many calls each has in those
+/// records. Recent results are cached, so peeking at an unchanged file again
+/// does not read it; see `bypass_cache` and [`clear_peek_cache`].
 ///
 /// # Errors
 /// Returns an error if the BAM file cannot be read, parsed, or if the
 /// path/URL is invalid.
 #[napi]
 pub async fn peek(options: PeekOptions) -> Result<PeekResult> {
-    tokio::task::spawn_blocking(move || peek_sync(&options))
-        .await
-        .map_err(|e| Error::from_reason(format!("Task join error: {e}")))?
+    run_limited(move || peek_sync(&options)).await
 }
 
 /// Synchronous implementation of peek that runs on a blocking thread.
 fn peek_sync(options: &PeekOptions) -> Result<PeekResult> {
-    // Handle treat_as_url: if true, parse as URL; otherwise treat as file path
-    let path_or_url: PathOrURLOrStdin = if options.treat_as_url == Some(true) {
-        let url = Url::parse(&options.bam_path)
-            .map_err(|e| Error::from_reason(format!("Invalid URL: {e}")))?;
-        PathOrURLOrStdin::URL(url)
-    } else {
-        PathOrURLOrStdin::Path(PathBuf::from(&options.bam_path))
-    };
-
-    let mut input_bam = InputBamBuilder::default()
-        .bam_path(path_or_url)
-        .build()
-        .map_err(|e| Error::from_reason(format!("Failed to build InputBam: {e}")))?;
+    let sample = peek_sample_size(options.sample_size)?;
+    let location = bam_location(&options.bam_path, options.treat_as_url)?;
+    cached_peek(options, sample, &location, || {
+        peek_file(options, sample, location.clone())
+    })
+}
 
-    let mut reader = load_bam(&input_bam)?;
+/// Number of records to examine for modifications, from the `sample_size` option.
+fn peek_sample_size(sample_size: Option<u32>) -> Result<u32> {
+    match sample_size {
+        Some(0) => Err(ErrorKind::InvalidOptions.error("sample_size must be a positive integer")),
+        Some(v) => Ok(v),
+        None => Ok(DEFAULT_PEEK_SAMPLE_SIZE),
+    }
+}
 
-    let bam_rc_records = BamRcRecords::new(
+/// Peeks at the file of `options`, examining `sample` records, without the cache.
+fn peek_file(options: &PeekOptions, sample: u32, location: PathOrURLOrStdin) -> Result<PeekResult> {
+    let mut builder = InputBamBuilder::default();
+    let _: &mut InputBamBuilder = builder.bam_path(location);
+    if let Some(v) = options.region.as_deref() {
+        let _: &mut InputBamBuilder =
+            builder.region(core_region(v, options.one_based)?.into_owned());
+    }
+    let mut input_bam = builder
+        .build()
+        .map_err(|e| ErrorKind::InvalidOptions.error(format!("Failed to build InputBam: {e}")))?;
+
+    let url_open = UrlOpen::new(
+        options.url_retries,
+        options.url_timeout_ms,
+        options.url_headers.as_ref(),
+    )?;
+    let mut reader = load_bam(
+        &input_bam,
+        options.reference_fasta.as_deref(),
+        &url_open,
+        false,
+    )?;
+
+    let header = thread_pool::prepare(
         &mut reader,
         &mut input_bam,
         &mut InputMods::<OptionalTag>::default(),
-    )
-    .map_err(|e| Error::from_reason(format!("Failed to read BAM records: {e}")))?;
-
-    // Run peek and capture output
-    let mut buffer = Vec::new();
-    rust_peek::run(
-        &mut buffer,
-        &bam_rc_records.header,
-        bam_rc_records.rc_records.take(100),
-    )
-    .map_err(|e| Error::from_reason(format!("Peek failed: {e}")))?;
+        true,
+    )?;
+
+    let contigs = peek_contigs(&header)?;
+    let mut modifications = BTreeMap::new();
+    for entry in reader
+        .rc_records()
+        .take(usize::try_from(sample).unwrap_or(usize::MAX))
+    {
+        let record = entry.map_err(|e| {
+            ErrorKind::of_htslib(&e).error(format!("Failed to read BAM record: {e}"))
+        })?;
+        add_peek_calls(&mut modifications, &record)?;
+    }
+    Ok(peek_result(contigs, modifications))
+}
 
-    // Parse output
-    let output_str =
-        String::from_utf8(buffer).map_err(|e| Error::from_reason(format!("Invalid UTF-8: {e}")))?;
+/// Result of `peek()` with `contigs` and the calls of the sampled records.
+fn peek_result(
+    contigs: HashMap<String, i64>,
+    modifications: BTreeMap<(char, char, String), PeekModification>,
+) -> PeekResult {
+    let modification_details: Vec<PeekModification> = modifications.into_values().collect();
+    let modification_list = modification_details
+        .iter()
+        .map(|v| vec![v.base.clone(), v.strand.clone(), v.mod_code.clone()])
+        .collect();
+    PeekResult {
+        contigs,
+        modifications: modification_list,
+        modification_details,
+    }
+}
 
+/// Collects contig names and lengths from the BAM header.
+fn peek_contigs(header: &HeaderView) -> Result<HashMap<String, i64>> {
     let mut contigs = HashMap::new();
-    let mut modifications = Vec::new();
-    let mut in_contigs_section = true;
-
-    for line in output_str.lines() {
-        let trimmed = line.trim();
-        match trimmed {
-            "" | "None" => {}
-            "contigs_and_lengths:" => in_contigs_section = true,
-            "modifications:" => in_contigs_section = false,
-            _ if in_contigs_section => {
-                // Parse "contig_name\tlength"
-                let parts: Vec<&str> = trimmed.split('\t').collect();
-                let contig_name = parts
-                    .first()
-                    .ok_or_else(|| Error::from_reason("Missing contig name in peek output"))?;
-                let length: i64 = parts
-                    .get(1)
-                    .ok_or_else(|| Error::from_reason("Missing contig length in peek output"))?
-                    .parse()
-                    .map_err(|e| {
-                        Error::from_reason(format!("Failed to parse contig length: {e}"))
-                    })?;
-                let _: Option<i64> = contigs.insert((*contig_name).to_string(), length);
-            }
-            _ => {
-                // Parse modification string like "G-7200" or "T+T"
-                // Format: base + strand + mod_code (strand is always '+' or '-' at position 1)
-                let mut chars = trimmed.chars();
-                let base = chars
-                    .next()
-                    .ok_or_else(|| Error::from_reason("Empty modification string"))?;
-                let strand = chars
-                    .next()
-                    .ok_or_else(|| Error::from_reason("Modification string missing strand"))?;
-                if strand != '+' && strand != '-' {
-                    return Err(Error::from_reason(format!(
-                        "Invalid strand character '{strand}' in modification string (expected '+' or '-')"
-                    )));
-                }
-                let mod_code: String = chars.collect();
-                if mod_code.is_empty() {
-                    return Err(Error::from_reason("Modification string missing mod code"));
-                }
-                modifications.push(vec![base.to_string(), strand.to_string(), mod_code]);
-            }
+    for (tid, name) in (0u32..).zip(header.target_names()) {
+        let contig_name = std::str::from_utf8(name).map_err(|e| {
+            ErrorKind::InvalidData.error(format!("Invalid contig name in header: {e}"))
+        })?;
+        let raw_length = header.target_len(tid).ok_or_else(|| {
+            ErrorKind::InvalidData.error(format!("Missing length for contig {contig_name}"))
+        })?;
+        let length = i64::try_from(raw_length).map_err(|e| {
+            ErrorKind::InvalidData.error(format!("Contig length out of range: {e}"))
+        })?;
+        let _: Option<i64> = contigs.insert(contig_name.to_owned(), length);
+    }
+    Ok(contigs)
+}
+
+/// Adds the modification calls of `record` to `modifications`, keyed by
+/// `(base, strand, mod_code)` so that they are sorted for stable output, with
+/// their call counts and first call.
+///
+/// Records with zero-length sequences carry no modification data and are skipped,
+/// matching the behaviour of the core `peek` command.
+fn add_peek_calls(
+    modifications: &mut BTreeMap<(char, char, String), PeekModification>,
+    record: &Record,
+) -> Result<()> {
+    if record.seq_len() == 0 {
+        return Ok(());
+    }
+    // Every call is kept, whatever its probability, so that all are counted
+    let curr_read = read_mod_data(record, &InputMods::default(), ModSelection::default())
+        .map_err(|e| ErrorKind::of_core(&e).error(format!("Peek failed: {e}")))?;
+    for base_mod in &curr_read.mod_data().0.base_mods {
+        let base = AllowedAGCTN::try_from(base_mod.modified_base)
+            .map_err(|e| ErrorKind::of_core(&e).error(format!("Peek failed: {e}")))?;
+        let mod_code = ModChar::from(base_mod.modification_type).to_string();
+        let entry = modifications
+            .entry((char::from(base), base_mod.strand, mod_code.clone()))
+            .or_insert_with(|| PeekModification {
+                base: char::from(base).to_string(),
+                strand: base_mod.strand.to_string(),
+                mod_code,
+                ..PeekModification::default()
+            });
+        let n_calls = i64::try_from(base_mod.ranges.annotations.len()).unwrap_or(i64::MAX);
+        entry.n_calls = entry.n_calls.saturating_add(n_calls);
+        if entry.example_read_id.is_none()
+            && let Some(k) = base_mod.ranges.annotations.first()
+        {
+            entry.example_read_id = Some(curr_read.read_id().to_owned());
+            entry.example_query_position = Some(k.start);
+            entry.example_reference_position = k.reference_start;
         }
     }
+    Ok(())
+}
 
-    Ok(PeekResult {
-        contigs,
-        modifications,
-    })
+/// Strand, relative to the basecalled sequence, on which modifications were called.
+#[napi(string_enum = "snake_case")]
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum ModStrand {
+    /// Modifications called on the basecalled strand.
+    Bc,
+    /// Modifications called on the complement of the basecalled strand.
+    BcComp,
+}
+
+impl ModStrand {
+    /// The strand name understood by the core modification options.
+    fn as_core_str(self) -> &'static str {
+        match self {
+            Self::Bc => "bc",
+            Self::BcComp => "bc_comp",
+        }
+    }
 }
 
 /// Options for read operations including BAM filtering and modification parameters.
-#[napi(object)]
+#[napi(object, object_to_js = false)]
 #[non_exhaustive]
 #[derive(Debug, Default, Clone)]
 pub struct ReadOptions {
-    /// Path to the BAM file (local path or URL).
+    /// Path to the BAM file (local path or URL), or `-` to read BAM data from stdin.
     pub bam_path: String,
-    /// If true, treat `bam_path` as a URL. Otherwise treat as file path.
+    /// If true, treat `bam_path` as a URL, if false as a file path. If unset, paths
+    /// starting with `http://`, `https://`, `s3://`, `gs://` or `file://` are URLs.
+    /// A `file://` URL is read as the local file it names.
     pub treat_as_url: Option<bool>,
+    /// Reference FASTA used to decode CRAM input, which cannot be read without it.
+    /// Ignored for BAM input.
+    pub reference_fasta: Option<String>,
+    /// Times to retry opening a URL after a failed attempt (default: 0). The wait
+    /// between attempts starts at 100 ms and doubles each time, up to 5 s. Ignored
+    /// for local files.
+    pub url_retries: Option<u32>,
+    /// Time limit of each attempt at opening a URL, in milliseconds. Must be at
+    /// least 1. Unset means no limit. An attempt that times out is not retried.
+    /// Ignored for local files.
+    pub url_timeout_ms: Option<u32>,
+    /// Extra HTTP request headers sent when opening a URL and reading from it, e.g.
+    /// `Authorization` or `User-Agent`. Only `http://` and `https://` URLs take
+    /// headers; other URLs are rejected. Ignored for local files.
+    pub url_headers: Option<HashMap<String, String>>,
+    /// Build a `.bai` index next to a local BAM file that has none, with a
+    /// warning, before reading it; the file must be sorted by coordinate. If not
+    /// set, such a file is rejected with an `IndexMissing` error. Defaults to
+    /// false.
+    pub auto_index: Option<bool>,
     /// Minimum sequence length filter.
     pub min_seq_len: Option<u32>,
+    /// Maximum sequence length filter. Must be >= `min_seq_len` if both are set.
+    pub max_seq_len: Option<u32>,
     /// Minimum alignment length filter.
     pub min_align_len: Option<i32>,
+    /// Maximum alignment length filter. Must be >= `min_align_len` if both are set.
+    /// Unmapped reads are excluded when it is set, as with `min_align_len`.
+    pub max_align_len: Option<i32>,
     /// Filter to a set of read IDs.
     pub read_id_set: Option<Vec<String>>,
-    /// Number of threads for BAM reading.
+    /// Keep reads whose id matches this regular expression, anywhere in the id
+    /// unless anchored (e.g. `^run1_`). With `read_id_set`, reads must satisfy both.
+    pub read_id_regex: Option<String>,
+    /// Keep reads whose `RG` tag is one of these read group ids, as listed by
+    /// `read_groups`. Reads without the tag are excluded.
+    pub read_group: Option<Vec<String>>,
+    /// Number of threads for BAM reading. Unset, the reader uses the pool of
+    /// [`init_thread_pool`] if it has been started.
     pub threads: Option<u8>,
-    /// Include zero-length sequences (may cause crashes).
+    /// Include records without a sequence, such as most secondary alignments. They
+    /// have a sequence length of 0 and no modification data, and are skipped when
+    /// windowing.
     pub include_zero_len: Option<bool>,
-    /// Comma-separated read filter (e.g., `primary_forward,primary_reverse`).
-    pub read_filter: Option<String>,
+    /// Alignment types to keep, as a list or a comma-separated string
+    /// (e.g., `primary_forward,primary_reverse`).
+    pub read_filter: Option<ReadFilter>,
     /// Subsample fraction (0.0 to 1.0).
     pub sample_fraction: Option<f64>,
     /// Minimum mapping quality filter.
     pub mapq_filter: Option<u8>,
+    /// Maximum mapping quality filter. Must be >= `mapq_filter` if both are set.
+    /// Reads with unavailable mapping quality (255) are excluded unless this is 255.
+    pub mapq_max: Option<u8>,
     /// Exclude reads with unavailable mapping quality.
     pub exclude_mapq_unavail: Option<bool>,
+    /// Exclude secondary alignments (SAM flag 0x100).
+    pub exclude_secondary: Option<bool>,
+    /// Exclude supplementary alignments (SAM flag 0x800).
+    pub exclude_supplementary: Option<bool>,
+    /// Exclude reads marked as PCR or optical duplicates (SAM flag 0x400), which
+    /// `nanalogue_core` cannot otherwise read.
+    pub exclude_duplicate: Option<bool>,
+    /// Exclude reads that failed quality checks (SAM flag 0x200), which
+    /// `nanalogue_core` cannot otherwise read.
+    pub exclude_qc_fail: Option<bool>,
+    /// Return every record of `read_info`, `read_info_stream` and `bam_mods`, also
+    /// those the read filters would leave out, each with `passed_prefilter` telling
+    /// whether it passes them. Other queries still filter. Defaults to false.
+    pub no_prefilter: Option<bool>,
+    /// Keep only the first record of each read id that passes the other filters,
+    /// so that a read with several alignments, such as a supplementary alignment
+    /// in another of the `regions`, is counted once. Defaults to false.
+    pub dedupe: Option<bool>,
+    /// Minimum number of modified calls per read: calls that pass the modification
+    /// filters with a probability of at least 0.5, as counted in `mod_count`.
+    pub min_mods: Option<u32>,
+    /// Keep only reads with modification data, that is with MM and ML tags, so
+    /// that reads without them do not pass as reads without calls. Defaults to
+    /// false.
+    pub require_mods: Option<bool>,
+    /// Add a `has_mod_data` field to each read of `bam_mods` and `bam_mods_json`,
+    /// false for a read without MM and ML tags, to tell it apart from a read whose
+    /// calls the modification filters all leave out, as both have an empty
+    /// `mod_table`. Other queries ignore it. Defaults to false.
+    pub report_mod_data: Option<bool>,
     /// Genomic region filter (e.g., "chr1:1000-2000").
     pub region: Option<String>,
-    /// Only include reads fully spanning the region.
+    /// Several genomic regions to query in one call, as an alternative to `region`.
+    /// A read overlapping more than one region is returned once, except by
+    /// `seq_table`, `seq_table_records` and `reads_table`, which give the table
+    /// of each region in turn.
+    pub regions: Option<Vec<String>>,
+    /// Path to a BED file whose intervals are queried as `regions`.
+    /// Only the first three columns are read.
+    pub region_bed: Option<String>,
+    /// Only include reads fully spanning the region (or one of the `regions`).
     pub full_region: Option<bool>,
+    /// Read the coordinates of `region`, `regions` and `mod_region` as 1-based
+    /// and inclusive, as samtools does, instead of 0-based and half-open (the
+    /// default). `region_bed` files are always 0-based.
+    pub one_based: Option<bool>,
     /// Filter to specific modification tag.
     pub tag: Option<String>,
-    /// Filter by modification strand (`bc` or `bc_comp`).
-    pub mod_strand: Option<String>,
+    /// Several modification tags whose calls are all kept, e.g. `["m", "h"]` for
+    /// 5mC and 5hmC together, as an alternative to `tag`.
+    pub tags: Option<Vec<String>>,
+    /// Filter by modification strand.
+    pub mod_strand: Option<ModStrand>,
     /// Minimum modification quality threshold.
     pub min_mod_qual: Option<u8>,
     /// Reject modification calls where low < probability < high.
     /// Array of [low, high] where both are 0-255.
     pub reject_mod_qual_non_inclusive: Option<Vec<u8>>,
+    /// Keep modification calls with a probability of at least this (0 to 1), e.g.
+    /// 0.8 for calls of ML value 204 and above. Overrides `min_mod_qual` and
+    /// `reject_mod_qual_non_inclusive`.
+    pub mod_threshold: Option<f64>,
+    /// Lowest ML value (0-255) of the calls kept per modification code, e.g.
+    /// `{ m: 200, h: 150 }` to call 5mC and 5hmC at their own cutoffs. Applies on
+    /// top of the other modification filters; codes not listed are unaffected.
+    /// Not available for windowing.
+    pub mod_thresholds: Option<HashMap<String, u8>>,
     /// Trim modification info from read ends (bp).
     pub trim_read_ends_mod: Option<u32>,
     /// Base quality filter for modifications.
@@ -199,86 +592,315 @@ pub struct ReadOptions {
     pub mod_region: Option<String>,
     /// Maximum number of records to return. Must be > 0 if set. If omitted, returns all records.
     pub limit: Option<i64>,
-    /// Number of records to skip before returning results. Must be >= 0 if set. Defaults to 0.
+    /// Number of records that pass the filters to skip before returning results. Must be >= 0
+    /// if set. Defaults to 0.
     pub offset: Option<i64>,
     /// Seed for deterministic sampling. Required for stable pagination with `sample_fraction`.
-    /// Must be non-negative if set.
+    /// Must be non-negative if set. If omitted, each call samples a different subset.
     pub sample_seed: Option<i64>,
+    /// Order of the reads listed by `read_info` and `bam_mods` (and their JSON and
+    /// typed forms). Sorting reads every read that passes the filters into memory
+    /// before the first is returned; `offset` and `limit` then apply to the sorted
+    /// reads. Not supported by `read_info_stream`. If unset, reads are listed in
+    /// file order.
+    pub sort_by: Option<SortBy>,
+    /// Called with the number of BAM records processed so far (`read_info`, `bam_mods` and `export_bam` only).
+    pub on_progress: Option<ProgressCallback>,
+    /// Number of records between `on_progress` calls. Must be > 0 if set. Defaults to 1000.
+    pub progress_interval: Option<u32>,
+    /// Columns of the `seq_table` output, in order (`seq_table` only). Defaults to
+    /// `read_id`, `sequence` and `qualities`.
+    pub columns: Option<Vec<String>>,
+    /// If true, show reads aligned to the minus strand reverse-complemented, in the
+    /// orientation they were sequenced in (`seq_table`, `seq_table_records` and
+    /// `reads_table` only). Defaults to false: all reads in the orientation of the
+    /// reference.
+    pub reverse_complement_minus: Option<bool>,
+    /// If false, leave the base qualities out of the sequence table: the
+    /// `qualities` column of `seq_table` and `reads_table`, which `columns` then
+    /// cannot list, and the `qualities` field of `seq_table_records`. Defaults to
+    /// true.
+    pub show_base_qual: Option<bool>,
+    /// If false, show insertions in the sequence table in upper case like the
+    /// other bases. Defaults to true: lower case.
+    pub show_insertions: Option<bool>,
+    /// If false, show modified bases in the sequence table as their base instead
+    /// of as `Z` (or `z` in an insertion). Defaults to true.
+    pub show_mod_markers: Option<bool>,
+    /// Offset of the base quality encoding, 33 for Phred+33 or 64 for the
+    /// Phred+64 of some legacy data (`quality_profiles`, `seq_table`,
+    /// `seq_table_records`, `reads_table` and `export_fastq` only). Defaults to
+    /// 33. Qualities are given as phred values whatever the encoding.
+    pub qual_offset: Option<u8>,
+    /// Most reads the sequence table has rows for (`seq_table`,
+    /// `seq_table_records` and `reads_table` only), such as for a preview of a
+    /// dense region. Must be at least 1. Reading stops at the first record of a
+    /// read past them, after any `offset`, so the table is not built in full.
+    pub max_reads: Option<u32>,
+    /// If true, indent each read of the `bam_mods_json` output over several lines
+    /// (`bam_mods_json` only). Defaults to false: one line per read.
+    pub pretty: Option<bool>,
 }
 
-/// Returns read information as JSON array.
+/// Returns read information as an array of records.
 ///
-/// Produces JSON with per-read information including alignment length,
+/// Produces per-read information including alignment length,
 /// sequence length, read ID, modification counts, etc.
 ///
 /// # Errors
-/// Returns an error if BAM reading fails, input options are invalid,
-/// or JSON parsing fails.
+/// Returns an error if BAM reading fails or input options are invalid.
 #[napi]
-pub async fn read_info(options: ReadOptions) -> Result<serde_json::Value> {
-    tokio::task::spawn_blocking(move || read_info_sync(&options))
-        .await
-        .map_err(|e| Error::from_reason(format!("Task join error: {e}")))?
+pub async fn read_info(options: ReadOptions) -> Result<Vec<ReadInfoRecord>> {
+    run_limited(move || read_info_sync(&options, None)).await
 }
 
 /// Synchronous implementation of `read_info` that runs on a blocking thread.
-fn read_info_sync(options: &ReadOptions) -> Result<serde_json::Value> {
+fn read_info_sync(
+    options: &ReadOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<Vec<ReadInfoRecord>> {
     let (offset, limit) = validate_pagination(options)?;
-    let (mut bam, mut mods) = build_input_options(options)?;
+    let interval = progress_interval(options.progress_interval)?;
+    let (mut bam, mut raw_mods, extra) = build_input_options(options)?;
+    let regions = Regions::from_options(options)?;
+
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, _) = reader.records(&mut bam, &mut raw_mods, regions.as_ref())?;
+    let mods = read_info_mods(raw_mods);
+    let no_prefilter = options.no_prefilter == Some(true);
+
+    let filtered = Progress::new(rc_records, options.on_progress.clone(), interval).filter(|r| {
+        no_prefilter
+            || r.as_ref()
+                .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    sort_records(filtered, options.sort_by)
+        .skip(offset)
+        .take(limit)
+        .map(|r| {
+            let record = r.map_err(|e| {
+                ErrorKind::of_htslib(&e).error(format!("Failed to read BAM record: {e}"))
+            })?;
+            let mut info = ReadInfoRecord::from_record(&record, &mods, extra.mod_selection())?;
+            if no_prefilter {
+                info.passed_prefilter = Some(passes_read_filters(&record, &bam, &mods, &extra));
+            }
+            Ok(info)
+        })
+        .collect()
+}
 
-    let mut reader = load_bam(&bam)?;
-    let bam_rc_records = BamRcRecords::new(&mut reader, &mut bam, &mut mods)
-        .map_err(|e| Error::from_reason(format!("Failed to read BAM records: {e}")))?;
+/// Returns the read information of the read `read_id`, or `None` if no read of
+/// that id passes the read filters.
+///
+/// With `region` set, only that region is fetched, so a read can be found
+/// without scanning the file; otherwise the whole file is scanned until the read
+/// is found. Of several alignments of the read, the first in file order is
+/// returned. `offset`, `limit` and `no_prefilter` are ignored.
+///
+/// # Errors
+/// Returns an error if `read_id_set` is set, input options are invalid or BAM
+/// reading fails.
+#[napi]
+pub async fn get_read(options: ReadOptions, read_id: String) -> Result<Option<ReadInfoRecord>> {
+    run_limited(move || get_read_sync(&options, &read_id, None)).await
+}
 
-    let filtered = bam_rc_records
-        .rc_records
-        .filter(|r| r.as_ref().map_or(true, |v| v.pre_filt(&bam)));
-    let paginated = filtered.skip(offset).take(limit);
+/// Synchronous implementation of `get_read` that runs on a blocking thread.
+fn get_read_sync(
+    options: &ReadOptions,
+    read_id: &str,
+    shared: Option<&mut IndexedReader>,
+) -> Result<Option<ReadInfoRecord>> {
+    if options.read_id_set.is_some() {
+        return Err(ErrorKind::InvalidOptions
+            .error("getRead takes the read id as its own argument; readIdSet is not supported"));
+    }
+    let mut query = options.clone();
+    query.read_id_set = Some(vec![read_id.to_owned()]);
+    query.offset = None;
+    query.limit = Some(1);
+    query.no_prefilter = None;
+    query.sort_by = None;
+    Ok(read_info_sync(&query, shared)?.into_iter().next())
+}
+
+/// Returns read information as the JSON text written by the core `read_info` command.
+///
+/// Takes the same options as `read_info`, for callers that want the raw JSON.
+///
+/// # Errors
+/// Returns an error if BAM reading fails or input options are invalid.
+#[napi]
+pub async fn read_info_json(options: ReadOptions) -> Result<String> {
+    run_limited(move || read_info_json_sync(&options, None)).await
+}
+
+/// Synchronous implementation of `read_info_json` that runs on a blocking thread.
+fn read_info_json_sync(
+    options: &ReadOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<String> {
+    let (offset, limit) = validate_pagination(options)?;
+    let interval = progress_interval(options.progress_interval)?;
+    let (mut bam, mut raw_mods, extra) = build_input_options(options)?;
+    let regions = Regions::from_options(options)?;
+
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, _) = reader.records(&mut bam, &mut raw_mods, regions.as_ref())?;
+    let mods = read_info_mods(raw_mods);
+
+    let filtered = Progress::new(rc_records, options.on_progress.clone(), interval).filter(|r| {
+        r.as_ref()
+            .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    let paginated = sort_records(filtered, options.sort_by)
+        .skip(offset)
+        .take(limit);
 
     let mut buffer = Vec::new();
-    rust_read_info::run(&mut buffer, paginated, mods, None)
-        .map_err(|e| Error::from_reason(format!("read_info failed: {e}")))?;
+    write_reads_json(
+        &mut buffer,
+        paginated,
+        &mods,
+        extra.mod_selection(),
+        ReadsJson::Summary,
+        false,
+    )
+    .map_err(|e| ErrorKind::of_core(&e).error(format!("read_info failed: {e}")))?;
+
+    String::from_utf8(buffer).map_err(|e| ErrorKind::Internal.error(format!("Invalid UTF-8: {e}")))
+}
 
-    let json_str =
-        String::from_utf8(buffer).map_err(|e| Error::from_reason(format!("Invalid UTF-8: {e}")))?;
-    serde_json::from_str(&json_str)
-        .map_err(|e| Error::from_reason(format!("Failed to parse JSON: {e}")))
+/// How [`write_reads_json`] writes each read.
+#[derive(Debug, Clone, Copy)]
+enum ReadsJson {
+    /// The summary lines of the core `read_info` command.
+    Summary,
+    /// Every modification call, on one line.
+    Detailed,
+    /// Every modification call, indented over several lines.
+    Pretty,
+}
+
+/// Writes reads as the JSON array of the core `read_info` command: as its summary
+/// lines, with `mods` from [`read_info_mods`], or with every modification call
+/// for the other `layout`s. The calls are those [`read_mod_data`] keeps with
+/// `mods` and `tags`. With `report_mod_data`, the reads of the other `layout`s
+/// end with a `has_mod_data` field.
+///
+/// Unlike the core command, records without sequence are written too, with no
+/// modification data, as described in [`read_mod_data`].
+///
+/// # Errors
+/// Returns an error if a record cannot be read or parsed, or writing fails.
+fn write_reads_json<W, D>(
+    handle: &mut W,
+    records: D,
+    mods: &InputMods<OptionalTag>,
+    selection: ModSelection<'_>,
+    layout: ReadsJson,
+    report_mod_data: bool,
+) -> std::result::Result<(), nanalogue_core::Error>
+where
+    W: Write,
+    D: IntoIterator<Item = std::result::Result<Rc<Record>, rust_htslib::errors::Error>>,
+{
+    write!(handle, "[")?;
+    for (i, entry) in records.into_iter().enumerate() {
+        let record = entry?;
+        let read = read_mod_data(&record, mods, selection)?;
+        writeln!(handle, "{}", if i == 0 { "" } else { "," })?;
+        let pretty = matches!(layout, ReadsJson::Pretty);
+        let has_mod_data = report_mod_data.then(|| has_mod_tags(&record));
+        match (layout, selection.has_thresholds()) {
+            (ReadsJson::Summary, _) => write!(handle, "{read}")?,
+            (_, false) => write_read_json(&mut *handle, &read, has_mod_data, pretty)?,
+            // The threshold of each modification code is added to its entry
+            (_, true) => write_read_json(
+                &mut *handle,
+                &read_json_value(&read, selection)?,
+                has_mod_data,
+                pretty,
+            )?,
+        }
+    }
+    writeln!(handle, "\n]")?;
+    Ok(())
+}
+
+/// A read of [`write_reads_json`] followed by its `has_mod_data` field.
+#[derive(Debug, Serialize)]
+struct WithModData<'a, T> {
+    /// The fields of the read, in their own order.
+    #[serde(flatten)]
+    read: &'a T,
+    /// Whether the record has MM and ML tags, telling a read without them from
+    /// one whose calls the modification filters all leave out, as both have an
+    /// empty `mod_table`.
+    has_mod_data: bool,
+}
+
+/// Writes `read` as a JSON object, indented if `pretty`, with a last
+/// `has_mod_data` field if given.
+///
+/// # Errors
+/// Returns an error if `read` cannot be serialized or writing fails.
+fn write_read_json<W, T>(
+    handle: W,
+    read: &T,
+    has_mod_data: Option<bool>,
+    pretty: bool,
+) -> serde_json::Result<()>
+where
+    W: Write,
+    T: Serialize,
+{
+    let with_mod_data = has_mod_data.map(|v| WithModData {
+        read,
+        has_mod_data: v,
+    });
+    match (with_mod_data, pretty) {
+        (Some(v), true) => serde_json::to_writer_pretty(handle, &v),
+        (Some(v), false) => serde_json::to_writer(handle, &v),
+        (None, true) => serde_json::to_writer_pretty(handle, read),
+        (None, false) => serde_json::to_writer(handle, read),
+    }
 }
 
 impl TryFrom<&ReadOptions> for InputBam {
     type Error = Error;
 
     fn try_from(options: &ReadOptions) -> Result<Self> {
-        // Guard against include_zero_len=true which can cause crashes
-        if options.include_zero_len == Some(true) {
-            return Err(Error::from_reason(
-                "include_zero_len=True is not yet supported due to potential crashes in the underlying library",
-            ));
-        }
-
         // Validate threads > 0
         if let Some(v) = options.threads
             && v == 0
         {
-            return Err(Error::from_reason("threads must be a positive integer"));
+            return Err(ErrorKind::InvalidOptions.error("threads must be a positive integer"));
         }
 
         // Validate sample_fraction in [0, 1]
         if let Some(v) = options.sample_fraction
             && !(0.0..=1.0).contains(&v)
         {
-            return Err(Error::from_reason(
-                "sample_fraction must be between 0 and 1",
-            ));
+            return Err(ErrorKind::InvalidOptions.error("sample_fraction must be between 0 and 1"));
         }
 
-        // Handle treat_as_url: if true, parse as URL; otherwise treat as file path
-        let path_or_url: PathOrURLOrStdin = if options.treat_as_url == Some(true) {
-            let url = Url::parse(&options.bam_path)
-                .map_err(|e| Error::from_reason(format!("Invalid URL: {e}")))?;
-            PathOrURLOrStdin::URL(url)
+        let bam_path = options.bam_path.as_str();
+
+        let path_or_url: PathOrURLOrStdin = if bam_path == STDIN_PATH
+            && !is_url(bam_path, options.treat_as_url)
+        {
+            // Checked here, before anything is read from stdin, as the data cannot be re-read
+            if options.region.is_some() || options.regions.is_some() || options.region_bed.is_some()
+            {
+                return Err(ErrorKind::InvalidOptions.error(
+                    "region, regions and regionBed cannot be used with BAM data from stdin, which cannot be fetched by region",
+                ));
+            }
+            PathOrURLOrStdin::Stdin
         } else {
-            PathOrURLOrStdin::Path(PathBuf::from(&options.bam_path))
+            bam_location(bam_path, options.treat_as_url)?
         };
 
         let mut builder = InputBamBuilder::default();
@@ -303,7 +925,7 @@ impl TryFrom<&ReadOptions> for InputBam {
             let _: &mut InputBamBuilder = builder.include_zero_len(v);
         }
         if let Some(v) = options.read_filter.as_ref() {
-            let _: &mut InputBamBuilder = builder.read_filter(v.clone());
+            let _: &mut InputBamBuilder = builder.read_filter(v.as_str().to_owned());
         }
         if let Some(v) = options.sample_fraction {
             #[expect(
@@ -320,15 +942,21 @@ impl TryFrom<&ReadOptions> for InputBam {
         if let Some(v) = options.exclude_mapq_unavail {
             let _: &mut InputBamBuilder = builder.exclude_mapq_unavail(v);
         }
-        if let Some(v) = options.region.as_ref() {
-            let _: &mut InputBamBuilder = builder.region(v.clone());
+        if let Some(v) = options.region.as_deref() {
+            let _: &mut InputBamBuilder =
+                builder.region(core_region(v, options.one_based)?.into_owned());
         }
-        if let Some(v) = options.full_region {
+        // With `regions` or `region_bed`, each region is fetched and checked by
+        // `RegionRecords` instead
+        if let Some(v) = options.full_region
+            && options.regions.is_none()
+            && options.region_bed.is_none()
+        {
             let _: &mut InputBamBuilder = builder.full_region(v);
         }
         if let Some(v) = options.sample_seed {
             if v < 0 {
-                return Err(Error::from_reason("sample_seed must be non-negative"));
+                return Err(ErrorKind::InvalidOptions.error("sample_seed must be non-negative"));
             }
             #[expect(clippy::cast_sign_loss, reason = "validated non-negative above")]
             let seed = v as u64;
@@ -337,7 +965,7 @@ impl TryFrom<&ReadOptions> for InputBam {
 
         builder
             .build()
-            .map_err(|e| Error::from_reason(format!("Failed to build InputBam: {e}")))
+            .map_err(|e| ErrorKind::InvalidOptions.error(format!("Failed to build InputBam: {e}")))
     }
 }
 
@@ -347,8 +975,9 @@ impl TryFrom<&ReadOptions> for InputMods<OptionalTag> {
     fn try_from(options: &ReadOptions) -> Result<Self> {
         let mut builder = InputModsBuilder::<OptionalTag>::default();
 
-        if let Some(v) = options.mod_strand.as_ref() {
-            let _: &mut InputModsBuilder<OptionalTag> = builder.mod_strand(v.clone());
+        if let Some(v) = options.mod_strand {
+            let _: &mut InputModsBuilder<OptionalTag> =
+                builder.mod_strand(v.as_core_str().to_owned());
         }
 
         // Handle mod_prob_filter: combines min_mod_qual and reject_mod_qual_non_inclusive
@@ -358,9 +987,8 @@ impl TryFrom<&ReadOptions> for InputMods<OptionalTag> {
                 match high.checked_sub(low) {
                     None => {
                         // high < low is invalid
-                        return Err(Error::from_reason(
-                            "for rejectModQualNonInclusive, please set low < high",
-                        ));
+                        return Err(ErrorKind::InvalidOptions
+                            .error("for rejectModQualNonInclusive, please set low < high"));
                     }
                     Some(0 | 1) => {
                         // If difference is 0 or 1, no meaningful rejection range, just use GtEq
@@ -374,22 +1002,39 @@ impl TryFrom<&ReadOptions> for InputMods<OptionalTag> {
                         )]
                         let ord_pair =
                             OrdPair::<u8>::try_from((low + 1, high - 1)).map_err(|e| {
-                                Error::from_reason(format!(
-                                    "Invalid rejectModQualNonInclusive range: {e}"
-                                ))
+                                ErrorKind::InvalidOptions
+                                    .error(format!("Invalid rejectModQualNonInclusive range: {e}"))
                             })?;
                         ThresholdState::Both((min_mod_qual, ord_pair))
                     }
                 }
             }
             Some(_) => {
-                return Err(Error::from_reason(
+                return Err(ErrorKind::InvalidOptions.error(
                     "rejectModQualNonInclusive must be an array of exactly 2 numbers [low, high]",
                 ));
             }
             None => ThresholdState::GtEq(min_mod_qual),
         };
-        let _: &mut InputModsBuilder<OptionalTag> = builder.mod_prob_filter(threshold_state);
+        // mod_threshold replaces the above with the ML value of its probability
+        let mod_prob_filter = match options.mod_threshold {
+            Some(v) if (0.0..=1.0).contains(&v) => {
+                #[expect(
+                    clippy::cast_possible_truncation,
+                    clippy::cast_sign_loss,
+                    reason = "validated between 0 and 1 above, so the value is within 0-255"
+                )]
+                let qual = (v * f64::from(u8::MAX)).ceil() as u8;
+                ThresholdState::GtEq(qual)
+            }
+            Some(_) => {
+                return Err(
+                    ErrorKind::InvalidOptions.error("mod_threshold must be between 0 and 1")
+                );
+            }
+            None => threshold_state,
+        };
+        let _: &mut InputModsBuilder<OptionalTag> = builder.mod_prob_filter(mod_prob_filter);
 
         if let Some(v) = options.trim_read_ends_mod {
             let _: &mut InputModsBuilder<OptionalTag> = builder.trim_read_ends_mod(v as usize);
@@ -397,18 +1042,26 @@ impl TryFrom<&ReadOptions> for InputMods<OptionalTag> {
         if let Some(v) = options.base_qual_filter_mod {
             let _: &mut InputModsBuilder<OptionalTag> = builder.base_qual_filter_mod(v);
         }
-        if let Some(v) = options.tag.as_ref() {
-            let tag = OptionalTag::from_str(v)
-                .map_err(|_err| Error::from_reason(format!("Invalid tag value: '{v}'")))?;
+        // Several tags are kept by `read_mod_data` through `ExtraFilters::mod_tags`,
+        // as the core options only hold one
+        let single_tag = match options.tags.as_deref() {
+            Some(v) => v.first().filter(|_| v.len() == 1),
+            None => options.tag.as_ref(),
+        };
+        if let Some(v) = single_tag {
+            let tag = OptionalTag::from_str(v).map_err(|_err| {
+                ErrorKind::InvalidOptions.error(format!("Invalid tag value: '{v}'"))
+            })?;
             let _: &mut InputModsBuilder<OptionalTag> = builder.tag(tag);
         }
-        if let Some(v) = options.mod_region.as_ref() {
-            let _: &mut InputModsBuilder<OptionalTag> = builder.mod_region(v.clone());
+        if let Some(v) = options.mod_region.as_deref() {
+            let _: &mut InputModsBuilder<OptionalTag> =
+                builder.mod_region(core_region(v, options.one_based)?.into_owned());
         }
 
         builder
             .build()
-            .map_err(|e| Error::from_reason(format!("Failed to build InputMods: {e}")))
+            .map_err(|e| ErrorKind::InvalidOptions.error(format!("Failed to build InputMods: {e}")))
     }
 }
 
@@ -426,12 +1079,12 @@ fn validate_pagination(options: &ReadOptions) -> Result<(usize, usize)> {
     if let Some(v) = options.offset
         && v < 0
     {
-        return Err(Error::from_reason("offset must be non-negative"));
+        return Err(ErrorKind::InvalidOptions.error("offset must be non-negative"));
     }
     if let Some(v) = options.limit
         && v <= 0
     {
-        return Err(Error::from_reason("limit must be a positive integer"));
+        return Err(ErrorKind::InvalidOptions.error("limit must be a positive integer"));
     }
     let offset = (options.offset.unwrap_or(0) as u64).min(usize::MAX as u64) as usize;
     let limit = options
@@ -440,44 +1093,444 @@ fn validate_pagination(options: &ReadOptions) -> Result<(usize, usize)> {
     Ok((offset, limit))
 }
 
-/// Builds `InputBam` and `InputMods` from the given options.
-fn build_input_options(options: &ReadOptions) -> Result<(InputBam, InputMods<OptionalTag>)> {
+/// Read filters of `ReadOptions` that `InputBam` has no counterpart for, checked
+/// and prepared once per query by [`build_input_options`].
+#[derive(Debug)]
+struct ExtraFilters {
+    /// Maximum mapping quality.
+    mapq_max: Option<u8>,
+    /// Maximum sequence length.
+    max_seq_len: Option<u32>,
+    /// Maximum alignment length.
+    max_align_len: Option<i32>,
+    /// Minimum number of modified calls.
+    min_mods: Option<u32>,
+    /// Whether reads must have MM and ML tags.
+    require_mods: bool,
+    /// Pattern that read ids must match.
+    read_id_regex: Option<Regex>,
+    /// Read group ids of which reads must have one in their `RG` tag.
+    read_group: Option<HashSet<Vec<u8>>>,
+    /// SAM flags of which reads must have none set.
+    excluded_flags: u32,
+    /// Modification tags whose calls are kept, if `tags` lists more than one.
+    mod_tags: Option<HashSet<ModChar>>,
+    /// Lowest ML value of the calls kept per modification code.
+    mod_thresholds: Option<HashMap<ModChar, u8>>,
+    /// Read ids of the records that passed so far, if `dedupe` is set.
+    seen_read_ids: Option<RefCell<HashSet<Vec<u8>>>>,
+}
+
+impl ExtraFilters {
+    /// Modification calls kept beyond the options of `InputMods`.
+    fn mod_selection(&self) -> ModSelection<'_> {
+        ModSelection::new(self.mod_tags.as_ref(), self.mod_thresholds.as_ref())
+    }
+}
+
+/// Whether a record passes the core read filters in `bam` and the `extra`
+/// filters, with `min_mods` counted over the calls that pass the modification
+/// filters in `mods`.
+///
+/// With `dedupe`, a passing record is remembered and later records of its read
+/// id fail, so each record should be checked once.
+fn passes_read_filters(
+    record: &Record,
+    bam: &InputBam,
+    mods: &InputMods<OptionalTag>,
+    extra: &ExtraFilters,
+) -> bool {
+    failed_stage(record, bam, mods, extra).is_none()
+}
+
+/// Whether `record` has at least `min_mods` calls that pass the modification
+/// filters and count as modified, as in `mod_summary`.
+///
+/// A record whose modification data cannot be read passes, so that the error is
+/// raised where the data is used.
+fn has_min_mods(
+    record: &Record,
+    mods: &InputMods<OptionalTag>,
+    selection: ModSelection<'_>,
+    min_mods: u32,
+) -> bool {
+    read_mod_data(record, mods, selection).map_or(true, |read| {
+        let n_mod = read
+            .mod_data()
+            .0
+            .base_mods
+            .iter()
+            .flat_map(|v| &v.ranges.annotations)
+            .filter(|k| MOD_THRESHOLD.contains(&k.qual))
+            .count();
+        u64::try_from(n_mod).unwrap_or(u64::MAX) >= u64::from(min_mods)
+    })
+}
+
+/// Builds `InputBam`, `InputMods` and the [`ExtraFilters`] from the given options.
+fn build_input_options(
+    options: &ReadOptions,
+) -> Result<(InputBam, InputMods<OptionalTag>, ExtraFilters)> {
+    if let (Some(min), Some(max)) = (options.mapq_filter, options.mapq_max)
+        && max < min
+    {
+        return Err(ErrorKind::InvalidOptions
+            .error(format!("mapq_max ({max}) must be >= mapq_filter ({min})")));
+    }
+    if let (Some(min), Some(max)) = (options.min_seq_len, options.max_seq_len)
+        && max < min
+    {
+        return Err(ErrorKind::InvalidOptions.error(format!(
+            "max_seq_len ({max}) must be >= min_seq_len ({min})"
+        )));
+    }
+    if let (Some(min), Some(max)) = (options.min_align_len, options.max_align_len)
+        && max < min
+    {
+        return Err(ErrorKind::InvalidOptions.error(format!(
+            "max_align_len ({max}) must be >= min_align_len ({min})"
+        )));
+    }
+    let read_id_regex = options
+        .read_id_regex
+        .as_deref()
+        .map(Regex::new)
+        .transpose()
+        .map_err(|e| ErrorKind::InvalidOptions.error(format!("Invalid read_id_regex: {e}")))?;
+    if options.read_group.as_ref().is_some_and(Vec::is_empty) {
+        return Err(
+            ErrorKind::InvalidOptions.error("read_group must contain at least one read group")
+        );
+    }
+    let mod_tags = match options.tags.as_deref() {
+        Some(_) if options.tag.is_some() => {
+            return Err(ErrorKind::InvalidOptions.error("tag and tags cannot be used together"));
+        }
+        Some(&[]) => {
+            return Err(ErrorKind::InvalidOptions.error("tags must contain at least one tag"));
+        }
+        Some(v) if v.len() > 1 => Some(
+            v.iter()
+                .map(|w| {
+                    ModChar::from_str(w).map_err(|_err| {
+                        ErrorKind::InvalidOptions.error(format!("Invalid tag value: '{w}'"))
+                    })
+                })
+                .collect::<Result<HashSet<_>>>()?,
+        ),
+        Some(_) | None => None,
+    };
+    let mod_thresholds = options
+        .mod_thresholds
+        .as_ref()
+        .map(|v| {
+            v.iter()
+                .map(|(code, &threshold)| {
+                    ModChar::from_str(code)
+                        .map(|w| (w, threshold))
+                        .map_err(|_err| {
+                            ErrorKind::InvalidOptions
+                                .error(format!("Invalid mod_thresholds code: '{code}'"))
+                        })
+                })
+                .collect::<Result<HashMap<_, _>>>()
+        })
+        .transpose()?;
     let bam = InputBam::try_from(options)?;
     let mods = InputMods::try_from(options)?;
-    Ok((bam, mods))
+    let extra = ExtraFilters {
+        mapq_max: options.mapq_max,
+        max_seq_len: options.max_seq_len,
+        max_align_len: options.max_align_len,
+        min_mods: options.min_mods,
+        require_mods: options.require_mods == Some(true),
+        read_id_regex,
+        read_group: options
+            .read_group
+            .as_ref()
+            .map(|v| v.iter().map(|w| w.as_bytes().to_vec()).collect()),
+        excluded_flags: [
+            (options.exclude_secondary, BAM_FSECONDARY),
+            (options.exclude_supplementary, BAM_FSUPPLEMENTARY),
+            (options.exclude_duplicate, BAM_FDUP),
+            (options.exclude_qc_fail, BAM_FQCFAIL),
+        ]
+        .into_iter()
+        .filter(|&(exclude, _)| exclude == Some(true))
+        .fold(0, |flags, (_, flag)| flags | flag),
+        mod_tags,
+        mod_thresholds,
+        seen_read_ids: (options.dedupe == Some(true)).then(RefCell::default),
+    };
+    Ok((bam, mods, extra))
 }
 
 /// Loads BAM data from a local file or URL; fetches only the region if specified.
+///
+/// CRAM input is decoded against `reference_fasta`, which it requires. URLs are
+/// opened with the headers, retries and time limit of `url_open`. A local file
+/// without an index is indexed first if `auto_index` is set, as in
+/// [`open_indexed`].
 #[expect(
     clippy::pattern_type_mismatch,
     reason = "matching on &Option<T> requires either ref patterns or & patterns; this is idiomatic"
 )]
-fn load_bam(bam: &InputBam) -> Result<rust_htslib::bam::IndexedReader> {
-    match (&bam.region, &bam.bam_path) {
+fn load_bam(
+    bam: &InputBam,
+    reference_fasta: Option<&str>,
+    url_open: &UrlOpen,
+    auto_index: bool,
+) -> Result<IndexedReader> {
+    check_cram_reference(&bam.bam_path, reference_fasta)?;
+    let mut reader = match (&bam.region, &bam.bam_path) {
         (Some(v), PathOrURLOrStdin::Path(w)) => {
             let fetch_def: FetchDefinition = v
                 .try_into()
-                .map_err(|e: nanalogue_core::Error| Error::from_reason(e.to_string()))?;
-            nanalogue_indexed_bam_reader(w, fetch_def)
-                .map_err(|e| Error::from_reason(format!("Failed to open BAM: {e}")))
+                .map_err(|e: nanalogue_core::Error| ErrorKind::of_core(&e).error(e.to_string()))?;
+            open_indexed(w, fetch_def, auto_index)
         }
-        (None, PathOrURLOrStdin::Path(w)) => nanalogue_indexed_bam_reader(w, FetchDefinition::All)
-            .map_err(|e| Error::from_reason(format!("Failed to open BAM: {e}"))),
-        (Some(v), PathOrURLOrStdin::URL(w)) => {
-            let fetch_def: FetchDefinition = v
-                .try_into()
-                .map_err(|e: nanalogue_core::Error| Error::from_reason(e.to_string()))?;
-            nanalogue_indexed_bam_reader_from_url(w, fetch_def)
-                .map_err(|e| Error::from_reason(format!("Failed to open BAM: {e}")))
+        (None, PathOrURLOrStdin::Path(w)) => open_indexed(w, FetchDefinition::All, auto_index),
+        (_, PathOrURLOrStdin::URL(w)) => url_open.open(w, bam.region.as_ref()),
+        _ => Err(ErrorKind::InvalidOptions.error("Stdin not supported")),
+    }?;
+    if let Some(v) = reference_fasta {
+        reader.set_reference(v).map_err(|e| {
+            ErrorKind::of_htslib(&e).error(format!("Failed to load reference FASTA: {e}"))
+        })?;
+    }
+    Ok(reader)
+}
+
+/// Opens the local file `path` through its index and fetches `fetch_def`.
+///
+/// If the file has no index, one is built next to it with a warning when
+/// `auto_index` is set, which needs a coordinate-sorted BAM file; otherwise the
+/// error suggests `buildIndex`. An index that exists but cannot be loaded is
+/// left alone.
+///
+/// # Errors
+/// Returns an `IndexMissing` error if the file has no index and `auto_index` is
+/// not set, or an error if the index cannot be built or the file cannot be read.
+fn open_indexed(
+    path: &Path,
+    fetch_def: FetchDefinition<'_>,
+    auto_index: bool,
+) -> Result<IndexedReader> {
+    let open_error =
+        |e: &nanalogue_core::Error| ErrorKind::of_core(e).error(format!("Failed to open BAM: {e}"));
+    let mut reader = match IndexedReader::from_path(path) {
+        Ok(v) => v,
+        Err(e)
+            if matches!(ErrorKind::of_htslib(&e), ErrorKind::IndexMissing)
+                && !has_index_file(path) =>
+        {
+            if !auto_index {
+                return Err(ErrorKind::IndexMissing.error(format!(
+                    "BAM '{}' has no index; create one with buildIndex, or set autoIndex to build it when the file is opened",
+                    path.display()
+                )));
+            }
+            logging::warn(&format!(
+                "BAM '{}' has no index; building one next to it",
+                path.display()
+            ));
+            build_bai(&path.to_string_lossy(), 1)?;
+            IndexedReader::from_path(path).map_err(|err| open_error(&err.into()))?
         }
-        (None, PathOrURLOrStdin::URL(w)) => {
-            nanalogue_indexed_bam_reader_from_url(w, FetchDefinition::All)
-                .map_err(|e| Error::from_reason(format!("Failed to open BAM: {e}")))
+        Err(e) => return Err(open_error(&e.into())),
+    };
+    reader.fetch(fetch_def).map_err(|e| open_error(&e.into()))?;
+    Ok(reader)
+}
+
+/// Checks that CRAM input comes with the reference FASTA needed to decode it.
+///
+/// # Errors
+/// Returns an error if `bam_path` is a CRAM file and `reference_fasta` is not set.
+fn check_cram_reference(bam_path: &PathOrURLOrStdin, reference_fasta: Option<&str>) -> Result<()> {
+    if reference_fasta.is_none() && is_cram(bam_path) {
+        return Err(
+            ErrorKind::InvalidOptions.error("referenceFasta is required to read CRAM input")
+        );
+    }
+    Ok(())
+}
+
+/// Whether `bam_path` holds CRAM data, from the magic bytes of a local file or
+/// the extension of a URL. Data from stdin is not inspected.
+#[expect(
+    clippy::pattern_type_mismatch,
+    reason = "the path is bound through the borrowed `bam_path` by default binding modes"
+)]
+fn is_cram(bam_path: &PathOrURLOrStdin) -> bool {
+    let has_cram_extension = |path: &Path| {
+        path.extension()
+            .is_some_and(|v| v.eq_ignore_ascii_case("cram"))
+    };
+    if let PathOrURLOrStdin::Path(path) = bam_path {
+        let mut magic = [0u8; 4];
+        return match File::open(path).and_then(|mut v| v.read_exact(&mut magic)) {
+            Ok(()) => &magic == b"CRAM",
+            Err(_) => has_cram_extension(path),
+        };
+    }
+    if let PathOrURLOrStdin::URL(url) = bam_path {
+        return has_cram_extension(Path::new(url.path()));
+    }
+    false
+}
+
+/// Reader used by a single query: either freshly opened or borrowed from a [`handle::NanalogueBam`].
+enum QueryReader<'a> {
+    /// Reader opened for this query only, using the thread pool of
+    /// [`init_thread_pool`] if `use_shared_pool` is set and the pool is started.
+    Owned {
+        /// The reader.
+        reader: IndexedReader,
+        /// Whether the reader may use the shared thread pool.
+        use_shared_pool: bool,
+    },
+    /// Unindexed reader over BAM data piped into stdin, using the thread pool of
+    /// [`init_thread_pool`] as an owned reader does.
+    Stdin {
+        /// The reader.
+        reader: Reader,
+        /// Whether the reader may use the shared thread pool.
+        use_shared_pool: bool,
+    },
+    /// Long-lived reader re-fetched for this query.
+    Shared(&'a mut IndexedReader),
+}
+
+impl QueryReader<'_> {
+    /// Returns the records of this query and the BAM header, converting the regions
+    /// in `bam` and `mods` to BED3 coordinates against that header.
+    ///
+    /// With `regions`, the records come from fetching each of them in turn.
+    fn records(
+        &mut self,
+        bam: &mut InputBam,
+        mods: &mut InputMods<OptionalTag>,
+        regions: Option<&Regions>,
+    ) -> Result<(QueryRecords<'_>, HeaderView)> {
+        let (reader, header) = match *self {
+            Self::Stdin {
+                ref mut reader,
+                use_shared_pool,
+            } => {
+                let header = thread_pool::prepare(reader, bam, mods, use_shared_pool)?;
+                return Ok((QueryRecords::Stdin(reader.rc_records()), header));
+            }
+            Self::Owned {
+                ref mut reader,
+                use_shared_pool,
+            } => {
+                let header = thread_pool::prepare(reader, bam, mods, use_shared_pool)?;
+                (reader, header)
+            }
+            Self::Shared(ref mut reader) => {
+                // `BamRcRecords::new` installs a new thread pool on every call, which htslib
+                // cannot do on a reader that already has one. The shared reader gets its pool
+                // once when it is opened, and the rest of that setup is repeated here.
+                let header = reader.header().clone();
+                prepare_regions(&header, bam, mods)?;
+                (&mut **reader, header)
+            }
+        };
+        let records = match regions {
+            Some(v) => QueryRecords::Regions(RegionRecords::new(reader, v, &header)?),
+            None => QueryRecords::Fetched(reader.rc_records()),
+        };
+        Ok((records, header))
+    }
+}
+
+/// Converts the regions in `bam` and `mods` to BED3 coordinates against `header`.
+///
+/// # Errors
+/// Returns an error if a region is not in the header.
+fn prepare_regions(
+    header: &HeaderView,
+    bam: &mut InputBam,
+    mods: &mut InputMods<OptionalTag>,
+) -> Result<()> {
+    bam.convert_region_to_bed3(header.clone())
+        .and_then(|()| mods.convert_region_to_bed3(header.clone()))
+        .map_err(|e| ErrorKind::of_core(&e).error(format!("Failed to read BAM records: {e}")))
+}
+
+/// Records of a single query, from one fetch or from several regions.
+enum QueryRecords<'r> {
+    /// Records of the region (or whole file) fetched when the reader was set up.
+    Fetched(RcRecords<'r, IndexedReader>),
+    /// Records of each of the `regions` option, fetched in turn.
+    Regions(RegionRecords<'r>),
+    /// Records read from stdin, in the order they arrive.
+    Stdin(RcRecords<'r, Reader>),
+}
+
+impl Iterator for QueryRecords<'_> {
+    type Item = std::result::Result<Rc<Record>, rust_htslib::errors::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match *self {
+            Self::Fetched(ref mut records) => records.next(),
+            Self::Regions(ref mut records) => records.next(),
+            Self::Stdin(ref mut records) => records.next(),
         }
-        _ => Err(Error::from_reason("Stdin not supported")),
     }
 }
 
+/// Returns a reader positioned at the region requested in `bam`.
+///
+/// A shared reader is re-fetched in place, keeping the reference it was opened
+/// with, and BAM data from stdin gets a plain, unindexed reader; otherwise the
+/// file is opened with [`load_bam`], using the `reference_fasta`, `url_retries`
+/// and `url_timeout_ms` of `options`.
+fn query_reader<'a>(
+    bam: &InputBam,
+    options: &ReadOptions,
+    shared: Option<&'a mut IndexedReader>,
+) -> Result<QueryReader<'a>> {
+    let reference_fasta = options.reference_fasta.as_deref();
+    let url_open = UrlOpen::new(
+        options.url_retries,
+        options.url_timeout_ms,
+        options.url_headers.as_ref(),
+    )?;
+    let Some(reader) = shared else {
+        if let PathOrURLOrStdin::Stdin = bam.bam_path {
+            let mut stdin_reader = nanalogue_bam_reader_from_stdin().map_err(|e| {
+                ErrorKind::of_core(&e).error(format!("Failed to open BAM from stdin: {e}"))
+            })?;
+            if let Some(v) = reference_fasta {
+                stdin_reader.set_reference(v).map_err(|e| {
+                    ErrorKind::of_htslib(&e).error(format!("Failed to load reference FASTA: {e}"))
+                })?;
+            }
+            return Ok(QueryReader::Stdin {
+                reader: stdin_reader,
+                use_shared_pool: options.threads.is_none(),
+            });
+        }
+        let auto_index = options.auto_index == Some(true);
+        return load_bam(bam, reference_fasta, &url_open, auto_index).map(|v| QueryReader::Owned {
+            reader: v,
+            use_shared_pool: options.threads.is_none(),
+        });
+    };
+    let fetch_def: FetchDefinition = match bam.region.as_ref() {
+        Some(v) => v
+            .try_into()
+            .map_err(|e: nanalogue_core::Error| ErrorKind::of_core(&e).error(e.to_string()))?,
+        None => FetchDefinition::All,
+    };
+    reader
+        .fetch(fetch_def)
+        .map_err(|e| ErrorKind::of_htslib(&e).error(format!("Failed to fetch region: {e}")))?;
+    Ok(QueryReader::Shared(reader))
+}
+
 /// Options for BAM simulation.
 #[napi(object)]
 #[non_exhaustive]
@@ -489,34 +1542,185 @@ pub struct SimulateOptions {
     pub bam_path: String,
     /// Output path for the generated FASTA reference file.
     pub fasta_path: String,
+    /// Seed for the random generation, so that the same seed and configuration
+    /// write byte-identical files. Overrides any `seed` in `json_config`. Must be
+    /// non-negative if set.
+    pub seed: Option<i64>,
+    /// Replace `bam_path` and `fasta_path` if they exist. Defaults to false, in
+    /// which case an existing file is an error.
+    pub overwrite: Option<bool>,
+}
+
+/// Summary of the data written by `simulate_mod_bam`.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug, Default)]
+pub struct SimulateStats {
+    /// Number of records in the BAM file, mapped or not.
+    #[napi(js_name = "n_reads")]
+    pub n_reads: i64,
+    /// Number of modification calls, i.e. of entries in the ML tags.
+    #[napi(js_name = "n_mods")]
+    pub n_mods: i64,
+    /// Number of contigs in the FASTA file and BAM header.
+    #[napi(js_name = "n_contigs")]
+    pub n_contigs: i64,
+    /// Total length of the read sequences.
+    #[napi(js_name = "total_bases")]
+    pub total_bases: i64,
+    /// Ids of the reads in the BAM file, in file order and each given once.
+    #[napi(js_name = "read_ids")]
+    pub read_ids: Vec<String>,
+    /// Names of the contigs in the FASTA file and BAM header, in header order.
+    pub contigs: Vec<String>,
 }
 
 /// Simulates a BAM file with modifications based on JSON configuration.
 ///
-/// Creates both a BAM file and a corresponding FASTA reference file.
+/// Creates both a BAM file and a corresponding FASTA reference file, and returns
+/// counts of what was generated, read back from the BAM file.
 ///
 /// # Errors
 /// Returns an error if JSON parsing fails or file I/O operations fail.
 #[napi]
-pub async fn simulate_mod_bam(options: SimulateOptions) -> Result<()> {
-    tokio::task::spawn_blocking(move || simulate_mod_bam_sync(&options))
-        .await
-        .map_err(|e| Error::from_reason(format!("Task join error: {e}")))?
+pub async fn simulate_mod_bam(options: SimulateOptions) -> Result<SimulateStats> {
+    run_limited(move || simulate_mod_bam_sync(&options)).await
 }
 
 /// Synchronous implementation of `simulate_mod_bam`.
-fn simulate_mod_bam_sync(options: &SimulateOptions) -> Result<()> {
-    // Parse JSON config
-    let config: SimulationConfig = serde_json::from_str(&options.json_config)
-        .map_err(|e| Error::from_reason(format!("Invalid JSON config: {e}")))?;
+fn simulate_mod_bam_sync(options: &SimulateOptions) -> Result<SimulateStats> {
+    let mut config = parse_simulation_config(&options.json_config)?;
+    if let Some(v) = options.seed {
+        config.seed = Some(
+            u64::try_from(v)
+                .map_err(|_err| ErrorKind::InvalidOptions.error("seed must be non-negative"))?,
+        );
+    }
+    run_simulation(
+        config,
+        &options.bam_path,
+        &options.fasta_path,
+        options.overwrite == Some(true),
+    )
+}
 
-    // Run simulation
-    rust_simulate_mod_bam::run(config, &options.bam_path, &options.fasta_path)
-        .map_err(|e| Error::from_reason(format!("Simulation failed: {e}")))?;
+/// Writes the BAM and FASTA files of `config` and counts what was generated.
+///
+/// The output paths are checked before anything is generated, which can take a
+/// while, and existing files are kept unless `overwrite` is set.
+fn run_simulation(
+    config: SimulationConfig,
+    bam_path: &str,
+    fasta_path: &str,
+    overwrite: bool,
+) -> Result<SimulateStats> {
+    check_output_path(bam_path, overwrite)?;
+    check_output_path(fasta_path, overwrite)?;
+    rust_simulate_mod_bam::run(config, bam_path, fasta_path)
+        .map_err(|e| ErrorKind::of_core(&e).error(format!("Simulation failed: {e}")))?;
+
+    simulation_stats(bam_path)
+}
 
+/// Checks that a file can be written at `path`: that it does not exist unless
+/// `overwrite` is set, and that its directory exists and is not read-only.
+fn check_output_path(path: &str, overwrite: bool) -> Result<()> {
+    let output = Path::new(path);
+    if !overwrite && output.exists() {
+        return Err(ErrorKind::IoError.error(format!(
+            "Output file '{path}' already exists; set overwrite to replace it"
+        )));
+    }
+    let dir = output
+        .parent()
+        .filter(|v| !v.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let metadata = fs::metadata(dir).map_err(|e| {
+        ErrorKind::of_io(&e).error(format!(
+            "Output directory '{}' cannot be used: {e}",
+            dir.display()
+        ))
+    })?;
+    if !metadata.is_dir() {
+        return Err(ErrorKind::IoError.error(format!(
+            "Output directory '{}' is not a directory",
+            dir.display()
+        )));
+    }
+    if metadata.permissions().readonly() {
+        return Err(ErrorKind::IoError.error(format!(
+            "Output directory '{}' is not writable",
+            dir.display()
+        )));
+    }
     Ok(())
 }
 
+/// Counts the reads, modification calls, contigs and bases of a simulated BAM
+/// file, and lists its read ids and contigs.
+fn simulation_stats(bam_path: &str) -> Result<SimulateStats> {
+    let mut reader = Reader::from_path(bam_path).map_err(|e| {
+        ErrorKind::of_htslib(&e).error(format!("Failed to read simulated BAM: {e}"))
+    })?;
+    let mut stats = SimulateStats {
+        n_contigs: i64::from(reader.header().target_count()),
+        contigs: reader
+            .header()
+            .target_names()
+            .into_iter()
+            .map(|v| String::from_utf8_lossy(v).into_owned())
+            .collect(),
+        ..SimulateStats::default()
+    };
+    let mut seen = HashSet::new();
+    for entry in reader.records() {
+        let record = entry.map_err(|e| {
+            ErrorKind::of_htslib(&e).error(format!("Failed to read simulated BAM: {e}"))
+        })?;
+        let n_mods = match record.aux(b"ML") {
+            Ok(Aux::ArrayU8(v)) => v.len(),
+            _ => 0,
+        };
+        let to_i64 = |v: usize| i64::try_from(v).unwrap_or(i64::MAX);
+        stats.n_reads = stats.n_reads.saturating_add(1);
+        stats.n_mods = stats.n_mods.saturating_add(to_i64(n_mods));
+        stats.total_bases = stats.total_bases.saturating_add(to_i64(record.seq_len()));
+        if seen.insert(record.qname().to_vec()) {
+            stats
+                .read_ids
+                .push(String::from_utf8_lossy(record.qname()).into_owned());
+        }
+    }
+    Ok(stats)
+}
+
+/// Checks a simulation configuration without simulating anything.
+///
+/// Parses `json_config` as `simulate_mod_bam` would, so that a configuration
+/// can be validated before any output paths are chosen. No files are read or
+/// written.
+///
+/// # Errors
+/// Returns an error naming the offending field, e.g. `contigs.number: invalid
+/// type: string "5", expected u32`, if the configuration is not valid.
+#[napi]
+pub async fn validate_simulation_config(json_config: String) -> Result<()> {
+    tokio::task::spawn_blocking(move || parse_simulation_config(&json_config).map(|_| ()))
+        .await
+        .map_err(|e| ErrorKind::Internal.error(format!("Task join error: {e}")))?
+}
+
+/// Parses a JSON simulation configuration, reporting the path to any invalid field.
+fn parse_simulation_config(json_config: &str) -> Result<SimulationConfig> {
+    let mut deserializer = serde_json::Deserializer::from_str(json_config);
+    let config = serde_path_to_error::deserialize(&mut deserializer)
+        .map_err(|e| ErrorKind::InvalidOptions.error(format!("Invalid JSON config: {e}")))?;
+    deserializer
+        .end()
+        .map_err(|e| ErrorKind::InvalidOptions.error(format!("Invalid JSON config: {e}")))?;
+    Ok(config)
+}
+
 /// Returns detailed modification data for reads as JSON.
 ///
 /// This is the non-polars alternative to `polars_bam_mods`.
@@ -525,89 +1729,352 @@ fn simulate_mod_bam_sync(options: &SimulateOptions) -> Result<()> {
 /// Returns an error if BAM reading fails or JSON parsing fails.
 #[napi]
 pub async fn bam_mods(options: ReadOptions) -> Result<serde_json::Value> {
-    tokio::task::spawn_blocking(move || bam_mods_sync(&options))
-        .await
-        .map_err(|e| Error::from_reason(format!("Task join error: {e}")))?
+    run_limited(move || bam_mods_sync(&options, None)).await
 }
 
 /// Synchronous implementation of `bam_mods`.
-fn bam_mods_sync(options: &ReadOptions) -> Result<serde_json::Value> {
-    let (offset, limit) = validate_pagination(options)?;
-    let (mut bam, mut mods) = build_input_options(options)?;
+fn bam_mods_sync(
+    options: &ReadOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<serde_json::Value> {
+    let mut passed = Vec::new();
+    let prefilter = (options.no_prefilter == Some(true)).then_some(&mut passed);
+    let json_str = bam_mods_text(options, shared, ReadsJson::Detailed, prefilter)?;
+    let mut value: serde_json::Value = serde_json::from_str(&json_str)
+        .map_err(|e| ErrorKind::Internal.error(format!("Failed to parse JSON: {e}")))?;
+    if options.no_prefilter == Some(true)
+        && let Some(reads) = value.as_array_mut()
+    {
+        for (read, flag) in reads.iter_mut().zip(passed) {
+            if let Some(fields) = read.as_object_mut() {
+                let _: Option<serde_json::Value> =
+                    fields.insert("passed_prefilter".to_owned(), flag.into());
+            }
+        }
+    }
+    Ok(value)
+}
 
-    let mut reader = load_bam(&bam)?;
-    let bam_rc_records = BamRcRecords::new(&mut reader, &mut bam, &mut mods)
-        .map_err(|e| Error::from_reason(format!("Failed to read BAM records: {e}")))?;
+/// Returns the detailed modification data of `bam_mods` as JSON text.
+///
+/// The text is compact, one read per line, unless `pretty` is set, which
+/// indents each read over several lines for reading by eye.
+///
+/// # Errors
+/// Returns an error if BAM reading fails or input options are invalid.
+#[napi]
+pub async fn bam_mods_json(options: ReadOptions) -> Result<String> {
+    run_limited(move || bam_mods_json_sync(&options, None)).await
+}
 
-    let filtered = bam_rc_records
-        .rc_records
-        .filter(|r| r.as_ref().map_or(true, |v| v.pre_filt(&bam)));
-    let paginated = filtered.skip(offset).take(limit);
+/// Synchronous implementation of `bam_mods_json`.
+fn bam_mods_json_sync(options: &ReadOptions, shared: Option<&mut IndexedReader>) -> Result<String> {
+    let layout = if options.pretty == Some(true) {
+        ReadsJson::Pretty
+    } else {
+        ReadsJson::Detailed
+    };
+    bam_mods_text(options, shared, layout, None)
+}
+
+/// Writes the reads of `bam_mods` as JSON text in the given `layout`.
+///
+/// With `prefilter`, every record is written, and whether each passes the read
+/// filters is pushed to it in the order written.
+fn bam_mods_text(
+    options: &ReadOptions,
+    shared: Option<&mut IndexedReader>,
+    layout: ReadsJson,
+    mut prefilter: Option<&mut Vec<bool>>,
+) -> Result<String> {
+    let (offset, limit) = validate_pagination(options)?;
+    let interval = progress_interval(options.progress_interval)?;
+    let (mut bam, mut mods, extra) = build_input_options(options)?;
+    let regions = Regions::from_options(options)?;
+
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, _) = reader.records(&mut bam, &mut mods, regions.as_ref())?;
+    let no_prefilter = prefilter.is_some();
+
+    let filtered = Progress::new(rc_records, options.on_progress.clone(), interval).filter(|r| {
+        no_prefilter
+            || r.as_ref()
+                .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    let paginated = sort_records(filtered, options.sort_by)
+        .skip(offset)
+        .take(limit)
+        .inspect(|r| {
+            if let (Some(passed), Ok(record)) = (prefilter.as_deref_mut(), r.as_ref()) {
+                passed.push(passes_read_filters(record, &bam, &mods, &extra));
+            }
+        });
 
     let mut buffer = Vec::new();
-    // Use detailed mode (Some(false) = compact JSON, Some(true) = pretty JSON)
-    rust_read_info::run(
+    write_reads_json(
         &mut buffer,
         paginated,
-        mods,
-        Some(false), // detailed=true, pretty=false
+        &mods,
+        extra.mod_selection(),
+        layout,
+        options.report_mod_data == Some(true),
     )
-    .map_err(|e| Error::from_reason(format!("bam_mods failed: {e}")))?;
+    .map_err(|e| ErrorKind::of_core(&e).error(format!("bam_mods failed: {e}")))?;
+
+    String::from_utf8(buffer).map_err(|e| ErrorKind::Internal.error(format!("Invalid UTF-8: {e}")))
+}
+
+/// Returns one record per modification call on the reads that `bam_mods` returns.
+///
+/// Offers the same data as `bam_mods` in a flat, typed form, built directly from
+/// the core read data. `offset` and `limit` count reads, not calls.
+///
+/// # Errors
+/// Returns an error if BAM reading fails or input options are invalid.
+#[napi]
+pub async fn bam_mods_typed(options: ReadOptions) -> Result<Vec<ModCall>> {
+    run_limited(move || bam_mods_typed_sync(&options, None)).await
+}
+
+/// Synchronous implementation of `bam_mods_typed`.
+fn bam_mods_typed_sync(
+    options: &ReadOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<Vec<ModCall>> {
+    let (offset, limit) = validate_pagination(options)?;
+    let interval = progress_interval(options.progress_interval)?;
+    let (mut bam, mut mods, extra) = build_input_options(options)?;
+    let regions = Regions::from_options(options)?;
+
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, _) = reader.records(&mut bam, &mut mods, regions.as_ref())?;
+
+    let filtered = Progress::new(rc_records, options.on_progress.clone(), interval).filter(|r| {
+        r.as_ref()
+            .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    let mut calls = Vec::new();
+    for entry in sort_records(filtered, options.sort_by)
+        .skip(offset)
+        .take(limit)
+    {
+        let record = entry.map_err(|e| {
+            ErrorKind::of_htslib(&e).error(format!("Failed to read BAM record: {e}"))
+        })?;
+        calls.extend(ModCall::from_record(&record, &mods, extra.mod_selection())?);
+    }
+    Ok(calls)
+}
+
+/// Windowing operation applied by `window_reads`.
+#[napi(string_enum = "snake_case")]
+#[non_exhaustive]
+#[derive(Debug, Default)]
+pub enum WinOp {
+    /// Mean modification density in each window.
+    #[default]
+    Density,
+    /// Gradient of the modification density in each window.
+    GradDensity,
+    /// Sum of the modification probabilities in each window.
+    Sum,
+    /// Highest modification probability in each window.
+    Max,
+    /// Number of modified calls in each window, i.e. those with a probability of
+    /// at least 0.5.
+    Count,
+    /// Mean modification probability in each window, without thresholding the
+    /// calls as `Density` does.
+    RawMean,
+}
+
+/// Output format of `window_reads`.
+#[napi(string_enum = "snake_case")]
+#[non_exhaustive]
+#[derive(Debug, Default)]
+pub enum WindowFormat {
+    /// JSON array with one entry per read, holding its windows in `mod_table`.
+    #[default]
+    Json,
+    /// Tab-separated table with a header line and one row per window.
+    Tsv,
+}
+
+/// Coordinates along which `window_reads` windows the calls of each read.
+#[napi(string_enum = "snake_case")]
+#[non_exhaustive]
+#[derive(Debug, Default)]
+pub enum WindowCoordinate {
+    /// Windows of `win` calls along the read.
+    #[default]
+    Read,
+    /// Windows of `win` reference bases, starting at multiples of `step` on the
+    /// contig, holding the calls of a read at those positions. Reads without an
+    /// alignment are left out. The JSON output then has one object per window,
+    /// with the fields of the TSV columns.
+    Reference,
+}
 
-    let json_str =
-        String::from_utf8(buffer).map_err(|e| Error::from_reason(format!("Invalid UTF-8: {e}")))?;
-    serde_json::from_str(&json_str)
-        .map_err(|e| Error::from_reason(format!("Failed to parse JSON: {e}")))
+/// What [`WindowCoordinate::Read`] windowing does with the calls of a read past
+/// its last window of `win` calls.
+#[napi(string_enum = "snake_case")]
+#[non_exhaustive]
+#[derive(Debug, Default)]
+pub enum EdgeMode {
+    /// Leave them out of every window, as do reads with fewer than `win` calls.
+    #[default]
+    Drop,
+    /// Add the window that starts at the next step, cut short at the last call.
+    /// It holds fewer than `win` calls and is valued over those, so `sum` and
+    /// `count` totals only count them. A read with fewer than `win` calls gets
+    /// one such window.
+    Partial,
+    /// Add a window of the last `win` calls, i.e. the short window padded out
+    /// with the calls before it, overlapping the window before it. Reads with
+    /// fewer than `win` calls are still left out.
+    Pad,
 }
 
 /// Options for windowed modification analysis.
-#[napi(object)]
+#[napi(object, object_to_js = false)]
 #[non_exhaustive]
 #[derive(Debug, Default)]
 pub struct WindowOptions {
-    /// Path to the BAM file (local path or URL).
+    /// Path to the BAM file (local path or URL), or `-` to read BAM data from stdin.
     pub bam_path: String,
-    /// If true, treat `bam_path` as a URL. Otherwise treat as file path.
+    /// If true, treat `bam_path` as a URL, if false as a file path. If unset, paths
+    /// starting with `http://`, `https://`, `s3://`, `gs://` or `file://` are URLs.
+    /// A `file://` URL is read as the local file it names.
     pub treat_as_url: Option<bool>,
-    /// Window size in number of bases.
+    /// Reference FASTA used to decode CRAM input, which cannot be read without it.
+    /// Ignored for BAM input.
+    pub reference_fasta: Option<String>,
+    /// Times to retry opening a URL after a failed attempt (default: 0). The wait
+    /// between attempts starts at 100 ms and doubles each time, up to 5 s. Ignored
+    /// for local files.
+    pub url_retries: Option<u32>,
+    /// Time limit of each attempt at opening a URL, in milliseconds. Must be at
+    /// least 1. Unset means no limit. An attempt that times out is not retried.
+    /// Ignored for local files.
+    pub url_timeout_ms: Option<u32>,
+    /// Extra HTTP request headers sent when opening a URL and reading from it, e.g.
+    /// `Authorization` or `User-Agent`. Only `http://` and `https://` URLs take
+    /// headers; other URLs are rejected. Ignored for local files.
+    pub url_headers: Option<HashMap<String, String>>,
+    /// Build a `.bai` index next to a local BAM file that has none, with a
+    /// warning, before reading it. Defaults to false.
+    pub auto_index: Option<bool>,
+    /// Window size in number of calls, or of reference bases with
+    /// [`WindowCoordinate::Reference`].
     pub win: i32,
     /// Step size for sliding the window.
     pub step: i32,
-    /// Type of windowing operation: `density` or `grad_density`.
-    pub win_op: Option<String>,
+    /// Type of windowing operation. Defaults to [`WinOp::Density`].
+    pub win_op: Option<WinOp>,
+    /// Format of the output. Defaults to [`WindowFormat::Json`].
+    pub output_format: Option<WindowFormat>,
+    /// Windows with fewer valid calls than this are left out of the output.
+    pub min_window_calls: Option<u32>,
+    /// Coordinates along which reads are windowed. Defaults to
+    /// [`WindowCoordinate::Read`].
+    pub coordinate: Option<WindowCoordinate>,
+    /// What to do with the calls of a read past its last window of `win` calls.
+    /// Defaults to [`EdgeMode::Drop`]. Ignored with [`WindowCoordinate::Reference`],
+    /// whose windows at the ends of a read already hold whatever calls fall in them.
+    pub edge_mode: Option<EdgeMode>,
+    /// Window the reads on several threads. The selected reads are all read into
+    /// memory first, and the output is in the same order as without it.
+    /// Defaults to false. Only honoured by `window_reads`.
+    pub parallel: Option<bool>,
     // BAM filtering options (duplicated from ReadOptions due to NAPI-RS limitation)
     /// Minimum sequence length filter.
     pub min_seq_len: Option<u32>,
+    /// Maximum sequence length filter. Must be >= `min_seq_len` if both are set.
+    pub max_seq_len: Option<u32>,
     /// Minimum alignment length filter.
     pub min_align_len: Option<i32>,
+    /// Maximum alignment length filter. Must be >= `min_align_len` if both are set.
+    /// Unmapped reads are excluded when it is set, as with `min_align_len`.
+    pub max_align_len: Option<i32>,
     /// Filter to a set of read IDs.
     pub read_id_set: Option<Vec<String>>,
-    /// Number of threads for BAM reading.
+    /// Keep reads whose id matches this regular expression, anywhere in the id
+    /// unless anchored (e.g. `^run1_`). With `read_id_set`, reads must satisfy both.
+    pub read_id_regex: Option<String>,
+    /// Keep reads whose `RG` tag is one of these read group ids, as listed by
+    /// `read_groups`. Reads without the tag are excluded.
+    pub read_group: Option<Vec<String>>,
+    /// Number of threads for BAM reading. Unset, the reader uses the pool of
+    /// [`init_thread_pool`] if it has been started.
     pub threads: Option<u8>,
-    /// Include zero-length sequences (may cause crashes).
+    /// Include records without a sequence, such as most secondary alignments. They
+    /// have a sequence length of 0 and no modification data, and are skipped when
+    /// windowing.
     pub include_zero_len: Option<bool>,
-    /// Comma-separated read filter.
-    pub read_filter: Option<String>,
+    /// Alignment types to keep, as a list or a comma-separated string.
+    pub read_filter: Option<ReadFilter>,
     /// Subsample fraction (0.0 to 1.0).
     pub sample_fraction: Option<f64>,
     /// Minimum mapping quality filter.
     pub mapq_filter: Option<u8>,
+    /// Maximum mapping quality filter. Must be >= `mapq_filter` if both are set.
+    /// Reads with unavailable mapping quality (255) are excluded unless this is 255.
+    pub mapq_max: Option<u8>,
     /// Exclude reads with unavailable mapping quality.
     pub exclude_mapq_unavail: Option<bool>,
+    /// Exclude secondary alignments (SAM flag 0x100).
+    pub exclude_secondary: Option<bool>,
+    /// Exclude supplementary alignments (SAM flag 0x800).
+    pub exclude_supplementary: Option<bool>,
+    /// Exclude reads marked as PCR or optical duplicates (SAM flag 0x400), which
+    /// `nanalogue_core` cannot otherwise read.
+    pub exclude_duplicate: Option<bool>,
+    /// Exclude reads that failed quality checks (SAM flag 0x200), which
+    /// `nanalogue_core` cannot otherwise read.
+    pub exclude_qc_fail: Option<bool>,
+    /// Return every record of `read_info` and `bam_mods`, also those the read
+    /// filters would leave out, each with `passed_prefilter` telling whether it
+    /// passes them. Other queries still filter. Defaults to false.
+    pub no_prefilter: Option<bool>,
+    /// Keep only the first record of each read id that passes the other filters,
+    /// so that a read with several alignments, such as a supplementary alignment
+    /// in another of the `regions`, is counted once. Defaults to false.
+    pub dedupe: Option<bool>,
+    /// Minimum number of modified calls per read: calls that pass the modification
+    /// filters with a probability of at least 0.5, as counted in `mod_count`.
+    pub min_mods: Option<u32>,
+    /// Keep only reads with modification data, that is with MM and ML tags.
+    /// Defaults to false.
+    pub require_mods: Option<bool>,
     /// Genomic region filter.
     pub region: Option<String>,
-    /// Only include reads fully spanning the region.
+    /// Several genomic regions to query in one call, as an alternative to `region`.
+    pub regions: Option<Vec<String>>,
+    /// Path to a BED file whose intervals are queried as `regions`.
+    /// Only the first three columns are read.
+    pub region_bed: Option<String>,
+    /// Only include reads fully spanning the region (or one of the `regions`).
     pub full_region: Option<bool>,
+    /// Read the coordinates of `region`, `regions` and `mod_region` as 1-based
+    /// and inclusive instead of 0-based and half-open (the default).
+    pub one_based: Option<bool>,
     // Mod options
     /// Filter to specific modification tag.
     pub tag: Option<String>,
+    /// Several modification tags whose calls are all kept, e.g. `["m", "h"]` for
+    /// 5mC and 5hmC together, as an alternative to `tag`.
+    pub tags: Option<Vec<String>>,
     /// Filter by modification strand.
-    pub mod_strand: Option<String>,
+    pub mod_strand: Option<ModStrand>,
     /// Minimum modification quality threshold.
     pub min_mod_qual: Option<u8>,
     /// Reject modification calls where low < probability < high.
     /// Array of [low, high] where both are 0-255.
     pub reject_mod_qual_non_inclusive: Option<Vec<u8>>,
+    /// Keep modification calls with a probability of at least this (0 to 1), e.g.
+    /// 0.8 for calls of ML value 204 and above. Overrides `min_mod_qual` and
+    /// `reject_mod_qual_non_inclusive`.
+    pub mod_threshold: Option<f64>,
     /// Trim modification info from read ends (bp).
     pub trim_read_ends_mod: Option<u32>,
     /// Base quality filter for modifications.
@@ -616,11 +2083,28 @@ pub struct WindowOptions {
     pub mod_region: Option<String>,
     /// Maximum number of records to return. Must be > 0 if set. If omitted, returns all records.
     pub limit: Option<i64>,
-    /// Number of records to skip before returning results. Must be >= 0 if set. Defaults to 0.
+    /// Number of records that pass the filters to skip before returning results. Must be >= 0
+    /// if set. Defaults to 0.
     pub offset: Option<i64>,
     /// Seed for deterministic sampling. Required for stable pagination with `sample_fraction`.
-    /// Must be non-negative if set.
+    /// Must be non-negative if set. If omitted, each call samples a different subset.
     pub sample_seed: Option<i64>,
+    /// Token to cancel the windowing before it finishes.
+    pub cancel_token: Option<CancelFlag>,
+    /// File to write the output to instead of returning it, for outputs too large
+    /// to hold in a string. The call then resolves to the number of lines written.
+    pub output_path: Option<String>,
+    /// Gzip-compress the file written to `output_path`, adding a `.gz` extension
+    /// to the path if it does not have one.
+    pub compress: Option<bool>,
+}
+
+impl WindowOptions {
+    /// Fewest valid calls of a window that is kept, from `min_window_calls`.
+    fn min_calls(&self) -> usize {
+        self.min_window_calls
+            .map_or(0, |v| usize::try_from(v).unwrap_or(usize::MAX))
+    }
 }
 
 impl From<&WindowOptions> for ReadOptions {
@@ -630,55 +2114,292 @@ impl From<&WindowOptions> for ReadOptions {
         Self {
             bam_path: opts.bam_path.clone(),
             treat_as_url: opts.treat_as_url,
+            reference_fasta: opts.reference_fasta.clone(),
+            url_retries: opts.url_retries,
+            url_timeout_ms: opts.url_timeout_ms,
+            url_headers: opts.url_headers.clone(),
+            auto_index: opts.auto_index,
             min_seq_len: opts.min_seq_len,
+            max_seq_len: opts.max_seq_len,
             min_align_len: opts.min_align_len,
+            max_align_len: opts.max_align_len,
             read_id_set: opts.read_id_set.clone(),
+            read_id_regex: opts.read_id_regex.clone(),
+            read_group: opts.read_group.clone(),
             threads: opts.threads,
             include_zero_len: opts.include_zero_len,
             read_filter: opts.read_filter.clone(),
             sample_fraction: opts.sample_fraction,
             mapq_filter: opts.mapq_filter,
+            mapq_max: opts.mapq_max,
             exclude_mapq_unavail: opts.exclude_mapq_unavail,
+            exclude_secondary: opts.exclude_secondary,
+            exclude_supplementary: opts.exclude_supplementary,
+            exclude_duplicate: opts.exclude_duplicate,
+            exclude_qc_fail: opts.exclude_qc_fail,
+            no_prefilter: opts.no_prefilter,
+            dedupe: opts.dedupe,
+            min_mods: opts.min_mods,
+            require_mods: opts.require_mods,
+            report_mod_data: None,
             region: opts.region.clone(),
+            regions: opts.regions.clone(),
+            region_bed: opts.region_bed.clone(),
             full_region: opts.full_region,
+            one_based: opts.one_based,
             tag: opts.tag.clone(),
-            mod_strand: opts.mod_strand.clone(),
+            tags: opts.tags.clone(),
+            mod_strand: opts.mod_strand,
             min_mod_qual: opts.min_mod_qual,
             reject_mod_qual_non_inclusive: opts.reject_mod_qual_non_inclusive.clone(),
+            mod_threshold: opts.mod_threshold,
+            mod_thresholds: None,
             trim_read_ends_mod: opts.trim_read_ends_mod,
             base_qual_filter_mod: opts.base_qual_filter_mod,
             mod_region: opts.mod_region.clone(),
             limit: opts.limit,
             offset: opts.offset,
             sample_seed: opts.sample_seed,
+            on_progress: None,
+            progress_interval: None,
+            columns: None,
+            reverse_complement_minus: None,
+            show_base_qual: None,
+            show_insertions: None,
+            show_mod_markers: None,
+            qual_offset: None,
+            max_reads: None,
+            pretty: None,
+            sort_by: None,
         }
     }
 }
 
-/// Windows modification data along reads and returns JSON as string.
+/// Windows modification data along reads and returns JSON as string, or TSV if
+/// `output_format` is [`WindowFormat::Tsv`].
+///
+/// Each window carries its number of modified calls (`n_mod`, those with a
+/// probability of at least 0.5) and of valid calls (`n_valid`), and windows of
+/// fewer than `min_window_calls` valid calls are left out.
+///
+/// With `output_path`, the output is written to that file instead and the number
+/// of lines written is returned, gzip-compressed if `compress` is set. The file
+/// is removed again if the call fails.
 ///
 /// # Errors
 /// Returns an error if window/step size is invalid, BAM reading fails,
 /// or the windowing operation fails.
 #[napi]
-pub async fn window_reads(options: WindowOptions) -> Result<String> {
-    tokio::task::spawn_blocking(move || window_reads_sync(&options))
-        .await
-        .map_err(|e| Error::from_reason(format!("Task join error: {e}")))?
+pub async fn window_reads(options: WindowOptions) -> Result<Either<String, i64>> {
+    run_limited(move || window_reads_sync(&options, None)).await
 }
 
 /// Synchronous implementation of `window_reads`.
-fn window_reads_sync(options: &WindowOptions) -> Result<String> {
+fn window_reads_sync(
+    options: &WindowOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<Either<String, i64>> {
+    let compress = options.compress == Some(true);
+    let Some(raw_output_path) = options.output_path.as_deref() else {
+        if compress {
+            return Err(ErrorKind::InvalidOptions.error("compress requires outputPath to be set"));
+        }
+        let mut buffer = Vec::new();
+        write_windows(options, shared, &mut buffer)?;
+        return String::from_utf8(buffer)
+            .map(Either::A)
+            .map_err(|e| ErrorKind::Internal.error(format!("Invalid UTF-8: {e}")));
+    };
+    let has_gz_extension = Path::new(raw_output_path)
+        .extension()
+        .is_some_and(|v| v.eq_ignore_ascii_case("gz"));
+    let output_path = if compress && !has_gz_extension {
+        format!("{raw_output_path}.gz")
+    } else {
+        raw_output_path.to_owned()
+    };
+    if options.bam_path == output_path && !is_url(&options.bam_path, options.treat_as_url) {
+        return Err(ErrorKind::InvalidOptions
+            .error("outputPath must differ from bamPath, which is being read"));
+    }
+    let file = File::create(&output_path)
+        .map_err(|e| ErrorKind::IoError.error(format!("Failed to create output file: {e}")))?;
+    let write_error =
+        |e: std::io::Error| ErrorKind::IoError.error(format!("Failed to write output file: {e}"));
+    let result = if compress {
+        let mut writer =
+            LineCounter::new(GzEncoder::new(BufWriter::new(file), Compression::default()));
+        write_windows(options, shared, &mut writer).and_then(|()| {
+            // The gzip trailer is only written when the encoder is finished
+            let LineCounter { inner, lines } = writer;
+            inner
+                .finish()
+                .and_then(|mut v| v.flush())
+                .map(|()| lines)
+                .map_err(write_error)
+        })
+    } else {
+        let mut writer = LineCounter::new(BufWriter::new(file));
+        write_windows(options, shared, &mut writer)
+            .and_then(|()| writer.flush().map(|()| writer.lines).map_err(write_error))
+    };
+    match result {
+        Ok(lines) => Ok(Either::B(lines)),
+        // Leave no partial output behind, e.g. after a cancellation. A file that
+        // cannot be removed is left in place, as the original error matters more.
+        Err(e) => match fs::remove_file(&output_path) {
+            Ok(()) | Err(_) => Err(e),
+        },
+    }
+}
+
+/// Writer that counts the lines passing through it.
+#[derive(Debug)]
+struct LineCounter<W> {
+    /// Wrapped writer.
+    inner: W,
+    /// Newline characters written so far.
+    lines: i64,
+}
+
+impl<W> LineCounter<W> {
+    /// Wraps `inner` with a count of zero.
+    fn new(inner: W) -> Self {
+        Self { inner, lines: 0 }
+    }
+}
+
+impl<W: Write> Write for LineCounter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        let newlines = buf.iter().take(written).filter(|&&b| b == b'\n').count();
+        self.lines = self
+            .lines
+            .saturating_add(i64::try_from(newlines).unwrap_or(i64::MAX));
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Windows the reads selected by `options` and writes the output to `handle`,
+/// on several threads if `parallel` is set.
+fn write_windows<W: Write>(
+    options: &WindowOptions,
+    shared: Option<&mut IndexedReader>,
+    handle: &mut W,
+) -> Result<()> {
+    with_window_records(
+        options,
+        shared,
+        "window_reads",
+        |records, window_options, mods, extra, header| {
+            let selection = extra.mod_selection();
+            if options.parallel == Some(true) {
+                // Reads without an alignment are left out of reference windows
+                // before the records are split into chunks, so that they are
+                // warned about once rather than once per chunk
+                let is_reference = matches!(
+                    options.coordinate.unwrap_or_default(),
+                    WindowCoordinate::Reference
+                );
+                let mut unaligned = 0usize;
+                let mut windowed = records.filter(|r| {
+                    let is_left_out = is_reference && r.as_ref().is_ok_and(|v| v.is_unmapped());
+                    if is_left_out {
+                        unaligned = unaligned.saturating_add(1);
+                    }
+                    !is_left_out
+                });
+                parallel_windows::run(
+                    handle,
+                    &mut windowed,
+                    header,
+                    options.output_format.unwrap_or_default(),
+                    options.cancel_token.as_ref(),
+                    |buffer, chunk| {
+                        window_to(buffer, chunk, window_options, mods, selection, options)
+                    },
+                )
+                .map(|()| ref_windows::warn_unaligned(unaligned))
+            } else {
+                window_to(handle, records, window_options, mods, selection, options)
+            }
+        },
+    )
+}
+
+/// Windows `records` in the coordinates and format of `options` and writes the
+/// output to `handle`.
+fn window_to<W: Write>(
+    handle: &mut W,
+    records: &mut dyn Iterator<Item = RecordResult>,
+    window_options: InputWindowing,
+    mods: &InputMods<OptionalTag>,
+    selection: ModSelection<'_>,
+    options: &WindowOptions,
+) -> std::result::Result<(), nanalogue_core::Error> {
+    let format = options.output_format.unwrap_or_default();
+    match (options.coordinate.unwrap_or_default(), selection.tags()) {
+        (WindowCoordinate::Reference, _) => ref_windows::write_ref_windows(
+            handle,
+            records,
+            &window_options,
+            mods,
+            selection,
+            options,
+        ),
+        (WindowCoordinate::Read, Some(v)) => {
+            let mut writer = TaggedWindows::new(format, v).writer(&mut *handle);
+            window_records(&mut writer, records, window_options, mods, options)?;
+            writer.finish().map_err(Into::into)
+        }
+        (WindowCoordinate::Read, None) => {
+            window_records(handle, records, window_options, mods, options)
+        }
+    }
+}
+
+/// Record of a BAM file as the windowing functions take it.
+type RecordResult = std::result::Result<Rc<Record>, rust_htslib::errors::Error>;
+
+/// Selects the reads of `options` for windowing and passes them to `run` along
+/// with the windowing options and the BAM header.
+///
+/// Errors of `run` are reported as failures of the function `name`, and a
+/// cancelled run is reported as such, as its output is incomplete.
+///
+/// # Errors
+/// Returns an error if the options are invalid, BAM reading fails or `run`
+/// fails.
+pub(crate) fn with_window_records<T, F>(
+    options: &WindowOptions,
+    shared: Option<&mut IndexedReader>,
+    name: &str,
+    run: F,
+) -> Result<T>
+where
+    F: FnOnce(
+        &mut dyn Iterator<Item = RecordResult>,
+        InputWindowing,
+        &InputMods<OptionalTag>,
+        &ExtraFilters,
+        &HeaderView,
+    ) -> std::result::Result<T, nanalogue_core::Error>,
+{
     let read_opts: ReadOptions = options.into();
     let (offset, limit) = validate_pagination(&read_opts)?;
-    let (mut bam, mut mods) = build_input_options(&read_opts)?;
+    let (mut bam, mut mods, extra) = build_input_options(&read_opts)?;
+    let regions = Regions::from_options(&read_opts)?;
 
     // Validate and build windowing options
     if options.win <= 0 {
-        return Err(Error::from_reason("Window size must be > 0"));
+        return Err(ErrorKind::InvalidOptions.error("Window size must be > 0"));
     }
     if options.step <= 0 {
-        return Err(Error::from_reason("Step size must be > 0"));
+        return Err(ErrorKind::InvalidOptions.error("Step size must be > 0"));
     }
     #[expect(clippy::cast_sign_loss, reason = "validated positive above")]
     let win = options.win as usize;
@@ -689,85 +2410,357 @@ fn window_reads_sync(options: &WindowOptions) -> Result<String> {
         .win(win)
         .step(step)
         .build()
-        .map_err(|e| Error::from_reason(format!("Failed to build windowing options: {e}")))?;
-
-    let mut reader = load_bam(&bam)?;
-    let bam_rc_records = BamRcRecords::new(&mut reader, &mut bam, &mut mods)
-        .map_err(|e| Error::from_reason(format!("Failed to read BAM records: {e}")))?;
-
-    let filtered = bam_rc_records
-        .rc_records
-        .filter(|r| r.as_ref().map_or(true, |v| v.pre_filt(&bam)));
-    let paginated = filtered.skip(offset).take(limit);
-
-    let mut buffer = Vec::new();
+        .map_err(|e| {
+            ErrorKind::InvalidOptions.error(format!("Failed to build windowing options: {e}"))
+        })?;
+
+    let cancel = options.cancel_token.as_ref();
+    cancel.map_or(Ok(()), CancelFlag::check)?;
+
+    let mut reader = query_reader(&bam, &read_opts, shared)?;
+    let (rc_records, header) = reader.records(&mut bam, &mut mods, regions.as_ref())?;
+
+    let filtered = Cancellable::new(rc_records, cancel.cloned()).filter(|r| {
+        r.as_ref()
+            .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    // Records without sequence, kept by `include_zero_len`, have no calls to window
+    // and the core windowing rejects them, so they are left out after pagination
+    let mut paginated = filtered
+        .skip(offset)
+        .take(limit)
+        .filter(|r| r.as_ref().map_or(true, |v| v.seq_len() > 0));
+
+    let result = run(&mut paginated, window_options, &mods, &extra, &header);
+
+    // A cancelled run ends the record iterator early, so its output is incomplete
+    cancel.map_or(Ok(()), CancelFlag::check)?;
+    result.map_err(|e| ErrorKind::of_core(&e).error(format!("{name} failed: {e}")))
+}
 
-    let win_op = options.win_op.as_deref().unwrap_or("density");
-    match win_op {
-        "density" => {
-            rust_window_reads::run_json(&mut buffer, paginated, window_options, &mods, |x| {
-                analysis::threshold_and_mean(x).map(Into::into)
-            })
-        }
-        "grad_density" => rust_window_reads::run_json(
-            &mut buffer,
-            paginated,
+/// Windows `records` along the reads with the window function of the `win_op`
+/// of `options`.
+fn window_records<W, D>(
+    handle: &mut W,
+    records: D,
+    window_options: InputWindowing,
+    mods: &InputMods<OptionalTag>,
+    options: &WindowOptions,
+) -> std::result::Result<(), nanalogue_core::Error>
+where
+    W: Write,
+    D: IntoIterator<Item = std::result::Result<Rc<Record>, rust_htslib::errors::Error>>,
+{
+    let format = options.output_format.unwrap_or_default();
+    let density = |x: &[u8]| analysis::threshold_and_mean(x).map(Into::into);
+    match options.win_op.unwrap_or_default() {
+        WinOp::Density => run_windows(handle, records, window_options, mods, density, options),
+        WinOp::GradDensity => run_windows(
+            handle,
+            records,
             window_options,
-            &mods,
+            mods,
             analysis::threshold_and_gradient,
+            options,
         ),
-        _ => {
-            return Err(Error::from_reason(
-                "win_op must be set to 'density' or 'grad_density'",
-            ));
+        WinOp::Max => run_windows(
+            handle,
+            records,
+            window_options,
+            mods,
+            window_ops::max_prob,
+            options,
+        ),
+        WinOp::RawMean => run_windows(
+            handle,
+            records,
+            window_options,
+            mods,
+            window_ops::mean_ml,
+            options,
+        ),
+        // Totals are computed as means over the calls of each window
+        WinOp::Sum => {
+            let mut writer = ScaledWindows::new(format, u8::MAX).writer(&mut *handle);
+            run_windows(
+                &mut writer,
+                records,
+                window_options,
+                mods,
+                window_ops::mean_ml,
+                options,
+            )?;
+            writer.finish().map_err(Into::into)
+        }
+        WinOp::Count => {
+            let mut writer = ScaledWindows::new(format, 1).writer(&mut *handle);
+            run_windows(&mut writer, records, window_options, mods, density, options)?;
+            writer.finish().map_err(Into::into)
         }
     }
-    .map_err(|e| Error::from_reason(format!("window_reads failed: {e}")))?;
+}
 
-    String::from_utf8(buffer).map_err(|e| Error::from_reason(format!("Invalid UTF-8: {e}")))
+/// Runs the windowing in the output format of `options` with `window_function`,
+/// adding the calls of each window and keeping those with enough calls.
+///
+/// The core windowing makes the windows, unless the `edge_mode` of `options`
+/// adds windows at the ends of the reads that only [`edge_windows::run`] makes,
+/// or `window_function` rejects windows of `win` calls, such as the gradient of
+/// a single call. The core windowing would skip each of those windows with a
+/// warning written straight to stderr, out of reach of the log callback, where
+/// [`edge_windows::run`] reports it through [`logging::warn`].
+fn run_windows<W, F, D>(
+    handle: &mut W,
+    records: D,
+    window_options: InputWindowing,
+    mods: &InputMods<OptionalTag>,
+    window_function: F,
+    options: &WindowOptions,
+) -> std::result::Result<(), nanalogue_core::Error>
+where
+    W: Write,
+    F: Fn(&[u8]) -> std::result::Result<F32AbsValAtMost1, nanalogue_core::Error>,
+    D: IntoIterator<Item = std::result::Result<Rc<Record>, rust_htslib::errors::Error>>,
+{
+    let format = options.output_format.unwrap_or_default();
+    // The window functions reject windows by their size alone, not their values
+    let rejects_windows = window_function(&vec![0; window_options.win.get()]).is_err();
+    let counts = RefCell::new(VecDeque::new());
+    let mut writer = CountedWindows::new(format, &counts, options.min_calls()).writer(&mut *handle);
+    let counted = window_ops::counting(window_function, &counts);
+    match (options.edge_mode.unwrap_or_default(), format) {
+        (EdgeMode::Drop, WindowFormat::Json) if !rejects_windows => {
+            rust_window_reads::run_json(&mut writer, records, window_options, mods, counted)?;
+        }
+        (EdgeMode::Drop, WindowFormat::Tsv) if !rejects_windows => {
+            rust_window_reads::run(&mut writer, records, window_options, mods, counted)?;
+        }
+        (edge_mode, _) => edge_windows::run(
+            &mut writer,
+            records,
+            &window_options,
+            mods,
+            counted,
+            format,
+            edge_mode,
+        )?,
+    }
+    writer.finish().map_err(Into::into)
 }
 
 /// Returns sequence table with read info as TSV string.
 ///
-/// Requires the `region` parameter to be set.
+/// Requires the `region` parameter to be set. The table has the `read_id`,
+/// `sequence` and `qualities` columns of [`seq_table_records`], or the columns of
+/// the core reads table listed in `columns`. With `regions` instead, the tables
+/// of the regions follow each other, with a first `region` column giving the
+/// region of each row.
 ///
 /// # Errors
-/// Returns an error if region is missing, BAM reading fails, or
-/// the table generation fails.
+/// Returns an error if region and regions are both missing, `columns` names an unknown column,
+/// BAM reading fails, or the table generation fails.
 #[napi]
 pub async fn seq_table(options: ReadOptions) -> Result<String> {
-    tokio::task::spawn_blocking(move || seq_table_sync(&options))
-        .await
-        .map_err(|e| Error::from_reason(format!("Task join error: {e}")))?
+    run_limited(move || seq_table_sync(&options, None)).await
 }
 
 /// Synchronous implementation of `seq_table`.
-fn seq_table_sync(options: &ReadOptions) -> Result<String> {
+fn seq_table_sync(options: &ReadOptions, shared: Option<&mut IndexedReader>) -> Result<String> {
+    let columns = seq_table_columns(
+        options.columns.as_deref(),
+        options.show_base_qual != Some(false),
+    )?;
+    seq_table_tsv(options, shared, &columns)
+}
+
+/// Returns every column of the core reads table over a region as TSV string.
+///
+/// Requires the `region` parameter to be set. Gives the rows of [`seq_table`]
+/// with all the columns `columns` can select: `read_id`, `align_length`,
+/// `sequence_length_template`, `alignment_type`, `mod_count`, `sequence` and
+/// `qualities`, in this order.
+///
+/// # Errors
+/// Returns an error if region is missing, `columns` is set, BAM reading fails,
+/// or the table generation fails.
+#[napi]
+pub async fn reads_table(options: ReadOptions) -> Result<String> {
+    run_limited(move || reads_table_sync(&options, None)).await
+}
+
+/// Synchronous implementation of `reads_table`.
+fn reads_table_sync(options: &ReadOptions, shared: Option<&mut IndexedReader>) -> Result<String> {
+    if options.columns.is_some() {
+        return Err(ErrorKind::InvalidOptions.error(
+            "columns is only supported by seqTable; readsTable always returns every column",
+        ));
+    }
+    let columns: Vec<&str> = SEQ_TABLE_COLUMNS
+        .into_iter()
+        .filter(|&v| options.show_base_qual != Some(false) || v != "qualities")
+        .collect();
+    seq_table_tsv(options, shared, &columns)
+}
+
+/// Writes the `columns` of the sequence table rows as TSV.
+fn seq_table_tsv(
+    options: &ReadOptions,
+    shared: Option<&mut IndexedReader>,
+    columns: &[&str],
+) -> Result<String> {
+    let rows = seq_table_rows_sync(options, shared)?;
+    // Rows of several regions are labelled with theirs, in a first column
+    let header: Vec<&str> = options
+        .regions
+        .as_ref()
+        .map(|_| "region")
+        .into_iter()
+        .chain(columns.iter().copied())
+        .collect();
+
+    let mut wtr = csv::WriterBuilder::new()
+        .delimiter(b'\t')
+        .from_writer(Vec::new());
+    wtr.write_record(&header)
+        .map_err(|e| ErrorKind::Internal.error(format!("Failed to write TSV header: {e}")))?;
+    for row in &rows {
+        wtr.write_record(header.iter().filter_map(|v| row.column(v)))
+            .map_err(|e| ErrorKind::Internal.error(format!("Failed to write TSV record: {e}")))?;
+    }
+
+    let buffer = wtr
+        .into_inner()
+        .map_err(|e| ErrorKind::Internal.error(format!("Failed to flush TSV writer: {e}")))?;
+    String::from_utf8(buffer).map_err(|e| ErrorKind::Internal.error(format!("Invalid UTF-8: {e}")))
+}
+
+/// Returns the sequence and base qualities of each read over a region.
+///
+/// Requires the `region` parameter to be set. Gives the rows of [`seq_table`] as
+/// objects, so that no TSV needs to be written or parsed.
+///
+/// # Errors
+/// Returns an error if region is missing, BAM reading fails, or
+/// the table generation fails.
+#[napi]
+pub async fn seq_table_records(options: ReadOptions) -> Result<Vec<SeqTableRecord>> {
+    run_limited(move || seq_table_records_sync(&options, None)).await
+}
+
+/// Synchronous implementation of `seq_table_records`.
+fn seq_table_records_sync(
+    options: &ReadOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<Vec<SeqTableRecord>> {
+    if options.columns.is_some() {
+        return Err(ErrorKind::InvalidOptions.error(
+            "columns is only supported by seqTable; seqTableRecords always returns read_id, sequence and qualities",
+        ));
+    }
+    let qualities = options.show_base_qual != Some(false);
+    Ok(seq_table_rows_sync(options, shared)?
+        .into_iter()
+        .map(|v| SeqTableRecord::new(v, qualities))
+        .collect())
+}
+
+/// Reads the rows of the sequence table shared by `seq_table`, `seq_table_records`
+/// and `reads_table`.
+///
+/// With `regions`, the table of each region is read in turn, as if it were the
+/// `region` and `mod_region`, and its rows are labelled with it; `offset`,
+/// `limit` and `max_reads` count the records and rows of all the regions
+/// together.
+fn seq_table_rows_sync(
+    options: &ReadOptions,
+    mut shared: Option<&mut IndexedReader>,
+) -> Result<Vec<SeqTableRow>> {
     let (offset, limit) = validate_pagination(options)?;
+    if options.max_reads == Some(0) {
+        return Err(ErrorKind::InvalidOptions.error("maxReads must be at least 1"));
+    }
+    let mut page = SeqTablePage {
+        skip: offset,
+        records: limit,
+        reads: options
+            .max_reads
+            .map_or(usize::MAX, |v| usize::try_from(v).unwrap_or(usize::MAX)),
+    };
+    let Some(regions) = options.regions.as_ref() else {
+        return seq_table_region_rows(options, shared, &mut page);
+    };
+    if options.region.is_some() || options.region_bed.is_some() {
+        return Err(ErrorKind::InvalidOptions
+            .error("seqTable takes either region or regions; regionBed is not supported"));
+    }
+    if regions.is_empty() {
+        return Err(ErrorKind::InvalidOptions.error("regions must contain at least one region"));
+    }
+    if options.mod_region.is_some() {
+        return Err(ErrorKind::InvalidOptions
+            .error("seqTable with regions requires modRegion to be omitted"));
+    }
+    let mut rows = Vec::new();
+    for region in regions {
+        if page.reads == 0 {
+            break;
+        }
+        let mut region_options = options.clone();
+        region_options.region = Some(region.clone());
+        region_options.regions = None;
+        rows.extend(
+            seq_table_region_rows(&region_options, shared.as_deref_mut(), &mut page)?
+                .into_iter()
+                .map(|v| v.in_region(region)),
+        );
+    }
+    Ok(rows)
+}
+
+/// Records and reads still to skip or take over the regions of a sequence table.
+#[derive(Debug)]
+struct SeqTablePage {
+    /// Records still to skip.
+    skip: usize,
+    /// Records still to take.
+    records: usize,
+    /// Reads still to add rows for.
+    reads: usize,
+}
 
+/// Reads the rows of the sequence table over `region`, skipping and then
+/// taking the records and reads left in `page`, which it counts down.
+fn seq_table_region_rows(
+    options: &ReadOptions,
+    shared: Option<&mut IndexedReader>,
+    page: &mut SeqTablePage,
+) -> Result<Vec<SeqTableRow>> {
     // Region is required for seq_table
+    if options.region_bed.is_some() {
+        return Err(ErrorKind::InvalidOptions
+            .error("seqTable takes either region or regions; regionBed is not supported"));
+    }
     let region_str = options.region.as_ref().ok_or_else(|| {
-        Error::from_reason("region parameter is required for seq_table (cannot be empty)")
+        ErrorKind::InvalidOptions
+            .error("region parameter is required for seq_table (cannot be empty)")
     })?;
 
     if region_str.is_empty() {
-        return Err(Error::from_reason(
-            "region parameter is required for seq_table (cannot be empty)",
-        ));
+        return Err(ErrorKind::InvalidOptions
+            .error("region parameter is required for seq_table (cannot be empty)"));
     }
 
     // Validate seqTable constraints for pynanalogue compatibility
     if options.full_region == Some(false) {
-        return Err(Error::from_reason(
-            "seqTable requires fullRegion to be true (or omitted)",
-        ));
+        return Err(
+            ErrorKind::InvalidOptions.error("seqTable requires fullRegion to be true (or omitted)")
+        );
     }
     if let Some(mod_region) = options.mod_region.as_ref()
         && mod_region != region_str
     {
-        return Err(Error::from_reason(
-            "seqTable requires modRegion to match region (or be omitted)",
-        ));
+        return Err(ErrorKind::InvalidOptions
+            .error("seqTable requires modRegion to match region (or be omitted)"));
     }
 
     // Create modified options with pynanalogue-compatible defaults:
@@ -777,87 +2770,47 @@ fn seq_table_sync(options: &ReadOptions) -> Result<String> {
     modified_options.full_region = Some(true);
     modified_options.mod_region = Some(region_str.clone());
 
-    let (mut bam, mut mods) = build_input_options(&modified_options)?;
-
-    let mut reader = load_bam(&bam)?;
-    let bam_rc_records = BamRcRecords::new(&mut reader, &mut bam, &mut mods)
-        .map_err(|e| Error::from_reason(format!("Failed to read BAM records: {e}")))?;
-
-    // Parse region to GenomicRegion then convert to Bed3 for SeqDisplayOptions
-    let genomic_region = GenomicRegion::from_str(region_str)
-        .map_err(|e| Error::from_reason(format!("Invalid region: {e}")))?;
-    let region_bed3 = genomic_region
-        .try_to_bed3(&bam_rc_records.header)
-        .map_err(|e| Error::from_reason(format!("Failed to convert region to bed3: {e}")))?;
-
-    // Build sequence display options - use Region variant for proper clipping and Z/z markers
-    let seq_display = SeqDisplayOptions::Region {
-        show_base_qual: true,
-        show_ins_lowercase: true,
-        region: region_bed3,
-        show_mod_z: true,
-    };
-
-    let filtered = bam_rc_records
-        .rc_records
-        .filter(|r| r.as_ref().map_or(true, |v| v.pre_filt(&bam)));
-    let paginated = filtered.skip(offset).take(limit);
+    let (mut bam, mut raw_mods, extra) = build_input_options(&modified_options)?;
 
-    let mut buffer = Vec::new();
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, header) = reader.records(&mut bam, &mut raw_mods, None)?;
+    let mods = read_info_mods(raw_mods);
 
-    rust_reads_table::run(&mut buffer, paginated, Some(mods), seq_display, "")
-        .map_err(|e| Error::from_reason(format!("seq_table failed: {e}")))?;
-
-    let full_tsv =
-        String::from_utf8(buffer).map_err(|e| Error::from_reason(format!("Invalid UTF-8: {e}")))?;
-
-    // Filter TSV to only include read_id, sequence, qualities columns (pynanalogue compatibility)
-    filter_seq_table_columns(&full_tsv)
-}
-
-/// Record struct for deserializing `seq_table` TSV rows.
-/// Only the columns we need are extracted; other columns are ignored.
-#[derive(Debug, serde::Deserialize)]
-struct SeqTableRecord {
-    /// The read identifier.
-    read_id: String,
-    /// Nucleotide sequence, with `.` for deletion, lower case for insertion, and base replaced by Z (or z) for modification.
-    sequence: String,
-    /// The quality scores as a string.
-    qualities: String,
-}
-
-/// Filters TSV output to only include `read_id`, sequence, qualities columns.
-/// This matches pynanalogue's `seq_table` behavior which only returns these 3 columns.
-fn filter_seq_table_columns(tsv: &str) -> Result<String> {
-    let mut rdr = csv::ReaderBuilder::new()
-        .delimiter(b'\t')
-        .has_headers(true)
-        .comment(Some(b'#'))
-        .from_reader(tsv.as_bytes());
-
-    let mut wtr = csv::WriterBuilder::new()
-        .delimiter(b'\t')
-        .from_writer(Vec::new());
-
-    // Write header
-    let _: () = wtr
-        .write_record(["read_id", "sequence", "qualities"])
-        .map_err(|e| Error::from_reason(format!("Failed to write TSV header: {e}")))?;
+    // Parse region to GenomicRegion then convert to Bed3 to clip each read to it
+    let genomic_region = GenomicRegion::from_str(&core_region(region_str, options.one_based)?)
+        .map_err(|e| ErrorKind::InvalidRegion.error(format!("Invalid region: {e}")))?;
+    let region_bed3 = genomic_region.try_to_bed3(&header).map_err(|e| {
+        ErrorKind::InvalidRegion.error(format!("Failed to convert region to bed3: {e}"))
+    })?;
 
-    // Process each record
-    for result in rdr.deserialize() {
-        let record: SeqTableRecord =
-            result.map_err(|e| Error::from_reason(format!("Failed to parse TSV row: {e}")))?;
-        let _: () = wtr
-            .write_record([&record.read_id, &record.sequence, &record.qualities])
-            .map_err(|e| Error::from_reason(format!("Failed to write TSV row: {e}")))?;
+    let filtered = rc_records.filter(|r| {
+        r.as_ref()
+            .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    let display = SeqDisplay::new(options)?;
+    let mut rows = SeqTableRows::default();
+    for entry in filtered {
+        if page.records == 0 {
+            break;
+        }
+        if let Some(v) = page.skip.checked_sub(1) {
+            page.skip = v;
+            continue;
+        }
+        page.records = page.records.saturating_sub(1);
+        let record =
+            entry.map_err(|e| ErrorKind::of_htslib(&e).error(format!("seq_table failed: {e}")))?;
+        if let Some(row) =
+            SeqTableRow::from_record(&record, &mods, extra.mod_selection(), &region_bed3, display)?
+        {
+            if !rows.contains(&row) {
+                let Some(v) = page.reads.checked_sub(1) else {
+                    break;
+                };
+                page.reads = v;
+            }
+            rows.push(row);
+        }
     }
-
-    let inner = wtr
-        .into_inner()
-        .map_err(|e| Error::from_reason(format!("Failed to flush TSV writer: {e}")))?;
-
-    String::from_utf8(inner)
-        .map_err(|e| Error::from_reason(format!("Invalid UTF-8 in output: {e}")))
+    Ok(rows.into_rows())
 }
@@ -0,0 +1,146 @@
+//! Diagnostics of the library and its dependencies passed on to a JavaScript
+//! callback.
+//!
+//! Dependencies of the core log through the `log` crate, which drops records
+//! unless a logger is installed. [`set_log_callback`] installs one that hands
+//! each record to the callback, and warnings of this crate that are otherwise
+//! written to stderr go to the callback too while it is set.
+
+use crate::error::ErrorKind;
+use log::{LevelFilter, Log, Metadata, Record};
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi_derive::napi;
+use std::fmt;
+use std::sync::{Mutex, Once};
+
+/// Callback of [`set_log_callback`], if one is set.
+static CALLBACK: Mutex<Option<LogCallback>> = Mutex::new(None);
+
+/// Logger of the `log` crate that passes records on to the callback.
+static LOGGER: CallbackLogger = CallbackLogger;
+
+/// Installs [`LOGGER`] once, as the `log` crate allows only one logger.
+static INSTALL_LOGGER: Once = Once::new();
+
+/// One diagnostic message, as the log callback receives it.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct LogRecord {
+    /// Severity: `error`, `warn`, `info`, `debug` or `trace`.
+    pub level: String,
+    /// The message.
+    pub message: String,
+}
+
+/// JavaScript callback receiving diagnostic messages.
+///
+/// Calls are queued onto the JS thread without blocking the worker, and the
+/// callback does not keep the Node.js process alive. If the JS side has gone
+/// away (e.g. the environment is shutting down) the call is dropped.
+#[derive(Clone)]
+pub struct LogCallback(ThreadsafeFunction<LogRecord, ErrorStrategy::Fatal>);
+
+impl LogCallback {
+    /// Queues a call with `level` and `message`, ignoring a closed callback.
+    fn send(&self, level: &str, message: String) {
+        let record = LogRecord {
+            level: level.to_owned(),
+            message,
+        };
+        let _: Status = self.0.call(record, ThreadsafeFunctionCallMode::NonBlocking);
+    }
+}
+
+impl fmt::Debug for LogCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogCallback").finish_non_exhaustive()
+    }
+}
+
+impl FromNapiValue for LogCallback {
+    unsafe fn from_napi_value(env: sys::napi_env, napi_val: sys::napi_value) -> Result<Self> {
+        // As for progress callbacks, napi aborts the process while cleaning up a
+        // threadsafe function made from a non-function, so those are rejected first
+        if napi::type_of!(env, napi_val)? != ValueType::Function {
+            return Err(ErrorKind::InvalidOptions.error("expected a function"));
+        }
+        let mut tsfn = ThreadsafeFunction::from_napi_value(env, napi_val)?;
+        // The callback stays set until it is replaced, which must not hold the
+        // event loop open
+        tsfn.unref(&Env::from_raw(env))?;
+        Ok(Self(tsfn))
+    }
+}
+
+/// Logger of the `log` crate that passes records on to the callback.
+#[derive(Debug, Clone, Copy)]
+struct CallbackLogger;
+
+impl Log for CallbackLogger {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        callback().is_some()
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if let Some(v) = callback() {
+            v.send(
+                &record.level().as_str().to_ascii_lowercase(),
+                record.args().to_string(),
+            );
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Sets a function that receives the diagnostics of the library as
+/// `{ level, message }` records, or removes it when called without one.
+///
+/// Messages at `info` level and above that the dependencies of the core log are
+/// passed on, along with the warnings of the windowing otherwise written to
+/// stderr: reads left out because they cannot be windowed in reference
+/// coordinates, and windows skipped because the window operation cannot value
+/// them, such as the gradient of a single call. The core itself logs nothing.
+/// Without a callback, logged messages are dropped and warnings go to stderr,
+/// as before. Calls are queued
+/// from the threads reading BAM files, so messages arrive asynchronously, and a
+/// query that is running when the callback is replaced may still report to the
+/// old one.
+///
+/// # Errors
+/// Returns an error if `callback` is not a function or the callback cannot be
+/// stored.
+#[napi]
+pub fn set_log_callback(callback: Option<LogCallback>) -> Result<()> {
+    INSTALL_LOGGER.call_once(|| {
+        // Another logger may have been installed by the embedding process, in
+        // which case records keep going there
+        log::set_logger(&LOGGER).unwrap_or_default();
+    });
+    log::set_max_level(if callback.is_some() {
+        LevelFilter::Info
+    } else {
+        LevelFilter::Off
+    });
+    *CALLBACK
+        .lock()
+        .map_err(|e| ErrorKind::Internal.error(format!("Log callback lock poisoned: {e}")))? =
+        callback;
+    Ok(())
+}
+
+/// The current callback, cloned so that it is not locked while called.
+fn callback() -> Option<LogCallback> {
+    let guard = CALLBACK.lock().ok()?;
+    guard.clone()
+}
+
+/// Reports a warning to the log callback if one is set, or to stderr otherwise.
+pub(crate) fn warn(message: &str) {
+    match callback() {
+        Some(v) => v.send("warn", message.to_owned()),
+        None => eprintln!("Warning: {message}"),
+    }
+}
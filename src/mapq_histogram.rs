@@ -0,0 +1,55 @@
+//! Distribution of the mapping qualities of reads.
+
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use crate::progress::{Progress, progress_interval};
+use crate::regions::Regions;
+use crate::{
+    ReadOptions, build_input_options, passes_read_filters, query_reader, validate_pagination,
+};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::IndexedReader;
+
+/// Counts the reads at each mapping quality, from 0 to 255.
+///
+/// Reads are those that `read_info` returns for the same options, so read filters
+/// apply, and `offset` and `limit` count reads. A mapping quality of 255 means it
+/// is unavailable, as for most unmapped reads; such reads are left out with
+/// `exclude_mapq_unavail`.
+///
+/// # Errors
+/// Returns an error if input options are invalid or BAM reading fails.
+#[napi]
+pub async fn mapq_histogram(options: ReadOptions) -> Result<Vec<u32>> {
+    run_limited(move || mapq_histogram_sync(&options, None)).await
+}
+
+/// Synchronous implementation of `mapq_histogram`.
+pub(crate) fn mapq_histogram_sync(
+    options: &ReadOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<Vec<u32>> {
+    let (offset, limit) = validate_pagination(options)?;
+    let interval = progress_interval(options.progress_interval)?;
+    let (mut bam, mut mods, extra) = build_input_options(options)?;
+    let regions = Regions::from_options(options)?;
+
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, _) = reader.records(&mut bam, &mut mods, regions.as_ref())?;
+
+    let filtered = Progress::new(rc_records, options.on_progress.clone(), interval).filter(|r| {
+        r.as_ref()
+            .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    let mut counts = vec![0u32; usize::from(u8::MAX) + 1];
+    for entry in filtered.skip(offset).take(limit) {
+        let record = entry.map_err(|e| {
+            ErrorKind::of_htslib(&e).error(format!("Failed to read BAM record: {e}"))
+        })?;
+        if let Some(count) = counts.get_mut(usize::from(record.mapq())) {
+            *count = count.saturating_add(1);
+        }
+    }
+    Ok(counts)
+}
@@ -0,0 +1,109 @@
+//! Totals of modification calls on each reference contig.
+
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use crate::pileup::MOD_THRESHOLD;
+use crate::progress::{Progress, progress_interval};
+use crate::records::read_mod_data;
+use crate::regions::Regions;
+use crate::{
+    ReadOptions, build_input_options, passes_read_filters, query_reader, validate_pagination,
+};
+use nanalogue_core::{Contains as _, ModChar};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::IndexedReader;
+use std::collections::BTreeMap;
+
+/// Modification calls of one modification code on one contig.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct ContigModCount {
+    /// Contig the calls are aligned to.
+    pub contig: String,
+    /// Modification code, e.g. `m`, or a numeric code such as `76792`.
+    #[napi(js_name = "mod_code")]
+    pub mod_code: String,
+    /// Number of calls with a probability of at least 0.5.
+    #[napi(js_name = "n_mod")]
+    pub n_mod: i64,
+    /// Number of calls that pass the modification filters.
+    #[napi(js_name = "n_valid")]
+    pub n_valid: i64,
+}
+
+/// Totals the modification calls aligned to each contig, per modification code.
+///
+/// Reads are selected as in `bam_mods`, so read and modification filters apply
+/// and `offset` and `limit` count reads. Calls that pass the modification
+/// filters are valid, and valid calls with a probability of at least 0.5 are
+/// modified, as in `pileup`. Calls on bases not aligned to the reference, and
+/// those of unmapped reads, are left out. Totals are listed in header order of
+/// the contigs, then by modification code, leaving out contigs without calls.
+///
+/// # Errors
+/// Returns an error if input options are invalid or BAM reading fails.
+#[napi]
+pub async fn mod_counts_by_contig(options: ReadOptions) -> Result<Vec<ContigModCount>> {
+    run_limited(move || mod_counts_by_contig_sync(&options, None)).await
+}
+
+/// Synchronous implementation of `mod_counts_by_contig`.
+pub(crate) fn mod_counts_by_contig_sync(
+    options: &ReadOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<Vec<ContigModCount>> {
+    let (offset, limit) = validate_pagination(options)?;
+    let interval = progress_interval(options.progress_interval)?;
+    let (mut bam, mut mods, extra) = build_input_options(options)?;
+    let regions = Regions::from_options(options)?;
+
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, header) = reader.records(&mut bam, &mut mods, regions.as_ref())?;
+
+    let filtered = Progress::new(rc_records, options.on_progress.clone(), interval).filter(|r| {
+        r.as_ref()
+            .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    // Keyed by contig id and modification code, holding the modified and valid
+    // call counts
+    let mut counts: BTreeMap<(u32, String), (i64, i64)> = BTreeMap::new();
+    for entry in filtered.skip(offset).take(limit) {
+        let record = entry.map_err(|e| {
+            ErrorKind::of_htslib(&e).error(format!("Failed to read BAM record: {e}"))
+        })?;
+        if record.is_unmapped() {
+            continue;
+        }
+        let Ok(tid) = u32::try_from(record.tid()) else {
+            continue;
+        };
+        let read = read_mod_data(&record, &mods, extra.mod_selection()).map_err(|e| {
+            ErrorKind::of_core(&e).error(format!("mod_counts_by_contig failed: {e}"))
+        })?;
+        for base_mod in &read.mod_data().0.base_mods {
+            let mod_code = ModChar::new(base_mod.modification_type).to_string();
+            for k in &base_mod.ranges.annotations {
+                if k.reference_start.is_none() {
+                    continue;
+                }
+                let count = counts.entry((tid, mod_code.clone())).or_insert((0, 0));
+                if MOD_THRESHOLD.contains(&k.qual) {
+                    count.0 = count.0.saturating_add(1);
+                }
+                count.1 = count.1.saturating_add(1);
+            }
+        }
+    }
+
+    Ok(counts
+        .into_iter()
+        .map(|((tid, mod_code), (n_mod, n_valid))| ContigModCount {
+            contig: String::from_utf8_lossy(header.tid2name(tid)).into_owned(),
+            mod_code,
+            n_mod,
+            n_valid,
+        })
+        .collect())
+}
@@ -0,0 +1,168 @@
+//! Distribution of the modification probabilities stored in ML tags, and the
+//! threshold it suggests.
+
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use crate::progress::{Progress, progress_interval};
+use crate::records::read_mod_data;
+use crate::regions::Regions;
+use crate::{
+    ReadOptions, build_input_options, passes_read_filters, query_reader, validate_pagination,
+};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::IndexedReader;
+
+/// Number of distinct ML values, and so of bins when `bins` is not set.
+const ML_VALUES: u32 = 256;
+
+/// Threshold suggested when the calls cannot be split in two: the ML value of a
+/// probability of 0.5, above which calls count as modified elsewhere.
+const DEFAULT_THRESHOLD: u8 = 128;
+
+/// Threshold on the ML values of modification calls, from `suggest_threshold`.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct ThresholdSuggestion {
+    /// Lowest ML value, from 0 to 255, of the calls above the threshold, so
+    /// that it can be given as `min_mod_qual`.
+    pub threshold: u8,
+    /// How the threshold was chosen: `otsu`, or `default` if the calls have
+    /// fewer than two distinct ML values.
+    pub method: String,
+}
+
+/// Counts the modification calls at each ML value, from 0 to 255.
+///
+/// Calls are those that `bam_mods_typed` returns for the same options, so read
+/// and modification filters apply, and `offset` and `limit` count reads. With
+/// `bins`, the 256 values are split into that many bins of near-equal width, bin
+/// `i` holding the values `v` with `v * bins / 256 == i`.
+///
+/// # Errors
+/// Returns an error if `bins` is not between 1 and 256, input options are invalid,
+/// or BAM reading fails.
+#[napi]
+pub async fn mod_qual_histogram(options: ReadOptions, bins: Option<u32>) -> Result<Vec<u32>> {
+    run_limited(move || mod_qual_histogram_sync(&options, bins, None)).await
+}
+
+/// Synchronous implementation of `mod_qual_histogram`.
+pub(crate) fn mod_qual_histogram_sync(
+    options: &ReadOptions,
+    raw_bins: Option<u32>,
+    shared: Option<&mut IndexedReader>,
+) -> Result<Vec<u32>> {
+    let bins = raw_bins.unwrap_or(ML_VALUES);
+    if !(1..=ML_VALUES).contains(&bins) {
+        return Err(ErrorKind::InvalidOptions.error(format!(
+            "bins must be between 1 and {ML_VALUES}, got {bins}"
+        )));
+    }
+    let (offset, limit) = validate_pagination(options)?;
+    let interval = progress_interval(options.progress_interval)?;
+    let (mut bam, mut mods, extra) = build_input_options(options)?;
+    let regions = Regions::from_options(options)?;
+
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, _) = reader.records(&mut bam, &mut mods, regions.as_ref())?;
+
+    let filtered = Progress::new(rc_records, options.on_progress.clone(), interval).filter(|r| {
+        r.as_ref()
+            .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    let mut counts = vec![0u32; usize::try_from(bins).unwrap_or(usize::MAX)];
+    for entry in filtered.skip(offset).take(limit) {
+        let record = entry.map_err(|e| {
+            ErrorKind::of_htslib(&e).error(format!("Failed to read BAM record: {e}"))
+        })?;
+        let read = read_mod_data(&record, &mods, extra.mod_selection())
+            .map_err(|e| ErrorKind::of_core(&e).error(format!("mod_qual_histogram failed: {e}")))?;
+        for base_mod in &read.mod_data().0.base_mods {
+            for k in &base_mod.ranges.annotations {
+                let bin = u32::from(k.qual).saturating_mul(bins) / ML_VALUES;
+                if let Some(count) = usize::try_from(bin).ok().and_then(|i| counts.get_mut(i)) {
+                    *count = count.saturating_add(1);
+                }
+            }
+        }
+    }
+    Ok(counts)
+}
+
+/// Suggests a threshold on the ML values of the modification calls, such as to
+/// prefill `min_mod_qual`.
+///
+/// The calls are counted as in [`mod_qual_histogram`], so the read and
+/// modification filters apply, and `limit` or `sample_fraction` keep a large
+/// file quick by counting a sample of reads. The threshold is chosen by Otsu's
+/// method, which splits the ML values where the two groups of calls are most
+/// apart. Where several thresholds split the calls the same, as across a gap
+/// with no calls, the middle one is taken. If the calls have fewer than two
+/// distinct ML values there is nothing to split, and 128, a probability of
+/// 0.5, is suggested with the `default` method.
+///
+/// # Errors
+/// Returns an error if input options are invalid or BAM reading fails.
+#[napi]
+pub async fn suggest_threshold(options: ReadOptions) -> Result<ThresholdSuggestion> {
+    run_limited(move || suggest_threshold_sync(&options, None)).await
+}
+
+/// Synchronous implementation of `suggest_threshold`.
+pub(crate) fn suggest_threshold_sync(
+    options: &ReadOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<ThresholdSuggestion> {
+    let counts = mod_qual_histogram_sync(options, None, shared)?;
+    Ok(match otsu_threshold(&counts) {
+        Some(threshold) => ThresholdSuggestion {
+            threshold,
+            method: "otsu".to_owned(),
+        },
+        None => ThresholdSuggestion {
+            threshold: DEFAULT_THRESHOLD,
+            method: "default".to_owned(),
+        },
+    })
+}
+
+/// Threshold of Otsu's method over the calls `counts` at each ML value: the
+/// lowest value of the upper group, for the split that gives the largest
+/// variance between the two groups, taking the middle of equal splits in a row.
+/// `None` if every split leaves a group empty.
+fn otsu_threshold(counts: &[u32]) -> Option<u8> {
+    let total: f64 = counts.iter().map(|&v| f64::from(v)).sum();
+    let weighted_total: f64 = (0u8..=u8::MAX)
+        .zip(counts)
+        .map(|(i, &v)| f64::from(i) * f64::from(v))
+        .sum();
+    let (mut below, mut weighted_below) = (0.0f64, 0.0f64);
+    // Between-group variance of the split at each threshold, from 1 to 255
+    let mut variances = Vec::with_capacity(counts.len());
+    for (i, &v) in (0u8..u8::MAX).zip(counts) {
+        below += f64::from(v);
+        weighted_below += f64::from(i) * f64::from(v);
+        let above = total - below;
+        let variance = if below > 0.0 && above > 0.0 {
+            let mean_gap = weighted_below / below - (weighted_total - weighted_below) / above;
+            below * above * mean_gap * mean_gap
+        } else {
+            0.0
+        };
+        variances.push((i.saturating_add(1), variance));
+    }
+    let best = variances.iter().map(|&(_, v)| v).fold(0.0f64, f64::max);
+    if best <= 0.0 {
+        return None;
+    }
+    let mut ties = variances
+        .iter()
+        .skip_while(|&&(_, v)| v < best)
+        .take_while(|&&(_, v)| v >= best)
+        .map(|&(t, _)| t);
+    let first = ties.next()?;
+    let last = ties.last().unwrap_or(first);
+    Some(first.saturating_add(last.saturating_sub(first) / 2))
+}
@@ -0,0 +1,99 @@
+//! Per-read totals of modification calls.
+
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use crate::pileup::MOD_THRESHOLD;
+use crate::progress::{Progress, progress_interval};
+use crate::records::read_mod_data;
+use crate::regions::Regions;
+use crate::{
+    ReadOptions, build_input_options, passes_read_filters, query_reader, validate_pagination,
+};
+use nanalogue_core::Contains as _;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::IndexedReader;
+
+/// Modification call totals of one read.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct ModSummary {
+    /// Read id.
+    #[napi(js_name = "read_id")]
+    pub read_id: String,
+    /// Number of calls with a probability of at least 0.5.
+    #[napi(js_name = "n_mod")]
+    pub n_mod: u32,
+    /// Number of calls that pass the modification filters.
+    #[napi(js_name = "n_valid")]
+    pub n_valid: u32,
+    /// Mean probability of modification of the valid calls, between 0 and 1,
+    /// absent if the read has none.
+    #[napi(js_name = "mean_prob")]
+    pub mean_prob: Option<f64>,
+}
+
+/// Totals the modification calls of each read.
+///
+/// Returns one summary per read that `bam_mods` would return, in the same order,
+/// so read and modification filters apply and `offset` and `limit` count reads.
+/// Calls that pass the modification filters are valid, and
+/// valid calls with a probability of at least 0.5 are modified, as in `pileup`.
+///
+/// # Errors
+/// Returns an error if input options are invalid or BAM reading fails.
+#[napi]
+pub async fn mod_summary(options: ReadOptions) -> Result<Vec<ModSummary>> {
+    run_limited(move || mod_summary_sync(&options, None)).await
+}
+
+/// Synchronous implementation of `mod_summary`.
+pub(crate) fn mod_summary_sync(
+    options: &ReadOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<Vec<ModSummary>> {
+    let (offset, limit) = validate_pagination(options)?;
+    let interval = progress_interval(options.progress_interval)?;
+    let (mut bam, mut mods, extra) = build_input_options(options)?;
+    let regions = Regions::from_options(options)?;
+
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, _) = reader.records(&mut bam, &mut mods, regions.as_ref())?;
+
+    let filtered = Progress::new(rc_records, options.on_progress.clone(), interval).filter(|r| {
+        r.as_ref()
+            .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    let mut summaries = Vec::new();
+    for entry in filtered.skip(offset).take(limit) {
+        let record = entry.map_err(|e| {
+            ErrorKind::of_htslib(&e).error(format!("Failed to read BAM record: {e}"))
+        })?;
+        let read = read_mod_data(&record, &mods, extra.mod_selection())
+            .map_err(|e| ErrorKind::of_core(&e).error(format!("mod_summary failed: {e}")))?;
+        let (mut n_mod, mut n_valid, mut qual_sum) = (0u32, 0u32, 0u64);
+        for base_mod in &read.mod_data().0.base_mods {
+            for k in &base_mod.ranges.annotations {
+                if MOD_THRESHOLD.contains(&k.qual) {
+                    n_mod = n_mod.saturating_add(1);
+                }
+                n_valid = n_valid.saturating_add(1);
+                qual_sum = qual_sum.saturating_add(u64::from(k.qual));
+            }
+        }
+        #[expect(
+            clippy::cast_precision_loss,
+            reason = "sums of ML values stay far below 2^53"
+        )]
+        let mean_prob =
+            (n_valid > 0).then(|| qual_sum as f64 / f64::from(n_valid) / f64::from(u8::MAX));
+        summaries.push(ModSummary {
+            read_id: read.read_id().to_owned(),
+            n_mod,
+            n_valid,
+            mean_prob,
+        });
+    }
+    Ok(summaries)
+}
@@ -0,0 +1,114 @@
+//! Windowing of reads on several threads, for `window_reads` with `parallel`.
+
+use crate::cancel::{CancelFlag, Cancellable};
+use crate::{RecordResult, WindowFormat};
+use nanalogue_core::Error;
+use rayon::prelude::*;
+use rust_htslib::bam::{HeaderView, Record};
+use std::io::Write;
+use std::rc::Rc;
+
+/// Windows `records` in contiguous chunks on the rayon thread pool and writes
+/// the output to `handle` in the order of the records, as windowing them one
+/// after the other would.
+///
+/// `window` writes the complete output in `format` of the records it is given,
+/// header included. The records are all read before the windowing starts, so
+/// they are held in memory together, and each chunk gets its own copy of
+/// `header`, which cannot be shared between threads.
+///
+/// # Errors
+/// Returns an error if reading a record, windowing a chunk or writing to
+/// `handle` fails.
+pub(crate) fn run<W, F>(
+    handle: &mut W,
+    records: &mut dyn Iterator<Item = RecordResult>,
+    header: &HeaderView,
+    format: WindowFormat,
+    cancel: Option<&CancelFlag>,
+    window: F,
+) -> Result<(), Error>
+where
+    W: Write,
+    F: Fn(&mut Vec<u8>, &mut dyn Iterator<Item = RecordResult>) -> Result<(), Error> + Sync,
+{
+    // The reader cannot be shared between threads, so the records are read
+    // first. Copies of records leave out the header they point to, which is
+    // reference counted without synchronisation, and are given that of their chunk.
+    let collected = records
+        .map(|r| r.map(|v| Record::clone(&v)))
+        .collect::<Result<Vec<Record>, _>>()?;
+    let header_text = header.as_bytes();
+    let chunk_size = collected
+        .len()
+        .div_ceil(rayon::current_num_threads())
+        .max(1);
+    // One chunk, even if empty, so that the header is written without records
+    let mut chunks: Vec<Vec<Record>> = vec![Vec::new()];
+    for record in collected {
+        match chunks.last_mut() {
+            Some(v) if v.len() < chunk_size => v.push(record),
+            Some(_) | None => chunks.push(vec![record]),
+        }
+    }
+    let outputs = chunks
+        .into_par_iter()
+        .map(|chunk| {
+            let mut buffer = Vec::new();
+            let chunk_header = Rc::new(HeaderView::from_bytes(header_text));
+            let with_header = chunk.into_iter().map(|mut v| {
+                v.set_header(Rc::clone(&chunk_header));
+                Ok(Rc::new(v))
+            });
+            let mut chunk_records = Cancellable::new(with_header, cancel.cloned());
+            window(&mut buffer, &mut chunk_records).map(|()| buffer)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    write_merged(handle, format, &outputs)
+}
+
+/// Writes the outputs of consecutive chunks of records to `handle` as one
+/// output in `format`: the TSV header once, or a single JSON array.
+fn write_merged<W: Write>(
+    handle: &mut W,
+    format: WindowFormat,
+    outputs: &[Vec<u8>],
+) -> Result<(), Error> {
+    match format {
+        WindowFormat::Tsv => {
+            for (i, output) in outputs.iter().enumerate() {
+                let rows = if i == 0 {
+                    output.as_slice()
+                } else {
+                    output
+                        .iter()
+                        .position(|&b| b == b'\n')
+                        .and_then(|v| output.get(v.saturating_add(1)..))
+                        .unwrap_or_default()
+                };
+                handle.write_all(rows)?;
+            }
+        }
+        WindowFormat::Json => {
+            // Each output is "[\n]\n" or "[\n" followed by its items joined by
+            // ",\n" and then "\n]\n"
+            handle.write_all(b"[\n")?;
+            let mut is_first = true;
+            for output in outputs {
+                let Some(items) = output
+                    .strip_prefix(b"[\n")
+                    .and_then(|v| v.strip_suffix(b"\n]\n"))
+                else {
+                    continue;
+                };
+                if !is_first {
+                    handle.write_all(b",\n")?;
+                }
+                handle.write_all(items)?;
+                is_first = false;
+            }
+            handle.write_all(if is_first { b"]\n" } else { b"\n]\n" })?;
+        }
+    }
+    Ok(())
+}
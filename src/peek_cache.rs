@@ -0,0 +1,114 @@
+//! Cache of recent `peek()` results, for apps that peek at the same files again.
+
+use crate::{PeekOptions, PeekResult};
+use nanalogue_core::PathOrURLOrStdin;
+use napi_derive::napi;
+use std::collections::VecDeque;
+use std::fs;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::UNIX_EPOCH;
+
+/// Most results kept; the least recently used one is dropped to make room.
+const CAPACITY: usize = 64;
+
+/// Cached results, least recently used first.
+static CACHE: Mutex<VecDeque<(PeekKey, PeekResult)>> = Mutex::new(VecDeque::new());
+
+/// What a `peek()` result depends on.
+#[derive(Debug, PartialEq, Eq)]
+struct PeekKey {
+    /// Path or URL, as given.
+    bam_path: String,
+    /// Modification time and size of a file, or `cache_version` of a URL.
+    version: Option<String>,
+    /// Number of records examined.
+    sample_size: u32,
+    /// Region the records are taken from, as given.
+    region: Option<String>,
+    /// Whether `region` is 1-based.
+    one_based: bool,
+    /// Reference FASTA of CRAM input.
+    reference_fasta: Option<String>,
+}
+
+impl PeekKey {
+    /// Key of a peek with `options` at `sample_size` records of the file or URL
+    /// at `location`, or `None` if the file cannot be looked at, in which case
+    /// it is not cached.
+    #[expect(
+        clippy::pattern_type_mismatch,
+        reason = "the path is bound through the borrowed `location` by default binding modes"
+    )]
+    fn new(options: &PeekOptions, sample_size: u32, location: &PathOrURLOrStdin) -> Option<Self> {
+        let version = match location {
+            PathOrURLOrStdin::Path(path) => {
+                let metadata = fs::metadata(path).ok()?;
+                let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+                Some(format!("{}:{}", modified.as_nanos(), metadata.len()))
+            }
+            PathOrURLOrStdin::URL(_) => options.cache_version.clone(),
+            PathOrURLOrStdin::Stdin | _ => return None,
+        };
+        Some(Self {
+            bam_path: options.bam_path.clone(),
+            version,
+            sample_size,
+            region: options.region.clone(),
+            one_based: options.one_based.unwrap_or(false),
+            reference_fasta: options.reference_fasta.clone(),
+        })
+    }
+}
+
+/// Result of peeking with `options`, from the cache if it holds one and
+/// `bypass_cache` is not set, otherwise from `peek`, which is then cached.
+///
+/// A file is looked up by its path, modification time and size, and a URL by the
+/// URL and `cache_version`, `location` telling which of them `options` names.
+/// Errors are not cached.
+pub(crate) fn cached_peek<F>(
+    options: &PeekOptions,
+    sample_size: u32,
+    location: &PathOrURLOrStdin,
+    peek: F,
+) -> napi::Result<PeekResult>
+where
+    F: FnOnce() -> napi::Result<PeekResult>,
+{
+    let Some(key) = PeekKey::new(options, sample_size, location) else {
+        return peek();
+    };
+    if !options.bypass_cache.unwrap_or(false) {
+        let mut cache = lock_cache();
+        if let Some(index) = cache.iter().position(|v| v.0 == key) {
+            // Moved to the back, as the most recently used
+            if let Some(entry) = cache.remove(index) {
+                let result = entry.1.clone();
+                cache.push_back(entry);
+                return Ok(result);
+            }
+        }
+    }
+    let result = peek()?;
+    let mut cache = lock_cache();
+    cache.retain(|v| v.0 != key);
+    if cache.len() >= CAPACITY {
+        drop(cache.pop_front());
+    }
+    cache.push_back((key, result.clone()));
+    Ok(result)
+}
+
+/// Empties the cache of `peek()` results.
+///
+/// Later calls to `peek()` and `peek_many()` read their files again.
+#[napi]
+pub fn clear_peek_cache() {
+    lock_cache().clear();
+}
+
+/// Locks the cache. A panic while it was held cannot leave it inconsistent, so a
+/// poisoned lock is taken over.
+fn lock_cache() -> MutexGuard<'static, VecDeque<(PeekKey, PeekResult)>> {
+    CACHE.lock().unwrap_or_else(PoisonError::into_inner)
+}
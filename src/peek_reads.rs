@@ -0,0 +1,104 @@
+//! `peek()` and the first reads of `read_info()` from one pass over a BAM file.
+
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use crate::progress::{Progress, progress_interval};
+use crate::records::{ReadInfoRecord, read_info_mods};
+use crate::regions::Regions;
+use crate::{
+    PeekResult, ReadOptions, add_peek_calls, build_input_options, passes_read_filters,
+    peek_contigs, peek_result, peek_sample_size, query_reader, validate_pagination,
+};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::IndexedReader;
+use std::collections::BTreeMap;
+
+/// Result of `peek_with_reads()`.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct PeekWithReads {
+    /// What `peek()` gives for the file, with modifications from the first
+    /// `sample_size` records of the file or region.
+    pub peek: PeekResult,
+    /// What `read_info()` gives for the same options.
+    pub reads: Vec<ReadInfoRecord>,
+}
+
+/// Peeks at a BAM file and lists its first reads, opening the file once.
+///
+/// Takes the options of `read_info`, whose `limit` sets how many reads are
+/// listed, and the `sample_size` of `peek` (default: 100). Both come from the
+/// same records: reading stops once `sample_size` records have been examined and
+/// `limit` reads listed. Unlike `peek()`, the result is not cached.
+///
+/// # Errors
+/// Returns an error if input options are invalid, `sort_by` is set, or BAM
+/// reading fails.
+#[napi]
+pub async fn peek_with_reads(
+    options: ReadOptions,
+    sample_size: Option<u32>,
+) -> Result<PeekWithReads> {
+    run_limited(move || peek_with_reads_sync(&options, sample_size, None)).await
+}
+
+/// Synchronous implementation of `peek_with_reads`.
+pub(crate) fn peek_with_reads_sync(
+    options: &ReadOptions,
+    sample_size: Option<u32>,
+    shared: Option<&mut IndexedReader>,
+) -> Result<PeekWithReads> {
+    if options.sort_by.is_some() {
+        return Err(ErrorKind::InvalidOptions
+            .error("peekWithReads lists reads in file order; sort_by is not supported"));
+    }
+    let sample = usize::try_from(peek_sample_size(sample_size)?).unwrap_or(usize::MAX);
+    let (offset, limit) = validate_pagination(options)?;
+    let interval = progress_interval(options.progress_interval)?;
+    let (mut bam, mut raw_mods, extra) = build_input_options(options)?;
+    let regions = Regions::from_options(options)?;
+
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, header) = reader.records(&mut bam, &mut raw_mods, regions.as_ref())?;
+    let mods = read_info_mods(raw_mods);
+    let no_prefilter = options.no_prefilter == Some(true);
+
+    let mut modifications = BTreeMap::new();
+    let mut reads = Vec::new();
+    let mut skipped = 0usize;
+    let records = Progress::new(rc_records, options.on_progress.clone(), interval);
+    for (index, entry) in records.enumerate() {
+        let sampled = index < sample;
+        if !sampled && reads.len() >= limit {
+            break;
+        }
+        let record = entry.map_err(|e| {
+            ErrorKind::of_htslib(&e).error(format!("Failed to read BAM record: {e}"))
+        })?;
+        if sampled {
+            add_peek_calls(&mut modifications, &record)?;
+        }
+        if reads.len() >= limit {
+            continue;
+        }
+        let passed = passes_read_filters(&record, &bam, &mods, &extra);
+        if !passed && !no_prefilter {
+            continue;
+        }
+        if skipped < offset {
+            skipped = skipped.saturating_add(1);
+            continue;
+        }
+        let mut info = ReadInfoRecord::from_record(&record, &mods, extra.mod_selection())?;
+        if no_prefilter {
+            info.passed_prefilter = Some(passed);
+        }
+        reads.push(info);
+    }
+    Ok(PeekWithReads {
+        peek: peek_result(peek_contigs(&header)?, modifications),
+        reads,
+    })
+}
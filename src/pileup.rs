@@ -0,0 +1,221 @@
+//! Per-position summary of modification calls over a region, as in bedMethyl files.
+
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use crate::progress::{Progress, progress_interval};
+use crate::records::read_mod_data;
+use crate::regions::core_region;
+use crate::{
+    ReadOptions, build_input_options, passes_read_filters, query_reader, validate_pagination,
+};
+use bedrs::Coordinates as _;
+use nanalogue_core::{Contains as _, GenomicRegion, ModChar, ThresholdState};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::{HeaderView, IndexedReader};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::str::FromStr as _;
+
+/// Calls with a probability at or above this ML value count as modified, as in
+/// the core windowing functions.
+pub(crate) const MOD_THRESHOLD: ThresholdState = ThresholdState::GtEq(128);
+
+/// Options of `pileup()` that the other queries do not take, given next to its
+/// [`ReadOptions`].
+#[napi(object, object_to_js = false)]
+#[non_exhaustive]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PileupOptions {
+    /// Leave out the positions with fewer valid calls than this, such as those
+    /// covered by only one or two reads. Defaults to 0: every position with a
+    /// call.
+    pub min_coverage: Option<u32>,
+    /// If true, count the calls on the C of the minus strand of a CG site at the
+    /// C of the plus strand, so each CG site gives one record. Calls on other
+    /// bases keep their strand. Defaults to false.
+    pub collapse_strands: Option<bool>,
+}
+
+/// Modification calls at one reference position, strand and modification code.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct PileupRecord {
+    /// Contig of the position.
+    pub contig: String,
+    /// 0-based position on the contig.
+    pub pos: i64,
+    /// Reference strand the modified base is on, `+` or `-`. With
+    /// `collapse_strands`, `+` for the collapsed calls on C.
+    pub strand: String,
+    /// Modification code, e.g. `m`, or a numeric code such as `76792`.
+    #[napi(js_name = "mod_code")]
+    pub mod_code: String,
+    /// Number of calls with a probability of at least 0.5.
+    #[napi(js_name = "n_mod")]
+    pub n_mod: u32,
+    /// Number of calls that pass the modification filters.
+    #[napi(js_name = "n_valid")]
+    pub n_valid: u32,
+    /// `n_mod / n_valid`.
+    pub fraction: f64,
+}
+
+/// Counts modified and valid modification calls at each position of a region.
+///
+/// Requires the `region` parameter to be set. Reads are selected as in
+/// `bam_mods`, so `full_region` and the read filters apply, and `offset` and
+/// `limit` count reads. Calls that pass the modification filters are valid, and
+/// valid calls with a probability of at least 0.5 are modified. Calls outside the
+/// region or on bases not aligned to the reference are left out, as are the
+/// positions with fewer valid calls than the `min_coverage` of `pileup_options`.
+/// Records are sorted by position, strand and modification code.
+///
+/// With the `collapse_strands` of `pileup_options`, the calls on C are taken to
+/// be in CG context, as methylation there is symmetric: a call on the C of the
+/// minus strand is counted at the C of the plus strand one position before it,
+/// so each CG site has one record on the `+` strand. This holds both for calls
+/// made on a C of the read (`C+m`) and for those made on the C paired with a G
+/// of the read (`G-m`). The reference is not read, so calls on a C outside a CG
+/// site are moved just the same. Calls on other bases keep their strand and
+/// position.
+///
+/// # Errors
+/// Returns an error if region is missing, input options are invalid, or BAM
+/// reading fails.
+#[napi]
+pub async fn pileup(
+    options: ReadOptions,
+    pileup_options: Option<PileupOptions>,
+) -> Result<Vec<PileupRecord>> {
+    run_limited(move || pileup_sync(&options, pileup_options.unwrap_or_default(), None)).await
+}
+
+/// Returns the `region` option of a function that works on exactly one region,
+/// in 0-based, half-open coordinates.
+///
+/// # Errors
+/// Returns an error if region is missing or invalid, or `regions` or
+/// `region_bed` are set.
+pub(crate) fn single_region<'a>(options: &'a ReadOptions, function: &str) -> Result<Cow<'a, str>> {
+    if options.regions.is_some() || options.region_bed.is_some() {
+        return Err(ErrorKind::InvalidOptions.error(format!(
+            "{function} takes a single region; regions and regionBed are not supported"
+        )));
+    }
+    let region = options
+        .region
+        .as_deref()
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| {
+            ErrorKind::InvalidOptions.error(format!("region parameter is required for {function}"))
+        })?;
+    core_region(region, options.one_based)
+}
+
+/// Resolves a region string to its contig name and 0-based, half-open bounds.
+///
+/// # Errors
+/// Returns an error if the region cannot be parsed or is not in the header.
+pub(crate) fn region_bounds(region_str: &str, header: &HeaderView) -> Result<(String, i64, i64)> {
+    let region = GenomicRegion::from_str(region_str)
+        .map_err(|e| ErrorKind::InvalidRegion.error(format!("Invalid region: {e}")))?
+        .try_to_bed3(header)
+        .map_err(|e| {
+            ErrorKind::InvalidRegion.error(format!("Failed to convert region to bed3: {e}"))
+        })?;
+    let contig =
+        String::from_utf8_lossy(header.tid2name(u32::try_from(*region.chr()).map_err(|e| {
+            ErrorKind::InvalidRegion.error(format!("Invalid contig id in region: {e}"))
+        })?))
+        .into_owned();
+    let to_i64 = |v: u64| {
+        i64::try_from(v).map_err(|e| ErrorKind::InvalidRegion.error(format!("Invalid region: {e}")))
+    };
+    Ok((contig, to_i64(region.start())?, to_i64(region.end())?))
+}
+
+/// Synchronous implementation of `pileup`.
+pub(crate) fn pileup_sync(
+    options: &ReadOptions,
+    pileup_options: PileupOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<Vec<PileupRecord>> {
+    let region_str = single_region(options, "pileup")?;
+    let (offset, limit) = validate_pagination(options)?;
+    let interval = progress_interval(options.progress_interval)?;
+    let min_coverage = pileup_options.min_coverage.unwrap_or(0);
+    let collapse = pileup_options.collapse_strands == Some(true);
+    let (mut bam, mut mods, extra) = build_input_options(options)?;
+
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, header) = reader.records(&mut bam, &mut mods, None)?;
+    let (contig, start, end) = region_bounds(&region_str, &header)?;
+
+    let filtered = Progress::new(rc_records, options.on_progress.clone(), interval).filter(|r| {
+        r.as_ref()
+            .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    // Keyed by position, reference strand and modification code, holding the
+    // modified and valid call counts
+    let mut counts: BTreeMap<(i64, char, String), (u32, u32)> = BTreeMap::new();
+    for entry in filtered.skip(offset).take(limit) {
+        let record = entry.map_err(|e| {
+            ErrorKind::of_htslib(&e).error(format!("Failed to read BAM record: {e}"))
+        })?;
+        let read = read_mod_data(&record, &mods, extra.mod_selection())
+            .map_err(|e| ErrorKind::of_core(&e).error(format!("pileup failed: {e}")))?;
+        for base_mod in &read.mod_data().0.base_mods {
+            // A call on the opposite strand of the basecalled sequence lies on the
+            // other reference strand than the read
+            let is_plus = record.is_reverse() == (base_mod.strand == '-');
+            // A call on the opposite strand is made on the complement of its base
+            let is_on_c = if base_mod.strand == '-' {
+                base_mod.modified_base == b'G'
+            } else {
+                base_mod.modified_base == b'C'
+            };
+            let mod_code = ModChar::new(base_mod.modification_type).to_string();
+            // The C of the minus strand of a CG site pairs with the C of the plus
+            // strand one position before it
+            let (strand, shift) = if is_plus {
+                ('+', 0)
+            } else if collapse && is_on_c {
+                ('+', 1)
+            } else {
+                ('-', 0)
+            };
+            for k in &base_mod.ranges.annotations {
+                let Some(pos) = k
+                    .reference_start
+                    .map(|v| v.saturating_sub(shift))
+                    .filter(|v| (start..end).contains(v))
+                else {
+                    continue;
+                };
+                let count = counts
+                    .entry((pos, strand, mod_code.clone()))
+                    .or_insert((0, 0));
+                if MOD_THRESHOLD.contains(&k.qual) {
+                    count.0 = count.0.saturating_add(1);
+                }
+                count.1 = count.1.saturating_add(1);
+            }
+        }
+    }
+
+    Ok(counts
+        .into_iter()
+        .filter(|&(_, (_, n_valid))| n_valid >= min_coverage)
+        .map(|((pos, strand, mod_code), (n_mod, n_valid))| PileupRecord {
+            contig: contig.clone(),
+            pos,
+            strand: strand.to_string(),
+            mod_code,
+            n_mod,
+            n_valid,
+            fraction: f64::from(n_mod) / f64::from(n_valid),
+        })
+        .collect())
+}
@@ -0,0 +1,108 @@
+//! Progress reporting from the blocking threads that iterate over BAM records.
+
+use crate::error::ErrorKind;
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use std::fmt;
+
+/// Number of records between progress callbacks when `progress_interval` is not set.
+const DEFAULT_PROGRESS_INTERVAL: u32 = 1000;
+
+/// JavaScript callback receiving the number of records processed so far.
+///
+/// Calls are queued onto the JS thread without blocking the worker. If the JS side
+/// has gone away (e.g. the environment is shutting down) the call is dropped.
+#[derive(Clone)]
+pub struct ProgressCallback(ThreadsafeFunction<u32, ErrorStrategy::Fatal>);
+
+impl ProgressCallback {
+    /// Queues a call with the given record count, ignoring a closed callback.
+    fn report(&self, count: u32) {
+        let _: Status = self.0.call(count, ThreadsafeFunctionCallMode::NonBlocking);
+    }
+}
+
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressCallback").finish_non_exhaustive()
+    }
+}
+
+impl FromNapiValue for ProgressCallback {
+    unsafe fn from_napi_value(env: sys::napi_env, napi_val: sys::napi_value) -> Result<Self> {
+        // napi aborts the process while cleaning up a threadsafe function that failed to
+        // be created from a non-function, so anything else is rejected up front.
+        if napi::type_of!(env, napi_val)? != ValueType::Function {
+            return Err(ErrorKind::InvalidOptions.error("expected a function"));
+        }
+        ThreadsafeFunction::from_napi_value(env, napi_val).map(Self)
+    }
+}
+
+/// Validates `progress_interval` and applies its default.
+pub(crate) fn progress_interval(interval: Option<u32>) -> Result<u32> {
+    match interval {
+        Some(0) => {
+            Err(ErrorKind::InvalidOptions.error("progress_interval must be a positive integer"))
+        }
+        Some(v) => Ok(v),
+        None => Ok(DEFAULT_PROGRESS_INTERVAL),
+    }
+}
+
+/// Iterator adapter that reports how many items have been pulled from `inner`.
+///
+/// The callback fires every `interval` items, and once more when the adapter is
+/// dropped if the final count has not been reported yet, so the last value seen on
+/// the JS side is always the total.
+pub(crate) struct Progress<I> {
+    /// Wrapped record iterator.
+    inner: I,
+    /// Callback to report to; `None` makes the adapter a pass-through.
+    callback: Option<ProgressCallback>,
+    /// Number of items between reports.
+    interval: u32,
+    /// Items pulled so far.
+    count: u32,
+    /// Count at the most recent report.
+    reported: Option<u32>,
+}
+
+impl<I> Progress<I> {
+    /// Wraps `inner`, reporting to `callback` every `interval` items.
+    pub(crate) fn new(inner: I, callback: Option<ProgressCallback>, interval: u32) -> Self {
+        Self {
+            inner,
+            callback,
+            interval,
+            count: 0,
+            reported: None,
+        }
+    }
+}
+
+impl<I: Iterator> Iterator for Progress<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+        if let Some(callback) = self.callback.as_ref() {
+            self.count = self.count.saturating_add(1);
+            if self.count.is_multiple_of(self.interval) {
+                callback.report(self.count);
+                self.reported = Some(self.count);
+            }
+        }
+        Some(item)
+    }
+}
+
+impl<I> Drop for Progress<I> {
+    fn drop(&mut self) {
+        if let Some(callback) = self.callback.as_ref()
+            && self.reported != Some(self.count)
+        {
+            callback.report(self.count);
+        }
+    }
+}
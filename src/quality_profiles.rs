@@ -0,0 +1,158 @@
+//! Base qualities of each read as numbers, for quality profile plots.
+
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use crate::progress::{Progress, progress_interval};
+use crate::records::{base_quals, qual_offset};
+use crate::regions::{read_positions, region_bed3};
+use crate::{
+    ReadOptions, build_input_options, passes_read_filters, query_reader, validate_pagination,
+};
+use bedrs::Bed3;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::{IndexedReader, Record};
+
+/// Base qualities of one read, from `quality_profiles`.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct QualityProfile {
+    /// Read id.
+    #[napi(js_name = "read_id")]
+    pub read_id: String,
+    /// Mean of the base qualities in `q`, leaving out deletions. Absent if the
+    /// read has no base qualities.
+    #[napi(js_name = "mean_q")]
+    pub mean_q: Option<f64>,
+    /// Phred quality of each base of the read, in the orientation of the
+    /// reference. Over a region, one per character of the `seq_table`
+    /// sequence, with 255 for a deletion.
+    pub q: Vec<u8>,
+}
+
+impl QualityProfile {
+    /// Builds the base qualities of `record`, over `region` if given, for
+    /// qualities encoded with `offset`.
+    ///
+    /// Returns `None` for a record without sequence, or, over a region, without
+    /// bases aligned in it.
+    ///
+    /// # Errors
+    /// Returns an error if the alignment of the record is invalid, or its
+    /// qualities are not encoded with `offset`.
+    fn from_record(
+        record: &Record,
+        region: Option<&Bed3<i32, u64>>,
+        offset: u8,
+    ) -> Result<Option<Self>> {
+        let qual = base_quals(record, offset)?;
+        if qual.is_empty() {
+            return Ok(None);
+        }
+        let q = match region {
+            None => qual,
+            Some(bed3) => {
+                let Some(positions) = read_positions(record, bed3, "quality_profiles")? else {
+                    return Ok(None);
+                };
+                positions
+                    .into_iter()
+                    .map(|entry| entry.map_or(Some(u8::MAX), |i| qual.get(i).copied()))
+                    .collect::<Option<Vec<u8>>>()
+                    .ok_or_else(|| {
+                        ErrorKind::Internal.error(format!(
+                            "quality_profiles failed: a position is outside read {}",
+                            String::from_utf8_lossy(record.qname())
+                        ))
+                    })?
+            }
+        };
+        if q.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            read_id: String::from_utf8_lossy(record.qname()).into_owned(),
+            mean_q: mean_quality(&q, region.is_some()),
+            q,
+        }))
+    }
+}
+
+/// Mean of the base qualities `q`, leaving out the deletions of a region if
+/// `clipped`, or `None` if there are no bases or the qualities are missing.
+fn mean_quality(q: &[u8], clipped: bool) -> Option<f64> {
+    // A missing quality string is stored as 255 for every base
+    if !clipped && q.first() == Some(&u8::MAX) {
+        return None;
+    }
+    let bases: Vec<f64> = q
+        .iter()
+        .filter(|&&v| !clipped || v != u8::MAX)
+        .map(|&v| f64::from(v))
+        .collect();
+    if bases.is_empty() {
+        return None;
+    }
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "numbers of bases in a read are far below 2^52"
+    )]
+    let count = bases.len() as f64;
+    Some(bases.iter().sum::<f64>() / count)
+}
+
+/// Returns the base qualities of each read as phred values.
+///
+/// With `region` set, the qualities are clipped to it so that they line up with
+/// the sequence [`crate::seq_table`] shows: one per character, insertions
+/// included, with 255 for a deletion. Reads that only overlap part of the
+/// region are kept unless `full_region` is set. Without `region`, each read
+/// gives the qualities of all its bases. Reads without bases are left out after
+/// `offset` and `limit` are applied. Qualities are decoded as Phred+33 unless
+/// `qual_offset` is 64.
+///
+/// # Errors
+/// Returns an error if `regions` or `region_bed` is set, input options are
+/// invalid, a quality is not in the encoding of `qual_offset` or BAM reading
+/// fails.
+#[napi]
+pub async fn quality_profiles(options: ReadOptions) -> Result<Vec<QualityProfile>> {
+    run_limited(move || quality_profiles_sync(&options, None)).await
+}
+
+/// Synchronous implementation of `quality_profiles`.
+pub(crate) fn quality_profiles_sync(
+    options: &ReadOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<Vec<QualityProfile>> {
+    let (offset, limit) = validate_pagination(options)?;
+    let interval = progress_interval(options.progress_interval)?;
+    let encoding = qual_offset(options)?;
+
+    if options.regions.is_some() || options.region_bed.is_some() {
+        return Err(ErrorKind::InvalidOptions.error(
+            "qualityProfiles takes a single region; regions and regionBed are not supported",
+        ));
+    }
+
+    let (mut bam, mut mods, extra) = build_input_options(options)?;
+    let mut reader = query_reader(&bam, options, shared)?;
+    let (rc_records, header) = reader.records(&mut bam, &mut mods, None)?;
+
+    let region = region_bed3(options, &header)?;
+
+    let filtered = Progress::new(rc_records, options.on_progress.clone(), interval).filter(|r| {
+        r.as_ref()
+            .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    let mut profiles = Vec::new();
+    for entry in filtered.skip(offset).take(limit) {
+        let record = entry
+            .map_err(|e| ErrorKind::of_htslib(&e).error(format!("quality_profiles failed: {e}")))?;
+        if let Some(v) = QualityProfile::from_record(&record, region.as_ref(), encoding)? {
+            profiles.push(v);
+        }
+    }
+    Ok(profiles)
+}
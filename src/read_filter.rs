@@ -0,0 +1,78 @@
+//! Typed alignment-type filters for the `read_filter` option.
+
+use crate::error::ErrorKind;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+/// Alignment type that a read must have to pass `read_filter`.
+#[napi(string_enum = "snake_case")]
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum ReadFilterKind {
+    /// Primary alignment to the reference strand.
+    PrimaryForward,
+    /// Primary alignment opposite the reference strand.
+    PrimaryReverse,
+    /// Secondary alignment to the reference strand.
+    SecondaryForward,
+    /// Secondary alignment opposite the reference strand.
+    SecondaryReverse,
+    /// Supplementary alignment to the reference strand.
+    SupplementaryForward,
+    /// Supplementary alignment opposite the reference strand.
+    SupplementaryReverse,
+    /// Read marked as unmapped.
+    Unmapped,
+}
+
+impl ReadFilterKind {
+    /// The alignment type name understood by the core read filter.
+    fn as_core_str(self) -> &'static str {
+        match self {
+            Self::PrimaryForward => "primary_forward",
+            Self::PrimaryReverse => "primary_reverse",
+            Self::SecondaryForward => "secondary_forward",
+            Self::SecondaryReverse => "secondary_reverse",
+            Self::SupplementaryForward => "supplementary_forward",
+            Self::SupplementaryReverse => "supplementary_reverse",
+            Self::Unmapped => "unmapped",
+        }
+    }
+}
+
+/// Value of the `read_filter` option, held as the comma-separated string that the
+/// core read filter expects.
+///
+/// JS may pass either a list of [`ReadFilterKind`]s or, as before, a comma-separated
+/// string of alignment types.
+#[derive(Debug, Clone)]
+pub struct ReadFilter(String);
+
+impl ReadFilter {
+    /// The comma-separated alignment types.
+    pub(crate) fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromNapiValue for ReadFilter {
+    unsafe fn from_napi_value(env: sys::napi_env, napi_val: sys::napi_value) -> Result<Self> {
+        if napi::type_of!(env, napi_val)? == ValueType::String {
+            return String::from_napi_value(env, napi_val).map(Self);
+        }
+        // Converted by hand rather than with `Either` so that an unknown alignment type
+        // in a list reports which value was wrong.
+        let kinds = Vec::<ReadFilterKind>::from_napi_value(env, napi_val)?;
+        if kinds.is_empty() {
+            return Err(ErrorKind::InvalidOptions
+                .error("readFilter must include at least one alignment type"));
+        }
+        Ok(Self(
+            kinds
+                .into_iter()
+                .map(ReadFilterKind::as_core_str)
+                .collect::<Vec<_>>()
+                .join(","),
+        ))
+    }
+}
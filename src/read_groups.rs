@@ -0,0 +1,57 @@
+//! Read groups (`@RG` header lines and `RG` tags) of a BAM file.
+
+use crate::concurrency::run_limited;
+use crate::url_open::UrlOpen;
+use crate::{PeekOptions, ReadOptions, load_bam};
+use nanalogue_core::InputBam;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::record::Aux;
+use rust_htslib::bam::{Header, HeaderView, Read as _, Record};
+use std::collections::HashSet;
+
+/// Lists the read group ids in the `@RG` lines of the BAM header.
+///
+/// Ids are given in header order, and the list is empty if the header has no
+/// read groups. Only the header is read.
+///
+/// # Errors
+/// Returns an error if the path/URL is invalid or the BAM file cannot be read.
+#[napi]
+pub async fn read_groups(options: PeekOptions) -> Result<Vec<String>> {
+    run_limited(move || {
+        let bam = InputBam::try_from(&ReadOptions {
+            bam_path: options.bam_path.clone(),
+            treat_as_url: options.treat_as_url,
+            ..ReadOptions::default()
+        })?;
+        let url_open = UrlOpen::new(
+            options.url_retries,
+            options.url_timeout_ms,
+            options.url_headers.as_ref(),
+        )?;
+        let reader = load_bam(&bam, options.reference_fasta.as_deref(), &url_open, false)?;
+        Ok(read_group_ids(reader.header()))
+    })
+    .await
+}
+
+/// Read group ids in the `@RG` lines of `header`, in order.
+pub(crate) fn read_group_ids(header: &HeaderView) -> Vec<String> {
+    Header::from_template(header)
+        .to_hashmap()
+        .remove("RG")
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|mut v| v.remove("ID"))
+        .collect()
+}
+
+/// Whether the `RG` tag of `record` is one of `read_groups`. A record without
+/// the tag is in no read group.
+pub(crate) fn in_read_groups(record: &Record, read_groups: &HashSet<Vec<u8>>) -> bool {
+    match record.aux(b"RG") {
+        Ok(Aux::String(v)) => read_groups.contains(v.as_bytes()),
+        Ok(_) | Err(_) => false,
+    }
+}
@@ -0,0 +1,824 @@
+//! Typed records returned to JS in place of JSON produced by the core commands.
+
+use crate::ReadOptions;
+use crate::error::ErrorKind;
+use bedrs::Bed3;
+use bedrs::prelude::{Coordinates as _, Intersect as _, StrandedBed3};
+use nanalogue_core::read_utils::{AlignAndModData, OnlyAlignDataComplete};
+use nanalogue_core::{
+    CurrRead, Error as CoreError, FilterByRefCoords as _, InputModOptions as _, InputMods,
+    InputRegionOptions as _, ModChar, OptionalTag, SeqCoordCalls, ThresholdState,
+};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::Record;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::str::FromStr as _;
+
+/// Information on one read, as returned by `read_info`.
+///
+/// Field names match the JSON written by the core `read_info` command. Contig and
+/// reference coordinates are absent for unmapped reads.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct ReadInfoRecord {
+    /// Read id.
+    #[napi(js_name = "read_id")]
+    pub read_id: String,
+    /// Length of the basecalled sequence.
+    #[napi(js_name = "sequence_length")]
+    pub sequence_length: Option<i64>,
+    /// Contig the read is aligned to.
+    pub contig: Option<String>,
+    /// 0-based start of the alignment on the contig.
+    #[napi(js_name = "reference_start")]
+    pub reference_start: Option<i64>,
+    /// 0-based exclusive end of the alignment on the contig.
+    #[napi(js_name = "reference_end")]
+    pub reference_end: Option<i64>,
+    /// Length of the alignment on the contig.
+    #[napi(js_name = "alignment_length")]
+    pub alignment_length: Option<i64>,
+    /// Alignment type, e.g. `primary_forward` or `unmapped`.
+    #[napi(js_name = "alignment_type")]
+    pub alignment_type: String,
+    /// Number of modified bases per modification type, followed by the thresholds
+    /// used, e.g. `T+T:3;(probabilities >= 0.5020, PHRED base qual >= 0)`, or `NA`
+    /// if the read has no modification calls.
+    #[napi(js_name = "mod_count")]
+    pub mod_count: String,
+    /// Whether the record passes the read filters, given only with `no_prefilter`.
+    #[napi(js_name = "passed_prefilter")]
+    pub passed_prefilter: Option<bool>,
+}
+
+impl ReadInfoRecord {
+    /// Builds the information on one record, counting modifications with `mods`
+    /// and `tags` as in [`read_mod_data`].
+    ///
+    /// `mods` should have passed through [`read_info_mods`] first.
+    ///
+    /// # Errors
+    /// Returns an error if the alignment or the MM/ML tags of the record are invalid.
+    pub(crate) fn from_record(
+        record: &Record,
+        mods: &InputMods<OptionalTag>,
+        selection: ModSelection<'_>,
+    ) -> Result<Self> {
+        let read = read_mod_data(record, mods, selection)
+            .map_err(|e| ErrorKind::of_core(&e).error(format!("read_info failed: {e}")))?;
+        let to_i64 = |v: u64| {
+            i64::try_from(v)
+                .map_err(|e| ErrorKind::Internal.error(format!("read_info failed: {e}")))
+        };
+
+        let (contig, reference_start, reference_end, alignment_length) =
+            match read.contig_id_and_start().ok() {
+                Some((tid, start)) => {
+                    let align_len = read.align_len().ok();
+                    let end = align_len
+                        .map(|v| {
+                            start.checked_add(v).ok_or_else(|| {
+                                ErrorKind::Internal
+                                    .error("read_info failed: reference end overflows")
+                            })
+                        })
+                        .transpose()?;
+                    (
+                        Some(
+                            read.contig_name()
+                                .map_or_else(|_| tid.to_string(), str::to_owned),
+                        ),
+                        Some(to_i64(start)?),
+                        end.map(to_i64).transpose()?,
+                        align_len.map(to_i64).transpose()?,
+                    )
+                }
+                None => (None, None, None, None),
+            };
+
+        let mod_data = read.mod_data();
+        let mut counts = String::new();
+        for k in &mod_data.0.base_mods {
+            write!(
+                counts,
+                "{}{}{}:{};",
+                char::from(k.modified_base),
+                k.strand,
+                ModChar::new(k.modification_type),
+                k.ranges.annotations.len()
+            )
+            .map_err(|e| ErrorKind::Internal.error(format!("read_info failed: {e}")))?;
+        }
+        let mod_count = if counts.is_empty() {
+            "NA".to_owned()
+        } else {
+            format!(
+                "{counts}({}, PHRED base qual >= {})",
+                mods.mod_prob_filter(),
+                mods.base_qual_filter_mod()
+            )
+        };
+
+        Ok(Self {
+            read_id: read.read_id().to_owned(),
+            sequence_length: read.seq_len().ok().map(to_i64).transpose()?,
+            contig,
+            reference_start,
+            reference_end,
+            alignment_length,
+            alignment_type: read.read_state().to_string(),
+            mod_count,
+            passed_prefilter: None,
+        })
+    }
+}
+
+/// One modification call on one read, as returned by `bam_mods_typed`.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct ModCall {
+    /// Read id.
+    #[napi(js_name = "read_id")]
+    pub read_id: String,
+    /// 0-based position of the call on the basecalled sequence.
+    #[napi(js_name = "query_position")]
+    pub query_position: i64,
+    /// 0-based position of the call on the contig, absent if the base is not aligned.
+    #[napi(js_name = "reference_position")]
+    pub reference_position: Option<i64>,
+    /// Modification code, e.g. `m`, or a numeric code such as `76792`.
+    #[napi(js_name = "mod_code")]
+    pub mod_code: String,
+    /// Unmodified base the modification is on, e.g. `C`.
+    #[napi(js_name = "canonical_base")]
+    pub canonical_base: String,
+    /// Strand of the modification in the MM tag, `+` or `-`.
+    pub strand: String,
+    /// Probability of modification as stored in the ML tag, from 0 to 255.
+    #[napi(js_name = "ml_value")]
+    pub ml_value: u32,
+    /// Probability of modification between 0 and 1.
+    pub probability: f64,
+    /// Lowest ML value kept for the modification code, if `mod_thresholds` gives
+    /// the code its own.
+    pub threshold: Option<u32>,
+}
+
+impl ModCall {
+    /// Builds the modification calls of one record that pass the filters in `mods`
+    /// and `tags`, as in [`read_mod_data`].
+    ///
+    /// # Errors
+    /// Returns an error if the alignment or the MM/ML tags of the record are invalid.
+    pub(crate) fn from_record(
+        record: &Record,
+        mods: &InputMods<OptionalTag>,
+        selection: ModSelection<'_>,
+    ) -> Result<Vec<Self>> {
+        let read = read_mod_data(record, mods, selection)
+            .map_err(|e| ErrorKind::of_core(&e).error(format!("bam_mods failed: {e}")))?;
+        let mut calls = Vec::new();
+        for base_mod in &read.mod_data().0.base_mods {
+            let mod_code = ModChar::new(base_mod.modification_type).to_string();
+            let canonical_base = char::from(base_mod.modified_base).to_string();
+            let strand = base_mod.strand.to_string();
+            let threshold = selection
+                .threshold(ModChar::new(base_mod.modification_type))
+                .map(u32::from);
+            for k in &base_mod.ranges.annotations {
+                calls.push(Self {
+                    read_id: read.read_id().to_owned(),
+                    query_position: k.start,
+                    reference_position: k.reference_start,
+                    mod_code: mod_code.clone(),
+                    canonical_base: canonical_base.clone(),
+                    strand: strand.clone(),
+                    ml_value: u32::from(k.qual),
+                    probability: f64::from(k.qual) / f64::from(u8::MAX),
+                    threshold,
+                });
+            }
+        }
+        Ok(calls)
+    }
+}
+
+/// Columns of the core reads table, which `seq_table` can output.
+pub(crate) const SEQ_TABLE_COLUMNS: [&str; 7] = [
+    "read_id",
+    "align_length",
+    "sequence_length_template",
+    "alignment_type",
+    "mod_count",
+    "sequence",
+    "qualities",
+];
+
+/// Columns `seq_table` outputs when `columns` is not set.
+const SEQ_TABLE_DEFAULT_COLUMNS: [&str; 3] = ["read_id", "sequence", "qualities"];
+
+/// Checks the `columns` option of `seq_table` against [`SEQ_TABLE_COLUMNS`] and
+/// applies its default, which leaves out `qualities` unless `qualities` is set.
+///
+/// # Errors
+/// Returns an error if the list is empty, or names an unknown column, the same
+/// column twice, or `qualities` without `qualities` set.
+pub(crate) fn seq_table_columns(columns: Option<&[String]>, qualities: bool) -> Result<Vec<&str>> {
+    let Some(names) = columns else {
+        return Ok(SEQ_TABLE_DEFAULT_COLUMNS
+            .into_iter()
+            .filter(|&v| qualities || v != "qualities")
+            .collect());
+    };
+    if names.is_empty() {
+        return Err(
+            ErrorKind::InvalidOptions.error("columns must name at least one seqTable column")
+        );
+    }
+    let mut selected: Vec<&str> = Vec::with_capacity(names.len());
+    for name in names {
+        if !SEQ_TABLE_COLUMNS.contains(&name.as_str()) {
+            return Err(ErrorKind::InvalidOptions.error(format!(
+                "Unknown seqTable column '{name}'; available columns are {}",
+                SEQ_TABLE_COLUMNS.join(", ")
+            )));
+        }
+        if selected.contains(&name.as_str()) {
+            return Err(ErrorKind::InvalidOptions
+                .error(format!("seqTable column '{name}' is listed more than once")));
+        }
+        if !qualities && name == "qualities" {
+            return Err(ErrorKind::InvalidOptions
+                .error("columns lists qualities, which showBaseQual false leaves out"));
+        }
+        selected.push(name);
+    }
+    Ok(selected)
+}
+
+/// Sequence and base qualities of one read over the region of `seq_table`.
+///
+/// If several alignments of the read overlap the region, their sequences and
+/// qualities are joined with `, `, as in the core reads table.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct SeqTableRecord {
+    /// Read id.
+    #[napi(js_name = "read_id")]
+    pub read_id: String,
+    /// Sequence over the region, with `.` for deletion, lower case for insertion,
+    /// and `Z` (or `z` in an insertion) for a modified base.
+    pub sequence: String,
+    /// Base qualities over the region, separated by `.`, with 255 for a deletion.
+    /// Absent if `show_base_qual` is false.
+    pub qualities: Option<String>,
+    /// Region of the read, when the table is over several `regions`.
+    pub region: Option<String>,
+}
+
+impl SeqTableRecord {
+    /// Record of `row`, with its qualities if `qualities` is set.
+    pub(crate) fn new(row: SeqTableRow, qualities: bool) -> Self {
+        Self {
+            sequence: join_alignments(&row.sequence),
+            qualities: qualities.then(|| join_alignments(&row.qualities)),
+            read_id: row.read_id,
+            region: row.region,
+        }
+    }
+}
+
+/// Every column of the core reads table for one read over the region of `seq_table`.
+///
+/// Each alignment of the read adds one entry to the per-alignment fields.
+#[derive(Debug)]
+pub(crate) struct SeqTableRow {
+    /// Read id.
+    read_id: String,
+    /// Alignment length of each alignment, 0 if unmapped.
+    align_length: Vec<u64>,
+    /// Largest sequence length over the alignments.
+    sequence_length_template: u64,
+    /// Alignment type of each alignment.
+    alignment_type: Vec<String>,
+    /// Modified base count per modification code of each alignment.
+    mod_count: Vec<String>,
+    /// Sequence over the region of each alignment.
+    sequence: Vec<String>,
+    /// Base qualities over the region of each alignment.
+    qualities: Vec<String>,
+    /// Region of the row, when the table is over several.
+    region: Option<String>,
+}
+
+impl SeqTableRow {
+    /// Builds the row of one record over `region`.
+    ///
+    /// Returns `None` for a record without alignment or sequence length, which the
+    /// core reads table skips. `mods` should have passed through [`read_info_mods`]
+    /// first. The sequence is marked up as `display` sets.
+    ///
+    /// # Errors
+    /// Returns an error if the alignment or the MM/ML tags of the record are invalid.
+    pub(crate) fn from_record(
+        record: &Record,
+        mods: &InputMods<OptionalTag>,
+        selection: ModSelection<'_>,
+        region: &Bed3<i32, u64>,
+        display: SeqDisplay,
+    ) -> Result<Option<Self>> {
+        let failed = |e: CoreError| ErrorKind::of_core(&e).error(format!("seq_table failed: {e}"));
+        let read = read_mod_data(record, mods, selection).map_err(failed)?;
+        let align_length = match read.align_len() {
+            Ok(v) => v,
+            Err(CoreError::Unmapped(_)) => 0,
+            Err(_) => return Ok(None),
+        };
+        let Ok(sequence_length_template) = read.seq_len() else {
+            return Ok(None);
+        };
+
+        let mut counts: Vec<_> = read.base_count_per_mod().into_iter().collect();
+        counts.sort_unstable_by_key(|v| v.0);
+        let mut mod_count = String::new();
+        for (code, count) in counts {
+            if !mod_count.is_empty() {
+                mod_count.push(';');
+            }
+            write!(mod_count, "{code}:{count}")
+                .map_err(|e| ErrorKind::Internal.error(format!("seq_table failed: {e}")))?;
+        }
+        if mod_count.is_empty() {
+            "NA".clone_into(&mut mod_count);
+        }
+
+        let (mut sequence, mut qualities) = region_sequence(&read, record, region, display)?;
+        if display.reverse_minus && read.strand() == '-' && sequence != "*" {
+            sequence = sequence.chars().rev().map(complement).collect();
+            qualities = qualities.rsplit('.').collect::<Vec<_>>().join(".");
+        }
+        Ok(Some(Self {
+            read_id: read.read_id().to_owned(),
+            align_length: vec![align_length],
+            sequence_length_template,
+            alignment_type: vec![read.read_state().to_string()],
+            mod_count: vec![mod_count],
+            sequence: vec![sequence],
+            qualities: vec![qualities],
+            region: None,
+        }))
+    }
+
+    /// Value of the column `name`, one of [`SEQ_TABLE_COLUMNS`] or `region`, as
+    /// written in the core reads table.
+    pub(crate) fn column(&self, name: &str) -> Option<String> {
+        match name {
+            "read_id" => Some(self.read_id.clone()),
+            "align_length" => Some(join_alignments(&self.align_length)),
+            "sequence_length_template" => Some(self.sequence_length_template.to_string()),
+            "alignment_type" => Some(join_alignments(&self.alignment_type)),
+            "mod_count" => Some(join_alignments(&self.mod_count)),
+            "sequence" => Some(join_alignments(&self.sequence)),
+            "qualities" => Some(join_alignments(&self.qualities)),
+            "region" => self.region.clone(),
+            _ => None,
+        }
+    }
+
+    /// The row labelled as one of `region`.
+    pub(crate) fn in_region(self, region: &str) -> Self {
+        Self {
+            region: Some(region.to_owned()),
+            ..self
+        }
+    }
+
+    /// Appends another alignment of the same read.
+    fn merge(&mut self, other: Self) {
+        self.align_length.extend(other.align_length);
+        self.sequence_length_template = self
+            .sequence_length_template
+            .max(other.sequence_length_template);
+        self.alignment_type.extend(other.alignment_type);
+        self.mod_count.extend(other.mod_count);
+        self.sequence.extend(other.sequence);
+        self.qualities.extend(other.qualities);
+    }
+}
+
+/// How the `seq_table` sequence of a read is shown.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SeqDisplay {
+    /// Whether insertions are in lower case.
+    insertions: bool,
+    /// Whether modified bases are marked `Z` (or `z` in an insertion).
+    mod_markers: bool,
+    /// Whether reads on the minus strand are reverse-complemented.
+    reverse_minus: bool,
+    /// Offset of the base quality encoding, from [`qual_offset`].
+    qual_offset: u8,
+}
+
+impl SeqDisplay {
+    /// Display set by the `show_insertions`, `show_mod_markers`,
+    /// `reverse_complement_minus` and `qual_offset` options.
+    ///
+    /// # Errors
+    /// Returns an error if `qual_offset` is invalid.
+    pub(crate) fn new(options: &ReadOptions) -> Result<Self> {
+        Ok(Self {
+            insertions: options.show_insertions != Some(false),
+            mod_markers: options.show_mod_markers != Some(false),
+            reverse_minus: options.reverse_complement_minus == Some(true),
+            qual_offset: qual_offset(options)?,
+        })
+    }
+}
+
+/// Sequence and `.`-separated base qualities of `record` over `region`, marked up
+/// as in the core reads table, with the marks `display` keeps.
+fn region_sequence(
+    read: &CurrRead<AlignAndModData>,
+    record: &Record,
+    region: &Bed3<i32, u64>,
+    display: SeqDisplay,
+) -> Result<(String, String)> {
+    let failed = |e: CoreError| ErrorKind::of_core(&e).error(format!("seq_table failed: {e}"));
+    let seq = record.seq().as_bytes();
+    let qual = base_quals(record, display.qual_offset)?;
+    let coord_map = match read.seq_coords_from_ref_coords(record, region) {
+        Ok(v) => v,
+        Err(CoreError::UnavailableData(_)) => Vec::new(),
+        Err(e) => return Err(failed(e)),
+    };
+    if coord_map.is_empty() || seq.is_empty() {
+        return Ok(("*".to_owned(), u8::MAX.to_string()));
+    }
+    let modified = match SeqCoordCalls::try_from(&read.mod_data().0) {
+        Ok(v) => v.collapse_mod_calls(),
+        Err(CoreError::UnavailableData(_)) => vec![false; seq.len()],
+        Err(e) => return Err(failed(e)),
+    };
+
+    let mut sequence = String::with_capacity(coord_map.len());
+    let mut qualities = Vec::with_capacity(coord_map.len());
+    for entry in coord_map {
+        let Some((is_match, i)) = entry else {
+            sequence.push('.');
+            qualities.push(u8::MAX.to_string());
+            continue;
+        };
+        let (Some(&base), Some(&base_qual), Some(&is_modified)) =
+            (seq.get(i), qual.get(i), modified.get(i))
+        else {
+            return Err(ErrorKind::Internal.error(format!(
+                "seq_table failed: position {i} is outside read {}",
+                read.read_id()
+            )));
+        };
+        sequence.push(
+            match (
+                is_match || !display.insertions,
+                is_modified && display.mod_markers,
+            ) {
+                (true, true) => 'Z',
+                (false, true) => 'z',
+                (true, false) => char::from(base),
+                (false, false) => char::from(base.to_ascii_lowercase()),
+            },
+        );
+        qualities.push(base_qual.to_string());
+    }
+    Ok((sequence, qualities.join(".")))
+}
+
+/// Complement of a base of the `seq_table` sequence, keeping its case. The marks
+/// of modified bases (`Z`, `z`) and deletions (`.`) are kept as they are.
+const fn complement(base: char) -> char {
+    match base {
+        'A' => 'T',
+        'T' => 'A',
+        'C' => 'G',
+        'G' => 'C',
+        'a' => 't',
+        't' => 'a',
+        'c' => 'g',
+        'g' => 'c',
+        other => other,
+    }
+}
+
+/// Joins the values of the alignments of one read with `, `, as in the core reads table.
+fn join_alignments<T: ToString>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Collects `seq_table` rows, one per read in the order reads are first seen.
+#[derive(Debug, Default)]
+pub(crate) struct SeqTableRows {
+    /// Rows collected so far.
+    rows: Vec<SeqTableRow>,
+    /// Index in `rows` of each read id.
+    index: HashMap<String, usize>,
+}
+
+impl SeqTableRows {
+    /// Adds a row, merging it into the row of the same read if there is one.
+    pub(crate) fn push(&mut self, row: SeqTableRow) {
+        if let Some(existing) = self
+            .index
+            .get(&row.read_id)
+            .and_then(|&i| self.rows.get_mut(i))
+        {
+            existing.merge(row);
+        } else {
+            let _: Option<usize> = self.index.insert(row.read_id.clone(), self.rows.len());
+            self.rows.push(row);
+        }
+    }
+
+    /// Whether there is a row of the read of `row` already.
+    pub(crate) fn contains(&self, row: &SeqTableRow) -> bool {
+        self.index.contains_key(&row.read_id)
+    }
+
+    /// The collected rows.
+    pub(crate) fn into_rows(self) -> Vec<SeqTableRow> {
+        self.rows
+    }
+}
+
+/// Modification calls kept beyond the options of `InputMods`.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct ModSelection<'a> {
+    /// Modification codes whose calls are kept, in place of the single tag of
+    /// `InputMods`.
+    tags: Option<&'a HashSet<ModChar>>,
+    /// Lowest ML value of the calls kept, for the modification codes listed.
+    thresholds: Option<&'a HashMap<ModChar, u8>>,
+}
+
+impl<'a> ModSelection<'a> {
+    /// Keeps the calls of any of `tags` and of each code in `thresholds` at or
+    /// above its threshold.
+    pub(crate) const fn new(
+        tags: Option<&'a HashSet<ModChar>>,
+        thresholds: Option<&'a HashMap<ModChar, u8>>,
+    ) -> Self {
+        Self { tags, thresholds }
+    }
+
+    /// Modification codes whose calls are kept, if several.
+    pub(crate) const fn tags(&self) -> Option<&'a HashSet<ModChar>> {
+        self.tags
+    }
+
+    /// Whether any modification code has its own threshold.
+    pub(crate) const fn has_thresholds(&self) -> bool {
+        self.thresholds.is_some()
+    }
+
+    /// Threshold of the calls of `mod_code`, if it has its own.
+    pub(crate) fn threshold(&self, mod_code: ModChar) -> Option<u8> {
+        self.thresholds?.get(&mod_code).copied()
+    }
+}
+
+/// Reads the alignment and modification data of a record.
+///
+/// As in the core reads table, a record without sequence, which only passes the
+/// filters with `include_zero_len`, is kept with a sequence length of 0 and no
+/// modification data, as its MM/ML tags cannot be parsed without the sequence.
+///
+/// With `selection.tags`, the calls of any of these modification codes are kept,
+/// in place of the single tag of `mods`, and with `selection.thresholds`, the
+/// calls of each code listed are kept only at or above its threshold.
+///
+/// # Errors
+/// Returns an error if the alignment or the MM/ML tags of the record are invalid.
+pub(crate) fn read_mod_data(
+    record: &Record,
+    mods: &InputMods<OptionalTag>,
+    selection: ModSelection<'_>,
+) -> std::result::Result<CurrRead<AlignAndModData>, CoreError> {
+    let set_mod_data = |read: CurrRead<OnlyAlignDataComplete>, v: &Record| match selection.tags() {
+        Some(w) => set_mod_data_with_tags(read, v, mods, w),
+        None => read.set_mod_data_restricted_options(v, mods),
+    };
+    let read = match CurrRead::default().try_from_only_alignment(record) {
+        Ok(v) => set_mod_data(v, record),
+        Err(CoreError::ZeroSeqLen(_)) => {
+            let read = CurrRead::default().try_from_only_alignment_zero_seq_len(record)?;
+            set_mod_data(read, &Record::new())
+        }
+        Err(e) => Err(e),
+    }?;
+    apply_mod_thresholds(read, selection)
+}
+
+/// Whether a record carries modification data: an MM and an ML tag, in either
+/// case, as the core reads them.
+///
+/// A record without them gives an empty `mod_table`, as does one whose calls
+/// the modification filters all leave out.
+pub(crate) fn has_mod_tags(record: &Record) -> bool {
+    (record.aux(b"MM").is_ok() || record.aux(b"Mm").is_ok())
+        && (record.aux(b"ML").is_ok() || record.aux(b"Ml").is_ok())
+}
+
+/// Offset of Phred+33 base qualities, as BAM files normally store them.
+const PHRED_33: u8 = 33;
+
+/// Offset of Phred+64 base qualities, found in some legacy data.
+const PHRED_64: u8 = 64;
+
+/// Offset of the base quality encoding of the input, from the `qual_offset`
+/// option: Phred+33, the default, or Phred+64 for legacy data.
+///
+/// # Errors
+/// Returns an error if `qual_offset` is neither 33 nor 64.
+pub(crate) fn qual_offset(options: &ReadOptions) -> Result<u8> {
+    match options.qual_offset {
+        None | Some(PHRED_33) => Ok(PHRED_33),
+        Some(PHRED_64) => Ok(PHRED_64),
+        Some(v) => {
+            Err(ErrorKind::InvalidOptions.error(format!("qual_offset must be 33 or 64, not {v}")))
+        }
+    }
+}
+
+/// Base qualities of `record` as phred values, for qualities encoded with
+/// `offset` from [`qual_offset`]. Missing qualities stay 255.
+///
+/// A BAM file stores each quality less 33, so Phred+64 qualities are stored 31
+/// above their phred values.
+///
+/// # Errors
+/// Returns an error if a quality is below what `offset` allows, which means the
+/// qualities are not in that encoding.
+pub(crate) fn base_quals(record: &Record, offset: u8) -> Result<Vec<u8>> {
+    let shift = offset.saturating_sub(PHRED_33);
+    record
+        .qual()
+        .iter()
+        .map(|&v| {
+            if v == u8::MAX {
+                Ok(v)
+            } else {
+                v.checked_sub(shift).ok_or_else(|| {
+                    ErrorKind::InvalidOptions.error(format!(
+                        "qual_offset {offset}: read {} has a base quality below the offset",
+                        String::from_utf8_lossy(record.qname())
+                    ))
+                })
+            }
+        })
+        .collect()
+}
+
+/// Leaves out the calls below the threshold of their modification code in
+/// `selection`.
+///
+/// The core read offers no way to drop calls, so a read with calls to drop is
+/// rebuilt from its serialized form with those calls left out of its `mod_table`.
+///
+/// # Errors
+/// Returns an error if the read cannot be serialized or rebuilt.
+fn apply_mod_thresholds(
+    read: CurrRead<AlignAndModData>,
+    selection: ModSelection<'_>,
+) -> std::result::Result<CurrRead<AlignAndModData>, CoreError> {
+    let has_calls_below = read.mod_data().0.base_mods.iter().any(|v| {
+        selection
+            .threshold(ModChar::new(v.modification_type))
+            .is_some_and(|t| v.ranges.annotations.iter().any(|k| k.qual < t))
+    });
+    if !has_calls_below {
+        return Ok(read);
+    }
+    let mut value = serde_json::to_value(&read)?;
+    for_each_thresholded_entry(&mut value, selection, |threshold, entry| {
+        if let Some(calls) = entry
+            .get_mut("data")
+            .and_then(serde_json::Value::as_array_mut)
+        {
+            // Each call is a `[start, ref_start, qual]` tuple
+            calls.retain(|v| {
+                v.get(2)
+                    .and_then(serde_json::Value::as_u64)
+                    .is_some_and(|q| q >= u64::from(threshold))
+            });
+        }
+    });
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Serializes `read` as the core does, adding to each `mod_table` entry the
+/// `threshold` of its modification code if `selection` gives it its own.
+///
+/// # Errors
+/// Returns an error if the read cannot be serialized.
+pub(crate) fn read_json_value(
+    read: &CurrRead<AlignAndModData>,
+    selection: ModSelection<'_>,
+) -> std::result::Result<serde_json::Value, CoreError> {
+    let mut value = serde_json::to_value(read)?;
+    for_each_thresholded_entry(&mut value, selection, |threshold, entry| {
+        let _: Option<serde_json::Value> = entry.insert("threshold".to_owned(), threshold.into());
+    });
+    Ok(value)
+}
+
+/// Passes each `mod_table` entry of a serialized read whose modification code
+/// has its own threshold in `selection` to `f`, along with the threshold.
+fn for_each_thresholded_entry<F>(
+    value: &mut serde_json::Value,
+    selection: ModSelection<'_>,
+    mut f: F,
+) where
+    F: FnMut(u8, &mut serde_json::Map<String, serde_json::Value>),
+{
+    let entries = value
+        .get_mut("mod_table")
+        .and_then(serde_json::Value::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(serde_json::Value::as_object_mut);
+    for entry in entries {
+        let threshold = entry
+            .get("mod_code")
+            .and_then(serde_json::Value::as_str)
+            .and_then(|v| ModChar::from_str(v).ok())
+            .and_then(|v| selection.threshold(v));
+        if let Some(v) = threshold {
+            f(v, entry);
+        }
+    }
+}
+
+/// Sets the modification data of `read` as `set_mod_data_restricted_options`
+/// does, but keeping the calls of any of `tags` instead of the tag of `mods`.
+///
+/// # Errors
+/// Returns an error if the MM/ML tags of the record are invalid, or the read
+/// cannot be compared with the modification region of `mods`.
+fn set_mod_data_with_tags(
+    read: CurrRead<OnlyAlignDataComplete>,
+    record: &Record,
+    mods: &InputMods<OptionalTag>,
+    tags: &HashSet<ModChar>,
+) -> std::result::Result<CurrRead<AlignAndModData>, CoreError> {
+    let seq_len = usize::try_from(read.seq_len()?)?;
+    let trim = mods.trim_read_ends_mod();
+    // Part of the read on the reference that the modification region covers, if
+    // it does not cover all of it
+    let covered = match mods.region_filter().as_ref() {
+        Some(bed3) => {
+            let read_bed3 = StrandedBed3::<i32, u64>::try_from(&read)?;
+            match bed3.intersect(&read_bed3) {
+                Some(v) if v.start() == read_bed3.start() && v.end() == read_bed3.end() => None,
+                Some(v) => Some((v.start(), v.end())),
+                None => Some((0, 0)),
+            }
+        }
+        None => None,
+    };
+    let mod_strand = mods.mod_strand();
+    let mut out = read.set_mod_data_restricted(
+        record,
+        mods.mod_prob_filter(),
+        |x| trim == 0 || (trim..seq_len.saturating_sub(trim)).contains(x),
+        |_, &strand, tag| tags.contains(tag) && mod_strand.is_none_or(|v| strand == char::from(v)),
+        mods.base_qual_filter_mod(),
+    )?;
+    match covered {
+        Some((start, end)) if start < end => {
+            out.filter_by_ref_pos(i64::try_from(start)?, i64::try_from(end)?)?;
+        }
+        // No part of the read is covered, so every call is left out
+        Some(_) => out.filter_by_ref_pos(i64::MAX.saturating_sub(1), i64::MAX)?,
+        None => {}
+    }
+    Ok(out)
+}
+
+/// Raises the modification threshold to 0.5 as the core `read_info` command does,
+/// so that `mod_count` counts the bases more likely modified than not.
+pub(crate) fn read_info_mods(mut mods: InputMods<OptionalTag>) -> InputMods<OptionalTag> {
+    mods.mod_prob_filter = match mods.mod_prob_filter {
+        ThresholdState::GtEq(v) => ThresholdState::GtEq(v.max(128)),
+        ThresholdState::InvertGtEqLtEq(v) => ThresholdState::Both((128, v)),
+        ThresholdState::Both((v, w)) => ThresholdState::Both((v.max(128), w)),
+        v => v,
+    };
+    mods
+}
@@ -0,0 +1,360 @@
+//! Windows of `window_reads` along the reference instead of along the read.
+//!
+//! The core windowing slides windows over the calls of each read, so windows
+//! hold a fixed number of calls but cover different stretches of the genome.
+//! Here windows are instead fixed stretches of `win` reference bases, starting
+//! at multiples of `step` on the contig, so that the windows of different reads
+//! line up. A window holds the calls of a read whose reference positions fall
+//! in it, and windows without calls are left out.
+
+use crate::logging;
+use crate::pileup::MOD_THRESHOLD;
+use crate::records::{ModSelection, read_mod_data};
+use crate::{WinOp, WindowFormat, WindowOptions, window_ops};
+use nanalogue_core::{
+    Contains as _, Error, F32AbsValAtMost1, InputMods, InputWindowing, ModChar, OptionalTag,
+    analysis,
+};
+use rust_htslib::bam::Record;
+use std::fmt::{self, Display};
+use std::io::Write;
+use std::rc::Rc;
+
+/// Value of a reference window.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum WindowValue {
+    /// Value between -1 and 1, as the core window functions give it.
+    Fraction(F32AbsValAtMost1),
+    /// Total over the calls of the window, such as a number of calls.
+    Total(f64),
+}
+
+impl Display for WindowValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Fraction(v) => v.fmt(f),
+            Self::Total(v) => v.fmt(f),
+        }
+    }
+}
+
+impl From<WindowValue> for f64 {
+    fn from(value: WindowValue) -> Self {
+        match value {
+            WindowValue::Fraction(v) => Self::from(v.val()),
+            WindowValue::Total(v) => v,
+        }
+    }
+}
+
+/// One reference window of the calls of one modification of a read.
+#[derive(Debug)]
+pub(crate) struct RefWindow<'a> {
+    /// Index of the contig of the read in the BAM header.
+    tid: i32,
+    /// Contig of the read.
+    contig: &'a str,
+    /// First reference position (0-based) of the window.
+    ref_start: i64,
+    /// Reference position (0-based) after the window.
+    ref_end: i64,
+    /// Read id.
+    read_id: &'a str,
+    /// Value of the window operation over the calls of the window.
+    win_val: WindowValue,
+    /// Strand of the alignment of the read.
+    strand: char,
+    /// Base that is modified.
+    base: char,
+    /// Strand of the modification.
+    mod_strand: char,
+    /// Modification code.
+    mod_type: ModChar,
+    /// Number of calls with a probability of at least 0.5.
+    n_mod: usize,
+    /// Number of calls in the window.
+    n_valid: usize,
+    /// Whether the window is the first of its read and modification.
+    first: bool,
+}
+
+impl<'a> RefWindow<'a> {
+    /// Index of the contig of the read in the BAM header.
+    pub(crate) const fn tid(&self) -> i32 {
+        self.tid
+    }
+
+    /// Contig of the read.
+    pub(crate) const fn contig(&self) -> &'a str {
+        self.contig
+    }
+
+    /// Reference positions (0-based) of the start of the window and after it.
+    pub(crate) const fn ref_range(&self) -> (i64, i64) {
+        (self.ref_start, self.ref_end)
+    }
+
+    /// Value of the window operation over the calls of the window.
+    pub(crate) const fn win_val(&self) -> WindowValue {
+        self.win_val
+    }
+
+    /// Modification code.
+    pub(crate) const fn mod_type(&self) -> ModChar {
+        self.mod_type
+    }
+
+    /// Read id.
+    pub(crate) const fn read_id(&self) -> &'a str {
+        self.read_id
+    }
+
+    /// Whether the window is the first of its read and modification, so that
+    /// the windows of each read and modification can be told apart.
+    pub(crate) const fn is_first(&self) -> bool {
+        self.first
+    }
+
+    /// Writes the window as a TSV row.
+    fn write_tsv<W: Write>(&self, handle: &mut W) -> std::io::Result<()> {
+        writeln!(
+            handle,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.contig,
+            self.ref_start,
+            self.ref_end,
+            self.read_id,
+            self.win_val,
+            self.strand,
+            self.base,
+            self.mod_strand,
+            self.mod_type,
+            self.n_mod,
+            self.n_valid,
+        )
+    }
+
+    /// Writes the window as a JSON object with the fields of the TSV columns.
+    fn write_json<W: Write>(&self, handle: &mut W) -> std::io::Result<()> {
+        write!(
+            handle,
+            "{{\"contig\":{},\"ref_start\":{},\"ref_end\":{},\"read_id\":{},\"win_val\":{},\
+             \"strand\":\"{}\",\"base\":\"{}\",\"mod_strand\":\"{}\",\"mod_type\":\"{}\",\
+             \"n_mod\":{},\"n_valid\":{}}}",
+            serde_json::to_string(self.contig)?,
+            self.ref_start,
+            self.ref_end,
+            serde_json::to_string(self.read_id)?,
+            self.win_val,
+            self.strand,
+            self.base,
+            self.mod_strand,
+            self.mod_type,
+            self.n_mod,
+            self.n_valid,
+        )
+    }
+}
+
+/// Windows `records` along the reference and writes one row or JSON object per
+/// window to `handle`, in the format and with the window operation of `options`.
+///
+/// # Errors
+/// Returns an error if a record cannot be read, its modification data is
+/// invalid, or writing fails.
+pub(crate) fn write_ref_windows<W, D>(
+    handle: &mut W,
+    records: D,
+    window_options: &InputWindowing,
+    mods: &InputMods<OptionalTag>,
+    selection: ModSelection<'_>,
+    options: &WindowOptions,
+) -> Result<(), Error>
+where
+    W: Write,
+    D: IntoIterator<Item = Result<Rc<Record>, rust_htslib::errors::Error>>,
+{
+    let format = options.output_format.unwrap_or_default();
+    match format {
+        WindowFormat::Json => write!(handle, "[")?,
+        WindowFormat::Tsv => writeln!(
+            handle,
+            "#contig\tref_start\tref_end\tread_id\twin_val\tstrand\t\
+             base\tmod_strand\tmod_type\tn_mod\tn_valid"
+        )?,
+    }
+    let mut is_first = true;
+    visit_ref_windows(
+        records,
+        window_options,
+        mods,
+        selection,
+        options,
+        |window| {
+            match format {
+                WindowFormat::Json => {
+                    write!(handle, "{}", if is_first { "\n" } else { ",\n" })?;
+                    window.write_json(handle)?;
+                }
+                WindowFormat::Tsv => window.write_tsv(handle)?,
+            }
+            is_first = false;
+            Ok(())
+        },
+    )?;
+    if let WindowFormat::Json = format {
+        writeln!(handle, "\n]")?;
+    }
+    Ok(())
+}
+
+/// Windows `records` along the reference with the window operation of
+/// `options` and passes each window to `visit`, read by read.
+///
+/// Records without an alignment cannot be windowed along the reference, so they
+/// are left out with a warning, see [`logging::warn`].
+///
+/// # Errors
+/// Returns an error if a record cannot be read, its modification data is
+/// invalid, or `visit` fails.
+pub(crate) fn visit_ref_windows<D, F>(
+    records: D,
+    window_options: &InputWindowing,
+    mods: &InputMods<OptionalTag>,
+    selection: ModSelection<'_>,
+    options: &WindowOptions,
+    mut visit: F,
+) -> Result<(), Error>
+where
+    D: IntoIterator<Item = Result<Rc<Record>, rust_htslib::errors::Error>>,
+    F: FnMut(&RefWindow<'_>) -> Result<(), Error>,
+{
+    let win = window_options.win.get();
+    let step = window_options.step.get();
+    let win_op = options.win_op.unwrap_or_default();
+    let min_calls = options.min_calls();
+
+    let mut unaligned = 0usize;
+    for entry in records {
+        let record = entry?;
+        if record.is_unmapped() {
+            unaligned = unaligned.saturating_add(1);
+            continue;
+        }
+        let read = read_mod_data(&record, mods, selection)?;
+        let contig = read.contig_name()?;
+        for base_mod in &read.mod_data().0.base_mods {
+            let mut calls: Vec<(i64, u8)> = base_mod
+                .ranges
+                .annotations
+                .iter()
+                .filter_map(|k| k.reference_start.map(|v| (v, k.qual)))
+                .collect();
+            calls.sort_unstable_by_key(|&(pos, _)| pos);
+            let mut first = true;
+            for (ref_start, ref_end, quals) in windows_of(&calls, win, step) {
+                if quals.len() < min_calls {
+                    continue;
+                }
+                // Windows the operation cannot value, such as a gradient of a
+                // single call, are left out as the core windowing does
+                let Ok(win_val) = window_value(win_op, &quals) else {
+                    continue;
+                };
+                visit(&RefWindow {
+                    tid: record.tid(),
+                    contig,
+                    ref_start,
+                    ref_end,
+                    read_id: read.read_id(),
+                    win_val,
+                    strand: read.strand(),
+                    base: char::from(base_mod.modified_base),
+                    mod_strand: base_mod.strand,
+                    mod_type: ModChar::new(base_mod.modification_type),
+                    n_mod: quals.iter().filter(|v| MOD_THRESHOLD.contains(v)).count(),
+                    n_valid: quals.len(),
+                    first,
+                })?;
+                first = false;
+            }
+        }
+    }
+    warn_unaligned(unaligned);
+    Ok(())
+}
+
+/// Warns that `unaligned` reads without an alignment were left out, if any.
+pub(crate) fn warn_unaligned(unaligned: usize) {
+    if unaligned > 0 {
+        logging::warn(&format!(
+            "left out {unaligned} read(s) without an alignment, \
+             which cannot be windowed in reference coordinates"
+        ));
+    }
+}
+
+/// Windows of `win` reference bases starting at multiples of `step` that hold
+/// any of `calls`, as `(ref_start, ref_end, quals)` in order along the contig.
+///
+/// `calls` are `(reference position, ML value)` pairs sorted by position.
+fn windows_of(calls: &[(i64, u8)], win: usize, step: usize) -> Vec<(i64, i64, Vec<u8>)> {
+    let (Some(&(first, _)), Some(&(last, _))) = (calls.first(), calls.last()) else {
+        return Vec::new();
+    };
+    let (Ok(win_len), Ok(step_len)) = (i64::try_from(win), i64::try_from(step)) else {
+        return Vec::new();
+    };
+    // First window that reaches the first call
+    let lowest = first.saturating_sub(win_len).saturating_add(1).max(0);
+    let mut start = lowest
+        .saturating_add(step_len)
+        .saturating_sub(1)
+        .checked_div(step_len)
+        .unwrap_or(0)
+        .saturating_mul(step_len);
+    let mut windows = Vec::new();
+    while start <= last {
+        let end = start.saturating_add(win_len);
+        let lo = calls.partition_point(|&(pos, _)| pos < start);
+        let hi = calls.partition_point(|&(pos, _)| pos < end);
+        let quals: Vec<u8> = calls
+            .get(lo..hi)
+            .unwrap_or_default()
+            .iter()
+            .map(|&(_, qual)| qual)
+            .collect();
+        if !quals.is_empty() {
+            windows.push((start, end, quals));
+        }
+        start = start.saturating_add(step_len);
+    }
+    windows
+}
+
+/// Value of `win_op` over the ML values of the calls of a window.
+///
+/// # Errors
+/// Returns an error if the window operation cannot value the window.
+pub(crate) fn window_value(win_op: WinOp, quals: &[u8]) -> Result<WindowValue, Error> {
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "numbers of calls and sums of ML values stay far below 2^52"
+    )]
+    let total = |v: usize| v as f64;
+    match win_op {
+        WinOp::Density => analysis::threshold_and_mean(quals)
+            .map(Into::into)
+            .map(WindowValue::Fraction),
+        WinOp::GradDensity => analysis::threshold_and_gradient(quals).map(WindowValue::Fraction),
+        WinOp::Max => window_ops::max_prob(quals).map(WindowValue::Fraction),
+        WinOp::RawMean => window_ops::mean_ml(quals).map(WindowValue::Fraction),
+        WinOp::Sum => {
+            let sum: usize = quals.iter().copied().map(usize::from).sum();
+            Ok(WindowValue::Total(total(sum) / f64::from(u8::MAX)))
+        }
+        WinOp::Count => Ok(WindowValue::Total(total(
+            quals.iter().filter(|v| MOD_THRESHOLD.contains(v)).count(),
+        ))),
+    }
+}
@@ -0,0 +1,366 @@
+//! Queries over several genomic regions of one indexed BAM file, and parsing of
+//! region strings.
+
+use crate::ReadOptions;
+use crate::error::ErrorKind;
+use bedrs::{Bed3, Coordinates as _};
+use nanalogue_core::{BamPreFilt as _, CurrRead, Error as CoreError, GenomicRegion};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::{FetchDefinition, HeaderView, IndexedReader, Read as _, Record};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fs;
+use std::rc::Rc;
+use std::str::FromStr as _;
+use std::vec;
+
+/// Bounds of a region string, as [`parse_region`] gives them.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct ParsedRegion {
+    /// Contig name.
+    pub contig: String,
+    /// First position (0-based) of the region, unset for a whole contig.
+    pub start: Option<i64>,
+    /// Position (0-based) after the region, unset for a whole contig or a region
+    /// that runs to the end of the contig.
+    pub end: Option<i64>,
+}
+
+/// Parses a region string of the form `contig[:start-[end]]` as the `region`
+/// option does, without opening any file.
+///
+/// Coordinates are 0-based and half-open, and must be plain integers with
+/// `start < end`, unless `one_based` is set: they are then 1-based and
+/// inclusive, with `start <= end`, and are returned converted to 0-based,
+/// half-open bounds. As no BAM header is read, the contig is not checked
+/// against the contigs of any file.
+///
+/// # Errors
+/// Returns an error if the region is empty or cannot be parsed, or a coordinate
+/// is too large.
+#[napi]
+pub fn parse_region(region: String, one_based: Option<bool>) -> Result<ParsedRegion> {
+    let parsed = GenomicRegion::from_str(&core_region(&region, one_based)?)
+        .map_err(|e| ErrorKind::InvalidRegion.error(format!("Invalid region '{region}': {e}")))?;
+    if parsed.contig().is_empty() {
+        return Err(ErrorKind::InvalidRegion.error(format!(
+            "Invalid region '{region}': the contig name is empty"
+        )));
+    }
+    let position = |v: u64| {
+        i64::try_from(v).map_err(|e| {
+            ErrorKind::InvalidRegion.error(format!("Invalid region '{region}': {v}: {e}"))
+        })
+    };
+    let (start, end) = match parsed.start_end() {
+        None => (None, None),
+        Some((start, u64::MAX)) => (Some(position(start)?), None),
+        Some((start, end)) => (Some(position(start)?), Some(position(end)?)),
+    };
+    // The contig name is the region string up to its last colon, if any
+    let mut contig = region;
+    contig.truncate(parsed.contig().len());
+    Ok(ParsedRegion { contig, start, end })
+}
+
+/// The region string `region` in the 0-based, half-open coordinates that the
+/// core reads, converted from 1-based, inclusive coordinates if `one_based` is
+/// set. A region of a whole contig is the same in both.
+///
+/// # Errors
+/// Returns an error if `one_based` is set and the coordinates of `region` are
+/// not integers with `1 <= start <= end`.
+pub(crate) fn core_region(region: &str, one_based: Option<bool>) -> Result<Cow<'_, str>> {
+    let (true, Some((contig, interval))) = (one_based == Some(true), region.rsplit_once(':'))
+    else {
+        return Ok(Cow::Borrowed(region));
+    };
+    let invalid = |reason: String| {
+        ErrorKind::InvalidRegion.error(format!("Invalid 1-based region '{region}': {reason}"))
+    };
+    let (raw_start, raw_end) = interval
+        .split_once('-')
+        .ok_or_else(|| invalid("expected 'start-end' or 'start-'".to_owned()))?;
+    let start: u64 = raw_start
+        .trim()
+        .parse()
+        .map_err(|e| invalid(format!("invalid start: {e}")))?;
+    let zero_start = start
+        .checked_sub(1)
+        .ok_or_else(|| invalid("start must be at least 1".to_owned()))?;
+    if raw_end.trim().is_empty() {
+        return Ok(Cow::Owned(format!("{contig}:{zero_start}-")));
+    }
+    let end: u64 = raw_end
+        .trim()
+        .parse()
+        .map_err(|e| invalid(format!("invalid end: {e}")))?;
+    if end < start {
+        return Err(invalid("end must not be less than start".to_owned()));
+    }
+    Ok(Cow::Owned(format!("{contig}:{zero_start}-{end}")))
+}
+
+/// The `region` option as an interval of the contigs of `header`, if set.
+///
+/// # Errors
+/// Returns an error if the region cannot be parsed or its contig is not in
+/// `header`.
+pub(crate) fn region_bed3(
+    options: &ReadOptions,
+    header: &HeaderView,
+) -> Result<Option<Bed3<i32, u64>>> {
+    options
+        .region
+        .as_deref()
+        .map(|v| {
+            GenomicRegion::from_str(&core_region(v, options.one_based)?)
+                .map_err(|e| ErrorKind::InvalidRegion.error(format!("Invalid region: {e}")))?
+                .try_to_bed3(header)
+                .map_err(|e| {
+                    ErrorKind::InvalidRegion.error(format!("Failed to convert region to bed3: {e}"))
+                })
+        })
+        .transpose()
+}
+
+/// Positions on the read of `record` of its bases over `region`, in reference
+/// order and with inserted bases included, or `None` for a deletion, as
+/// `seq_table` shows them.
+///
+/// Returns `None` for an unmapped record or one without bases aligned in
+/// `region`. `query` names the query in error messages.
+///
+/// # Errors
+/// Returns an error if the alignment of the record is invalid.
+pub(crate) fn read_positions(
+    record: &Record,
+    region: &Bed3<i32, u64>,
+    query: &str,
+) -> Result<Option<Vec<Option<usize>>>> {
+    if record.is_unmapped() {
+        return Ok(None);
+    }
+    let failed = |e: CoreError| ErrorKind::of_core(&e).error(format!("{query} failed: {e}"));
+    let read = CurrRead::default()
+        .try_from_only_alignment(record)
+        .map_err(failed)?;
+    match read.seq_coords_from_ref_coords(record, region) {
+        Ok(v) => Ok(Some(
+            v.into_iter().map(|entry| entry.map(|(_, i)| i)).collect(),
+        )),
+        Err(CoreError::UnavailableData(_)) => Ok(None),
+        Err(e) => Err(failed(e)),
+    }
+}
+
+/// Regions requested through the `regions` or `region_bed` option.
+#[derive(Debug)]
+pub(crate) struct Regions {
+    /// Regions in the order they were given.
+    list: Vec<GenomicRegion>,
+    /// Only keep reads that pass through a region in full.
+    full_region: bool,
+}
+
+impl Regions {
+    /// Parses the `regions` or `region_bed` option, returning `None` if neither is set.
+    ///
+    /// # Errors
+    /// Returns an error if more than one way of giving regions is used, if there are
+    /// no regions, or if a region cannot be parsed.
+    pub(crate) fn from_options(options: &ReadOptions) -> Result<Option<Self>> {
+        let list = match (options.regions.as_deref(), options.region_bed.as_deref()) {
+            (None, None) => return Ok(None),
+            (Some(_), Some(_)) => {
+                return Err(ErrorKind::InvalidOptions
+                    .error("set only one of region, regions and regionBed"));
+            }
+            (Some(regions), None) => {
+                if options.region.is_some() {
+                    return Err(
+                        ErrorKind::InvalidOptions.error("set either region or regions, not both")
+                    );
+                }
+                if regions.is_empty() {
+                    return Err(
+                        ErrorKind::InvalidOptions.error("regions must contain at least one region")
+                    );
+                }
+                regions
+                    .iter()
+                    .map(|v| {
+                        GenomicRegion::from_str(&core_region(v, options.one_based)?).map_err(|e| {
+                            ErrorKind::InvalidRegion.error(format!("Invalid region '{v}': {e}"))
+                        })
+                    })
+                    .collect::<Result<_>>()?
+            }
+            (None, Some(path)) => {
+                if options.region.is_some() {
+                    return Err(ErrorKind::InvalidOptions
+                        .error("set only one of region, regions and regionBed"));
+                }
+                read_bed(path)?
+            }
+        };
+        Ok(Some(Self {
+            list,
+            full_region: options.full_region.unwrap_or(false),
+        }))
+    }
+
+    /// The regions as intervals of the contigs of `header`, in the order they
+    /// were given.
+    ///
+    /// # Errors
+    /// Returns an error if a region does not fit a contig in the header.
+    pub(crate) fn to_bed3(&self, header: &HeaderView) -> Result<Vec<Bed3<i32, u64>>> {
+        self.list
+            .iter()
+            .map(|v| {
+                if header.tid(v.contig().as_bytes()).is_none() {
+                    return Err(ErrorKind::InvalidRegion.error(format!(
+                        "Failed to convert region to bed3: contig '{}' is not in the BAM header",
+                        v.contig()
+                    )));
+                }
+                v.clone().try_to_bed3(header).map_err(|e| {
+                    ErrorKind::InvalidRegion.error(format!("Failed to convert region to bed3: {e}"))
+                })
+            })
+            .collect()
+    }
+}
+
+/// Reads the intervals of a BED file as regions.
+///
+/// Only the first three columns are used. BED intervals are 0-based and half-open,
+/// like the coordinates of a [`GenomicRegion`], so they carry over unchanged. Blank
+/// lines, comments and `track`/`browser` lines are skipped.
+fn read_bed(path: &str) -> Result<Vec<GenomicRegion>> {
+    let contents = fs::read_to_string(path).map_err(|e| {
+        ErrorKind::of_io(&e).error(format!("Failed to read BED file '{path}': {e}"))
+    })?;
+    let mut regions = Vec::new();
+    for (line_number, line) in (1usize..).zip(contents.lines()) {
+        let trimmed = line.trim();
+        if trimmed.is_empty()
+            || trimmed.starts_with('#')
+            || trimmed.starts_with("track")
+            || trimmed.starts_with("browser")
+        {
+            continue;
+        }
+        let invalid = |reason: &str| {
+            ErrorKind::InvalidRegion.error(format!(
+                "Invalid BED line {line_number} in '{path}': {reason}"
+            ))
+        };
+        let mut fields = trimmed.split_whitespace();
+        let (Some(contig), Some(raw_start), Some(raw_end)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(invalid("expected contig, start and end columns"));
+        };
+        let start: u64 = raw_start
+            .parse()
+            .map_err(|_err| invalid("start is not a non-negative integer"))?;
+        let end: u64 = raw_end
+            .parse()
+            .map_err(|_err| invalid("end is not a non-negative integer"))?;
+        if start >= end {
+            return Err(invalid("start must be less than end"));
+        }
+        regions.push(
+            GenomicRegion::from_str(&format!("{contig}:{start}-{end}"))
+                .map_err(|e| invalid(&e.to_string()))?,
+        );
+    }
+    if regions.is_empty() {
+        return Err(
+            ErrorKind::InvalidOptions.error(format!("BED file '{path}' contains no regions"))
+        );
+    }
+    Ok(regions)
+}
+
+/// Records from a sequence of region fetches on one reader.
+///
+/// Each region is fetched in turn and its records are filtered by overlap with that
+/// region, as `pre_filt` does for a single region. A record returned by an earlier
+/// region, because it spans both regions or the regions overlap, is not returned again.
+pub(crate) struct RegionRecords<'r> {
+    /// Reader the regions are fetched from.
+    reader: &'r mut IndexedReader,
+    /// Regions still to be fetched.
+    pending: vec::IntoIter<Bed3<i32, u64>>,
+    /// Region whose records are being read, if any.
+    current: Option<Bed3<i32, u64>>,
+    /// Only keep reads that pass through a region in full.
+    full_region: bool,
+    /// Records already returned, identified by read id, flags and position.
+    seen: HashSet<(Vec<u8>, u16, i32, i64)>,
+}
+
+impl<'r> RegionRecords<'r> {
+    /// Prepares to read `regions` from `reader`, resolving them against `header`.
+    ///
+    /// # Errors
+    /// Returns an error if a region does not fit a contig in the header.
+    pub(crate) fn new(
+        reader: &'r mut IndexedReader,
+        regions: &Regions,
+        header: &HeaderView,
+    ) -> Result<Self> {
+        Ok(Self {
+            reader,
+            pending: regions.to_bed3(header)?.into_iter(),
+            current: None,
+            full_region: regions.full_region,
+            seen: HashSet::new(),
+        })
+    }
+}
+
+impl Iterator for RegionRecords<'_> {
+    type Item = std::result::Result<Rc<Record>, rust_htslib::errors::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some(region) = self.current.as_ref() else {
+                let next = self.pending.next()?;
+                let fetch_def = FetchDefinition::Region(
+                    *next.chr(),
+                    i64::try_from(next.start()).unwrap_or(i64::MAX),
+                    i64::try_from(next.end()).unwrap_or(i64::MAX),
+                );
+                if let Err(e) = self.reader.fetch(fetch_def) {
+                    return Some(Err(e));
+                }
+                self.current = Some(next);
+                continue;
+            };
+            let mut record = Record::new();
+            match self.reader.read(&mut record) {
+                None => self.current = None,
+                Some(Err(e)) => return Some(Err(e)),
+                Some(Ok(())) => {
+                    if record.filt_by_region(region, self.full_region)
+                        && self.seen.insert((
+                            record.qname().to_vec(),
+                            record.flags(),
+                            record.tid(),
+                            record.pos(),
+                        ))
+                    {
+                        return Some(Ok(Rc::new(record)));
+                    }
+                }
+            }
+        }
+    }
+}
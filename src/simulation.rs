@@ -0,0 +1,263 @@
+//! Typed simulation configuration, as an alternative to the JSON string of
+//! `simulate_mod_bam`.
+
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use crate::{SimulateStats, run_simulation};
+use nanalogue_core::simulate_mod_bam::{ContigConfig, ModConfig, ReadConfig};
+use nanalogue_core::{
+    AllowedAGCTN, DNARestrictive, Error as CoreError, F32Bw0and1, ModChar, OrdPair,
+    SimulationConfig,
+};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use std::fmt::Display;
+use std::num::{NonZeroU32, NonZeroU64};
+use std::str::FromStr as _;
+
+/// Simulation configuration, with the fields of the JSON configuration of
+/// `simulate_mod_bam` in camel case.
+#[napi(object, object_to_js = false, js_name = "SimulationConfig")]
+#[non_exhaustive]
+#[derive(Debug, Default)]
+pub struct TypedSimulationConfig {
+    /// Contigs to generate.
+    pub contigs: TypedContigConfig,
+    /// Groups of reads to generate, each with its own settings.
+    pub reads: Vec<TypedReadConfig>,
+    /// Seed that makes the simulation reproducible. Must be non-negative if set.
+    pub seed: Option<i64>,
+}
+
+/// Contigs of a [`TypedSimulationConfig`].
+#[napi(object, object_to_js = false, js_name = "ContigConfig")]
+#[non_exhaustive]
+#[derive(Debug, Default)]
+pub struct TypedContigConfig {
+    /// Number of contigs.
+    pub number: u32,
+    /// Range of contig lengths in bp, as `[min, max]`.
+    pub len_range: Vec<i64>,
+    /// Sequence repeated along each contig instead of a random one.
+    pub repeated_seq: Option<String>,
+}
+
+/// One group of reads of a [`TypedSimulationConfig`].
+#[napi(object, object_to_js = false, js_name = "ReadConfig")]
+#[non_exhaustive]
+#[derive(Debug, Default)]
+pub struct TypedReadConfig {
+    /// Number of reads.
+    pub number: u32,
+    /// Range of read lengths as fractions of the contig length, as `[min, max]`.
+    pub len_range: Vec<f64>,
+    /// Range of mapping qualities, as `[min, max]`. Defaults to `[0, 0]`.
+    pub mapq_range: Option<Vec<u8>>,
+    /// Range of base qualities, as `[min, max]`. Defaults to `[0, 0]`.
+    pub base_qual_range: Option<Vec<u8>>,
+    /// Barcode added to both ends of each read.
+    pub barcode: Option<String>,
+    /// Part of each read to delete, as fractions `[start, end]` of its length.
+    pub delete: Option<Vec<f64>>,
+    /// Sequence inserted in the middle of each read.
+    pub insert_middle: Option<String>,
+    /// Fraction of bases changed to another base, between 0 and 1.
+    pub mismatch: Option<f64>,
+    /// Modifications of the reads.
+    pub mods: Option<Vec<TypedModConfig>>,
+}
+
+/// One modification of a [`TypedReadConfig`].
+#[napi(object, object_to_js = false, js_name = "ModConfig")]
+#[non_exhaustive]
+#[derive(Debug, Default)]
+pub struct TypedModConfig {
+    /// Base that is modified (A, C, G, T or N).
+    pub base: String,
+    /// Whether the modification is on the plus strand.
+    pub is_strand_plus: bool,
+    /// Modification code (e.g. `m`, or a number such as `76792`).
+    pub mod_code: String,
+    /// Number of bases of interest in each window, repeated along the read.
+    pub win: Vec<u32>,
+    /// Range of modification probabilities of each window, as `[low, high]`.
+    pub mod_range: Vec<Vec<f64>>,
+}
+
+/// Simulates a BAM file with modifications from a typed configuration.
+///
+/// Does the same as [`crate::simulate_mod_bam`], whose JSON configuration has
+/// the same fields in snake case, without writing or parsing JSON. Existing files
+/// at `bam_path` or `fasta_path` are only replaced if `overwrite` is set.
+///
+/// # Errors
+/// Returns an error naming the offending field if the configuration is not
+/// valid, or if file I/O operations fail.
+#[napi]
+pub async fn simulate_mod_bam_typed(
+    config: TypedSimulationConfig,
+    bam_path: String,
+    fasta_path: String,
+    overwrite: Option<bool>,
+) -> Result<SimulateStats> {
+    run_limited(move || {
+        run_simulation(
+            SimulationConfig::try_from(&config)?,
+            &bam_path,
+            &fasta_path,
+            overwrite == Some(true),
+        )
+    })
+    .await
+}
+
+impl TryFrom<&TypedSimulationConfig> for SimulationConfig {
+    type Error = Error;
+
+    fn try_from(config: &TypedSimulationConfig) -> Result<Self> {
+        let mut out = Self::default();
+        out.contigs = build_contigs(&config.contigs)?;
+        out.reads = config
+            .reads
+            .iter()
+            .enumerate()
+            .map(|(i, v)| build_reads(v, &format!("reads[{i}]")))
+            .collect::<Result<_>>()?;
+        out.seed = config
+            .seed
+            .map(|v| checked("seed", u64::try_from(v)))
+            .transpose()?;
+        Ok(out)
+    }
+}
+
+/// Builds the contigs of the configuration.
+fn build_contigs(config: &TypedContigConfig) -> Result<ContigConfig> {
+    let (min, max) = pair(&config.len_range, "contigs.lenRange")?;
+    let mut out = ContigConfig::default();
+    out.number = checked("contigs.number", NonZeroU32::try_from(config.number))?;
+    out.len_range = checked(
+        "contigs.lenRange",
+        u64::try_from(min)
+            .and_then(|v| Ok((v, u64::try_from(max)?)))
+            .map_err(CoreError::from)
+            .and_then(OrdPair::<NonZeroU64>::try_from),
+    )?;
+    out.repeated_seq = sequence("contigs.repeatedSeq", config.repeated_seq.as_deref())?;
+    Ok(out)
+}
+
+/// Builds the group of reads at `path` of the configuration.
+fn build_reads(config: &TypedReadConfig, path: &str) -> Result<ReadConfig> {
+    let mut out = ReadConfig::default();
+    out.number = checked(
+        &format!("{path}.number"),
+        NonZeroU32::try_from(config.number),
+    )?;
+    out.len_range = fraction_pair(&config.len_range, &format!("{path}.lenRange"))?;
+    if let Some(v) = config.mapq_range.as_deref() {
+        let field = format!("{path}.mapqRange");
+        out.mapq_range = checked(&field, OrdPair::try_from(pair(v, &field)?))?;
+    }
+    if let Some(v) = config.base_qual_range.as_deref() {
+        let field = format!("{path}.baseQualRange");
+        out.base_qual_range = checked(&field, OrdPair::try_from(pair(v, &field)?))?;
+    }
+    out.barcode = sequence(&format!("{path}.barcode"), config.barcode.as_deref())?;
+    out.delete = config
+        .delete
+        .as_deref()
+        .map(|v| fraction_pair(v, &format!("{path}.delete")))
+        .transpose()?;
+    out.insert_middle = sequence(
+        &format!("{path}.insertMiddle"),
+        config.insert_middle.as_deref(),
+    )?;
+    out.mismatch = config
+        .mismatch
+        .map(|v| fraction(v, &format!("{path}.mismatch")))
+        .transpose()?;
+    out.mods = config
+        .mods
+        .iter()
+        .flatten()
+        .enumerate()
+        .map(|(i, v)| build_mod(v, &format!("{path}.mods[{i}]")))
+        .collect::<Result<_>>()?;
+    Ok(out)
+}
+
+/// Builds the modification at `path` of the configuration.
+fn build_mod(config: &TypedModConfig, path: &str) -> Result<ModConfig> {
+    let field = format!("{path}.base");
+    let mut chars = config.base.chars();
+    let (Some(base), None) = (chars.next(), chars.next()) else {
+        return Err(invalid_field(&field, "must be a single character"));
+    };
+    let mut out = ModConfig::default();
+    out.base = checked(&field, AllowedAGCTN::try_from(base))?;
+    out.is_strand_plus = config.is_strand_plus;
+    out.mod_code = checked(
+        &format!("{path}.modCode"),
+        ModChar::from_str(&config.mod_code),
+    )?;
+    out.win = config
+        .win
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| checked(&format!("{path}.win[{i}]"), NonZeroU32::try_from(v)))
+        .collect::<Result<_>>()?;
+    out.mod_range = config
+        .mod_range
+        .iter()
+        .enumerate()
+        .map(|(i, v)| fraction_pair(v, &format!("{path}.modRange[{i}]")))
+        .collect::<Result<_>>()?;
+    Ok(out)
+}
+
+/// Error for an invalid `field` of the configuration.
+fn invalid_field<E: Display>(field: &str, e: E) -> Error {
+    ErrorKind::InvalidOptions.error(format!("Invalid simulation config: {field}: {e}"))
+}
+
+/// The value of `field`, or an error naming it.
+fn checked<T, E: Display>(field: &str, value: std::result::Result<T, E>) -> Result<T> {
+    value.map_err(|e| invalid_field(field, e))
+}
+
+/// A DNA sequence such as a barcode, if set.
+fn sequence(field: &str, value: Option<&str>) -> Result<Option<DNARestrictive>> {
+    value
+        .map(|v| checked(field, DNARestrictive::from_str(v)))
+        .transpose()
+}
+
+/// The two values of a `[low, high]` array.
+fn pair<T: Copy>(values: &[T], field: &str) -> Result<(T, T)> {
+    match *values {
+        [low, high] => Ok((low, high)),
+        _ => Err(invalid_field(
+            field,
+            "must be an array of exactly 2 numbers [low, high]",
+        )),
+    }
+}
+
+/// A `[low, high]` array of fractions between 0 and 1.
+fn fraction_pair(values: &[f64], field: &str) -> Result<OrdPair<F32Bw0and1>> {
+    let (low, high) = pair(values, field)?;
+    checked(
+        field,
+        OrdPair::new(fraction(low, field)?, fraction(high, field)?),
+    )
+}
+
+/// A fraction between 0 and 1.
+fn fraction(value: f64, field: &str) -> Result<F32Bw0and1> {
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "f64 to f32 truncation is acceptable for fractions"
+    )]
+    checked(field, F32Bw0and1::new(value as f32))
+}
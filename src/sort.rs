@@ -0,0 +1,48 @@
+//! Ordering of the reads listed by `read_info` and `bam_mods`.
+
+use crate::RecordResult;
+use napi_derive::napi;
+
+/// Order in which `read_info` and `bam_mods` list the reads that pass the filters.
+#[napi(string_enum = "snake_case")]
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum SortBy {
+    /// By contig in header order, then by the first aligned reference position.
+    /// Unmapped reads come last.
+    Position,
+    /// By sequence length, shortest first.
+    Length,
+    /// By read id, comparing its bytes.
+    ReadId,
+}
+
+/// Passes `records` through in file order, or, with `sort_by`, reads them all
+/// and yields them in that order.
+///
+/// Sorting holds every record in memory until the last one is read. Reads that
+/// compare equal keep their file order. If a record fails to read, only the
+/// error is yielded.
+pub(crate) fn sort_records<'a, I>(
+    records: I,
+    sort_by: Option<SortBy>,
+) -> Box<dyn Iterator<Item = RecordResult> + 'a>
+where
+    I: Iterator<Item = RecordResult> + 'a,
+{
+    let Some(order) = sort_by else {
+        return Box::new(records);
+    };
+    let mut buffered = match records.collect::<Result<Vec<_>, _>>() {
+        Ok(v) => v,
+        Err(e) => return Box::new(std::iter::once(Err(e))),
+    };
+    match order {
+        SortBy::Position => {
+            buffered.sort_by_key(|v| (v.is_unmapped() || v.tid() < 0, v.tid(), v.pos()));
+        }
+        SortBy::Length => buffered.sort_by_key(|v| v.seq_len()),
+        SortBy::ReadId => buffered.sort_by(|a, b| a.qname().cmp(b.qname())),
+    }
+    Box::new(buffered.into_iter().map(Ok))
+}
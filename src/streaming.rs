@@ -0,0 +1,201 @@
+//! Streaming read information one read at a time.
+
+use crate::concurrency::acquire_slot;
+use crate::error::ErrorKind;
+use crate::progress::{Progress, progress_interval};
+use crate::records::{ReadInfoRecord, read_info_mods};
+use crate::regions::Regions;
+use crate::{
+    ReadOptions, build_input_options, passes_read_filters, query_reader, validate_pagination,
+};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tokio::sync::{Mutex, OwnedSemaphorePermit, mpsc, watch};
+
+/// Number of reads the worker may prepare ahead of the JS consumer.
+const STREAM_BUFFER: usize = 16;
+
+/// One step of a [`ReadInfoStream`], shaped like a JS iterator result.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug, Default)]
+pub struct ReadInfoStreamResult {
+    /// True once every read has been yielded.
+    pub done: bool,
+    /// The next read, in the same format as an element of `read_info`'s array.
+    pub value: Option<ReadInfoRecord>,
+}
+
+/// Async iterator over the reads that `read_info` would return.
+///
+/// Reads are produced on a worker thread and handed over through a small bounded
+/// buffer, so the worker pauses whenever the consumer stops pulling. The worker
+/// stops, releasing the BAM file and its reader slot, as soon as the stream is
+/// closed or dropped, even while it waits for the consumer or for a slot. A
+/// stream that is left unfinished without being closed is dropped only when it
+/// is garbage-collected.
+#[napi]
+#[derive(Debug)]
+pub struct ReadInfoStream {
+    /// Receiving end of the worker's channel, closed when the stream ends.
+    receiver: Arc<Mutex<mpsc::Receiver<Result<ReadInfoRecord>>>>,
+    /// Set to true when the stream is closed. The worker watching it also stops
+    /// once it is dropped with the stream.
+    stop: watch::Sender<bool>,
+}
+
+#[napi]
+impl ReadInfoStream {
+    /// Returns the next read, or `done: true` once the stream is exhausted.
+    ///
+    /// # Errors
+    /// Returns an error if reading or converting a record fails. The stream ends
+    /// after an error.
+    #[napi]
+    pub async fn next(&self) -> Result<ReadInfoStreamResult> {
+        let mut receiver = self.receiver.lock().await;
+        match receiver.recv().await {
+            Some(Ok(value)) => Ok(ReadInfoStreamResult {
+                done: false,
+                value: Some(value),
+            }),
+            Some(Err(e)) => {
+                receiver.close();
+                Err(e)
+            }
+            None => Ok(ReadInfoStreamResult::finished()),
+        }
+    }
+
+    /// Stops the stream early, letting the worker release the BAM file.
+    ///
+    /// Called by `for await` loops that exit before the stream is exhausted.
+    #[napi(js_name = "return")]
+    pub async fn close(&self) -> ReadInfoStreamResult {
+        // Told first, as a pending `next()` may hold the receiver until the worker sends
+        let _: bool = self.stop.send_replace(true);
+        self.receiver.lock().await.close();
+        ReadInfoStreamResult::finished()
+    }
+}
+
+impl ReadInfoStreamResult {
+    /// The result signalling the end of the stream.
+    fn finished() -> Self {
+        Self {
+            done: true,
+            value: None,
+        }
+    }
+}
+
+/// Streams read information as JSON objects, one read at a time.
+///
+/// Takes the same options as `read_info`, `no_prefilter` included, and yields the
+/// same objects, but never holds more than a few reads in memory, so `sort_by` is
+/// not supported. Errors in the options or the BAM file are raised by the first
+/// call to `next()`.
+#[napi]
+#[must_use]
+pub fn read_info_stream(options: ReadOptions) -> ReadInfoStream {
+    let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+    let (stop, mut stopped) = watch::channel(false);
+    // The worker is detached; it ends on its own once the records run out or the
+    // stream is closed or dropped.
+    drop(spawn_blocking(move || {
+        // The slot is held until the stream ends, as the file stays open till then
+        let result = match wait_for_slot(&mut stopped) {
+            Ok(Some(_slot)) => stream_read_info(&options, &sender, &mut stopped),
+            Ok(None) => return,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            // Nobody is left to report to if the stream was closed meanwhile
+            let _: bool = send(&sender, &mut stopped, Err(e));
+        }
+    }));
+    ReadInfoStream {
+        receiver: Arc::new(Mutex::new(receiver)),
+        stop,
+    }
+}
+
+/// Whether the [`ReadInfoStream`] watched by `stopped` has been closed or dropped.
+async fn closed(stopped: &mut watch::Receiver<bool>) {
+    // An error means that the stream, and with it the sender, has been dropped
+    drop(stopped.wait_for(|v| *v).await);
+}
+
+/// Waits for a reader slot, returning `None` instead if the stream is closed or
+/// dropped first.
+///
+/// # Errors
+/// Returns an error if the semaphore cannot be reached.
+fn wait_for_slot(stopped: &mut watch::Receiver<bool>) -> Result<Option<OwnedSemaphorePermit>> {
+    Handle::current().block_on(async {
+        tokio::select! {
+            slot = acquire_slot() => slot.map(Some),
+            () = closed(stopped) => Ok(None),
+        }
+    })
+}
+
+/// Sends `value` to the stream once its buffer has room, returning false instead
+/// if the stream is closed or dropped first.
+fn send(
+    sender: &mpsc::Sender<Result<ReadInfoRecord>>,
+    stopped: &mut watch::Receiver<bool>,
+    value: Result<ReadInfoRecord>,
+) -> bool {
+    Handle::current().block_on(async {
+        tokio::select! {
+            permit = sender.reserve() => permit.map(|v| v.send(value)).is_ok(),
+            () = closed(stopped) => false,
+        }
+    })
+}
+
+/// Sends the read information of each selected record to `sender`.
+///
+/// Stops quietly if the receiving [`ReadInfoStream`] has been closed or dropped.
+fn stream_read_info(
+    options: &ReadOptions,
+    sender: &mpsc::Sender<Result<ReadInfoRecord>>,
+    stopped: &mut watch::Receiver<bool>,
+) -> Result<()> {
+    if options.sort_by.is_some() {
+        return Err(ErrorKind::InvalidOptions.error(
+            "sortBy reads every read into memory before the first is returned; it is not supported by readInfoStream",
+        ));
+    }
+    let (offset, limit) = validate_pagination(options)?;
+    let interval = progress_interval(options.progress_interval)?;
+    let (mut bam, mut raw_mods, extra) = build_input_options(options)?;
+    let regions = Regions::from_options(options)?;
+
+    let mut reader = query_reader(&bam, options, None)?;
+    let (rc_records, _) = reader.records(&mut bam, &mut raw_mods, regions.as_ref())?;
+    let mods = read_info_mods(raw_mods);
+    let no_prefilter = options.no_prefilter == Some(true);
+
+    let filtered = Progress::new(rc_records, options.on_progress.clone(), interval).filter(|r| {
+        no_prefilter
+            || r.as_ref()
+                .map_or(true, |v| passes_read_filters(v, &bam, &mods, &extra))
+    });
+    for entry in filtered.skip(offset).take(limit) {
+        let record = entry.map_err(|e| {
+            ErrorKind::of_htslib(&e).error(format!("Failed to read BAM record: {e}"))
+        })?;
+        let mut value = ReadInfoRecord::from_record(&record, &mods, extra.mod_selection())?;
+        if no_prefilter {
+            value.passed_prefilter = Some(passes_read_filters(&record, &bam, &mods, &extra));
+        }
+        if !send(sender, stopped, Ok(value)) {
+            break;
+        }
+    }
+    Ok(())
+}
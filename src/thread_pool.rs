@@ -0,0 +1,89 @@
+//! Thread pool of htslib shared by the readers of all queries.
+//!
+//! Each query otherwise starts htslib threads of its own to decompress the BAM
+//! data and stops them again when it ends. After [`init_thread_pool`], readers
+//! opened without a `threads` option use one pool that outlives the queries.
+
+use crate::error::ErrorKind;
+use crate::prepare_regions;
+use nanalogue_core::{BamRcRecords, InputBam, InputMods, OptionalTag};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::{self, HeaderView};
+use rust_htslib::tpool::ThreadPool;
+use std::sync::{Mutex, PoisonError};
+
+/// The shared pool, once [`init_thread_pool`] has been called.
+static POOL: Mutex<Option<SharedPool>> = Mutex::new(None);
+
+/// Thread pool of htslib that can be kept in a static.
+#[derive(Debug)]
+struct SharedPool(ThreadPool);
+
+// SAFETY: htslib thread pools are made to be used by readers on several threads
+// at once. The Rust wrapper counts its references atomically and only borrows
+// its `RefCell` when the pool is given to a reader, which is done with the lock
+// of `POOL` held.
+unsafe impl Send for SharedPool {}
+
+/// Starts a pool of `threads` htslib threads that the readers of later queries
+/// share to decompress BAM data, in place of starting and stopping threads of
+/// their own for each query.
+///
+/// Readers given the `threads` option keep a pool of their own of that size.
+/// Calling this again replaces the pool; readers that are already open keep the
+/// old one until they are closed.
+///
+/// # Errors
+/// Returns an error if `threads` is 0 or htslib cannot start the threads.
+#[napi]
+pub fn init_thread_pool(threads: u8) -> Result<()> {
+    if threads == 0 {
+        return Err(ErrorKind::InvalidOptions.error("threads must be a positive integer"));
+    }
+    let pool = ThreadPool::new(u32::from(threads))
+        .map_err(|e| ErrorKind::Internal.error(format!("Failed to start thread pool: {e}")))?;
+    *POOL.lock().unwrap_or_else(PoisonError::into_inner) = Some(SharedPool(pool));
+    Ok(())
+}
+
+/// Gives `reader` the shared pool and returns true, or returns false if
+/// [`init_thread_pool`] has not been called.
+///
+/// # Errors
+/// Returns an error if htslib cannot give the pool to the reader.
+pub(crate) fn attach<R: bam::Read>(reader: &mut R) -> Result<bool> {
+    let guard = POOL.lock().unwrap_or_else(PoisonError::into_inner);
+    let Some(pool) = guard.as_ref() else {
+        return Ok(false);
+    };
+    reader
+        .set_thread_pool(&pool.0)
+        .map_err(|e| ErrorKind::Internal.error(format!("Failed to set thread pool: {e}")))?;
+    Ok(true)
+}
+
+/// Sets up `reader`, which has no thread pool yet, as [`BamRcRecords::new`]
+/// does and returns its header.
+///
+/// The reader gets the shared pool if `use_shared` is set and the pool is
+/// started, or a new pool of the `threads` of `bam` otherwise.
+///
+/// # Errors
+/// Returns an error if the pool cannot be set or the regions of `bam` and `mods`
+/// are not in the header.
+pub(crate) fn prepare<R: bam::Read>(
+    reader: &mut R,
+    bam: &mut InputBam,
+    mods: &mut InputMods<OptionalTag>,
+    use_shared: bool,
+) -> Result<HeaderView> {
+    if use_shared && attach(reader)? {
+        let header = reader.header().clone();
+        prepare_regions(&header, bam, mods)?;
+        return Ok(header);
+    }
+    BamRcRecords::new(reader, bam, mods)
+        .map(|v| v.header)
+        .map_err(|e| ErrorKind::of_core(&e).error(format!("Failed to read BAM records: {e}")))
+}
@@ -0,0 +1,161 @@
+//! Request headers for URL reads, passed to htslib through a URL scheme of our own.
+//!
+//! htslib takes extra request headers only as arguments to `hopen`, which
+//! `rust_htslib` never passes. A URL with headers is therefore opened as
+//! `nnlg-hdr:<id>:<url>`; htslib hands such names, including that of the index,
+//! to [`open_with_headers`], which opens `<url>` with the headers registered
+//! under `<id>`.
+
+use rust_htslib::htslib::hFILE;
+use std::collections::BTreeMap;
+use std::ffi::{CStr, CString, c_char, c_int};
+use std::ptr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, Once};
+use url::Url;
+
+/// Scheme of the URLs handled by [`open_with_headers`].
+const SCHEME: &str = "nnlg-hdr";
+
+/// Headers of the URLs being opened, by the id in their name.
+static HEADERS: Mutex<BTreeMap<u64, Vec<CString>>> = Mutex::new(BTreeMap::new());
+
+/// Id given to the next URL with headers.
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Registers [`HANDLER`] with htslib once.
+static REGISTER: Once = Once::new();
+
+/// htslib's `struct hFILE_scheme_handler`, up to the fields it reads for a
+/// priority below 2000.
+#[repr(C)]
+#[derive(Debug)]
+struct SchemeHandler {
+    /// Opens a file of the scheme.
+    open: unsafe extern "C" fn(*const c_char, *const c_char) -> *mut hFILE,
+    /// Whether a file of the scheme is remote.
+    isremote: unsafe extern "C" fn(*const c_char) -> c_int,
+    /// Name of the code providing the handler.
+    provider: *const c_char,
+    /// Priority among the handlers of the scheme.
+    priority: c_int,
+}
+
+// SAFETY: the only pointer is to a static string that is never written.
+unsafe impl Sync for SchemeHandler {}
+
+/// Handler of the `nnlg-hdr` scheme.
+static HANDLER: SchemeHandler = SchemeHandler {
+    open: open_with_headers,
+    isremote: always_remote,
+    provider: c"nanalogue-node".as_ptr(),
+    priority: 50,
+};
+
+unsafe extern "C" {
+    /// Opens a file, taking extra arguments such as request headers.
+    fn hopen(filename: *const c_char, mode: *const c_char, ...) -> *mut hFILE;
+    /// Whether a file is remote; loads the scheme handlers on first use.
+    fn hisremote(filename: *const c_char) -> c_int;
+    /// Adds a handler for the URLs of `scheme`.
+    fn hfile_add_scheme_handler(scheme: *const c_char, handler: *const SchemeHandler);
+}
+
+/// A URL whose headers are registered for as long as the value lives.
+#[derive(Debug)]
+pub(crate) struct HeaderedUrl {
+    /// Id of the headers.
+    id: u64,
+    /// The URL in the `nnlg-hdr` scheme, to be opened instead of the original.
+    url: Url,
+}
+
+impl HeaderedUrl {
+    /// Registers `headers`, each a `Name: value` line, for opening `url`.
+    ///
+    /// # Errors
+    /// Returns the URL parse error if the wrapped URL is invalid.
+    pub(crate) fn new(url: &Url, headers: Vec<CString>) -> Result<Self, url::ParseError> {
+        REGISTER.call_once(|| {
+            // SAFETY: the strings are NUL-terminated, and `HANDLER` is static as
+            // htslib requires. Asking about a URL first makes htslib load its own
+            // handlers, which it must do before handlers can be added.
+            unsafe {
+                let _: c_int = hisremote(c"http://".as_ptr());
+                hfile_add_scheme_handler(c"nnlg-hdr".as_ptr(), &raw const HANDLER);
+            }
+        });
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let wrapped = Url::parse(&format!("{SCHEME}:{id}:{url}"))?;
+        let _: Option<Vec<CString>> = HEADERS
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(id, headers);
+        Ok(Self { id, url: wrapped })
+    }
+
+    /// The URL to open in place of the original.
+    pub(crate) const fn url(&self) -> &Url {
+        &self.url
+    }
+}
+
+impl Drop for HeaderedUrl {
+    fn drop(&mut self) {
+        let _: Option<Vec<CString>> = HEADERS
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(&self.id);
+    }
+}
+
+/// Opens `nnlg-hdr:<id>:<url>` as `<url>` with the headers registered under
+/// `<id>`. Returns null if the name is malformed, its headers are gone, or the
+/// open fails.
+unsafe extern "C" fn open_with_headers(filename: *const c_char, mode: *const c_char) -> *mut hFILE {
+    // SAFETY: htslib passes a NUL-terminated file name and mode.
+    let (name, plain_mode) = unsafe { (CStr::from_ptr(filename), CStr::from_ptr(mode)) };
+    let Some((id, url)) = name
+        .to_str()
+        .ok()
+        .and_then(|v| v.strip_prefix(SCHEME))
+        .and_then(|v| v.strip_prefix(':'))
+        .and_then(|v| v.split_once(':'))
+    else {
+        return ptr::null_mut();
+    };
+    // `hopen` reads the arguments after the mode only if the mode ends in ':'
+    let (Ok(real), Ok(mode_with_args)) = (
+        CString::new(url),
+        CString::new([plain_mode.to_bytes(), b":"].concat()),
+    ) else {
+        return ptr::null_mut();
+    };
+    let Some(headers) = id.parse::<u64>().ok().and_then(|v| {
+        HEADERS
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(&v)
+            .cloned()
+    }) else {
+        return ptr::null_mut();
+    };
+    let mut lines: Vec<*const c_char> = headers.iter().map(|v| v.as_ptr()).collect();
+    lines.push(ptr::null());
+    // SAFETY: every string is NUL-terminated and outlives the call, which copies
+    // the headers; the list of headers and the arguments are NULL-terminated.
+    unsafe {
+        hopen(
+            real.as_ptr(),
+            mode_with_args.as_ptr(),
+            c"httphdr:v".as_ptr(),
+            lines.as_ptr(),
+            ptr::null::<c_char>(),
+        )
+    }
+}
+
+/// Reports every file of the scheme as remote, as the wrapped URLs are.
+unsafe extern "C" fn always_remote(_: *const c_char) -> c_int {
+    1
+}
@@ -0,0 +1,256 @@
+//! Request headers, retries and a time limit for opening BAM data from a URL.
+
+use crate::error::ErrorKind;
+use crate::url_headers::HeaderedUrl;
+use nanalogue_core::{GenomicRegion, nanalogue_indexed_bam_reader_from_url};
+use napi::bindgen_prelude::*;
+use rust_htslib::bam::{FetchDefinition, IndexedReader};
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::CString;
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::sync::{Arc, mpsc};
+use std::thread;
+use std::time::Duration;
+use url::Url;
+
+/// Wait before the first retry; each later retry waits twice as long as the one
+/// before.
+const FIRST_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Longest wait between two attempts.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Most attempts that ran past their time limit that may still be running; no
+/// attempt with a time limit is started while there are this many.
+const MAX_TIMED_OUT_ATTEMPTS: usize = 4;
+
+/// Attempts that ran past their time limit and are still running.
+static TIMED_OUT_ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+
+/// State of an attempt with a time limit: still running.
+const RUNNING: u8 = 0;
+/// State of an attempt with a time limit: finished within it.
+const FINISHED: u8 = 1;
+/// State of an attempt with a time limit: ran past it, and counted in
+/// [`TIMED_OUT_ATTEMPTS`] until it finishes.
+const TIMED_OUT: u8 = 2;
+
+/// Why an attempt at opening a URL did not give a reader.
+#[derive(Debug)]
+enum Failure {
+    /// The open failed with this kind and message of error.
+    Failed(ErrorKind, String),
+    /// The attempt ran past this time limit and goes on in the background.
+    TimedOut(Duration),
+    /// Too many earlier attempts that timed out are still running.
+    Busy,
+}
+
+/// How a URL is opened: with which request headers, and how often and for how
+/// long it is tried before opening it fails.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct UrlOpen {
+    /// Attempts made after the first one fails.
+    retries: u32,
+    /// Time limit of each attempt, if any.
+    timeout: Option<Duration>,
+    /// Extra request headers, each a `Name: value` line, sorted by name.
+    headers: Vec<CString>,
+}
+
+impl UrlOpen {
+    /// Settings from the `url_retries`, `url_timeout_ms` and `url_headers` options.
+    ///
+    /// # Errors
+    /// Returns an error if `url_timeout_ms` is 0, or a header name is not a valid
+    /// HTTP token or a header value has a line break or NUL character.
+    pub(crate) fn new(
+        retries: Option<u32>,
+        timeout_ms: Option<u32>,
+        headers: Option<&HashMap<String, String>>,
+    ) -> Result<Self> {
+        if timeout_ms == Some(0) {
+            return Err(ErrorKind::InvalidOptions.error("url_timeout_ms must be at least 1"));
+        }
+        let header_lines = headers
+            .map(|v| v.iter().collect::<BTreeMap<_, _>>())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(name, value)| header_line(name, value))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            retries: retries.unwrap_or(0),
+            timeout: timeout_ms.map(|v| Duration::from_millis(u64::from(v))),
+            headers: header_lines,
+        })
+    }
+
+    /// Opens the BAM data at `url` and its index, fetching `region` or every read.
+    ///
+    /// A failed attempt is retried after a wait that doubles each time, from 100 ms
+    /// up to 5 s. htslib takes no time limit, so an attempt that runs past it is
+    /// abandoned: it goes on in the background until the open ends, and its reader
+    /// is then dropped. Such an attempt is not retried, so that it is never joined
+    /// by another one, and no attempt with a time limit is started while
+    /// [`MAX_TIMED_OUT_ATTEMPTS`] abandoned ones are still running. The headers are
+    /// sent with every request for the file and its index.
+    ///
+    /// # Errors
+    /// Returns an error if `region` is invalid, if there are headers and `url` is
+    /// not `http` or `https`, if too many abandoned attempts are still running, or
+    /// with the error of the last attempt and the number of attempts made if every
+    /// attempt fails or one times out.
+    pub(crate) fn open(&self, url: &Url, region: Option<&GenomicRegion>) -> Result<IndexedReader> {
+        // A malformed region fails the same way every time, so it is not retried
+        let _: FetchDefinition<'_> =
+            fetch_definition(region).map_err(|e| ErrorKind::of_core(&e).error(e.to_string()))?;
+        if !self.headers.is_empty() && !matches!(url.scheme(), "http" | "https") {
+            return Err(ErrorKind::InvalidOptions.error(format!(
+                "url_headers can only be sent to http:// and https:// URLs, not {}://",
+                url.scheme()
+            )));
+        }
+        let attempts = self.retries.saturating_add(1);
+        let mut delay = FIRST_RETRY_DELAY;
+        let mut attempt = 1;
+        loop {
+            let (kind, reason) = match self.attempt(url, region) {
+                Ok(v) => return Ok(v),
+                Err(Failure::Failed(_, _)) if attempt < attempts => {
+                    thread::sleep(delay);
+                    delay = delay.saturating_mul(2).min(MAX_RETRY_DELAY);
+                    attempt = attempt.saturating_add(1);
+                    continue;
+                }
+                Err(Failure::Failed(kind, reason)) => (kind, reason),
+                Err(Failure::TimedOut(timeout)) => (
+                    ErrorKind::IoError,
+                    format!("timed out after {} ms", timeout.as_millis()),
+                ),
+                Err(Failure::Busy) => (
+                    ErrorKind::IoError,
+                    format!(
+                        "{MAX_TIMED_OUT_ATTEMPTS} earlier attempts that timed out are still running"
+                    ),
+                ),
+            };
+            return Err(if attempt == 1 {
+                kind.error(format!("Failed to open BAM: {reason}"))
+            } else {
+                kind.error(format!(
+                    "Failed to open BAM after {attempt} attempts: {reason}"
+                ))
+            });
+        }
+    }
+
+    /// Makes one attempt at opening `url`, within the time limit if there is one.
+    fn attempt(
+        &self,
+        url: &Url,
+        region: Option<&GenomicRegion>,
+    ) -> std::result::Result<IndexedReader, Failure> {
+        let Some(timeout) = self.timeout else {
+            return open_once(url, region, &self.headers)
+                .map_err(|(kind, reason)| Failure::Failed(kind, reason));
+        };
+        if TIMED_OUT_ATTEMPTS.load(Ordering::Acquire) >= MAX_TIMED_OUT_ATTEMPTS {
+            return Err(Failure::Busy);
+        }
+        let (sender, receiver) = mpsc::channel();
+        let state = Arc::new(AtomicU8::new(RUNNING));
+        let attempt_state = Arc::clone(&state);
+        let owned_url = url.clone();
+        let owned_region = region.cloned();
+        let owned_headers = self.headers.clone();
+        let _: thread::JoinHandle<()> = thread::spawn(move || {
+            let outcome = open_once(&owned_url, owned_region.as_ref(), &owned_headers);
+            if attempt_state
+                .compare_exchange(RUNNING, FINISHED, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+            {
+                drop(sender.send(outcome));
+            } else {
+                // Nobody waits for a timed-out attempt; its reader is dropped here
+                let _: usize = TIMED_OUT_ATTEMPTS.fetch_sub(1, Ordering::AcqRel);
+            }
+        });
+        let outcome = match receiver.recv_timeout(timeout) {
+            Ok(v) => Some(v),
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                // Counted before the attempt is marked, so that the attempt never
+                // takes itself off the count before it is on it
+                let _: usize = TIMED_OUT_ATTEMPTS.fetch_add(1, Ordering::AcqRel);
+                if state
+                    .compare_exchange(RUNNING, TIMED_OUT, Ordering::AcqRel, Ordering::Acquire)
+                    .is_ok()
+                {
+                    return Err(Failure::TimedOut(timeout));
+                }
+                // The attempt finished just as the time ran out, so its outcome is
+                // on its way
+                let _: usize = TIMED_OUT_ATTEMPTS.fetch_sub(1, Ordering::AcqRel);
+                receiver.recv().ok()
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => None,
+        };
+        outcome
+            .unwrap_or_else(|| Err((ErrorKind::Internal, "URL open thread failed".to_owned())))
+            .map_err(|(kind, reason)| Failure::Failed(kind, reason))
+    }
+}
+
+/// The `Name: value` line of a request header.
+fn header_line(name: &str, value: &str) -> Result<CString> {
+    let is_token = !name.is_empty()
+        && name
+            .bytes()
+            .all(|v| v.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&v));
+    if !is_token {
+        return Err(
+            ErrorKind::InvalidOptions.error(format!("url_headers: invalid header name {name:?}"))
+        );
+    }
+    CString::new(format!("{name}: {value}"))
+        .ok()
+        .filter(|v| !v.as_bytes().contains(&b'\r') && !v.as_bytes().contains(&b'\n'))
+        .ok_or_else(|| {
+            ErrorKind::InvalidOptions.error(format!(
+                "url_headers: the value of {name} has a line break or NUL character"
+            ))
+        })
+}
+
+/// Reads `region`, or every read if there is none.
+fn fetch_definition(
+    region: Option<&GenomicRegion>,
+) -> std::result::Result<FetchDefinition<'_>, nanalogue_core::Error> {
+    region.map_or(Ok(FetchDefinition::All), TryInto::try_into)
+}
+
+/// Opens `url` with `headers` and fetches `region`, giving the kind and message of
+/// any error.
+fn open_once(
+    url: &Url,
+    region: Option<&GenomicRegion>,
+    headers: &[CString],
+) -> std::result::Result<IndexedReader, (ErrorKind, String)> {
+    let headered = if headers.is_empty() {
+        None
+    } else {
+        Some(
+            HeaderedUrl::new(url, headers.to_vec())
+                .map_err(|e| (ErrorKind::InvalidOptions, format!("invalid URL: {e}")))?,
+        )
+    };
+    let opened = headered.as_ref().map_or(url, HeaderedUrl::url);
+    fetch_definition(region)
+        .and_then(|v| nanalogue_indexed_bam_reader_from_url(opened, v))
+        // Messages name the URL as given, not the one it was opened as
+        .map_err(|e| {
+            (
+                ErrorKind::of_core(&e),
+                e.to_string().replace(opened.as_str(), url.as_str()),
+            )
+        })
+}
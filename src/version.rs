@@ -0,0 +1,33 @@
+//! Versions of this package and of the libraries it is built on.
+
+use napi_derive::napi;
+use std::ffi::CStr;
+
+/// Versions of the binding and of the libraries it loaded.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct Versions {
+    /// Version of this package.
+    #[napi(js_name = "node_binding")]
+    pub node_binding: String,
+    /// Version of `nanalogue_core` the binding was built with.
+    pub core: String,
+    /// Version of the htslib library linked into the binding.
+    pub htslib: String,
+}
+
+/// Returns the versions of the binding, of `nanalogue_core` and of htslib, for
+/// diagnostics and bug reports.
+#[napi]
+#[must_use]
+pub fn version() -> Versions {
+    // SAFETY: `hts_version` takes no arguments and returns a pointer to a static,
+    // NUL-terminated string.
+    let htslib = unsafe { CStr::from_ptr(rust_htslib::htslib::hts_version()) };
+    Versions {
+        node_binding: env!("CARGO_PKG_VERSION").to_owned(),
+        core: env!("NANALOGUE_CORE_VERSION").to_owned(),
+        htslib: htslib.to_string_lossy().into_owned(),
+    }
+}
@@ -0,0 +1,152 @@
+//! Windows of many reads pooled into genomic bins.
+
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use crate::records::ModSelection;
+use crate::ref_windows::visit_ref_windows;
+use crate::{WindowCoordinate, WindowOptions, with_window_records};
+use nanalogue_core::{Error as CoreError, InputMods, InputWindowing, OptionalTag};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::{IndexedReader, Record};
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+/// Window values of the reads over one bin of the reference, pooled.
+#[napi(object)]
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct WindowBin {
+    /// Contig of the bin.
+    pub contig: String,
+    /// First reference position (0-based) of the bin.
+    #[napi(js_name = "bin_start")]
+    pub bin_start: i64,
+    /// Reference position (0-based) after the bin.
+    #[napi(js_name = "bin_end")]
+    pub bin_end: i64,
+    /// Modification code.
+    #[napi(js_name = "mod_type")]
+    pub mod_type: String,
+    /// Mean of the window values of the reads over the bin.
+    pub mean: f64,
+    /// Number of read windows pooled in the bin.
+    #[napi(js_name = "n_reads")]
+    pub n_reads: u32,
+}
+
+/// Running total of the windows of one bin.
+#[derive(Debug)]
+struct BinTotal {
+    /// Contig of the bin.
+    contig: String,
+    /// Reference position (0-based) after the bin.
+    bin_end: i64,
+    /// Sum of the window values.
+    sum: f64,
+    /// Number of windows summed.
+    n_reads: u32,
+}
+
+/// Windows each read along the reference, as `window_reads` does with
+/// `coordinate: 'reference'`, and pools the window values of all reads per bin.
+///
+/// Bins are the reference windows of `win` bases starting at multiples of
+/// `step`, so each read has at most one window per bin and modification. Bins
+/// are listed by contig in header order, then by position and modification
+/// code, and bins without any read window are left out.
+///
+/// # Errors
+/// Returns an error if window/step size is invalid, options that only apply to
+/// `window_reads` output are set, or BAM reading fails.
+#[napi]
+pub async fn window_aggregate(options: WindowOptions) -> Result<Vec<WindowBin>> {
+    run_limited(move || window_aggregate_sync(&options, None)).await
+}
+
+/// Synchronous implementation of `window_aggregate`.
+pub(crate) fn window_aggregate_sync(
+    options: &WindowOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<Vec<WindowBin>> {
+    if options.output_path.is_some()
+        || options.compress.is_some()
+        || options.output_format.is_some()
+    {
+        return Err(ErrorKind::InvalidOptions.error(
+            "windowAggregate returns its bins; outputPath, compress and outputFormat are not supported",
+        ));
+    }
+    if let Some(WindowCoordinate::Read) = options.coordinate {
+        return Err(ErrorKind::InvalidOptions.error(
+            "windowAggregate windows reads in reference coordinates; coordinate must be 'reference'",
+        ));
+    }
+
+    with_window_records(
+        options,
+        shared,
+        "window_aggregate",
+        |records, window_options, mods, extra, _| {
+            pool_windows(
+                records,
+                &window_options,
+                mods,
+                extra.mod_selection(),
+                options,
+            )
+        },
+    )
+}
+
+/// Windows `records` along the reference and pools the window values of all
+/// reads per bin, returning the bins in the order of `window_aggregate`.
+///
+/// # Errors
+/// Returns an error if a record cannot be read or its modification data is
+/// invalid.
+pub(crate) fn pool_windows<D>(
+    records: D,
+    window_options: &InputWindowing,
+    mods: &InputMods<OptionalTag>,
+    selection: ModSelection<'_>,
+    options: &WindowOptions,
+) -> std::result::Result<Vec<WindowBin>, CoreError>
+where
+    D: IntoIterator<Item = std::result::Result<Rc<Record>, rust_htslib::errors::Error>>,
+{
+    let mut bins: BTreeMap<(i32, i64, String), BinTotal> = BTreeMap::new();
+    visit_ref_windows(
+        records,
+        window_options,
+        mods,
+        selection,
+        options,
+        |window| {
+            let (bin_start, bin_end) = window.ref_range();
+            let total = bins
+                .entry((window.tid(), bin_start, window.mod_type().to_string()))
+                .or_insert_with(|| BinTotal {
+                    contig: window.contig().to_owned(),
+                    bin_end,
+                    sum: 0.0,
+                    n_reads: 0,
+                });
+            total.sum += f64::from(window.win_val());
+            total.n_reads = total.n_reads.saturating_add(1);
+            Ok(())
+        },
+    )?;
+
+    Ok(bins
+        .into_iter()
+        .map(|((_, bin_start, mod_type), total)| WindowBin {
+            contig: total.contig,
+            bin_start,
+            bin_end: total.bin_end,
+            mod_type,
+            mean: total.sum / f64::from(total.n_reads),
+            n_reads: total.n_reads,
+        })
+        .collect())
+}
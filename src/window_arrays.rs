@@ -0,0 +1,199 @@
+//! Window values of `window_reads` as typed arrays, for callers that feed them
+//! straight into a chart without parsing TSV or JSON.
+
+use crate::concurrency::run_limited;
+use crate::edge_windows::window_ranges;
+use crate::error::ErrorKind;
+use crate::records::read_mod_data;
+use crate::ref_windows::{visit_ref_windows, window_value};
+use crate::{WindowCoordinate, WindowOptions, with_window_records};
+use nanalogue_core::read_utils::AlignAndModData;
+use nanalogue_core::{CurrRead, Error as CoreError, InputWindowing, ModChar};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::IndexedReader;
+use std::fmt;
+
+/// Window values of the reads, with the read and modification each run of
+/// values belongs to.
+#[napi(object)]
+#[non_exhaustive]
+pub struct WindowArrays {
+    /// Values of all windows, read by read and along each read.
+    pub values: Float64Array,
+    /// Read id of each run of windows: one per read and modification.
+    #[napi(js_name = "read_ids")]
+    pub read_ids: Vec<String>,
+    /// Modification code of each run of windows.
+    #[napi(js_name = "mod_types")]
+    pub mod_types: Vec<String>,
+    /// Index in `values` of the first window of each run, followed by the
+    /// length of `values`, so that run `i` is `values[offsets[i]..offsets[i + 1]]`.
+    pub offsets: Uint32Array,
+}
+
+impl fmt::Debug for WindowArrays {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WindowArrays")
+            .field("values", &self.values.len())
+            .field("read_ids", &self.read_ids)
+            .field("mod_types", &self.mod_types)
+            .field("offsets", &self.offsets.len())
+            .finish()
+    }
+}
+
+/// Window values and runs of windows as they are collected.
+#[derive(Debug, Default)]
+struct Runs {
+    /// Values of all windows.
+    values: Vec<f64>,
+    /// Read id of each run.
+    read_ids: Vec<String>,
+    /// Modification code of each run.
+    mod_types: Vec<String>,
+    /// Index in `values` of the first window of each run.
+    offsets: Vec<u32>,
+}
+
+impl Runs {
+    /// Starts a run of windows of `read_id` and `mod_type`.
+    fn start(&mut self, read_id: &str, mod_type: ModChar) -> std::result::Result<(), CoreError> {
+        self.offsets.push(self.offset()?);
+        self.read_ids.push(read_id.to_owned());
+        self.mod_types.push(mod_type.to_string());
+        Ok(())
+    }
+
+    /// Index in `values` of the next window.
+    fn offset(&self) -> std::result::Result<u32, CoreError> {
+        Ok(u32::try_from(self.values.len())?)
+    }
+
+    /// Typed arrays of the runs.
+    fn finish(mut self) -> std::result::Result<WindowArrays, CoreError> {
+        self.offsets.push(self.offset()?);
+        Ok(WindowArrays {
+            values: Float64Array::new(self.values),
+            read_ids: self.read_ids,
+            mod_types: self.mod_types,
+            offsets: Uint32Array::new(self.offsets),
+        })
+    }
+}
+
+/// Windows modification data as `window_reads` does and returns the window
+/// values as a `Float64Array`, without writing TSV or JSON.
+///
+/// The values of the windows of each read and modification form a run, in the
+/// order `window_reads` writes them; `offsets` gives where each run starts, and
+/// `read_ids` and `mod_types` whom it belongs to. Reads without windows have no
+/// run. Windows are read coordinate windows unless `coordinate` is
+/// [`WindowCoordinate::Reference`], and `min_window_calls` and the tags apply as
+/// in `window_reads`.
+///
+/// # Errors
+/// Returns an error if window/step size is invalid, options of the text output
+/// of `window_reads` are set, or BAM reading fails.
+#[napi]
+pub async fn window_reads_arrays(options: WindowOptions) -> Result<WindowArrays> {
+    run_limited(move || window_reads_arrays_sync(&options, None)).await
+}
+
+/// Synchronous implementation of `window_reads_arrays`.
+pub(crate) fn window_reads_arrays_sync(
+    options: &WindowOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<WindowArrays> {
+    if options.output_path.is_some()
+        || options.compress.is_some()
+        || options.output_format.is_some()
+    {
+        return Err(ErrorKind::InvalidOptions.error(
+            "windowReadsArrays returns typed arrays; outputPath, compress and outputFormat are not supported",
+        ));
+    }
+    let min_calls = options.min_calls();
+
+    with_window_records(
+        options,
+        shared,
+        "window_reads_arrays",
+        |records, window_options, mods, extra, _| {
+            let selection = extra.mod_selection();
+            let mut runs = Runs::default();
+            match options.coordinate.unwrap_or_default() {
+                WindowCoordinate::Reference => {
+                    visit_ref_windows(
+                        records,
+                        &window_options,
+                        mods,
+                        selection,
+                        options,
+                        |window| {
+                            if window.is_first() {
+                                runs.start(window.read_id(), window.mod_type())?;
+                            }
+                            runs.values.push(window.win_val().into());
+                            Ok(())
+                        },
+                    )?;
+                }
+                // Windows hold at most `win` calls, so none is kept below `min_calls`
+                WindowCoordinate::Read if window_options.win.get() < min_calls => {}
+                WindowCoordinate::Read => {
+                    for entry in records {
+                        let record = entry?;
+                        let read = read_mod_data(&record, mods, selection)?;
+                        read_windows(&mut runs, &read, &window_options, options)?;
+                    }
+                }
+            }
+            runs.finish()
+        },
+    )
+}
+
+/// Adds the read coordinate windows of `read` to `runs`, sliding `win` calls
+/// along each modification of the read as the core windowing does, with the
+/// window at the end that the `edge_mode` of `options` adds.
+///
+/// # Errors
+/// Returns an error if `runs` cannot index more windows.
+fn read_windows(
+    runs: &mut Runs,
+    read: &CurrRead<AlignAndModData>,
+    window_options: &InputWindowing,
+    options: &WindowOptions,
+) -> std::result::Result<(), CoreError> {
+    let win = window_options.win.get();
+    let step = window_options.step.get();
+    let win_op = options.win_op.unwrap_or_default();
+    let min_calls = options.min_calls();
+    for base_mod in &read.mod_data().0.base_mods {
+        let quals: Vec<u8> = base_mod.ranges.annotations.iter().map(|k| k.qual).collect();
+        let mut first = true;
+        for range in window_ranges(
+            quals.len(),
+            win,
+            step,
+            options.edge_mode.unwrap_or_default(),
+        ) {
+            let window = quals.get(range).unwrap_or_default();
+            if window.len() < min_calls {
+                continue;
+            }
+            // Windows the operation cannot value are left out, as the core
+            // windowing does
+            let Ok(value) = window_value(win_op, window) else {
+                continue;
+            };
+            if first {
+                runs.start(read.read_id(), ModChar::new(base_mod.modification_type))?;
+                first = false;
+            }
+            runs.values.push(value.into());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,211 @@
+//! Window values of many reads as a matrix of reads by reference windows, for
+//! heatmaps.
+
+use crate::concurrency::run_limited;
+use crate::error::ErrorKind;
+use crate::pileup::single_region;
+use crate::ref_windows::{RefWindow, visit_ref_windows};
+use crate::{ReadOptions, WindowCoordinate, WindowOptions, with_window_records};
+use bedrs::Coordinates as _;
+use nanalogue_core::{Error as CoreError, GenomicRegion};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use rust_htslib::bam::{HeaderView, IndexedReader};
+use std::fmt;
+use std::str::FromStr as _;
+
+/// Window values of the reads over the windows of a region, one row per read
+/// and modification and one column per window.
+#[napi(object)]
+#[non_exhaustive]
+pub struct WindowMatrix {
+    /// Read id of each row.
+    #[napi(js_name = "read_ids")]
+    pub read_ids: Vec<String>,
+    /// Modification code of each row.
+    #[napi(js_name = "mod_types")]
+    pub mod_types: Vec<String>,
+    /// First reference position (0-based) of the window of each column.
+    pub positions: Vec<i64>,
+    /// Window values, row after row, so that the value of row `i` and column
+    /// `j` is `values[i * positions.length + j]`, with NaN where the read has no
+    /// window.
+    pub values: Float64Array,
+}
+
+impl fmt::Debug for WindowMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WindowMatrix")
+            .field("read_ids", &self.read_ids)
+            .field("mod_types", &self.mod_types)
+            .field("positions", &self.positions)
+            .field("values", &self.values.len())
+            .finish()
+    }
+}
+
+/// Rows of the matrix as they are collected.
+#[derive(Debug)]
+struct Rows {
+    /// Index of the contig of the region in the BAM header.
+    tid: i32,
+    /// First reference position (0-based) of the window of each column.
+    positions: Vec<i64>,
+    /// Step between the windows of the columns.
+    step: i64,
+    /// Read id of each row.
+    read_ids: Vec<String>,
+    /// Modification code of each row.
+    mod_types: Vec<String>,
+    /// Window values, row after row.
+    values: Vec<f64>,
+    /// Row of the current read and modification, if it has a window in the
+    /// region yet.
+    current: Option<usize>,
+}
+
+impl Rows {
+    /// Rows over the windows of `win` bases starting at multiples of `step` that
+    /// overlap `region`, without any row yet.
+    fn new(
+        region: &str,
+        header: &HeaderView,
+        win: usize,
+        step: usize,
+    ) -> std::result::Result<Self, CoreError> {
+        let bed3 = GenomicRegion::from_str(region)?.try_to_bed3(header)?;
+        let (win_len, step_len) = (u64::try_from(win)?, u64::try_from(step)?);
+        // Windows starting at or before `start - win` end before the region
+        let first = bed3
+            .start()
+            .saturating_add(1)
+            .saturating_sub(win_len)
+            .div_ceil(step_len)
+            .saturating_mul(step_len);
+        let (first_pos, end) = (i64::try_from(first)?, i64::try_from(bed3.end())?);
+        Ok(Self {
+            tid: *bed3.chr(),
+            positions: (first_pos..end).step_by(step).collect(),
+            step: i64::try_from(step_len)?,
+            read_ids: Vec::new(),
+            mod_types: Vec::new(),
+            values: Vec::new(),
+            current: None,
+        })
+    }
+
+    /// Adds `window` to the row of its read and modification, starting the row
+    /// if it is the first window of the read and modification in the region.
+    /// Windows outside the region are left out.
+    fn add(&mut self, window: &RefWindow<'_>) {
+        if window.is_first() {
+            self.current = None;
+        }
+        let (ref_start, _) = window.ref_range();
+        let Some(first) = self.positions.first() else {
+            return;
+        };
+        let Some(column) = ref_start
+            .checked_sub(*first)
+            .and_then(|v| v.checked_div(self.step))
+            .and_then(|v| usize::try_from(v).ok())
+            .filter(|&v| window.tid() == self.tid && v < self.positions.len())
+        else {
+            return;
+        };
+        let row = if let Some(v) = self.current {
+            v
+        } else {
+            self.read_ids.push(window.read_id().to_owned());
+            self.mod_types.push(window.mod_type().to_string());
+            self.values
+                .extend(std::iter::repeat_n(f64::NAN, self.positions.len()));
+            let v = self.read_ids.len().saturating_sub(1);
+            self.current = Some(v);
+            v
+        };
+        if let Some(v) = self.values.get_mut(
+            row.saturating_mul(self.positions.len())
+                .saturating_add(column),
+        ) {
+            *v = window.win_val().into();
+        }
+    }
+
+    /// Matrix of the rows.
+    fn finish(self) -> WindowMatrix {
+        WindowMatrix {
+            read_ids: self.read_ids,
+            mod_types: self.mod_types,
+            positions: self.positions,
+            values: Float64Array::new(self.values),
+        }
+    }
+}
+
+/// Windows each read along the reference, as `window_reads` does with
+/// `coordinate: 'reference'`, and returns the window values over `region` as a
+/// matrix of reads by windows, e.g. for a heatmap.
+///
+/// Columns are the windows of `win` bases starting at multiples of `step` that
+/// overlap the region, in order along the contig, so each read has at most one
+/// window per column and modification. Rows are the reads and modifications
+/// with a window in the region, in the order the reads are read. `values` holds
+/// the window values row after row in one `Float64Array`, handed to JavaScript
+/// without a copy, with NaN for the windows a read does not cover or that hold
+/// too few calls.
+///
+/// # Errors
+/// Returns an error if region is missing, window/step size is invalid, options
+/// that only apply to `window_reads` output are set, or BAM reading fails.
+#[napi]
+pub async fn window_matrix(options: WindowOptions) -> Result<WindowMatrix> {
+    run_limited(move || window_matrix_sync(&options, None)).await
+}
+
+/// Synchronous implementation of `window_matrix`.
+pub(crate) fn window_matrix_sync(
+    options: &WindowOptions,
+    shared: Option<&mut IndexedReader>,
+) -> Result<WindowMatrix> {
+    if options.output_path.is_some()
+        || options.compress.is_some()
+        || options.output_format.is_some()
+    {
+        return Err(ErrorKind::InvalidOptions.error(
+            "windowMatrix returns a matrix; outputPath, compress and outputFormat are not supported",
+        ));
+    }
+    if let Some(WindowCoordinate::Read) = options.coordinate {
+        return Err(ErrorKind::InvalidOptions.error(
+            "windowMatrix windows reads in reference coordinates; coordinate must be 'reference'",
+        ));
+    }
+    let region = single_region(&ReadOptions::from(options), "windowMatrix")?.into_owned();
+
+    with_window_records(
+        options,
+        shared,
+        "window_matrix",
+        |records, window_options, mods, extra, header| {
+            let mut rows = Rows::new(
+                &region,
+                header,
+                window_options.win.get(),
+                window_options.step.get(),
+            )?;
+            visit_ref_windows(
+                records,
+                &window_options,
+                mods,
+                extra.mod_selection(),
+                options,
+                |window| {
+                    rows.add(window);
+                    Ok(())
+                },
+            )?;
+            Ok(rows.finish())
+        },
+    )
+}
@@ -0,0 +1,531 @@
+//! Window operations that `nanalogue_core::analysis` lacks.
+//!
+//! The core windowing functions only carry window values between -1 and 1, so
+//! totals such as the number of modified calls in a window cannot be returned by
+//! a window function directly. Instead the totals are computed as means and
+//! multiplied back by the calls of each window in [`ScaledWindows`] as the output
+//! is written. Likewise, [`TaggedWindows`] keeps the windows of
+//! several modification tags, where the core windowing takes at most one, and
+//! [`CountedWindows`] adds the calls behind each window that [`counting`]
+//! records as the windows are valued. All three change the output a line at a time,
+//! through a [`LineWriter`].
+
+use crate::WindowFormat;
+use crate::pileup::MOD_THRESHOLD;
+use nanalogue_core::{Contains as _, Error, F32AbsValAtMost1, F32Bw0and1, ModChar};
+use serde::Deserialize;
+use serde::de::{DeserializeOwned, IgnoredAny};
+use std::cell::RefCell;
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::io::{self, Write};
+
+/// Highest probability of modification in a window.
+///
+/// # Errors
+/// Returns `Error::EmptyWindow` if the window has no calls.
+pub(crate) fn max_prob(mod_list: &[u8]) -> Result<F32AbsValAtMost1, Error> {
+    mod_list
+        .iter()
+        .max()
+        .map(|&v| F32Bw0and1::from(v).into())
+        .ok_or_else(|| Error::EmptyWindow("in `max_prob`".to_owned()))
+}
+
+/// Mean ML value of a window, as a fraction of 255.
+///
+/// # Errors
+/// Returns `Error::EmptyWindow` if the window has no calls.
+pub(crate) fn mean_ml(mod_list: &[u8]) -> Result<F32AbsValAtMost1, Error> {
+    if mod_list.is_empty() {
+        return Err(Error::EmptyWindow("in `mean_ml`".to_owned()));
+    }
+    let total: u64 = mod_list.iter().copied().map(u64::from).sum();
+    #[expect(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        reason = "the mean is at most 1 and only needs the precision of the core window values"
+    )]
+    let mean = (total as f64 / (mod_list.len() as f64 * f64::from(u8::MAX))) as f32;
+    F32Bw0and1::new(mean).map(Into::into)
+}
+
+/// Modified and valid calls behind one window, as `(n_mod, n_valid)`.
+pub(crate) type WindowCalls = (usize, usize);
+
+/// Wraps `window_function` to record the calls behind each window it values in
+/// `counts`, in the order the core writes the windows out.
+///
+/// Windows that `window_function` rejects are skipped by the core windowing, so
+/// nothing is recorded for them.
+pub(crate) fn counting<'a, F>(
+    window_function: F,
+    counts: &'a RefCell<VecDeque<WindowCalls>>,
+) -> impl Fn(&[u8]) -> Result<F32AbsValAtMost1, Error> + 'a
+where
+    F: Fn(&[u8]) -> Result<F32AbsValAtMost1, Error> + 'a,
+{
+    move |mod_list| {
+        let value = window_function(mod_list)?;
+        let n_mod = mod_list
+            .iter()
+            .filter(|v| MOD_THRESHOLD.contains(v))
+            .count();
+        counts.borrow_mut().push_back((n_mod, mod_list.len()));
+        Ok(value)
+    }
+}
+
+/// Window of the core JSON output:
+/// `(win_start, win_end, win_val, mean_base_qual, ref_win_start, ref_win_end)`.
+type CoreWindow = (i64, i64, f32, u8, i64, i64);
+
+/// Window of the JSON output with its calls: the values of a [`CoreWindow`]
+/// followed by `n_mod` and `n_valid`.
+type CountedWindow = (i64, i64, f64, u8, i64, i64, usize, usize);
+
+/// Writer that hands each line of output, with its line end, to a transform that
+/// writes what it makes of the line to the inner writer.
+///
+/// The windows of the output are changed a line at a time, so output is held
+/// back until its line ends. [`CountedWindows`], [`ScaledWindows`] and
+/// [`TaggedWindows`] are such transforms.
+pub(crate) struct LineWriter<W: Write, F: FnMut(&[u8], &mut W) -> io::Result<()>> {
+    /// Writer receiving the transformed output.
+    inner: W,
+    /// Output of an unfinished line, held until its end is written.
+    pending: Vec<u8>,
+    /// Writes a line of output to the inner writer.
+    transform: F,
+}
+
+impl<W: Write, F: FnMut(&[u8], &mut W) -> io::Result<()>> LineWriter<W, F> {
+    /// Wraps `inner` to pass each line of output through `transform`.
+    pub(crate) const fn new(inner: W, transform: F) -> Self {
+        Self {
+            inner,
+            pending: Vec::new(),
+            transform,
+        }
+    }
+
+    /// Writes out any unfinished line and flushes the inner writer.
+    ///
+    /// # Errors
+    /// Returns an error if the line cannot be transformed or writing fails.
+    pub(crate) fn finish(mut self) -> io::Result<()> {
+        if !self.pending.is_empty() {
+            (self.transform)(&self.pending, &mut self.inner)?;
+        }
+        self.inner.flush()
+    }
+}
+
+impl<W: Write, F: FnMut(&[u8], &mut W) -> io::Result<()>> Write for LineWriter<W, F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Some(last_newline) = buf.iter().rposition(|&b| b == b'\n') else {
+            self.pending.extend_from_slice(buf);
+            return Ok(buf.len());
+        };
+        let (complete, partial) = buf.split_at(last_newline.saturating_add(1));
+        self.pending.extend_from_slice(complete);
+        let pending = std::mem::take(&mut self.pending);
+        for line in pending.split_inclusive(|&b| b == b'\n') {
+            (self.transform)(line, &mut self.inner)?;
+        }
+        self.pending.extend_from_slice(partial);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Write, F: FnMut(&[u8], &mut W) -> io::Result<()>> fmt::Debug for LineWriter<W, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LineWriter")
+            .field("pending", &self.pending)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Column of the window value in a TSV row of `window_reads` output.
+const TSV_WIN_VAL: usize = 4;
+
+/// Column of the modification code in a TSV row of `window_reads` output.
+const TSV_MOD_TYPE: usize = 8;
+
+/// Column of `n_valid` in a TSV row of `window_reads` output, as added by
+/// [`CountedWindows`].
+const TSV_N_VALID: usize = 13;
+
+/// Line of TSV output of `window_reads`: the header, a row or a blank line.
+#[derive(Debug, Clone, Copy)]
+struct TsvLine<'a> {
+    /// The line without its line end.
+    text: &'a str,
+    /// The line end, if any.
+    end: &'a str,
+}
+
+impl<'a> TsvLine<'a> {
+    /// Splits `line` from its line end.
+    fn new(line: &'a str) -> Self {
+        line.strip_suffix('\n').map_or(
+            Self {
+                text: line,
+                end: "",
+            },
+            |v| Self { text: v, end: "\n" },
+        )
+    }
+
+    /// Whether the line is a row of a window, rather than the header, which
+    /// starts with `#`, or a blank line.
+    fn is_row(&self) -> bool {
+        !self.text.starts_with('#') && !self.text.is_empty()
+    }
+
+    /// Field of the row in `column`.
+    fn field(&self, column: usize) -> io::Result<&'a str> {
+        self.text
+            .split('\t')
+            .nth(column)
+            .ok_or_else(|| invalid_data(format!("missing column {column} of a window")))
+    }
+
+    /// The line with the field in `column` replaced by `value`.
+    fn with_field(&self, column: usize, value: &str) -> io::Result<String> {
+        let _: &str = self.field(column)?;
+        let fields: Vec<&str> = self
+            .text
+            .split('\t')
+            .enumerate()
+            .map(|(i, field)| if i == column { value } else { field })
+            .collect();
+        Ok(format!("{}{}", fields.join("\t"), self.end))
+    }
+}
+
+impl fmt::Display for TsvLine<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.text, self.end)
+    }
+}
+
+/// Key of the window arrays of a modification table entry in JSON output.
+const DATA_KEY: &str = "\"data\":";
+
+/// Key of the modification table of a read entry in JSON output.
+const MOD_TABLE_KEY: &str = "\"mod_table\":";
+
+/// Replaces the JSON value after each `key` of a line of JSON output by what
+/// `map` makes of its text, leaving the rest of the line as written.
+///
+/// Keys inside JSON strings have their quotes escaped, so every unescaped `key`
+/// is a key of the output.
+fn map_json_values<F>(line: &str, key: &str, mut map: F) -> io::Result<String>
+where
+    F: FnMut(&str) -> io::Result<String>,
+{
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some((head, tail)) = rest.split_once(key) {
+        out.push_str(head);
+        out.push_str(key);
+        let end = json_value_len(tail)?;
+        out.push_str(&map(tail.get(..end).unwrap_or_default())?);
+        rest = tail.get(end..).unwrap_or_default();
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Length of the JSON value at the start of `text`.
+fn json_value_len(text: &str) -> io::Result<usize> {
+    let mut values = serde_json::Deserializer::from_str(text).into_iter::<IgnoredAny>();
+    let _: IgnoredAny = values
+        .next()
+        .ok_or_else(|| invalid_data("missing JSON value"))?
+        .map_err(invalid_data)?;
+    Ok(values.byte_offset())
+}
+
+/// Splits the text of a JSON array into the text of its elements.
+fn json_elements(array: &str) -> io::Result<Vec<&str>> {
+    let mut rest = array
+        .strip_prefix('[')
+        .ok_or_else(|| invalid_data("expected a JSON array"))?;
+    let mut elements = Vec::new();
+    loop {
+        rest = rest.trim_start_matches(',');
+        if let Some(tail) = rest.strip_prefix(']') {
+            return if tail.is_empty() {
+                Ok(elements)
+            } else {
+                Err(invalid_data("text after a JSON array"))
+            };
+        }
+        let (element, tail) = rest
+            .split_at_checked(json_value_len(rest)?)
+            .ok_or_else(|| invalid_data("invalid JSON array"))?;
+        elements.push(element);
+        rest = tail;
+    }
+}
+
+/// Parses the text of a JSON value of the output.
+fn parse_json<T: DeserializeOwned>(text: &str) -> io::Result<T> {
+    serde_json::from_str(text).map_err(invalid_data)
+}
+
+/// The text of a line of output.
+fn utf8(line: &[u8]) -> io::Result<&str> {
+    std::str::from_utf8(line).map_err(invalid_data)
+}
+
+/// Error for output that is not as the core writes it.
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// Adds the `n_mod` and `n_valid` calls of each window to `window_reads`
+/// output, leaving out windows of fewer than `min_calls` calls, one line at a
+/// time through a [`LineWriter`].
+///
+/// The calls come from the `counts` that [`counting`] fills while the core
+/// windowing values the windows, which it does before writing them out.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CountedWindows<'a> {
+    /// Format of the output, which tells where the windows are.
+    format: WindowFormat,
+    /// Calls of the windows valued but not yet written, in output order.
+    counts: &'a RefCell<VecDeque<WindowCalls>>,
+    /// Fewest valid calls of a window that is kept.
+    min_calls: usize,
+}
+
+impl<'a> CountedWindows<'a> {
+    /// Adds the calls in `counts` to the windows, keeping those with at least
+    /// `min_calls` valid calls.
+    pub(crate) const fn new(
+        format: WindowFormat,
+        counts: &'a RefCell<VecDeque<WindowCalls>>,
+        min_calls: usize,
+    ) -> Self {
+        Self {
+            format,
+            counts,
+            min_calls,
+        }
+    }
+
+    /// Wraps `inner` to receive the output with the calls added.
+    pub(crate) fn writer<W: Write>(
+        self,
+        inner: W,
+    ) -> LineWriter<W, impl FnMut(&[u8], &mut W) -> io::Result<()>> {
+        LineWriter::new(inner, move |line, out| self.write_line(line, out))
+    }
+
+    /// Adds the calls to the windows of one line of output and writes it to
+    /// `out`.
+    fn write_line<W: Write>(self, raw_line: &[u8], out: &mut W) -> io::Result<()> {
+        let line = utf8(raw_line)?;
+        let counted = match self.format {
+            WindowFormat::Json => map_json_values(line, DATA_KEY, |raw| self.count_json(raw))?,
+            WindowFormat::Tsv => self.count_tsv(&TsvLine::new(line))?,
+        };
+        out.write_all(counted.as_bytes())
+    }
+
+    /// Calls of the next window written out.
+    fn next_calls(self) -> io::Result<WindowCalls> {
+        self.counts
+            .borrow_mut()
+            .pop_front()
+            .ok_or_else(|| invalid_data("missing calls of a window"))
+    }
+
+    /// Adds the `n_mod` and `n_valid` columns to a TSV row or to the header,
+    /// leaving the row out if it has too few calls.
+    fn count_tsv(self, line: &TsvLine<'_>) -> io::Result<String> {
+        let TsvLine { text, end } = *line;
+        if text.starts_with('#') {
+            return Ok(format!("{text}\tn_mod\tn_valid{end}"));
+        }
+        if !line.is_row() {
+            return Ok(line.to_string());
+        }
+        let (n_mod, n_valid) = self.next_calls()?;
+        Ok(if n_valid < self.min_calls {
+            String::new()
+        } else {
+            format!("{text}\t{n_mod}\t{n_valid}{end}")
+        })
+    }
+
+    /// Adds the calls to the windows of the text of a `data` array of JSON
+    /// output, leaving out windows with too few calls.
+    fn count_json(self, raw: &str) -> io::Result<String> {
+        let data: Vec<CoreWindow> = parse_json(raw)?;
+        let mut counted = Vec::with_capacity(data.len());
+        for (a, b, v, c, d, e) in data {
+            let (n_mod, n_valid) = self.next_calls()?;
+            if n_valid >= self.min_calls {
+                counted.push((a, b, v, c, d, e, n_mod, n_valid));
+            }
+        }
+        Ok(serde_json::to_string(&counted)?)
+    }
+}
+
+/// Turns the window means of `window_reads` output back into totals, one line
+/// at a time through a [`LineWriter`].
+///
+/// Each window value `v` is replaced by `round(v * n_valid * unit) / unit`, where
+/// `n_valid` is the number of calls of the window that [`CountedWindows`] adds
+/// and `unit` the denominator of the totals, e.g. 1 for counts or 255 for sums
+/// of ML values as probabilities. The rounding removes the error of the single
+/// precision means, as long as `n_valid * unit` is well below 2^24.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ScaledWindows {
+    /// Format of the output, which tells where the window values are.
+    format: WindowFormat,
+    /// Denominator of the totals.
+    unit: f64,
+}
+
+impl ScaledWindows {
+    /// Rescales window means into totals of `unit`ths.
+    pub(crate) fn new(format: WindowFormat, unit: u8) -> Self {
+        Self {
+            format,
+            unit: f64::from(unit),
+        }
+    }
+
+    /// Wraps `inner` to receive the rescaled output.
+    pub(crate) fn writer<W: Write>(
+        self,
+        inner: W,
+    ) -> LineWriter<W, impl FnMut(&[u8], &mut W) -> io::Result<()>> {
+        LineWriter::new(inner, move |line, out| self.write_line(line, out))
+    }
+
+    /// Rescales the window values of one line of output and writes it to `out`.
+    fn write_line<W: Write>(self, raw_line: &[u8], out: &mut W) -> io::Result<()> {
+        let line = utf8(raw_line)?;
+        let rescaled = match self.format {
+            WindowFormat::Json => map_json_values(line, DATA_KEY, |raw| {
+                let data: Vec<CountedWindow> = parse_json(raw)?;
+                let rescaled: Vec<CountedWindow> = data
+                    .into_iter()
+                    .map(|(a, b, v, c, d, e, m, n)| (a, b, self.total(v, n), c, d, e, m, n))
+                    .collect();
+                Ok(serde_json::to_string(&rescaled)?)
+            })?,
+            WindowFormat::Tsv => self.rescale_tsv(&TsvLine::new(line))?,
+        };
+        out.write_all(rescaled.as_bytes())
+    }
+
+    /// Total for the mean of a window of `calls` calls.
+    fn total(self, mean: f64, calls: usize) -> f64 {
+        #[expect(
+            clippy::cast_precision_loss,
+            reason = "numbers of calls are far below 2^52"
+        )]
+        let factor = calls as f64 * self.unit;
+        (mean * factor).round() / self.unit
+    }
+
+    /// Rescales the window value of a TSV row by its `n_valid`, leaving the
+    /// header as is.
+    fn rescale_tsv(self, line: &TsvLine<'_>) -> io::Result<String> {
+        if !line.is_row() {
+            return Ok(line.to_string());
+        }
+        let calls = line
+            .field(TSV_N_VALID)?
+            .parse::<usize>()
+            .map_err(invalid_data)?;
+        let mean = line
+            .field(TSV_WIN_VAL)?
+            .parse::<f64>()
+            .map_err(invalid_data)?;
+        line.with_field(TSV_WIN_VAL, &self.total(mean, calls).to_string())
+    }
+}
+
+/// Keeps only the windows of some modification tags in `window_reads` output,
+/// as the core windowing takes at most one tag, one line at a time through a
+/// [`LineWriter`].
+#[derive(Debug)]
+pub(crate) struct TaggedWindows {
+    /// Format of the output, which tells where the modification codes are.
+    format: WindowFormat,
+    /// Modification codes to keep, as written in the output.
+    tags: HashSet<String>,
+}
+
+/// Modification table entry of JSON output, as far as it is needed to tell its
+/// tag.
+#[derive(Debug, Deserialize)]
+struct TableEntry {
+    /// Modification code of the entry.
+    mod_code: String,
+}
+
+impl TaggedWindows {
+    /// Keeps only the windows of `tags`.
+    pub(crate) fn new(format: WindowFormat, tags: &HashSet<ModChar>) -> Self {
+        Self {
+            format,
+            tags: tags.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    /// Wraps `inner` to receive the filtered output.
+    pub(crate) fn writer<W: Write>(
+        self,
+        inner: W,
+    ) -> LineWriter<W, impl FnMut(&[u8], &mut W) -> io::Result<()>> {
+        LineWriter::new(inner, move |line, out| self.write_line(line, out))
+    }
+
+    /// Filters the windows of one line of output and writes it to `out`.
+    fn write_line<W: Write>(&self, raw_line: &[u8], out: &mut W) -> io::Result<()> {
+        let line = utf8(raw_line)?;
+        match self.format {
+            WindowFormat::Json => {
+                let filtered = map_json_values(line, MOD_TABLE_KEY, |raw| self.filter_table(raw))?;
+                out.write_all(filtered.as_bytes())
+            }
+            // Rows of other tags are left out, along with their line ends
+            WindowFormat::Tsv if self.keeps_tsv(&TsvLine::new(line))? => out.write_all(raw_line),
+            WindowFormat::Tsv => Ok(()),
+        }
+    }
+
+    /// Whether a TSV line is the header or a row of one of the tags.
+    fn keeps_tsv(&self, line: &TsvLine<'_>) -> io::Result<bool> {
+        Ok(!line.is_row() || self.tags.contains(line.field(TSV_MOD_TYPE)?))
+    }
+
+    /// Removes the entries of other tags from the text of a modification table,
+    /// leaving the other entries as written.
+    fn filter_table(&self, table: &str) -> io::Result<String> {
+        let mut kept = Vec::new();
+        for entry in json_elements(table)? {
+            if self
+                .tags
+                .contains(&parse_json::<TableEntry>(entry)?.mod_code)
+            {
+                kept.push(entry);
+            }
+        }
+        Ok(format!("[{}]", kept.join(",")))
+    }
+}
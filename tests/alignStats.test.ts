@@ -0,0 +1,81 @@
+// Tests for alignStats, which summarises the CIGAR string of each read
+
+import { describe, expect, it } from 'vitest';
+import { alignStats, NanalogueBam, readInfo } from '../index';
+import { EXAMPLE_1_BAM, EXAMPLE_CIGAR_BAM } from './fixtures';
+
+describe('alignStats', () => {
+  it('counts clips, indels and aligned bases of each read', async () => {
+    const stats = await alignStats({ bamPath: EXAMPLE_CIGAR_BAM });
+
+    expect(stats).toEqual([
+      {
+        read_id: 'read_clipped',
+        ref_span: 35,
+        soft_clip_left: 3,
+        soft_clip_right: 2,
+        n_ins: 2,
+        n_del: 4,
+        n_match: 31,
+      },
+      {
+        read_id: 'read_hard_clipped',
+        ref_span: 16,
+        soft_clip_left: 2,
+        soft_clip_right: 0,
+        n_ins: 0,
+        n_del: 1,
+        n_match: 15,
+      },
+      // Reverse read with a skipped region, which spans the reference but is
+      // not a deletion
+      {
+        read_id: 'read_spliced',
+        ref_span: 23,
+        soft_clip_left: 4,
+        soft_clip_right: 0,
+        n_ins: 0,
+        n_del: 0,
+        n_match: 20,
+      },
+      {
+        read_id: 'read_unmapped',
+        ref_span: 0,
+        soft_clip_left: 0,
+        soft_clip_right: 0,
+        n_ins: 0,
+        n_del: 0,
+        n_match: 0,
+      },
+    ]);
+  });
+
+  it('gives the alignment length of readInfo as the span', async () => {
+    const stats = await alignStats({ bamPath: EXAMPLE_1_BAM });
+    const reads = await readInfo({ bamPath: EXAMPLE_1_BAM });
+
+    expect(stats.map((v) => v.read_id)).toEqual(reads.map((v) => v.read_id));
+    for (const [i, read] of reads.entries()) {
+      const span = 'alignment_length' in read ? read.alignment_length : 0;
+      expect(stats[i]?.ref_span).toBe(span);
+    }
+  });
+
+  it('applies read filters and pagination', async () => {
+    const stats = await alignStats({
+      bamPath: EXAMPLE_CIGAR_BAM,
+      readFilter: 'primary_forward',
+      offset: 1,
+      limit: 1,
+    });
+
+    expect(stats.map((v) => v.read_id)).toEqual(['read_hard_clipped']);
+  });
+
+  it('works on a NanalogueBam', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_CIGAR_BAM });
+    expect(await bam.alignStats({ limit: 2 })).toEqual(
+      await alignStats({ bamPath: EXAMPLE_CIGAR_BAM, limit: 2 }),
+    );
+  });
+});
@@ -0,0 +1,100 @@
+// Tests for alignedSequences, which gives the plain aligned bases over a region
+
+import { describe, expect, it } from 'vitest';
+import {
+  alignedSequences,
+  ErrorKind,
+  NanalogueBam,
+  seqTableRecords,
+} from '../index';
+import { EXAMPLE_1_BAM, EXAMPLE_CIGAR_BAM } from './fixtures';
+
+describe('alignedSequences', () => {
+  it('gives one base per position with gaps for deletions', async () => {
+    const sequences = await alignedSequences({
+      bamPath: EXAMPLE_CIGAR_BAM,
+      region: 'contig1:0-200',
+    });
+
+    // Clips and insertions are left out; the skipped region of the spliced
+    // read is a gap too. The unmapped read has no aligned bases.
+    expect(sequences).toEqual([
+      {
+        read_id: 'read_clipped',
+        ref_start: 9,
+        aligned_seq: 'ATTTTCATATTATGCAGAAACTACT----TCGCCT',
+      },
+      {
+        read_id: 'read_hard_clipped',
+        ref_start: 59,
+        aligned_seq: 'CGAGTCGGTT-ATCTT',
+      },
+      {
+        read_id: 'read_spliced',
+        ref_start: 99,
+        aligned_seq: 'TACTGTATAG---TCCCACCTGG',
+      },
+    ]);
+  });
+
+  it('clips reads to the region', async () => {
+    const sequences = await alignedSequences({
+      bamPath: EXAMPLE_CIGAR_BAM,
+      region: 'contig1:20-30',
+    });
+
+    expect(sequences).toEqual([
+      { read_id: 'read_clipped', ref_start: 20, aligned_seq: 'ATGCAGAAAC' },
+    ]);
+  });
+
+  it('does not mark modified bases', async () => {
+    const options = { bamPath: EXAMPLE_1_BAM, region: 'dummyIII:30-35' };
+    const [record] = await seqTableRecords(options);
+    const [sequence] = await alignedSequences(options);
+
+    expect(record?.sequence).toBe('AZCCA');
+    expect(sequence).toEqual({
+      read_id: record?.read_id,
+      ref_start: 30,
+      aligned_seq: 'ATCCA',
+    });
+  });
+
+  it('keeps only reads spanning the region with fullRegion', async () => {
+    const spanned = await alignedSequences({
+      bamPath: EXAMPLE_CIGAR_BAM,
+      region: 'contig1:10-20',
+      fullRegion: true,
+    });
+    expect(spanned.map((v) => v.read_id)).toEqual(['read_clipped']);
+
+    // read_clipped starts at position 9, inside the region
+    const partial = await alignedSequences({
+      bamPath: EXAMPLE_CIGAR_BAM,
+      region: 'contig1:5-20',
+      fullRegion: true,
+    });
+    expect(partial).toEqual([]);
+  });
+
+  it('requires a single region', async () => {
+    await expect(
+      alignedSequences({ bamPath: EXAMPLE_CIGAR_BAM }),
+    ).rejects.toMatchObject({ code: ErrorKind.InvalidOptions });
+    await expect(
+      alignedSequences({
+        bamPath: EXAMPLE_CIGAR_BAM,
+        regions: ['contig1:0-50', 'contig1:60-80'],
+      }),
+    ).rejects.toMatchObject({ code: ErrorKind.InvalidOptions });
+  });
+
+  it('works on a NanalogueBam', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_CIGAR_BAM });
+    const options = { region: 'contig1:50-120', limit: 1 };
+    expect(await bam.alignedSequences(options)).toEqual(
+      await alignedSequences({ bamPath: EXAMPLE_CIGAR_BAM, ...options }),
+    );
+  });
+});
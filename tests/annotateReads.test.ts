@@ -0,0 +1,116 @@
+// Tests for annotateReads, which lists the features each read overlaps
+
+import { mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import {
+  annotateReads,
+  ErrorKind,
+  type Feature,
+  NanalogueBam,
+  readInfo,
+} from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM } from './fixtures';
+
+describe('annotateReads', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-annotate-reads-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  // Reads of dummyI:9-17, dummyIII:23-71, dummyII:3-36 and an unmapped read
+  const features: Feature[] = [
+    { contig: 'dummyIII', start: 20, end: 30, name: 'gene_a' },
+    { contig: 'dummyI', start: 0, end: 10, name: 'gene_b' },
+    { contig: 'dummyIII', start: 0, end: 5, name: 'gene_c' },
+    { contig: 'dummyII', start: 35, end: 40, name: 'gene_d' },
+    { contig: 'dummyI', start: 0, end: 9, name: 'gene_e' },
+    { contig: 'dummyIII', start: 30, end: 31, name: 'gene_f' },
+  ];
+
+  it('names the features each read overlaps, in the order given', async () => {
+    const annotations = await annotateReads(
+      { bamPath: EXAMPLE_1_BAM },
+      features,
+    );
+
+    expect(annotations).toEqual([
+      {
+        read_id: '5d10eb9a-aae1-4db8-8ec6-7ebb34d32575',
+        overlapping: ['gene_b'],
+      },
+      {
+        read_id: 'a4f36092-b4d5-47a9-813e-c22c3b477a0c',
+        overlapping: ['gene_a', 'gene_f'],
+      },
+      {
+        read_id: 'fffffff1-10d2-49cb-8ca3-e8d48979001b',
+        overlapping: ['gene_d'],
+      },
+      { read_id: 'a4f36092-b4d5-47a9-813e-c22c3b477a0c', overlapping: [] },
+    ]);
+  });
+
+  it('agrees with a search of every feature for many features', async () => {
+    const many: Feature[] = Array.from({ length: 2000 }, (_, i) => {
+      const start = (i * 7919) % 9900;
+      return {
+        contig: `contig_0000${i % 2}`,
+        start,
+        end: start + 1 + (i % 100),
+        name: `feature_${i}`,
+      };
+    });
+    const options = { bamPath: simpleBamPath, limit: 200 };
+    const reads = await readInfo(options);
+    const expected = reads.map((read) => ({
+      read_id: read.read_id,
+      overlapping: many
+        .filter(
+          (f) =>
+            f.contig === read.contig &&
+            f.start < (read.reference_end ?? 0) &&
+            f.end > (read.reference_start ?? 0),
+        )
+        .map((f) => f.name),
+    }));
+
+    expect(await annotateReads(options, many)).toEqual(expected);
+  });
+
+  it('returns empty lists without features', async () => {
+    const annotations = await annotateReads({ bamPath: EXAMPLE_1_BAM }, []);
+
+    expect(annotations).toHaveLength(4);
+    expect(annotations.every((a) => a.overlapping.length === 0)).toBe(true);
+  });
+
+  it.each([
+    [{ contig: 'chrX', start: 0, end: 10, name: 'x' }, /not in the BAM header/],
+    [{ contig: 'dummyI', start: 5, end: 5, name: 'x' }, /less than end/],
+    [{ contig: 'dummyI', start: -1, end: 5, name: 'x' }, /negative/],
+  ])('rejects the feature %o', async (feature, message) => {
+    await expect(
+      annotateReads({ bamPath: EXAMPLE_1_BAM }, [feature]),
+    ).rejects.toMatchObject({
+      code: ErrorKind.InvalidRegion,
+      message: expect.stringMatching(message),
+    });
+  });
+
+  it('is a NanalogueBam method', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+
+    expect(await bam.annotateReads({ limit: 2 }, features)).toEqual(
+      await annotateReads({ bamPath: EXAMPLE_1_BAM, limit: 2 }, features),
+    );
+  });
+});
@@ -0,0 +1,105 @@
+// Tests for autoIndex, which builds the missing index of a local BAM file
+
+import { existsSync } from 'node:fs';
+import { copyFile, mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import {
+  afterAll,
+  afterEach,
+  beforeAll,
+  describe,
+  expect,
+  it,
+  vi,
+} from 'vitest';
+import {
+  ErrorKind,
+  exportBam,
+  type LogRecord,
+  NanalogueBam,
+  readInfo,
+  setLogCallback,
+} from '../index';
+import { createSimpleBam } from './fixtures';
+
+describe('autoIndex', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+  const region = 'contig_00000:0-100';
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-auto-index-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  afterEach(() => {
+    setLogCallback();
+  });
+
+  /** Copies the simple BAM file, without its index, to `name`. */
+  const unindexedCopy = async (name: string) => {
+    const bamPath = join(tmpDir, name);
+    await copyFile(simpleBamPath, bamPath);
+    return bamPath;
+  };
+
+  it('rejects a file without an index unless set', async () => {
+    const bamPath = await unindexedCopy('rejected.bam');
+
+    await expect(readInfo({ bamPath, region })).rejects.toMatchObject({
+      code: ErrorKind.IndexMissing,
+      message: expect.stringMatching(/buildIndex/),
+    });
+    await expect(
+      readInfo({ bamPath, region, autoIndex: false }),
+    ).rejects.toMatchObject({ code: ErrorKind.IndexMissing });
+    expect(existsSync(`${bamPath}.bai`)).toBe(false);
+  });
+
+  it('builds the index with a warning and goes on', async () => {
+    const bamPath = await unindexedCopy('built.bam');
+    const records: LogRecord[] = [];
+    setLogCallback((record) => records.push(record));
+
+    expect(await readInfo({ bamPath, region, autoIndex: true })).toEqual(
+      await readInfo({ bamPath: simpleBamPath, region }),
+    );
+    expect(existsSync(`${bamPath}.bai`)).toBe(true);
+    await vi.waitFor(() =>
+      expect(records).toContainEqual({
+        level: 'warn',
+        message: expect.stringMatching(/has no index; building one/),
+      }),
+    );
+  });
+
+  it('applies to NanalogueBam', async () => {
+    const bamPath = await unindexedCopy('handle.bam');
+
+    expect(() => new NanalogueBam({ bamPath })).toThrow(/has no index/);
+    const bam = new NanalogueBam({ bamPath, autoIndex: true });
+    expect(await bam.readInfo({ region })).toEqual(
+      await readInfo({ bamPath: simpleBamPath, region }),
+    );
+  });
+
+  it('cannot index an unsorted file', async () => {
+    const bamPath = join(tmpDir, 'unsorted.bam');
+    await exportBam(
+      {
+        bamPath: simpleBamPath,
+        regions: ['contig_00000:9000-10000', 'contig_00000:0-100'],
+      },
+      bamPath,
+    );
+
+    await expect(
+      readInfo({ bamPath, autoIndex: true }),
+    ).rejects.toMatchObject({ code: ErrorKind.InvalidData });
+  });
+});
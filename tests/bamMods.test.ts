@@ -1,13 +1,22 @@
 // Tests for bamMods function
 
+import { readFileSync } from 'node:fs';
 import { resolve } from 'node:path';
 import { describe, expect, it } from 'vitest';
 import {
+  type BamModRecord,
   bamMods,
+  bamModsJson,
+  bamModsTyped,
   type MappedBamModRecord,
+  type ModCall,
   type UnmappedBamModRecord,
 } from '../index';
-import { getExampleBamPath, loadExpectedJson } from './helpers';
+import {
+  getExampleBamPath,
+  getExpectedOutputPath,
+  loadExpectedJson,
+} from './helpers';
 
 const getTestDataPath = (relativePath: string) =>
   resolve(__dirname, 'data', relativePath);
@@ -33,6 +42,22 @@ describe('bamMods expected output comparison', () => {
     const expected = loadExpectedJson('example_7_bam_mods.json');
     expect(result).toEqual(expected);
   });
+
+  it.each([1, 3, 7])(
+    'test_example_%i_bam_bam_mods_with_mod_data',
+    async (example) => {
+      const bamPath = getExampleBamPath(`example_${example}.bam`);
+      const expectedPath = (suffix: string) =>
+        getExpectedOutputPath(`example_${example}_bam_mods${suffix}.json`);
+
+      expect(await bamModsJson({ bamPath })).toBe(
+        readFileSync(expectedPath(''), 'utf-8'),
+      );
+      expect(await bamModsJson({ bamPath, reportModData: true })).toBe(
+        readFileSync(expectedPath('_with_mod_data'), 'utf-8'),
+      );
+    },
+  );
 });
 
 describe('bamMods', () => {
@@ -44,6 +69,25 @@ describe('bamMods', () => {
     expect(result.length).toBeGreaterThan(0);
   });
 
+  it('filters by minMods after the modification filters', async () => {
+    const result = await bamMods({ bamPath: testBamPath, minMods: 1 });
+    expect(result.map((r) => r.read_id)).toEqual([
+      'a4f36092-b4d5-47a9-813e-c22c3b477a0c',
+      'fffffff1-10d2-49cb-8ca3-e8d48979001b',
+      'a4f36092-b4d5-47a9-813e-c22c3b477a0c',
+    ]);
+
+    // The only modified call of the reverse read has a probability below 0.8
+    const strict = await bamMods({
+      bamPath: testBamPath,
+      minMods: 1,
+      minModQual: 204,
+    });
+    expect(strict.map((r) => r.read_id)).not.toContain(
+      'fffffff1-10d2-49cb-8ca3-e8d48979001b',
+    );
+  });
+
   it('returns records with expected structure for mapped reads', async () => {
     const result = await bamMods({ bamPath: testBamPath });
     const mappedRecords = result.filter(
@@ -132,3 +176,87 @@ describe('bamMods', () => {
     }
   });
 });
+
+/** Flattens bamMods records into one entry per modification call. */
+function flattenCalls(records: BamModRecord[]): ModCall[] {
+  return records.flatMap((record) =>
+    record.mod_table.flatMap((entry) =>
+      entry.data.map(([queryPosition, referencePosition, mlValue]) => ({
+        read_id: record.read_id,
+        query_position: queryPosition,
+        ...(referencePosition === -1 ? {} : { reference_position: referencePosition }),
+        mod_code: entry.mod_code,
+        canonical_base: entry.base,
+        strand: entry.is_strand_plus ? ('+' as const) : ('-' as const),
+        ml_value: mlValue,
+        probability: mlValue / 255,
+      })),
+    ),
+  );
+}
+
+describe('bamModsTyped', () => {
+  it('returns the calls of bamMods, one object per call', async () => {
+    const bamPath = getExampleBamPath('example_1.bam');
+
+    const calls = await bamModsTyped({ bamPath });
+
+    expect(calls).toEqual(flattenCalls(await bamMods({ bamPath })));
+  });
+
+  it('applies modification filters like bamMods', async () => {
+    const options = {
+      bamPath: getExampleBamPath('example_pynanalogue_1.bam'),
+      minModQual: 150,
+      trimReadEndsMod: 5,
+    };
+
+    const calls = await bamModsTyped(options);
+
+    expect(calls.length).toBeGreaterThan(0);
+    expect(calls).toEqual(flattenCalls(await bamMods(options)));
+    for (const call of calls) {
+      expect(call.ml_value).toBeGreaterThanOrEqual(150);
+      expect(call.probability).toBeCloseTo(call.ml_value / 255);
+    }
+  });
+
+  it('counts reads, not calls, for limit', async () => {
+    const bamPath = getExampleBamPath('example_1.bam');
+
+    const calls = await bamModsTyped({ bamPath, limit: 1 });
+
+    expect(calls.length).toBeGreaterThan(1);
+    expect(new Set(calls.map((c) => c.read_id)).size).toBe(1);
+  });
+});
+
+describe('bamModsJson', () => {
+  const bamPath = getTestDataPath('examples/example_1.bam');
+
+  it('returns the reads of bamMods as one line each', async () => {
+    const json = await bamModsJson({ bamPath });
+    const reads = await bamMods({ bamPath });
+
+    expect(JSON.parse(json)).toEqual(reads);
+    expect(json.trimEnd().split('\n')).toHaveLength(reads.length + 2);
+  });
+
+  it('indents each read with pretty', async () => {
+    const json = await bamModsJson({ bamPath, pretty: true });
+
+    expect(JSON.parse(json)).toEqual(await bamMods({ bamPath }));
+    expect(json).toContain('\n  "alignment_type": "primary_forward",\n');
+    expect(await bamModsJson({ bamPath, pretty: false })).toBe(
+      await bamModsJson({ bamPath }),
+    );
+  });
+
+  it('applies filters and pagination like bamMods', async () => {
+    const options = { bamPath, region: 'dummyIII', limit: 1 };
+
+    expect(JSON.parse(await bamModsJson(options))).toEqual(
+      await bamMods(options),
+    );
+  });
+});
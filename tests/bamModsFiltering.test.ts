@@ -7,7 +7,12 @@ import { tmpdir } from 'node:os';
 import { join } from 'node:path';
 import { afterAll, beforeAll, describe, expect, it } from 'vitest';
 
-import { type BamModRecord, bamMods } from '../index';
+import {
+  type BamModRecord,
+  bamMods,
+  bamModsTyped,
+  ErrorKind,
+} from '../index';
 import {
   createInputOptions,
   createSimpleBam,
@@ -89,6 +94,18 @@ describe('TestInputBamFiltering', () => {
     expect(resultFiltered2.length).toBe(0);
   });
 
+  it('test_mapq_max', async () => {
+    const base = createInputOptions(simpleBamPath);
+
+    const resultAll = await bamMods(base);
+    const resultInRange = await bamMods({ ...base, mapqMax: 20 });
+    const resultAbove = await bamMods({ ...base, mapqFilter: 21 });
+
+    expect(resultInRange.length).toBeGreaterThan(0);
+    expect(resultInRange.length + resultAbove.length).toBe(resultAll.length);
+    expect(resultInRange.every((r) => r.alignment_type !== 'unmapped')).toBe(true);
+  });
+
   it.each([
     [1.0, 1.0],
     [0.5, 0.5],
@@ -399,6 +416,55 @@ describe('TestTagFiltering', () => {
 
     expect(total76792 + totalT).toBe(totalAll);
   });
+
+  it('test_tags_filter', async () => {
+    const base = createInputOptions(twoModsBamPath);
+    const resultAll = await bamMods(base);
+
+    // Both tags keep every call
+    const resultBoth = await bamMods({ ...base, tags: ['T', '76792'] });
+    expect(getTotalModTableCount(resultBoth)).toBe(
+      getTotalModTableCount(resultAll),
+    );
+
+    // A single tag, or a tag absent from the file, matches `tag`
+    const resultT = await bamMods({ ...base, tag: 'T' });
+    expect(await bamMods({ ...base, tags: ['T'] })).toEqual(resultT);
+    const resultTm = await bamMods({ ...base, tags: ['T', 'm'] });
+    expect(new Set(getUniqueModCodes(resultTm))).toEqual(new Set(['T']));
+    expect(getTotalModTableCount(resultTm)).toBe(
+      getTotalModTableCount(resultT),
+    );
+  });
+
+  it('test_mod_thresholds_filter', async () => {
+    const base = createInputOptions(twoModsBamPath);
+    const calls = await bamModsTyped(base);
+    const result = await bamModsTyped({ ...base, modThresholds: { T: 200 } });
+
+    // Calls of T below the threshold go, those of 76792 are unaffected
+    const keptT = calls.filter((c) => c.mod_code === 'T' && c.ml_value >= 200);
+    const other = calls.filter((c) => c.mod_code === '76792');
+    expect(result.filter((c) => c.mod_code === 'T')).toEqual(
+      keptT.map((c) => ({ ...c, threshold: 200 })),
+    );
+    expect(result.filter((c) => c.mod_code === '76792')).toEqual(other);
+
+    // mod_table entries of T carry the threshold
+    const records = await bamMods({ ...base, modThresholds: { T: 200 } });
+    for (const entry of records.flatMap((r) => r.mod_table)) {
+      expect(entry.threshold).toBe(entry.mod_code === 'T' ? 200 : undefined);
+    }
+    expect(getTotalModTableCount(records)).toBe(keptT.length + other.length);
+  });
+
+  it('test_mod_thresholds_invalid_code', async () => {
+    const base = createInputOptions(twoModsBamPath);
+
+    await expect(
+      bamMods({ ...base, modThresholds: { '': 200 } }),
+    ).rejects.toMatchObject({ code: ErrorKind.InvalidOptions });
+  });
 });
 
 describe('TestPaginationWithFiltering', () => {
@@ -0,0 +1,64 @@
+// Tests for buildIndex, which creates the .bai index of a BAM file
+
+import { copyFile, mkdtemp, rm, writeFile } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import { buildIndex, exportBam, readInfo } from '../index';
+import { createSimpleBam } from './fixtures';
+
+describe('buildIndex', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-index-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('makes an unindexed BAM file queryable', async () => {
+    const bamPath = join(tmpDir, 'unindexed.bam');
+    await copyFile(simpleBamPath, bamPath);
+
+    await expect(
+      readInfo({ bamPath, region: 'contig_00000:0-100' }),
+    ).rejects.toThrow(/has no index; create one with buildIndex/);
+    await buildIndex(bamPath);
+
+    expect(await readInfo({ bamPath, region: 'contig_00000:0-100' })).toEqual(
+      await readInfo({ bamPath: simpleBamPath, region: 'contig_00000:0-100' }),
+    );
+  });
+
+  it('explains that an unsorted BAM file cannot be indexed', async () => {
+    const bamPath = join(tmpDir, 'unsorted.bam');
+    await exportBam(
+      {
+        bamPath: simpleBamPath,
+        regions: ['contig_00000:9000-10000', 'contig_00000:0-100'],
+      },
+      bamPath,
+    );
+
+    await expect(buildIndex(bamPath)).rejects.toThrow(
+      /records are not sorted by coordinate/,
+    );
+  });
+
+  it('rejects a file that is not a BAM file', async () => {
+    const path = join(tmpDir, 'reads.txt');
+    await writeFile(path, 'not a bam file\n');
+
+    await expect(buildIndex(path)).rejects.toThrow(/only BAM files can be indexed/);
+  });
+
+  it('raises an error for a missing file', async () => {
+    await expect(buildIndex('/nonexistent/file.bam')).rejects.toThrow(
+      /Failed to index BAM/,
+    );
+  });
+});
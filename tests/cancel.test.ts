@@ -0,0 +1,120 @@
+// Tests for cancelling windowReads with a CancelToken
+
+import { existsSync } from 'node:fs';
+import { mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import {
+  CancelToken,
+  NanalogueBam,
+  type WindowOptions,
+  windowReads,
+} from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM } from './fixtures';
+
+describe('CancelToken', () => {
+  it('starts uncancelled and stays cancelled once tripped', () => {
+    const token = new CancelToken();
+    expect(token.isCancelled).toBe(false);
+
+    token.cancel();
+    token.cancel();
+    expect(token.isCancelled).toBe(true);
+  });
+});
+
+describe('windowReads cancellation', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-cancel-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('rejects with a Cancelled error when cancelled mid-run', async () => {
+    const cancelToken = new CancelToken();
+    const pending = windowReads({
+      bamPath: simpleBamPath,
+      win: 5,
+      step: 1,
+      cancelToken,
+    });
+    cancelToken.cancel();
+
+    await expect(pending).rejects.toMatchObject({
+      code: 'Cancelled',
+      message: 'Cancelled',
+    });
+  });
+
+  it('removes a partly written outputPath when cancelled', async () => {
+    const cancelToken = new CancelToken();
+    const outputPath = join(tmpDir, 'cancelled.tsv');
+    const pending = windowReads({
+      bamPath: simpleBamPath,
+      win: 5,
+      step: 1,
+      outputFormat: 'tsv',
+      outputPath,
+      cancelToken,
+    });
+    cancelToken.cancel();
+
+    await expect(pending).rejects.toThrow(/^Cancelled$/);
+    expect(existsSync(outputPath)).toBe(false);
+  });
+
+  it('rejects straight away with an already-cancelled token', async () => {
+    const cancelToken = new CancelToken();
+    cancelToken.cancel();
+
+    await expect(
+      windowReads({ bamPath: EXAMPLE_1_BAM, win: 2, step: 1, cancelToken }),
+    ).rejects.toThrow(/^Cancelled$/);
+  });
+
+  it('returns normal output with an untripped token', async () => {
+    const cancelToken = new CancelToken();
+    const withToken = await windowReads({
+      bamPath: EXAMPLE_1_BAM,
+      win: 2,
+      step: 1,
+      cancelToken,
+    });
+    const withoutToken = await windowReads({
+      bamPath: EXAMPLE_1_BAM,
+      win: 2,
+      step: 1,
+    });
+
+    expect(JSON.parse(withToken)).toEqual(JSON.parse(withoutToken));
+  });
+
+  it('leaves a NanalogueBam usable after a cancelled query', async () => {
+    const bam = new NanalogueBam({ bamPath: simpleBamPath });
+    const cancelToken = new CancelToken();
+    const pending = bam.windowReads({ win: 5, step: 1, cancelToken });
+    cancelToken.cancel();
+
+    await expect(pending).rejects.toThrow(/^Cancelled$/);
+    const result = JSON.parse(await bam.windowReads({ win: 5, step: 1, limit: 2 }));
+    expect(result).toHaveLength(2);
+  });
+
+  it('rejects a cancelToken that is not a CancelToken', async () => {
+    await expect(
+      windowReads({
+        bamPath: EXAMPLE_1_BAM,
+        win: 2,
+        step: 1,
+        cancelToken: {},
+      } as unknown as WindowOptions),
+    ).rejects.toThrow(/instanceof class `CancelToken`/);
+  });
+});
@@ -0,0 +1,128 @@
+// Tests for setMaxConcurrency, which limits how many queries read at once
+
+import { spawnSync } from 'node:child_process';
+import { mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join, resolve } from 'node:path';
+import { afterAll, afterEach, beforeAll, describe, expect, it } from 'vitest';
+import {
+  NanalogueBam,
+  readInfo,
+  readInfoStream,
+  setMaxConcurrency,
+} from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM } from './fixtures';
+
+const INDEX_PATH = resolve(__dirname, '..', 'index.js');
+
+/** Resolves to whether `promise` settles within `ms` milliseconds. */
+async function settlesWithin(
+  promise: Promise<unknown>,
+  ms: number,
+): Promise<boolean> {
+  const timeout = new Promise<boolean>((r) => setTimeout(() => r(false), ms));
+  return Promise.race([promise.then(() => true), timeout]);
+}
+
+describe('setMaxConcurrency', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-concurrency-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  afterEach(() => {
+    setMaxConcurrency();
+  });
+
+  it('rejects a limit of 0', () => {
+    expect(() => setMaxConcurrency(0)).toThrow(
+      /maxConcurrency must be at least 1/,
+    );
+  });
+
+  it('runs queries started together under a limit of 1', async () => {
+    setMaxConcurrency(1);
+    const regions = ['dummyI', 'dummyII', 'dummyIII'];
+    const results = await Promise.all(
+      regions.map((region) => readInfo({ bamPath: EXAMPLE_1_BAM, region })),
+    );
+
+    setMaxConcurrency();
+    const expected = await Promise.all(
+      regions.map((region) => readInfo({ bamPath: EXAMPLE_1_BAM, region })),
+    );
+    expect(results).toEqual(expected);
+  });
+
+  it('holds queries back while an open stream uses the only slot', async () => {
+    setMaxConcurrency(1);
+    const stream = readInfoStream({ bamPath: simpleBamPath });
+    await stream.next();
+
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+    const queries = Promise.all([
+      readInfo({ bamPath: EXAMPLE_1_BAM }),
+      bam.readInfo(),
+    ]);
+    expect(await settlesWithin(queries, 200)).toBe(false);
+
+    await stream.return();
+    const [reads, handleReads] = await queries;
+    expect(reads).toHaveLength(4);
+    expect(handleReads).toEqual(reads);
+  });
+
+  it('lets new queries past an open stream once the limit is removed', async () => {
+    setMaxConcurrency(1);
+    const stream = readInfoStream({ bamPath: simpleBamPath });
+    await stream.next();
+
+    setMaxConcurrency();
+    expect(await readInfo({ bamPath: EXAMPLE_1_BAM })).toHaveLength(4);
+    await stream.return();
+  });
+
+  it('frees the slot of a stream closed while it waits for one', async () => {
+    setMaxConcurrency(1);
+    const first = readInfoStream({ bamPath: simpleBamPath });
+    await first.next();
+    const waiting = readInfoStream({ bamPath: simpleBamPath });
+    const pending = waiting.next();
+
+    await waiting.return();
+    expect(await pending).toEqual({ done: true });
+    await first.return();
+    expect(await readInfo({ bamPath: EXAMPLE_1_BAM })).toHaveLength(4);
+  });
+
+  it('frees the slot of an abandoned stream once it is collected', () => {
+    // Run in a child with gc exposed, so that the stream can be collected
+    const simple = JSON.stringify({ bamPath: simpleBamPath });
+    const example = JSON.stringify({ bamPath: EXAMPLE_1_BAM });
+    const script = `
+      const m = require(${JSON.stringify(INDEX_PATH)});
+      (async () => {
+        m.setMaxConcurrency(1);
+        await m.readInfoStream(${simple}).next();
+        await new Promise((r) => setTimeout(r, 50));
+        global.gc();
+        const reads = await m.readInfo(${example});
+        process.stdout.write(String(reads.length));
+      })();
+    `;
+    const child = spawnSync(process.execPath, ['--expose-gc', '-e', script], {
+      encoding: 'utf8',
+      timeout: 10000,
+    });
+
+    expect(child.stderr).toBe('');
+    expect(child.stdout).toBe('4');
+  });
+});
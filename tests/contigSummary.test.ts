@@ -0,0 +1,85 @@
+// Tests for contigSummary, which counts the mapped records on each contig
+
+import { copyFile, mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import { contigSummary, NanalogueBam, peek, readInfo, type ReadOptions } from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM } from './fixtures';
+
+/** Counts the mapped reads of readInfo per contig. */
+async function countsFromReads(options: ReadOptions): Promise<Record<string, number>> {
+  const counts: Record<string, number> = {};
+  for (const read of await readInfo(options)) {
+    if (read.alignment_type === 'unmapped') continue;
+    counts[read.contig] = (counts[read.contig] ?? 0) + 1;
+  }
+  return counts;
+}
+
+describe('contigSummary', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-contigs-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('lists every contig of the header with its length', async () => {
+    expect(await contigSummary({ bamPath: EXAMPLE_1_BAM })).toEqual([
+      { contig: 'dummyI', length: 22, n_reads: 1 },
+      { contig: 'dummyII', length: 48, n_reads: 1 },
+      { contig: 'dummyIII', length: 76, n_reads: 1 },
+    ]);
+  });
+
+  it('counts the same reads from the index as readInfo', async () => {
+    const summary = await contigSummary({ bamPath: simpleBamPath });
+    const { contigs } = await peek({ bamPath: simpleBamPath });
+    const counts = await countsFromReads({ bamPath: simpleBamPath });
+
+    expect(summary.map((c) => c.contig).sort()).toEqual(Object.keys(contigs).sort());
+    for (const { contig, length, n_reads } of summary) {
+      expect(length).toBe(contigs[contig]);
+      expect(n_reads).toBe(counts[contig] ?? 0);
+    }
+  });
+
+  it('streams a BAM file without an index', async () => {
+    const bamPath = join(tmpDir, 'unindexed.bam');
+    await copyFile(simpleBamPath, bamPath);
+
+    expect(await contigSummary({ bamPath })).toEqual(
+      await contigSummary({ bamPath: simpleBamPath }),
+    );
+  });
+
+  it.each([
+    { mapqFilter: 15, readFilter: 'primary_forward' },
+    { region: 'contig_00000:0-1000' },
+    { minSeqLen: 1, limit: 50 },
+  ])('counts the reads passing %o', async (options) => {
+    const query = { bamPath: simpleBamPath, ...options };
+    const summary = await contigSummary(query);
+    const counts = await countsFromReads(query);
+
+    expect(summary).toHaveLength(2);
+    for (const { contig, n_reads } of summary) {
+      expect(n_reads).toBe(counts[contig] ?? 0);
+    }
+  });
+
+  it('works on a NanalogueBam', async () => {
+    const bam = new NanalogueBam({ bamPath: simpleBamPath });
+
+    expect(await bam.contigSummary()).toEqual(await contigSummary({ bamPath: simpleBamPath }));
+    expect(await bam.contigSummary({ mapqFilter: 15 })).toEqual(
+      await contigSummary({ bamPath: simpleBamPath, mapqFilter: 15 }),
+    );
+  });
+});
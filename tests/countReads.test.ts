@@ -0,0 +1,38 @@
+// Tests for countReads, which counts the reads passing the filters
+
+import { describe, expect, it } from 'vitest';
+import { countReads, NanalogueBam, readInfo } from '../index';
+import { EXAMPLE_1_BAM, EXAMPLE_ZERO_LEN_BAM } from './fixtures';
+
+describe('countReads', () => {
+  it('counts the reads readInfo returns', async () => {
+    const optionsList = [
+      { bamPath: EXAMPLE_1_BAM },
+      { bamPath: EXAMPLE_1_BAM, region: 'dummyIII' },
+      { bamPath: EXAMPLE_1_BAM, readFilter: 'primary_reverse,unmapped' },
+      { bamPath: EXAMPLE_1_BAM, minSeqLen: 40 },
+      { bamPath: EXAMPLE_1_BAM, offset: 1, limit: 2 },
+      { bamPath: EXAMPLE_ZERO_LEN_BAM },
+      { bamPath: EXAMPLE_ZERO_LEN_BAM, includeZeroLen: true },
+    ];
+    for (const options of optionsList) {
+      expect(await countReads(options)).toBe((await readInfo(options)).length);
+    }
+  });
+
+  it('counts 0 past the last read', async () => {
+    expect(await countReads({ bamPath: EXAMPLE_1_BAM, offset: 100 })).toBe(0);
+  });
+
+  it('rejects invalid options', async () => {
+    await expect(
+      countReads({ bamPath: EXAMPLE_1_BAM, limit: 0 }),
+    ).rejects.toThrow(/limit must be a positive integer/);
+  });
+
+  it('counts reads on a NanalogueBam', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+    expect(await bam.countReads()).toBe(4);
+    expect(await bam.countReads({ region: 'dummyI' })).toBe(1);
+  });
+});
@@ -0,0 +1,120 @@
+// Tests for coverage, which counts the reads spanning each position of a region
+
+import { mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import {
+  coverage,
+  type MappedReadInfo,
+  NanalogueBam,
+  readInfo,
+  type ReadOptions,
+} from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM } from './fixtures';
+
+/** Computes depths from the read spans of readInfo in JS, as coverage does in Rust. */
+async function depthsFromReads(
+  options: ReadOptions,
+  start: number,
+  end: number,
+  binSize = 1,
+) {
+  const reads = (await readInfo(options)).filter(
+    (r): r is MappedReadInfo => r.alignment_type !== 'unmapped',
+  );
+  const depths: number[] = [];
+  for (let pos = start; pos < end; pos += 1) {
+    depths.push(
+      reads.filter((r) => r.reference_start <= pos && pos < r.reference_end).length,
+    );
+  }
+  const records = [];
+  for (let i = 0; i < depths.length; i += binSize) {
+    const bin = depths.slice(i, i + binSize);
+    records.push({
+      pos: start + i,
+      depth: bin.reduce((sum, v) => sum + v, 0) / bin.length,
+    });
+  }
+  return records;
+}
+
+describe('coverage', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-coverage-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('returns one depth per position of the region', async () => {
+    const depths = await coverage({ bamPath: EXAMPLE_1_BAM, region: 'dummyIII:20-40' });
+
+    expect(depths).toHaveLength(20);
+    expect(depths[0]).toEqual({ pos: 20, depth: 0 });
+    expect(depths).toEqual(
+      await depthsFromReads({ bamPath: EXAMPLE_1_BAM, region: 'dummyIII:20-40' }, 20, 40),
+    );
+  });
+
+  it('covers the whole contig when the region has no range', async () => {
+    const depths = await coverage({ bamPath: simpleBamPath, region: 'contig_00000' }, 1000);
+
+    expect(depths.map((d) => d.pos)).toEqual([
+      0, 1000, 2000, 3000, 4000, 5000, 6000, 7000, 8000, 9000,
+    ]);
+  });
+
+  it.each([
+    [{ region: 'contig_00000:1000-1200' }, 1],
+    [{ region: 'contig_00001:3000-3100', fullRegion: true }, 3],
+    [{ region: 'contig_00001:0-2000', mapqFilter: 15, limit: 20 }, 100],
+  ])('agrees with the read spans of readInfo for %o in bins of %i', async (options, binSize) => {
+    const query = { bamPath: simpleBamPath, ...options };
+    const [start, end] = options.region.split(':')[1].split('-').map(Number);
+    const depths = await coverage(query, binSize);
+    const expected = await depthsFromReads(query, start, end, binSize);
+
+    expect(depths.map((d) => d.pos)).toEqual(expected.map((d) => d.pos));
+    depths.forEach((d, i) => expect(d.depth).toBeCloseTo(expected[i].depth));
+  });
+
+  it('shortens the last bin to the end of the region', async () => {
+    const depths = await coverage({ bamPath: EXAMPLE_1_BAM, region: 'dummyIII:20-45' }, 10);
+
+    expect(depths.map((d) => d.pos)).toEqual([20, 30, 40]);
+    expect(depths[2].depth).toBe(1);
+  });
+
+  it('requires a region', async () => {
+    await expect(coverage({ bamPath: EXAMPLE_1_BAM })).rejects.toThrow(
+      /region parameter is required for coverage/,
+    );
+  });
+
+  it('rejects multiple regions', async () => {
+    await expect(
+      coverage({ bamPath: EXAMPLE_1_BAM, regions: ['dummyI', 'dummyIII'] }),
+    ).rejects.toThrow(/coverage takes a single region/);
+  });
+
+  it('rejects a bin size of zero', async () => {
+    await expect(
+      coverage({ bamPath: EXAMPLE_1_BAM, region: 'dummyIII' }, 0),
+    ).rejects.toThrow(/bin_size must be at least 1/);
+  });
+
+  it('works on a NanalogueBam', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+
+    expect(await bam.coverage({ region: 'dummyIII' }, 5)).toEqual(
+      await coverage({ bamPath: EXAMPLE_1_BAM, region: 'dummyIII' }, 5),
+    );
+  });
+});
@@ -0,0 +1,94 @@
+// Tests for reading CRAM input, decoded with the referenceFasta option
+
+import { describe, expect, it } from 'vitest';
+import {
+  bamMods,
+  contigSummary,
+  NanalogueBam,
+  peek,
+  readInfo,
+  seqTable,
+  windowReads,
+} from '../index';
+import { EXAMPLE_1_BAM, EXAMPLE_1_CRAM, EXAMPLE_REFERENCE } from './fixtures';
+
+const cram = { bamPath: EXAMPLE_1_CRAM, referenceFasta: EXAMPLE_REFERENCE };
+
+describe('CRAM input', () => {
+  it('gives the same reads and calls as the BAM it was made from', async () => {
+    expect(await readInfo(cram)).toEqual(
+      await readInfo({ bamPath: EXAMPLE_1_BAM }),
+    );
+    expect(await bamMods(cram)).toEqual(
+      await bamMods({ bamPath: EXAMPLE_1_BAM }),
+    );
+    expect(await peek(cram)).toEqual(await peek({ bamPath: EXAMPLE_1_BAM }));
+  });
+
+  it('decodes sequences against the reference', async () => {
+    const region = 'dummyIII:0-50';
+
+    expect(await seqTable({ ...cram, region })).toBe(
+      await seqTable({ bamPath: EXAMPLE_1_BAM, region }),
+    );
+  });
+
+  it('fetches regions through the CRAM index', async () => {
+    const reads = await readInfo({ ...cram, region: 'dummyII' });
+
+    expect(reads).toEqual(
+      await readInfo({ bamPath: EXAMPLE_1_BAM, region: 'dummyII' }),
+    );
+    expect(reads).toHaveLength(1);
+  });
+
+  it('windows reads as from the BAM', async () => {
+    expect(await windowReads({ ...cram, win: 2, step: 1 })).toBe(
+      await windowReads({ bamPath: EXAMPLE_1_BAM, win: 2, step: 1 }),
+    );
+  });
+
+  it('counts reads per contig by reading the records', async () => {
+    expect(await contigSummary(cram)).toEqual(
+      await contigSummary({ bamPath: EXAMPLE_1_BAM }),
+    );
+  });
+
+  it('rejects CRAM input without referenceFasta', async () => {
+    await expect(readInfo({ bamPath: EXAMPLE_1_CRAM })).rejects.toThrow(
+      /referenceFasta is required to read CRAM input/,
+    );
+    await expect(peek({ bamPath: EXAMPLE_1_CRAM })).rejects.toThrow(
+      /referenceFasta is required/,
+    );
+    expect(() => new NanalogueBam({ bamPath: EXAMPLE_1_CRAM })).toThrow(
+      /referenceFasta is required/,
+    );
+  });
+
+  it('rejects a missing reference file', async () => {
+    await expect(
+      readInfo({ ...cram, referenceFasta: `${EXAMPLE_REFERENCE}.missing` }),
+    ).rejects.toThrow(/Failed to load reference FASTA/);
+  });
+
+  it('ignores referenceFasta for BAM input', async () => {
+    expect(
+      await readInfo({
+        bamPath: EXAMPLE_1_BAM,
+        referenceFasta: EXAMPLE_REFERENCE,
+      }),
+    ).toEqual(await readInfo({ bamPath: EXAMPLE_1_BAM }));
+  });
+
+  it('works on a NanalogueBam opened with the reference', async () => {
+    const bam = new NanalogueBam(cram);
+
+    expect(await bam.readInfo({ region: 'dummyIII' })).toEqual(
+      await readInfo({ bamPath: EXAMPLE_1_BAM, region: 'dummyIII' }),
+    );
+    expect(await bam.contigSummary()).toEqual(
+      await contigSummary({ bamPath: EXAMPLE_1_BAM }),
+    );
+  });
+});
@@ -0,0 +1,51 @@
+// Tests for the dedupe option, which keeps only the first record of each read id
+
+import { describe, expect, it } from 'vitest';
+import { bamMods, countReads, NanalogueBam, readInfo } from '../index';
+import { EXAMPLE_1_BAM } from './fixtures';
+
+// example_1.bam holds this read twice: mapped to dummyIII, then unmapped
+const repeatedRead = 'a4f36092-b4d5-47a9-813e-c22c3b477a0c';
+
+describe('dedupe', () => {
+  it('keeps the first record of each read id', async () => {
+    const all = await readInfo({ bamPath: EXAMPLE_1_BAM });
+    const deduped = await readInfo({ bamPath: EXAMPLE_1_BAM, dedupe: true });
+
+    expect(all.filter((r) => r.read_id === repeatedRead)).toHaveLength(2);
+    expect(deduped.map((r) => r.read_id)).toEqual([
+      ...new Set(all.map((r) => r.read_id)),
+    ]);
+    const kept = deduped.find((r) => r.read_id === repeatedRead);
+    expect(kept?.alignment_type).toBe('primary_forward');
+  });
+
+  it('keeps every record when unset or false', async () => {
+    expect(await readInfo({ bamPath: EXAMPLE_1_BAM, dedupe: false })).toEqual(
+      await readInfo({ bamPath: EXAMPLE_1_BAM }),
+    );
+  });
+
+  it('counts each read once', async () => {
+    expect(await countReads({ bamPath: EXAMPLE_1_BAM })).toBe(4);
+    expect(await countReads({ bamPath: EXAMPLE_1_BAM, dedupe: true })).toBe(3);
+  });
+
+  it('drops repeats before pagination', async () => {
+    const options = { bamPath: EXAMPLE_1_BAM, dedupe: true };
+    const all = await bamMods(options);
+    const page = await bamMods({ ...options, offset: 1, limit: 5 });
+
+    expect(page.map((r) => r.read_id)).toEqual(
+      all.slice(1).map((r) => r.read_id),
+    );
+  });
+
+  it('starts afresh on each query of a NanalogueBam', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+    const first = await bam.readInfo({ dedupe: true });
+
+    expect(await bam.readInfo({ dedupe: true })).toEqual(first);
+    expect(first).toHaveLength(3);
+  });
+});
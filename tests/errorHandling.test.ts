@@ -16,49 +16,6 @@ import {
 } from '../index';
 import { createSimpleBam } from './fixtures';
 
-describe('TestZeroLengthReadGuard', () => {
-  let tmpDir: string;
-  let simpleBamPath: string;
-
-  beforeAll(async () => {
-    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-error-'));
-    simpleBamPath = await createSimpleBam(tmpDir);
-  });
-
-  afterAll(async () => {
-    await rm(tmpDir, { recursive: true });
-  });
-
-  it('readInfo with includeZeroLen=true raises error', async () => {
-    await expect(
-      readInfo({
-        bamPath: simpleBamPath,
-        includeZeroLen: true,
-      }),
-    ).rejects.toThrow(/include_zero_len.*not.*supported/i);
-  });
-
-  it('bamMods with includeZeroLen=true raises error', async () => {
-    await expect(
-      bamMods({
-        bamPath: simpleBamPath,
-        includeZeroLen: true,
-      }),
-    ).rejects.toThrow(/include_zero_len.*not.*supported/i);
-  });
-
-  it('windowReads with includeZeroLen=true raises error', async () => {
-    await expect(
-      windowReads({
-        bamPath: simpleBamPath,
-        win: 5,
-        step: 2,
-        includeZeroLen: true,
-      }),
-    ).rejects.toThrow(/include_zero_len.*not.*supported/i);
-  });
-});
-
 describe('TestWindowReadsValidation', () => {
   let tmpDir: string;
   let simpleBamPath: string;
@@ -321,6 +278,29 @@ describe('TestInputValidation', () => {
     ).rejects.toThrow(/invalid.*tag/i);
   });
 
+  it('tag and tags together raise error', async () => {
+    await expect(
+      readInfo({ bamPath: simpleBamPath, tag: 'm', tags: ['m', 'h'] }),
+    ).rejects.toThrow(/tag and tags cannot be used together/);
+  });
+
+  it('empty tags raise error', async () => {
+    await expect(
+      readInfo({ bamPath: simpleBamPath, tags: [] }),
+    ).rejects.toThrow(/tags must contain at least one tag/);
+  });
+
+  it('invalid value in tags raises error for windowReads', async () => {
+    await expect(
+      windowReads({
+        bamPath: simpleBamPath,
+        win: 5,
+        step: 2,
+        tags: ['m', 'bogus_tag_value'],
+      }),
+    ).rejects.toThrow(/invalid.*tag/i);
+  });
+
   it('invalid tag value raises error for seqTable', async () => {
     await expect(
       seqTable({
@@ -0,0 +1,148 @@
+// Tests for the ErrorKind codes of errors thrown by the bindings
+
+import { copyFile, mkdtemp, rm, writeFile } from 'node:fs/promises';
+import { createRequire } from 'node:module';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import {
+  CancelToken,
+  ErrorKind,
+  NanalogueBam,
+  readInfo,
+  readInfoStream,
+  setMaxConcurrency,
+  windowReads,
+} from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM } from './fixtures';
+
+describe('ErrorKind', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-error-kind-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('marks invalid options', async () => {
+    await expect(
+      readInfo({ bamPath: EXAMPLE_1_BAM, limit: 0 }),
+    ).rejects.toMatchObject({
+      code: ErrorKind.InvalidOptions,
+      message: 'limit must be a positive integer',
+    });
+  });
+
+  it('marks a region missing from the header', async () => {
+    await expect(
+      readInfo({ bamPath: EXAMPLE_1_BAM, region: 'chrAbsent:1-10' }),
+    ).rejects.toMatchObject({ code: ErrorKind.InvalidRegion });
+  });
+
+  it('marks a missing BAM file', async () => {
+    await expect(
+      readInfo({ bamPath: join(tmpDir, 'absent.bam') }),
+    ).rejects.toMatchObject({
+      code: ErrorKind.FileNotFound,
+      message: expect.stringMatching(/^Failed to open BAM/),
+    });
+  });
+
+  it('marks a BAM file without an index', async () => {
+    const unindexed = join(tmpDir, 'unindexed.bam');
+    await copyFile(EXAMPLE_1_BAM, unindexed);
+
+    await expect(readInfo({ bamPath: unindexed })).rejects.toMatchObject({
+      code: ErrorKind.IndexMissing,
+    });
+  });
+
+  it('marks a BED file that cannot be read', async () => {
+    await expect(
+      readInfo({ bamPath: EXAMPLE_1_BAM, regionBed: join(tmpDir, 'absent.bed') }),
+    ).rejects.toMatchObject({ code: ErrorKind.FileNotFound });
+
+    const bedPath = join(tmpDir, 'bad.bed');
+    await writeFile(bedPath, 'dummyI\tten\t20\n');
+    await expect(
+      readInfo({ bamPath: EXAMPLE_1_BAM, regionBed: bedPath }),
+    ).rejects.toMatchObject({ code: ErrorKind.InvalidRegion });
+  });
+
+  it('marks a cancelled call', async () => {
+    const cancelToken = new CancelToken();
+    const pending = windowReads({
+      bamPath: simpleBamPath,
+      win: 5,
+      step: 1,
+      cancelToken,
+    });
+    cancelToken.cancel();
+
+    await expect(pending).rejects.toMatchObject({
+      code: ErrorKind.Cancelled,
+      message: 'Cancelled',
+    });
+  });
+
+  it('marks errors thrown synchronously', () => {
+    expect(() => setMaxConcurrency(0)).toThrow(
+      expect.objectContaining({ code: ErrorKind.InvalidOptions }),
+    );
+    expect(
+      () => new NanalogueBam({ bamPath: join(tmpDir, 'absent.bam') }),
+    ).toThrow(expect.objectContaining({ code: ErrorKind.FileNotFound }));
+  });
+
+  it('marks errors of NanalogueBam methods and streams', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+    await expect(bam.readInfo({ offset: -1 })).rejects.toMatchObject({
+      code: ErrorKind.InvalidOptions,
+    });
+
+    const stream = readInfoStream({ bamPath: join(tmpDir, 'absent.bam') });
+    await expect(stream.next()).rejects.toMatchObject({
+      code: ErrorKind.FileNotFound,
+    });
+  });
+
+  it('keeps the message free of the code in the stack', async () => {
+    const error = await readInfo({
+      bamPath: EXAMPLE_1_BAM,
+      limit: 0,
+    }).catch((e: Error) => e);
+
+    expect(error).toBeInstanceOf(Error);
+    expect((error as Error).stack).toMatch(
+      /^Error: limit must be a positive integer/,
+    );
+  });
+
+  it('keeps instances of the classes recognisable', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+    const cancelToken = new CancelToken();
+
+    expect(bam).toBeInstanceOf(NanalogueBam);
+    expect(cancelToken).toBeInstanceOf(CancelToken);
+    expect(await bam.readInfo({ region: 'dummyI' })).toHaveLength(1);
+  });
+
+  it('marks the errors of every export of the generated bindings', () => {
+    // binding.js is regenerated by `napi build`; index.js wraps its exports
+    const require = createRequire(__filename);
+    const binding = require('../binding.js');
+    const exported = require('../index.js');
+
+    expect(Object.keys(exported).sort()).toEqual(Object.keys(binding).sort());
+    for (const name of Object.keys(binding)) {
+      if (typeof binding[name] === 'function') {
+        expect(exported[name]).not.toBe(binding[name]);
+      }
+    }
+  });
+});
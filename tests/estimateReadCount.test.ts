@@ -0,0 +1,62 @@
+// Tests for estimateReadCount, which counts query records from the index
+
+import { describe, expect, it, vi } from 'vitest';
+import { ErrorKind, estimateReadCount, NanalogueBam, readInfo } from '../index';
+import { EXAMPLE_1_BAM } from './fixtures';
+
+describe('estimateReadCount', () => {
+  it('counts every record without a region', async () => {
+    expect(await estimateReadCount({ bamPath: EXAMPLE_1_BAM })).toBe(4);
+  });
+
+  it('matches the progress of a full scan', async () => {
+    const counts: number[] = [];
+    await readInfo({
+      bamPath: EXAMPLE_1_BAM,
+      onProgress: (n) => counts.push(n),
+    });
+    const estimate = await estimateReadCount({ bamPath: EXAMPLE_1_BAM });
+
+    await vi.waitFor(() => expect(counts).toEqual([estimate]));
+  });
+
+  it('is exact for a region covering a whole contig', async () => {
+    const count = await estimateReadCount({
+      bamPath: EXAMPLE_1_BAM,
+      region: 'dummyII',
+    });
+
+    expect(count).toBe(1);
+  });
+
+  it('scales the count of a contig by the share a region covers', async () => {
+    const count = await estimateReadCount({
+      bamPath: EXAMPLE_1_BAM,
+      region: 'dummyIII:0-19',
+    });
+
+    expect(count).toBe(0);
+  });
+
+  it('adds up the counts of regions', async () => {
+    const count = await estimateReadCount({
+      bamPath: EXAMPLE_1_BAM,
+      regions: ['dummyI', 'dummyII'],
+    });
+
+    expect(count).toBe(2);
+  });
+
+  it('rejects a contig missing from the header', async () => {
+    await expect(
+      estimateReadCount({ bamPath: EXAMPLE_1_BAM, region: 'chrZ' }),
+    ).rejects.toMatchObject({ code: ErrorKind.InvalidRegion });
+  });
+
+  it('is a NanalogueBam method', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+
+    expect(await bam.estimateReadCount()).toBe(4);
+    expect(await bam.estimateReadCount({ region: 'dummyI' })).toBe(1);
+  });
+});
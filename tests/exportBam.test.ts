@@ -0,0 +1,145 @@
+// Tests for exportBam, which writes the reads passing the filters to a new BAM file
+
+import { existsSync } from 'node:fs';
+import { copyFile, mkdtemp, readFile, rm, writeFile } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import { bamMods, exportBam, NanalogueBam, readInfo } from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM } from './fixtures';
+
+describe('exportBam', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-export-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('writes the reads that readInfo returns', async () => {
+    const options = {
+      bamPath: simpleBamPath,
+      region: 'contig_00001',
+      minSeqLen: 100,
+    };
+    const outputPath = join(tmpDir, 'filtered.bam');
+
+    const result = await exportBam(options, outputPath);
+    const expected = await readInfo(options);
+    const exported = await readInfo({ bamPath: outputPath });
+
+    expect(result.recordsWritten).toBe(expected.length);
+    expect(exported).toEqual(expected);
+  });
+
+  it('keeps modification tags', async () => {
+    const outputPath = join(tmpDir, 'mods.bam');
+
+    await exportBam({ bamPath: simpleBamPath, region: 'contig_00000:0-2000' }, outputPath);
+
+    expect(await bamMods({ bamPath: outputPath })).toEqual(
+      await bamMods({ bamPath: simpleBamPath, region: 'contig_00000:0-2000' }),
+    );
+  });
+
+  it('indexes coordinate-sorted output', async () => {
+    const outputPath = join(tmpDir, 'indexed.bam');
+
+    const result = await exportBam({ bamPath: simpleBamPath, limit: 20 }, outputPath);
+
+    expect(result.indexPath).toBe(`${outputPath}.bai`);
+    expect(existsSync(`${outputPath}.bai`)).toBe(true);
+    expect(await readInfo({ bamPath: outputPath, region: 'contig_00000' })).toEqual(
+      await readInfo({ bamPath: simpleBamPath, region: 'contig_00000', limit: 20 }),
+    );
+  });
+
+  it('indexes output whose contigs are out of header order', async () => {
+    const outputPath = join(tmpDir, 'contigs.bam');
+
+    const result = await exportBam(
+      { bamPath: simpleBamPath, regions: ['contig_00001', 'contig_00000'] },
+      outputPath,
+    );
+
+    expect(result.indexPath).toBe(`${outputPath}.bai`);
+  });
+
+  it('does not index output that is out of coordinate order', async () => {
+    const outputPath = join(tmpDir, 'unsorted.bam');
+
+    const result = await exportBam(
+      {
+        bamPath: simpleBamPath,
+        regions: ['contig_00000:9000-10000', 'contig_00000:0-100'],
+      },
+      outputPath,
+    );
+
+    expect(result.recordsWritten).toBeGreaterThan(0);
+    expect(result.indexPath).toBeUndefined();
+    expect(existsSync(`${outputPath}.bai`)).toBe(false);
+  });
+
+  it('removes the index of an earlier output it replaces', async () => {
+    const outputPath = join(tmpDir, 'replaced.bam');
+    await exportBam({ bamPath: simpleBamPath, limit: 20 }, outputPath);
+    expect(existsSync(`${outputPath}.bai`)).toBe(true);
+
+    const result = await exportBam(
+      {
+        bamPath: simpleBamPath,
+        regions: ['contig_00000:9000-10000', 'contig_00000:0-100'],
+      },
+      outputPath,
+    );
+
+    expect(result.indexPath).toBeUndefined();
+    expect(existsSync(`${outputPath}.bai`)).toBe(false);
+  });
+
+  it('removes the output when reading fails partway', async () => {
+    const truncatedPath = join(tmpDir, 'truncated.bam');
+    const bytes = await readFile(simpleBamPath);
+    const half = Math.floor(bytes.length / 2);
+    await writeFile(truncatedPath, bytes.subarray(0, half));
+    await copyFile(`${simpleBamPath}.bai`, `${truncatedPath}.bai`);
+    const outputPath = join(tmpDir, 'partial.bam');
+    await writeFile(`${outputPath}.bai`, 'stale');
+
+    await expect(
+      exportBam({ bamPath: truncatedPath }, outputPath),
+    ).rejects.toThrow(/Failed to read BAM record/);
+    expect(existsSync(outputPath)).toBe(false);
+    expect(existsSync(`${outputPath}.bai`)).toBe(false);
+  });
+
+  it('works on a NanalogueBam', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+    const outputPath = join(tmpDir, 'handle.bam');
+
+    const result = await bam.exportBam({ region: 'dummyI' }, outputPath);
+
+    expect(result.recordsWritten).toBe(1);
+    expect(await readInfo({ bamPath: outputPath })).toEqual(
+      await bam.readInfo({ region: 'dummyI' }),
+    );
+  });
+
+  it('refuses to overwrite the input file', async () => {
+    await expect(exportBam({ bamPath: simpleBamPath }, simpleBamPath)).rejects.toThrow(
+      /outputPath must differ from bamPath/,
+    );
+  });
+
+  it('raises an error for an unwritable output path', async () => {
+    await expect(
+      exportBam({ bamPath: EXAMPLE_1_BAM }, '/nonexistent/dir/out.bam'),
+    ).rejects.toThrow(/Failed to create BAM file/);
+  });
+});
@@ -0,0 +1,133 @@
+// Tests for exportBedgraph, which writes the bins of windowAggregate to a
+// bedGraph file
+
+import { existsSync } from 'node:fs';
+import { mkdtemp, readFile, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import { exportBedgraph, NanalogueBam, windowAggregate } from '../index';
+import { createTwoModsBam, EXAMPLE_1_BAM } from './fixtures';
+
+/** Lines of a bedGraph file, split into their columns. */
+async function readBedgraph(path: string): Promise<string[][]> {
+  const text = await readFile(path, 'utf8');
+  return text
+    .trimEnd()
+    .split('\n')
+    .map((line) => line.split('\t'));
+}
+
+describe('exportBedgraph', () => {
+  let tmpDir: string;
+  let twoModsBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-export-bedgraph-'));
+    twoModsBamPath = await createTwoModsBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('writes the bins of windowAggregate', async () => {
+    const options = { bamPath: EXAMPLE_1_BAM, win: 10, step: 10 };
+    const outputPath = join(tmpDir, 'all.bedgraph');
+    const written = await exportBedgraph(options, outputPath);
+    const bins = await windowAggregate(options);
+
+    expect(written).toBe(bins.length);
+    const lines = await readBedgraph(outputPath);
+    expect(lines.map(([contig, start]) => `${contig}:${start}`)).toEqual(
+      bins.map((b) => `${b.contig}:${b.bin_start}`),
+    );
+    lines.forEach(([, , , value], i) => {
+      expect(Number(value)).toBeCloseTo(bins[i]?.mean ?? NaN, 12);
+    });
+    // The last bin of dummyIII ends at the end of the contig
+    expect(lines.at(-1)).toEqual(['dummyIII', '70', '76', '1']);
+  });
+
+  it('writes bins without windows as zero unless skipped', async () => {
+    const options = {
+      bamPath: EXAMPLE_1_BAM,
+      region: 'dummyIII:15-45',
+      win: 10,
+      step: 10,
+    };
+    const skipped = join(tmpDir, 'skipped.bedgraph');
+    const zeros = join(tmpDir, 'zeros.bedgraph');
+
+    expect(await exportBedgraph(options, skipped)).toBe(2);
+    expect(await exportBedgraph(options, zeros, false)).toBe(4);
+    expect(await readBedgraph(skipped)).toEqual([
+      ['dummyIII', '20', '30', '1'],
+      ['dummyIII', '30', '40', '1'],
+    ]);
+    expect(await readBedgraph(zeros)).toEqual([
+      ['dummyIII', '10', '20', '0'],
+      ['dummyIII', '20', '30', '1'],
+      ['dummyIII', '30', '40', '1'],
+      ['dummyIII', '40', '50', '0'],
+    ]);
+  });
+
+  it('converts a 1-based region to bedGraph coordinates', async () => {
+    const options = { bamPath: EXAMPLE_1_BAM, win: 10, step: 10 };
+    const zeroBased = join(tmpDir, 'zero-based.bedgraph');
+    const oneBased = join(tmpDir, 'one-based.bedgraph');
+    await exportBedgraph({ ...options, region: 'dummyIII:20-30' }, zeroBased);
+    await exportBedgraph(
+      { ...options, region: 'dummyIII:21-30', oneBased: true },
+      oneBased,
+    );
+
+    expect(await readBedgraph(oneBased)).toEqual(
+      await readBedgraph(zeroBased),
+    );
+  });
+
+  it('needs the calls of one modification', async () => {
+    const options = { bamPath: twoModsBamPath, win: 100, step: 100 };
+    const outputPath = join(tmpDir, 'two-mods.bedgraph');
+
+    await expect(exportBedgraph(options, outputPath)).rejects.toMatchObject({
+      code: 'InvalidOptions',
+    });
+    expect(existsSync(outputPath)).toBe(false);
+    expect(
+      await exportBedgraph({ ...options, tag: 'T' }, outputPath),
+    ).toBeGreaterThan(0);
+  });
+
+  it('rejects overlapping bins and windowReads output options', async () => {
+    const options = { bamPath: EXAMPLE_1_BAM, win: 10, step: 10 };
+    const outputPath = join(tmpDir, 'rejected.bedgraph');
+    await expect(
+      exportBedgraph({ ...options, step: 5 }, outputPath),
+    ).rejects.toMatchObject({ code: 'InvalidOptions' });
+    await expect(
+      exportBedgraph({ ...options, coordinate: 'read' }, outputPath),
+    ).rejects.toMatchObject({ code: 'InvalidOptions' });
+    await expect(
+      exportBedgraph({ ...options, outputFormat: 'tsv' }, outputPath),
+    ).rejects.toMatchObject({ code: 'InvalidOptions' });
+  });
+
+  it('writes the same file through a NanalogueBam', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+    const fromHandle = join(tmpDir, 'handle.bedgraph');
+    const fromPath = join(tmpDir, 'path.bedgraph');
+    await bam.exportBedgraph({ win: 20, step: 20 }, fromHandle, false);
+    await exportBedgraph(
+      { bamPath: EXAMPLE_1_BAM, win: 20, step: 20 },
+      fromPath,
+      false,
+    );
+
+    expect(await readFile(fromHandle, 'utf8')).toBe(
+      await readFile(fromPath, 'utf8'),
+    );
+  });
+});
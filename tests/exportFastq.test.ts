@@ -0,0 +1,122 @@
+// Tests for exportFastq, which writes the reads passing the filters as FASTQ
+
+import { mkdtemp, readFile, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { gunzipSync } from 'node:zlib';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import { ErrorKind, exportFastq, NanalogueBam, readInfo } from '../index';
+import {
+  EXAMPLE_1_BAM,
+  EXAMPLE_CIGAR_BAM,
+  EXAMPLE_FLAGS_BAM,
+} from './fixtures';
+
+/** Parses FASTQ text into its entries. */
+function parseFastq(text: string) {
+  const lines = text.trimEnd().split('\n');
+  const entries: { id: string; seq: string; qual: string }[] = [];
+  for (let i = 0; i < lines.length; i += 4) {
+    expect(lines[i + 2]).toBe('+');
+    entries.push({ id: lines[i], seq: lines[i + 1], qual: lines[i + 3] });
+  }
+  return entries;
+}
+
+describe('exportFastq', () => {
+  let tmpDir: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-fastq-'));
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('writes reverse-strand reads as they were sequenced', async () => {
+    const outputPath = join(tmpDir, 'cigar.fastq');
+
+    const result = await exportFastq(
+      { bamPath: EXAMPLE_CIGAR_BAM },
+      outputPath,
+    );
+    const entries = parseFastq(await readFile(outputPath, 'utf-8'));
+
+    expect(result).toEqual({ recordsWritten: 4, outputPath });
+    expect(entries.map((e) => e.id)).toEqual([
+      '@read_clipped',
+      '@read_hard_clipped',
+      '@read_spliced',
+      '@read_unmapped',
+    ]);
+    // read_spliced is stored as CGGATACTGTATAGTCCCACCTGG on the reverse strand
+    expect(entries[2]?.seq).toBe('CCAGGTGGGACTATACAGTATCCG');
+    expect(entries[0]?.qual).toBe('I'.repeat(38));
+  });
+
+  it('leaves out secondary and supplementary alignments', async () => {
+    const outputPath = join(tmpDir, 'flags.fastq');
+
+    await exportFastq({ bamPath: EXAMPLE_FLAGS_BAM }, outputPath);
+    const entries = parseFastq(await readFile(outputPath, 'utf-8'));
+
+    expect(entries.map((e) => e.id)).toEqual([
+      '@read_primary',
+      '@read_duplicate',
+      '@read_qc_fail',
+      '@read_reverse',
+    ]);
+  });
+
+  it('writes the reads that readInfo returns', async () => {
+    const options = { bamPath: EXAMPLE_1_BAM, readFilter: 'primary_forward' };
+    const outputPath = join(tmpDir, 'filtered.fastq');
+
+    await exportFastq(options, outputPath);
+    const entries = parseFastq(await readFile(outputPath, 'utf-8'));
+    const reads = await readInfo(options);
+
+    expect(entries.map((e) => e.id.slice(1))).toEqual(
+      reads.map((r) => r.read_id),
+    );
+    // Bases stored without qualities are given 1
+    expect(entries[0]?.qual).toBe('"'.repeat(entries[0]?.seq.length ?? 0));
+  });
+
+  it('gzip-compresses the output with compress', async () => {
+    const plainPath = join(tmpDir, 'plain.fastq');
+    const outputPath = join(tmpDir, 'compressed.fastq');
+
+    await exportFastq({ bamPath: EXAMPLE_CIGAR_BAM }, plainPath);
+    const result = await exportFastq(
+      { bamPath: EXAMPLE_CIGAR_BAM },
+      outputPath,
+      true,
+    );
+
+    expect(result.outputPath).toBe(`${outputPath}.gz`);
+    expect(gunzipSync(await readFile(result.outputPath)).toString()).toBe(
+      await readFile(plainPath, 'utf-8'),
+    );
+  });
+
+  it('refuses to overwrite the input', async () => {
+    await expect(
+      exportFastq({ bamPath: EXAMPLE_CIGAR_BAM }, EXAMPLE_CIGAR_BAM),
+    ).rejects.toMatchObject({ code: ErrorKind.InvalidOptions });
+  });
+
+  it('is a NanalogueBam method', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_CIGAR_BAM });
+    const outputPath = join(tmpDir, 'handle.fastq');
+    const expectedPath = join(tmpDir, 'function.fastq');
+
+    await bam.exportFastq({ limit: 2 }, outputPath);
+    await exportFastq({ bamPath: EXAMPLE_CIGAR_BAM, limit: 2 }, expectedPath);
+
+    expect(await readFile(outputPath, 'utf-8')).toBe(
+      await readFile(expectedPath, 'utf-8'),
+    );
+  });
+});
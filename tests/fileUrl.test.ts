@@ -0,0 +1,78 @@
+// Tests for file:// URLs, which are read as the local file they name
+
+import { copyFile, mkdir, mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { pathToFileURL } from 'node:url';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import { ErrorKind, NanalogueBam, peek, readInfo } from '../index';
+import { createSimpleBam } from './fixtures';
+
+describe('file:// URLs', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+  let spacedBamPath: string;
+  const region = 'contig_00000:0-100';
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-file-url-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+    await mkdir(join(tmpDir, 'dropped files'));
+    spacedBamPath = join(tmpDir, 'dropped files', 'simple copy.bam');
+    await copyFile(simpleBamPath, spacedBamPath);
+    await copyFile(`${simpleBamPath}.bai`, `${spacedBamPath}.bai`);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('reads the local file, with and without a region', async () => {
+    const bamPath = pathToFileURL(simpleBamPath).href;
+
+    expect(await readInfo({ bamPath })).toEqual(
+      await readInfo({ bamPath: simpleBamPath }),
+    );
+    expect(await readInfo({ bamPath, region })).toEqual(
+      await readInfo({ bamPath: simpleBamPath, region }),
+    );
+  });
+
+  it('decodes escaped characters such as spaces', async () => {
+    const bamPath = pathToFileURL(spacedBamPath).href;
+
+    expect(bamPath).toContain('%20');
+    expect(await readInfo({ bamPath, region })).toEqual(
+      await readInfo({ bamPath: simpleBamPath, region }),
+    );
+  });
+
+  it('applies to peek and NanalogueBam', async () => {
+    const bamPath = pathToFileURL(spacedBamPath).href;
+    const bam = new NanalogueBam({ bamPath });
+
+    expect(await peek({ bamPath })).toEqual(
+      await peek({ bamPath: simpleBamPath }),
+    );
+    expect(await bam.readInfo({ region })).toEqual(
+      await readInfo({ bamPath: simpleBamPath, region }),
+    );
+  });
+
+  it('rejects URLs naming a file on another host', async () => {
+    await expect(
+      readInfo({ bamPath: 'file://remote/reads.bam' }),
+    ).rejects.toMatchObject({
+      code: ErrorKind.InvalidOptions,
+      message: expect.stringMatching(/does not name a local file/),
+    });
+  });
+
+  it('reports a missing file as not found', async () => {
+    const bamPath = pathToFileURL(join(tmpDir, 'missing.bam')).href;
+
+    await expect(readInfo({ bamPath })).rejects.toMatchObject({
+      code: ErrorKind.FileNotFound,
+    });
+  });
+});
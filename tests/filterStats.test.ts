@@ -0,0 +1,98 @@
+// Tests for filterStats, which counts the reads left after each read filter
+
+import { mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import {
+  countReads,
+  type FilterStats,
+  filterStats,
+  NanalogueBam,
+  type ReadOptions,
+} from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM } from './fixtures';
+
+/** Counts of the stats in their order, total first. */
+const funnel = (stats: FilterStats) => [
+  stats.total,
+  stats.after_sample,
+  stats.after_len,
+  stats.after_mapq,
+  stats.after_read_id,
+  stats.after_align_len,
+  stats.after_read_filter,
+  stats.after_region,
+  stats.after_flags,
+  stats.after_read_group,
+  stats.after_min_mods,
+  stats.after_dedupe,
+];
+
+describe('filterStats', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-filter-stats-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('counts every record when no filter is set', async () => {
+    const stats = await filterStats({ bamPath: EXAMPLE_1_BAM });
+
+    expect(funnel(stats)).toEqual(Array(12).fill(4));
+  });
+
+  it('shows the filter that drops reads', async () => {
+    const stats = await filterStats({
+      bamPath: EXAMPLE_1_BAM,
+      readFilter: 'primary_forward,primary_reverse',
+      minSeqLen: 10,
+    });
+
+    expect(funnel(stats)).toEqual([4, 4, 3, 3, 3, 3, 2, 2, 2, 2, 2, 2]);
+  });
+
+  it('never rises and ends at the count of countReads', async () => {
+    const options: ReadOptions = {
+      bamPath: simpleBamPath,
+      region: 'contig_00000:1000-3000',
+      fullRegion: true,
+      sampleFraction: 0.5,
+      sampleSeed: 3,
+      mapqFilter: 20,
+      readFilter: 'primary_forward',
+      minMods: 2,
+      dedupe: true,
+    };
+    const counts = funnel(await filterStats(options));
+
+    for (const [i, count] of counts.entries()) {
+      expect(count).toBeLessThanOrEqual(counts[i - 1] ?? count);
+    }
+    expect(counts[0]).toBeGreaterThan(counts[1]);
+    expect(counts.at(-1)).toBe(await countReads(options));
+  });
+
+  it('gives the same counts through a NanalogueBam', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+
+    expect(await bam.filterStats({ minSeqLen: 10 })).toEqual(
+      await filterStats({ bamPath: EXAMPLE_1_BAM, minSeqLen: 10 }),
+    );
+  });
+
+  it('rejects limit and offset', async () => {
+    await expect(
+      filterStats({ bamPath: EXAMPLE_1_BAM, limit: 1 }),
+    ).rejects.toMatchObject({ code: 'InvalidOptions' });
+    await expect(
+      filterStats({ bamPath: EXAMPLE_1_BAM, offset: 1 }),
+    ).rejects.toMatchObject({ code: 'InvalidOptions' });
+  });
+});
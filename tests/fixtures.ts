@@ -4,7 +4,13 @@
 import { randomUUID } from 'node:crypto';
 import { readFile } from 'node:fs/promises';
 import { join, resolve } from 'node:path';
-import { type ReadOptions, simulateModBam, type WindowOptions } from '../index';
+import {
+  type ModStrand,
+  type ReadFilterKind,
+  type ReadOptions,
+  simulateModBam,
+  type WindowOptions,
+} from '../index';
 
 const TEST_DATA_DIR = resolve(__dirname, 'data');
 const SIMULATION_CONFIGS_DIR = join(TEST_DATA_DIR, 'simulation_configs');
@@ -101,18 +107,29 @@ export interface InputOptions {
   bamPath: string;
   treatAsUrl?: boolean;
   minSeqLen?: number;
+  maxSeqLen?: number;
   minAlignLen?: number;
+  maxAlignLen?: number;
   readIdSet?: string[];
+  readIdRegex?: string;
+  readGroup?: string[];
   threads?: number;
   includeZeroLen?: boolean;
-  readFilter?: string;
+  readFilter?: ReadFilterKind[] | string;
   sampleFraction?: number;
   mapqFilter?: number;
+  mapqMax?: number;
   excludeMapqUnavail?: boolean;
+  excludeSecondary?: boolean;
+  excludeSupplementary?: boolean;
+  excludeDuplicate?: boolean;
+  excludeQcFail?: boolean;
+  minMods?: number;
   region?: string;
   fullRegion?: boolean;
   tag?: string;
-  modStrand?: string;
+  tags?: string[];
+  modStrand?: ModStrand;
   minModQual?: number;
   rejectModQualNonInclusive?: [number, number];
   trimReadEndsMod?: number;
@@ -191,6 +208,16 @@ export const EXAMPLE_3_BAM = getExampleBamPath('example_3.bam');
 export const EXAMPLE_7_BAM = getExampleBamPath('example_7.bam');
 export const EXAMPLE_10_BAM = getExampleBamPath('example_10.bam');
 export const EXAMPLE_11_BAM = getExampleBamPath('example_11.bam');
+export const EXAMPLE_ZERO_LEN_BAM = getExampleBamPath(
+  'example_zero_len.bam',
+);
+export const EXAMPLE_READ_GROUPS_BAM = getExampleBamPath(
+  'example_read_groups.bam',
+);
+export const EXAMPLE_CIGAR_BAM = getExampleBamPath('example_cigar.bam');
+export const EXAMPLE_FLAGS_BAM = getExampleBamPath('example_flags.bam');
+export const EXAMPLE_1_CRAM = getExampleBamPath('example_1.cram');
+export const EXAMPLE_REFERENCE = getExampleBamPath('contigs.fa');
 export const EXAMPLE_PYNANALOGUE_1_BAM = getExampleBamPath(
   'example_pynanalogue_1.bam',
 );
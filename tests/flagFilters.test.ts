@@ -0,0 +1,94 @@
+// Tests for the options that exclude alignments by SAM flag
+
+import { describe, expect, it } from 'vitest';
+import {
+  bamMods,
+  contigSummary,
+  countReads,
+  ReadFilterKind,
+  type ReadOptions,
+  readInfo,
+  windowReads,
+} from '../index';
+import { EXAMPLE_FLAGS_BAM } from './fixtures';
+
+describe('SAM flag filters', () => {
+  // Duplicate and QC-fail reads cannot be read, so most tests exclude them
+  const readable = {
+    bamPath: EXAMPLE_FLAGS_BAM,
+    excludeDuplicate: true,
+    excludeQcFail: true,
+  };
+
+  const readIds = async (options: ReadOptions) =>
+    (await readInfo(options)).map((r) => r.read_id);
+
+  it('fails on duplicate and QC-fail reads unless excluded', async () => {
+    await expect(readInfo({ bamPath: EXAMPLE_FLAGS_BAM })).rejects.toThrow(
+      /duplicate\/qual-check-failed flags not supported/,
+    );
+    expect(await readIds(readable)).toEqual([
+      'read_primary',
+      'read_secondary',
+      'read_supplementary',
+      'read_reverse',
+    ]);
+  });
+
+  it('excludes secondary alignments', async () => {
+    expect(await readIds({ ...readable, excludeSecondary: true })).toEqual([
+      'read_primary',
+      'read_supplementary',
+      'read_reverse',
+    ]);
+  });
+
+  it('excludes supplementary alignments', async () => {
+    expect(await readIds({ ...readable, excludeSupplementary: true })).toEqual(
+      ['read_primary', 'read_secondary', 'read_reverse'],
+    );
+  });
+
+  it('ignores the flags when set to false', async () => {
+    expect(
+      await readIds({
+        ...readable,
+        excludeSecondary: false,
+        excludeSupplementary: false,
+      }),
+    ).toEqual(await readIds(readable));
+  });
+
+  it('combines with readFilter', async () => {
+    const result = await readInfo({
+      ...readable,
+      readFilter: [
+        ReadFilterKind.PrimaryForward,
+        ReadFilterKind.SecondaryForward,
+      ],
+      excludeSecondary: true,
+    });
+    expect(result.map((r) => r.read_id)).toEqual(['read_primary']);
+  });
+
+  it('applies to the other queries', async () => {
+    const options = {
+      ...readable,
+      excludeSecondary: true,
+      excludeSupplementary: true,
+    };
+    const expected = ['read_primary', 'read_reverse'];
+
+    expect((await bamMods(options)).map((r) => r.read_id)).toEqual(expected);
+    expect(await countReads(options)).toBe(2);
+    expect(await contigSummary(options)).toEqual([
+      { contig: 'contig1', length: 200, n_reads: 2 },
+    ]);
+    const windows = JSON.parse(
+      await windowReads({ ...options, win: 2, step: 1 }),
+    );
+    expect(windows.map((r: { read_id: string }) => r.read_id)).toEqual(
+      expected,
+    );
+  });
+});
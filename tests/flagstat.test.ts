@@ -0,0 +1,95 @@
+// Tests for flagstat, which counts reads by alignment category
+
+import { mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import { flagstat, NanalogueBam, readInfo } from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM, EXAMPLE_FLAGS_BAM } from './fixtures';
+
+describe('flagstat', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-flagstat-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true, force: true });
+  });
+
+  it('counts each flag category', async () => {
+    expect(await flagstat({ bamPath: EXAMPLE_FLAGS_BAM })).toEqual({
+      total: 6,
+      qc_failed: 1,
+      primary: 4,
+      secondary: 1,
+      supplementary: 1,
+      duplicates: 1,
+      primary_duplicates: 1,
+      mapped: 6,
+      primary_mapped: 4,
+      paired: 0,
+      read1: 0,
+      read2: 0,
+      properly_paired: 0,
+      with_mate_mapped: 0,
+      singletons: 0,
+      mate_on_other_contig: 0,
+      mate_on_other_contig_mapq5: 0,
+    });
+  });
+
+  it('counts unmapped reads as not mapped', async () => {
+    const stats = await flagstat({ bamPath: EXAMPLE_1_BAM });
+
+    expect(stats.total).toBe(4);
+    expect(stats.mapped).toBe(3);
+    expect(stats.primary_mapped).toBe(3);
+  });
+
+  it('matches the alignment types of readInfo', async () => {
+    const stats = await flagstat({ bamPath: simpleBamPath });
+    const reads = await readInfo({ bamPath: simpleBamPath });
+    const count = (match: (type: string) => boolean) =>
+      reads.filter((r) => match(r.alignment_type)).length;
+
+    expect(stats.total).toBe(reads.length);
+    expect(stats.secondary).toBe(count((t) => t.startsWith('secondary')));
+    expect(stats.supplementary).toBe(
+      count((t) => t.startsWith('supplementary')),
+    );
+    expect(stats.mapped).toBe(count((t) => t !== 'unmapped'));
+    expect(stats.primary).toBe(
+      stats.total - stats.secondary - stats.supplementary,
+    );
+  });
+
+  it('applies the read filters', async () => {
+    const stats = await flagstat({
+      bamPath: EXAMPLE_FLAGS_BAM,
+      excludeSecondary: true,
+      excludeQcFail: true,
+    });
+
+    expect(stats.total).toBe(4);
+    expect(stats.secondary).toBe(0);
+    expect(stats.qc_failed).toBe(0);
+  });
+
+  it('counts only the reads within limit', async () => {
+    const stats = await flagstat({ bamPath: simpleBamPath, limit: 10 });
+
+    expect(stats.total).toBe(10);
+  });
+
+  it('is a NanalogueBam method', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_FLAGS_BAM });
+
+    expect(await bam.flagstat({ excludeDuplicate: true })).toEqual(
+      await flagstat({ bamPath: EXAMPLE_FLAGS_BAM, excludeDuplicate: true }),
+    );
+  });
+});
@@ -0,0 +1,56 @@
+// Tests for gcContent, which gives the GC fraction of each read
+
+import { describe, expect, it } from 'vitest';
+import { ErrorKind, gcContent, NanalogueBam } from '../index';
+import { EXAMPLE_1_BAM, EXAMPLE_CIGAR_BAM } from './fixtures';
+
+describe('gcContent', () => {
+  it('counts every base of the read without a region', async () => {
+    const contents = await gcContent({ bamPath: EXAMPLE_CIGAR_BAM });
+
+    // Soft-clipped bases count, as does the unmapped read
+    expect(contents).toEqual([
+      { read_id: 'read_clipped', gc_fraction: 13 / 38, n_count: 0 },
+      { read_id: 'read_hard_clipped', gc_fraction: 7 / 17, n_count: 0 },
+      { read_id: 'read_spliced', gc_fraction: 13 / 24, n_count: 0 },
+      { read_id: 'read_unmapped', gc_fraction: 12 / 30, n_count: 0 },
+    ]);
+  });
+
+  it('counts only the bases aligned in the region', async () => {
+    const contents = await gcContent({
+      bamPath: EXAMPLE_CIGAR_BAM,
+      region: 'contig1:30-70',
+    });
+
+    // TACTTCGCCT and CGAGTCGGTT, with the deletions left out
+    expect(contents).toEqual([
+      { read_id: 'read_clipped', gc_fraction: 0.5, n_count: 0 },
+      { read_id: 'read_hard_clipped', gc_fraction: 0.6, n_count: 0 },
+    ]);
+  });
+
+  it('applies the read filters', async () => {
+    const contents = await gcContent({
+      bamPath: EXAMPLE_CIGAR_BAM,
+      readFilter: 'unmapped',
+    });
+
+    expect(contents.map((c) => c.read_id)).toEqual(['read_unmapped']);
+  });
+
+  it('rejects regions', async () => {
+    await expect(
+      gcContent({ bamPath: EXAMPLE_1_BAM, regions: ['dummyI'] }),
+    ).rejects.toMatchObject({ code: ErrorKind.InvalidOptions });
+  });
+
+  it('is a NanalogueBam method', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_CIGAR_BAM });
+    const region = 'contig1:0-200';
+
+    expect(await bam.gcContent({ region })).toEqual(
+      await gcContent({ bamPath: EXAMPLE_CIGAR_BAM, region }),
+    );
+  });
+});
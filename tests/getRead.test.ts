@@ -0,0 +1,59 @@
+// Tests for getRead, which returns the record of a single read id
+
+import { describe, expect, it } from 'vitest';
+import { ErrorKind, getRead, NanalogueBam, readInfo } from '../index';
+import { EXAMPLE_1_BAM } from './fixtures';
+
+const REVERSE_READ = 'fffffff1-10d2-49cb-8ca3-e8d48979001b';
+const SPLIT_READ = 'a4f36092-b4d5-47a9-813e-c22c3b477a0c';
+
+describe('getRead', () => {
+  it('returns the readInfo record of the read', async () => {
+    const reads = await readInfo({ bamPath: EXAMPLE_1_BAM });
+
+    expect(await getRead({ bamPath: EXAMPLE_1_BAM }, REVERSE_READ)).toEqual(
+      reads.find((r) => r.read_id === REVERSE_READ),
+    );
+  });
+
+  it('fetches the region when it is set', async () => {
+    const read = await getRead(
+      { bamPath: EXAMPLE_1_BAM, region: 'dummyII' },
+      REVERSE_READ,
+    );
+
+    expect(read?.alignment_type).toBe('primary_reverse');
+    expect(
+      await getRead({ bamPath: EXAMPLE_1_BAM, region: 'dummyI' }, REVERSE_READ),
+    ).toBeNull();
+  });
+
+  it('returns the first alignment, subject to the read filters', async () => {
+    const first = await getRead({ bamPath: EXAMPLE_1_BAM }, SPLIT_READ);
+    const unmapped = await getRead(
+      { bamPath: EXAMPLE_1_BAM, readFilter: 'unmapped' },
+      SPLIT_READ,
+    );
+
+    expect(first?.alignment_type).toBe('primary_forward');
+    expect(unmapped?.alignment_type).toBe('unmapped');
+  });
+
+  it('returns null for an absent read id', async () => {
+    expect(await getRead({ bamPath: EXAMPLE_1_BAM }, 'absent')).toBeNull();
+  });
+
+  it('rejects readIdSet', async () => {
+    await expect(
+      getRead({ bamPath: EXAMPLE_1_BAM, readIdSet: ['absent'] }, REVERSE_READ),
+    ).rejects.toMatchObject({ code: ErrorKind.InvalidOptions });
+  });
+
+  it('is a NanalogueBam method', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+
+    expect(await bam.getRead({}, REVERSE_READ)).toEqual(
+      await getRead({ bamPath: EXAMPLE_1_BAM }, REVERSE_READ),
+    );
+  });
+});
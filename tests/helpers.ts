@@ -1,8 +1,12 @@
 // Test helper functions for tsnanalogue tests
 // Provides TSV parsing, mod counting, and other utilities
 
+import { randomUUID } from 'node:crypto';
 import { readFileSync } from 'node:fs';
-import { resolve } from 'node:path';
+import { rm } from 'node:fs/promises';
+import { createServer } from 'node:http';
+import type { AddressInfo } from 'node:net';
+import { join, resolve } from 'node:path';
 import type {
   BamModRecord,
   MappedBamModRecord,
@@ -439,6 +443,20 @@ export function parseWindowReadsJson(jsonStr: string): WindowReadEntry[] {
   return JSON.parse(jsonStr) as WindowReadEntry[];
 }
 
+/**
+ * Parse windowReads JSON without the n_mod and n_valid calls that follow the
+ * core values of each window, for comparison with core outputs
+ */
+export function parseCoreWindowReadsJson(jsonStr: string): WindowReadEntry[] {
+  return parseWindowReadsJson(jsonStr).map((entry) => ({
+    ...entry,
+    mod_table: entry.mod_table.map((modEntry) => ({
+      ...modEntry,
+      data: modEntry.data.map((row) => row.slice(0, 6)),
+    })),
+  }));
+}
+
 /**
  * Get total number of data entries (windows) across all reads and mod_table entries
  */
@@ -505,3 +523,67 @@ export function getWindowDataCountForReadId(
   }
   return total;
 }
+
+/**
+ * Local HTTP server of the files of a directory, for tests of URL input
+ */
+export interface FileServer {
+  /** URL of the file `name` of the directory */
+  url: (name: string) => string;
+  /** Stops the server and removes the indexes htslib saved from it */
+  close: () => Promise<void>;
+}
+
+/**
+ * Serve the files of `dir` over HTTP on localhost, with the byte ranges htslib
+ * requests. Each file is read when requested, so a removed file is missing.
+ * File names get a unique prefix in the URL, as htslib saves the index of a URL
+ * under its name in the current directory.
+ */
+export async function serveFiles(dir: string): Promise<FileServer> {
+  const prefix = randomUUID();
+  const indexes = new Set<string>();
+  const server = createServer((req, res) => {
+    const name = (req.url ?? '').replace(`/${prefix}-`, '');
+    if (name.endsWith('.bai')) {
+      indexes.add(`${prefix}-${name}`);
+    }
+    let data: Buffer;
+    try {
+      data = readFileSync(join(dir, name));
+    } catch {
+      res.writeHead(404).end();
+      return;
+    }
+    const range = /bytes=(\d+)-(\d*)/.exec(req.headers.range ?? '');
+    const start = Number(range?.[1] ?? 0);
+    const end = Math.min(Number(range?.[2] || Infinity), data.length - 1);
+    if (start >= data.length) {
+      res.writeHead(416, { 'Content-Range': `bytes */${data.length}` });
+      res.end();
+      return;
+    }
+    res.writeHead(range ? 206 : 200, {
+      'Accept-Ranges': 'bytes',
+      'Content-Length': end - start + 1,
+      ...(range && {
+        'Content-Range': `bytes ${start}-${end}/${data.length}`,
+      }),
+    });
+    res.end(data.subarray(start, end + 1));
+  });
+  await new Promise<void>((done) => {
+    server.listen(0, '127.0.0.1', done);
+  });
+  const { port } = server.address() as AddressInfo;
+  return {
+    url: (name) => `http://127.0.0.1:${port}/${prefix}-${name}`,
+    close: async () => {
+      server.closeAllConnections();
+      server.close();
+      for (const index of indexes) {
+        await rm(join(process.cwd(), index), { force: true });
+      }
+    },
+  };
+}
@@ -0,0 +1,85 @@
+// Tests for idxstats, which lists record counts per contig from the index
+
+import { copyFile, mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import {
+  contigSummary,
+  ErrorKind,
+  idxstats,
+  NanalogueBam,
+  readInfo,
+} from '../index';
+import {
+  createSimpleBam,
+  EXAMPLE_1_BAM,
+  EXAMPLE_1_CRAM,
+  EXAMPLE_REFERENCE,
+} from './fixtures';
+
+describe('idxstats', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-idxstats-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true, force: true });
+  });
+
+  it('lists each contig and the unplaced unmapped records', async () => {
+    expect(await idxstats({ bamPath: EXAMPLE_1_BAM })).toEqual([
+      { contig: 'dummyI', length: 22, mapped: 1, unmapped: 0 },
+      { contig: 'dummyII', length: 48, mapped: 1, unmapped: 0 },
+      { contig: 'dummyIII', length: 76, mapped: 1, unmapped: 0 },
+      { contig: '*', length: 0, mapped: 0, unmapped: 1 },
+    ]);
+  });
+
+  it('agrees with contigSummary and readInfo', async () => {
+    const stats = await idxstats({ bamPath: simpleBamPath });
+    const summary = await contigSummary({ bamPath: simpleBamPath });
+    const reads = await readInfo({ bamPath: simpleBamPath });
+
+    expect(stats.at(-1)?.contig).toBe('*');
+    expect(
+      stats.slice(0, -1).map(({ contig, length, mapped }) => ({
+        contig,
+        length,
+        n_reads: mapped,
+      })),
+    ).toEqual(summary);
+    const total = stats.reduce((n, s) => n + s.mapped + s.unmapped, 0);
+    expect(total).toBe(reads.length);
+  });
+
+  it('counts CRAM records by reading them', async () => {
+    expect(
+      await idxstats({
+        bamPath: EXAMPLE_1_CRAM,
+        referenceFasta: EXAMPLE_REFERENCE,
+      }),
+    ).toEqual(await idxstats({ bamPath: EXAMPLE_1_BAM }));
+  });
+
+  it('needs an index', async () => {
+    const bamPath = join(tmpDir, 'unindexed.bam');
+    await copyFile(EXAMPLE_1_BAM, bamPath);
+
+    await expect(idxstats({ bamPath })).rejects.toMatchObject({
+      code: ErrorKind.IndexMissing,
+    });
+  });
+
+  it('is a NanalogueBam method', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+
+    expect(await bam.idxstats()).toEqual(
+      await idxstats({ bamPath: EXAMPLE_1_BAM }),
+    );
+  });
+});
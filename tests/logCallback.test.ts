@@ -0,0 +1,115 @@
+// Tests for setLogCallback, which passes the diagnostics of the library to JS
+
+import { spawnSync } from 'node:child_process';
+import { resolve } from 'node:path';
+import { afterEach, describe, expect, it, vi } from 'vitest';
+import {
+  type LogRecord,
+  setLogCallback,
+  windowAggregate,
+  windowReads,
+} from '../index';
+import { EXAMPLE_1_BAM } from './fixtures';
+
+const INDEX_PATH = resolve(__dirname, '..', 'index.js');
+
+const UNALIGNED_WARNING =
+  'left out 1 read(s) without an alignment, which cannot be windowed in ' +
+  'reference coordinates';
+
+// The core windowing skips windows that the window operation cannot value
+const SKIPPED_WINDOW_WARNING =
+  'Skipping 1 window starting at 5d10eb9a-aae1-4db8-8ec6-7ebb34d32575:0 ' +
+  'due to error: data is not of sufficient size (e.g. in a window): ' +
+  '`threshold and gradient needs > 1 data point`';
+
+/** Options of a windowing whose windows are all skipped by the core. */
+const SKIPPED_WINDOWS = {
+  bamPath: EXAMPLE_1_BAM,
+  win: 1,
+  step: 1,
+  winOp: 'grad_density',
+} as const;
+
+describe('setLogCallback', () => {
+  afterEach(() => {
+    setLogCallback();
+  });
+
+  it('passes warnings to the callback', async () => {
+    const records: LogRecord[] = [];
+    setLogCallback((record) => records.push(record));
+    await windowAggregate({ bamPath: EXAMPLE_1_BAM, win: 10, step: 10 });
+
+    await vi.waitFor(() =>
+      expect(records).toContainEqual({
+        level: 'warn',
+        message: UNALIGNED_WARNING,
+      }),
+    );
+  });
+
+  it('passes the warnings of windows the core would skip', async () => {
+    const records: LogRecord[] = [];
+    setLogCallback((record) => records.push(record));
+    const output = await windowReads(SKIPPED_WINDOWS);
+
+    for (const read of JSON.parse(output)) {
+      for (const windows of read.mod_table) {
+        expect(windows.data).toEqual([]);
+      }
+    }
+    await vi.waitFor(() =>
+      expect(records).toContainEqual({
+        level: 'warn',
+        message: SKIPPED_WINDOW_WARNING,
+      }),
+    );
+  });
+
+  it('writes skipped windows to stderr only without a callback', () => {
+    const script = (body: string) => `
+      const m = require(${JSON.stringify(INDEX_PATH)});
+      ${body}
+      m.windowReads(${JSON.stringify(SKIPPED_WINDOWS)});
+    `;
+    const run = (body: string) =>
+      spawnSync(process.execPath, ['-e', script(body)], { encoding: 'utf8' });
+
+    expect(run('').stderr).toContain(`Warning: ${SKIPPED_WINDOW_WARNING}\n`);
+    expect(run('m.setLogCallback(() => {});').stderr).toBe('');
+  });
+
+  it('stops passing messages once removed', async () => {
+    const records: LogRecord[] = [];
+    setLogCallback((record) => records.push(record));
+    setLogCallback();
+    await windowReads({
+      bamPath: EXAMPLE_1_BAM,
+      win: 10,
+      step: 10,
+      coordinate: 'reference',
+    });
+
+    // Give any queued call the chance to arrive
+    await new Promise((resolve) => setTimeout(resolve, 50));
+    expect(records).toEqual([]);
+  });
+
+  it('sends messages to the latest callback', async () => {
+    const first: LogRecord[] = [];
+    const second: LogRecord[] = [];
+    setLogCallback((record) => first.push(record));
+    setLogCallback((record) => second.push(record));
+    await windowAggregate({ bamPath: EXAMPLE_1_BAM, win: 10, step: 10 });
+
+    await vi.waitFor(() => expect(second.length).toBeGreaterThan(0));
+    expect(first).toEqual([]);
+  });
+
+  it('rejects a callback that is not a function', () => {
+    expect(() =>
+      setLogCallback(5 as unknown as (record: LogRecord) => void),
+    ).toThrow(/expected a function/);
+  });
+});
@@ -0,0 +1,65 @@
+// Tests for mapqHistogram, which counts reads per mapping quality
+
+import { mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import { countReads, mapqHistogram, NanalogueBam } from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM } from './fixtures';
+
+describe('mapqHistogram', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-mapq-histogram-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('counts every read at its mapping quality', async () => {
+    const counts = await mapqHistogram({ bamPath: EXAMPLE_1_BAM });
+
+    expect(counts).toHaveLength(256);
+    expect(counts[0]).toBe(1);
+    // Reads without mapping quality, the unmapped one among them, count at 255
+    expect(counts[255]).toBe(3);
+    expect(counts.reduce((x, y) => x + y)).toBe(
+      await countReads({ bamPath: EXAMPLE_1_BAM }),
+    );
+  });
+
+  it('leaves out unavailable mapping qualities on request', async () => {
+    const counts = await mapqHistogram({
+      bamPath: simpleBamPath,
+      excludeMapqUnavail: true,
+    });
+    const all = await mapqHistogram({ bamPath: simpleBamPath });
+
+    expect(all[255]).toBeGreaterThan(0);
+    expect(counts[255]).toBe(0);
+    expect(counts.slice(0, 255)).toEqual(all.slice(0, 255));
+  });
+
+  it('applies read filters and pagination', async () => {
+    const options = { bamPath: simpleBamPath, mapqFilter: 12, mapqMax: 15 };
+    const counts = await mapqHistogram(options);
+
+    const inRange = (mapq: number) => mapq >= 12 && mapq <= 15;
+    expect(counts.every((v, mapq) => v === 0 || inRange(mapq))).toBe(true);
+    expect(counts.reduce((x, y) => x + y)).toBe(await countReads(options));
+
+    const page = await mapqHistogram({ ...options, limit: 10 });
+    expect(page.reduce((x, y) => x + y)).toBe(10);
+  });
+
+  it('works on a NanalogueBam', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+    expect(await bam.mapqHistogram({ region: 'dummyI' })).toEqual(
+      await mapqHistogram({ bamPath: EXAMPLE_1_BAM, region: 'dummyI' }),
+    );
+  });
+});
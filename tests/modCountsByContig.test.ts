@@ -0,0 +1,91 @@
+// Tests for modCountsByContig, which totals the modification calls per contig
+
+import { mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import {
+  bamMods,
+  modCountsByContig,
+  NanalogueBam,
+  type ReadOptions,
+} from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM } from './fixtures';
+
+/** Totals the aligned calls of each contig in JS, as Rust does. */
+async function tally(options: ReadOptions) {
+  const counts = new Map<string, { n_mod: number; n_valid: number }>();
+  for (const { alignment, mod_table } of await bamMods(options)) {
+    if (alignment === undefined) {
+      continue;
+    }
+    for (const { mod_code, data } of mod_table) {
+      const key = `${alignment.contig}\t${mod_code}`;
+      const count = counts.get(key) ?? { n_mod: 0, n_valid: 0 };
+      for (const [, refPos, qual] of data) {
+        if (refPos !== -1) {
+          count.n_valid += 1;
+          count.n_mod += qual >= 128 ? 1 : 0;
+        }
+      }
+      counts.set(key, count);
+    }
+  }
+  return [...counts].map(([key, count]) => {
+    const [contig, mod_code] = key.split('\t');
+    return { contig, mod_code, ...count };
+  });
+}
+
+describe('modCountsByContig', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-mod-counts-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('totals the calls of each contig', async () => {
+    expect(await modCountsByContig({ bamPath: EXAMPLE_1_BAM })).toEqual([
+      { contig: 'dummyI', mod_code: 'T', n_mod: 0, n_valid: 4 },
+      { contig: 'dummyII', mod_code: 'T', n_mod: 1, n_valid: 5 },
+      { contig: 'dummyIII', mod_code: 'T', n_mod: 3, n_valid: 5 },
+    ]);
+  });
+
+  it('matches the calls of bamMods', async () => {
+    const options = { bamPath: simpleBamPath };
+    const counts = await modCountsByContig(options);
+    const expected = await tally(options);
+
+    expect(counts).toHaveLength(2);
+    expect(counts).toEqual(
+      expected.sort((a, b) => a.contig.localeCompare(b.contig)),
+    );
+  });
+
+  it('applies read and modification filters', async () => {
+    const counts = await modCountsByContig({
+      bamPath: simpleBamPath,
+      region: 'contig_00001:0-3000',
+      minModQual: 200,
+    });
+
+    expect(counts).toHaveLength(1);
+    expect(counts[0]?.contig).toBe('contig_00001');
+    expect(counts[0]?.n_mod).toBe(counts[0]?.n_valid);
+  });
+
+  it('works on a NanalogueBam', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+
+    expect(await bam.modCountsByContig({ region: 'dummyIII' })).toEqual(
+      await modCountsByContig({ bamPath: EXAMPLE_1_BAM, region: 'dummyIII' }),
+    );
+  });
+});
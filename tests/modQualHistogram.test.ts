@@ -0,0 +1,80 @@
+// Tests for modQualHistogram, which counts modification calls per ML value
+
+import { mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import { bamModsTyped, modQualHistogram, NanalogueBam } from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM } from './fixtures';
+
+/** Bins the ML values of calls in JS, as modQualHistogram does in Rust. */
+function binCalls(mlValues: number[], bins = 256): number[] {
+  const counts = new Array<number>(bins).fill(0);
+  for (const v of mlValues) {
+    counts[Math.floor((v * bins) / 256)] += 1;
+  }
+  return counts;
+}
+
+describe('modQualHistogram', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-histogram-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('counts every call at its ML value', async () => {
+    const counts = await modQualHistogram({ bamPath: EXAMPLE_1_BAM });
+    const calls = await bamModsTyped({ bamPath: EXAMPLE_1_BAM });
+
+    expect(counts).toHaveLength(256);
+    expect(counts).toEqual(binCalls(calls.map((c) => c.ml_value)));
+  });
+
+  it('applies read and modification filters', async () => {
+    const options = {
+      bamPath: simpleBamPath,
+      region: 'contig_00001:0-3000',
+      minModQual: 100,
+      limit: 20,
+    };
+    const counts = await modQualHistogram(options);
+    const calls = await bamModsTyped(options);
+
+    expect(counts.slice(0, 100).every((v) => v === 0)).toBe(true);
+    expect(counts).toEqual(binCalls(calls.map((c) => c.ml_value)));
+  });
+
+  it.each([1, 4, 7, 256])('coarsens into %i bins', async (bins) => {
+    const counts = await modQualHistogram({ bamPath: EXAMPLE_1_BAM }, bins);
+    const calls = await bamModsTyped({ bamPath: EXAMPLE_1_BAM });
+
+    expect(counts).toHaveLength(bins);
+    expect(counts).toEqual(
+      binCalls(
+        calls.map((c) => c.ml_value),
+        bins,
+      ),
+    );
+  });
+
+  it.each([0, 257])('rejects %i bins', async (bins) => {
+    await expect(modQualHistogram({ bamPath: EXAMPLE_1_BAM }, bins)).rejects.toThrow(
+      /bins must be between 1 and 256/,
+    );
+  });
+
+  it('works on a NanalogueBam', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+
+    expect(await bam.modQualHistogram({ region: 'dummyIII' }, 8)).toEqual(
+      await modQualHistogram({ bamPath: EXAMPLE_1_BAM, region: 'dummyIII' }, 8),
+    );
+  });
+});
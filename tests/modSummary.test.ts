@@ -0,0 +1,102 @@
+// Tests for modSummary, which totals the modification calls of each read
+
+import { mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import {
+  bamMods,
+  type ModSummary,
+  modSummary,
+  NanalogueBam,
+  type ReadOptions,
+} from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM } from './fixtures';
+
+/** Totals the calls of each read in JS, as modSummary does in Rust. */
+async function summarise(options: ReadOptions) {
+  const reads = await bamMods(options);
+  return reads.map(({ read_id, mod_table }) => {
+    const quals = mod_table.flatMap((e) => e.data.map(([, , qual]) => qual));
+    const summary = {
+      read_id,
+      n_mod: quals.filter((v) => v >= 128).length,
+      n_valid: quals.length,
+    };
+    return quals.length > 0
+      ? {
+          ...summary,
+          mean_prob: quals.reduce((a, b) => a + b, 0) / quals.length / 255,
+        }
+      : summary;
+  });
+}
+
+describe('modSummary', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-mod-summary-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('totals the calls of each read', async () => {
+    const options = { bamPath: EXAMPLE_1_BAM };
+    const summaries = await modSummary(options);
+    const expected = await summarise(options);
+
+    expect(summaries).toHaveLength(expected.length);
+    summaries.forEach((s, i) => {
+      expect(s.read_id).toBe(expected[i].read_id);
+      expect(s.n_mod).toBe(expected[i].n_mod);
+      expect(s.n_valid).toBe(expected[i].n_valid);
+      expect(s.mean_prob).toBeCloseTo(expected[i].mean_prob ?? Number.NaN, 12);
+    });
+  });
+
+  it('applies read and modification filters', async () => {
+    const options = {
+      bamPath: simpleBamPath,
+      region: 'contig_00001:0-3000',
+      minModQual: 200,
+      limit: 20,
+    };
+    const summaries = await modSummary(options);
+    const expected = await summarise(options);
+
+    expect(summaries).toHaveLength(20);
+    const counts = ({ read_id, n_mod, n_valid }: ModSummary) => ({
+      read_id,
+      n_mod,
+      n_valid,
+    });
+    expect(summaries.map(counts)).toEqual(expected.map(counts));
+    expect(summaries.every((s) => s.n_mod === s.n_valid)).toBe(true);
+  });
+
+  it('leaves out mean_prob for reads without valid calls', async () => {
+    const summaries = await modSummary({
+      bamPath: EXAMPLE_1_BAM,
+      minModQual: 255,
+    });
+
+    expect(summaries.length).toBeGreaterThan(0);
+    for (const s of summaries) {
+      expect(s.n_valid).toBe(0);
+      expect(s.mean_prob).toBeUndefined();
+    }
+  });
+
+  it('works on a NanalogueBam', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+
+    expect(await bam.modSummary({ region: 'dummyIII' })).toEqual(
+      await modSummary({ bamPath: EXAMPLE_1_BAM, region: 'dummyIII' }),
+    );
+  });
+});
@@ -0,0 +1,117 @@
+// Tests for the NanalogueBam class which keeps a BAM file open across queries
+
+import { mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import {
+  bamMods,
+  NanalogueBam,
+  readInfo,
+  seqTable,
+  windowReads,
+} from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM, EXAMPLE_3_BAM } from './fixtures';
+
+describe('NanalogueBam', () => {
+  it('throws from the constructor for a missing file', () => {
+    expect(() => new NanalogueBam({ bamPath: '/nonexistent/file.bam' })).toThrow(
+      /failed to open bam/i,
+    );
+  });
+
+  it('throws from the constructor for an invalid URL', () => {
+    expect(
+      () => new NanalogueBam({ bamPath: 'not a url', treatAsUrl: true }),
+    ).toThrow(/invalid url/i);
+  });
+
+  it('readInfo without options matches the readInfo function', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+    const expected = await readInfo({ bamPath: EXAMPLE_1_BAM });
+
+    expect(await bam.readInfo()).toEqual(expected);
+  });
+
+  it('answers repeated queries for different regions', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+
+    for (const region of ['dummyI', 'dummyII', 'dummyIII', 'dummyI']) {
+      const expected = await readInfo({ bamPath: EXAMPLE_1_BAM, region });
+      expect(await bam.readInfo({ region })).toEqual(expected);
+    }
+    expect(await bam.readInfo()).toHaveLength(4);
+  });
+
+  it('bamMods matches the bamMods function', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+    const expected = await bamMods({ bamPath: EXAMPLE_1_BAM, limit: 2 });
+
+    expect(await bam.bamMods({ limit: 2 })).toEqual(expected);
+  });
+
+  it('windowReads matches the windowReads function', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+    const expected = await windowReads({
+      bamPath: EXAMPLE_1_BAM,
+      win: 2,
+      step: 1,
+    });
+
+    expect(JSON.parse(await bam.windowReads({ win: 2, step: 1 }))).toEqual(
+      JSON.parse(expected),
+    );
+  });
+
+  it('seqTable matches the seqTable function', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+    const region = 'dummyIII:30-35';
+    const expected = await seqTable({ bamPath: EXAMPLE_1_BAM, region });
+
+    expect(await bam.seqTable({ region })).toBe(expected);
+  });
+
+  it('queries its own file, as the file is not a method option', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+    const expected = await readInfo({ bamPath: EXAMPLE_1_BAM });
+
+    // @ts-expect-error - bamPath is given to the constructor only
+    expect(await bam.readInfo({ bamPath: EXAMPLE_3_BAM })).toEqual(expected);
+  });
+
+  it('surfaces query errors without breaking later queries', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+
+    await expect(bam.readInfo({ limit: 0 })).rejects.toThrow(/limit/i);
+    await expect(bam.readInfo({ region: 'noSuchContig' })).rejects.toThrow();
+    expect(await bam.readInfo()).toHaveLength(4);
+  });
+});
+
+describe('NanalogueBam with generated BAM fixtures', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-handle-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('runs concurrent queries on one instance', async () => {
+    const bam = new NanalogueBam({ bamPath: simpleBamPath, threads: 2 });
+    const regions = ['contig_00000', 'contig_00001', 'contig_00000:0-5000'];
+
+    const results = await Promise.all(
+      regions.map((region) => bam.readInfo({ region })),
+    );
+
+    for (const [i, region] of regions.entries()) {
+      const expected = await readInfo({ bamPath: simpleBamPath, region });
+      expect(results[i]).toEqual(expected);
+    }
+  });
+});
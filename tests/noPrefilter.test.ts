@@ -0,0 +1,65 @@
+// Tests for the noPrefilter option, which returns the records that filters drop
+
+import { describe, expect, it } from 'vitest';
+import { bamMods, readInfo, readInfoStream } from '../index';
+import { EXAMPLE_1_BAM } from './fixtures';
+
+describe('noPrefilter', () => {
+  const options = { bamPath: EXAMPLE_1_BAM, readFilter: 'primary_forward' };
+
+  it('returns every record of readInfo with its filter result', async () => {
+    const all = await readInfo({ bamPath: EXAMPLE_1_BAM });
+    const filtered = await readInfo(options);
+    const raw = await readInfo({ ...options, noPrefilter: true });
+
+    expect(raw.map((r) => r.read_id)).toEqual(all.map((r) => r.read_id));
+    expect(
+      raw.filter((r) => r.passed_prefilter).map((r) => r.read_id),
+    ).toEqual(filtered.map((r) => r.read_id));
+    const passed = raw.map((r) => r.passed_prefilter);
+    expect(passed).toEqual([true, true, false, false]);
+  });
+
+  it('flags the records of bamMods', async () => {
+    const raw = await bamMods({ ...options, noPrefilter: true });
+
+    expect(raw).toHaveLength(4);
+    expect(raw.map((r) => [r.alignment_type, r.passed_prefilter])).toEqual([
+      ['primary_forward', true],
+      ['primary_forward', true],
+      ['primary_reverse', false],
+      ['unmapped', false],
+    ]);
+  });
+
+  it('streams the same records as readInfo', async () => {
+    const rawOptions = { ...options, noPrefilter: true };
+    const raw = [];
+    for await (const read of readInfoStream(rawOptions)) {
+      raw.push(read);
+    }
+
+    expect(raw).toEqual(await readInfo(rawOptions));
+  });
+
+  it('paginates over every record', async () => {
+    const raw = await readInfo({ ...options, noPrefilter: true });
+    const page = await readInfo({
+      ...options,
+      noPrefilter: true,
+      offset: 2,
+      limit: 1,
+    });
+
+    expect(page).toEqual(raw.slice(2, 3));
+  });
+
+  it('leaves out passed_prefilter unless set', async () => {
+    for (const read of await readInfo({ ...options, noPrefilter: false })) {
+      expect(read).not.toHaveProperty('passed_prefilter');
+    }
+    for (const read of await bamMods(options)) {
+      expect(read).not.toHaveProperty('passed_prefilter');
+    }
+  });
+});
@@ -0,0 +1,82 @@
+// Tests for the oneBased option, which reads region coordinates as 1-based and inclusive
+
+import { describe, expect, it } from 'vitest';
+import { parseRegion, pileup, readInfo, seqTable, windowReads } from '../index';
+import { EXAMPLE_1_BAM } from './fixtures';
+
+describe('oneBased', () => {
+  it('converts 1-based inclusive bounds to 0-based half-open', () => {
+    expect(parseRegion('chr1:100-200', true)).toEqual({
+      contig: 'chr1',
+      start: 99,
+      end: 200,
+    });
+    expect(parseRegion('chr1:100-100', true)).toEqual({
+      contig: 'chr1',
+      start: 99,
+      end: 100,
+    });
+    expect(parseRegion('chr1:100-', true)).toEqual({
+      contig: 'chr1',
+      start: 99,
+    });
+    expect(parseRegion('chr1', true)).toEqual({ contig: 'chr1' });
+  });
+
+  it('reads regions as 0-based when unset or false', () => {
+    expect(parseRegion('chr1:100-200', false)).toEqual(
+      parseRegion('chr1:100-200'),
+    );
+    expect(parseRegion('chr1:100-200')).toEqual({
+      contig: 'chr1',
+      start: 100,
+      end: 200,
+    });
+  });
+
+  it.each(['chr1:0-10', 'chr1:20-10'])('rejects %j', (region) => {
+    expect(() => parseRegion(region, true)).toThrow(
+      expect.objectContaining({ code: 'InvalidRegion' }),
+    );
+  });
+
+  it('reads the same bases in every function', async () => {
+    const zeroBased = { bamPath: EXAMPLE_1_BAM, region: 'dummyIII:20-40' };
+    const oneBased = {
+      bamPath: EXAMPLE_1_BAM,
+      region: 'dummyIII:21-40',
+      oneBased: true,
+    };
+
+    expect(await readInfo(oneBased)).toEqual(await readInfo(zeroBased));
+    expect(await seqTable(oneBased)).toEqual(await seqTable(zeroBased));
+    expect(await pileup(oneBased)).toEqual(await pileup(zeroBased));
+    expect(await windowReads({ ...oneBased, win: 2, step: 1 })).toEqual(
+      await windowReads({ ...zeroBased, win: 2, step: 1 }),
+    );
+  });
+
+  it('applies to regions and modRegion', async () => {
+    expect(
+      await readInfo({
+        bamPath: EXAMPLE_1_BAM,
+        regions: ['dummyIII:21-40', 'dummyI:1-10'],
+        oneBased: true,
+      }),
+    ).toEqual(
+      await readInfo({
+        bamPath: EXAMPLE_1_BAM,
+        regions: ['dummyIII:20-40', 'dummyI:0-10'],
+      }),
+    );
+    expect(
+      await readInfo({
+        bamPath: EXAMPLE_1_BAM,
+        modRegion: 'dummyIII:21-40',
+        oneBased: true,
+      }),
+    ).toEqual(
+      await readInfo({ bamPath: EXAMPLE_1_BAM, modRegion: 'dummyIII:20-40' }),
+    );
+  });
+});
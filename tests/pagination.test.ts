@@ -91,6 +91,49 @@ describe('readInfo pagination', () => {
   });
 });
 
+describe('pagination after filtering', () => {
+  const filters = {
+    region: 'contig_00001',
+    readFilter: 'primary_forward,secondary_forward',
+  };
+
+  it('counts offset in filtered reads for readInfo', async () => {
+    const all = await readInfo({ bamPath: simpleBamPath, ...filters });
+    const PAGE_SIZE = 25;
+    const collected: ReadInfoRecord[] = [];
+
+    for (let offset = 0; ; offset += PAGE_SIZE) {
+      const page = await readInfo({
+        bamPath: simpleBamPath,
+        ...filters,
+        limit: PAGE_SIZE,
+        offset,
+      });
+      collected.push(...page);
+      if (page.length < PAGE_SIZE) break;
+    }
+
+    expect(all.length).toBeGreaterThan(PAGE_SIZE);
+    expect(collected.map((r) => r.read_id)).toEqual(all.map((r) => r.read_id));
+  });
+
+  it('gives the same page for the same filters on every call', async () => {
+    const options = {
+      bamPath: simpleBamPath,
+      ...filters,
+      limit: 5,
+      offset: 40,
+    };
+    const all = await bamMods({ bamPath: simpleBamPath, ...filters });
+    const page = await bamMods(options);
+
+    expect(page.map((r) => r.read_id)).toEqual(
+      all.slice(40, 45).map((r) => r.read_id),
+    );
+    expect(await bamMods(options)).toEqual(page);
+  });
+});
+
 describe('bamMods pagination', () => {
   it('returns limited records with limit', async () => {
     const result = await bamMods({ bamPath, limit: 2 });
@@ -0,0 +1,55 @@
+// Tests for parseRegion, which parses region strings without opening a BAM file
+
+import { describe, expect, it } from 'vitest';
+import { parseRegion, readInfo } from '../index';
+import { EXAMPLE_1_BAM } from './fixtures';
+
+describe('parseRegion', () => {
+  it('parses a contig with start and end', () => {
+    expect(parseRegion('chr1:1000-2000')).toEqual({
+      contig: 'chr1',
+      start: 1000,
+      end: 2000,
+    });
+  });
+
+  it('leaves out the end of a region running to the end of the contig', () => {
+    expect(parseRegion('chr1:1000-')).toEqual({ contig: 'chr1', start: 1000 });
+  });
+
+  it('leaves out the bounds of a whole contig', () => {
+    expect(parseRegion('chr1')).toEqual({ contig: 'chr1' });
+  });
+
+  it('keeps colons in contig names', () => {
+    expect(parseRegion('chr1:alt:5-10')).toEqual({
+      contig: 'chr1:alt',
+      start: 5,
+      end: 10,
+    });
+  });
+
+  it.each([
+    '',
+    'chr1:2000-1000',
+    'chr1:1000-1000',
+    'chr1:1,000-2,000',
+    'chr1:abc-10',
+    'chr1:10',
+    ':10-20',
+  ])('rejects %j', (region) => {
+    expect(() => parseRegion(region)).toThrow(
+      expect.objectContaining({ code: 'InvalidRegion' }),
+    );
+  });
+
+  it('accepts the regions that the region option accepts', async () => {
+    const bounds = parseRegion('dummyIII:20-40');
+    const reads = await readInfo({
+      bamPath: EXAMPLE_1_BAM,
+      region: `${bounds.contig}:${bounds.start}-${bounds.end}`,
+    });
+
+    expect(reads.length).toBeGreaterThan(0);
+  });
+});
@@ -4,7 +4,7 @@ import { mkdtemp, rm } from 'node:fs/promises';
 import { tmpdir } from 'node:os';
 import { join, resolve } from 'node:path';
 import { afterAll, beforeAll, describe, expect, it } from 'vitest';
-import { peek, simulateModBam } from '../index';
+import { ErrorKind, peek, peekMany, simulateModBam } from '../index';
 import { createSimpleBam, createTwoModsBam } from './fixtures';
 
 const getTestDataPath = (relativePath: string) =>
@@ -51,6 +51,98 @@ describe('peek', () => {
     expect(modStrings).toContain('T+T');
   });
 
+  it('returns modifications sorted by base, strand, and mod code', async () => {
+    const result = await peek({
+      bamPath: getTestDataPath('examples/example_1.bam'),
+    });
+
+    expect(result.modifications).toEqual([
+      ['G', '-', '7200'],
+      ['T', '+', 'T'],
+    ]);
+  });
+
+  it('returns the call count and first call of each mod', async () => {
+    const result = await peek({
+      bamPath: getTestDataPath('examples/example_1.bam'),
+    });
+
+    // Calls are counted whatever their probability, as bamMods returns them
+    expect(result.modification_details).toEqual([
+      {
+        base: 'G',
+        strand: '-',
+        mod_code: '7200',
+        n_calls: 6,
+        example_read_id: 'a4f36092-b4d5-47a9-813e-c22c3b477a0c',
+        example_query_position: 28,
+      },
+      {
+        base: 'T',
+        strand: '+',
+        mod_code: 'T',
+        n_calls: 19,
+        example_read_id: '5d10eb9a-aae1-4db8-8ec6-7ebb34d32575',
+        example_query_position: 0,
+        example_reference_position: 9,
+      },
+    ]);
+  });
+
+  it('only examines sampleSize records', async () => {
+    const result = await peek({
+      bamPath: getTestDataPath('examples/example_1.bam'),
+      sampleSize: 1,
+    });
+
+    // The first record only has its 4 T+T calls
+    expect(result.modifications).toEqual([['T', '+', 'T']]);
+    expect(result.modification_details[0].n_calls).toBe(4);
+  });
+
+  it('rejects a sampleSize of 0', async () => {
+    await expect(
+      peek({
+        bamPath: getTestDataPath('examples/example_1.bam'),
+        sampleSize: 0,
+      }),
+    ).rejects.toMatchObject({
+      code: ErrorKind.InvalidOptions,
+      message: 'sample_size must be a positive integer',
+    });
+  });
+
+  it('examines the records of a region', async () => {
+    const result = await peek({
+      bamPath: getTestDataPath('examples/example_1.bam'),
+      region: 'dummyIII',
+    });
+
+    // Only the mapped copy of a4f36092 lies on dummyIII
+    expect(result.modification_details).toEqual([
+      {
+        base: 'T',
+        strand: '+',
+        mod_code: 'T',
+        n_calls: 5,
+        example_read_id: 'a4f36092-b4d5-47a9-813e-c22c3b477a0c',
+        example_query_position: 3,
+        example_reference_position: 26,
+      },
+    ]);
+    // Contigs still come from the whole header
+    expect(Object.keys(result.contigs)).toHaveLength(3);
+  });
+
+  it('rejects a region on an unknown contig', async () => {
+    await expect(
+      peek({
+        bamPath: getTestDataPath('examples/example_1.bam'),
+        region: 'nope',
+      }),
+    ).rejects.toMatchObject({ code: ErrorKind.InvalidRegion });
+  });
+
   it('returns correct data for example_3.bam', async () => {
     const result = await peek({
       bamPath: getTestDataPath('examples/example_3.bam'),
@@ -165,3 +257,36 @@ describe('peek with generated BAM fixtures', () => {
     expect(result.modifications).toEqual([]);
   });
 });
+
+describe('peekMany', () => {
+  it('returns one entry per path, in order', async () => {
+    const paths = [
+      getTestDataPath('examples/example_3.bam'),
+      getTestDataPath('examples/missing.bam'),
+      getTestDataPath('examples/example_1.bam'),
+    ];
+
+    const entries = await peekMany(paths);
+
+    expect(entries.map((e) => e.bam_path)).toEqual(paths);
+    expect(entries[0]).toEqual({
+      bam_path: paths[0],
+      ok: true,
+      result: await peek({ bamPath: paths[0] }),
+    });
+    expect(entries[2].result).toEqual(await peek({ bamPath: paths[2] }));
+  });
+
+  it('reports a failing file without failing the batch', async () => {
+    const [entry] = await peekMany([getTestDataPath('examples/missing.bam')]);
+
+    expect(entry.ok).toBe(false);
+    expect(entry.result).toBeUndefined();
+    expect(entry.code).toBe(ErrorKind.FileNotFound);
+    expect(entry.error).toMatch(/^Failed to open BAM/);
+  });
+
+  it('returns no entries for no paths', async () => {
+    expect(await peekMany([])).toEqual([]);
+  });
+});
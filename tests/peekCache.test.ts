@@ -0,0 +1,118 @@
+// Tests for the cache of peek results and clearPeekCache
+
+import { copyFile, mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, beforeEach, describe, expect, it } from 'vitest';
+import { clearPeekCache, peek, peekMany } from '../index';
+import { EXAMPLE_1_BAM, EXAMPLE_3_BAM } from './fixtures';
+import { type FileServer, serveFiles } from './helpers';
+
+describe('peek cache', () => {
+  let tmpDir: string;
+  let files: FileServer;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-peek-cache-'));
+    files = await serveFiles(tmpDir);
+  });
+
+  beforeEach(() => {
+    clearPeekCache();
+  });
+
+  afterAll(async () => {
+    await files.close();
+    await rm(tmpDir, { recursive: true, force: true });
+    clearPeekCache();
+  });
+
+  /** Copies `source` with its index to a file in tmpDir, returning its path. */
+  const copyBam = async (source: string, name: string) => {
+    const bamPath = join(tmpDir, name);
+    await copyFile(source, bamPath);
+    await copyFile(`${source}.bai`, `${bamPath}.bai`);
+    return bamPath;
+  };
+
+  it('returns the same result again', async () => {
+    const first = await peek({ bamPath: EXAMPLE_1_BAM });
+
+    expect(await peek({ bamPath: EXAMPLE_1_BAM })).toEqual(first);
+  });
+
+  it('reads a local file again once it changes', async () => {
+    const bamPath = await copyBam(EXAMPLE_1_BAM, 'changing.bam');
+    const before = await peek({ bamPath });
+    await copyBam(EXAMPLE_3_BAM, 'changing.bam');
+
+    const after = await peek({ bamPath });
+
+    expect(after).toEqual(await peek({ bamPath: EXAMPLE_3_BAM }));
+    expect(after).not.toEqual(before);
+  });
+
+  it('serves a URL from the cache until cacheVersion changes', async () => {
+    const bamPath = await copyBam(EXAMPLE_1_BAM, 'remote.bam');
+    const url = files.url('remote.bam');
+    const first = await peek({ bamPath: url, cacheVersion: 'v1' });
+    await rm(bamPath);
+
+    expect(await peek({ bamPath: url, cacheVersion: 'v1' })).toEqual(first);
+    await expect(peek({ bamPath: url, cacheVersion: 'v2' })).rejects.toThrow(
+      /Failed to open BAM/,
+    );
+  });
+
+  it('keeps results for different options apart', async () => {
+    const bamPath = await copyBam(EXAMPLE_1_BAM, 'options.bam');
+    const url = files.url('options.bam');
+    await peek({ bamPath: url });
+    await rm(bamPath);
+
+    await expect(peek({ bamPath: url, sampleSize: 1 })).rejects.toThrow();
+    await expect(peek({ bamPath: url, region: 'dummyI' })).rejects.toThrow();
+  });
+
+  it('reads the file with bypassCache', async () => {
+    const bamPath = await copyBam(EXAMPLE_1_BAM, 'bypass.bam');
+    const url = files.url('bypass.bam');
+    await peek({ bamPath: url });
+    await rm(bamPath);
+
+    await expect(peek({ bamPath: url, bypassCache: true })).rejects.toThrow(
+      /Failed to open BAM/,
+    );
+  });
+
+  it('reads the file again after clearPeekCache', async () => {
+    const bamPath = await copyBam(EXAMPLE_1_BAM, 'cleared.bam');
+    const url = files.url('cleared.bam');
+    await peek({ bamPath: url });
+    await rm(bamPath);
+    clearPeekCache();
+
+    await expect(peek({ bamPath: url })).rejects.toThrow(/Failed to open BAM/);
+  });
+
+  it('does not cache errors', async () => {
+    const bamPath = join(tmpDir, 'late.bam');
+    await expect(peek({ bamPath })).rejects.toThrow();
+    await copyBam(EXAMPLE_1_BAM, 'late.bam');
+
+    expect(await peek({ bamPath })).toEqual(
+      await peek({ bamPath: EXAMPLE_1_BAM }),
+    );
+  });
+
+  it('is shared with peekMany', async () => {
+    const bamPath = await copyBam(EXAMPLE_1_BAM, 'many.bam');
+    const url = files.url('many.bam');
+    const first = await peek({ bamPath: url });
+    await rm(bamPath);
+
+    const [entry] = await peekMany([url]);
+
+    expect(entry?.result).toEqual(first);
+  });
+});
@@ -0,0 +1,100 @@
+// Tests for peekWithReads, which peeks and lists the first reads in one pass
+
+import { mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import {
+  NanalogueBam,
+  peek,
+  peekWithReads,
+  type ReadOptions,
+  readInfo,
+} from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM } from './fixtures';
+
+describe('peekWithReads', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-peek-with-reads-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true, force: true });
+  });
+
+  it('matches peek and readInfo with the same options', async () => {
+    const options: ReadOptions = { bamPath: EXAMPLE_1_BAM, limit: 2 };
+
+    const result = await peekWithReads(options);
+
+    expect(result.peek).toEqual(await peek({ bamPath: EXAMPLE_1_BAM }));
+    expect(result.reads).toEqual(await readInfo(options));
+  });
+
+  it('applies the read filters and pagination to the reads only', async () => {
+    const options: ReadOptions = {
+      bamPath: simpleBamPath,
+      minSeqLen: 5000,
+      readFilter: 'primary_reverse',
+      offset: 3,
+      limit: 5,
+    };
+
+    const result = await peekWithReads(options, 20);
+
+    expect(result.reads).toEqual(await readInfo(options));
+    expect(result.peek).toEqual(
+      await peek({ bamPath: simpleBamPath, sampleSize: 20 }),
+    );
+  });
+
+  it('samples sampleSize records even past the last listed read', async () => {
+    const result = await peekWithReads(
+      { bamPath: simpleBamPath, limit: 1 },
+      500,
+    );
+
+    expect(result.reads).toHaveLength(1);
+    expect(result.peek).toEqual(
+      await peek({ bamPath: simpleBamPath, sampleSize: 500 }),
+    );
+  });
+
+  it('takes both from the region', async () => {
+    const result = await peekWithReads({
+      bamPath: EXAMPLE_1_BAM,
+      region: 'dummyIII',
+    });
+
+    expect(result.reads.map((r) => r.read_id)).toEqual([
+      'a4f36092-b4d5-47a9-813e-c22c3b477a0c',
+    ]);
+    expect(result.peek).toEqual(
+      await peek({ bamPath: EXAMPLE_1_BAM, region: 'dummyIII' }),
+    );
+  });
+
+  it('rejects sortBy', async () => {
+    await expect(
+      peekWithReads({ bamPath: EXAMPLE_1_BAM, sortBy: 'length' }),
+    ).rejects.toThrow(/sort_by is not supported/);
+  });
+
+  it('rejects a sampleSize of 0', async () => {
+    await expect(peekWithReads({ bamPath: EXAMPLE_1_BAM }, 0)).rejects.toThrow(
+      /sample_size must be a positive integer/,
+    );
+  });
+
+  it('is a NanalogueBam method', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+
+    expect(await bam.peekWithReads({ limit: 2 })).toEqual(
+      await peekWithReads({ bamPath: EXAMPLE_1_BAM, limit: 2 }),
+    );
+  });
+});
@@ -0,0 +1,329 @@
+// Tests for pileup, which summarises modification calls per reference position
+
+import { mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import {
+  bamMods,
+  type ModConfig,
+  NanalogueBam,
+  type PileupRecord,
+  pileup,
+  type ReadOptions,
+  simulateModBamTyped,
+} from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM } from './fixtures';
+
+/** Orders strings by code unit, as Rust orders them by byte. */
+function compareCodeUnits(a: string, b: string): number {
+  return a < b ? -1 : a > b ? 1 : 0;
+}
+
+/** Aggregates the calls of bamMods per position in JS, as pileup does in Rust. */
+async function aggregateCalls(options: ReadOptions & { region: string }) {
+  const [contig, range] = options.region.split(':');
+  const [start, end] = range ? range.split('-').map(Number) : [0, Infinity];
+  const counts = new Map<string, [number, number]>();
+  for (const record of await bamMods(options)) {
+    if (record.alignment_type === 'unmapped') continue;
+    const reverse = record.alignment_type.endsWith('reverse');
+    for (const table of record.mod_table) {
+      const strand = reverse === !table.is_strand_plus ? '+' : '-';
+      for (const [, refPos, mlValue] of table.data) {
+        if (refPos < start || refPos >= end) continue;
+        const key = `${refPos}|${strand}|${table.mod_code}`;
+        const count = counts.get(key) ?? [0, 0];
+        if (mlValue >= 128) count[0] += 1;
+        count[1] += 1;
+        counts.set(key, count);
+      }
+    }
+  }
+  return [...counts.entries()]
+    .map(([key, [nMod, nValid]]) => {
+      const [pos, strand, modCode] = key.split('|');
+      return {
+        contig,
+        pos: Number(pos),
+        strand,
+        mod_code: modCode,
+        n_mod: nMod,
+        n_valid: nValid,
+        fraction: nMod / nValid,
+      };
+    })
+    .sort(
+      (a, b) =>
+        a.pos - b.pos ||
+        compareCodeUnits(a.strand, b.strand) ||
+        compareCodeUnits(a.mod_code, b.mod_code),
+    );
+}
+
+describe('pileup', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-pileup-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('counts calls per position of a region', async () => {
+    const sites = await pileup({ bamPath: EXAMPLE_1_BAM, region: 'dummyIII' });
+
+    expect(sites.map((s) => [s.pos, s.n_mod, s.n_valid])).toEqual([
+      [26, 1, 1],
+      [31, 1, 1],
+      [50, 0, 1],
+      [62, 0, 1],
+      [70, 1, 1],
+    ]);
+    expect(sites[0]).toEqual({
+      contig: 'dummyIII',
+      pos: 26,
+      strand: '+',
+      mod_code: 'T',
+      n_mod: 1,
+      n_valid: 1,
+      fraction: 1,
+    });
+  });
+
+  it.each([
+    { region: 'contig_00000:1000-1200' },
+    { region: 'contig_00001:3000-3100', fullRegion: true, minModQual: 60 },
+    { region: 'contig_00001:0-500', mapqFilter: 15, limit: 10 },
+  ])('agrees with the calls of bamMods for %o', async (options) => {
+    const query = { bamPath: simpleBamPath, ...options };
+
+    expect(await pileup(query)).toEqual(await aggregateCalls(query));
+  });
+
+  it('leaves out positions below minCoverage', async () => {
+    const query = { bamPath: simpleBamPath, region: 'contig_00000:1000-1200' };
+    const sites = await pileup(query);
+    const covered = await pileup(query, { minCoverage: 55 });
+
+    expect(covered.length).toBeGreaterThan(0);
+    expect(covered.length).toBeLessThan(sites.length);
+    expect(covered).toEqual(sites.filter((s) => s.n_valid >= 55));
+    expect(
+      await pileup(
+        { bamPath: EXAMPLE_1_BAM, region: 'dummyIII' },
+        { minCoverage: 2 },
+      ),
+    ).toEqual([]);
+  });
+
+  it('takes minCoverage only as a pileup option', async () => {
+    const query = { bamPath: EXAMPLE_1_BAM, region: 'dummyIII' };
+
+    // @ts-expect-error - minCoverage is not a ReadOptions field
+    expect(await pileup({ ...query, minCoverage: 2 })).toEqual(
+      await pileup(query),
+    );
+    expect(await pileup(query, {})).toEqual(await pileup(query));
+  });
+
+  it('applies minCoverage to the calls in modRegion', async () => {
+    const query = {
+      bamPath: simpleBamPath,
+      region: 'contig_00000:1000-1200',
+      modRegion: 'contig_00000:1050-1100',
+    };
+    const sites = await pileup(query);
+    const covered = await pileup(query, { minCoverage: 55 });
+
+    expect(covered.every((s) => s.pos >= 1050 && s.pos < 1100)).toBe(true);
+    expect(covered).toEqual(sites.filter((s) => s.n_valid >= 55));
+  });
+
+  it('returns no records for a region without reads', async () => {
+    expect(await pileup({ bamPath: EXAMPLE_1_BAM, region: 'dummyII:0-5' })).toEqual([]);
+  });
+
+  it('requires a region', async () => {
+    await expect(pileup({ bamPath: EXAMPLE_1_BAM })).rejects.toThrow(
+      /region parameter is required for pileup/,
+    );
+  });
+
+  it('rejects multiple regions', async () => {
+    await expect(
+      pileup({ bamPath: EXAMPLE_1_BAM, regions: ['dummyI', 'dummyIII'] }),
+    ).rejects.toThrow(/pileup takes a single region/);
+  });
+
+  it('works on a NanalogueBam', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+
+    expect(await bam.pileup({ region: 'dummyIII' })).toEqual(
+      await pileup({ bamPath: EXAMPLE_1_BAM, region: 'dummyIII' }),
+    );
+    expect(
+      await bam.pileup({ region: 'dummyIII' }, { minCoverage: 2 }),
+    ).toEqual([]);
+  });
+});
+
+/**
+ * Collapses the pileup `sites` of m calls on the minus strand onto the plus
+ * strand C one position before, within [start, end), as collapseStrands does.
+ */
+function collapseCpg(sites: PileupRecord[], start: number, end: number) {
+  const merged = new Map<string, PileupRecord>();
+  for (const site of sites) {
+    const moved = site.mod_code === 'm' && site.strand === '-';
+    const pos = moved ? site.pos - 1 : site.pos;
+    if (pos < start || pos >= end) continue;
+    const strand = moved ? '+' : site.strand;
+    const key = `${pos}|${strand}|${site.mod_code}`;
+    const entry = merged.get(key) ?? {
+      ...site,
+      pos,
+      strand,
+      n_mod: 0,
+      n_valid: 0,
+    };
+    entry.n_mod += site.n_mod;
+    entry.n_valid += site.n_valid;
+    entry.fraction = entry.n_mod / entry.n_valid;
+    merged.set(key, entry);
+  }
+  return [...merged.values()].sort(
+    (a, b) =>
+      a.pos - b.pos ||
+      compareCodeUnits(a.strand, b.strand) ||
+      compareCodeUnits(a.mod_code, b.mod_code),
+  );
+}
+
+/** Options of simulateModBamTyped for the 5mC calls of the collapse tests. */
+const METHYL_ON_C: ModConfig = {
+  base: 'C',
+  isStrandPlus: true,
+  modCode: 'm',
+  win: [4, 4],
+  modRange: [[0.1, 0.9]],
+};
+
+/** Simulates reads with the 5mC calls of `methyl` and calls of code T on T. */
+async function simulateCpgBam(
+  bamPath: string,
+  methyl: ModConfig,
+): Promise<void> {
+  await simulateModBamTyped(
+    {
+      contigs: { number: 1, lenRange: [1000, 1000] },
+      reads: [
+        {
+          number: 200,
+          lenRange: [0.2, 0.2],
+          mods: [
+            methyl,
+            {
+              base: 'T',
+              isStrandPlus: true,
+              modCode: 'T',
+              win: [4, 4],
+              modRange: [[0.2, 0.8]],
+            },
+          ],
+        },
+      ],
+      seed: 3,
+    },
+    bamPath,
+    bamPath.replace(/\.bam$/, '.fa'),
+  );
+}
+
+describe('pileup with collapseStrands', () => {
+  const region = 'contig_00000:100-400';
+  let tmpDir: string;
+  let bamPath: string;
+  // 5mC calls reported on the complement of the C, as G-m
+  let complementBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-pileup-cpg-'));
+    bamPath = join(tmpDir, 'cpg.bam');
+    complementBamPath = join(tmpDir, 'cpg_g.bam');
+    await simulateCpgBam(bamPath, METHYL_ON_C);
+    await simulateCpgBam(complementBamPath, {
+      ...METHYL_ON_C,
+      base: 'G',
+      isStrandPlus: false,
+    });
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('counts the minus strand C of a CpG at the plus strand C', async () => {
+    const sites = await pileup({ bamPath, region }, { collapseStrands: true });
+    // The minus strand C at the end of the region pairs with a C inside it
+    const wider = await pileup({ bamPath, region: 'contig_00000:100-401' });
+
+    expect(sites).toEqual(collapseCpg(wider, 100, 400));
+    expect(sites.some((s) => s.mod_code === 'm' && s.strand === '-')).toBe(
+      false,
+    );
+  });
+
+  it('collapses the calls reported on the G of a CpG', async () => {
+    const query = { bamPath: complementBamPath, region };
+    const wider = await pileup({
+      ...query,
+      region: 'contig_00000:100-401',
+    });
+    const sites = await pileup(query, { collapseStrands: true });
+
+    expect(wider.some((s) => s.mod_code === 'm' && s.strand === '-')).toBe(
+      true,
+    );
+    expect(sites).toEqual(collapseCpg(wider, 100, 400));
+    expect(sites.some((s) => s.mod_code === 'm' && s.strand === '-')).toBe(
+      false,
+    );
+  });
+
+  it('keeps the strands of calls on other bases', async () => {
+    const modsOnT = (sites: PileupRecord[]) =>
+      sites.filter((s) => s.mod_code === 'T');
+
+    const sites = await pileup({ bamPath, region });
+    expect(sites.some((s) => s.mod_code === 'T' && s.strand === '-')).toBe(
+      true,
+    );
+    expect(
+      modsOnT(await pileup({ bamPath, region }, { collapseStrands: true })),
+    ).toEqual(modsOnT(sites));
+  });
+
+  it('takes collapseStrands only as a pileup option', async () => {
+    const query = { bamPath, region };
+
+    // @ts-expect-error - collapseStrands is not a ReadOptions field
+    expect(await pileup({ ...query, collapseStrands: true })).toEqual(
+      await pileup(query),
+    );
+  });
+
+  it('applies minCoverage to the collapsed counts', async () => {
+    const query = { bamPath, region };
+    const sites = await pileup(query, { collapseStrands: true });
+    const threshold = Math.max(...sites.map((s) => s.n_valid));
+
+    expect(
+      await pileup(query, { collapseStrands: true, minCoverage: threshold }),
+    ).toEqual(sites.filter((s) => s.n_valid >= threshold));
+  });
+});
@@ -0,0 +1,91 @@
+// Tests for the onProgress callback of readInfo and bamMods
+
+import { mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it, vi } from 'vitest';
+import { bamMods, NanalogueBam, type ReadOptions, readInfo } from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM } from './fixtures';
+
+describe('onProgress', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-progress-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('readInfo reports every progressInterval records and the total', async () => {
+    const counts: number[] = [];
+    const result = await readInfo({
+      bamPath: simpleBamPath,
+      onProgress: (n) => counts.push(n),
+      progressInterval: 300,
+    });
+
+    // simple_bam has 1000 records, all of which pass the default filters
+    expect(result).toHaveLength(1000);
+    await vi.waitFor(() => expect(counts).toEqual([300, 600, 900, 1000]));
+  });
+
+  it('bamMods uses the default interval of 1000 records', async () => {
+    const counts: number[] = [];
+    await bamMods({
+      bamPath: simpleBamPath,
+      onProgress: (n) => counts.push(n),
+    });
+
+    await vi.waitFor(() => expect(counts).toEqual([1000]));
+  });
+
+  it('counts records read before pagination stops iteration', async () => {
+    const counts: number[] = [];
+    await readInfo({
+      bamPath: simpleBamPath,
+      onProgress: (n) => counts.push(n),
+      limit: 5,
+    });
+
+    await vi.waitFor(() => expect(counts).toEqual([5]));
+  });
+
+  it('reports records that are later filtered out', async () => {
+    const counts: number[] = [];
+    const result = await readInfo({
+      bamPath: EXAMPLE_1_BAM,
+      readIdSet: ['no-such-read'],
+      onProgress: (n) => counts.push(n),
+    });
+
+    expect(result).toHaveLength(0);
+    await vi.waitFor(() => expect(counts).toEqual([4]));
+  });
+
+  it('works with NanalogueBam methods', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+    const counts: number[] = [];
+    await bam.readInfo({ onProgress: (n) => counts.push(n), progressInterval: 2 });
+
+    await vi.waitFor(() => expect(counts).toEqual([2, 4]));
+  });
+
+  it('rejects progressInterval of 0', async () => {
+    await expect(
+      readInfo({ bamPath: EXAMPLE_1_BAM, progressInterval: 0 }),
+    ).rejects.toThrow(/progress_interval must be a positive integer/i);
+  });
+
+  it('rejects an onProgress value that is not a function', async () => {
+    await expect(
+      readInfo({
+        bamPath: EXAMPLE_1_BAM,
+        onProgress: 5,
+      } as unknown as ReadOptions),
+    ).rejects.toThrow(/expected a function/i);
+  });
+});
@@ -0,0 +1,116 @@
+// Tests for qualOffset, which decodes Phred+64 base qualities of legacy data
+
+import { mkdtemp, readFile, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import {
+  ErrorKind,
+  exportFastq,
+  NanalogueBam,
+  qualityProfiles,
+  readInfo,
+  seqTableRecords,
+  simulateModBamTyped,
+} from '../index';
+import { EXAMPLE_PYNANALOGUE_1_BAM } from './fixtures';
+
+describe('qualOffset', () => {
+  let tmpDir: string;
+  let bamPath: string;
+  let region: string;
+
+  // Phred+64 qualities from 20 to 30 are stored as 51 to 61
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-qual-offset-'));
+    bamPath = join(tmpDir, 'phred64.bam');
+    await simulateModBamTyped(
+      {
+        contigs: { number: 1, lenRange: [200, 200] },
+        reads: [{ number: 20, baseQualRange: [51, 61], lenRange: [0.5, 0.5] }],
+        seed: 1,
+      },
+      bamPath,
+      join(tmpDir, 'phred64.fa'),
+    );
+    const [read] = await readInfo({ bamPath });
+    region = `${read?.contig}:${read?.reference_start}-${read?.reference_end}`;
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('decodes Phred+64 qualities in qualityProfiles', async () => {
+    const stored = await qualityProfiles({ bamPath });
+    const decoded = await qualityProfiles({ bamPath, qualOffset: 64 });
+
+    expect(decoded.map((p) => p.q)).toEqual(
+      stored.map((p) => p.q.map((v) => v - 31)),
+    );
+    for (const profile of decoded) {
+      expect(profile.q.every((v) => v >= 20 && v <= 30)).toBe(true);
+    }
+  });
+
+  it('decodes Phred+64 qualities in seqTableRecords', async () => {
+    const stored = await seqTableRecords({ bamPath, region });
+    const decoded = await seqTableRecords({ bamPath, region, qualOffset: 64 });
+    const shift = (qualities: string) =>
+      qualities
+        .split('.')
+        .map((v) => (v === '255' ? v : String(Number(v) - 31)))
+        .join('.');
+
+    expect(decoded.length).toBeGreaterThan(0);
+    expect(decoded.map((r) => r.qualities)).toEqual(
+      stored.map((r) => shift(r.qualities ?? '')),
+    );
+  });
+
+  it('writes Phred+33 FASTQ qualities from Phred+64 input', async () => {
+    const stored = join(tmpDir, 'stored.fastq');
+    const decoded = join(tmpDir, 'decoded.fastq');
+    await exportFastq({ bamPath }, stored);
+    await exportFastq({ bamPath, qualOffset: 64 }, decoded);
+    const qualLines = async (path: string) =>
+      (await readFile(path, 'utf-8'))
+        .split('\n')
+        .filter((_, i) => i % 4 === 3);
+
+    const shifted = (await qualLines(stored)).map((line) =>
+      [...line].map((c) => String.fromCharCode(c.charCodeAt(0) - 31)).join(''),
+    );
+    expect(shifted.length).toBeGreaterThan(0);
+    expect(await qualLines(decoded)).toEqual(shifted);
+  });
+
+  it('is a NanalogueBam query option', async () => {
+    const bam = new NanalogueBam({ bamPath });
+
+    expect(await bam.qualityProfiles({ qualOffset: 64 })).toEqual(
+      await qualityProfiles({ bamPath, qualOffset: 64 }),
+    );
+  });
+
+  it('rejects offsets other than 33 and 64', async () => {
+    expect(await qualityProfiles({ bamPath, qualOffset: 33 })).toEqual(
+      await qualityProfiles({ bamPath }),
+    );
+    await expect(
+      qualityProfiles({ bamPath, qualOffset: 40 }),
+    ).rejects.toMatchObject({
+      code: ErrorKind.InvalidOptions,
+      message: expect.stringMatching(/must be 33 or 64/),
+    });
+  });
+
+  it('rejects qualities below the offset', async () => {
+    await expect(
+      qualityProfiles({ bamPath: EXAMPLE_PYNANALOGUE_1_BAM, qualOffset: 64 }),
+    ).rejects.toMatchObject({
+      code: ErrorKind.InvalidOptions,
+      message: expect.stringMatching(/below the offset/),
+    });
+  });
+});
@@ -0,0 +1,86 @@
+// Tests for qualityProfiles, which gives the base qualities of each read
+
+import { describe, expect, it } from 'vitest';
+import {
+  ErrorKind,
+  NanalogueBam,
+  qualityProfiles,
+  seqTableRecords,
+} from '../index';
+import {
+  EXAMPLE_1_BAM,
+  EXAMPLE_CIGAR_BAM,
+  EXAMPLE_PYNANALOGUE_1_BAM,
+} from './fixtures';
+
+describe('qualityProfiles', () => {
+  it('gives the qualities of every base without a region', async () => {
+    const profiles = await qualityProfiles({
+      bamPath: EXAMPLE_PYNANALOGUE_1_BAM,
+    });
+
+    expect(profiles.map((p) => [p.mean_q, p.q.length])).toEqual([
+      [20, 164],
+      [30, 164],
+    ]);
+    expect(profiles[0]?.q.every((v) => v === 20)).toBe(true);
+  });
+
+  it('clips to the region with 255 for a deletion', async () => {
+    const profiles = await qualityProfiles({
+      bamPath: EXAMPLE_CIGAR_BAM,
+      region: 'contig1:30-70',
+    });
+
+    expect(profiles).toEqual([
+      {
+        read_id: 'read_clipped',
+        mean_q: 40,
+        q: [40, 40, 40, 40, 255, 255, 255, 255, 40, 40, 40, 40, 40, 40],
+      },
+      {
+        read_id: 'read_hard_clipped',
+        mean_q: 40,
+        q: [40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 255],
+      },
+    ]);
+  });
+
+  it('lines up with the seqTable qualities, insertions included', async () => {
+    const options = {
+      bamPath: EXAMPLE_PYNANALOGUE_1_BAM,
+      region: 'contig_00000:95-105',
+    };
+    const profiles = await qualityProfiles(options);
+    const records = await seqTableRecords(options);
+
+    expect(profiles.map((p) => [p.read_id, p.q.join('.')])).toEqual(
+      records.map((r) => [r.read_id, r.qualities]),
+    );
+  });
+
+  it('leaves out the mean of reads without qualities', async () => {
+    const profiles = await qualityProfiles({ bamPath: EXAMPLE_1_BAM });
+
+    expect(profiles).toHaveLength(4);
+    for (const profile of profiles) {
+      expect(profile.mean_q).toBeUndefined();
+      expect(profile.q.every((v) => v === 255)).toBe(true);
+    }
+  });
+
+  it('rejects regions', async () => {
+    await expect(
+      qualityProfiles({ bamPath: EXAMPLE_1_BAM, regions: ['dummyI'] }),
+    ).rejects.toMatchObject({ code: ErrorKind.InvalidOptions });
+  });
+
+  it('is a NanalogueBam method', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_CIGAR_BAM });
+    const region = 'contig1:0-200';
+
+    expect(await bam.qualityProfiles({ region })).toEqual(
+      await qualityProfiles({ bamPath: EXAMPLE_CIGAR_BAM, region }),
+    );
+  });
+});
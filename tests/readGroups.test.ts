@@ -0,0 +1,74 @@
+// Tests for readGroups and the readGroup read filter
+
+import { describe, expect, it } from 'vitest';
+import {
+  ErrorKind,
+  NanalogueBam,
+  readGroups,
+  readInfo,
+  windowReads,
+} from '../index';
+import { EXAMPLE_READ_GROUPS_BAM } from './fixtures';
+
+describe('readGroups', () => {
+  it('lists the read groups of the header in order', async () => {
+    expect(await readGroups({ bamPath: EXAMPLE_READ_GROUPS_BAM })).toEqual([
+      'sampleA',
+      'sampleB',
+    ]);
+  });
+
+  it('works on a NanalogueBam', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_READ_GROUPS_BAM });
+    expect(await bam.readGroups()).toEqual(['sampleA', 'sampleB']);
+  });
+});
+
+describe('readGroup filter', () => {
+  const readIds = async (readGroup: string[]) =>
+    (await readInfo({ bamPath: EXAMPLE_READ_GROUPS_BAM, readGroup })).map(
+      (v) => v.read_id,
+    );
+
+  it('keeps only reads of the given read groups', async () => {
+    expect(await readIds(['sampleA'])).toEqual(['read_a1', 'read_a2']);
+    expect(await readIds(['sampleB', 'sampleA'])).toEqual([
+      'read_a1',
+      'read_b1',
+      'read_a2',
+      'read_b2',
+    ]);
+  });
+
+  it('excludes reads without an RG tag', async () => {
+    const all = await readInfo({ bamPath: EXAMPLE_READ_GROUPS_BAM });
+    expect(all.map((v) => v.read_id)).toContain('read_none');
+    expect(await readIds(['sampleA', 'sampleB'])).not.toContain('read_none');
+    expect(await readIds(['absent'])).toEqual([]);
+  });
+
+  it('applies to windowReads', async () => {
+    const tsv = await windowReads({
+      bamPath: EXAMPLE_READ_GROUPS_BAM,
+      readGroup: ['sampleB'],
+      win: 2,
+      step: 1,
+      outputFormat: 'tsv',
+    });
+    const ids = new Set(
+      tsv
+        .toString()
+        .trim()
+        .split('\n')
+        .slice(1)
+        .map((line) => line.split('\t')[3]),
+    );
+    expect([...ids].sort()).toEqual(['read_b1', 'read_b2']);
+  });
+
+  it('rejects an empty list', async () => {
+    await expect(
+      readInfo({ bamPath: EXAMPLE_READ_GROUPS_BAM, readGroup: [] }),
+    ).rejects.toMatchObject({ code: ErrorKind.InvalidOptions });
+  });
+});
@@ -4,7 +4,7 @@
 import { readFile } from 'node:fs/promises';
 import { resolve } from 'node:path';
 import { describe, expect, it } from 'vitest';
-import { type ReadInfoRecord, readInfo } from '../index';
+import { type ReadInfoRecord, readInfo, readInfoJson } from '../index';
 import { getExampleBamPath, loadExpectedJson } from './helpers';
 
 const getTestDataPath = (relativePath: string) =>
@@ -76,4 +76,78 @@ describe('readInfo', () => {
 
     expect(resultAll.length).toBeGreaterThan(resultFiltered.length);
   });
+
+  it('filters by maxSeqLen', async () => {
+    const bamPath = getTestDataPath('examples/example_1.bam');
+
+    const resultAll = await readInfo({ bamPath });
+    const resultShort = await readInfo({ bamPath, maxSeqLen: 40 });
+    const resultLong = await readInfo({ bamPath, minSeqLen: 41 });
+
+    expect(resultShort.map((r) => r.sequence_length)).toEqual([8, 33]);
+    expect(resultShort.length + resultLong.length).toBe(resultAll.length);
+    expect(
+      await readInfo({ bamPath, minSeqLen: 33, maxSeqLen: 33 }),
+    ).toHaveLength(1);
+  });
+
+  it('filters by maxAlignLen, leaving out unmapped reads', async () => {
+    const bamPath = getTestDataPath('examples/example_1.bam');
+
+    const result = await readInfo({ bamPath, maxAlignLen: 40 });
+
+    expect(result.map((r) => r.alignment_length)).toEqual([8, 33]);
+    expect(await readInfo({ bamPath, maxAlignLen: 48 })).toHaveLength(3);
+  });
+
+  it('filters by minMods, counting the calls in mod_count', async () => {
+    const bamPath = getTestDataPath('examples/example_1.bam');
+
+    const result = await readInfo({ bamPath, minMods: 2 });
+
+    expect(result.map((r) => r.mod_count)).toEqual([
+      'T+T:3;(probabilities >= 0.5020, PHRED base qual >= 0)',
+      'G-7200:0;T+T:3;(probabilities >= 0.5020, PHRED base qual >= 0)',
+    ]);
+    expect(await readInfo({ bamPath, minMods: 1 })).toHaveLength(3);
+    expect(await readInfo({ bamPath, minMods: 4 })).toEqual([]);
+  });
+
+  it('rejects a maximum length below the minimum', async () => {
+    const bamPath = getTestDataPath('examples/example_1.bam');
+
+    await expect(
+      readInfo({ bamPath, minSeqLen: 20, maxSeqLen: 10 }),
+    ).rejects.toThrow(/max_seq_len \(10\) must be >= min_seq_len \(20\)/);
+    await expect(
+      readInfo({ bamPath, minAlignLen: 20, maxAlignLen: 10 }),
+    ).rejects.toThrow(
+      /max_align_len \(10\) must be >= min_align_len \(20\)/,
+    );
+  });
+});
+
+describe('readInfoJson', () => {
+  it('returns JSON text that parses to the readInfo records', async () => {
+    const bamPath = getExampleBamPath('example_1.bam');
+
+    const text = await readInfoJson({ bamPath });
+
+    expect(typeof text).toBe('string');
+    expect(JSON.parse(text)).toEqual(await readInfo({ bamPath }));
+  });
+
+  it('agrees with readInfo on mod_count under modification filters', async () => {
+    const options = {
+      bamPath: getExampleBamPath('example_pynanalogue_1.bam'),
+      minModQual: 100,
+      rejectModQualNonInclusive: [50, 200] as [number, number],
+      baseQualFilterMod: 15,
+      trimReadEndsMod: 10,
+    };
+
+    expect(JSON.parse(await readInfoJson(options))).toEqual(
+      await readInfo(options),
+    );
+  });
 });
@@ -0,0 +1,129 @@
+// Tests for readInfoBatch and windowReadsBatch, which run several queries in
+// one call
+
+import { mkdtemp, readFile, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, afterEach, beforeAll, describe, expect, it } from 'vitest';
+import {
+  ErrorKind,
+  readInfo,
+  readInfoBatch,
+  setMaxConcurrency,
+  windowReads,
+  windowReadsBatch,
+} from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM, getExampleBamPath } from './fixtures';
+
+const MISSING_BAM = getExampleBamPath('missing.bam');
+
+describe('readInfoBatch', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-batch-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  afterEach(() => {
+    setMaxConcurrency();
+  });
+
+  it('returns the result of each query, in order', async () => {
+    const optionsList = [
+      { bamPath: simpleBamPath, region: 'contig_00000:0-2000' },
+      { bamPath: EXAMPLE_1_BAM, region: 'dummyIII' },
+      { bamPath: EXAMPLE_1_BAM, readIdSet: ['absent'] },
+      { bamPath: EXAMPLE_1_BAM },
+    ];
+
+    const entries = await readInfoBatch(optionsList);
+
+    expect(entries).toEqual(
+      await Promise.all(
+        optionsList.map(async (options) => ({
+          ok: true,
+          result: await readInfo(options),
+        })),
+      ),
+    );
+    expect(entries[0].result).not.toEqual([]);
+  });
+
+  it('reports a failing query without failing the batch', async () => {
+    const entries = await readInfoBatch([
+      { bamPath: MISSING_BAM },
+      { bamPath: EXAMPLE_1_BAM, region: 'absent' },
+      { bamPath: EXAMPLE_1_BAM, region: 'dummyI' },
+    ]);
+
+    expect(entries[0].ok).toBe(false);
+    expect(entries[0].result).toBeUndefined();
+    expect(entries[0].code).toBe(ErrorKind.FileNotFound);
+    expect(entries[0].error).toMatch(/^Failed to open BAM/);
+    expect(entries[1].ok).toBe(false);
+    expect(entries[1].code).toBe(ErrorKind.InvalidRegion);
+    expect(entries[2]).toEqual({
+      ok: true,
+      result: await readInfo({ bamPath: EXAMPLE_1_BAM, region: 'dummyI' }),
+    });
+  });
+
+  it('returns no entries for no options', async () => {
+    expect(await readInfoBatch([])).toEqual([]);
+  });
+
+  it('completes every query under the concurrency limit', async () => {
+    setMaxConcurrency(1);
+    const optionsList = Array.from({ length: 6 }, () => ({
+      bamPath: simpleBamPath,
+    }));
+
+    const entries = await readInfoBatch(optionsList);
+
+    const expected = await readInfo({ bamPath: simpleBamPath });
+    expect(entries).toEqual(
+      optionsList.map(() => ({ ok: true, result: expected })),
+    );
+  });
+});
+
+describe('windowReadsBatch', () => {
+  let tmpDir: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-window-batch-'));
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('returns the output of each query, in order', async () => {
+    const options = { bamPath: EXAMPLE_1_BAM, win: 2, step: 1 };
+    const outputPath = join(tmpDir, 'windows.tsv');
+
+    const entries = await windowReadsBatch([
+      options,
+      { ...options, win: 0 },
+      { ...options, outputPath },
+    ]);
+
+    expect(entries[0]).toEqual({
+      ok: true,
+      result: await windowReads(options),
+    });
+    expect(entries[1].ok).toBe(false);
+    expect(entries[1].code).toBe(ErrorKind.InvalidOptions);
+    expect(entries[1].error).toMatch(/Window size must be > 0/);
+    expect(entries[2].ok).toBe(true);
+    const written = await readFile(outputPath, 'utf8');
+    expect(written).toBe(entries[0].result);
+    expect(entries[2].result).toBe(written.trimEnd().split('\n').length);
+  });
+});
@@ -5,9 +5,16 @@
 import { mkdtemp, rm } from 'node:fs/promises';
 import { tmpdir } from 'node:os';
 import { join } from 'node:path';
+import { pathToFileURL } from 'node:url';
 import { afterAll, beforeAll, describe, expect, it } from 'vitest';
 
-import { type ReadInfoRecord, readInfo } from '../index';
+import {
+  ModStrand,
+  ReadFilterKind,
+  type ReadInfoRecord,
+  type ReadOptions,
+  readInfo,
+} from '../index';
 import {
   createInputOptions,
   createSimpleBam,
@@ -91,6 +98,31 @@ describe('TestInputBamFiltering', () => {
     expect(resultFiltered2.length).toBe(0);
   });
 
+  it('test_mapq_max', async () => {
+    const base = createInputOptions(simpleBamPath);
+
+    const resultAll = await readInfo(base);
+    // Mapped reads have mapq 10-20; unmapped reads have mapq 255 (unavailable)
+    const resultInRange = await readInfo({ ...base, mapqFilter: 10, mapqMax: 20 });
+    const resultAbove = await readInfo({ ...base, mapqFilter: 21 });
+
+    expect(resultInRange.length).toBeGreaterThan(0);
+    expect(resultAbove.length).toBeGreaterThan(0);
+    expect(resultInRange.length + resultAbove.length).toBe(resultAll.length);
+    expect(resultInRange.every((r) => r.alignment_type !== 'unmapped')).toBe(true);
+
+    // No read has a mapq below 10
+    expect(await readInfo({ ...base, mapqMax: 9 })).toEqual([]);
+  });
+
+  it('test_mapq_max_below_mapq_filter', async () => {
+    const base = createInputOptions(simpleBamPath);
+
+    await expect(readInfo({ ...base, mapqFilter: 20, mapqMax: 10 })).rejects.toThrow(
+      /mapq_max \(10\) must be >= mapq_filter \(20\)/,
+    );
+  });
+
   it.each([
     [1.0, 1.0],
     [0.5, 0.5],
@@ -205,6 +237,41 @@ describe('TestInputBamFiltering', () => {
     expect(resultPrimary.length).toBe(resultPrimary2.length);
   });
 
+  it('test_read_filter_kind_list_matches_string', async () => {
+    const base = createInputOptions(simpleBamPath);
+
+    const fromList = await readInfo({
+      ...base,
+      readFilter: [ReadFilterKind.PrimaryForward, ReadFilterKind.PrimaryReverse],
+    });
+    const fromString = await readInfo({
+      ...base,
+      readFilter: 'primary_forward,primary_reverse',
+    });
+
+    expect(fromList.length).toBeGreaterThan(0);
+    expect(fromList).toEqual(fromString);
+  });
+
+  it('test_read_filter_empty_list_raises_error', async () => {
+    const base = createInputOptions(simpleBamPath);
+
+    await expect(readInfo({ ...base, readFilter: [] })).rejects.toThrow(
+      /readFilter must include at least one alignment type/,
+    );
+  });
+
+  it('test_read_filter_unknown_kind_raises_error', async () => {
+    const base = createInputOptions(simpleBamPath);
+
+    await expect(
+      readInfo({
+        ...base,
+        readFilter: ['primary_fwd'] as unknown as ReadFilterKind[],
+      }),
+    ).rejects.toThrow(/"primary_fwd"` does not match any variant of enum `ReadFilterKind`/);
+  });
+
   it('test_read_ids_filter_single', async () => {
     const base = createInputOptions(simpleBamPath);
 
@@ -255,6 +322,51 @@ describe('TestInputBamFiltering', () => {
     );
     expect(filteredIds).toEqual(new Set(selectedReadIds));
   });
+
+  it('test_read_id_regex_filter', async () => {
+    const base = createInputOptions(simpleBamPath);
+
+    // Simulated read ids are `<group>.<uuid>`; keep those whose uuid starts
+    // with the same character as that of the first read
+    const resultAll = await readInfo(base);
+    const uuidStart = resultAll[0].read_id.split('.')[1][0];
+    const expected = resultAll.filter((r: ReadInfoRecord) =>
+      r.read_id.split('.')[1].startsWith(uuidStart),
+    );
+
+    const resultFiltered = await readInfo({
+      ...base,
+      readIdRegex: `^\\d+\\.${uuidStart}`,
+    });
+
+    expect(resultFiltered).toEqual(expected);
+    expect(resultFiltered.length).toBeLessThan(resultAll.length);
+  });
+
+  it('test_read_id_regex_and_read_id_set', async () => {
+    const base = createInputOptions(simpleBamPath);
+
+    const resultAll = await readInfo(base);
+    const allReadIds = getUniqueReadIdsFromRecords(resultAll);
+    const [kept, dropped] = allReadIds;
+
+    // Only the read id in the set that also matches the pattern is kept
+    const resultFiltered = await readInfo({
+      ...base,
+      readIdSet: [kept, dropped],
+      readIdRegex: `^${kept}$`,
+    });
+
+    expect(getUniqueReadIdsFromRecords(resultFiltered)).toEqual([kept]);
+  });
+
+  it('test_read_id_regex_invalid', async () => {
+    const base = createInputOptions(simpleBamPath);
+
+    await expect(readInfo({ ...base, readIdRegex: '(' })).rejects.toThrow(
+      /Invalid read_id_regex: regex parse error/,
+    );
+  });
 });
 
 describe('TestInputModsFiltering', () => {
@@ -287,6 +399,24 @@ describe('TestInputModsFiltering', () => {
     expect(totalModsBcComp).toBe(0);
   });
 
+  it('test_mod_strand_enum_matches_string', async () => {
+    const base = createInputOptions(simpleBamPath);
+
+    const fromEnum = await readInfo({ ...base, modStrand: ModStrand.BcComp });
+    const fromString = await readInfo({ ...base, modStrand: 'bc_comp' });
+
+    expect(ModStrand.Bc).toBe('bc');
+    expect(fromEnum).toEqual(fromString);
+  });
+
+  it('test_mod_strand_invalid_raises_error', async () => {
+    const base = createInputOptions(simpleBamPath);
+
+    await expect(
+      readInfo({ ...base, modStrand: 'foo' } as unknown as ReadOptions),
+    ).rejects.toThrow(/does not match any variant of enum `ModStrand`/);
+  });
+
   it('test_min_mod_qual_filter', async () => {
     const base = createInputOptions(simpleBamPath);
 
@@ -366,8 +496,8 @@ describe('TestInputModsFiltering', () => {
 });
 
 describe('TestTreatAsUrl', () => {
-  // NOTE: We only test treatAsUrl=false here because we cannot rely on URLs
-  // being active forever. The treatAsUrl=true pathway is not tested.
+  // NOTE: We only test local file:// URLs here because we cannot rely on remote
+  // URLs being active forever.
 
   let tmpDir: string;
   let simpleBamPath: string;
@@ -401,6 +531,24 @@ describe('TestTreatAsUrl', () => {
 
     expect(result.length).toBeGreaterThan(0);
   });
+
+  it('test_treat_as_url_undefined_detects_url_scheme', async () => {
+    const base = createInputOptions(pathToFileURL(simpleBamPath).href);
+
+    // treatAsUrl undefined (default) - a file:// URL is read as the local file
+    const result = await readInfo(base);
+
+    expect(result).toEqual(await readInfo(createInputOptions(simpleBamPath)));
+  });
+
+  it('test_treat_as_url_false_overrides_url_scheme', async () => {
+    const base = createInputOptions(pathToFileURL(simpleBamPath).href);
+
+    // Explicitly set treatAsUrl=false - the URL is taken as a file name
+    await expect(readInfo({ ...base, treatAsUrl: false })).rejects.toThrow(
+      /file not found/,
+    );
+  });
 });
 
 describe('TestRejectModQualNonInclusive', () => {
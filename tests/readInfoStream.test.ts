@@ -0,0 +1,103 @@
+// Tests for readInfoStream, the async-iterator version of readInfo
+
+import { mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import { type ReadInfoRecord, readInfo, readInfoStream } from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM } from './fixtures';
+
+async function collect(
+  stream: AsyncIterable<ReadInfoRecord>,
+): Promise<ReadInfoRecord[]> {
+  const records: ReadInfoRecord[] = [];
+  for await (const record of stream) {
+    records.push(record);
+  }
+  return records;
+}
+
+describe('readInfoStream', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-stream-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('yields the same records as readInfo', async () => {
+    const expected = await readInfo({ bamPath: simpleBamPath });
+    const actual = await collect(readInfoStream({ bamPath: simpleBamPath }));
+
+    expect(actual).toHaveLength(1000);
+    expect(actual).toEqual(expected);
+  });
+
+  it('applies filters and pagination like readInfo', async () => {
+    const options = {
+      bamPath: simpleBamPath,
+      region: 'contig_00001',
+      minModQual: 200,
+      offset: 5,
+      limit: 10,
+    };
+    const expected = await readInfo(options);
+    const actual = await collect(readInfoStream(options));
+
+    expect(actual).toEqual(expected);
+  });
+
+  it('reports done after the last record', async () => {
+    const stream = readInfoStream({ bamPath: EXAMPLE_1_BAM, region: 'dummyI' });
+
+    const first = await stream.next();
+    expect(first.done).toBe(false);
+    expect(first.value?.read_id).toBe('5d10eb9a-aae1-4db8-8ec6-7ebb34d32575');
+    expect((await stream.next()).done).toBe(true);
+    expect((await stream.next()).done).toBe(true);
+  });
+
+  it('stays a bounded number of records ahead of the consumer', async () => {
+    const counts: number[] = [];
+    const stream = readInfoStream({
+      bamPath: simpleBamPath,
+      onProgress: (n) => counts.push(n),
+      progressInterval: 1,
+    });
+
+    await stream.next();
+    await new Promise((r) => setTimeout(r, 200));
+    expect(Math.max(...counts)).toBeLessThan(50);
+    await stream.return();
+  });
+
+  it('stops when the consumer breaks out of the loop', async () => {
+    let seen = 0;
+    const stream = readInfoStream({ bamPath: simpleBamPath });
+    for await (const _record of stream) {
+      seen += 1;
+      if (seen === 3) break;
+    }
+
+    expect(seen).toBe(3);
+    expect((await stream.next()).done).toBe(true);
+  });
+
+  it('raises option errors from the first next()', async () => {
+    const stream = readInfoStream({ bamPath: EXAMPLE_1_BAM, limit: 0 });
+
+    await expect(stream.next()).rejects.toThrow(/limit must be a positive integer/);
+    expect((await stream.next()).done).toBe(true);
+  });
+
+  it('raises an error for a missing file', async () => {
+    const stream = readInfoStream({ bamPath: '/nonexistent/file.bam' });
+
+    await expect(stream.next()).rejects.toThrow(/failed to open bam/i);
+  });
+});
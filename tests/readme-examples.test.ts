@@ -192,7 +192,7 @@ async function executeCode(code: string): Promise<string> {
     'nanalogue',
     'console',
     `
-    const { peek, readInfo, bamMods, windowReads, seqTable, simulateModBam } = nanalogue;
+    const { peek, peekMany, clearPeekCache, peekWithReads, readInfo, readInfoBatch, readInfoJson, getRead, countReads, filterStats, bamMods, bamModsJson, bamModsTyped, windowReads, windowReadsBatch, windowReadsArrays, windowAggregate, windowMatrix, seqTable, seqTableRecords, readsTable, alignedSequences, qualityProfiles, gcContent, simulateModBam, simulateModBamTyped, NanalogueBam, CancelToken, readInfoStream, exportBam, exportFastq, exportBedgraph, buildIndex, modQualHistogram, suggestThreshold, mapqHistogram, modSummary, modCountsByContig, alignStats, annotateReads, pileup, coverage, contigSummary, idxstats, estimateReadCount, flagstat, readGroups, validateSimulationConfig, setMaxConcurrency, initThreadPool, setLogCallback, version, parseRegion, ErrorKind } = nanalogue;
     return (async () => {
       ${transformed}
     })();
@@ -238,12 +238,17 @@ describe('README examples', () => {
           }
         }
       } finally {
-        // Clean up any files that NOOUTPUT examples may have created
+        // Clean up any files that examples may have created
         // (e.g. simulateModBam writes output.bam and output.fasta)
-        if (expectedOutput === null) {
-          for (const file of ['output.bam', 'output.bam.bai', 'output.fasta']) {
-            rmSync(file, { force: true });
-          }
+        for (const file of [
+          'output.bam',
+          'output.bam.bai',
+          'output.bedgraph',
+          'output.fasta',
+          'output.fastq.gz',
+          'windows.tsv',
+        ]) {
+          rmSync(file, { force: true });
         }
       }
     });
@@ -0,0 +1,228 @@
+// Tests for the regions and regionBed options, which query several genomic regions in one call
+
+import { mkdtemp, rm, writeFile } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import {
+  bamMods,
+  NanalogueBam,
+  type ReadInfoRecord,
+  type ReadOptions,
+  readInfo,
+  seqTable,
+  windowReads,
+} from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM } from './fixtures';
+
+/** Identifies one alignment of a read, so supplementary alignments stay distinct. */
+function alignmentKey(record: ReadInfoRecord): string {
+  return 'contig' in record
+    ? `${record.read_id}/${record.alignment_type}/${record.contig}/${record.reference_start}`
+    : `${record.read_id}/${record.alignment_type}`;
+}
+
+describe('regions', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-regions-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('returns the reads of each region in the order given', async () => {
+    const dummyIII = await readInfo({ bamPath: EXAMPLE_1_BAM, region: 'dummyIII' });
+    const dummyI = await readInfo({ bamPath: EXAMPLE_1_BAM, region: 'dummyI' });
+
+    const result = await readInfo({
+      bamPath: EXAMPLE_1_BAM,
+      regions: ['dummyIII', 'dummyI'],
+    });
+
+    expect(result).toEqual([...dummyIII, ...dummyI]);
+  });
+
+  it('returns a read overlapping several regions once', async () => {
+    const first = await readInfo({
+      bamPath: simpleBamPath,
+      region: 'contig_00000:0-5000',
+    });
+    const second = await readInfo({
+      bamPath: simpleBamPath,
+      region: 'contig_00000:4000-9000',
+    });
+    const expected = new Set([...first, ...second].map(alignmentKey));
+
+    const result = await readInfo({
+      bamPath: simpleBamPath,
+      regions: ['contig_00000:0-5000', 'contig_00000:4000-9000'],
+    });
+    const keys = result.map(alignmentKey);
+
+    expect(new Set(keys).size).toBe(keys.length);
+    expect(new Set(keys)).toEqual(expected);
+  });
+
+  it('applies fullRegion to each region', async () => {
+    const single = await readInfo({
+      bamPath: simpleBamPath,
+      region: 'contig_00001:100-200',
+      fullRegion: true,
+    });
+    const multiple = await readInfo({
+      bamPath: simpleBamPath,
+      regions: ['contig_00001:100-200'],
+      fullRegion: true,
+    });
+
+    expect(single.length).toBeGreaterThan(0);
+    expect(multiple).toEqual(single);
+  });
+
+  it('works with bamMods and windowReads', async () => {
+    const regions = ['contig_00000:0-500', 'contig_00001:0-500'];
+    const mods = await bamMods({ bamPath: simpleBamPath, regions, limit: 3 });
+    const windows = JSON.parse(
+      await windowReads({ bamPath: simpleBamPath, win: 5, step: 5, regions }),
+    );
+
+    expect(mods).toHaveLength(3);
+    expect(windows.length).toBeGreaterThan(0);
+  });
+
+  it('can be repeated on a NanalogueBam', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+    const first = await bam.readInfo({ regions: ['dummyI', 'dummyII'] });
+    const second = await bam.readInfo({ regions: ['dummyI', 'dummyII'] });
+
+    expect(first).toHaveLength(2);
+    expect(second).toEqual(first);
+  });
+
+  it('rejects region and regions together', async () => {
+    await expect(
+      readInfo({
+        bamPath: EXAMPLE_1_BAM,
+        region: 'dummyI',
+        regions: ['dummyII'],
+      } as unknown as ReadOptions),
+    ).rejects.toThrow(/set either region or regions, not both/);
+  });
+
+  it('rejects an empty list of regions', async () => {
+    await expect(readInfo({ bamPath: EXAMPLE_1_BAM, regions: [] })).rejects.toThrow(
+      /regions must contain at least one region/,
+    );
+  });
+
+  it('rejects a region on a contig absent from the header', async () => {
+    await expect(
+      readInfo({ bamPath: EXAMPLE_1_BAM, regions: ['dummyI', 'nope:1-2'] }),
+    ).rejects.toThrow(/Failed to convert region to bed3/);
+  });
+
+  it('cannot be combined with region in seqTable', async () => {
+    await expect(
+      seqTable({
+        bamPath: EXAMPLE_1_BAM,
+        region: 'dummyI',
+        regions: ['dummyI'],
+      } as unknown as ReadOptions),
+    ).rejects.toThrow(/seqTable takes either region or regions/);
+  });
+});
+
+describe('regionBed', () => {
+  let tmpDir: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-region-bed-'));
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  async function writeBed(name: string, contents: string): Promise<string> {
+    const path = join(tmpDir, name);
+    await writeFile(path, contents);
+    return path;
+  }
+
+  it('queries each interval like the regions option', async () => {
+    const regionBed = await writeBed(
+      'two.bed',
+      '# regions of interest\ntrack name=roi\ndummyIII\t0\t76\tname\t0\t+\n\ndummyI\t0\t22\n',
+    );
+
+    const fromBed = await readInfo({ bamPath: EXAMPLE_1_BAM, regionBed });
+    const fromList = await readInfo({
+      bamPath: EXAMPLE_1_BAM,
+      regions: ['dummyIII', 'dummyI'],
+    });
+
+    expect(fromBed).toHaveLength(2);
+    expect(fromBed).toEqual(fromList);
+  });
+
+  it('uses BED start and end as 0-based half-open coordinates', async () => {
+    const regionBed = await writeBed('edge.bed', 'dummyIII\t26\t32\n');
+
+    const fromBed = await readInfo({ bamPath: EXAMPLE_1_BAM, regionBed });
+    const fromRegion = await readInfo({
+      bamPath: EXAMPLE_1_BAM,
+      region: 'dummyIII:26-32',
+    });
+
+    expect(fromBed).toEqual(fromRegion);
+  });
+
+  it('reports the line of a malformed interval', async () => {
+    const regionBed = await writeBed('bad.bed', 'dummyI\t0\t22\ndummyI\t10\t5\n');
+
+    await expect(readInfo({ bamPath: EXAMPLE_1_BAM, regionBed })).rejects.toThrow(
+      /Invalid BED line 2 in .*: start must be less than end/,
+    );
+  });
+
+  it('rejects a line with fewer than three columns', async () => {
+    const regionBed = await writeBed('short.bed', 'dummyI\t5\n');
+
+    await expect(readInfo({ bamPath: EXAMPLE_1_BAM, regionBed })).rejects.toThrow(
+      /expected contig, start and end columns/,
+    );
+  });
+
+  it('rejects a contig absent from the BAM header', async () => {
+    const regionBed = await writeBed('missing.bed', 'chrUn\t1\t5\n');
+
+    await expect(readInfo({ bamPath: EXAMPLE_1_BAM, regionBed })).rejects.toThrow(
+      /contig 'chrUn' is not in the BAM header/,
+    );
+  });
+
+  it('rejects a BED file without intervals', async () => {
+    const regionBed = await writeBed('empty.bed', '# nothing here\n');
+
+    await expect(readInfo({ bamPath: EXAMPLE_1_BAM, regionBed })).rejects.toThrow(
+      /contains no regions/,
+    );
+  });
+
+  it('cannot be combined with regions', async () => {
+    const regionBed = await writeBed('one.bed', 'dummyI\t0\t22\n');
+
+    await expect(
+      readInfo({
+        bamPath: EXAMPLE_1_BAM,
+        regionBed,
+        regions: ['dummyI'],
+      } as unknown as ReadOptions),
+    ).rejects.toThrow(/set only one of region, regions and regionBed/);
+  });
+});
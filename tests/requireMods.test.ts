@@ -0,0 +1,100 @@
+// Tests for reads without modification data: the has_mod_data field that
+// reportModData adds to bamMods, the without_mod_data count of filterStats and
+// requireMods
+
+import { describe, expect, it } from 'vitest';
+import {
+  bamMods,
+  bamModsJson,
+  countReads,
+  filterStats,
+  NanalogueBam,
+} from '../index';
+import { EXAMPLE_1_BAM, EXAMPLE_3_BAM } from './fixtures';
+
+describe('has_mod_data', () => {
+  it('is false for reads without MM and ML tags', async () => {
+    const reads = await bamMods({
+      bamPath: EXAMPLE_3_BAM,
+      reportModData: true,
+    });
+
+    expect(reads).toHaveLength(10);
+    for (const read of reads) {
+      expect(read.has_mod_data).toBe(false);
+      expect(read.mod_table).toEqual([]);
+    }
+  });
+
+  it('stays true when the filters leave out every call', async () => {
+    const reads = await bamMods({
+      bamPath: EXAMPLE_1_BAM,
+      tag: 'm',
+      reportModData: true,
+    });
+
+    expect(reads).toHaveLength(4);
+    for (const read of reads) {
+      expect(read.has_mod_data).toBe(true);
+      expect(read.mod_table).toEqual([]);
+    }
+  });
+
+  it('is the last field of each bamModsJson read', async () => {
+    const options = { bamPath: EXAMPLE_1_BAM, limit: 1, reportModData: true };
+
+    expect(await bamModsJson(options)).toContain(
+      '"seq_len":8,"has_mod_data":true}',
+    );
+    expect(await bamModsJson({ ...options, pretty: true })).toContain(
+      '"seq_len": 8,\n  "has_mod_data": true\n}',
+    );
+  });
+
+  it('is left out unless reportModData is set', async () => {
+    for (const read of await bamMods({ bamPath: EXAMPLE_3_BAM })) {
+      expect(read).not.toHaveProperty('has_mod_data');
+    }
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+    for (const read of await bam.bamMods({ reportModData: true })) {
+      expect(read.has_mod_data).toBe(true);
+    }
+  });
+});
+
+describe('requireMods', () => {
+  it('leaves out reads without MM and ML tags', async () => {
+    expect(await countReads({ bamPath: EXAMPLE_3_BAM })).toBe(10);
+    expect(
+      await countReads({ bamPath: EXAMPLE_3_BAM, requireMods: true }),
+    ).toBe(0);
+  });
+
+  it('keeps reads with tags but no calls left', async () => {
+    const options = { bamPath: EXAMPLE_1_BAM, tag: 'm', requireMods: true };
+
+    expect(await countReads(options)).toBe(4);
+  });
+
+  it('is counted by filterStats', async () => {
+    const stats = await filterStats({ bamPath: EXAMPLE_3_BAM });
+    const required = await filterStats({
+      bamPath: EXAMPLE_3_BAM,
+      requireMods: true,
+    });
+
+    expect(stats.without_mod_data).toBe(10);
+    expect(required.after_read_group).toBe(10);
+    expect(required.after_min_mods).toBe(0);
+    expect(required.without_mod_data).toBe(0);
+    expect(
+      (await filterStats({ bamPath: EXAMPLE_1_BAM })).without_mod_data,
+    ).toBe(0);
+  });
+
+  it('applies to NanalogueBam methods', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_3_BAM });
+
+    expect(await bam.bamMods({ requireMods: true })).toEqual([]);
+  });
+});
@@ -2,7 +2,7 @@
 
 import { resolve } from 'node:path';
 import { describe, expect, it } from 'vitest';
-import { seqTable } from '../index';
+import { NanalogueBam, readsTable, seqTable, seqTableRecords } from '../index';
 
 const getTestDataPath = (relativePath: string) =>
   resolve(__dirname, 'data', relativePath);
@@ -100,3 +100,316 @@ describe('seqTable', () => {
     expect(filteredDataLines.length).toBeGreaterThanOrEqual(2);
   });
 });
+
+describe('seqTable columns', () => {
+  const testBamPath = getTestDataPath('examples/example_1.bam');
+  const region = 'dummyIII:30-35';
+
+  it('defaults to read_id, sequence and qualities', async () => {
+    const result = await seqTable({ bamPath: testBamPath, region });
+
+    expect(result.split('\n')[0]).toBe('read_id\tsequence\tqualities');
+  });
+
+  it('returns the requested columns in order', async () => {
+    const result = await seqTable({
+      bamPath: testBamPath,
+      region,
+      columns: [
+        'mod_count',
+        'read_id',
+        'align_length',
+        'sequence_length_template',
+        'alignment_type',
+      ],
+    });
+
+    expect(result.trimEnd().split('\n')).toEqual([
+      'mod_count\tread_id\talign_length\tsequence_length_template\talignment_type',
+      'T:1\ta4f36092-b4d5-47a9-813e-c22c3b477a0c\t48\t48\tprimary_forward',
+    ]);
+  });
+
+  it('keeps the default columns when they are requested explicitly', async () => {
+    const options = { bamPath: testBamPath, region };
+
+    expect(
+      await seqTable({ ...options, columns: ['read_id', 'sequence', 'qualities'] }),
+    ).toBe(await seqTable(options));
+  });
+
+  it('rejects an unknown column', async () => {
+    await expect(
+      // @ts-expect-error - testing an invalid column name
+      seqTable({ bamPath: testBamPath, region, columns: ['read_id', 'cigar'] }),
+    ).rejects.toThrow(/Unknown seqTable column 'cigar'/);
+  });
+
+  it('rejects an empty or repeated list', async () => {
+    await expect(seqTable({ bamPath: testBamPath, region, columns: [] })).rejects.toThrow(
+      /at least one seqTable column/,
+    );
+    await expect(
+      seqTable({ bamPath: testBamPath, region, columns: ['read_id', 'read_id'] }),
+    ).rejects.toThrow(/listed more than once/);
+  });
+});
+
+describe('seqTable reverseComplementMinus', () => {
+  const testBamPath = getTestDataPath('examples/example_1.bam');
+
+  it('shows minus-strand reads in sequencing orientation', async () => {
+    // fffffff1 is the primary_reverse read on dummyII
+    const region = 'dummyII:15-24';
+    const genomic = await seqTableRecords({ bamPath: testBamPath, region });
+    const sequenced = await seqTableRecords({
+      bamPath: testBamPath,
+      region,
+      reverseComplementMinus: true,
+    });
+
+    expect(genomic).toEqual([
+      {
+        read_id: 'fffffff1-10d2-49cb-8ca3-e8d48979001b',
+        sequence: 'AACGACCAZ',
+        qualities: '255.255.255.255.255.255.255.255.255',
+      },
+    ]);
+    expect(sequenced).toEqual([{ ...genomic[0], sequence: 'ZTGGTCGTT' }]);
+  });
+
+  it('leaves plus-strand reads as they are', async () => {
+    const options = { bamPath: testBamPath, region: 'dummyI:9-17' };
+
+    expect(await seqTable({ ...options, reverseComplementMinus: true })).toBe(
+      await seqTable(options),
+    );
+  });
+});
+
+describe('seqTable display options', () => {
+  const pynanaloguePath = getTestDataPath('examples/example_pynanalogue_1.bam');
+  // Both reads have an insertion of four bases here, modified in read 0
+  const options = { bamPath: pynanaloguePath, region: 'contig_00000:95-105' };
+  const sequences = async (extra: object) =>
+    (await seqTableRecords({ ...options, ...extra }))
+      .map((r) => r.sequence)
+      .sort();
+
+  it('marks insertions and modifications by default', async () => {
+    expect(await sequences({})).toEqual(['TACGTctctACGTA', 'TAZGTztztAZGTA']);
+  });
+
+  it('shows insertions in upper case with showInsertions false', async () => {
+    expect(await sequences({ showInsertions: false })).toEqual([
+      'TACGTCTCTACGTA',
+      'TAZGTZTZTAZGTA',
+    ]);
+  });
+
+  it('shows modified bases unmarked with showModMarkers false', async () => {
+    expect(await sequences({ showModMarkers: false })).toEqual([
+      'TACGTctctACGTA',
+      'TACGTctctACGTA',
+    ]);
+  });
+
+  it('leaves out the qualities with showBaseQual false', async () => {
+    const records = await seqTableRecords({ ...options, showBaseQual: false });
+    const tsv = await seqTable({ ...options, showBaseQual: false });
+    const reads = await readsTable({ ...options, showBaseQual: false });
+
+    expect(records.every((r) => r.qualities === undefined)).toBe(true);
+    expect(tsv.split('\n')[0]).toBe('read_id\tsequence');
+    expect(reads.split('\n')[0]).not.toContain('qualities');
+  });
+
+  it('rejects the qualities column with showBaseQual false', async () => {
+    await expect(
+      seqTable({ ...options, showBaseQual: false, columns: ['qualities'] }),
+    ).rejects.toThrow(/showBaseQual false/);
+  });
+});
+
+describe('seqTable regions', () => {
+  const testBamPath = getTestDataPath('examples/example_1.bam');
+  const regions = ['dummyIII:30-35', 'dummyII:15-24', 'dummyIII:30-32'];
+
+  it('labels the rows of each region in a first column', async () => {
+    const result = await seqTable({ bamPath: testBamPath, regions });
+
+    expect(result.trimEnd().split('\n')).toEqual([
+      'region\tread_id\tsequence\tqualities',
+      'dummyIII:30-35\ta4f36092-b4d5-47a9-813e-c22c3b477a0c\tAZCCA\t' +
+        '255.255.255.255.255',
+      'dummyII:15-24\tfffffff1-10d2-49cb-8ca3-e8d48979001b\tAACGACCAZ\t' +
+        '255.255.255.255.255.255.255.255.255',
+      'dummyIII:30-32\ta4f36092-b4d5-47a9-813e-c22c3b477a0c\tAZ\t255.255',
+    ]);
+  });
+
+  it('matches seqTable over each region', async () => {
+    const records = await seqTableRecords({ bamPath: testBamPath, regions });
+
+    for (const region of regions) {
+      const single = await seqTableRecords({ bamPath: testBamPath, region });
+      expect(records.filter((r) => r.region === region)).toEqual(
+        single.map((r) => ({ ...r, region })),
+      );
+    }
+  });
+
+  it('pages over the records of all regions', async () => {
+    const records = await seqTableRecords({
+      bamPath: testBamPath,
+      regions,
+      offset: 1,
+      limit: 1,
+    });
+
+    expect(records.map((r) => r.region)).toEqual(['dummyII:15-24']);
+  });
+
+  it('rejects modRegion', async () => {
+    await expect(
+      seqTable({ bamPath: testBamPath, regions, modRegion: 'dummyII:15-24' }),
+    ).rejects.toThrow(/requires modRegion to be omitted/);
+  });
+});
+
+describe('seqTable maxReads', () => {
+  const pynanaloguePath = getTestDataPath('examples/example_pynanalogue_1.bam');
+  const options = { bamPath: pynanaloguePath, region: 'contig_00000:0-10' };
+
+  it('returns the rows of the first reads only', async () => {
+    const all = await seqTableRecords(options);
+    const first = await seqTableRecords({ ...options, maxReads: 1 });
+
+    expect(all).toHaveLength(2);
+    expect(first).toEqual(all.slice(0, 1));
+  });
+
+  it('counts the rows of all regions together', async () => {
+    const records = await seqTableRecords({
+      bamPath: getTestDataPath('examples/example_1.bam'),
+      regions: ['dummyIII:30-35', 'dummyII:15-24', 'dummyIII:30-32'],
+      maxReads: 2,
+    });
+
+    expect(records.map((r) => r.region)).toEqual([
+      'dummyIII:30-35',
+      'dummyII:15-24',
+    ]);
+  });
+
+  it('rejects zero', async () => {
+    await expect(seqTable({ ...options, maxReads: 0 })).rejects.toThrow(
+      /maxReads must be at least 1/,
+    );
+  });
+});
+
+describe('seqTableRecords', () => {
+  const pynanaloguePath = getTestDataPath('examples/example_pynanalogue_1.bam');
+  const region = 'contig_00000:0-10';
+
+  it('returns one object per read', async () => {
+    const records = await seqTableRecords({ bamPath: pynanaloguePath, region });
+    const sorted = [...records].sort((a, b) => a.read_id.localeCompare(b.read_id));
+
+    expect(sorted).toEqual([
+      {
+        read_id: '0.dc09ae0d-6b6e-4cb2-b092-078f251a778e',
+        sequence: 'AZGTAZGTAZ',
+        qualities: '20.20.20.20.20.20.20.20.20.20',
+      },
+      {
+        read_id: '1.cb098e1d-26d6-4e14-b979-b089e492c068',
+        sequence: 'ACGTACGTAC',
+        qualities: '30.30.30.30.30.30.30.30.30.30',
+      },
+    ]);
+  });
+
+  it('matches the rows of seqTable', async () => {
+    const options = { bamPath: pynanaloguePath, region: 'contig_00000' };
+    const records = await seqTableRecords(options);
+    const tsv = await seqTable(options);
+
+    const [header, ...rows] = tsv.trimEnd().split('\n');
+    expect(header).toBe('read_id\tsequence\tqualities');
+    expect(rows).toEqual(
+      records.map((r) => [r.read_id, r.sequence, r.qualities].join('\t')),
+    );
+  });
+
+  it('works on a NanalogueBam', async () => {
+    const bam = new NanalogueBam({ bamPath: pynanaloguePath });
+
+    expect(await bam.seqTableRecords({ region })).toEqual(
+      await seqTableRecords({ bamPath: pynanaloguePath, region }),
+    );
+  });
+
+  it('requires a region', async () => {
+    await expect(seqTableRecords({ bamPath: pynanaloguePath })).rejects.toThrow(
+      /region parameter is required/,
+    );
+  });
+
+  it('does not accept columns', async () => {
+    await expect(
+      seqTableRecords({ bamPath: pynanaloguePath, region, columns: ['read_id'] }),
+    ).rejects.toThrow(/columns is only supported by seqTable/);
+  });
+});
+
+describe('readsTable', () => {
+  const testBamPath = getTestDataPath('examples/example_1.bam');
+  const allColumns = [
+    'read_id',
+    'align_length',
+    'sequence_length_template',
+    'alignment_type',
+    'mod_count',
+    'sequence',
+    'qualities',
+  ];
+
+  it('returns every column of the core reads table', async () => {
+    const result = await readsTable({
+      bamPath: testBamPath,
+      region: 'dummyIII:30-35',
+    });
+
+    expect(result.trimEnd().split('\n')).toEqual([
+      allColumns.join('\t'),
+      'a4f36092-b4d5-47a9-813e-c22c3b477a0c\t48\t48\tprimary_forward\tT:1\tAZCCA\t255.255.255.255.255',
+    ]);
+  });
+
+  it('matches seqTable with all columns listed', async () => {
+    const options = { bamPath: testBamPath, region: 'dummyI' };
+
+    expect(await readsTable(options)).toBe(
+      await seqTable({ ...options, columns: allColumns }),
+    );
+  });
+
+  it('works on a NanalogueBam', async () => {
+    const bam = new NanalogueBam({ bamPath: testBamPath });
+
+    expect(await bam.readsTable({ region: 'dummyI' })).toBe(
+      await readsTable({ bamPath: testBamPath, region: 'dummyI' }),
+    );
+  });
+
+  it('requires a region and does not accept columns', async () => {
+    await expect(readsTable({ bamPath: testBamPath })).rejects.toThrow(
+      /region parameter is required/,
+    );
+    await expect(
+      readsTable({ bamPath: testBamPath, region: 'dummyI', columns: ['read_id'] }),
+    ).rejects.toThrow(/columns is only supported by seqTable/);
+  });
+});
@@ -6,7 +6,16 @@ import { access, readFile, rm } from 'node:fs/promises';
 import { tmpdir } from 'node:os';
 import { join, resolve } from 'node:path';
 import { afterEach, beforeEach, describe, expect, it } from 'vitest';
-import { peek, readInfo, simulateModBam } from '../index';
+import {
+  bamMods,
+  ErrorKind,
+  peek,
+  readInfo,
+  type SimulationConfig,
+  simulateModBam,
+  simulateModBamTyped,
+  validateSimulationConfig,
+} from '../index';
 
 const getTestDataPath = (relativePath: string) =>
   resolve(__dirname, 'data', relativePath);
@@ -93,6 +102,139 @@ describe('simulateModBam', () => {
     expect(reads.length).toBe(config.reads[0].number);
   });
 
+  it('returns counts that match the generated BAM', async () => {
+    const configPath = getTestDataPath('simulation_configs/two_mods_bam.json');
+    const jsonConfig = await readFile(configPath, 'utf-8');
+
+    const stats = await simulateModBam({ jsonConfig, bamPath, fastaPath });
+
+    const reads = await readInfo({ bamPath });
+    const calls = await bamMods({ bamPath });
+    const { contigs } = await peek({ bamPath });
+    expect(stats).toEqual({
+      n_reads: reads.length,
+      n_mods: calls.reduce(
+        (sum, r) => sum + r.mod_table.reduce((n, t) => n + t.data.length, 0),
+        0,
+      ),
+      n_contigs: Object.keys(contigs).length,
+      total_bases: reads.reduce((sum, r) => sum + r.sequence_length, 0),
+      read_ids: reads.map((r) => r.read_id),
+      contigs: expect.arrayContaining(Object.keys(contigs)),
+    });
+  });
+
+  it('returns read ids that can be queried directly', async () => {
+    const configPath = getTestDataPath('simulation_configs/simple_bam.json');
+    const jsonConfig = await readFile(configPath, 'utf-8');
+
+    const { read_ids, contigs } = await simulateModBam({
+      jsonConfig,
+      bamPath,
+      fastaPath,
+    });
+
+    expect(new Set(read_ids).size).toBe(read_ids.length);
+    const reads = await readInfo({ bamPath, readIdSet: [read_ids[0]] });
+    expect(reads.map((r) => r.read_id)).toEqual([read_ids[0]]);
+    const fasta = await readFile(fastaPath, 'utf-8');
+    expect(
+      fasta
+        .split('\n')
+        .filter((line) => line.startsWith('>'))
+        .map((line) => line.slice(1)),
+    ).toEqual(contigs);
+  });
+
+  it('writes identical files with the same seed', async () => {
+    const configPath = getTestDataPath('simulation_configs/two_mods_bam.json');
+    const jsonConfig = await readFile(configPath, 'utf-8');
+    const otherBamPath = `${bamPath}.again.bam`;
+    const otherFastaPath = `${fastaPath}.again.fasta`;
+
+    try {
+      await simulateModBam({ jsonConfig, bamPath, fastaPath, seed: 7 });
+      await simulateModBam({
+        jsonConfig,
+        bamPath: otherBamPath,
+        fastaPath: otherFastaPath,
+        seed: 7,
+      });
+
+      expect(await readFile(otherBamPath)).toEqual(await readFile(bamPath));
+      expect(await readFile(otherFastaPath)).toEqual(
+        await readFile(fastaPath),
+      );
+
+      // The option overrides the seed of the configuration
+      await simulateModBam({
+        jsonConfig: JSON.stringify({ ...JSON.parse(jsonConfig), seed: 8 }),
+        bamPath: otherBamPath,
+        fastaPath: otherFastaPath,
+        seed: 7,
+        overwrite: true,
+      });
+      expect(await readFile(otherBamPath)).toEqual(await readFile(bamPath));
+    } finally {
+      await rm(otherBamPath, { force: true });
+      await rm(`${otherBamPath}.bai`, { force: true });
+      await rm(otherFastaPath, { force: true });
+    }
+  });
+
+  it('rejects a negative seed', async () => {
+    const configPath = getTestDataPath('simulation_configs/simple_bam.json');
+    const jsonConfig = await readFile(configPath, 'utf-8');
+
+    await expect(
+      simulateModBam({ jsonConfig, bamPath, fastaPath, seed: -1 }),
+    ).rejects.toMatchObject({
+      code: ErrorKind.InvalidOptions,
+      message: 'seed must be non-negative',
+    });
+  });
+
+  it('refuses to replace existing files without overwrite', async () => {
+    const configPath = getTestDataPath('simulation_configs/simple_bam.json');
+    const jsonConfig = await readFile(configPath, 'utf-8');
+
+    await simulateModBam({ jsonConfig, bamPath, fastaPath, seed: 1 });
+    const bam = await readFile(bamPath);
+
+    await expect(
+      simulateModBam({ jsonConfig, bamPath, fastaPath, seed: 2 }),
+    ).rejects.toMatchObject({
+      code: ErrorKind.IoError,
+      message: `Output file '${bamPath}' already exists; set overwrite to replace it`,
+    });
+    expect(await readFile(bamPath)).toEqual(bam);
+
+    await simulateModBam({
+      jsonConfig,
+      bamPath,
+      fastaPath,
+      seed: 2,
+      overwrite: true,
+    });
+    expect(await readFile(bamPath)).not.toEqual(bam);
+  });
+
+  it('rejects a missing output directory before generating', async () => {
+    const configPath = getTestDataPath('simulation_configs/simple_bam.json');
+    const jsonConfig = await readFile(configPath, 'utf-8');
+    const missingDir = join(tempDir, `missing_${randomUUID().slice(0, 8)}`);
+
+    await expect(
+      simulateModBam({
+        jsonConfig,
+        bamPath: join(missingDir, 'out.bam'),
+        fastaPath,
+      }),
+    ).rejects.toMatchObject({ code: ErrorKind.FileNotFound });
+    // Nothing was generated, so the FASTA file was not written either
+    await expect(access(fastaPath)).rejects.toThrow();
+  });
+
   it('rejects invalid JSON config', async () => {
     const invalidConfig = '{ invalid json }';
 
@@ -112,3 +254,217 @@ describe('simulateModBam', () => {
     expect(result.modifications.length).toBeGreaterThan(0);
   });
 });
+
+describe('simulateModBamTyped', () => {
+  let tempDir: string;
+  let bamPath: string;
+  let fastaPath: string;
+
+  beforeEach(() => {
+    tempDir = tmpdir();
+    const uniqueId = randomUUID().slice(0, 8);
+    bamPath = join(tempDir, `test_${uniqueId}.bam`);
+    fastaPath = join(tempDir, `test_${uniqueId}.fasta`);
+  });
+
+  afterEach(async () => {
+    for (const path of [bamPath, fastaPath]) {
+      await rm(path, { force: true });
+      await rm(`${path}.bai`, { force: true });
+    }
+  });
+
+  const config: SimulationConfig = {
+    contigs: { number: 2, lenRange: [1000, 1000] },
+    reads: [
+      {
+        number: 50,
+        mapqRange: [10, 20],
+        baseQualRange: [10, 20],
+        lenRange: [0.5, 0.5],
+        insertMiddle: 'ATCG',
+        mods: [
+          {
+            base: 'T',
+            isStrandPlus: true,
+            modCode: 'T',
+            win: [40, 40],
+            modRange: [
+              [0.1, 0.2],
+              [0.7, 0.8],
+            ],
+          },
+        ],
+      },
+    ],
+    seed: 42,
+  };
+
+  it('simulates the same data as the JSON configuration', async () => {
+    const jsonConfig = JSON.stringify({
+      contigs: { number: 2, len_range: [1000, 1000] },
+      reads: [
+        {
+          number: 50,
+          mapq_range: [10, 20],
+          base_qual_range: [10, 20],
+          len_range: [0.5, 0.5],
+          insert_middle: 'ATCG',
+          mods: [
+            {
+              base: 'T',
+              is_strand_plus: true,
+              mod_code: 'T',
+              win: [40, 40],
+              mod_range: [
+                [0.1, 0.2],
+                [0.7, 0.8],
+              ],
+            },
+          ],
+        },
+      ],
+      seed: 42,
+    });
+    const jsonBamPath = `${bamPath}.json.bam`;
+    const jsonFastaPath = `${fastaPath}.json.fasta`;
+
+    try {
+      const stats = await simulateModBamTyped(config, bamPath, fastaPath);
+      const jsonStats = await simulateModBam({
+        jsonConfig,
+        bamPath: jsonBamPath,
+        fastaPath: jsonFastaPath,
+      });
+
+      expect(stats).toEqual(jsonStats);
+      expect(stats.n_reads).toBe(50);
+      expect(await bamMods({ bamPath })).toEqual(
+        await bamMods({ bamPath: jsonBamPath }),
+      );
+    } finally {
+      await rm(jsonBamPath, { force: true });
+      await rm(`${jsonBamPath}.bai`, { force: true });
+      await rm(jsonFastaPath, { force: true });
+    }
+  });
+
+  it('leaves optional settings at their defaults', async () => {
+    const stats = await simulateModBamTyped(
+      {
+        contigs: { number: 1, lenRange: [500, 500] },
+        reads: [{ number: 5, lenRange: [0.2, 0.4] }],
+      },
+      bamPath,
+      fastaPath,
+    );
+
+    expect(stats).toMatchObject({ n_reads: 5, n_mods: 0, n_contigs: 1 });
+  });
+
+  it.each<[string, SimulationConfig]>([
+    [
+      'contigs.number',
+      { ...config, contigs: { number: 0, lenRange: [1, 2] } },
+    ],
+    [
+      'contigs.lenRange',
+      { ...config, contigs: { number: 1, lenRange: [2, 1] } },
+    ],
+    [
+      'reads[0].lenRange',
+      { ...config, reads: [{ number: 1, lenRange: [0.5, 1.5] }] },
+    ],
+    [
+      'reads[0].barcode',
+      {
+        ...config,
+        reads: [{ number: 1, lenRange: [0.5, 0.6], barcode: 'XYZ' }],
+      },
+    ],
+    [
+      'reads[0].mods[0].base',
+      {
+        ...config,
+        reads: [
+          {
+            number: 1,
+            lenRange: [0.5, 0.6],
+            mods: [
+              {
+                base: 'X',
+                isStrandPlus: true,
+                modCode: 'm',
+                win: [1],
+                modRange: [[0.1, 0.2]],
+              },
+            ],
+          },
+        ],
+      },
+    ],
+    ['seed', { ...config, seed: -1 }],
+  ])('names the invalid field %s', async (field, invalid) => {
+    await expect(
+      simulateModBamTyped(invalid, bamPath, fastaPath),
+    ).rejects.toMatchObject({
+      code: ErrorKind.InvalidOptions,
+      message: expect.stringContaining(`Invalid simulation config: ${field}:`),
+    });
+  });
+});
+
+describe('validateSimulationConfig', () => {
+  it.each(['simple_bam.json', 'two_mods_bam.json'])('accepts %s', async (name) => {
+    const jsonConfig = await readFile(
+      getTestDataPath(`simulation_configs/${name}`),
+      'utf-8',
+    );
+
+    await expect(validateSimulationConfig(jsonConfig)).resolves.toBeUndefined();
+  });
+
+  it.each([
+    [{ contigs: { number: '2' } }, /contigs\.number: invalid type: string "2"/],
+    [{ contigs: { number: 0 } }, /contigs\.number: invalid value: integer `0`/],
+    [
+      { reads: [{ number: 10, len_range: [0.5, 1.5] }] },
+      /reads\[0\]\.len_range\[1\]/,
+    ],
+    [
+      {
+        reads: [
+          {
+            number: 10,
+            mods: [
+              {
+                base: 'X',
+                is_strand_plus: true,
+                mod_code: 'm',
+                win: [1],
+                mod_range: [[0.1, 0.2]],
+              },
+            ],
+          },
+        ],
+      },
+      /reads\[0\]\.mods\[0\]\.base: unknown variant `X`/,
+    ],
+  ])('names the invalid field in %j', async (config, message) => {
+    await expect(validateSimulationConfig(JSON.stringify(config))).rejects.toThrow(
+      message,
+    );
+  });
+
+  it('rejects text that is not JSON', async () => {
+    await expect(validateSimulationConfig('{ invalid json }')).rejects.toThrow(
+      /Invalid JSON config/,
+    );
+  });
+
+  it('rejects trailing text after the config', async () => {
+    await expect(validateSimulationConfig('{} extra')).rejects.toThrow(
+      /trailing characters/,
+    );
+  });
+});
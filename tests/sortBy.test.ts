@@ -0,0 +1,112 @@
+// Tests for the sortBy option, which lists the reads of readInfo and bamMods in a chosen order
+
+import { mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import {
+  bamMods,
+  bamModsTyped,
+  NanalogueBam,
+  readInfo,
+  readInfoJson,
+  readInfoStream,
+  SortBy,
+} from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM } from './fixtures';
+
+describe('sortBy', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-sort-by-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('lists reads by contig and position, unmapped reads last', async () => {
+    const reads = await readInfo({
+      bamPath: EXAMPLE_1_BAM,
+      sortBy: SortBy.Position,
+    });
+
+    expect(
+      reads.map((r) =>
+        'contig' in r ? `${r.contig}:${r.reference_start}` : r.alignment_type,
+      ),
+    ).toEqual(['dummyI:9', 'dummyII:3', 'dummyIII:23', 'unmapped']);
+  });
+
+  it('orders the reads of each contig by reference start', async () => {
+    const reads = await readInfo({
+      bamPath: simpleBamPath,
+      sortBy: SortBy.Position,
+    });
+    const mapped = reads.flatMap((r) =>
+      'contig' in r ? [[r.contig, r.reference_start] as const] : [],
+    );
+
+    const unsorted = await readInfo({ bamPath: simpleBamPath });
+    expect(reads).toHaveLength(unsorted.length);
+    for (const [i, [contig, start]] of mapped.entries()) {
+      const next = mapped[i + 1];
+      if (next?.[0] === contig) {
+        expect(next[1]).toBeGreaterThanOrEqual(start);
+      }
+    }
+  });
+
+  it('lists reads by length or read id', async () => {
+    const bamPath = EXAMPLE_1_BAM;
+    const byLength = await readInfo({ bamPath, sortBy: 'length' });
+    const byId = await readInfo({ bamPath, sortBy: 'read_id' });
+    const ids = byId.map((r) => r.read_id);
+
+    expect(byLength.map((r) => r.sequence_length)).toEqual([8, 33, 48, 48]);
+    expect(ids).toEqual([...ids].sort());
+  });
+
+  it('pages through the sorted reads', async () => {
+    const options = { bamPath: simpleBamPath, sortBy: SortBy.ReadId };
+    const all = await bamMods(options);
+    const page = await bamMods({ ...options, offset: 10, limit: 5 });
+
+    expect(page.map((r) => r.read_id)).toEqual(
+      all.slice(10, 15).map((r) => r.read_id),
+    );
+  });
+
+  it('sorts the JSON and typed forms alike', async () => {
+    const options = { bamPath: EXAMPLE_1_BAM, sortBy: SortBy.Length };
+    const ids = (await readInfo(options)).map((r) => r.read_id);
+    const json: { read_id: string }[] = JSON.parse(await readInfoJson(options));
+    const calls = await bamModsTyped(options);
+
+    expect(json.map((r) => r.read_id)).toEqual(ids);
+    expect((await bamMods(options)).map((r) => r.read_id)).toEqual(ids);
+    expect(calls[0]?.read_id).toBe(ids[0]);
+  });
+
+  it('sorts the reads of a NanalogueBam', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+
+    expect(await bam.readInfo({ sortBy: SortBy.ReadId })).toEqual(
+      await readInfo({ bamPath: EXAMPLE_1_BAM, sortBy: SortBy.ReadId }),
+    );
+  });
+
+  it('is rejected by readInfoStream', async () => {
+    const stream = readInfoStream({
+      bamPath: EXAMPLE_1_BAM,
+      sortBy: SortBy.Length,
+    });
+
+    await expect(stream.next()).rejects.toMatchObject({
+      code: 'InvalidOptions',
+    });
+  });
+});
@@ -0,0 +1,68 @@
+// Tests for reading BAM data from stdin with bamPath '-'
+
+import { spawnSync } from 'node:child_process';
+import { readFileSync } from 'node:fs';
+import { resolve } from 'node:path';
+import { describe, expect, it } from 'vitest';
+import { NanalogueBam, readInfo, seqTable, windowReads } from '../index';
+import { EXAMPLE_1_BAM } from './fixtures';
+
+const INDEX_PATH = resolve(__dirname, '..', 'index.js');
+
+/**
+ * Runs `call` (an expression using the module as `m`) in a child process with
+ * `input` piped into its stdin, and returns the JSON-serialised result.
+ */
+function runWithStdin(call: string, input: Buffer): unknown {
+  const script = `
+    const m = require(${JSON.stringify(INDEX_PATH)});
+    (${call}).then(
+      (r) => process.stdout.write(JSON.stringify(r)),
+      (e) => { process.stderr.write(e.message); process.exit(1); },
+    );
+  `;
+  const child = spawnSync(process.execPath, ['-e', script], {
+    input,
+    encoding: 'utf8',
+  });
+  if (child.status !== 0) {
+    throw new Error(child.stderr);
+  }
+  return JSON.parse(child.stdout);
+}
+
+describe('stdin input', () => {
+  const exampleBam = readFileSync(EXAMPLE_1_BAM);
+
+  it('readInfo reads the same records as from the file', async () => {
+    const fromFile = await readInfo({ bamPath: EXAMPLE_1_BAM });
+    const fromStdin = runWithStdin("m.readInfo({ bamPath: '-' })", exampleBam);
+
+    expect(fromStdin).toEqual(fromFile);
+  });
+
+  it('bamMods applies filters and pagination to stdin data', async () => {
+    const fromStdin = runWithStdin(
+      "m.bamMods({ bamPath: '-', readFilter: 'primary_forward', limit: 1 })",
+      exampleBam,
+    );
+
+    expect(fromStdin).toHaveLength(1);
+  });
+
+  it('rejects a region without reading stdin', async () => {
+    await expect(readInfo({ bamPath: '-', region: 'dummyI' })).rejects.toThrow(
+      /cannot be used with BAM data from stdin/,
+    );
+    await expect(
+      windowReads({ bamPath: '-', win: 2, step: 1, regions: ['dummyI'] }),
+    ).rejects.toThrow(/cannot be used with BAM data from stdin/);
+    await expect(seqTable({ bamPath: '-', region: 'dummyI' })).rejects.toThrow(
+      /cannot be used with BAM data from stdin/,
+    );
+  });
+
+  it('is not supported by NanalogueBam', () => {
+    expect(() => new NanalogueBam({ bamPath: '-' })).toThrow(/Stdin not supported/);
+  });
+});
@@ -0,0 +1,68 @@
+// Tests for suggestThreshold, which picks a threshold on ML values from the
+// distribution of the modification calls
+
+import { mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import { modQualHistogram, NanalogueBam, suggestThreshold } from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM, EXAMPLE_3_BAM } from './fixtures';
+
+describe('suggestThreshold', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-suggest-threshold-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('splits the calls in the middle of the gap between them', async () => {
+    // The calls have probabilities of 0.1 to 0.2 or 0.7 to 0.8, which are the
+    // ML values 26 to 51 and 179 to 204
+    const counts = await modQualHistogram({ bamPath: simpleBamPath });
+    const suggestion = await suggestThreshold({ bamPath: simpleBamPath });
+
+    expect(suggestion).toEqual({ threshold: 115, method: 'otsu' });
+    expect(counts.slice(52, 179).every((v) => v === 0)).toBe(true);
+  });
+
+  it('separates the calls of a small file', async () => {
+    expect(await suggestThreshold({ bamPath: EXAMPLE_1_BAM })).toEqual({
+      threshold: 130,
+      method: 'otsu',
+    });
+  });
+
+  it('counts the calls of the reads the options select', async () => {
+    expect(
+      await suggestThreshold({ bamPath: simpleBamPath, limit: 5 }),
+    ).toEqual({ threshold: 115, method: 'otsu' });
+    // Only the calls of 179 to 204 are left to split
+    const { threshold } = await suggestThreshold({
+      bamPath: simpleBamPath,
+      minModQual: 100,
+    });
+    expect(threshold).toBeGreaterThan(179);
+    expect(threshold).toBeLessThanOrEqual(204);
+  });
+
+  it('falls back to 128 without calls', async () => {
+    expect(await suggestThreshold({ bamPath: EXAMPLE_3_BAM })).toEqual({
+      threshold: 128,
+      method: 'default',
+    });
+  });
+
+  it('is a NanalogueBam method', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+
+    expect(await bam.suggestThreshold()).toEqual(
+      await suggestThreshold({ bamPath: EXAMPLE_1_BAM }),
+    );
+  });
+});
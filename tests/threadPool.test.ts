@@ -0,0 +1,112 @@
+// Tests for initThreadPool, which starts htslib threads shared by all readers
+
+import { spawnSync } from 'node:child_process';
+import { existsSync } from 'node:fs';
+import { mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join, resolve } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import { ErrorKind, initThreadPool } from '../index';
+import { createSimpleBam, EXAMPLE_1_BAM } from './fixtures';
+
+const INDEX_PATH = resolve(__dirname, '..', 'index.js');
+
+/**
+ * Runs `body` (statements of an async function using the module as `m`) in a
+ * child process, as the pool lasts for the life of the process, and returns
+ * the JSON-serialised value it returns.
+ */
+function runInChild(body: string): unknown {
+  const script = `
+    const m = require(${JSON.stringify(INDEX_PATH)});
+    const { readdirSync } = require('node:fs');
+    const tasks = () => readdirSync('/proc/self/task').length;
+    (async () => { ${body} })().then(
+      (r) => process.stdout.write(JSON.stringify(r)),
+      (e) => { process.stderr.write(e.message); process.exit(1); },
+    );
+  `;
+  const child = spawnSync(process.execPath, ['-e', script], {
+    encoding: 'utf8',
+  });
+  if (child.status !== 0) {
+    throw new Error(child.stderr);
+  }
+  return JSON.parse(child.stdout);
+}
+
+describe('initThreadPool', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-thread-pool-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('rejects a size of 0', () => {
+    expect(() => initThreadPool(0)).toThrow(
+      expect.objectContaining({
+        code: ErrorKind.InvalidOptions,
+        message: expect.stringMatching(/threads must be a positive integer/),
+      }),
+    );
+  });
+
+  it('gives queries the same results as without it', () => {
+    const query = JSON.stringify({
+      bamPath: simpleBamPath,
+      region: 'contig_00000:0-2000',
+    });
+    const simple = JSON.stringify({ bamPath: simpleBamPath });
+    const example = JSON.stringify({ bamPath: EXAMPLE_1_BAM });
+    const [before, after] = runInChild(`
+      const run = async () => {
+        const bam = new m.NanalogueBam(${simple});
+        return [
+          await m.readInfo(${query}),
+          await m.readInfo({ ...${query}, threads: 2 }),
+          await bam.readInfo({ region: 'contig_00000:0-2000' }),
+          await m.windowReads({ ...${example}, win: 2, step: 1 }),
+          await m.peek(${example}),
+        ];
+      };
+      const before = await run();
+      m.initThreadPool(2);
+      return [before, await run()];
+    `) as unknown[][];
+
+    expect(before?.[0]).not.toEqual([]);
+    expect(after).toEqual(before);
+  });
+
+  it('keeps the results after the pool is replaced', () => {
+    const query = JSON.stringify({ bamPath: EXAMPLE_1_BAM });
+    const [first, second] = runInChild(`
+      m.initThreadPool(4);
+      const bam = new m.NanalogueBam(${query});
+      const first = await bam.readInfo();
+      m.initThreadPool(1);
+      return [first, [await bam.readInfo(), await m.readInfo(${query})]];
+    `) as [unknown, unknown[]];
+
+    expect(second).toEqual([first, first]);
+  });
+
+  it.runIf(existsSync('/proc/self/task'))(
+    'starts its threads when called rather than in each query',
+    () => {
+      const started = runInChild(`
+        const before = tasks();
+        m.initThreadPool(4);
+        return tasks() - before;
+      `);
+
+      expect(started).toBe(4);
+    },
+  );
+});
@@ -61,7 +61,7 @@ describe('TestReturnTypes', () => {
     expect(typeof result).toBe('string');
   });
 
-  it('simulateModBam returns void on success', async () => {
+  it('simulateModBam returns SimulateStats on success', async () => {
     const config = {
       contigs: { number: 1, len_range: [100, 100] },
       reads: [
@@ -84,7 +84,16 @@ describe('TestReturnTypes', () => {
       fastaPath,
     });
 
-    expect(result).toBeUndefined();
+    expect(result).toEqual({
+      n_reads: 10,
+      n_mods: 0,
+      n_contigs: 1,
+      total_bases: 500,
+      read_ids: expect.any(Array),
+      contigs: expect.any(Array),
+    });
+    expect(result.read_ids).toHaveLength(10);
+    expect(result.contigs).toHaveLength(1);
   });
 
   it('peek returns PeekResult with contigs and modifications', async () => {
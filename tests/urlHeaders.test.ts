@@ -0,0 +1,133 @@
+// Tests for urlHeaders, which sends extra HTTP request headers to URLs
+
+import { randomUUID } from 'node:crypto';
+import { readFileSync } from 'node:fs';
+import { rm } from 'node:fs/promises';
+import {
+  createServer,
+  type IncomingHttpHeaders,
+  type Server,
+} from 'node:http';
+import type { AddressInfo } from 'node:net';
+import { join } from 'node:path';
+import { pathToFileURL } from 'node:url';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import { readInfo } from '../index';
+import { EXAMPLE_1_BAM } from './fixtures';
+
+describe('urlHeaders', () => {
+  // A unique name, as htslib saves the index of a URL in the current directory
+  const name = `headers-${randomUUID()}.bam`;
+  const files: Record<string, Buffer> = {
+    [`/${name}`]: readFileSync(EXAMPLE_1_BAM),
+    [`/${name}.bai`]: readFileSync(`${EXAMPLE_1_BAM}.bai`),
+  };
+  const requests: IncomingHttpHeaders[] = [];
+  let server: Server;
+  let url: string;
+
+  beforeAll(async () => {
+    // Serves byte ranges of the files to requests with the token only
+    server = createServer((req, res) => {
+      requests.push(req.headers);
+      const data = files[req.url ?? ''];
+      if (req.headers['x-token'] !== 'secret') {
+        res.writeHead(401).end();
+      } else if (data === undefined) {
+        res.writeHead(404).end();
+      } else {
+        const range = /bytes=(\d+)-(\d*)/.exec(req.headers.range ?? '');
+        const start = Number(range?.[1] ?? 0);
+        const end = Math.min(Number(range?.[2] || Infinity), data.length - 1);
+        if (start >= data.length) {
+          res.writeHead(416, { 'Content-Range': `bytes */${data.length}` });
+          res.end();
+          return;
+        }
+        const contentRange = `bytes ${start}-${end}/${data.length}`;
+        res.writeHead(range ? 206 : 200, {
+          'Accept-Ranges': 'bytes',
+          'Content-Length': end - start + 1,
+          ...(range && { 'Content-Range': contentRange }),
+        });
+        res.end(data.subarray(start, end + 1));
+      }
+    });
+    await new Promise<void>((resolve) => {
+      server.listen(0, '127.0.0.1', resolve);
+    });
+    const { port } = server.address() as AddressInfo;
+    url = `http://127.0.0.1:${port}/${name}`;
+  });
+
+  afterAll(async () => {
+    server.close();
+    await rm(join(process.cwd(), `${name}.bai`), { force: true });
+  });
+
+  it('is needed by the test server', async () => {
+    await expect(readInfo({ bamPath: url })).rejects.toThrow(
+      /Failed to open BAM/,
+    );
+  });
+
+  it('sends the headers with the requests for the file and index', async () => {
+    requests.length = 0;
+    const result = await readInfo({
+      bamPath: url,
+      region: 'dummyIII',
+      urlHeaders: { 'X-Token': 'secret', 'User-Agent': 'my-app/1.0' },
+    });
+
+    expect(result).toEqual(
+      await readInfo({ bamPath: EXAMPLE_1_BAM, region: 'dummyIII' }),
+    );
+    expect(requests.length).toBeGreaterThan(0);
+    for (const headers of requests) {
+      expect(headers['x-token']).toBe('secret');
+      expect(headers['user-agent']).toBe('my-app/1.0');
+    }
+  });
+
+  it('rejects an invalid header name', async () => {
+    await expect(
+      readInfo({ bamPath: url, urlHeaders: { 'X Token': 'secret' } }),
+    ).rejects.toThrow(/invalid header name "X Token"/);
+  });
+
+  it('rejects a line break in a value', async () => {
+    await expect(
+      readInfo({ bamPath: url, urlHeaders: { 'X-Token': 'a\r\nB: c' } }),
+    ).rejects.toThrow(/line break/);
+  });
+
+  it('rejects URLs other than http and https', async () => {
+    const error = await readInfo({
+      bamPath: 's3://bucket/reads.bam',
+      urlHeaders: { 'X-Token': 'secret' },
+    }).catch((e: unknown) => e);
+
+    expect(error).toMatchObject({
+      code: 'InvalidOptions',
+      message: expect.stringMatching(/not s3:\/\//),
+    });
+  });
+
+  it('is ignored for file URLs, which are local files', async () => {
+    const result = await readInfo({
+      bamPath: pathToFileURL(EXAMPLE_1_BAM).href,
+      urlHeaders: { 'X-Token': 'secret' },
+    });
+
+    expect(result).toEqual(await readInfo({ bamPath: EXAMPLE_1_BAM }));
+  });
+
+  it('is ignored for local files', async () => {
+    const result = await readInfo({
+      bamPath: EXAMPLE_1_BAM,
+      urlHeaders: { 'X-Token': 'secret' },
+    });
+
+    expect(result).toHaveLength(4);
+  });
+});
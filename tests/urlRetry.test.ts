@@ -0,0 +1,133 @@
+// Tests for urlRetries and urlTimeoutMs, which retry and time-limit URL opens
+
+import { createServer, type Server } from 'node:http';
+import type { AddressInfo } from 'node:net';
+import { dirname } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it, vi } from 'vitest';
+import { NanalogueBam, peek, readGroups, readInfo } from '../index';
+import { EXAMPLE_1_BAM } from './fixtures';
+import { type FileServer, serveFiles } from './helpers';
+
+// file:// URLs are read as local files, so the URLs are served over HTTP
+let files: FileServer;
+let exampleUrl: string;
+let missingUrl: string;
+
+beforeAll(async () => {
+  files = await serveFiles(dirname(EXAMPLE_1_BAM));
+  exampleUrl = files.url('example_1.bam');
+  missingUrl = files.url('example_1.bam.missing');
+});
+
+afterAll(async () => {
+  await files.close();
+});
+
+describe('urlRetries', () => {
+  it('reads a URL as before when set', async () => {
+    const result = await readInfo({ bamPath: exampleUrl, urlRetries: 2 });
+
+    expect(result).toEqual(await readInfo({ bamPath: EXAMPLE_1_BAM }));
+  });
+
+  it('gives the number of attempts once every attempt fails', async () => {
+    await expect(
+      readInfo({ bamPath: missingUrl, urlRetries: 2 }),
+    ).rejects.toThrow(/Failed to open BAM after 3 attempts/);
+  });
+
+  it('waits between attempts', async () => {
+    const start = Date.now();
+    await expect(
+      readInfo({ bamPath: missingUrl, urlRetries: 2 }),
+    ).rejects.toThrow();
+
+    // 100 ms before the first retry and 200 ms before the second
+    expect(Date.now() - start).toBeGreaterThanOrEqual(300);
+  });
+
+  it('keeps the plain message without retries', async () => {
+    await expect(readInfo({ bamPath: missingUrl })).rejects.toThrow(
+      /Failed to open BAM: /,
+    );
+  });
+
+  it('applies to peek, readGroups and NanalogueBam', async () => {
+    const pattern = /after 2 attempts/;
+    const options = { bamPath: missingUrl, urlRetries: 1 };
+
+    await expect(peek(options)).rejects.toThrow(pattern);
+    await expect(readGroups(options)).rejects.toThrow(pattern);
+    expect(() => new NanalogueBam(options)).toThrow(pattern);
+  });
+});
+
+describe('urlTimeoutMs', () => {
+  let server: Server;
+  let silentUrl: string;
+
+  beforeAll(async () => {
+    // Accepts connections and never answers
+    server = createServer(() => {});
+    await new Promise<void>((resolve) => {
+      server.listen(0, '127.0.0.1', resolve);
+    });
+    const { port } = server.address() as AddressInfo;
+    silentUrl = `http://127.0.0.1:${port}/reads.bam`;
+  });
+
+  afterAll(() => {
+    server.closeAllConnections();
+    server.close();
+  });
+
+  it('abandons an attempt that takes too long without retrying', async () => {
+    const error = await readInfo({
+      bamPath: silentUrl,
+      urlTimeoutMs: 200,
+      urlRetries: 1,
+    }).catch((e: unknown) => e);
+
+    // A retry would run alongside the abandoned attempt
+    expect(error).toMatchObject({
+      code: 'IoError',
+      message: 'Failed to open BAM: timed out after 200 ms',
+    });
+  });
+
+  it('starts no attempt while too many abandoned ones run', async () => {
+    const options = { bamPath: silentUrl, urlTimeoutMs: 200 };
+    const messages: string[] = [];
+    for (let i = 0; i < 5; i++) {
+      messages.push(
+        await readInfo(options).then(
+          () => '',
+          (e: Error) => e.message,
+        ),
+      );
+    }
+
+    expect(messages.at(-1)).toMatch(
+      /4 earlier attempts that timed out are still running/,
+    );
+    // The abandoned attempts end once their connections are closed
+    server.closeAllConnections();
+    await vi.waitFor(
+      () =>
+        expect(readInfo(options)).rejects.toThrow(/timed out after 200 ms/),
+      { timeout: 5000, interval: 50 },
+    );
+  });
+
+  it('does not limit an attempt that finishes in time', async () => {
+    const result = await readInfo({ bamPath: exampleUrl, urlTimeoutMs: 5000 });
+
+    expect(result).toHaveLength(4);
+  });
+
+  it('rejects 0', async () => {
+    await expect(
+      readInfo({ bamPath: exampleUrl, urlTimeoutMs: 0 }),
+    ).rejects.toThrow(/url_timeout_ms must be at least 1/);
+  });
+});
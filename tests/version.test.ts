@@ -0,0 +1,32 @@
+// Tests for version, which reports the versions of the binding and its libraries
+
+import { readFileSync } from 'node:fs';
+import { resolve } from 'node:path';
+import { describe, expect, it } from 'vitest';
+import { version } from '../index';
+
+/** Version of `name` locked in Cargo.lock. */
+function lockedVersion(name: string): string | undefined {
+  const lock = readFileSync(resolve(__dirname, '..', 'Cargo.lock'), 'utf-8');
+  return lock
+    .split('[[package]]')
+    .find((v) => v.includes(`name = "${name}"\n`))
+    ?.match(/version = "([^"]+)"/)?.[1];
+}
+
+describe('version', () => {
+  it('gives the version of the package', () => {
+    const pkg = JSON.parse(
+      readFileSync(resolve(__dirname, '..', 'package.json'), 'utf-8'),
+    );
+    expect(version().node_binding).toBe(pkg.version);
+  });
+
+  it('gives the version of nanalogue-core the binding was built with', () => {
+    expect(version().core).toBe(lockedVersion('nanalogue'));
+  });
+
+  it('gives the version of the linked htslib', () => {
+    expect(version().htslib).toMatch(/^\d+\.\d+/);
+  });
+});
@@ -0,0 +1,146 @@
+// Tests for windowAggregate function
+// Validates pooling of reference windows into bins and error handling
+
+import { mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import {
+  NanalogueBam,
+  type WindowBin,
+  type WindowOptions,
+  windowAggregate,
+  windowReads,
+} from '../index';
+import { createTwoModsBam, EXAMPLE_1_BAM } from './fixtures';
+
+/** Pools the reference windows of windowReads per bin in JS. */
+async function poolWindows(options: WindowOptions): Promise<WindowBin[]> {
+  const windows: {
+    contig: string;
+    ref_start: number;
+    ref_end: number;
+    mod_type: string;
+    win_val: number;
+  }[] = JSON.parse(
+    (await windowReads({ ...options, coordinate: 'reference' })) as string,
+  );
+  const bins = new Map<string, WindowBin & { sum: number }>();
+  for (const w of windows) {
+    const key = `${w.contig}|${w.ref_start}|${w.mod_type}`;
+    const bin = bins.get(key) ?? {
+      contig: w.contig,
+      bin_start: w.ref_start,
+      bin_end: w.ref_end,
+      mod_type: w.mod_type,
+      mean: 0,
+      n_reads: 0,
+      sum: 0,
+    };
+    bin.sum += w.win_val;
+    bin.n_reads += 1;
+    bins.set(key, bin);
+  }
+  return [...bins.values()].map(({ sum, ...bin }) => ({
+    ...bin,
+    mean: sum / bin.n_reads,
+  }));
+}
+
+/** Key that identifies a bin. */
+const binKey = (b: WindowBin) => `${b.contig}|${b.bin_start}|${b.mod_type}`;
+
+describe('windowAggregate', () => {
+  let tmpDir: string;
+  let twoModsBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-window-aggregate-'));
+    twoModsBamPath = await createTwoModsBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('pools the reference windows of the reads per bin', async () => {
+    const options = { bamPath: twoModsBamPath, win: 50, step: 50 };
+    const bins = await windowAggregate(options);
+    const expected = await poolWindows(options);
+
+    expect(bins.length).toBe(expected.length);
+    expect(bins.some((b) => b.n_reads > 1)).toBe(true);
+    const byKey = new Map(expected.map((b) => [binKey(b), b]));
+    for (const bin of bins) {
+      const want = byKey.get(binKey(bin));
+      expect(want).toBeDefined();
+      expect(bin.bin_end).toBe(want?.bin_end);
+      expect(bin.n_reads).toBe(want?.n_reads);
+      expect(bin.mean).toBeCloseTo(want?.mean ?? NaN, 5);
+    }
+  });
+
+  it('lists bins by contig, position and modification', async () => {
+    const bins = await windowAggregate({
+      bamPath: twoModsBamPath,
+      win: 100,
+      step: 50,
+    });
+
+    expect(new Set(bins.map((b) => b.mod_type)).size).toBe(2);
+    for (const [i, bin] of bins.entries()) {
+      expect(bin.bin_end - bin.bin_start).toBe(100);
+      expect(bin.bin_start % 50).toBe(0);
+      const next = bins[i + 1];
+      if (next?.contig === bin.contig) {
+        expect(
+          next.bin_start > bin.bin_start ||
+            (next.bin_start === bin.bin_start && next.mod_type > bin.mod_type),
+        ).toBe(true);
+      }
+    }
+  });
+
+  it('applies the window operation and minWindowCalls', async () => {
+    const options = {
+      bamPath: twoModsBamPath,
+      win: 50,
+      step: 50,
+      winOp: 'count' as const,
+      minWindowCalls: 5,
+    };
+    const bins = await windowAggregate(options);
+    const expected = await poolWindows(options);
+
+    expect(bins.map(binKey).sort()).toEqual(expected.map(binKey).sort());
+    for (const bin of bins) {
+      expect(bin.mean).toBeGreaterThanOrEqual(0);
+    }
+  });
+
+  it('gives the same bins through a NanalogueBam', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+    expect(await bam.windowAggregate({ win: 10, step: 10 })).toEqual(
+      await windowAggregate({ bamPath: EXAMPLE_1_BAM, win: 10, step: 10 }),
+    );
+  });
+
+  it('rejects options of the windowReads output', async () => {
+    const options = { bamPath: EXAMPLE_1_BAM, win: 10, step: 10 };
+    await expect(
+      windowAggregate({ ...options, outputFormat: 'tsv' }),
+    ).rejects.toMatchObject({ code: 'InvalidOptions' });
+    await expect(
+      windowAggregate({ ...options, outputPath: 'windows.tsv' }),
+    ).rejects.toMatchObject({ code: 'InvalidOptions' });
+    await expect(
+      windowAggregate({ ...options, coordinate: 'read' }),
+    ).rejects.toMatchObject({ code: 'InvalidOptions' });
+  });
+
+  it('rejects a window size of 0', async () => {
+    await expect(
+      windowAggregate({ bamPath: EXAMPLE_1_BAM, win: 0, step: 10 }),
+    ).rejects.toMatchObject({ code: 'InvalidOptions' });
+  });
+});
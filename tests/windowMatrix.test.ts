@@ -0,0 +1,109 @@
+// Tests for windowMatrix, which gives the reference windows of the reads over
+// a region as a matrix of reads by windows
+
+import { mkdtemp, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import { NanalogueBam, windowMatrix, windowReads } from '../index';
+import { createTwoModsBam, EXAMPLE_1_BAM } from './fixtures';
+
+describe('windowMatrix', () => {
+  let tmpDir: string;
+  let twoModsBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-window-matrix-'));
+    twoModsBamPath = await createTwoModsBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it('gives NaN for the windows a read does not cover', async () => {
+    const matrix = await windowMatrix({
+      bamPath: EXAMPLE_1_BAM,
+      region: 'dummyIII',
+      win: 10,
+      step: 10,
+    });
+
+    expect(matrix.read_ids).toEqual(['a4f36092-b4d5-47a9-813e-c22c3b477a0c']);
+    expect(matrix.mod_types).toEqual(['T']);
+    expect(matrix.positions).toEqual([0, 10, 20, 30, 40, 50, 60, 70]);
+    expect(Array.from(matrix.values)).toEqual([
+      NaN,
+      NaN,
+      1,
+      1,
+      NaN,
+      0,
+      0,
+      1,
+    ]);
+  });
+
+  it('holds the reference windows of windowReads', async () => {
+    const options = {
+      bamPath: twoModsBamPath,
+      region: 'contig_00000:1000-3000',
+      win: 100,
+      step: 50,
+    };
+    const matrix = await windowMatrix(options);
+    const windows: {
+      ref_start: number;
+      read_id: string;
+      mod_type: string;
+      win_val: number;
+    }[] = JSON.parse(
+      (await windowReads({ ...options, coordinate: 'reference' })) as string,
+    );
+    const inRegion = windows.filter(
+      (w) => w.ref_start >= 950 && w.ref_start < 3000,
+    );
+
+    expect(matrix.positions[0]).toBe(950);
+    expect(matrix.positions.at(-1)).toBe(2950);
+    expect(new Set(matrix.mod_types).size).toBe(2);
+    const cols = matrix.positions.length;
+    expect(matrix.values.length).toBe(matrix.read_ids.length * cols);
+    const cells = new Map<string, number>();
+    matrix.read_ids.forEach((readId, i) => {
+      matrix.positions.forEach((pos, j) => {
+        const value = matrix.values[i * cols + j];
+        if (!Number.isNaN(value)) {
+          cells.set(`${readId}|${matrix.mod_types[i]}|${pos}`, value);
+        }
+      });
+    });
+    expect(cells.size).toBe(inRegion.length);
+    for (const w of inRegion) {
+      const key = `${w.read_id}|${w.mod_type}|${w.ref_start}`;
+      expect(cells.get(key)).toBeCloseTo(w.win_val, 5);
+    }
+  });
+
+  it('gives the same matrix through a NanalogueBam', async () => {
+    const bam = new NanalogueBam({ bamPath: EXAMPLE_1_BAM });
+    const options = { region: 'dummyII', win: 10, step: 5 };
+
+    expect(await bam.windowMatrix(options)).toEqual(
+      await windowMatrix({ bamPath: EXAMPLE_1_BAM, ...options }),
+    );
+  });
+
+  it('requires a single region in reference coordinates', async () => {
+    const options = { bamPath: EXAMPLE_1_BAM, win: 10, step: 10 };
+    await expect(windowMatrix(options)).rejects.toMatchObject({
+      code: 'InvalidOptions',
+    });
+    await expect(
+      windowMatrix({ ...options, regions: ['dummyI', 'dummyII'] }),
+    ).rejects.toMatchObject({ code: 'InvalidOptions' });
+    await expect(
+      windowMatrix({ ...options, region: 'dummyI', coordinate: 'read' }),
+    ).rejects.toMatchObject({ code: 'InvalidOptions' });
+  });
+});
@@ -1,13 +1,32 @@
 // Tests for windowReads function
 // Validates JSON output format, expected outputs, gradient mode, and error handling
 
-import { resolve } from 'node:path';
-import { describe, expect, it } from 'vitest';
-import { windowReads } from '../index';
+import { spawnSync } from 'node:child_process';
+import { existsSync } from 'node:fs';
+import { mkdtemp, readFile, rm } from 'node:fs/promises';
+import { tmpdir } from 'node:os';
+import { join, resolve } from 'node:path';
+import { gunzipSync } from 'node:zlib';
+import { afterAll, beforeAll, describe, expect, it } from 'vitest';
+import {
+  type WindowFormat as WindowFormatValue,
+  type WinOp as WinOpValue,
+  bamMods,
+  bamModsTyped,
+  EdgeMode,
+  NanalogueBam,
+  WindowCoordinate,
+  WindowFormat,
+  WinOp,
+  windowReads,
+  windowReadsArrays,
+} from '../index';
+import { createSimpleBam } from './fixtures';
 import {
   getExampleBamPath,
   loadExpectedJson,
   normalizeJsonForComparison,
+  parseCoreWindowReadsJson,
   parseWindowReadsJson,
 } from './helpers';
 
@@ -18,7 +37,7 @@ describe('windowReads expected output comparison', () => {
   it('test_example_1_bam_window_reads', async () => {
     const bamPath = getExampleBamPath('example_1.bam');
     const result = await windowReads({ bamPath, win: 2, step: 1 });
-    const actual = normalizeJsonForComparison(JSON.parse(result));
+    const actual = normalizeJsonForComparison(parseCoreWindowReadsJson(result));
     const expected = normalizeJsonForComparison(
       loadExpectedJson('example_1_window_reads_json'),
     );
@@ -28,7 +47,7 @@ describe('windowReads expected output comparison', () => {
   it('test_example_3_bam_window_reads', async () => {
     const bamPath = getExampleBamPath('example_3.bam');
     const result = await windowReads({ bamPath, win: 2, step: 1 });
-    const actual = normalizeJsonForComparison(JSON.parse(result));
+    const actual = normalizeJsonForComparison(parseCoreWindowReadsJson(result));
     const expected = normalizeJsonForComparison(
       loadExpectedJson('example_3_window_reads_json'),
     );
@@ -38,7 +57,7 @@ describe('windowReads expected output comparison', () => {
   it('test_example_7_bam_window_reads', async () => {
     const bamPath = getExampleBamPath('example_7.bam');
     const result = await windowReads({ bamPath, win: 2, step: 1 });
-    const actual = normalizeJsonForComparison(JSON.parse(result));
+    const actual = normalizeJsonForComparison(parseCoreWindowReadsJson(result));
     const expected = normalizeJsonForComparison(
       loadExpectedJson('example_7_window_reads_json'),
     );
@@ -55,7 +74,7 @@ describe('windowReads gradient (grad_density) tests', () => {
       step: 1,
       winOp: 'grad_density',
     });
-    const actual = normalizeJsonForComparison(JSON.parse(result));
+    const actual = normalizeJsonForComparison(parseCoreWindowReadsJson(result));
     const expected = normalizeJsonForComparison(
       loadExpectedJson('example_10_win_grad_json_win_10_step_1'),
     );
@@ -70,7 +89,7 @@ describe('windowReads gradient (grad_density) tests', () => {
       step: 2,
       winOp: 'grad_density',
     });
-    const actual = normalizeJsonForComparison(JSON.parse(result));
+    const actual = normalizeJsonForComparison(parseCoreWindowReadsJson(result));
     const expected = normalizeJsonForComparison(
       loadExpectedJson('example_10_win_grad_json_win_20_step_2'),
     );
@@ -85,7 +104,7 @@ describe('windowReads gradient (grad_density) tests', () => {
       step: 1,
       winOp: 'grad_density',
     });
-    const actual = normalizeJsonForComparison(JSON.parse(result));
+    const actual = normalizeJsonForComparison(parseCoreWindowReadsJson(result));
     const expected = normalizeJsonForComparison(
       loadExpectedJson('example_11_win_grad_json_win_10_step_1'),
     );
@@ -100,7 +119,7 @@ describe('windowReads gradient (grad_density) tests', () => {
       step: 2,
       winOp: 'grad_density',
     });
-    const actual = normalizeJsonForComparison(JSON.parse(result));
+    const actual = normalizeJsonForComparison(parseCoreWindowReadsJson(result));
     const expected = normalizeJsonForComparison(
       loadExpectedJson('example_11_win_grad_json_win_20_step_2'),
     );
@@ -108,6 +127,611 @@ describe('windowReads gradient (grad_density) tests', () => {
   });
 });
 
+describe('WinOp enum', () => {
+  it('maps to the string values windowReads has always accepted', () => {
+    expect(WinOp.Density).toBe('density');
+    expect(WinOp.GradDensity).toBe('grad_density');
+    expect(WinOp.Sum).toBe('sum');
+    expect(WinOp.Max).toBe('max');
+    expect(WinOp.Count).toBe('count');
+    expect(WinOp.RawMean).toBe('raw_mean');
+  });
+
+  it('gives the same output as the equivalent string', async () => {
+    const bamPath = getExampleBamPath('example_10.bam');
+    const fromEnum = await windowReads({
+      bamPath,
+      win: 10,
+      step: 1,
+      winOp: WinOp.GradDensity,
+    });
+    const fromString = await windowReads({
+      bamPath,
+      win: 10,
+      step: 1,
+      winOp: 'grad_density',
+    });
+    expect(JSON.parse(fromEnum)).toEqual(JSON.parse(fromString));
+  });
+});
+
+describe('windowReads sum, max, count and raw mean', () => {
+  const bamPath = getExampleBamPath('example_10.bam');
+  const win = 5;
+  const step = 3;
+
+  /** Reduces the ML values of every window of each read in JS. */
+  async function expectedWindows(reduce: (mlValues: number[]) => number) {
+    const reads = await bamMods({ bamPath });
+    return reads.map((read) =>
+      read.mod_table.map((table) => {
+        const values = table.data.map(([, , ml]) => ml);
+        const windows: number[] = [];
+        for (let i = 0; i + win <= values.length; i += step) {
+          windows.push(reduce(values.slice(i, i + win)));
+        }
+        return windows;
+      }),
+    );
+  }
+
+  /** Window values of windowReads JSON output, nested as in expectedWindows. */
+  async function windowValues(winOp: WinOpValue) {
+    const entries = JSON.parse(await windowReads({ bamPath, win, step, winOp }));
+    return entries.map((entry: { mod_table: { data: number[][] }[] }) =>
+      entry.mod_table.map((table) => table.data.map(([, , v]) => v)),
+    );
+  }
+
+  it('sums the probabilities in each window', async () => {
+    const actual = await windowValues(WinOp.Sum);
+    const expected = await expectedWindows(
+      (v) => v.reduce((a, b) => a + b, 0) / 255,
+    );
+
+    expect(actual.flat(2).length).toBeGreaterThan(0);
+    expect(actual.flat(2)).toEqual(
+      expected.flat(2).map((v) => expect.closeTo(v, 12)),
+    );
+  });
+
+  it('takes the highest probability in each window', async () => {
+    const actual = await windowValues(WinOp.Max);
+    const expected = await expectedWindows((v) => Math.max(...v) / 255);
+
+    expect(actual.flat(2)).toEqual(
+      expected.flat(2).map((v) => expect.closeTo(v, 6)),
+    );
+  });
+
+  it('counts the modified calls in each window', async () => {
+    const actual = await windowValues(WinOp.Count);
+    const expected = await expectedWindows(
+      (v) => v.filter((ml) => ml >= 128).length,
+    );
+
+    expect(actual).toEqual(expected);
+    expect(actual.flat(2).some((v: number) => v > 1)).toBe(true);
+  });
+
+  it('averages the raw probabilities in each window', async () => {
+    const actual = await windowValues(WinOp.RawMean);
+    const expected = await expectedWindows(
+      (v) => v.reduce((a, b) => a + b, 0) / (255 * v.length),
+    );
+
+    expect(actual.flat(2)).toEqual(
+      expected.flat(2).map((v) => expect.closeTo(v, 6)),
+    );
+    // Thresholding only keeps whether each call is above 0.5
+    const densities = await windowValues(WinOp.Density);
+    expect(actual.flat(2)).not.toEqual(densities.flat(2));
+  });
+
+  it('gives counts of density times the window size', async () => {
+    const counts = await windowValues(WinOp.Count);
+    const densities = await windowValues(WinOp.Density);
+
+    expect(counts.flat(2)).toEqual(
+      densities.flat(2).map((v: number) => Math.round(v * win)),
+    );
+  });
+
+  it('rescales the TSV output and files written to outputPath', async () => {
+    const tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-win-ops-'));
+    try {
+      const options = { bamPath, win, step, winOp: WinOp.Sum };
+      const tsv = await windowReads({ ...options, outputFormat: 'tsv' });
+      const outputPath = join(tmpDir, 'windows.tsv.gz');
+      const lines = await windowReads({
+        ...options,
+        outputFormat: 'tsv',
+        outputPath,
+        compress: true,
+      });
+
+      expect(gunzipSync(await readFile(outputPath)).toString()).toBe(tsv);
+      expect(lines).toBe(tsv.trimEnd().split('\n').length);
+      const sums = tsv
+        .trimEnd()
+        .split('\n')
+        .slice(1)
+        .map((line) => +line.split('\t')[4]);
+      expect(sums).toEqual((await windowValues(WinOp.Sum)).flat(2));
+    } finally {
+      await rm(tmpDir, { recursive: true });
+    }
+  });
+});
+
+describe('windowReads call counts', () => {
+  const bamPath = getExampleBamPath('example_10.bam');
+  const win = 5;
+  const step = 3;
+
+  /** Windows of windowReads JSON output, flattened over reads and tables. */
+  async function windowRows(options: { winOp?: WinOpValue } = {}) {
+    const entries = parseWindowReadsJson(
+      await windowReads({ bamPath, win, step, ...options }),
+    );
+    return entries.flatMap((entry) =>
+      entry.mod_table.flatMap((table) => table.data),
+    );
+  }
+
+  it('gives the modified and valid calls of each window', async () => {
+    const rows = await windowRows();
+    const counts = await windowRows({ winOp: WinOp.Count });
+
+    expect(rows.length).toBeGreaterThan(0);
+    for (const [, , density, , , , nMod, nValid] of rows) {
+      expect(nValid).toBe(win);
+      expect(density).toBeCloseTo(nMod / nValid, 6);
+    }
+    expect(rows.map((row) => row[6])).toEqual(counts.map((row) => row[2]));
+    expect(counts.map((row) => row.slice(6))).toEqual(
+      rows.map((row) => row.slice(6)),
+    );
+  });
+
+  it('leaves out windows with fewer than minWindowCalls calls', async () => {
+    const rows = await windowRows();
+    const kept = parseWindowReadsJson(
+      await windowReads({ bamPath, win, step, minWindowCalls: win }),
+    );
+    const dropped = parseWindowReadsJson(
+      await windowReads({ bamPath, win, step, minWindowCalls: win + 1 }),
+    );
+    const tsv = await windowReads({
+      bamPath,
+      win,
+      step,
+      minWindowCalls: win + 1,
+      outputFormat: 'tsv',
+    });
+
+    expect(kept.flatMap((e) => e.mod_table.flatMap((t) => t.data))).toEqual(
+      rows,
+    );
+    // Reads are kept with empty windows, and the TSV output keeps its header
+    expect(dropped.length).toBe(kept.length);
+    expect(dropped.flatMap((e) => e.mod_table.flatMap((t) => t.data))).toEqual(
+      [],
+    );
+    expect(tsv.trimEnd().split('\n')).toHaveLength(1);
+  });
+});
+
+describe('windowReads in reference coordinates', () => {
+  const bamPath = getExampleBamPath('example_1.bam');
+  const options = {
+    bamPath,
+    win: 10,
+    step: 5,
+    coordinate: WindowCoordinate.Reference,
+  };
+
+  interface RefWindow {
+    contig: string;
+    ref_start: number;
+    ref_end: number;
+    read_id: string;
+    win_val: number;
+    mod_type: string;
+    n_mod: number;
+    n_valid: number;
+  }
+
+  it('has Read and Reference values', () => {
+    expect(WindowCoordinate.Read).toBe('read');
+    expect(WindowCoordinate.Reference).toBe('reference');
+  });
+
+  it('keeps read coordinates as the default', async () => {
+    expect(await windowReads({ bamPath, win: 2, step: 1 })).toBe(
+      await windowReads({ bamPath, win: 2, step: 1, coordinate: 'read' }),
+    );
+  });
+
+  it('windows the calls at the positions of each window', async () => {
+    const windows: RefWindow[] = JSON.parse(await windowReads(options));
+    const calls = await bamModsTyped({ bamPath });
+
+    expect(windows.length).toBeGreaterThan(0);
+    for (const w of windows) {
+      expect(w.ref_end - w.ref_start).toBe(10);
+      expect(w.ref_start % 5).toBe(0);
+      const inWindow = calls.filter(
+        (c) =>
+          c.read_id === w.read_id &&
+          c.mod_code === w.mod_type &&
+          c.reference_position !== undefined &&
+          c.reference_position >= w.ref_start &&
+          c.reference_position < w.ref_end,
+      );
+      expect(w.n_valid).toBe(inWindow.length);
+      expect(w.n_mod).toBe(inWindow.filter((c) => c.ml_value >= 128).length);
+      expect(w.win_val).toBeCloseTo(w.n_mod / w.n_valid, 6);
+    }
+  });
+
+  it('gives the same windows in TSV and JSON', async () => {
+    const windows: RefWindow[] = JSON.parse(await windowReads(options));
+    const [header, ...rows] = (
+      await windowReads({ ...options, outputFormat: 'tsv' })
+    )
+      .trimEnd()
+      .split('\n');
+
+    expect(header.split('\t')).toEqual([
+      '#contig',
+      'ref_start',
+      'ref_end',
+      'read_id',
+      'win_val',
+      'strand',
+      'base',
+      'mod_strand',
+      'mod_type',
+      'n_mod',
+      'n_valid',
+    ]);
+    expect(
+      rows.map((line) => {
+        const f = line.split('\t');
+        return [f[0], +f[1], +f[2], f[3], +f[4], +f[9], +f[10]];
+      }),
+    ).toEqual(
+      windows.map((w) => [
+        w.contig,
+        w.ref_start,
+        w.ref_end,
+        w.read_id,
+        w.win_val,
+        w.n_mod,
+        w.n_valid,
+      ]),
+    );
+  });
+
+  it('leaves out windows with fewer than minWindowCalls calls', async () => {
+    const windows: RefWindow[] = JSON.parse(
+      await windowReads({ ...options, minWindowCalls: 3 }),
+    );
+
+    expect(windows.length).toBeGreaterThan(0);
+    expect(windows.every((w) => w.n_valid >= 3)).toBe(true);
+  });
+
+  it('leaves out unaligned reads with a warning', () => {
+    const script = `
+      require(${JSON.stringify(resolve(__dirname, '..', 'index.js'))})
+        .windowReads(${JSON.stringify(options)});
+    `;
+    const child = spawnSync(process.execPath, ['-e', script], {
+      encoding: 'utf8',
+    });
+
+    expect(child.status).toBe(0);
+    expect(child.stderr).toMatch(/left out 1 read\(s\) without an alignment/);
+  });
+});
+
+describe('windowReads edgeMode', () => {
+  const bamPath = getExampleBamPath('example_1.bam');
+  const options = { bamPath, win: 4, step: 3 };
+
+  // Windows of each read and modification, in output order
+  const windowsOf = async (edgeMode?: EdgeMode, winOp?: WinOpValue) =>
+    parseWindowReadsJson(
+      await windowReads({ ...options, edgeMode, winOp }),
+    ).flatMap((e) => e.mod_table.map((t) => t.data));
+
+  // End of the last call of each read and modification
+  const lastCallEnds = async () =>
+    parseWindowReadsJson(
+      await windowReads({ ...options, win: 1, step: 1 }),
+    ).flatMap((e) => e.mod_table.map((t) => t.data.at(-1)?.[1]));
+
+  it('has Drop, Partial and Pad values', () => {
+    expect(EdgeMode.Drop).toBe('drop');
+    expect(EdgeMode.Partial).toBe('partial');
+    expect(EdgeMode.Pad).toBe('pad');
+  });
+
+  it('drops the calls past the last window by default', async () => {
+    expect(await windowReads(options)).toBe(
+      await windowReads({ ...options, edgeMode: 'drop' }),
+    );
+    expect(await windowReads({ ...options, outputFormat: 'tsv' })).toBe(
+      await windowReads({ ...options, edgeMode: 'drop', outputFormat: 'tsv' }),
+    );
+  });
+
+  it('adds a short window at the end with partial', async () => {
+    const dropped = await windowsOf();
+    const partial = await windowsOf(EdgeMode.Partial);
+    const ends = await lastCallEnds();
+
+    expect(partial.length).toBe(dropped.length);
+    let added = 0;
+    partial.forEach((data, i) => {
+      const extra = data.slice(dropped[i].length);
+      expect(data.slice(0, dropped[i].length)).toEqual(dropped[i]);
+      expect(extra.length).toBeLessThanOrEqual(1);
+      for (const w of extra) {
+        expect(w[1]).toBe(ends[i]);
+        expect(w[7]).toBeLessThan(4);
+        added += 1;
+      }
+    });
+    expect(added).toBeGreaterThan(0);
+  });
+
+  it('adds a window of the last win calls with pad', async () => {
+    const dropped = await windowsOf();
+    const padded = await windowsOf(EdgeMode.Pad);
+    const ends = await lastCallEnds();
+
+    let added = 0;
+    padded.forEach((data, i) => {
+      const extra = data.slice(dropped[i].length);
+      expect(data.slice(0, dropped[i].length)).toEqual(dropped[i]);
+      for (const w of extra) {
+        expect(w[1]).toBe(ends[i]);
+        expect(w[7]).toBe(4);
+        added += 1;
+      }
+    });
+    expect(added).toBeGreaterThan(0);
+  });
+
+  it('values short windows over the calls they hold', async () => {
+    const density = (await windowsOf(EdgeMode.Partial)).flat();
+    const counts = (await windowsOf(EdgeMode.Partial, WinOp.Count)).flat();
+    const gradients = await windowsOf(EdgeMode.Partial, WinOp.GradDensity);
+
+    for (const w of density) {
+      expect(w[2]).toBeCloseTo(w[6] / w[7], 6);
+    }
+    expect(counts.every((w) => w[2] === w[6])).toBe(true);
+    expect(gradients.map((d) => d.length)).toEqual(
+      (await windowsOf(EdgeMode.Partial)).map((d) => d.length),
+    );
+  });
+
+  it('gives the same windows in windowReadsArrays', async () => {
+    for (const edgeMode of [EdgeMode.Partial, EdgeMode.Pad]) {
+      const arrays = await windowReadsArrays({ ...options, edgeMode });
+      const values = (await windowsOf(edgeMode)).flat().map((w) => w[2]);
+
+      expect(arrays.values.length).toBe(values.length);
+      for (const [i, value] of values.entries()) {
+        expect(arrays.values[i]).toBeCloseTo(value, 6);
+      }
+    }
+  });
+});
+
+describe('windowReads TSV output', () => {
+  const bamPath = getExampleBamPath('example_1.bam');
+
+  it('keeps JSON as the default format', async () => {
+    expect(await windowReads({ bamPath, win: 2, step: 1 })).toBe(
+      await windowReads({ bamPath, win: 2, step: 1, outputFormat: WindowFormat.Json }),
+    );
+  });
+
+  it('writes one row per window after a header line', async () => {
+    const tsv = await windowReads({ bamPath, win: 2, step: 1, outputFormat: 'tsv' });
+    const [header, first] = tsv.trimEnd().split('\n');
+
+    expect(header.split('\t')).toEqual([
+      '#contig',
+      'ref_win_start',
+      'ref_win_end',
+      'read_id',
+      'win_val',
+      'strand',
+      'base',
+      'mod_strand',
+      'mod_type',
+      'win_start',
+      'win_end',
+      'basecall_qual',
+      'n_mod',
+      'n_valid',
+    ]);
+    expect(first.split('\t')).toEqual([
+      'dummyI',
+      '9',
+      '13',
+      '5d10eb9a-aae1-4db8-8ec6-7ebb34d32575',
+      '0',
+      '+',
+      'T',
+      '+',
+      'T',
+      '0',
+      '4',
+      '255',
+      '0',
+      '2',
+    ]);
+  });
+
+  it.each(Object.values(WinOp))(
+    'holds the same windows as the JSON output with %s',
+    async (winOp) => {
+      const options = { bamPath, win: 4, step: 2, winOp };
+      const entries = JSON.parse(await windowReads(options));
+      const tsv = await windowReads({ ...options, outputFormat: WindowFormat.Tsv });
+
+      const fromJson = entries.flatMap(
+        (entry: {
+          read_id: string;
+          mod_table: { mod_code: string; data: number[][] }[];
+        }) =>
+          entry.mod_table.flatMap((table) =>
+            table.data.map(
+              ([winStart, winEnd, winVal, , refStart, refEnd, nMod, nValid]) => [
+                entry.read_id,
+                table.mod_code,
+                winStart,
+                winEnd,
+                winVal,
+                refStart,
+                refEnd,
+                nMod,
+                nValid,
+              ],
+            ),
+          ),
+      );
+      const fromTsv = tsv
+        .trimEnd()
+        .split('\n')
+        .slice(1)
+        .map((line) => {
+          const f = line.split('\t');
+          return [
+            f[3],
+            f[8],
+            +f[9],
+            +f[10],
+            +f[4],
+            +f[1],
+            +f[2],
+            +f[12],
+            +f[13],
+          ];
+        });
+      expect(fromTsv).toEqual(fromJson);
+    },
+  );
+
+  it('rejects an unknown format', async () => {
+    await expect(
+      windowReads({
+        bamPath,
+        win: 2,
+        step: 1,
+        outputFormat: 'csv' as WindowFormatValue, // Cast to bypass TS check; tests runtime validation for JS users
+      }),
+    ).rejects.toThrow(/does not match any variant of enum `WindowFormat`/);
+  });
+});
+
+describe('windowReads outputPath', () => {
+  const bamPath = getExampleBamPath('example_1.bam');
+  let tmpDir: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-windows-'));
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it.each([WindowFormat.Tsv, WindowFormat.Json])(
+    'writes the %s output to the file and returns its line count',
+    async (outputFormat) => {
+      const outputPath = join(tmpDir, `windows.${outputFormat}`);
+      const options = { bamPath, win: 2, step: 1, outputFormat };
+
+      const lines = await windowReads({ ...options, outputPath });
+      const written = await readFile(outputPath, 'utf-8');
+
+      expect(written).toBe(await windowReads(options));
+      expect(lines).toBe(written.split('\n').length - 1);
+    },
+  );
+
+  it('gzips the file with compress, adding a .gz extension', async () => {
+    const outputPath = join(tmpDir, 'compressed.tsv');
+    const options = { bamPath, win: 2, step: 1, outputFormat: WindowFormat.Tsv };
+
+    const lines = await windowReads({ ...options, outputPath, compress: true });
+
+    expect(existsSync(outputPath)).toBe(false);
+    const written = gunzipSync(await readFile(`${outputPath}.gz`)).toString('utf-8');
+    expect(written).toBe(await windowReads(options));
+    expect(lines).toBe(21);
+  });
+
+  it('keeps an existing .gz extension', async () => {
+    const outputPath = join(tmpDir, 'compressed.json.gz');
+
+    await windowReads({ bamPath, win: 2, step: 1, outputPath, compress: true });
+
+    expect(
+      JSON.parse(gunzipSync(await readFile(outputPath)).toString('utf-8')),
+    ).toEqual(JSON.parse(await windowReads({ bamPath, win: 2, step: 1 })));
+  });
+
+  it('requires outputPath for compress', async () => {
+    await expect(
+      windowReads({ bamPath, win: 2, step: 1, compress: true }),
+    ).rejects.toThrow(/compress requires outputPath/);
+  });
+
+  it('works on a NanalogueBam', async () => {
+    const outputPath = join(tmpDir, 'handle.tsv');
+    const bam = new NanalogueBam({ bamPath });
+
+    expect(
+      await bam.windowReads({ win: 2, step: 1, outputFormat: 'tsv', outputPath }),
+    ).toBe(21);
+  });
+
+  it('refuses to overwrite the input BAM file', async () => {
+    await expect(
+      windowReads({ bamPath, win: 2, step: 1, outputPath: bamPath }),
+    ).rejects.toThrow(/outputPath must differ from bamPath/);
+  });
+
+  it('removes the file when the call fails', async () => {
+    const outputPath = join(tmpDir, 'failed.tsv');
+
+    await expect(
+      windowReads({ bamPath, win: 0, step: 1, outputPath }),
+    ).rejects.toThrow(/Window size must be > 0/);
+    expect(existsSync(outputPath)).toBe(false);
+  });
+
+  it('reports a directory that does not exist', async () => {
+    await expect(
+      windowReads({
+        bamPath,
+        win: 2,
+        step: 1,
+        outputPath: join(tmpDir, 'missing', 'windows.tsv'),
+      }),
+    ).rejects.toThrow(/Failed to create output file/);
+  });
+});
+
 describe('windowReads error handling', () => {
   it('test_window_reads_invalid_win_op_raises_error', async () => {
     const bamPath = getExampleBamPath('example_1.bam');
@@ -116,9 +740,9 @@ describe('windowReads error handling', () => {
         bamPath,
         win: 5,
         step: 2,
-        winOp: 'invalid_option' as 'density', // Cast to bypass TS check; tests runtime validation for JS users
+        winOp: 'invalid_option' as WinOpValue, // Cast to bypass TS check; tests runtime validation for JS users
       }),
-    ).rejects.toThrow(/win_op must be set to/);
+    ).rejects.toThrow(/does not match any variant of enum `WinOp`/);
   });
 });
 
@@ -163,7 +787,7 @@ describe('windowReads', () => {
         expect(Array.isArray(modEntry.data)).toBe(true);
 
         for (const row of modEntry.data) {
-          expect(row).toHaveLength(6);
+          expect(row).toHaveLength(8);
         }
       }
     }
@@ -208,3 +832,170 @@ describe('windowReads', () => {
     ).rejects.toThrow();
   });
 });
+
+describe('windowReadsArrays', () => {
+  const bamPath = getExampleBamPath('example_1.bam');
+
+  /** Values of windowReads JSON output with the read and code of each run. */
+  async function jsonRuns(options: { win: number; step: number }) {
+    const reads: {
+      read_id: string;
+      mod_table: { mod_code: string; data: number[][] }[];
+    }[] = JSON.parse(await windowReads({ bamPath, ...options }));
+    return reads.flatMap((r) =>
+      r.mod_table
+        .filter((t) => t.data.length > 0)
+        .map((t) => ({
+          read_id: r.read_id,
+          mod_type: t.mod_code,
+          values: t.data.map((d) => d[2]),
+        })),
+    );
+  }
+
+  it('gives the window values of windowReads in typed arrays', async () => {
+    const arrays = await windowReadsArrays({ bamPath, win: 2, step: 1 });
+    const runs = await jsonRuns({ win: 2, step: 1 });
+
+    expect(arrays.values).toBeInstanceOf(Float64Array);
+    expect(arrays.offsets).toBeInstanceOf(Uint32Array);
+    expect(arrays.read_ids).toEqual(runs.map((r) => r.read_id));
+    expect(arrays.mod_types).toEqual(runs.map((r) => r.mod_type));
+    expect(arrays.offsets.length).toBe(runs.length + 1);
+    expect(arrays.offsets.at(-1)).toBe(arrays.values.length);
+    for (const [i, run] of runs.entries()) {
+      const values = arrays.values.subarray(
+        arrays.offsets[i],
+        arrays.offsets[i + 1],
+      );
+      expect(values.length).toBe(run.values.length);
+      for (const [j, value] of run.values.entries()) {
+        expect(values[j]).toBeCloseTo(value, 6);
+      }
+    }
+  });
+
+  it('gives the windows of windowReads in reference coordinates', async () => {
+    const options = {
+      bamPath,
+      win: 10,
+      step: 5,
+      coordinate: WindowCoordinate.Reference,
+    };
+    const arrays = await windowReadsArrays(options);
+    const windows: { win_val: number }[] = JSON.parse(
+      await windowReads(options),
+    );
+
+    expect(Array.from(arrays.values)).toEqual(
+      windows.map((w) => expect.closeTo(w.win_val, 6)),
+    );
+  });
+
+  it('leaves out windows below minWindowCalls', async () => {
+    const arrays = await windowReadsArrays({
+      bamPath,
+      win: 2,
+      step: 1,
+      minWindowCalls: 3,
+    });
+
+    expect(arrays.values.length).toBe(0);
+    expect(arrays.read_ids).toEqual([]);
+    expect(Array.from(arrays.offsets)).toEqual([0]);
+  });
+
+  it('gives the same arrays through a NanalogueBam', async () => {
+    const bam = new NanalogueBam({ bamPath });
+    const arrays = await bam.windowReadsArrays({ win: 2, step: 1 });
+
+    expect(Array.from(arrays.values)).toEqual(
+      Array.from((await windowReadsArrays({ bamPath, win: 2, step: 1 })).values),
+    );
+  });
+
+  it('rejects options of the text output', async () => {
+    await expect(
+      windowReadsArrays({ bamPath, win: 2, step: 1, outputFormat: 'tsv' }),
+    ).rejects.toMatchObject({ code: 'InvalidOptions' });
+  });
+});
+
+describe('windowReads in parallel', () => {
+  let tmpDir: string;
+  let simpleBamPath: string;
+
+  beforeAll(async () => {
+    tmpDir = await mkdtemp(join(tmpdir(), 'nanalogue-window-parallel-'));
+    simpleBamPath = await createSimpleBam(tmpDir);
+  });
+
+  afterAll(async () => {
+    await rm(tmpDir, { recursive: true });
+  });
+
+  it.each([
+    {},
+    { outputFormat: WindowFormat.Tsv },
+    { coordinate: WindowCoordinate.Reference },
+    { coordinate: WindowCoordinate.Reference, outputFormat: WindowFormat.Tsv },
+    { winOp: WinOp.Sum, minWindowCalls: 3 },
+    { edgeMode: EdgeMode.Pad },
+    { tags: ['T'] },
+    { readIdSet: ['missing'] },
+  ])('gives the output of windowing reads in turn for %o', async (extra) => {
+    const options = { bamPath: simpleBamPath, win: 5, step: 2, ...extra };
+
+    expect(await windowReads({ ...options, parallel: true })).toBe(
+      await windowReads(options),
+    );
+  });
+
+  it('writes the same file to outputPath', async () => {
+    const outputPath = join(tmpDir, 'parallel.tsv');
+    const options = {
+      bamPath: simpleBamPath,
+      win: 5,
+      step: 2,
+      outputFormat: WindowFormat.Tsv,
+    };
+    const tsv = await windowReads(options);
+
+    expect(
+      await windowReads({ ...options, parallel: true, outputPath }),
+    ).toBe(tsv.split('\n').length - 1);
+    expect(await readFile(outputPath, 'utf8')).toBe(tsv);
+  });
+
+  it('keeps the order of reads split over threads and warns once', async () => {
+    const bamPath = getExampleBamPath('example_1.bam');
+    const options = {
+      bamPath,
+      win: 2,
+      step: 1,
+      coordinate: WindowCoordinate.Reference,
+    };
+    const script = `
+      require(${JSON.stringify(resolve(__dirname, '..', 'index.js'))})
+        .windowReads(${JSON.stringify({ ...options, parallel: true })})
+        .then((v) => process.stdout.write(v));
+    `;
+    // Four threads split the reads into several chunks even on one core
+    const child = spawnSync(process.execPath, ['-e', script], {
+      encoding: 'utf8',
+      env: { ...process.env, RAYON_NUM_THREADS: '4' },
+    });
+
+    expect(child.status).toBe(0);
+    expect(child.stdout).toBe(await windowReads(options));
+    expect(child.stderr.match(/without an alignment/g)).toHaveLength(1);
+  });
+
+  it('is a NanalogueBam query option', async () => {
+    const bam = new NanalogueBam({ bamPath: simpleBamPath });
+
+    expect(await bam.windowReads({ win: 5, step: 2, parallel: true })).toBe(
+      await windowReads({ bamPath: simpleBamPath, win: 5, step: 2 }),
+    );
+  });
+});
@@ -7,7 +7,7 @@ import { tmpdir } from 'node:os';
 import { join } from 'node:path';
 import { afterAll, beforeAll, describe, expect, it } from 'vitest';
 
-import { windowReads } from '../index';
+import { modSummary, windowReads } from '../index';
 import {
   createSimpleBam,
   createTwoModsBam,
@@ -305,6 +305,25 @@ describe('TestWindowReadsModsFiltering', () => {
     expect(countBcComp).toBe(0);
   });
 
+  it('test_min_mods_filter', async () => {
+    const base = createWindowInputOptions(simpleBamPath, 5);
+    const summaries = await modSummary({ bamPath: simpleBamPath });
+    const counts = summaries.map((s) => s.n_mod).sort((x, y) => x - y);
+    const minMods = counts[Math.floor(counts.length / 2)];
+
+    const result = await windowReads({ ...base, minMods });
+    const expected = summaries
+      .filter((s) => s.n_mod >= minMods)
+      .map((s) => s.read_id);
+
+    expect(getUniqueReadIdsFromWindowJson(result)).toEqual([
+      ...new Set(expected),
+    ]);
+    expect(
+      getWindowDataCount(await windowReads({ ...base, minMods: 1e6 })),
+    ).toBe(0);
+  });
+
   it('test_min_mod_qual_filter', async () => {
     const base = createWindowInputOptions(simpleBamPath, 5);
 
@@ -319,6 +338,27 @@ describe('TestWindowReadsModsFiltering', () => {
     expect(countHighQual).toBeLessThan(countAll);
   });
 
+  it('test_mod_threshold', async () => {
+    const base = createWindowInputOptions(simpleBamPath, 5);
+
+    // A probability of 0.8 is an ML value of 204
+    expect(await windowReads({ ...base, modThreshold: 0.8 })).toBe(
+      await windowReads({ ...base, minModQual: 204 }),
+    );
+    // It replaces the thresholds given as ML values
+    expect(
+      await windowReads({
+        ...base,
+        modThreshold: 0,
+        minModQual: 250,
+        rejectModQualNonInclusive: [10, 240],
+      }),
+    ).toBe(await windowReads(base));
+    await expect(windowReads({ ...base, modThreshold: 1.5 })).rejects.toThrow(
+      /mod_threshold must be between 0 and 1/,
+    );
+  });
+
   it('test_trim_read_ends_mod', async () => {
     const base = createWindowInputOptions(simpleBamPath, 5);
 
@@ -425,6 +465,41 @@ describe('TestTagFiltering', () => {
       getWindowDataCount(resultAll),
     );
   });
+
+  it('test_tags_filter', async () => {
+    const base = createWindowInputOptions(twoModsBamPath, 5);
+    const resultAll = await windowReads(base);
+    const resultT = await windowReads({ ...base, tag: 'T' });
+
+    // Both tags keep every window, and output stays valid JSON
+    const resultBoth = await windowReads({ ...base, tags: ['T', '76792'] });
+    expect(parseWindowReadsJson(resultBoth)).toEqual(
+      parseWindowReadsJson(resultAll),
+    );
+
+    // A tag absent from the file leaves the windows of the other one
+    const resultTm = await windowReads({ ...base, tags: ['T', 'm'] });
+    expect(parseWindowReadsJson(resultTm)).toEqual(
+      parseWindowReadsJson(resultT),
+    );
+  });
+
+  it('test_tags_filter_tsv', async () => {
+    const base = createWindowInputOptions(twoModsBamPath, 5);
+    const resultT = await windowReads({
+      ...base,
+      outputFormat: 'tsv',
+      tag: 'T',
+    });
+    const resultTm = await windowReads({
+      ...base,
+      outputFormat: 'tsv',
+      tags: ['T', 'm'],
+    });
+
+    expect(resultTm).toBe(resultT);
+    expect(resultTm.split('\n')[0]).toMatch(/^#contig/);
+  });
 });
 
 describe('TestWindowReadsWindowingParams', () => {
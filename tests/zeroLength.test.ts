@@ -0,0 +1,85 @@
+// Tests for includeZeroLen, which keeps records without a sequence
+
+import { describe, expect, it } from 'vitest';
+import {
+  bamMods,
+  bamModsTyped,
+  modSummary,
+  readInfo,
+  readInfoJson,
+  seqTableRecords,
+  windowReads,
+} from '../index';
+import { EXAMPLE_ZERO_LEN_BAM } from './fixtures';
+
+describe('includeZeroLen', () => {
+  const options = { bamPath: EXAMPLE_ZERO_LEN_BAM, includeZeroLen: true };
+
+  it('leaves out records without a sequence by default', async () => {
+    const reads = await readInfo({ bamPath: EXAMPLE_ZERO_LEN_BAM });
+    expect(reads.map((r) => r.read_id)).toEqual(['read2']);
+  });
+
+  it('keeps records without a sequence in readInfo', async () => {
+    const reads = await readInfo(options);
+
+    expect(reads.map((r) => r.read_id)).toEqual([
+      'read1',
+      'read2',
+      'read3',
+      'read4',
+    ]);
+    expect(reads.map((r) => r.sequence_length)).toEqual([0, 48, 0, 0]);
+    expect(reads[0]).toMatchObject({
+      contig: 'dummyI',
+      reference_start: 9,
+      alignment_length: 8,
+      mod_count: 'NA',
+    });
+    expect(reads[3].alignment_type).toBe('unmapped');
+  });
+
+  it('keeps records without a sequence in readInfoJson', async () => {
+    const reads = JSON.parse(await readInfoJson(options));
+    expect(reads).toEqual(await readInfo(options));
+  });
+
+  it('gives records without a sequence no modification data', async () => {
+    const reads = await bamMods(options);
+
+    expect(reads).toHaveLength(4);
+    for (const read of reads.filter((r) => r.read_id !== 'read2')) {
+      expect(read.seq_len).toBe(0);
+      expect(read.mod_table).toEqual([]);
+    }
+
+    const calls = await bamModsTyped(options);
+    expect(new Set(calls.map((c) => c.read_id))).toEqual(new Set(['read2']));
+
+    const summaries = await modSummary(options);
+    expect(summaries.find((s) => s.read_id === 'read1')).toMatchObject({
+      n_mod: 0,
+      n_valid: 0,
+    });
+  });
+
+  it('skips records without a sequence when windowing', async () => {
+    const result = JSON.parse(
+      await windowReads({ ...options, win: 2, step: 1 }),
+    );
+    expect(result.map((r: { read_id: string }) => r.read_id)).toEqual([
+      'read2',
+    ]);
+  });
+
+  it('marks records without a sequence in seqTableRecords', async () => {
+    const rows = await seqTableRecords({
+      ...options,
+      region: 'dummyIII:30-35',
+    });
+    expect(rows).toEqual([
+      { read_id: 'read2', sequence: 'ATCCA', qualities: '255.255.255.255.255' },
+      { read_id: 'read3', sequence: '*', qualities: '255' },
+    ]);
+  });
+});